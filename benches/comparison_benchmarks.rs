@@ -22,7 +22,7 @@ fn test_signer() -> PrivateKeySigner {
 
 fn test_order_args() -> OrderArgs {
     OrderArgs {
-        token_id: TOKEN_ID.to_string(),
+        token_id: TOKEN_ID.parse().unwrap(),
         price: Decimal::from_str("0.7537").unwrap(),
         size: Decimal::from_str("100.25").unwrap(),
         side: Side::BUY,
@@ -61,7 +61,7 @@ fn fast_delta(token_id_hash: u64) -> FastOrderDelta {
 
 fn decimal_delta() -> OrderDelta {
     OrderDelta {
-        token_id: TOKEN_ID.to_string(),
+        token_id: TOKEN_ID.parse().unwrap(),
         timestamp: chrono::Utc::now(),
         side: Side::BUY,
         price: Decimal::from_str("0.7537").unwrap(),
@@ -62,7 +62,7 @@ fn benchmark_real_order_creation(c: &mut Criterion) {
                 }
 
                 let order_args = OrderArgs::new(
-                    "test_token_id",
+                    "123456".parse().unwrap(),
                     Decimal::from_str("0.75").unwrap(),
                     Decimal::from_str("100.0").unwrap(),
                     Side::BUY,
@@ -133,14 +133,7 @@ fn build_book_update_for(asset_id: &str, levels_per_side: usize) -> BookUpdate {
         });
     }
 
-    BookUpdate {
-        asset_id: asset_id.to_string(),
-        market: BOOK_MARKET.to_string(),
-        timestamp: 1,
-        bids,
-        asks,
-        hash: None,
-    }
+    BookUpdate::new(asset_id, BOOK_MARKET, 1, bids, asks)
 }
 
 fn build_ws_book_template(levels_per_side: usize) -> Vec<u8> {
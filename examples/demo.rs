@@ -104,6 +104,8 @@ impl PolyfillDemo {
             funder: None,
             timeout: Some(Duration::from_secs(30)),
             max_connections: Some(100),
+            dns_cache: None,
+            compression: true,
         };
         let client = ClobClient::new(&config.base_url);
 
@@ -340,7 +342,7 @@ impl PolyfillDemo {
         info!("=== Demo 4: Order Creation and Management ===");
 
         // Create order arguments
-        let order_args = OrderArgs::new("12345", dec!(0.75), dec!(100.0), Side::BUY);
+        let order_args = OrderArgs::new("12345".parse()?, dec!(0.75), dec!(100.0), Side::BUY);
 
         info!("Created order args: {:?}", order_args);
 
@@ -601,34 +603,30 @@ impl PolyfillDemo {
         info!("Created subscription: {:?}", subscription);
 
         // Simulate receiving stream messages
+        let mut fill = TradeMessage::new(
+            "fill1",
+            "market1",
+            "12345",
+            Side::BUY,
+            dec!(50.0),
+            dec!(0.75),
+        );
+        fill.status = Some("MATCHED".to_string());
         let messages = vec![
-            StreamMessage::Book(BookUpdate {
-                asset_id: "12345".to_string(),
-                market: "market1".to_string(),
-                timestamp: time::now_millis(),
-                bids: vec![OrderSummary {
+            StreamMessage::Book(BookUpdate::new(
+                "12345",
+                "market1",
+                time::now_millis(),
+                vec![OrderSummary {
                     price: dec!(0.75),
                     size: dec!(100.0),
                 }],
-                asks: vec![OrderSummary {
+                vec![OrderSummary {
                     price: dec!(0.76),
                     size: dec!(50.0),
                 }],
-                hash: None,
-            }),
-            StreamMessage::Trade(TradeMessage {
-                id: "fill1".to_string(),
-                market: "market1".to_string(),
-                asset_id: "12345".to_string(),
-                side: Side::BUY,
-                size: dec!(50.0),
-                price: dec!(0.75),
-                status: Some("MATCHED".to_string()),
-                msg_type: None,
-                last_update: None,
-                matchtime: None,
-                timestamp: None,
-            }),
+            )),
+            StreamMessage::Trade(fill),
         ];
 
         for message in messages {
@@ -1,4 +1,4 @@
-use polyfill_rs::ClobClient;
+use polyfill_rs::{ClientConfig, ClobClient};
 use std::time::{Duration, Instant};
 
 async fn measure_multiple_runs<F, Fut, T>(name: &str, iterations: usize, mut f: F) -> Vec<Duration>
@@ -109,25 +109,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("- Custodial order operations (via API, not on-chain)");
     println!();
 
-    // Check for required environment variables (API credentials only - no private key needed)
-    let api_key = std::env::var("POLYMARKET_API_KEY")
-        .map_err(|_| "POLYMARKET_API_KEY not found in .env file")?;
-    let secret = std::env::var("POLYMARKET_SECRET")
-        .map_err(|_| "POLYMARKET_SECRET not found in .env file")?;
-    let passphrase = std::env::var("POLYMARKET_PASSPHRASE")
-        .map_err(|_| "POLYMARKET_PASSPHRASE not found in .env file")?;
+    // API credentials only - no private key needed
+    let config = ClientConfig::from_env()?;
+    let api_creds = config.api_credentials.clone().ok_or(
+        "POLYMARKET_API_KEY, POLYMARKET_API_SECRET and POLYMARKET_API_PASSPHRASE must be set in .env",
+    )?;
 
     println!("✅ Loaded API credentials from environment");
 
-    // Create API credentials
-    let api_creds = polyfill_rs::ApiCredentials {
-        api_key,
-        secret,
-        passphrase,
-    };
-
     // Create client with API credentials only (no private key needed for custodial trading)
-    let mut client = ClobClient::new("https://clob.polymarket.com");
+    let client = ClobClient::new(&config.base_url);
     client.set_api_creds(api_creds)?;
 
     println!("✅ Client configured for custodial API trading");
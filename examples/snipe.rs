@@ -5,14 +5,23 @@
 //! - Stale quote detection
 //! - Rapid order execution
 //! - Market impact analysis
+//!
+//! Runs are deterministic: the mock feed and order sizing draw from a seeded
+//! RNG and all timestamps come from a virtual clock. Set `SNIPE_SEED` to
+//! explore other paths.
+//!
+//! The run ends with a JSON run report on stdout, or in the file named by
+//! `SNIPE_REPORT`.
 
 use polyfill_rs::{
     book::OrderBookManager,
     errors::Result,
     fill::{FillEngine, FillStatus},
+    run_report::RunRecorder,
+    sim::{Clock, SharedClock, SimRng, VirtualClock},
     types::*,
-    utils::time,
 };
+use rand::Rng;
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
@@ -36,14 +45,18 @@ pub struct SnipeStrategy {
     /// Last known best prices
     last_best_bid: Option<Decimal>,
     last_best_ask: Option<Decimal>,
-    /// Last update timestamp
-    last_update: u64,
     /// Order book manager
     book_manager: OrderBookManager,
     /// Fill engine
     fill_engine: FillEngine,
     /// Statistics
     stats: SnipeStats,
+    /// Run report figures
+    recorder: RunRecorder,
+    /// Time source
+    clock: SharedClock,
+    /// Randomness for order sizing
+    rng: SimRng,
 }
 
 /// Snipe trading statistics
@@ -78,6 +91,8 @@ impl SnipeStrategy {
         min_order_size: Decimal,
         max_order_size: Decimal,
         stale_threshold: u64,
+        clock: SharedClock,
+        seed: u64,
     ) -> Self {
         Self {
             token_id,
@@ -87,14 +102,18 @@ impl SnipeStrategy {
             stale_threshold,
             last_best_bid: None,
             last_best_ask: None,
-            last_update: 0,
             book_manager: OrderBookManager::new(100),
             fill_engine: FillEngine::new(
                 min_order_size,
                 dec!(2.0), // 2% max slippage
                 5,         // 5 bps fee rate
-            ),
+            )
+            .with_clock(clock.clone())
+            .with_seed(seed),
             stats: SnipeStats::default(),
+            recorder: RunRecorder::with_clock(clock.clone()),
+            rng: SimRng::from_seed(seed.wrapping_add(1)),
+            clock,
         }
     }
 
@@ -117,68 +136,13 @@ impl SnipeStrategy {
 
     /// Process order book update
     fn process_book_update(&mut self, book: BookUpdate) -> Result<()> {
-        // Ensure book exists
-        self.book_manager.get_or_create_book(&self.token_id)?;
-
-        // Clear the existing book and rebuild from the snapshot.
-        if let Ok(current) = self.book_manager.get_book(&self.token_id) {
-            for level in &current.bids {
-                let _ = self.book_manager.apply_delta(OrderDelta {
-                    token_id: self.token_id.clone(),
-                    timestamp: chrono::Utc::now(),
-                    side: Side::BUY,
-                    price: level.price,
-                    size: Decimal::ZERO,
-                    sequence: book.timestamp,
-                });
-            }
-
-            for level in &current.asks {
-                let _ = self.book_manager.apply_delta(OrderDelta {
-                    token_id: self.token_id.clone(),
-                    timestamp: chrono::Utc::now(),
-                    side: Side::SELL,
-                    price: level.price,
-                    size: Decimal::ZERO,
-                    sequence: book.timestamp,
-                });
-            }
-        }
-
-        let ts = chrono::DateTime::from_timestamp(
-            (book.timestamp / 1000) as i64,
-            ((book.timestamp % 1000) * 1_000_000) as u32,
-        )
-        .unwrap_or_else(chrono::Utc::now);
-
-        for level in &book.bids {
-            let _ = self.book_manager.apply_delta(OrderDelta {
-                token_id: self.token_id.clone(),
-                timestamp: ts,
-                side: Side::BUY,
-                price: level.price,
-                size: level.size,
-                sequence: book.timestamp,
-            });
-        }
-
-        for level in &book.asks {
-            let _ = self.book_manager.apply_delta(OrderDelta {
-                token_id: self.token_id.clone(),
-                timestamp: ts,
-                side: Side::SELL,
-                price: level.price,
-                size: level.size,
-                sequence: book.timestamp,
-            });
-        }
+        // Snapshots keep the age of levels whose size did not change
+        self.book_manager.apply_book_update(&book)?;
 
         // Update best prices directly from the snapshot
         self.last_best_bid = book.bids.first().map(|l| l.price);
         self.last_best_ask = book.asks.first().map(|l| l.price);
 
-        self.last_update = time::now_secs();
-
         // Check for trading opportunities
         self.check_opportunities()?;
 
@@ -236,7 +200,7 @@ impl SnipeStrategy {
     /// Execute a snipe order
     fn execute_snipe_order(&mut self, bid: Decimal, ask: Decimal) -> Result<()> {
         // Calculate order size (random between min and max)
-        let random_factor = Decimal::from(rand::random::<u64>() % 100) / Decimal::from(100);
+        let random_factor = Decimal::from(self.rng.gen_range(0u64..100)) / Decimal::from(100);
         let size =
             self.min_order_size + (self.max_order_size - self.min_order_size) * random_factor;
 
@@ -253,7 +217,7 @@ impl SnipeStrategy {
             side,
             amount: size,
             slippage_tolerance: Some(dec!(1.0)), // 1% slippage tolerance
-            client_id: Some(format!("snipe_{}", time::now_millis())),
+            client_id: Some(format!("snipe_{}", self.clock.now_millis())),
         };
 
         // Get current book for execution simulation
@@ -264,7 +228,7 @@ impl SnipeStrategy {
         for level in &book.bids {
             book_impl.apply_delta(OrderDelta {
                 token_id: self.token_id.clone(),
-                timestamp: chrono::Utc::now(),
+                timestamp: self.clock.now(),
                 side: Side::BUY,
                 price: level.price,
                 size: level.size,
@@ -275,7 +239,7 @@ impl SnipeStrategy {
         for level in &book.asks {
             book_impl.apply_delta(OrderDelta {
                 token_id: self.token_id.clone(),
-                timestamp: chrono::Utc::now(),
+                timestamp: self.clock.now(),
                 side: Side::SELL,
                 price: level.price,
                 size: level.size,
@@ -292,8 +256,12 @@ impl SnipeStrategy {
 
         // Update statistics
         self.stats.orders_placed += 1;
+        self.recorder.record_orders(1);
+        self.recorder.record_latency("fill", start_time.elapsed());
         if result.status == FillStatus::Filled {
             self.stats.orders_filled += 1;
+            self.recorder
+                .record_fill(result.total_size, result.average_price);
         }
 
         // Update average fill time
@@ -312,15 +280,23 @@ impl SnipeStrategy {
         Ok(())
     }
 
-    /// Check for stale quotes
+    /// Check for stale quotes at the top of the book
     fn check_stale_quotes(&mut self) -> Result<()> {
-        let now = time::now_secs();
-        let age = now.saturating_sub(self.last_update);
+        let now = self.clock.now();
+        let Ok(top) = self.book_manager.with_book(&self.token_id, |book| {
+            [Side::BUY, Side::SELL].map(|side| book.level_ages(side, Some(1)))
+        }) else {
+            return Ok(());
+        };
 
-        if age > self.stale_threshold {
+        for level in top.iter().flatten() {
+            let age = level.age_at(now).as_secs();
+            if age <= self.stale_threshold {
+                continue;
+            }
             warn!(
-                "Stale quotes detected: {}s old (threshold: {}s)",
-                age, self.stale_threshold
+                "Stale quote at {}: {}s old (threshold: {}s)",
+                level.price, age, self.stale_threshold
             );
 
             // In a real implementation, you might:
@@ -337,6 +313,11 @@ impl SnipeStrategy {
     pub fn get_stats(&self) -> &SnipeStats {
         &self.stats
     }
+
+    /// Figures for the run report
+    pub fn recorder(&self) -> &RunRecorder {
+        &self.recorder
+    }
 }
 
 /// Mock market data generator for testing
@@ -345,15 +326,19 @@ struct MockMarketData {
     base_price: Decimal,
     volatility: Decimal,
     sequence: u64,
+    clock: VirtualClock,
+    rng: SimRng,
 }
 
 impl MockMarketData {
-    fn new(token_id: String, base_price: Decimal) -> Self {
+    fn new(token_id: String, base_price: Decimal, clock: VirtualClock, seed: u64) -> Self {
         Self {
             token_id,
             base_price,
             volatility: dec!(0.01), // 1% volatility
             sequence: 0,
+            clock,
+            rng: SimRng::from_seed(seed),
         }
     }
 
@@ -361,24 +346,23 @@ impl MockMarketData {
         self.sequence += 1;
 
         // Generate random price movement
-        let random_factor = Decimal::from(rand::random::<i64>() % 100 - 50) / Decimal::from(100);
+        let random_factor = Decimal::from(self.rng.gen_range(-50i64..50)) / Decimal::from(100);
         let _volatility_f64 = self.volatility.to_f64().unwrap_or(0.01);
         let price_change = random_factor * Decimal::from(2) * self.volatility;
         let new_price = self.base_price * (Decimal::from(1) + price_change);
 
         // Generate a simple orderbook snapshot update
-        let size = Decimal::from(rand::random::<u64>() % 1000 + 100);
+        let size = Decimal::from(self.rng.gen_range(100u64..1100));
         let bid = new_price - dec!(0.01);
         let ask = new_price + dec!(0.01);
 
-        StreamMessage::Book(BookUpdate {
-            asset_id: self.token_id.clone(),
-            market: "0xmock".to_string(),
-            timestamp: time::now_millis(),
-            bids: vec![OrderSummary { price: bid, size }],
-            asks: vec![OrderSummary { price: ask, size }],
-            hash: None,
-        })
+        StreamMessage::Book(BookUpdate::new(
+            self.token_id.clone(),
+            "0xmock",
+            self.clock.now_millis(),
+            vec![OrderSummary { price: bid, size }],
+            vec![OrderSummary { price: ask, size }],
+        ))
     }
 }
 
@@ -389,6 +373,13 @@ async fn main() -> Result<()> {
 
     info!("Starting snipe trading example...");
 
+    let seed = std::env::var("SNIPE_SEED")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(42u64);
+    let clock = VirtualClock::new(1_700_000_000_000);
+    info!("Seed: {}", seed);
+
     // Create snipe strategy
     let mut strategy = SnipeStrategy::new(
         "12345".to_string(), // Example token ID
@@ -396,12 +387,16 @@ async fn main() -> Result<()> {
         dec!(10),            // Min order size
         dec!(100),           // Max order size
         5,                   // 5 second stale threshold
+        clock.shared(),
+        seed,
     );
 
     // Create mock market data generator
     let mut market_data = MockMarketData::new(
         "12345".to_string(),
         dec!(0.5), // Base price $0.50
+        clock.clone(),
+        seed,
     );
 
     // Simulate market data stream
@@ -415,6 +410,7 @@ async fn main() -> Result<()> {
         // Process update
         if let Err(e) = strategy.process_update(update) {
             error!("Error processing update: {}", e);
+            strategy.recorder().record_error(&e);
         }
 
         // Print statistics every 10 messages
@@ -430,20 +426,22 @@ async fn main() -> Result<()> {
         }
 
         message_count += 1;
-        sleep(Duration::from_millis(100)).await; // 100ms between updates
+        clock.advance(Duration::from_millis(100)); // 100ms between updates
+        sleep(Duration::from_millis(10)).await;
     }
 
-    // Print final statistics
-    let final_stats = strategy.get_stats();
-    info!("Final statistics:");
     info!(
-        "  Opportunities detected: {}",
-        final_stats.opportunities_detected
+        "Opportunities detected: {}",
+        strategy.get_stats().opportunities_detected
     );
-    info!("  Orders placed: {}", final_stats.orders_placed);
-    info!("  Orders filled: {}", final_stats.orders_filled);
-    info!("  Total volume: {}", final_stats.total_volume);
-    info!("  Average fill time: {:.2}ms", final_stats.avg_fill_time_ms);
+    let report = strategy.recorder().report();
+    match std::env::var("SNIPE_REPORT") {
+        Ok(path) => {
+            report.write_json(&path)?;
+            info!("Run report written to {}", path);
+        },
+        Err(_) => println!("{}", report.to_json()?),
+    }
 
     info!("Snipe trading example completed!");
     Ok(())
@@ -0,0 +1,323 @@
+//! Rolling self-metrics for the client's own order flow
+//!
+//! Venues expect a reasonable ratio between orders placed and orders that
+//! trade, and a quoting loop gone wrong shows up first as a burst of orders
+//! and cancels. [`ActivityMetrics`] counts this client's orders, cancels and
+//! trades over a trailing window and checks them against
+//! [`ActivityThresholds`]. A newly breached threshold is logged once; the
+//! current breaches are available from [`ActivityMetrics::check`].
+//!
+//! Install it with [`crate::ClobClient::set_activity_metrics`] to count
+//! orders and cancels as they are accepted, and pass user-channel messages to
+//! [`ActivityMetrics::observe`] to count trades.
+
+use crate::types::StreamMessage;
+use parking_lot::Mutex;
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Limits to warn on; `None` disables a check
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ActivityThresholds {
+    /// Orders placed within the window
+    pub max_orders: Option<usize>,
+    /// Orders cancelled within the window
+    pub max_cancels: Option<usize>,
+    /// Orders placed per trade
+    pub max_order_to_trade: Option<f64>,
+    /// Orders placed before the order-to-trade ratio is checked
+    pub min_orders_for_ratio: usize,
+}
+
+impl ActivityThresholds {
+    pub fn with_max_orders(mut self, max_orders: usize) -> Self {
+        self.max_orders = Some(max_orders);
+        self
+    }
+
+    pub fn with_max_cancels(mut self, max_cancels: usize) -> Self {
+        self.max_cancels = Some(max_cancels);
+        self
+    }
+
+    /// Warn once orders outnumber trades by `ratio`, after `min_orders` orders
+    pub fn with_max_order_to_trade(mut self, ratio: f64, min_orders: usize) -> Self {
+        self.max_order_to_trade = Some(ratio);
+        self.min_orders_for_ratio = min_orders;
+        self
+    }
+}
+
+/// Counts over the trailing window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActivitySnapshot {
+    pub window: Duration,
+    pub orders: usize,
+    pub cancels: usize,
+    pub trades: usize,
+}
+
+impl ActivitySnapshot {
+    /// Orders per trade, or `None` without trades
+    pub fn order_to_trade(&self) -> Option<f64> {
+        (self.trades > 0).then(|| self.orders as f64 / self.trades as f64)
+    }
+
+    /// Share of placed orders that were cancelled
+    pub fn cancel_rate(&self) -> Option<f64> {
+        (self.orders > 0).then(|| self.cancels as f64 / self.orders as f64)
+    }
+
+    pub fn orders_per_sec(&self) -> f64 {
+        self.orders as f64 / self.window.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// A threshold the current window exceeds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActivityWarning {
+    Orders {
+        count: usize,
+        limit: usize,
+    },
+    Cancels {
+        count: usize,
+        limit: usize,
+    },
+    /// `ratio` is infinite when orders were placed without any trade
+    OrderToTrade {
+        ratio: f64,
+        limit: f64,
+    },
+}
+
+impl ActivityWarning {
+    fn key(&self) -> u8 {
+        match self {
+            Self::Orders { .. } => 0,
+            Self::Cancels { .. } => 1,
+            Self::OrderToTrade { .. } => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Activity {
+    Order,
+    Cancel,
+    Trade,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    events: VecDeque<(Instant, Activity, usize)>,
+    orders: usize,
+    cancels: usize,
+    trades: usize,
+    /// Warnings already logged, until they clear
+    breached: HashSet<u8>,
+}
+
+impl Counters {
+    fn count_mut(&mut self, activity: Activity) -> &mut usize {
+        match activity {
+            Activity::Order => &mut self.orders,
+            Activity::Cancel => &mut self.cancels,
+            Activity::Trade => &mut self.trades,
+        }
+    }
+
+    fn evict(&mut self, cutoff: Instant) {
+        while let Some(&(at, activity, count)) = self.events.front() {
+            if at >= cutoff {
+                break;
+            }
+            self.events.pop_front();
+            *self.count_mut(activity) -= count;
+        }
+    }
+}
+
+/// Rolling order, cancel and trade counts for one client or strategy
+#[derive(Debug)]
+pub struct ActivityMetrics {
+    window: Duration,
+    thresholds: ActivityThresholds,
+    counters: Mutex<Counters>,
+}
+
+impl Default for ActivityMetrics {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(60))
+    }
+}
+
+impl ActivityMetrics {
+    /// Metrics over a trailing `window`, without thresholds
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            thresholds: ActivityThresholds::default(),
+            counters: Mutex::new(Counters::default()),
+        }
+    }
+
+    pub fn with_thresholds(mut self, thresholds: ActivityThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
+
+    pub fn record_orders(&self, count: usize) {
+        self.record_at(Activity::Order, count, Instant::now());
+    }
+
+    pub fn record_cancels(&self, count: usize) {
+        self.record_at(Activity::Cancel, count, Instant::now());
+    }
+
+    pub fn record_trades(&self, count: usize) {
+        self.record_at(Activity::Trade, count, Instant::now());
+    }
+
+    /// Count this client's trades from user-channel messages.
+    ///
+    /// A trade is reported again as it is mined and confirmed; only the
+    /// first, matched, report is counted.
+    pub fn observe(&self, message: &StreamMessage) {
+        if let StreamMessage::Trade(trade) = message {
+            let matched = trade
+                .status
+                .as_deref()
+                .is_none_or(|status| status.eq_ignore_ascii_case("MATCHED"));
+            if matched {
+                self.record_trades(1);
+            }
+        }
+    }
+
+    pub fn snapshot(&self) -> ActivitySnapshot {
+        self.snapshot_at(Instant::now())
+    }
+
+    /// Thresholds the current window exceeds
+    pub fn check(&self) -> Vec<ActivityWarning> {
+        let snapshot = self.snapshot();
+        self.warnings(&snapshot)
+    }
+
+    fn record_at(&self, activity: Activity, count: usize, now: Instant) {
+        if count == 0 {
+            return;
+        }
+        let mut counters = self.counters.lock();
+        counters.events.push_back((now, activity, count));
+        *counters.count_mut(activity) += count;
+        let snapshot = Self::snapshot_locked(&mut counters, self.window, now);
+
+        let warnings = self.warnings(&snapshot);
+        let current: HashSet<u8> = warnings.iter().map(ActivityWarning::key).collect();
+        for warning in &warnings {
+            if !counters.breached.contains(&warning.key()) {
+                warn!(
+                    "Order activity over {:?} exceeds threshold: {:?}",
+                    self.window, warning
+                );
+            }
+        }
+        counters.breached = current;
+    }
+
+    fn snapshot_at(&self, now: Instant) -> ActivitySnapshot {
+        Self::snapshot_locked(&mut self.counters.lock(), self.window, now)
+    }
+
+    fn snapshot_locked(
+        counters: &mut Counters,
+        window: Duration,
+        now: Instant,
+    ) -> ActivitySnapshot {
+        if let Some(cutoff) = now.checked_sub(window) {
+            counters.evict(cutoff);
+        }
+        ActivitySnapshot {
+            window,
+            orders: counters.orders,
+            cancels: counters.cancels,
+            trades: counters.trades,
+        }
+    }
+
+    fn warnings(&self, snapshot: &ActivitySnapshot) -> Vec<ActivityWarning> {
+        let thresholds = &self.thresholds;
+        let mut warnings = Vec::new();
+        if let Some(limit) = thresholds.max_orders.filter(|&l| snapshot.orders > l) {
+            warnings.push(ActivityWarning::Orders {
+                count: snapshot.orders,
+                limit,
+            });
+        }
+        if let Some(limit) = thresholds.max_cancels.filter(|&l| snapshot.cancels > l) {
+            warnings.push(ActivityWarning::Cancels {
+                count: snapshot.cancels,
+                limit,
+            });
+        }
+        if let Some(limit) = thresholds.max_order_to_trade {
+            let ratio = snapshot.order_to_trade().unwrap_or(f64::INFINITY);
+            if snapshot.orders >= thresholds.min_orders_for_ratio.max(1) && ratio > limit {
+                warnings.push(ActivityWarning::OrderToTrade { ratio, limit });
+            }
+        }
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rolling_counts_and_thresholds() {
+        let metrics = ActivityMetrics::new(Duration::from_secs(10)).with_thresholds(
+            ActivityThresholds::default()
+                .with_max_cancels(5)
+                .with_max_order_to_trade(4.0, 10),
+        );
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        metrics.record_at(Activity::Order, 8, at(0));
+        metrics.record_at(Activity::Cancel, 6, at(1));
+        metrics.record_at(Activity::Trade, 1, at(2));
+        let snapshot = metrics.snapshot_at(at(3));
+        assert_eq!(
+            (snapshot.orders, snapshot.cancels, snapshot.trades),
+            (8, 6, 1)
+        );
+        assert_eq!(snapshot.cancel_rate(), Some(0.75));
+        assert_eq!(
+            metrics.warnings(&snapshot),
+            vec![ActivityWarning::Cancels { count: 6, limit: 5 }]
+        );
+
+        metrics.record_at(Activity::Order, 4, at(4));
+        let warnings = metrics.warnings(&metrics.snapshot_at(at(5)));
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(
+            warnings[1],
+            ActivityWarning::OrderToTrade {
+                ratio: 12.0,
+                limit: 4.0
+            }
+        );
+
+        // The first burst leaves the window
+        let snapshot = metrics.snapshot_at(at(12));
+        assert_eq!(
+            (snapshot.orders, snapshot.cancels, snapshot.trades),
+            (4, 0, 1)
+        );
+        assert!(metrics.warnings(&snapshot).is_empty());
+    }
+}
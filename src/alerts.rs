@@ -0,0 +1,390 @@
+//! Price and liquidity alerts
+//!
+//! [`AlertEngine`] evaluates registered [`AlertCondition`]s per token: the
+//! midpoint crossing a level, the spread widening past a limit, traded
+//! volume over a rolling window exceeding a threshold, or the midpoint
+//! drifting from an external reference price. Feed it market-channel
+//! messages with [`AlertEngine::observe`], or check locally maintained books
+//! with [`AlertEngine::check_book`].
+//!
+//! Alerts are edge-triggered: a condition fires once when it becomes true and
+//! re-arms when it turns false again. Fired alerts are returned to the caller,
+//! passed to callbacks registered with [`AlertEngine::on_alert`] and
+//! broadcast to [`AlertEngine::subscribe`] receivers, so bots and dashboards
+//! can consume the same engine.
+
+use crate::book::OrderBook;
+use crate::intern::TokenKey;
+use crate::price_feed::ReferencePrices;
+use crate::types::{BookUpdate, StreamMessage};
+use crate::window::{StreamWindows, WindowSource, WindowSpec};
+use parking_lot::{Mutex, RwLock};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Alerts kept for slow [`AlertEngine::subscribe`] receivers
+const ALERT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Condition checked against a token's book or trades
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertCondition {
+    /// Midpoint at or above the level
+    MidAbove(Decimal),
+    /// Midpoint at or below the level
+    MidBelow(Decimal),
+    /// Spread wider than the limit
+    SpreadAbove(Decimal),
+    /// Traded size within the trailing window above the threshold
+    VolumeAbove { window: Duration, volume: Decimal },
+    /// Midpoint further than this from its linked reference price
+    BasisAbove(Decimal),
+}
+
+/// Handle of a registered alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AlertId(u64);
+
+/// A condition that became true
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alert {
+    pub id: AlertId,
+    pub token_id: String,
+    pub condition: AlertCondition,
+    /// Midpoint, spread or volume that triggered the alert
+    pub value: Decimal,
+    /// Exchange timestamp of the triggering update, in milliseconds
+    pub timestamp: u64,
+}
+
+/// Callback invoked for every fired alert
+pub type AlertHandler = Arc<dyn Fn(&Alert) + Send + Sync>;
+
+#[derive(Debug)]
+struct Rule {
+    id: AlertId,
+    token: TokenKey,
+    condition: AlertCondition,
+    active: bool,
+}
+
+/// Registry of alert conditions and their delivery targets
+pub struct AlertEngine {
+    rules: RwLock<Vec<Rule>>,
+    /// Traded size per distinct volume window
+    volumes: Mutex<HashMap<Duration, StreamWindows>>,
+    references: RwLock<Option<Arc<ReferencePrices>>>,
+    handlers: RwLock<Vec<AlertHandler>>,
+    sender: broadcast::Sender<Alert>,
+    next_id: AtomicU64,
+}
+
+impl Default for AlertEngine {
+    fn default() -> Self {
+        Self {
+            rules: RwLock::new(Vec::new()),
+            volumes: Mutex::new(HashMap::new()),
+            references: RwLock::new(None),
+            handlers: RwLock::new(Vec::new()),
+            sender: broadcast::channel(ALERT_CHANNEL_CAPACITY).0,
+            next_id: AtomicU64::new(1),
+        }
+    }
+}
+
+impl std::fmt::Debug for AlertEngine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlertEngine")
+            .field("rules", &self.rules.read().len())
+            .field("handlers", &self.handlers.read().len())
+            .finish()
+    }
+}
+
+impl AlertEngine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Watch `token_id` for `condition`
+    pub fn register(&self, token_id: &str, condition: AlertCondition) -> AlertId {
+        let id = AlertId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.rules.write().push(Rule {
+            id,
+            token: TokenKey::new(token_id),
+            condition,
+            active: false,
+        });
+        id
+    }
+
+    /// Stop watching; returns whether the alert was registered
+    pub fn remove(&self, id: AlertId) -> bool {
+        let mut rules = self.rules.write();
+        let before = rules.len();
+        rules.retain(|rule| rule.id != id);
+        rules.len() != before
+    }
+
+    /// Reference prices checked by [`AlertCondition::BasisAbove`]
+    pub fn set_reference_prices(&self, references: Arc<ReferencePrices>) {
+        *self.references.write() = Some(references);
+    }
+
+    /// Call `handler` for every fired alert
+    pub fn on_alert<F>(&self, handler: F)
+    where
+        F: Fn(&Alert) + Send + Sync + 'static,
+    {
+        self.handlers.write().push(Arc::new(handler));
+    }
+
+    /// Receive fired alerts as a stream of events
+    pub fn subscribe(&self) -> broadcast::Receiver<Alert> {
+        self.sender.subscribe()
+    }
+
+    /// Evaluate a market-channel message.
+    ///
+    /// `book` and `best_bid_ask` events update midpoint and spread alerts;
+    /// `last_trade_price` events count towards volume alerts.
+    pub fn observe(&self, message: &StreamMessage) -> Vec<Alert> {
+        match message {
+            StreamMessage::Book(update) => {
+                let (bid, ask) = best_prices(update);
+                self.check_prices(&update.asset_id, bid, ask, update.timestamp)
+            },
+            StreamMessage::BestBidAsk(quote) => self.check_prices(
+                &quote.asset_id,
+                Some(quote.best_bid),
+                Some(quote.best_ask),
+                quote.timestamp,
+            ),
+            StreamMessage::LastTradePrice(trade) => match trade.size {
+                Some(size) => self.record_trade(&trade.asset_id, size, trade.timestamp),
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Evaluate midpoint and spread alerts against a local book
+    pub fn check_book(&self, book: &OrderBook) -> Vec<Alert> {
+        self.check_prices(
+            &book.token_id,
+            book.best_bid().map(|level| level.price),
+            book.best_ask().map(|level| level.price),
+            book.timestamp.timestamp_millis().max(0) as u64,
+        )
+    }
+
+    /// Count a trade of `size` at `timestamp` (milliseconds) towards volume
+    /// alerts
+    pub fn record_trade(&self, token_id: &str, size: Decimal, timestamp: u64) -> Vec<Alert> {
+        let token = TokenKey::new(token_id);
+        let mut windows: Vec<Duration> = self
+            .rules
+            .read()
+            .iter()
+            .filter(|rule| rule.token == token)
+            .filter_map(|rule| match rule.condition {
+                AlertCondition::VolumeAbove { window, .. } => Some(window),
+                _ => None,
+            })
+            .collect();
+        if windows.is_empty() {
+            return Vec::new();
+        }
+        windows.sort();
+        windows.dedup();
+
+        let traded: Vec<(Duration, Decimal)> = {
+            let mut volumes = self.volumes.lock();
+            windows
+                .into_iter()
+                .map(|window| {
+                    let volume = volumes.entry(window).or_insert_with(|| {
+                        StreamWindows::new(WindowSpec::Sliding(window), WindowSource::TradeSize)
+                    });
+                    volume.push(token_id, timestamp, size);
+                    let sum = volume.stats(token_id).map(|stats| stats.sum);
+                    (window, sum.unwrap_or_default())
+                })
+                .collect()
+        };
+
+        self.evaluate(&token, timestamp, |condition| match condition {
+            AlertCondition::VolumeAbove { window, volume } => traded
+                .iter()
+                .find(|(traded_window, _)| *traded_window == window)
+                .map(|(_, traded)| (*traded > volume, *traded)),
+            _ => None,
+        })
+    }
+
+    fn check_prices(
+        &self,
+        token_id: &str,
+        bid: Option<Decimal>,
+        ask: Option<Decimal>,
+        timestamp: u64,
+    ) -> Vec<Alert> {
+        let Some(token) = TokenKey::lookup(token_id) else {
+            return Vec::new();
+        };
+        let quote = bid.zip(ask);
+        let mid = quote.map(|(bid, ask)| (bid + ask) / Decimal::TWO);
+        let spread = quote.map(|(bid, ask)| ask - bid);
+        let references = self.references.read().clone();
+        let basis = mid.zip(references).and_then(|(mid, references)| {
+            references
+                .basis(token_id, mid, timestamp)
+                .map(|basis| basis.basis)
+        });
+
+        self.evaluate(&token, timestamp, |condition| match condition {
+            AlertCondition::MidAbove(level) => mid.map(|mid| (mid >= level, mid)),
+            AlertCondition::MidBelow(level) => mid.map(|mid| (mid <= level, mid)),
+            AlertCondition::SpreadAbove(limit) => spread.map(|spread| (spread > limit, spread)),
+            AlertCondition::BasisAbove(limit) => basis.map(|basis| (basis.abs() > limit, basis)),
+            AlertCondition::VolumeAbove { .. } => None,
+        })
+    }
+
+    /// Update rules for `token`; `check` returns whether a condition holds
+    /// and the observed value, or `None` if the update says nothing about it
+    fn evaluate(
+        &self,
+        token: &TokenKey,
+        timestamp: u64,
+        check: impl Fn(AlertCondition) -> Option<(bool, Decimal)>,
+    ) -> Vec<Alert> {
+        let mut fired = Vec::new();
+        for rule in self.rules.write().iter_mut() {
+            if rule.token != *token {
+                continue;
+            }
+            let Some((holds, value)) = check(rule.condition) else {
+                continue;
+            };
+            if holds && !rule.active {
+                fired.push(Alert {
+                    id: rule.id,
+                    token_id: token.to_string(),
+                    condition: rule.condition,
+                    value,
+                    timestamp,
+                });
+            }
+            rule.active = holds;
+        }
+
+        if !fired.is_empty() {
+            let handlers = self.handlers.read().clone();
+            for alert in &fired {
+                for handler in &handlers {
+                    handler(alert);
+                }
+                // No receivers is fine
+                let _ = self.sender.send(alert.clone());
+            }
+        }
+        fired
+    }
+}
+
+fn best_prices(update: &BookUpdate) -> (Option<Decimal>, Option<Decimal>) {
+    let live = |levels: &[crate::types::OrderSummary]| {
+        levels
+            .iter()
+            .filter(|level| !level.size.is_zero())
+            .map(|level| level.price)
+            .collect::<Vec<_>>()
+    };
+    let bid = live(&update.bids).into_iter().max();
+    let ask = live(&update.asks).into_iter().min();
+    (bid, ask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn book(bid: &str, ask: &str, timestamp: u64) -> StreamMessage {
+        serde_json::from_str(&format!(
+            r#"{{"event_type":"book","asset_id":"1","market":"0xabc","timestamp":"{timestamp}",
+                "bids":[{{"price":"{bid}","size":"10"}}],"asks":[{{"price":"{ask}","size":"10"}}]}}"#
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_alerts_are_edge_triggered() {
+        let engine = AlertEngine::new();
+        let crossed = engine.register("1", AlertCondition::MidAbove(dec!(0.60)));
+        engine.register("1", AlertCondition::SpreadAbove(dec!(0.05)));
+        let mut events = engine.subscribe();
+        let seen = Arc::new(AtomicU64::new(0));
+        let counter = seen.clone();
+        engine.on_alert(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert!(engine.observe(&book("0.50", "0.52", 1)).is_empty());
+        let fired = engine.observe(&book("0.60", "0.62", 2));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].id, crossed);
+        assert_eq!(fired[0].value, dec!(0.61));
+        // Still above: no repeat until the mid drops back and crosses again
+        assert!(engine.observe(&book("0.61", "0.63", 3)).is_empty());
+        assert!(engine.observe(&book("0.50", "0.52", 4)).is_empty());
+        let fired = engine.observe(&book("0.55", "0.70", 5));
+        assert_eq!(fired.len(), 2);
+
+        assert_eq!(events.recv().await.unwrap().timestamp, 2);
+        assert_eq!(seen.load(Ordering::Relaxed), 3);
+        assert!(engine.remove(crossed));
+    }
+
+    #[test]
+    fn test_volume_over_window() {
+        let engine = AlertEngine::new();
+        engine.register(
+            "1",
+            AlertCondition::VolumeAbove {
+                window: Duration::from_secs(60),
+                volume: dec!(100),
+            },
+        );
+
+        assert!(engine.record_trade("1", dec!(60), 0).is_empty());
+        // The first trade has left the window
+        assert!(engine.record_trade("1", dec!(60), 61_000).is_empty());
+        let fired = engine.record_trade("1", dec!(50), 62_000);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].value, dec!(110));
+    }
+
+    #[test]
+    fn test_basis_against_reference_price() {
+        use crate::price_feed::{ManualFeed, ReferenceLink};
+
+        let feed = Arc::new(ManualFeed::new("oracle"));
+        feed.set("btc", dec!(0.50), 1);
+        let references = Arc::new(ReferencePrices::new());
+        references.link("1", ReferenceLink::new(feed.clone(), "btc"));
+
+        let engine = AlertEngine::new();
+        engine.set_reference_prices(references);
+        engine.register("1", AlertCondition::BasisAbove(dec!(0.05)));
+        assert!(engine.observe(&book("0.52", "0.54", 2)).is_empty());
+        let fired = engine.observe(&book("0.58", "0.60", 3));
+        assert_eq!(fired[0].value, dec!(0.09));
+        feed.set("btc", dec!(0.58), 4);
+        assert!(engine.observe(&book("0.58", "0.60", 5)).is_empty());
+    }
+}
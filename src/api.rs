@@ -0,0 +1,774 @@
+//! Exchange abstraction for testing strategies
+//!
+//! [`ClobApi`] covers the REST calls a trading strategy typically makes
+//! (market data, order entry, cancels and order queries). [`ClobClient`]
+//! implements it by delegating to its inherent methods, and [`FakeClob`] is an
+//! in-memory implementation with canned books, injectable latency and
+//! scripted failures, so strategy code written against `impl ClobApi` can be
+//! unit-tested without a mock HTTP server or network access.
+
+use crate::client::ClobClient;
+use crate::errors::{OrderErrorKind, PolyfillError, Result};
+use crate::sim::SharedClock;
+use crate::types::{
+    CancelOrdersResponse, CreateOrderOptions, Market, MidpointResponse, OpenOrder, OpenOrderParams,
+    OrderArgs, OrderBookSummary, OrderSummary, PostOrderOptions, PostOrderResponse, PriceResponse,
+    Side, SpreadResponse, TokenId, TradeParams,
+};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::time::Duration;
+
+/// REST surface used by trading strategies
+pub trait ClobApi: Send + Sync {
+    fn get_server_time(&self) -> impl Future<Output = Result<u64>> + Send;
+
+    fn get_order_book(
+        &self,
+        token_id: &str,
+    ) -> impl Future<Output = Result<OrderBookSummary>> + Send;
+
+    fn get_order_books(
+        &self,
+        token_ids: &[String],
+    ) -> impl Future<Output = Result<Vec<OrderBookSummary>>> + Send;
+
+    fn get_midpoint(&self, token_id: &str)
+        -> impl Future<Output = Result<MidpointResponse>> + Send;
+
+    fn get_spread(&self, token_id: &str) -> impl Future<Output = Result<SpreadResponse>> + Send;
+
+    fn get_price(
+        &self,
+        token_id: &str,
+        side: Side,
+    ) -> impl Future<Output = Result<PriceResponse>> + Send;
+
+    fn get_tick_size(&self, token_id: &str) -> impl Future<Output = Result<Decimal>> + Send;
+
+    fn get_neg_risk(&self, token_id: &str) -> impl Future<Output = Result<bool>> + Send;
+
+    fn get_fee_rate_bps(&self, token_id: &str) -> impl Future<Output = Result<u32>> + Send;
+
+    fn get_market(&self, condition_id: &str) -> impl Future<Output = Result<Market>> + Send;
+
+    fn create_and_post_order(
+        &self,
+        order_args: &OrderArgs,
+        create_options: Option<&CreateOrderOptions>,
+        post_options: Option<&PostOrderOptions>,
+    ) -> impl Future<Output = Result<PostOrderResponse>> + Send;
+
+    fn cancel(&self, order_id: &str) -> impl Future<Output = Result<CancelOrdersResponse>> + Send;
+
+    fn cancel_orders(
+        &self,
+        order_ids: &[String],
+    ) -> impl Future<Output = Result<CancelOrdersResponse>> + Send;
+
+    fn cancel_all(&self) -> impl Future<Output = Result<CancelOrdersResponse>> + Send;
+
+    /// Cancel every open order in one market
+    fn cancel_condition_orders(
+        &self,
+        condition_id: &str,
+    ) -> impl Future<Output = Result<CancelOrdersResponse>> + Send;
+
+    /// Cancel every open order on one outcome token
+    fn cancel_asset_orders(
+        &self,
+        token_id: &TokenId,
+    ) -> impl Future<Output = Result<CancelOrdersResponse>> + Send;
+
+    fn get_orders(
+        &self,
+        params: Option<&OpenOrderParams>,
+        next_cursor: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<OpenOrder>>> + Send;
+
+    fn get_order(&self, order_id: &str) -> impl Future<Output = Result<OpenOrder>> + Send;
+
+    fn get_trades(
+        &self,
+        trade_params: Option<&TradeParams>,
+        next_cursor: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<Value>>> + Send;
+}
+
+impl ClobApi for ClobClient {
+    async fn get_server_time(&self) -> Result<u64> {
+        ClobClient::get_server_time(self).await
+    }
+
+    async fn get_order_book(&self, token_id: &str) -> Result<OrderBookSummary> {
+        ClobClient::get_order_book(self, token_id).await
+    }
+
+    async fn get_order_books(&self, token_ids: &[String]) -> Result<Vec<OrderBookSummary>> {
+        ClobClient::get_order_books(self, token_ids).await
+    }
+
+    async fn get_midpoint(&self, token_id: &str) -> Result<MidpointResponse> {
+        ClobClient::get_midpoint(self, token_id).await
+    }
+
+    async fn get_spread(&self, token_id: &str) -> Result<SpreadResponse> {
+        ClobClient::get_spread(self, token_id).await
+    }
+
+    async fn get_price(&self, token_id: &str, side: Side) -> Result<PriceResponse> {
+        ClobClient::get_price(self, token_id, side).await
+    }
+
+    async fn get_tick_size(&self, token_id: &str) -> Result<Decimal> {
+        ClobClient::get_tick_size(self, token_id).await
+    }
+
+    async fn get_neg_risk(&self, token_id: &str) -> Result<bool> {
+        ClobClient::get_neg_risk(self, token_id).await
+    }
+
+    async fn get_fee_rate_bps(&self, token_id: &str) -> Result<u32> {
+        ClobClient::get_fee_rate_bps(self, token_id).await
+    }
+
+    async fn get_market(&self, condition_id: &str) -> Result<Market> {
+        ClobClient::get_market(self, condition_id).await
+    }
+
+    async fn create_and_post_order(
+        &self,
+        order_args: &OrderArgs,
+        create_options: Option<&CreateOrderOptions>,
+        post_options: Option<&PostOrderOptions>,
+    ) -> Result<PostOrderResponse> {
+        ClobClient::create_and_post_order(self, order_args, create_options, post_options).await
+    }
+
+    async fn cancel(&self, order_id: &str) -> Result<CancelOrdersResponse> {
+        ClobClient::cancel(self, order_id).await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[String]) -> Result<CancelOrdersResponse> {
+        ClobClient::cancel_orders(self, order_ids).await
+    }
+
+    async fn cancel_all(&self) -> Result<CancelOrdersResponse> {
+        ClobClient::cancel_all(self).await
+    }
+
+    async fn cancel_condition_orders(&self, condition_id: &str) -> Result<CancelOrdersResponse> {
+        ClobClient::cancel_condition_orders(self, condition_id).await
+    }
+
+    async fn cancel_asset_orders(&self, token_id: &TokenId) -> Result<CancelOrdersResponse> {
+        ClobClient::cancel_asset_orders(self, token_id).await
+    }
+
+    async fn get_orders(
+        &self,
+        params: Option<&OpenOrderParams>,
+        next_cursor: Option<&str>,
+    ) -> Result<Vec<OpenOrder>> {
+        ClobClient::get_orders(self, params, next_cursor).await
+    }
+
+    async fn get_order(&self, order_id: &str) -> Result<OpenOrder> {
+        ClobClient::get_order(self, order_id).await
+    }
+
+    async fn get_trades(
+        &self,
+        trade_params: Option<&TradeParams>,
+        next_cursor: Option<&str>,
+    ) -> Result<Vec<Value>> {
+        ClobClient::get_trades(self, trade_params, next_cursor).await
+    }
+}
+
+/// Canned market data for one token in a [`FakeClob`]
+#[derive(Debug, Clone)]
+pub struct FakeToken {
+    /// Condition ID reported on orders for this token
+    pub market: String,
+    /// Bids, best first
+    pub bids: Vec<OrderSummary>,
+    /// Asks, best first
+    pub asks: Vec<OrderSummary>,
+    pub tick_size: Decimal,
+    pub min_order_size: Decimal,
+    pub neg_risk: bool,
+    pub fee_rate_bps: u32,
+}
+
+impl FakeToken {
+    /// Token with an empty book and Polymarket's common defaults
+    pub fn new(market: impl Into<String>) -> Self {
+        Self {
+            market: market.into(),
+            bids: Vec::new(),
+            asks: Vec::new(),
+            tick_size: Decimal::new(1, 2),
+            min_order_size: Decimal::from(5),
+            neg_risk: false,
+            fee_rate_bps: 0,
+        }
+    }
+
+    /// Replace both sides of the book with `(price, size)` levels, best first
+    pub fn with_book(mut self, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> Self {
+        let levels = |levels: &[(Decimal, Decimal)]| {
+            levels
+                .iter()
+                .map(|&(price, size)| OrderSummary { price, size })
+                .collect()
+        };
+        self.bids = levels(bids);
+        self.asks = levels(asks);
+        self
+    }
+
+    pub fn with_tick_size(mut self, tick_size: Decimal) -> Self {
+        self.tick_size = tick_size;
+        self
+    }
+
+    fn best_bid(&self) -> Option<Decimal> {
+        self.bids.first().map(|l| l.price)
+    }
+
+    fn best_ask(&self) -> Option<Decimal> {
+        self.asks.first().map(|l| l.price)
+    }
+}
+
+#[derive(Debug, Default)]
+struct FakeState {
+    tokens: HashMap<String, FakeToken>,
+    markets: HashMap<String, Market>,
+    orders: BTreeMap<String, OpenOrder>,
+    trades: Vec<Value>,
+    next_order_id: u64,
+    /// One-shot failures by method name
+    fail_next: HashMap<String, Vec<PolyfillError>>,
+    /// Persistent failures by method name
+    fail_always: HashMap<String, PolyfillError>,
+    calls: Vec<String>,
+}
+
+/// In-memory [`ClobApi`] for unit tests.
+///
+/// Orders are accepted and rest as open orders; no matching is performed.
+/// Every call is recorded in [`FakeClob::calls`].
+#[derive(Debug)]
+pub struct FakeClob {
+    state: Mutex<FakeState>,
+    latency: Mutex<Duration>,
+    clock: SharedClock,
+}
+
+impl Default for FakeClob {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FakeClob {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(FakeState::default()),
+            latency: Mutex::new(Duration::ZERO),
+            clock: crate::sim::system_clock(),
+        }
+    }
+
+    /// Use `clock` for server time and order timestamps
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Register or replace a token's canned market data
+    pub fn set_token(&self, token_id: impl Into<String>, token: FakeToken) {
+        self.state.lock().tokens.insert(token_id.into(), token);
+    }
+
+    /// Register or replace a market returned by `get_market`
+    pub fn set_market(&self, market: Market) {
+        self.state
+            .lock()
+            .markets
+            .insert(market.condition_id.clone(), market);
+    }
+
+    /// Delay applied before every call
+    pub fn set_latency(&self, latency: Duration) {
+        *self.latency.lock() = latency;
+    }
+
+    /// Fail the next call to `method` (e.g. `"get_order_book"`) with `error`
+    pub fn fail_next(&self, method: &str, error: PolyfillError) {
+        self.state
+            .lock()
+            .fail_next
+            .entry(method.to_string())
+            .or_default()
+            .push(error);
+    }
+
+    /// Fail every call to `method` until [`Self::clear_failures`]
+    pub fn fail_always(&self, method: &str, error: PolyfillError) {
+        self.state
+            .lock()
+            .fail_always
+            .insert(method.to_string(), error);
+    }
+
+    pub fn clear_failures(&self) {
+        let mut state = self.state.lock();
+        state.fail_next.clear();
+        state.fail_always.clear();
+    }
+
+    /// Method names of every call made so far, in order
+    pub fn calls(&self) -> Vec<String> {
+        self.state.lock().calls.clone()
+    }
+
+    /// Currently open orders
+    pub fn open_orders(&self) -> Vec<OpenOrder> {
+        self.state.lock().orders.values().cloned().collect()
+    }
+
+    /// Add or replace an open order, as if placed or updated out of band
+    pub fn insert_order(&self, order: OpenOrder) {
+        self.state.lock().orders.insert(order.id.clone(), order);
+    }
+
+    /// Append a trade returned by `get_trades`
+    pub fn add_trade(&self, trade: Value) {
+        self.state.lock().trades.push(trade);
+    }
+
+    /// Record the call, apply latency and return any scripted failure
+    async fn enter(&self, method: &str) -> Result<()> {
+        let latency = *self.latency.lock();
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+
+        let mut state = self.state.lock();
+        state.calls.push(method.to_string());
+        if let Some(errors) = state.fail_next.get_mut(method) {
+            if !errors.is_empty() {
+                return Err(errors.remove(0));
+            }
+        }
+        match state.fail_always.get(method) {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
+    }
+
+    fn with_token<T>(&self, token_id: &str, f: impl FnOnce(&FakeToken) -> Result<T>) -> Result<T> {
+        let state = self.state.lock();
+        let token = state.tokens.get(token_id).ok_or_else(|| {
+            PolyfillError::api(404, format!("No orderbook exists for token {token_id}"))
+        })?;
+        f(token)
+    }
+
+    fn summary(&self, token_id: &str, token: &FakeToken) -> OrderBookSummary {
+        OrderBookSummary {
+            market: token.market.clone(),
+            asset_id: token_id.to_string(),
+            hash: None,
+            timestamp: self.clock.now_millis(),
+            bids: token.bids.clone(),
+            asks: token.asks.clone(),
+            min_order_size: token.min_order_size,
+            neg_risk: token.neg_risk,
+            tick_size: token.tick_size,
+            last_trade_price: None,
+        }
+    }
+
+    fn cancel_ids<'a>(&self, ids: impl IntoIterator<Item = &'a String>) -> CancelOrdersResponse {
+        let mut state = self.state.lock();
+        let mut response = CancelOrdersResponse::default();
+        for id in ids {
+            if state.orders.remove(id).is_some() {
+                response.canceled.push(id.clone());
+            } else {
+                response
+                    .not_canceled
+                    .insert(id.clone(), "order not found".to_string());
+            }
+        }
+        response
+    }
+}
+
+impl ClobApi for FakeClob {
+    async fn get_server_time(&self) -> Result<u64> {
+        self.enter("get_server_time").await?;
+        Ok(self.clock.now_millis() / 1000)
+    }
+
+    async fn get_order_book(&self, token_id: &str) -> Result<OrderBookSummary> {
+        self.enter("get_order_book").await?;
+        self.with_token(token_id, |token| Ok(self.summary(token_id, token)))
+    }
+
+    async fn get_order_books(&self, token_ids: &[String]) -> Result<Vec<OrderBookSummary>> {
+        self.enter("get_order_books").await?;
+        token_ids
+            .iter()
+            .map(|id| self.with_token(id, |token| Ok(self.summary(id, token))))
+            .collect()
+    }
+
+    async fn get_midpoint(&self, token_id: &str) -> Result<MidpointResponse> {
+        self.enter("get_midpoint").await?;
+        self.with_token(token_id, |token| {
+            match (token.best_bid(), token.best_ask()) {
+                (Some(bid), Some(ask)) => Ok(MidpointResponse {
+                    mid: (bid + ask) / Decimal::TWO,
+                }),
+                _ => Err(PolyfillError::api(404, "No midpoint for one-sided book")),
+            }
+        })
+    }
+
+    async fn get_spread(&self, token_id: &str) -> Result<SpreadResponse> {
+        self.enter("get_spread").await?;
+        self.with_token(token_id, |token| {
+            match (token.best_bid(), token.best_ask()) {
+                (Some(bid), Some(ask)) => Ok(SpreadResponse { spread: ask - bid }),
+                _ => Err(PolyfillError::api(404, "No spread for one-sided book")),
+            }
+        })
+    }
+
+    async fn get_price(&self, token_id: &str, side: Side) -> Result<PriceResponse> {
+        self.enter("get_price").await?;
+        self.with_token(token_id, |token| {
+            let price = match side {
+                Side::BUY => token.best_bid(),
+                Side::SELL => token.best_ask(),
+            };
+            price
+                .map(|price| PriceResponse { price })
+                .ok_or_else(|| PolyfillError::api(404, "No price for empty side"))
+        })
+    }
+
+    async fn get_tick_size(&self, token_id: &str) -> Result<Decimal> {
+        self.enter("get_tick_size").await?;
+        self.with_token(token_id, |token| Ok(token.tick_size))
+    }
+
+    async fn get_neg_risk(&self, token_id: &str) -> Result<bool> {
+        self.enter("get_neg_risk").await?;
+        self.with_token(token_id, |token| Ok(token.neg_risk))
+    }
+
+    async fn get_fee_rate_bps(&self, token_id: &str) -> Result<u32> {
+        self.enter("get_fee_rate_bps").await?;
+        self.with_token(token_id, |token| Ok(token.fee_rate_bps))
+    }
+
+    async fn get_market(&self, condition_id: &str) -> Result<Market> {
+        self.enter("get_market").await?;
+        self.state
+            .lock()
+            .markets
+            .get(condition_id)
+            .cloned()
+            .ok_or_else(|| PolyfillError::api(404, format!("Market {condition_id} not found")))
+    }
+
+    async fn create_and_post_order(
+        &self,
+        order_args: &OrderArgs,
+        create_options: Option<&CreateOrderOptions>,
+        post_options: Option<&PostOrderOptions>,
+    ) -> Result<PostOrderResponse> {
+        self.enter("create_and_post_order").await?;
+
+        let (market, tick_size, min_order_size) =
+            self.with_token(&order_args.token_id, |token| {
+                let tick_size = create_options
+                    .and_then(|o| o.tick_size)
+                    .unwrap_or(token.tick_size);
+                Ok((token.market.clone(), tick_size, token.min_order_size))
+            })?;
+
+        if order_args.price <= Decimal::ZERO
+            || order_args.price >= Decimal::ONE
+            || !(order_args.price % tick_size).is_zero()
+        {
+            return Err(PolyfillError::order(
+                format!(
+                    "Invalid price {} for tick size {}",
+                    order_args.price, tick_size
+                ),
+                OrderErrorKind::InvalidPrice,
+            ));
+        }
+        if order_args.size < min_order_size {
+            return Err(PolyfillError::order(
+                format!("Size {} below minimum {}", order_args.size, min_order_size),
+                OrderErrorKind::SizeConstraint,
+            ));
+        }
+
+        let order_type = post_options.map(|o| o.order_type).unwrap_or_default();
+        let mut state = self.state.lock();
+        state.next_order_id += 1;
+        let order_id = format!("fake-order-{}", state.next_order_id);
+        state.orders.insert(
+            order_id.clone(),
+            OpenOrder {
+                associate_trades: Vec::new(),
+                id: order_id.clone(),
+                status: "LIVE".to_string(),
+                market,
+                original_size: order_args.size,
+                outcome: String::new(),
+                maker_address: String::new(),
+                owner: String::new(),
+                price: order_args.price,
+                side: order_args.side,
+                size_matched: Decimal::ZERO,
+                asset_id: order_args.token_id.to_string(),
+                expiration: order_args.expiration.unwrap_or_default(),
+                order_type,
+                created_at: self.clock.now_millis() / 1000,
+            },
+        );
+
+        Ok(PostOrderResponse {
+            success: true,
+            order_id,
+            status: "live".to_string(),
+            making_amount: String::new(),
+            taking_amount: String::new(),
+            transactions_hashes: Vec::new(),
+            trade_ids: Vec::new(),
+            error_msg: String::new(),
+        })
+    }
+
+    async fn cancel(&self, order_id: &str) -> Result<CancelOrdersResponse> {
+        self.enter("cancel").await?;
+        Ok(self.cancel_ids([&order_id.to_string()]))
+    }
+
+    async fn cancel_orders(&self, order_ids: &[String]) -> Result<CancelOrdersResponse> {
+        self.enter("cancel_orders").await?;
+        Ok(self.cancel_ids(order_ids))
+    }
+
+    async fn cancel_all(&self) -> Result<CancelOrdersResponse> {
+        self.enter("cancel_all").await?;
+        let ids: Vec<String> = self.state.lock().orders.keys().cloned().collect();
+        Ok(self.cancel_ids(&ids))
+    }
+
+    async fn cancel_condition_orders(&self, condition_id: &str) -> Result<CancelOrdersResponse> {
+        self.enter("cancel_condition_orders").await?;
+        let ids: Vec<String> = self
+            .state
+            .lock()
+            .orders
+            .values()
+            .filter(|order| order.market == condition_id)
+            .map(|order| order.id.clone())
+            .collect();
+        Ok(self.cancel_ids(&ids))
+    }
+
+    async fn cancel_asset_orders(&self, token_id: &TokenId) -> Result<CancelOrdersResponse> {
+        self.enter("cancel_asset_orders").await?;
+        let ids: Vec<String> = self
+            .state
+            .lock()
+            .orders
+            .values()
+            .filter(|order| order.asset_id == token_id.as_str())
+            .map(|order| order.id.clone())
+            .collect();
+        Ok(self.cancel_ids(&ids))
+    }
+
+    async fn get_orders(
+        &self,
+        params: Option<&OpenOrderParams>,
+        _next_cursor: Option<&str>,
+    ) -> Result<Vec<OpenOrder>> {
+        self.enter("get_orders").await?;
+        let state = self.state.lock();
+        Ok(state
+            .orders
+            .values()
+            .filter(|order| match params {
+                Some(p) => {
+                    p.id.as_ref().is_none_or(|id| *id == order.id)
+                        && p.asset_id.as_ref().is_none_or(|a| *a == order.asset_id)
+                        && p.market.as_ref().is_none_or(|m| *m == order.market)
+                },
+                None => true,
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_order(&self, order_id: &str) -> Result<OpenOrder> {
+        self.enter("get_order").await?;
+        self.state
+            .lock()
+            .orders
+            .get(order_id)
+            .cloned()
+            .ok_or_else(|| {
+                PolyfillError::order(
+                    format!("Order {order_id} not found"),
+                    OrderErrorKind::OrderNotFound,
+                )
+            })
+    }
+
+    async fn get_trades(
+        &self,
+        _trade_params: Option<&TradeParams>,
+        _next_cursor: Option<&str>,
+    ) -> Result<Vec<Value>> {
+        self.enter("get_trades").await?;
+        Ok(self.state.lock().trades.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fake() -> FakeClob {
+        let fake = FakeClob::new();
+        fake.set_token(
+            "1",
+            FakeToken::new("0xabc")
+                .with_book(&[(dec!(0.48), dec!(100))], &[(dec!(0.52), dec!(50))]),
+        );
+        fake
+    }
+
+    /// Strategy code generic over the exchange
+    async fn quote_inside<A: ClobApi>(api: &A, token_id: &str) -> Result<PostOrderResponse> {
+        let book = api.get_order_book(token_id).await?;
+        let tick = api.get_tick_size(token_id).await?;
+        let price = book.bids[0].price + tick;
+        api.create_and_post_order(
+            &OrderArgs::new(token_id.parse()?, price, dec!(10), Side::BUY),
+            None,
+            None,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_strategy_runs_against_fake() {
+        let fake = fake();
+        let response = quote_inside(&fake, "1").await.unwrap();
+        assert!(response.success);
+
+        let orders = fake.get_orders(None, None).await.unwrap();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].price, dec!(0.49));
+        assert_eq!(fake.get_midpoint("1").await.unwrap().mid, dec!(0.50));
+
+        let cancelled = fake.cancel_all().await.unwrap();
+        assert_eq!(cancelled.canceled, vec![response.order_id]);
+        assert!(fake.open_orders().is_empty());
+        assert_eq!(
+            fake.calls(),
+            vec![
+                "get_order_book",
+                "get_tick_size",
+                "create_and_post_order",
+                "get_orders",
+                "get_midpoint",
+                "cancel_all"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scoped_cancels_leave_other_markets() {
+        let fake = fake();
+        fake.set_token("2", FakeToken::new("0xabc"));
+        fake.set_token("3", FakeToken::new("0xdef"));
+        for token in ["1", "2", "3"] {
+            let args = OrderArgs::new(token.parse().unwrap(), dec!(0.4), dec!(10), Side::BUY);
+            fake.create_and_post_order(&args, None, None).await.unwrap();
+        }
+
+        let cancelled = fake
+            .cancel_asset_orders(&"2".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(cancelled.canceled.len(), 1);
+        let cancelled = fake.cancel_condition_orders("0xabc").await.unwrap();
+        assert_eq!(cancelled.canceled.len(), 1);
+        let remaining = fake.open_orders();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].market, "0xdef");
+    }
+
+    #[tokio::test]
+    async fn test_scripted_failures() {
+        let fake = fake();
+        fake.fail_next("get_order_book", PolyfillError::rate_limit("slow down"));
+        assert!(matches!(
+            fake.get_order_book("1").await,
+            Err(PolyfillError::RateLimit { .. })
+        ));
+        assert!(fake.get_order_book("1").await.is_ok());
+
+        fake.fail_always("cancel", PolyfillError::api(503, "unavailable"));
+        assert!(fake.cancel("x").await.is_err());
+        assert!(fake.cancel("x").await.is_err());
+        fake.clear_failures();
+        let response = fake.cancel("x").await.unwrap();
+        assert!(response.not_canceled.contains_key("x"));
+
+        assert!(matches!(
+            fake.get_order_book("missing").await,
+            Err(PolyfillError::Api { status: 404, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_order_validation_and_latency() {
+        let fake = fake();
+        let off_tick = OrderArgs::new("1".parse().unwrap(), dec!(0.495), dec!(10), Side::BUY);
+        assert!(fake
+            .create_and_post_order(&off_tick, None, None)
+            .await
+            .is_err());
+        let too_small = OrderArgs::new("1".parse().unwrap(), dec!(0.49), dec!(1), Side::BUY);
+        assert!(fake
+            .create_and_post_order(&too_small, None, None)
+            .await
+            .is_err());
+
+        fake.set_latency(Duration::from_millis(20));
+        let start = std::time::Instant::now();
+        fake.get_tick_size("1").await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,379 @@
+//! Tamper-evident audit log of order submissions and cancels
+//!
+//! [`AuditLog`] appends one JSON line per event: each signed order as it is
+//! posted together with its digest, the exchange's answer, and each cancel
+//! request and its outcome. Every line carries the SHA-256 of the line
+//! before it, so editing, removing or reordering entries breaks the chain
+//! and [`verify`] reports the first entry that no longer fits. Keep
+//! [`AuditLog::head`] somewhere else (a ticket, a log shipper) to also catch
+//! truncation of the tail.
+//!
+//! Attach a log with [`crate::ClobClient::set_audit_log`]. Submissions are
+//! written and synced before the request is sent, and a submission that
+//! cannot be recorded is not sent.
+
+use crate::errors::{PolyfillError, Result};
+use crate::types::{CancelOrdersResponse, OrderType, PostOrderResponse, SignedOrderRequest};
+use alloy_primitives::hex;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// `prev_hash` of the first entry
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Something the client did or was told
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+pub enum AuditEvent {
+    /// A signed order about to be posted
+    OrderSubmitted {
+        digest: String,
+        order: SignedOrderRequest,
+        order_type: OrderType,
+        post_only: bool,
+        defer_exec: bool,
+    },
+    /// The exchange's answer to a submitted order
+    OrderResult {
+        digest: String,
+        success: bool,
+        order_id: Option<String>,
+        status: Option<String>,
+        error: Option<String>,
+    },
+    /// A cancel request about to be sent
+    CancelSubmitted { endpoint: String, body: Value },
+    /// The exchange's answer to a cancel request
+    CancelResult {
+        endpoint: String,
+        canceled: Vec<String>,
+        not_canceled: BTreeMap<String, String>,
+        error: Option<String>,
+    },
+}
+
+/// One line of the log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub recorded_at: DateTime<Utc>,
+    /// SHA-256 of the previous line, hex encoded
+    pub prev_hash: String,
+    pub event: AuditEvent,
+}
+
+/// SHA-256 of an order as posted, hex encoded
+pub fn order_digest(order: &SignedOrderRequest) -> Result<String> {
+    Ok(hex::encode(Sha256::digest(serde_json::to_vec(order)?)))
+}
+
+#[derive(Debug)]
+struct Inner {
+    file: File,
+    next_seq: u64,
+    head: String,
+}
+
+/// Append-only, hash-chained log of signed payloads and their outcomes
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    inner: Mutex<Inner>,
+}
+
+impl AuditLog {
+    /// Open (or create) the log at `path`; fails if the existing chain is
+    /// broken
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let body = match std::fs::read(&path) {
+            Ok(body) => body,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(io_error(&path, e)),
+        };
+        let chain = walk(&path, &body)?;
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| io_error(&path, e))?;
+        if chain.valid_len < body.len() {
+            // Appends must start on a fresh line
+            file.set_len(chain.valid_len as u64)
+                .map_err(|e| io_error(&path, e))?;
+        }
+        Ok(Self {
+            path,
+            inner: Mutex::new(Inner {
+                file,
+                next_seq: chain.entries,
+                head: chain.head,
+            }),
+        })
+    }
+
+    /// Hash of the last entry, to anchor the chain outside the file
+    pub fn head(&self) -> String {
+        self.inner.lock().head.clone()
+    }
+
+    /// Append and sync `event`
+    pub fn record(&self, event: AuditEvent) -> Result<AuditEntry> {
+        let mut inner = self.inner.lock();
+        let entry = AuditEntry {
+            seq: inner.next_seq,
+            recorded_at: Utc::now(),
+            prev_hash: inner.head.clone(),
+            event,
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        let head = hex::encode(Sha256::digest(&line));
+        line.push(b'\n');
+        inner
+            .file
+            .write_all(&line)
+            .and_then(|()| inner.file.sync_data())
+            .map_err(|e| io_error(&self.path, e))?;
+        inner.next_seq += 1;
+        inner.head = head;
+        Ok(entry)
+    }
+
+    /// Record orders about to be posted; returns their digests
+    pub(crate) fn record_submitted<'a>(
+        &self,
+        orders: impl IntoIterator<Item = (&'a SignedOrderRequest, OrderType, bool, bool)>,
+    ) -> Result<Vec<String>> {
+        orders
+            .into_iter()
+            .map(|(order, order_type, post_only, defer_exec)| {
+                let digest = order_digest(order)?;
+                self.record(AuditEvent::OrderSubmitted {
+                    digest: digest.clone(),
+                    order: order.clone(),
+                    order_type,
+                    post_only,
+                    defer_exec,
+                })?;
+                Ok(digest)
+            })
+            .collect()
+    }
+
+    /// Record the outcome of posting the orders behind `digests`.
+    ///
+    /// The orders are already out, so a failed write is logged rather than
+    /// returned.
+    pub(crate) fn record_results(
+        &self,
+        digests: &[String],
+        result: std::result::Result<&[PostOrderResponse], &PolyfillError>,
+    ) {
+        for (i, digest) in digests.iter().enumerate() {
+            let event = match result {
+                Ok(responses) => match responses.get(i) {
+                    Some(response) => AuditEvent::OrderResult {
+                        digest: digest.clone(),
+                        success: response.success,
+                        order_id: Some(response.order_id.clone())
+                            .filter(|order_id| !order_id.is_empty()),
+                        status: Some(response.status.clone()),
+                        error: Some(response.error_msg.clone()).filter(|e| !e.is_empty()),
+                    },
+                    None => AuditEvent::OrderResult {
+                        digest: digest.clone(),
+                        success: false,
+                        order_id: None,
+                        status: None,
+                        error: Some("No response for this order".to_string()),
+                    },
+                },
+                Err(e) => AuditEvent::OrderResult {
+                    digest: digest.clone(),
+                    success: false,
+                    order_id: None,
+                    status: None,
+                    error: Some(e.to_string()),
+                },
+            };
+            if let Err(e) = self.record(event) {
+                warn!("Failed to record order result {}: {}", digest, e);
+            }
+        }
+    }
+
+    /// Record the outcome of a cancel request; failed writes are logged
+    pub(crate) fn record_cancel_result(
+        &self,
+        endpoint: &str,
+        result: std::result::Result<&CancelOrdersResponse, &PolyfillError>,
+    ) {
+        let event = match result {
+            Ok(response) => AuditEvent::CancelResult {
+                endpoint: endpoint.to_string(),
+                canceled: response.canceled.clone(),
+                not_canceled: response.not_canceled.clone().into_iter().collect(),
+                error: None,
+            },
+            Err(e) => AuditEvent::CancelResult {
+                endpoint: endpoint.to_string(),
+                canceled: Vec::new(),
+                not_canceled: BTreeMap::new(),
+                error: Some(e.to_string()),
+            },
+        };
+        if let Err(e) = self.record(event) {
+            warn!("Failed to record cancel result for {}: {}", endpoint, e);
+        }
+    }
+}
+
+/// Check the chain of the log at `path`; returns its entries
+pub fn verify(path: impl AsRef<Path>) -> Result<Vec<AuditEntry>> {
+    let path = path.as_ref();
+    let body = std::fs::read(path).map_err(|e| io_error(path, e))?;
+    let chain = walk(path, &body)?;
+    if chain.valid_len < body.len() {
+        return Err(PolyfillError::validation(format!(
+            "Audit log {} ends in an incomplete entry",
+            path.display()
+        )));
+    }
+    Ok(chain.parsed)
+}
+
+struct Chain {
+    entries: u64,
+    head: String,
+    /// Bytes up to the end of the last complete entry
+    valid_len: usize,
+    parsed: Vec<AuditEntry>,
+}
+
+fn walk(path: &Path, body: &[u8]) -> Result<Chain> {
+    let mut chain = Chain {
+        entries: 0,
+        head: GENESIS_HASH.to_string(),
+        valid_len: 0,
+        parsed: Vec::new(),
+    };
+    let mut lines = body.split_inclusive(|byte| *byte == b'\n').peekable();
+    while let Some(line) = lines.next() {
+        let content = line.strip_suffix(b"\n").unwrap_or(line);
+        let entry = match serde_json::from_slice::<AuditEntry>(content) {
+            Ok(entry) if line.ends_with(b"\n") => entry,
+            // A crash mid-append leaves a torn last line
+            Ok(_) => {
+                warn!("Dropping incomplete last audit entry");
+                break;
+            },
+            Err(e) if lines.peek().is_none() => {
+                warn!("Dropping incomplete last audit entry: {}", e);
+                break;
+            },
+            Err(e) => {
+                return Err(PolyfillError::validation(format!(
+                    "Audit log {} has an unreadable entry after seq {}: {e}",
+                    path.display(),
+                    chain.entries
+                )))
+            },
+        };
+        if entry.seq != chain.entries || entry.prev_hash != chain.head {
+            return Err(PolyfillError::validation(format!(
+                "Audit log {} chain is broken at seq {}",
+                path.display(),
+                entry.seq
+            )));
+        }
+        chain.entries += 1;
+        chain.head = hex::encode(Sha256::digest(content));
+        chain.valid_len += line.len();
+        chain.parsed.push(entry);
+    }
+    Ok(chain)
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> PolyfillError {
+    PolyfillError::internal(format!("Audit log {}", path.display()), e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(salt: u64) -> SignedOrderRequest {
+        SignedOrderRequest {
+            salt,
+            maker: "0x1".to_string(),
+            signer: "0x1".to_string(),
+            token_id: "123".to_string(),
+            maker_amount: "5000000".to_string(),
+            taker_amount: "10000000".to_string(),
+            expiration: "0".to_string(),
+            side: "BUY".to_string(),
+            signature_type: 0,
+            timestamp: "0".to_string(),
+            metadata: String::new(),
+            builder: String::new(),
+            signature: "0xsig".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_chain_survives_reopen_and_detects_tampering() {
+        let dir = std::env::temp_dir().join(format!("polyfill-audit-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
+
+        let log = AuditLog::open(&path).unwrap();
+        let digests = log
+            .record_submitted([(&order(1), OrderType::GTC, false, false)])
+            .unwrap();
+        assert_eq!(digests[0], order_digest(&order(1)).unwrap());
+        assert_ne!(digests[0], order_digest(&order(2)).unwrap());
+        log.record_results(
+            &digests,
+            Err(&PolyfillError::network(
+                "timed out",
+                std::io::Error::other("timeout"),
+            )),
+        );
+        drop(log);
+
+        let log = AuditLog::open(&path).unwrap();
+        log.record(AuditEvent::CancelSubmitted {
+            endpoint: "/cancel-all".to_string(),
+            body: Value::Null,
+        })
+        .unwrap();
+        let head = log.head();
+        drop(log);
+
+        let entries = verify(&path).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[2].seq, 2);
+        assert!(matches!(
+            &entries[1].event,
+            AuditEvent::OrderResult { success: false, error: Some(e), .. } if e.contains("timed out")
+        ));
+        assert_eq!(AuditLog::open(&path).unwrap().head(), head);
+
+        let body = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, body.replacen("5000000", "9000000", 1)).unwrap();
+        let err = verify(&path).unwrap_err();
+        assert!(err.to_string().contains("broken at seq 1"), "{err}");
+        assert!(AuditLog::open(&path).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
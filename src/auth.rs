@@ -17,11 +17,10 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::ops::Deref;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 // Header constants
 const POLY_ADDR_HEADER: &str = "poly_address";
-const POLY_SIG_HEADER: &str = "poly_signature";
+pub(crate) const POLY_SIG_HEADER: &str = "poly_signature";
 const POLY_TS_HEADER: &str = "poly_timestamp";
 const POLY_NONCE_HEADER: &str = "poly_nonce";
 const POLY_API_KEY_HEADER: &str = "poly_api_key";
@@ -81,6 +80,20 @@ impl HmacApiCredentials for ApiCredentials {
     }
 }
 
+impl<T: HmacApiCredentials + ?Sized> HmacApiCredentials for Arc<T> {
+    fn api_key(&self) -> &str {
+        (**self).api_key()
+    }
+
+    fn passphrase(&self) -> &str {
+        (**self).passphrase()
+    }
+
+    fn decoded_secret_bytes(&self) -> Result<Cow<'_, [u8]>> {
+        (**self).decoded_secret_bytes()
+    }
+}
+
 impl HmacApiCredentials for PreparedApiCredentials {
     fn api_key(&self) -> &str {
         &self.credentials.api_key
@@ -172,12 +185,9 @@ impl PreparedOrderDomain {
     }
 }
 
-/// Get current Unix timestamp in seconds
+/// Get current Unix timestamp in seconds, never less than a previous reading
 pub fn get_current_unix_time_secs() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_secs()
+    crate::nonce::NonceManager::global().timestamp_secs()
 }
 
 /// Sign CLOB authentication message using EIP-712
@@ -303,6 +313,69 @@ fn order_sol(order: SignedOrderMessage) -> Order {
     }
 }
 
+/// EIP-712 hash an EOA signs for `order` under `domain`
+pub(crate) fn order_signing_hash(order: SignedOrderMessage, domain: &PreparedOrderDomain) -> B256 {
+    order_sol(order).eip712_signing_hash(&domain.domain)
+}
+
+/// Recover the EOA behind a POLY_1271 wrapped signature, after checking the
+/// wrapper was built for `order` under `domain`
+pub(crate) fn recover_poly1271_signer(
+    signature: &[u8],
+    order: SignedOrderMessage,
+    domain: &PreparedOrderDomain,
+    deposit_wallet: Address,
+    chain_id: u64,
+) -> Result<Address> {
+    let type_bytes = ORDER_TYPE_STRING.as_bytes();
+    if signature.len() != 65 + 32 + 32 + type_bytes.len() + 2 {
+        return Err(PolyfillError::validation(
+            "POLY_1271 signature has the wrong length for an ERC-7739 wrapper",
+        ));
+    }
+    let (inner, rest) = signature.split_at(65);
+    let (app_domain_separator, rest) = rest.split_at(32);
+    let (contents_hash, rest) = rest.split_at(32);
+
+    let order = order_sol(order);
+    if app_domain_separator != domain.domain.hash_struct().as_slice() {
+        return Err(PolyfillError::validation(
+            "POLY_1271 signature was wrapped for a different exchange domain",
+        ));
+    }
+    if contents_hash != order.eip712_hash_struct().as_slice()
+        || &rest[..type_bytes.len()] != type_bytes
+    {
+        return Err(PolyfillError::validation(
+            "POLY_1271 signature was wrapped for different order contents",
+        ));
+    }
+
+    let envelope = TypedDataSign {
+        contents: order,
+        name: DEPOSIT_WALLET_NAME.to_string(),
+        version: DEPOSIT_WALLET_VERSION.to_string(),
+        chainId: U256::from(chain_id),
+        verifyingContract: deposit_wallet,
+        salt: ERC7739_TYPED_DATA_SIGN_SALT,
+    };
+    recover_signer(inner, &envelope.eip712_signing_hash(&domain.domain))
+}
+
+/// Whether a POLY_1271 wrapped signature names `domain` as its app domain
+pub(crate) fn poly1271_wrapped_for(signature: &[u8], domain: &PreparedOrderDomain) -> bool {
+    signature
+        .get(65..97)
+        .is_some_and(|separator| separator == domain.domain.hash_struct().as_slice())
+}
+
+/// Recover the address behind a 65-byte signature of `hash`
+pub(crate) fn recover_signer(signature: &[u8], hash: &B256) -> Result<Address> {
+    alloy_primitives::Signature::from_raw(signature)
+        .and_then(|signature| signature.recover_address_from_prehash(hash))
+        .map_err(|e| PolyfillError::crypto(format!("Invalid order signature: {e}")))
+}
+
 /// Build HMAC signature for L2 authentication
 ///
 /// Performs cryptographic message authentication using SHA-256 with
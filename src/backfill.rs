@@ -0,0 +1,427 @@
+//! Historical data backfill
+//!
+//! [`Backfiller`] pulls a token's price history from the CLOB
+//! `/prices-history` endpoint and its public trade tape from the Polymarket
+//! data API, converting both into the capture record types so backtests can
+//! mix recorded and backfilled data. With the `capture` feature the results
+//! can be written straight into a capture root via
+//! [`Backfiller::backfill_to`].
+
+use crate::capture::{PricePointRecord, TradeRecord};
+use crate::client::ClobClient;
+use crate::errors::{PolyfillError, Result};
+use crate::types::Side;
+use crate::utils::retry::until_cancelled;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::Value;
+use std::str::FromStr;
+use tokio_util::sync::CancellationToken;
+use tracing::debug;
+
+/// Public Polymarket data API (trade history)
+pub const DEFAULT_DATA_API_URL: &str = "https://data-api.polymarket.com";
+
+/// Number of records fetched and written by a backfill run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackfillSummary {
+    pub prices: usize,
+    pub trades: usize,
+}
+
+/// Trade as returned by the data API `/trades` endpoint
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DataApiTrade {
+    asset: String,
+    side: Side,
+    size: serde_json::Number,
+    price: serde_json::Number,
+    /// Unix seconds
+    timestamp: u64,
+    #[serde(default)]
+    transaction_hash: Option<String>,
+}
+
+/// Pulls historical prices and trades into capture records
+#[derive(Debug, Clone)]
+pub struct Backfiller {
+    data_api_url: String,
+    page_size: usize,
+    fidelity: Option<u32>,
+    max_window: Duration,
+    cancel: CancellationToken,
+}
+
+impl Default for Backfiller {
+    fn default() -> Self {
+        Self {
+            data_api_url: DEFAULT_DATA_API_URL.to_string(),
+            page_size: 500,
+            fidelity: None,
+            max_window: Duration::days(14),
+            cancel: CancellationToken::new(),
+        }
+    }
+}
+
+impl Backfiller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the data API base URL
+    pub fn with_data_api_url(mut self, url: impl Into<String>) -> Self {
+        self.data_api_url = url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Trades requested per data API page
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Price history resolution in minutes (server default if unset)
+    pub fn with_fidelity(mut self, minutes: u32) -> Self {
+        self.fidelity = Some(minutes);
+        self
+    }
+
+    /// Longest time range requested from `/prices-history` in one call
+    pub fn with_max_window(mut self, window: Duration) -> Self {
+        self.max_window = window.max(Duration::minutes(1));
+        self
+    }
+
+    /// Abort in-progress pagination when `cancel` fires
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Fetch price history for `token_id` in `[start, end)`, oldest first
+    pub async fn prices(
+        &self,
+        client: &ClobClient,
+        token_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<PricePointRecord>> {
+        validate_range(start, end)?;
+
+        let mut records = Vec::new();
+        let mut window_start = start;
+        while window_start < end {
+            let window_end = (window_start + self.max_window).min(end);
+            let response = until_cancelled(
+                &self.cancel,
+                "price backfill",
+                client.get_prices_history_range(
+                    token_id,
+                    window_start.timestamp() as u64,
+                    window_end.timestamp() as u64,
+                    self.fidelity,
+                ),
+            )
+            .await?;
+
+            for point in &response.history {
+                let record = parse_price_point(token_id, point)?;
+                let ts = record.timestamp_ms as i64;
+                if ts >= window_start.timestamp_millis() && ts < window_end.timestamp_millis() {
+                    records.push(record);
+                }
+            }
+            window_start = window_end;
+        }
+
+        records.sort_by_key(|r| r.timestamp_ms);
+        records.dedup_by_key(|r| r.timestamp_ms);
+        debug!("Backfilled {} price points for {}", records.len(), token_id);
+        Ok(records)
+    }
+
+    /// Fetch public trades for `token_id` in `[start, end)`, oldest first.
+    ///
+    /// The data API filters by market, so the token's `condition_id` is
+    /// required; trades for the other outcome are dropped.
+    pub async fn trades(
+        &self,
+        client: &ClobClient,
+        condition_id: &str,
+        token_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<TradeRecord>> {
+        validate_range(start, end)?;
+        let start_s = start.timestamp() as u64;
+        let end_s = end.timestamp() as u64;
+
+        let mut records = Vec::new();
+        let mut offset = 0usize;
+        loop {
+            let page: Vec<DataApiTrade> = until_cancelled(&self.cancel, "trade backfill", async {
+                let response = client
+                    .http_client
+                    .get(format!("{}/trades", self.data_api_url))
+                    .query(&[("market", condition_id), ("takerOnly", "false")])
+                    .query(&[("limit", self.page_size), ("offset", offset)])
+                    .send()
+                    .await?;
+
+                if !response.status().is_success() {
+                    return Err(PolyfillError::api_from_response(
+                        "GET",
+                        response,
+                        "Failed to get trade history",
+                    )
+                    .await);
+                }
+
+                response
+                    .json()
+                    .await
+                    .map_err(|e| PolyfillError::parse(format!("Failed to parse trades: {e}"), None))
+            })
+            .await?;
+            let page_len = page.len();
+
+            // Pages are newest first; stop once we are past the range start
+            let mut reached_start = false;
+            for trade in page {
+                if trade.timestamp < start_s {
+                    reached_start = true;
+                    continue;
+                }
+                if trade.timestamp >= end_s || trade.asset != token_id {
+                    continue;
+                }
+                records.push(TradeRecord {
+                    token_id: trade.asset,
+                    timestamp_ms: trade.timestamp * 1000,
+                    trade_id: trade.transaction_hash,
+                    side: Some(trade.side),
+                    price: number_to_decimal(&trade.price)?,
+                    size: Some(number_to_decimal(&trade.size)?),
+                });
+            }
+
+            if reached_start || page_len < self.page_size {
+                break;
+            }
+            offset += page_len;
+        }
+
+        records.sort_by_key(|r| r.timestamp_ms);
+        debug!("Backfilled {} trades for {}", records.len(), token_id);
+        Ok(records)
+    }
+
+    /// Backfill prices and (if `condition_id` is given) trades into a capture root
+    #[cfg(feature = "capture")]
+    pub async fn backfill_to(
+        &self,
+        client: &ClobClient,
+        writer: &mut crate::capture::ParquetCaptureWriter,
+        token_id: &str,
+        condition_id: Option<&str>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<BackfillSummary> {
+        let mut summary = BackfillSummary::default();
+
+        for record in self.prices(client, token_id, start, end).await? {
+            writer.write_price(record)?;
+            summary.prices += 1;
+        }
+
+        if let Some(condition_id) = condition_id {
+            for record in self
+                .trades(client, condition_id, token_id, start, end)
+                .await?
+            {
+                writer.write_trade(record)?;
+                summary.trades += 1;
+            }
+        }
+
+        writer.flush()?;
+        Ok(summary)
+    }
+}
+
+fn validate_range(start: DateTime<Utc>, end: DateTime<Utc>) -> Result<()> {
+    if start >= end {
+        return Err(PolyfillError::validation(
+            "Backfill start must be before end",
+        ));
+    }
+    Ok(())
+}
+
+fn number_to_decimal(number: &serde_json::Number) -> Result<Decimal> {
+    let text = number.to_string();
+    Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .map_err(|e| PolyfillError::parse(format!("Invalid decimal {text}: {e}"), None))
+}
+
+/// Parse a `{"t": <unix seconds>, "p": <price>}` history entry
+fn parse_price_point(token_id: &str, point: &Value) -> Result<PricePointRecord> {
+    let timestamp = point
+        .get("t")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| PolyfillError::parse("Price history point missing `t`", None))?;
+    let price = match point.get("p") {
+        Some(Value::Number(n)) => number_to_decimal(n)?,
+        Some(Value::String(s)) => Decimal::from_str(s)
+            .map_err(|e| PolyfillError::parse(format!("Invalid price {s}: {e}"), None))?,
+        _ => {
+            return Err(PolyfillError::parse(
+                "Price history point missing `p`",
+                None,
+            ))
+        },
+    };
+
+    Ok(PricePointRecord {
+        token_id: token_id.to_string(),
+        timestamp_ms: timestamp * 1000,
+        price,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use mockito::{Matcher, Server};
+    use rust_decimal_macros::dec;
+
+    fn ts(secs: i64) -> DateTime<Utc> {
+        Utc.timestamp_opt(secs, 0).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_prices_are_chunked_and_sorted() {
+        let mut server = Server::new_async().await;
+        let first = server
+            .mock("GET", "/prices-history")
+            .match_query(Matcher::UrlEncoded("startTs".into(), "0".into()))
+            .with_status(200)
+            .with_body(r#"{"history":[{"t":60,"p":0.51},{"t":0,"p":0.5}]}"#)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/prices-history")
+            .match_query(Matcher::UrlEncoded("startTs".into(), "120".into()))
+            .with_status(200)
+            .with_body(r#"{"history":[{"t":120,"p":"0.52"},{"t":500,"p":0.9}]}"#)
+            .create_async()
+            .await;
+
+        let client = ClobClient::new(&server.url());
+        let points = Backfiller::new()
+            .with_max_window(Duration::minutes(2))
+            .prices(&client, "123", ts(0), ts(180))
+            .await
+            .unwrap();
+
+        first.assert_async().await;
+        second.assert_async().await;
+        let prices: Vec<Decimal> = points.iter().map(|p| p.price).collect();
+        assert_eq!(prices, vec![dec!(0.5), dec!(0.51), dec!(0.52)]);
+        assert_eq!(points[2].timestamp_ms, 120_000);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_trades_paginate_and_filter() {
+        let mut server = Server::new_async().await;
+        let page1 = server
+            .mock("GET", "/trades")
+            .match_query(Matcher::UrlEncoded("offset".into(), "0".into()))
+            .with_status(200)
+            .with_body(
+                r#"[{"asset":"1","side":"BUY","size":5,"price":0.5,"timestamp":300,"transactionHash":"0xa"},
+                    {"asset":"2","side":"SELL","size":5,"price":0.5,"timestamp":250}]"#,
+            )
+            .create_async()
+            .await;
+        let page2 = server
+            .mock("GET", "/trades")
+            .match_query(Matcher::UrlEncoded("offset".into(), "2".into()))
+            .with_status(200)
+            .with_body(
+                r#"[{"asset":"1","side":"SELL","size":2.5,"price":0.45,"timestamp":150},
+                    {"asset":"1","side":"SELL","size":1,"price":0.4,"timestamp":50}]"#,
+            )
+            .create_async()
+            .await;
+
+        let client = ClobClient::new("http://unused.invalid");
+        let trades = Backfiller::new()
+            .with_data_api_url(server.url())
+            .with_page_size(2)
+            .trades(&client, "0xcond", "1", ts(100), ts(400))
+            .await
+            .unwrap();
+
+        page1.assert_async().await;
+        page2.assert_async().await;
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].timestamp_ms, 150_000);
+        assert_eq!(trades[0].size, Some(dec!(2.5)));
+        assert_eq!(trades[1].trade_id.as_deref(), Some("0xa"));
+    }
+
+    #[cfg(feature = "capture")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_backfill_to_capture_root() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/prices-history")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"history":[{"t":1700000000,"p":0.5},{"t":1700003600,"p":0.6}]}"#)
+            .create_async()
+            .await;
+
+        let root = std::env::temp_dir().join(format!("polyfill-backfill-{}", uuid::Uuid::new_v4()));
+        let mut writer = crate::capture::ParquetCaptureWriter::new(&root);
+        let client = ClobClient::new(&server.url());
+        let summary = Backfiller::new()
+            .backfill_to(
+                &client,
+                &mut writer,
+                "123",
+                None,
+                ts(1_699_999_000),
+                ts(1_700_004_000),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            BackfillSummary {
+                prices: 2,
+                trades: 0
+            }
+        );
+
+        let points: Vec<PricePointRecord> = crate::capture::read_dataset(&root, "123").unwrap();
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].price, dec!(0.6));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_empty_range() {
+        let client = ClobClient::new("http://unused.invalid");
+        let result = Backfiller::new()
+            .prices(&client, "123", ts(10), ts(10))
+            .await;
+        assert!(matches!(result, Err(PolyfillError::Validation { .. })));
+    }
+}
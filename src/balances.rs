@@ -0,0 +1,304 @@
+//! Cached balance and allowance state
+//!
+//! [`BalanceCache`] keeps the last `/balance-allowance` response per asset so
+//! pre-trade checks can read balances without a REST call per order. Fills
+//! from the user channel adjust cached balances in place; cancellations
+//! invalidate the entry the order was drawing on, so the next read refreshes
+//! it. Entries also expire after a maximum age to bound drift from fees and
+//! transfers the user channel does not report.
+//!
+//! Balances are held in token units (USDC for collateral, shares for
+//! conditional tokens), not the 6-decimal base units the endpoint returns.
+
+use crate::errors::{PolyfillError, Result};
+use crate::types::{OrderMessage, Side, StreamMessage, TradeMessage};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Asset a cached balance belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BalanceKey {
+    /// USDC collateral
+    Collateral,
+    /// Conditional (outcome) token by token ID
+    Conditional(String),
+}
+
+impl BalanceKey {
+    /// Query parameters fetching this balance from `/balance-allowance`
+    pub fn params(&self) -> crate::types::BalanceAllowanceParams {
+        match self {
+            Self::Collateral => crate::types::BalanceAllowanceParams {
+                asset_type: Some(crate::types::AssetType::COLLATERAL),
+                ..Default::default()
+            },
+            Self::Conditional(token_id) => crate::types::BalanceAllowanceParams {
+                asset_type: Some(crate::types::AssetType::CONDITIONAL),
+                token_id: Some(token_id.clone()),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Balance and allowances for one asset
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedBalance {
+    pub balance: Decimal,
+    /// Allowance per spender address
+    pub allowances: HashMap<String, Decimal>,
+    /// Whether fills have been applied since the last REST refresh
+    pub adjusted: bool,
+}
+
+impl CachedBalance {
+    /// Parse a `/balance-allowance` response
+    pub fn from_response(response: &Value) -> Result<Self> {
+        let balance = response
+            .get("balance")
+            .map(base_units)
+            .transpose()?
+            .ok_or_else(|| PolyfillError::parse("Balance response missing `balance`", None))?;
+
+        let mut allowances = HashMap::new();
+        if let Some(map) = response.get("allowances").and_then(Value::as_object) {
+            for (spender, amount) in map {
+                allowances.insert(spender.clone(), base_units(amount)?);
+            }
+        }
+        // Older responses carry a single allowance
+        if let Some(amount) = response.get("allowance") {
+            allowances.insert(String::new(), base_units(amount)?);
+        }
+
+        Ok(Self {
+            balance,
+            allowances,
+            adjusted: false,
+        })
+    }
+}
+
+fn base_units(value: &Value) -> Result<Decimal> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        Value::Number(number) => number.to_string(),
+        other => {
+            return Err(PolyfillError::parse(
+                format!("Invalid balance amount: {other}"),
+                None,
+            ))
+        },
+    };
+    match Decimal::from_str(&text) {
+        Ok(units) => Ok(units / Decimal::from(1_000_000)),
+        // Unlimited (max uint256) approvals exceed Decimal's range
+        Err(_) if !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()) => Ok(Decimal::MAX),
+        Err(e) => Err(PolyfillError::parse(
+            format!("Invalid balance amount {text}: {e}"),
+            None,
+        )),
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    balance: CachedBalance,
+    fetched_at: Instant,
+}
+
+/// Balance-allowance cache kept current by user-channel events
+#[derive(Debug)]
+pub struct BalanceCache {
+    entries: RwLock<HashMap<BalanceKey, Entry>>,
+    max_age: Duration,
+}
+
+impl Default for BalanceCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(300))
+    }
+}
+
+impl BalanceCache {
+    /// Create a cache whose entries expire `max_age` after their REST refresh
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            entries: RwLock::new(HashMap::new()),
+            max_age,
+        }
+    }
+
+    /// Cached balance, if present and not expired
+    pub fn get(&self, key: &BalanceKey) -> Option<CachedBalance> {
+        let entries = self.entries.read();
+        let entry = entries.get(key)?;
+        (entry.fetched_at.elapsed() < self.max_age).then(|| entry.balance.clone())
+    }
+
+    /// Store a freshly fetched balance
+    pub fn insert(&self, key: BalanceKey, balance: CachedBalance) {
+        self.entries.write().insert(
+            key,
+            Entry {
+                balance,
+                fetched_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drop a cached balance so the next read refreshes it
+    pub fn invalidate(&self, key: &BalanceKey) {
+        self.entries.write().remove(key);
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().clear();
+    }
+
+    /// Apply a user-channel message; other messages are ignored
+    pub fn apply(&self, message: &StreamMessage) {
+        match message {
+            StreamMessage::Trade(trade) => self.apply_fill(trade),
+            StreamMessage::Order(order) => self.apply_order_update(order),
+            _ => {},
+        }
+    }
+
+    /// Move collateral and shares for a fill.
+    ///
+    /// A trade is reported again as it is mined and confirmed; only the
+    /// initial `MATCHED` report moves balances, and a `FAILED` settlement
+    /// invalidates both entries. Only entries already cached are adjusted;
+    /// fees are not known from the fill and are picked up at the next refresh.
+    pub fn apply_fill(&self, trade: &TradeMessage) {
+        let keys = [
+            BalanceKey::Collateral,
+            BalanceKey::Conditional(trade.asset_id.clone()),
+        ];
+        match trade.status.as_deref() {
+            None | Some("MATCHED") => {},
+            Some("FAILED") => {
+                let mut entries = self.entries.write();
+                for key in &keys {
+                    entries.remove(key);
+                }
+                return;
+            },
+            Some(_) => return,
+        }
+
+        let notional = trade.price * trade.size;
+        let (collateral_delta, shares_delta) = match trade.side {
+            Side::BUY => (-notional, trade.size),
+            Side::SELL => (notional, -trade.size),
+        };
+
+        let mut entries = self.entries.write();
+        for (key, delta) in keys.iter().zip([collateral_delta, shares_delta]) {
+            if let Some(entry) = entries.get_mut(key) {
+                entry.balance.balance += delta;
+                entry.balance.adjusted = true;
+            }
+        }
+        debug!("Applied fill {} to cached balances", trade.id);
+    }
+
+    /// Invalidate the balance a cancelled order was drawing on
+    pub fn apply_order_update(&self, order: &OrderMessage) {
+        if order.msg_type.as_deref() != Some("CANCELLATION") {
+            return;
+        }
+        let key = match order.side {
+            Side::BUY => BalanceKey::Collateral,
+            Side::SELL => BalanceKey::Conditional(order.asset_id.clone()),
+        };
+        self.invalidate(&key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn message(json: &str) -> StreamMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_response_scales_base_units() {
+        let balance = CachedBalance::from_response(&serde_json::json!({
+            "balance": "12500000",
+            "allowances": {"0xexchange": "115792089237316195423570985008687907853269984665640"}
+        }))
+        .unwrap();
+        assert_eq!(balance.balance, dec!(12.5));
+        assert!(balance.allowances["0xexchange"] > dec!(1_000_000));
+        assert!(CachedBalance::from_response(&serde_json::json!({})).is_err());
+    }
+
+    #[test]
+    fn test_fills_adjust_and_cancels_invalidate() {
+        let cache = BalanceCache::default();
+        let cached = |balance| CachedBalance {
+            balance,
+            allowances: HashMap::new(),
+            adjusted: false,
+        };
+        cache.insert(BalanceKey::Collateral, cached(dec!(100)));
+        cache.insert(BalanceKey::Conditional("1".to_string()), cached(dec!(0)));
+
+        cache.apply(&message(
+            r#"{"event_type":"trade","id":"t1","market":"0xabc","asset_id":"1","side":"BUY","size":"10","price":"0.4"}"#,
+        ));
+        // Later settlement reports of the same trade do not move balances again
+        cache.apply(&message(
+            r#"{"event_type":"trade","id":"t1","market":"0xabc","asset_id":"1","side":"BUY","size":"10","price":"0.4","status":"CONFIRMED"}"#,
+        ));
+        let collateral = cache.get(&BalanceKey::Collateral).unwrap();
+        assert_eq!(collateral.balance, dec!(96));
+        assert!(collateral.adjusted);
+        assert_eq!(
+            cache
+                .get(&BalanceKey::Conditional("1".to_string()))
+                .unwrap()
+                .balance,
+            dec!(10)
+        );
+
+        // Placements leave the cache alone; cancellations drop the entry
+        cache.apply(&message(
+            r#"{"event_type":"order","id":"o1","market":"0xabc","asset_id":"1","side":"SELL","price":"0.6","type":"PLACEMENT"}"#,
+        ));
+        assert!(cache
+            .get(&BalanceKey::Conditional("1".to_string()))
+            .is_some());
+        cache.apply(&message(
+            r#"{"event_type":"order","id":"o1","market":"0xabc","asset_id":"1","side":"SELL","price":"0.6","type":"CANCELLATION"}"#,
+        ));
+        assert!(cache
+            .get(&BalanceKey::Conditional("1".to_string()))
+            .is_none());
+        assert!(cache.get(&BalanceKey::Collateral).is_some());
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let cache = BalanceCache::new(Duration::ZERO);
+        cache.insert(
+            BalanceKey::Collateral,
+            CachedBalance {
+                balance: dec!(1),
+                allowances: HashMap::new(),
+                adjusted: false,
+            },
+        );
+        assert!(cache.get(&BalanceKey::Collateral).is_none());
+    }
+}
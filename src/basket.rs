@@ -0,0 +1,246 @@
+//! Negative-risk basket orders
+//!
+//! In a neg-risk event exactly one outcome resolves YES, so a set of YES
+//! shares across outcomes pays 1 per share if any of them wins. Two trades
+//! follow from that:
+//!
+//! - "not outcome X": buy YES on every other outcome
+//! - sum-below-one arbitrage: buy YES on every outcome when their asks add up
+//!   to less than 1
+//!
+//! [`NegRiskBasket`] sizes every leg to the same share count, capped by the
+//! thinnest leg, and posts the legs as one batch. If some legs are rejected
+//! the placed ones are cancelled, so the basket is all-or-nothing as far as
+//! the exchange allows.
+
+use crate::book::OrderBookManager;
+use crate::client::ClobClient;
+use crate::errors::{MarketDataErrorKind, PolyfillError, Result};
+use crate::types::{
+    CreateOrderOptions, OrderArgs, OrderType, PostOrderOptions, PostOrderResponse, Side, TokenId,
+};
+use rust_decimal::{Decimal, RoundingStrategy};
+use tracing::warn;
+
+/// Leg sizes are rounded down to this many decimal places
+const SIZE_DECIMALS: u32 = 2;
+
+/// YES token of one outcome and the ask it can be bought at
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasketLeg {
+    pub token_id: TokenId,
+    pub price: Decimal,
+    /// Shares offered at `price`
+    pub available: Decimal,
+}
+
+impl BasketLeg {
+    pub fn new(token_id: TokenId, price: Decimal, available: Decimal) -> Self {
+        Self {
+            token_id,
+            price,
+            available,
+        }
+    }
+
+    /// Leg at the best ask of the local book for `token_id`
+    pub fn from_book(books: &OrderBookManager, token_id: &str) -> Result<Self> {
+        let token = TokenId::new(token_id)?;
+        let ask = books.with_book(token_id, |book| book.best_ask())?;
+        let ask = ask.ok_or_else(|| {
+            PolyfillError::market_data(
+                format!("No asks for {token_id}"),
+                MarketDataErrorKind::IncompleteData,
+            )
+        })?;
+        Ok(Self::new(token, ask.price, ask.size))
+    }
+}
+
+/// Balanced set of YES orders across a neg-risk event
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegRiskBasket {
+    pub legs: Vec<BasketLeg>,
+    /// Shares bought on every leg
+    pub size: Decimal,
+}
+
+/// Outcome of [`NegRiskBasket::execute`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasketExecution {
+    /// One response per leg, in leg order
+    pub responses: Vec<PostOrderResponse>,
+    /// Placed legs cancelled because another leg failed
+    pub cancelled: Vec<String>,
+    /// Placed legs that could not be cancelled, e.g. because they matched
+    pub stranded: Vec<String>,
+}
+
+impl BasketExecution {
+    /// Whether every leg was accepted
+    pub fn is_complete(&self) -> bool {
+        self.responses.iter().all(|response| response.success)
+    }
+}
+
+impl NegRiskBasket {
+    /// Basket of `size` shares on each leg, reduced to what the thinnest leg
+    /// offers
+    pub fn balanced(legs: Vec<BasketLeg>, size: Decimal) -> Result<Self> {
+        if legs.is_empty() {
+            return Err(PolyfillError::validation("Basket has no legs"));
+        }
+        for leg in &legs {
+            if leg.price <= Decimal::ZERO || leg.price >= Decimal::ONE {
+                return Err(PolyfillError::validation(format!(
+                    "Price for {} must be between 0 and 1, got {}",
+                    leg.token_id, leg.price
+                )));
+            }
+        }
+        let size = legs
+            .iter()
+            .map(|leg| leg.available)
+            .fold(size, Decimal::min)
+            .round_dp_with_strategy(SIZE_DECIMALS, RoundingStrategy::ToZero);
+        if size <= Decimal::ZERO {
+            return Err(PolyfillError::validation("No size available on every leg"));
+        }
+        Ok(Self { legs, size })
+    }
+
+    /// Express "not `excluded`" by buying YES on every other outcome
+    pub fn not_outcome(outcomes: &[BasketLeg], excluded: &str, size: Decimal) -> Result<Self> {
+        if !outcomes.iter().any(|leg| leg.token_id == excluded) {
+            return Err(PolyfillError::validation(format!(
+                "Outcome {excluded} is not part of the event"
+            )));
+        }
+        let legs = outcomes
+            .iter()
+            .filter(|leg| leg.token_id != excluded)
+            .cloned()
+            .collect();
+        Self::balanced(legs, size)
+    }
+
+    /// Buy every outcome if their asks sum to at most `1 - min_edge`.
+    ///
+    /// `outcomes` must cover the whole event, including any "other" outcome,
+    /// or the payout is not guaranteed.
+    pub fn arbitrage(
+        outcomes: &[BasketLeg],
+        max_size: Decimal,
+        min_edge: Decimal,
+    ) -> Result<Option<Self>> {
+        let total: Decimal = outcomes.iter().map(|leg| leg.price).sum();
+        if total > Decimal::ONE - min_edge {
+            return Ok(None);
+        }
+        Self::balanced(outcomes.to_vec(), max_size).map(Some)
+    }
+
+    /// Capital spent if every leg fills
+    pub fn cost(&self) -> Decimal {
+        self.legs.iter().map(|leg| leg.price * self.size).sum()
+    }
+
+    /// Payout if one of the legs' outcomes wins
+    pub fn payout(&self) -> Decimal {
+        self.size
+    }
+
+    pub fn orders(&self) -> Vec<OrderArgs> {
+        self.legs
+            .iter()
+            .map(|leg| OrderArgs::new(leg.token_id.clone(), leg.price, self.size, Side::BUY))
+            .collect()
+    }
+
+    /// Post every leg in one batch, cancelling placed legs if any fail.
+    ///
+    /// Legs default to FOK so a leg either fills at once or is rejected.
+    pub async fn execute(
+        &self,
+        client: &ClobClient,
+        post_options: Option<&PostOrderOptions>,
+    ) -> Result<BasketExecution> {
+        let post_options = post_options.copied().unwrap_or(PostOrderOptions {
+            order_type: OrderType::FOK,
+            ..PostOrderOptions::default()
+        });
+        let create_options = CreateOrderOptions {
+            tick_size: None,
+            neg_risk: Some(true),
+        };
+        let responses = client
+            .create_and_post_orders(&self.orders(), Some(&create_options), Some(&post_options))
+            .await?;
+
+        let mut execution = BasketExecution {
+            responses,
+            cancelled: Vec::new(),
+            stranded: Vec::new(),
+        };
+        if execution.is_complete() {
+            return Ok(execution);
+        }
+
+        let placed: Vec<String> = execution
+            .responses
+            .iter()
+            .filter(|response| response.success && !response.order_id.is_empty())
+            .map(|response| response.order_id.clone())
+            .collect();
+        if placed.is_empty() {
+            return Ok(execution);
+        }
+        match client.cancel_orders(&placed).await {
+            Ok(cancelled) => {
+                execution.stranded = placed
+                    .into_iter()
+                    .filter(|id| !cancelled.canceled.contains(id))
+                    .collect();
+                execution.cancelled = cancelled.canceled;
+            },
+            Err(e) => {
+                warn!("Failed to roll back basket legs: {}", e);
+                execution.stranded = placed;
+            },
+        }
+        Ok(execution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_baskets_balance_to_thinnest_leg() {
+        let outcomes = vec![
+            BasketLeg::new("1".parse().unwrap(), dec!(0.50), dec!(100)),
+            BasketLeg::new("2".parse().unwrap(), dec!(0.30), dec!(40.567)),
+            BasketLeg::new("3".parse().unwrap(), dec!(0.15), dec!(200)),
+        ];
+
+        let not_first = NegRiskBasket::not_outcome(&outcomes, "1", dec!(50)).unwrap();
+        assert_eq!(not_first.legs.len(), 2);
+        assert_eq!(not_first.size, dec!(40.56));
+        assert_eq!(not_first.cost(), dec!(18.2520));
+        let orders = not_first.orders();
+        assert_eq!(orders[0].token_id.as_str(), "2");
+        assert!(orders.iter().all(|order| order.size == dec!(40.56)));
+        assert!(NegRiskBasket::not_outcome(&outcomes, "9", dec!(50)).is_err());
+
+        let arb = NegRiskBasket::arbitrage(&outcomes, dec!(10), dec!(0.02))
+            .unwrap()
+            .unwrap();
+        assert_eq!(arb.size, dec!(10));
+        assert_eq!(arb.payout() - arb.cost(), dec!(0.5));
+        assert!(NegRiskBasket::arbitrage(&outcomes, dec!(10), dec!(0.1))
+            .unwrap()
+            .is_none());
+    }
+}
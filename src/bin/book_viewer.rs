@@ -0,0 +1,199 @@
+//! Live order book viewer for the terminal
+//!
+//! Subscribes to the market channel for the given tokens, keeps their books
+//! in an [`OrderBookManager`] fed by [`ShardedIngest`], and redraws a price
+//! ladder for each one along with connection health: message counts, time
+//! since the last message, disconnects and reconnects. Useful for telling a
+//! quiet market from a dead connection.
+//!
+//! ```text
+//! cargo run --features book-viewer --bin book_viewer -- <token_id>...
+//! ```
+//!
+//! `POLYMARKET_CLOB_URL` and `POLYMARKET_WS_URL` override the REST and
+//! WebSocket hosts; `BOOK_VIEWER_DEPTH` sets the levels shown per side.
+
+use futures::StreamExt;
+use polyfill_rs::{
+    BookView, ClobClient, OrderBookManager, ReconnectConfig, Result, ShardedIngest, TradeTape,
+    WebSocketStream, WsEndpoint, DEFAULT_BASE_URL, DEFAULT_WS_BASE_URL,
+};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const REDRAW_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default)]
+struct Health {
+    messages: AtomicU64,
+    errors: AtomicU64,
+    disconnects: AtomicU64,
+    reconnects: AtomicU64,
+    /// Milliseconds since the epoch
+    last_message_ms: AtomicU64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let token_ids: Vec<String> = std::env::args().skip(1).collect();
+    if token_ids.is_empty() {
+        eprintln!("usage: book_viewer <token_id>...");
+        std::process::exit(2);
+    }
+    let rest_url =
+        std::env::var("POLYMARKET_CLOB_URL").unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+    let ws_url =
+        std::env::var("POLYMARKET_WS_URL").unwrap_or_else(|_| DEFAULT_WS_BASE_URL.to_string());
+    let depth = std::env::var("BOOK_VIEWER_DEPTH")
+        .ok()
+        .and_then(|depth| depth.parse().ok())
+        .unwrap_or(10);
+
+    let books = Arc::new(OrderBookManager::new(100));
+    for token_id in &token_ids {
+        books.begin_warmup(token_id);
+    }
+
+    let health = Arc::new(Health::default());
+    let mut stream = WebSocketStream::new(&WsEndpoint::new(&ws_url).market_url())
+        .with_reconnect_config(ReconnectConfig::default());
+    {
+        let health = health.clone();
+        stream.on_disconnect(move |_| {
+            health.disconnects.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+    {
+        let health = health.clone();
+        stream.on_reconnect(move |_| {
+            health.reconnects.fetch_add(1, Ordering::Relaxed);
+        });
+    }
+    stream.subscribe_market_channel(token_ids.clone()).await?;
+
+    // Seed from REST only after subscribing, so no update falls in between
+    let client = ClobClient::new(&rest_url);
+    for summary in client.get_order_books(&token_ids).await? {
+        books.seed_snapshot(&summary)?;
+    }
+    for token_id in &token_ids {
+        books.end_warmup(token_id)?;
+    }
+
+    let tape = Arc::new(parking_lot::Mutex::new(TradeTape::new(5)));
+    let ingest = ShardedIngest::spawn(books.clone(), polyfill_rs::ingest::DEFAULT_QUEUE_CAPACITY);
+    let feed = {
+        let health = health.clone();
+        let tape = tape.clone();
+        stream.inspect(move |message| {
+            health.messages.fetch_add(1, Ordering::Relaxed);
+            health.last_message_ms.store(
+                chrono::Utc::now().timestamp_millis() as u64,
+                Ordering::Relaxed,
+            );
+            match message {
+                Ok(message) => tape.lock().observe(message),
+                Err(_) => {
+                    health.errors.fetch_add(1, Ordering::Relaxed);
+                },
+            }
+        })
+    };
+    let mut feed = std::pin::pin!(ingest.run(feed));
+
+    let mut ctrl_c = std::pin::pin!(tokio::signal::ctrl_c());
+    let mut ticker = tokio::time::interval(REDRAW_INTERVAL);
+    loop {
+        tokio::select! {
+            result = &mut feed => {
+                result?;
+                println!("Market channel closed");
+                return Ok(());
+            },
+            _ = &mut ctrl_c => return Ok(()),
+            _ = ticker.tick() => {
+                let views = token_ids
+                    .iter()
+                    .filter_map(|token_id| {
+                        let book = books.get_book(token_id).ok()?;
+                        let trades = tape.lock().trades(token_id);
+                        Some(BookView::from_snapshot(&book, Some(depth)).with_trades(trades))
+                    })
+                    .collect::<Vec<_>>();
+                draw(&views, &health, ingest.stats().failed);
+            },
+        }
+    }
+}
+
+fn draw(views: &[BookView], health: &Health, failed: u64) {
+    let mut screen = String::new();
+    // Clear the screen and move the cursor home
+    screen.push_str("\x1b[2J\x1b[H");
+
+    let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+    let last = health.last_message_ms.load(Ordering::Relaxed);
+    let silence = if last == 0 {
+        "never".to_string()
+    } else {
+        format!("{:.1}s ago", now_ms.saturating_sub(last) as f64 / 1000.0)
+    };
+    let _ = writeln!(
+        screen,
+        "messages {}  errors {}  rejected {}  disconnects {}  reconnects {}  last message {}",
+        health.messages.load(Ordering::Relaxed),
+        health.errors.load(Ordering::Relaxed),
+        failed,
+        health.disconnects.load(Ordering::Relaxed),
+        health.reconnects.load(Ordering::Relaxed),
+        silence,
+    );
+
+    for view in views {
+        let _ = writeln!(screen, "\n{}  updated {}", view.token_id, view.timestamp);
+        let _ = writeln!(
+            screen,
+            "{:>12} {:>12} {:>8} | {:<8} {:<12} {:<12}",
+            "cum", "size", "bid", "ask", "size", "cum"
+        );
+        for row in 0..view.bids.len().max(view.asks.len()) {
+            let bid = view.bids.get(row);
+            let ask = view.asks.get(row);
+            let _ = writeln!(
+                screen,
+                "{:>12} {:>12} {:>8} | {:<8} {:<12} {:<12}",
+                cell(bid.map(|level| level.cumulative_size)),
+                cell(bid.map(|level| level.size)),
+                cell(bid.map(|level| level.price)),
+                cell(ask.map(|level| level.price)),
+                cell(ask.map(|level| level.size)),
+                cell(ask.map(|level| level.cumulative_size)),
+            );
+        }
+        if let (Some(mid), Some(spread)) = (view.mid, view.spread) {
+            let _ = writeln!(screen, "mid {mid}  spread {spread}");
+        }
+        let trades: Vec<String> = view
+            .trades
+            .iter()
+            .map(|trade| match trade.size {
+                Some(size) => format!("{}@{}", size, trade.price),
+                None => trade.price.to_string(),
+            })
+            .collect();
+        if !trades.is_empty() {
+            let _ = writeln!(screen, "trades {}", trades.join("  "));
+        }
+    }
+
+    let mut stdout = std::io::stdout().lock();
+    let _ = stdout.write_all(screen.as_bytes());
+    let _ = stdout.flush();
+}
+
+fn cell(value: Option<rust_decimal::Decimal>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
@@ -1,12 +1,13 @@
 //! Order book management for Polymarket client
 
 use crate::errors::{PolyfillError, Result};
+use crate::fixed::{Ticks, Units};
+use crate::intern::{TokenKey, TokenMap};
 use crate::types::*;
 use crate::utils::math;
 use chrono::Utc;
 use parking_lot::RwLock;
 use rust_decimal::Decimal;
-use std::collections::HashMap;
 use std::sync::Arc; // For shared access across multiple tasks
 use tracing::{debug, trace, warn}; // Logging for debugging and monitoring
 
@@ -14,6 +15,8 @@ use tracing::{debug, trace, warn}; // Logging for debugging and monitoring
 struct StoredLevel {
     qty: Qty,
     generation: u64,
+    /// When the size at this price last changed, in milliseconds
+    updated_ms: i64,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,6 +26,14 @@ pub(crate) struct ParsedBookLevel {
     pub size_units: Qty,
 }
 
+/// WebSocket snapshot held back while a book waits for its REST seed
+#[derive(Debug, Clone)]
+struct BufferedSnapshot {
+    timestamp: u64,
+    hash: Option<String>,
+    levels: Vec<ParsedBookLevel>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum BookSideKind {
     Bid,
@@ -81,7 +92,17 @@ impl BookSide {
                 self.levels.remove(idx);
             },
             Ok(idx) => {
-                self.levels[idx].1 = level;
+                let existing = &mut self.levels[idx].1;
+                // An unchanged size keeps its age, e.g. across full snapshots
+                let updated_ms = if existing.qty == level.qty {
+                    existing.updated_ms
+                } else {
+                    level.updated_ms
+                };
+                *existing = StoredLevel {
+                    updated_ms,
+                    ..level
+                };
             },
             Err(idx) if level.qty != 0 => {
                 self.levels.insert(idx, (price, level));
@@ -160,7 +181,7 @@ const DEFAULT_BOOK_SHARDS: usize = 64;
 #[derive(Debug, Clone)]
 pub struct OrderBook {
     /// Token ID this book represents (like "123456" for a specific prediction market outcome)
-    pub token_id: String,
+    pub token_id: TokenKey,
 
     /// Hash of token_id for fast lookups (avoids string comparisons in hot path)
     pub token_id_hash: u64,
@@ -230,20 +251,19 @@ pub struct OrderBook {
     ///
     /// Typical values: 10-50 for retail, 100-500 for institutional HFT systems
     max_depth: usize,
+
+    /// WebSocket snapshots received since [`Self::begin_warmup`], replayed by
+    /// [`Self::seed_snapshot`]; `None` when not warming up
+    warmup: Option<Vec<BufferedSnapshot>>,
 }
 
 impl OrderBook {
     /// Create a new order book
     /// Just sets up empty bid/ask maps and basic metadata
-    pub fn new(token_id: String, max_depth: usize) -> Self {
-        // Hash the token_id once for fast lookups later
-        let token_id_hash = {
-            use std::collections::hash_map::DefaultHasher;
-            use std::hash::{Hash, Hasher};
-            let mut hasher = DefaultHasher::new();
-            token_id.hash(&mut hasher);
-            hasher.finish()
-        };
+    pub fn new(token_id: impl Into<TokenKey>, max_depth: usize) -> Self {
+        // Interning hashes the token_id once for fast lookups later
+        let token_id = token_id.into();
+        let token_id_hash = token_id.hash_u64();
 
         Self {
             token_id,
@@ -258,6 +278,7 @@ impl OrderBook {
             last_snapshot_hash_fingerprint: None,
             tick_size_ticks: None, // We'll set this later when we learn about the market
             max_depth,
+            warmup: None,
         }
     }
 
@@ -277,6 +298,11 @@ impl OrderBook {
         self.tick_size_ticks = Some(tick_size_ticks);
     }
 
+    /// Maximum number of levels kept per side
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
     /// Get the current best bid (highest price someone is willing to pay)
     /// Bids are stored highest price first.
     ///
@@ -365,6 +391,26 @@ impl OrderBook {
         Some(price_to_decimal(mid_ticks))
     }
 
+    /// Which sides of the book have liquidity. `spread` and `mid_price` are
+    /// `None` unless this is [`BookSides::TwoSided`].
+    pub fn sides(&self) -> BookSides {
+        match (self.bids.best().is_some(), self.asks.best().is_some()) {
+            (true, true) => BookSides::TwoSided,
+            (true, false) => BookSides::BidOnly,
+            (false, true) => BookSides::AskOnly,
+            (false, false) => BookSides::Empty,
+        }
+    }
+
+    /// Mark price by the configured method; see [`crate::mark`]
+    pub fn mark_price(
+        &self,
+        mark: &crate::mark::MarkPrice,
+        last_trade: Option<Decimal>,
+    ) -> Option<Decimal> {
+        mark.mark(self, last_trade)
+    }
+
     /// Get the spread as a percentage (relative to the bid price)
     /// Useful for comparing spreads across different price levels
     ///
@@ -455,7 +501,7 @@ impl OrderBook {
     /// without worrying about the original book changing
     pub fn snapshot(&self) -> crate::types::OrderBook {
         crate::types::OrderBook {
-            token_id: self.token_id.clone(),
+            token_id: self.token_id.to_string(),
             timestamp: self.timestamp,
             bids: self.bids(None), // Get all bids (up to max_depth)
             asks: self.asks(None), // Get all asks (up to max_depth)
@@ -532,9 +578,10 @@ impl OrderBook {
         self.timestamp = delta.timestamp;
 
         // Apply the actual change to the appropriate side (FAST VERSION)
+        let updated_ms = delta.timestamp.timestamp_millis();
         match delta.side {
-            Side::BUY => self.apply_bid_delta_fast(delta.price, delta.size),
-            Side::SELL => self.apply_ask_delta_fast(delta.price, delta.size),
+            Side::BUY => self.apply_bid_delta_fast(delta.price, delta.size, updated_ms),
+            Side::SELL => self.apply_ask_delta_fast(delta.price, delta.size, updated_ms),
         }
 
         // Keep the book from getting too deep (memory management)
@@ -558,7 +605,7 @@ impl OrderBook {
         timestamp: u64,
         hash: Option<&str>,
     ) -> Result<bool> {
-        if asset_id != self.token_id {
+        if self.token_id != asset_id {
             return Err(PolyfillError::validation("Token ID mismatch"));
         }
 
@@ -580,6 +627,14 @@ impl OrderBook {
         if !self.should_apply_ws_book_update(asset_id, timestamp, hash)? {
             return Ok(false);
         }
+        if let Some(buffer) = &mut self.warmup {
+            buffer.push(BufferedSnapshot {
+                timestamp,
+                hash: hash.map(str::to_owned),
+                levels: levels.to_vec(),
+            });
+            return Ok(false);
+        }
 
         self.apply_validated_snapshot(timestamp, hash, levels)?;
 
@@ -597,7 +652,7 @@ impl OrderBook {
     /// - Levels omitted from the message are removed.
     /// - Insertions of *new* price levels may allocate or shift vector entries.
     pub fn apply_book_update(&mut self, update: &BookUpdate) -> Result<()> {
-        if update.asset_id != self.token_id {
+        if self.token_id != update.asset_id {
             return Err(PolyfillError::validation("Token ID mismatch"));
         }
 
@@ -622,6 +677,18 @@ impl OrderBook {
             self.validate_snapshot_level(parsed)?;
         }
 
+        if self.warmup.is_some() {
+            let levels = self.parse_snapshot_levels(&update.bids, &update.asks)?;
+            if let Some(buffer) = &mut self.warmup {
+                buffer.push(BufferedSnapshot {
+                    timestamp: update.timestamp,
+                    hash: update.hash.clone(),
+                    levels,
+                });
+            }
+            return Ok(());
+        }
+
         self.last_snapshot_timestamp_ms = update.timestamp;
         self.last_snapshot_hash_fingerprint = update.hash.as_deref().map(snapshot_hash_fingerprint);
         self.timestamp = chrono::DateTime::<Utc>::from_timestamp_millis(update.timestamp as i64)
@@ -635,14 +702,14 @@ impl OrderBook {
             let parsed = self
                 .parse_snapshot_summary(Side::BUY, level)
                 .expect("book update bid level was validated before mutation");
-            self.apply_snapshot_level(parsed.side, parsed.price_ticks, parsed.size_units);
+            self.apply_snapshot_level(parsed, update.timestamp as i64);
         }
 
         for level in &update.asks {
             let parsed = self
                 .parse_snapshot_summary(Side::SELL, level)
                 .expect("book update ask level was validated before mutation");
-            self.apply_snapshot_level(parsed.side, parsed.price_ticks, parsed.size_units);
+            self.apply_snapshot_level(parsed, update.timestamp as i64);
         }
 
         self.finish_snapshot();
@@ -650,6 +717,123 @@ impl OrderBook {
         Ok(())
     }
 
+    /// Start buffering WebSocket `book` snapshots instead of applying them.
+    ///
+    /// Call this before subscribing, then fetch the REST book and hand it to
+    /// [`Self::seed_snapshot`]. Updates that race the REST request are kept
+    /// rather than lost or overwritten by an older snapshot.
+    pub fn begin_warmup(&mut self) {
+        self.warmup.get_or_insert_with(Vec::new);
+    }
+
+    /// Whether WebSocket snapshots are being buffered for a REST seed
+    pub fn is_warming_up(&self) -> bool {
+        self.warmup.is_some()
+    }
+
+    /// Apply a REST snapshot, then replay the WebSocket snapshots buffered
+    /// since [`Self::begin_warmup`] that are newer than it.
+    ///
+    /// Snapshots are ordered by timestamp; on a tie a buffered snapshot with a
+    /// different hash wins, since it arrived after the REST request was sent.
+    /// Also adopts the snapshot's tick size. Returns the number of buffered
+    /// snapshots applied. A malformed snapshot is rejected with the book and
+    /// buffer untouched, so the seed can be retried.
+    pub fn seed_snapshot(&mut self, summary: &OrderBookSummary) -> Result<usize> {
+        if self.token_id != summary.asset_id {
+            return Err(PolyfillError::validation("Token ID mismatch"));
+        }
+        let tick_size_ticks = decimal_to_price_exact(summary.tick_size)
+            .map_err(|_| PolyfillError::validation("Invalid tick size"))?;
+        let levels = self.parse_snapshot_levels(&summary.bids, &summary.asks)?;
+        if tick_size_ticks > 0
+            && levels
+                .iter()
+                .any(|l| !l.price_ticks.is_multiple_of(tick_size_ticks))
+        {
+            return Err(PolyfillError::validation("Price not aligned to tick size"));
+        }
+
+        self.tick_size_ticks = Some(tick_size_ticks);
+        if self.should_apply_snapshot(summary.timestamp, summary.hash.as_deref()) {
+            self.apply_validated_snapshot(summary.timestamp, summary.hash.as_deref(), &levels)?;
+        }
+        Ok(self.end_warmup())
+    }
+
+    /// The fewest deltas that turn this book's levels into `other`'s, bids
+    /// then asks, best price first.
+    ///
+    /// A level missing from `other` becomes a zero-size delta. Sequences
+    /// continue from [`Self::last_delta_sequence`], so applying the deltas
+    /// to this book in order reproduces `other`.
+    pub fn diff(&self, other: &OrderBook) -> Vec<OrderDelta> {
+        let mut deltas = Vec::new();
+        for (side, ours, theirs) in [
+            (Side::BUY, &self.bids, &other.bids),
+            (Side::SELL, &self.asks, &other.asks),
+        ] {
+            let mut levels: std::collections::BTreeMap<Price, (Qty, Qty)> = Default::default();
+            for (price, level) in &ours.levels {
+                levels.entry(*price).or_default().0 = level.qty;
+            }
+            for (price, level) in &theirs.levels {
+                levels.entry(*price).or_default().1 = level.qty;
+            }
+            let mut changed: Vec<(Price, Qty)> = levels
+                .into_iter()
+                .filter(|(_, (ours, theirs))| ours != theirs)
+                .map(|(price, (_, theirs))| (price, theirs))
+                .collect();
+            if side == Side::BUY {
+                changed.reverse();
+            }
+            for (price, qty) in changed {
+                deltas.push(OrderDelta {
+                    token_id: self.token_id.to_string(),
+                    timestamp: other.timestamp,
+                    side,
+                    price: price_to_decimal(price),
+                    size: qty_to_decimal(qty),
+                    sequence: self.last_delta_sequence + deltas.len() as u64 + 1,
+                });
+            }
+        }
+        deltas
+    }
+
+    /// Stop buffering and apply the buffered snapshots without a REST seed,
+    /// e.g. after the REST request failed. Returns the number applied.
+    pub fn end_warmup(&mut self) -> usize {
+        let mut applied = 0;
+        for snapshot in self.warmup.take().unwrap_or_default() {
+            if !self.should_apply_snapshot(snapshot.timestamp, snapshot.hash.as_deref()) {
+                continue;
+            }
+            match self.apply_validated_snapshot(
+                snapshot.timestamp,
+                snapshot.hash.as_deref(),
+                &snapshot.levels,
+            ) {
+                Ok(()) => applied += 1,
+                Err(e) => warn!("Dropping buffered snapshot for {}: {}", self.token_id, e),
+            }
+        }
+        applied
+    }
+
+    fn parse_snapshot_levels(
+        &self,
+        bids: &[OrderSummary],
+        asks: &[OrderSummary],
+    ) -> Result<Vec<ParsedBookLevel>> {
+        let bids = bids.iter().map(|level| (Side::BUY, level));
+        let asks = asks.iter().map(|level| (Side::SELL, level));
+        bids.chain(asks)
+            .map(|(side, level)| self.parse_snapshot_summary(side, level))
+            .collect()
+    }
+
     /// Apply a bid-side delta (someone wants to buy) - LEGACY VERSION
     /// If size is 0, it means "remove this price level entirely"
     /// Otherwise, set the total size at this price level
@@ -660,7 +844,7 @@ impl OrderBook {
         // Convert to fixed-point (this should be rare since we use fast path)
         let price_ticks = decimal_to_price_lossy(price).unwrap_or(0);
         let size_units = decimal_to_qty(size).unwrap_or(0);
-        self.apply_bid_delta_fast(price_ticks, size_units);
+        self.apply_bid_delta_fast(price_ticks, size_units, self.timestamp.timestamp_millis());
     }
 
     /// Apply an ask-side delta (someone wants to sell) - LEGACY VERSION
@@ -672,14 +856,14 @@ impl OrderBook {
         // Convert to fixed-point (this should be rare since we use fast path)
         let price_ticks = decimal_to_price_lossy(price).unwrap_or(0);
         let size_units = decimal_to_qty(size).unwrap_or(0);
-        self.apply_ask_delta_fast(price_ticks, size_units);
+        self.apply_ask_delta_fast(price_ticks, size_units, self.timestamp.timestamp_millis());
     }
 
     /// Apply a bid-side delta (someone wants to buy) - FAST VERSION
     ///
     /// This is the high-performance version that works directly with fixed-point.
     /// Much faster than the Decimal version - pure integer operations.
-    fn apply_bid_delta_fast(&mut self, price_ticks: Price, size_units: Qty) {
+    fn apply_bid_delta_fast(&mut self, price_ticks: Price, size_units: Qty, updated_ms: i64) {
         // BEFORE (slow, ~100ns + allocation):
         // if size.is_zero() {
         //     self.bids.remove(&price);
@@ -693,6 +877,7 @@ impl OrderBook {
             StoredLevel {
                 qty: size_units,
                 generation: self.snapshot_generation,
+                updated_ms,
             },
         );
     }
@@ -701,7 +886,7 @@ impl OrderBook {
     ///
     /// This is the high-performance version that works directly with fixed-point.
     /// Much faster than the Decimal version - pure integer operations.
-    fn apply_ask_delta_fast(&mut self, price_ticks: Price, size_units: Qty) {
+    fn apply_ask_delta_fast(&mut self, price_ticks: Price, size_units: Qty, updated_ms: i64) {
         // BEFORE (slow, ~100ns + allocation):
         // if size.is_zero() {
         //     self.asks.remove(&price);
@@ -715,6 +900,7 @@ impl OrderBook {
             StoredLevel {
                 qty: size_units,
                 generation: self.snapshot_generation,
+                updated_ms,
             },
         );
     }
@@ -782,7 +968,7 @@ impl OrderBook {
         self.begin_snapshot();
 
         for &level in levels {
-            self.apply_snapshot_level(level.side, level.price_ticks, level.size_units);
+            self.apply_snapshot_level(level, timestamp as i64);
         }
 
         self.finish_snapshot();
@@ -792,18 +978,19 @@ impl OrderBook {
     }
 
     #[inline]
-    fn apply_snapshot_level(&mut self, side: Side, price_ticks: Price, size_units: Qty) {
+    fn apply_snapshot_level(&mut self, level: ParsedBookLevel, updated_ms: i64) {
         let generation = self.snapshot_generation;
-        let book_side = match side {
+        let book_side = match level.side {
             Side::BUY => &mut self.bids,
             Side::SELL => &mut self.asks,
         };
 
         book_side.upsert(
-            price_ticks,
+            level.price_ticks,
             StoredLevel {
-                qty: size_units,
+                qty: level.size_units,
                 generation,
+                updated_ms,
             },
         );
     }
@@ -868,6 +1055,23 @@ impl OrderBook {
         })
     }
 
+    /// Estimate executing a market order of `size` against this book.
+    ///
+    /// Only the levels retained under `max_depth` are considered, so deep
+    /// orders may report an unfilled remainder the exchange could still fill.
+    pub fn estimate_execution(&self, side: Side, size: Decimal) -> ExecutionEstimate {
+        let levels = match side {
+            Side::BUY => self.asks(None),
+            Side::SELL => self.bids(None),
+        };
+        ExecutionEstimate::from_levels(
+            &self.token_id,
+            side,
+            size,
+            levels.into_iter().map(|level| (level.price, level.size)),
+        )
+    }
+
     /// Check if the book is stale (no recent updates)
     /// Useful for detecting when we've lost connection to live data
     pub fn is_stale(&self, max_age: std::time::Duration) -> bool {
@@ -875,6 +1079,39 @@ impl OrderBook {
         age > chrono::Duration::from_std(max_age).unwrap_or_default()
     }
 
+    /// When the size resting at `price` on `side` (BUY for bids) last changed
+    pub fn level_updated_at(&self, side: Side, price: Decimal) -> Option<chrono::DateTime<Utc>> {
+        let price_ticks = decimal_to_price_exact(price).ok()?;
+        let book_side = match side {
+            Side::BUY => &self.bids,
+            Side::SELL => &self.asks,
+        };
+        book_side
+            .get(price_ticks)
+            .and_then(|level| chrono::DateTime::from_timestamp_millis(level.updated_ms))
+    }
+
+    /// Levels on `side` (BUY for bids), best first, with when each last changed
+    ///
+    /// Both sides together form a staleness heatmap of the book: deep levels
+    /// that have not moved in a long time are often forgotten orders.
+    pub fn level_ages(&self, side: Side, depth: Option<usize>) -> Vec<LevelAge> {
+        let depth = depth.unwrap_or(self.max_depth);
+        let book_side = match side {
+            Side::BUY => &self.bids,
+            Side::SELL => &self.asks,
+        };
+        book_side
+            .iter_top(depth)
+            .map(|(price_ticks, level)| LevelAge {
+                price: price_to_decimal(price_ticks),
+                size: qty_to_decimal(level.qty),
+                updated_at: chrono::DateTime::from_timestamp_millis(level.updated_ms)
+                    .unwrap_or(self.timestamp),
+            })
+            .collect()
+    }
+
     /// Get the total liquidity at a given price level
     /// Tells you how much you can buy/sell at exactly this price
     pub fn liquidity_at_price(&self, price: Decimal, side: Side) -> Decimal {
@@ -939,6 +1176,104 @@ impl OrderBook {
             .map(|(best_bid_ticks, best_ask_ticks)| best_bid_ticks < best_ask_ticks)
             .unwrap_or(true)
     }
+
+    /// Borrow the book as a [`FastBookView`] for integer-only reads
+    pub fn fast_view(&self) -> FastBookView<'_> {
+        FastBookView { book: self }
+    }
+}
+
+/// Read-only view of an [`OrderBook`] in [`Ticks`] and [`Units`].
+///
+/// Every method works on the stored fixed-point levels directly: nothing
+/// allocates and nothing converts to `Decimal`. Obtain one from
+/// [`OrderBook::fast_view`] or [`OrderBookManager::with_fast_view`].
+#[derive(Debug, Clone, Copy)]
+pub struct FastBookView<'a> {
+    book: &'a OrderBook,
+}
+
+impl<'a> FastBookView<'a> {
+    pub fn token_id(&self) -> &'a str {
+        self.book.token_id.as_str()
+    }
+
+    pub fn tick_size(&self) -> Option<Ticks> {
+        self.book.tick_size_ticks.map(Ticks)
+    }
+
+    pub fn best_bid(&self) -> Option<(Ticks, Units)> {
+        self.book
+            .bids
+            .best()
+            .map(|(price, level)| (Ticks(price), Units(level.qty)))
+    }
+
+    pub fn best_ask(&self) -> Option<(Ticks, Units)> {
+        self.book
+            .asks
+            .best()
+            .map(|(price, level)| (Ticks(price), Units(level.qty)))
+    }
+
+    pub fn spread(&self) -> Option<Ticks> {
+        self.book.spread_fast().map(Ticks)
+    }
+
+    pub fn mid(&self) -> Option<Ticks> {
+        self.book.mid_price_fast().map(Ticks)
+    }
+
+    /// Bid levels, highest price first
+    pub fn bids(&self) -> impl ExactSizeIterator<Item = (Ticks, Units)> + 'a {
+        self.book
+            .bids
+            .levels
+            .iter()
+            .map(|(price, level)| (Ticks(*price), Units(level.qty)))
+    }
+
+    /// Ask levels, lowest price first
+    pub fn asks(&self) -> impl ExactSizeIterator<Item = (Ticks, Units)> + 'a {
+        self.book
+            .asks
+            .levels
+            .iter()
+            .map(|(price, level)| (Ticks(*price), Units(level.qty)))
+    }
+
+    /// Resting size at `price` on the bid (`BUY`) or ask (`SELL`) side
+    pub fn size_at(&self, side: Side, price: Ticks) -> Units {
+        let levels = match side {
+            Side::BUY => &self.book.bids,
+            Side::SELL => &self.book.asks,
+        };
+        Units(levels.get(price.0).map_or(0, |level| level.qty))
+    }
+
+    /// Size a taker on `side` can fill at `limit` or better
+    pub fn liquidity_to(&self, side: Side, limit: Ticks) -> Units {
+        match side {
+            Side::BUY => Units(self.book.asks.sum_range(0, limit.0)),
+            Side::SELL => Units(self.book.bids.sum_range(limit.0, Price::MAX)),
+        }
+    }
+
+    /// Average price for a taker on `side` filling `size`, rounded against
+    /// the taker; `None` if the book is too thin
+    pub fn fill_price(&self, side: Side, size: Units) -> Option<Ticks> {
+        let levels = match side {
+            Side::BUY => self.book.asks.iter_all(),
+            Side::SELL => self.book.bids.iter_all(),
+        };
+        let (filled, notional, _) = fill_market_impact(levels, size.0)?;
+        let filled = filled as i128;
+        let average = match side {
+            Side::BUY => (notional + filled - 1) / filled,
+            Side::SELL => notional / filled,
+        };
+        Price::try_from(average).ok().map(Ticks)
+    }
 }
 
 fn fill_market_impact<'a>(
@@ -991,6 +1326,157 @@ pub struct MarketImpact {
     pub size_filled: Decimal,   // How much of your order got filled
 }
 
+/// Which sides of a book have liquidity
+///
+/// Books near resolution often lose one side entirely; anything derived from
+/// both touches is unavailable until it comes back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BookSides {
+    TwoSided,
+    BidOnly,
+    AskOnly,
+    Empty,
+}
+
+impl BookSides {
+    pub fn is_one_sided(&self) -> bool {
+        matches!(self, Self::BidOnly | Self::AskOnly)
+    }
+}
+
+/// A price computed from a local book, stamped with the book's last update
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalQuote {
+    pub value: Decimal,
+    /// When the book last changed
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+impl LocalQuote {
+    /// Time since the book last changed
+    pub fn age(&self) -> std::time::Duration {
+        (Utc::now() - self.updated_at).to_std().unwrap_or_default()
+    }
+
+    pub fn is_stale(&self, max_age: std::time::Duration) -> bool {
+        self.age() > max_age
+    }
+}
+
+/// A book level stamped with when its size last changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LevelAge {
+    pub price: Decimal,
+    pub size: Decimal,
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+impl LevelAge {
+    /// Age of the level as of `now`
+    pub fn age_at(&self, now: chrono::DateTime<Utc>) -> std::time::Duration {
+        (now - self.updated_at).to_std().unwrap_or_default()
+    }
+
+    pub fn age(&self) -> std::time::Duration {
+        self.age_at(Utc::now())
+    }
+}
+
+/// Expected outcome of executing an order against current depth
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionEstimate {
+    pub token_id: String,
+    pub side: Side,
+    pub requested_size: Decimal,
+    pub filled_size: Decimal,
+    /// Size the visible book cannot absorb
+    pub unfilled_size: Decimal,
+    /// Total paid (buys) or received (sells) for the filled size
+    pub notional: Decimal,
+    /// Size-weighted fill price; `None` if nothing fills
+    pub average_price: Option<Decimal>,
+    /// Top-of-book price the estimate starts from
+    pub best_price: Option<Decimal>,
+    /// Deepest level touched
+    pub worst_price: Option<Decimal>,
+    /// Average price versus best price, in basis points
+    pub slippage_bps: Decimal,
+}
+
+impl ExecutionEstimate {
+    /// Walk `levels` (in any order) best-first until `size` is filled
+    pub fn from_levels(
+        token_id: &str,
+        side: Side,
+        size: Decimal,
+        levels: impl IntoIterator<Item = (Decimal, Decimal)>,
+    ) -> Self {
+        let mut levels: Vec<_> = levels
+            .into_iter()
+            .filter(|(_, s)| *s > Decimal::ZERO)
+            .collect();
+        match side {
+            Side::BUY => levels.sort_by_key(|&(price, _)| price),
+            Side::SELL => levels.sort_by_key(|&(price, _)| std::cmp::Reverse(price)),
+        }
+
+        let mut remaining = size;
+        let mut notional = Decimal::ZERO;
+        let mut worst_price = None;
+        for &(price, level_size) in &levels {
+            if remaining <= Decimal::ZERO {
+                break;
+            }
+            let fill = remaining.min(level_size);
+            notional += fill * price;
+            remaining -= fill;
+            worst_price = Some(price);
+        }
+
+        let filled_size = size - remaining;
+        let best_price = levels.first().map(|&(price, _)| price);
+        let average_price = (!filled_size.is_zero()).then(|| notional / filled_size);
+        let slippage_bps = match (best_price, average_price) {
+            (Some(best), Some(average)) if !best.is_zero() => {
+                math::calculate_slippage(best, average, side) * Decimal::from(10_000)
+            },
+            _ => Decimal::ZERO,
+        };
+
+        Self {
+            token_id: token_id.to_string(),
+            side,
+            requested_size: size,
+            filled_size,
+            unfilled_size: remaining,
+            notional,
+            average_price,
+            best_price,
+            worst_price,
+            slippage_bps,
+        }
+    }
+
+    /// Estimate against a REST book snapshot
+    pub fn from_summary(summary: &OrderBookSummary, side: Side, size: Decimal) -> Self {
+        let levels = match side {
+            Side::BUY => &summary.asks,
+            Side::SELL => &summary.bids,
+        };
+        Self::from_levels(
+            &summary.asset_id,
+            side,
+            size,
+            levels.iter().map(|level| (level.price, level.size)),
+        )
+    }
+
+    /// Whether the visible book can absorb the whole order
+    pub fn is_fully_filled(&self) -> bool {
+        self.unfilled_size.is_zero()
+    }
+}
+
 /// Thread-safe order book manager
 /// This manages multiple order books (one per token) and handles concurrent access
 /// Multiple threads can read/write different books simultaneously
@@ -1009,7 +1495,7 @@ pub struct OrderBookManager {
 
 #[derive(Debug, Default)]
 struct BookShard {
-    books: RwLock<HashMap<String, OrderBook>>,
+    books: RwLock<TokenMap<OrderBook>>,
 }
 
 #[inline]
@@ -1023,14 +1509,16 @@ fn snapshot_hash_fingerprint(hash: &str) -> u64 {
 }
 
 #[inline]
-fn shard_index(token_id: &str, shard_count: usize) -> usize {
+fn shard_index(token_id: &TokenKey, shard_count: usize) -> usize {
     debug_assert!(shard_count > 0);
-    let mut hash = 0xcbf2_9ce4_8422_2325u64;
-    for &byte in token_id.as_bytes() {
-        hash ^= byte as u64;
-        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
-    }
-    (hash as usize) % shard_count
+    (token_id.hash_u64() as usize) % shard_count
+}
+
+fn book_not_found(token_id: &str) -> PolyfillError {
+    PolyfillError::market_data(
+        format!("No book found for token: {}", token_id),
+        crate::errors::MarketDataErrorKind::TokenNotFound,
+    )
 }
 
 impl OrderBookManager {
@@ -1054,25 +1542,39 @@ impl OrderBookManager {
         Self { shards, max_depth }
     }
 
+    /// Number of independently locked shards
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Index of the shard holding `token_id`'s book
+    pub fn shard_of(&self, token_id: &TokenKey) -> usize {
+        shard_index(token_id, self.shards.len())
+    }
+
     #[inline]
-    fn shard_for(&self, token_id: &str) -> &BookShard {
+    fn shard_for(&self, token_id: &TokenKey) -> &BookShard {
         &self.shards[shard_index(token_id, self.shards.len())]
     }
 
+    /// Key of a token that may have a book; tokens never interned have none
+    #[inline]
+    fn existing_key(token_id: &str) -> Result<TokenKey> {
+        TokenKey::lookup(token_id).ok_or_else(|| book_not_found(token_id))
+    }
+
     /// Get or create an order book for a token
     /// If we don't have a book for this token yet, create a new empty one
     pub fn get_or_create_book(&self, token_id: &str) -> Result<OrderBook> {
-        let shard = self.shard_for(token_id);
+        let key = TokenKey::new(token_id);
+        let shard = self.shard_for(&key);
         let mut books = shard.books.write();
 
-        if let Some(book) = books.get(token_id) {
-            Ok(book.clone()) // Return a copy of the existing book
-        } else {
-            // Create a new book for this token
-            let book = OrderBook::new(token_id.to_string(), self.max_depth);
-            books.insert(token_id.to_string(), book.clone());
-            Ok(book)
-        }
+        let max_depth = self.max_depth;
+        Ok(books
+            .entry(key)
+            .or_insert_with_key(|key| OrderBook::new(key.clone(), max_depth))
+            .clone()) // Return a copy of the book
     }
 
     /// Execute a closure with mutable access to a managed book.
@@ -1083,33 +1585,70 @@ impl OrderBookManager {
         &self,
         token_id: &str,
         f: impl FnOnce(&mut OrderBook) -> Result<R>,
+    ) -> Result<R> {
+        self.with_book_mut_by_key(&Self::existing_key(token_id)?, f)
+    }
+
+    /// [`Self::with_book_mut`] for an already interned token
+    pub fn with_book_mut_by_key<R>(
+        &self,
+        token_id: &TokenKey,
+        f: impl FnOnce(&mut OrderBook) -> Result<R>,
     ) -> Result<R> {
         let shard = self.shard_for(token_id);
         let mut books = shard.books.write();
 
-        let book = books.get_mut(token_id).ok_or_else(|| {
-            PolyfillError::market_data(
-                format!("No book found for token: {}", token_id),
-                crate::errors::MarketDataErrorKind::TokenNotFound,
-            )
-        })?;
+        let book = books
+            .get_mut(token_id)
+            .ok_or_else(|| book_not_found(token_id))?;
 
         f(book)
     }
 
+    /// Execute a closure with shared access to a managed book.
+    ///
+    /// Takes only the shard's read lock, so readers such as analytics and
+    /// simulation do not block each other.
+    pub fn with_book<R>(&self, token_id: &str, f: impl FnOnce(&OrderBook) -> R) -> Result<R> {
+        self.with_book_by_key(&Self::existing_key(token_id)?, f)
+    }
+
+    /// Run `f` against a [`FastBookView`] of the book under the read lock
+    pub fn with_fast_view<R>(
+        &self,
+        token_id: &str,
+        f: impl FnOnce(FastBookView<'_>) -> R,
+    ) -> Result<R> {
+        self.with_book(token_id, |book| f(book.fast_view()))
+    }
+
+    /// [`Self::with_book`] for an already interned token
+    pub fn with_book_by_key<R>(
+        &self,
+        token_id: &TokenKey,
+        f: impl FnOnce(&OrderBook) -> R,
+    ) -> Result<R> {
+        let shard = self.shard_for(token_id);
+        let books = shard.books.read();
+
+        let book = books
+            .get(token_id)
+            .ok_or_else(|| book_not_found(token_id))?;
+
+        Ok(f(book))
+    }
+
     /// Update a book with a delta
     /// This is called when we receive real-time updates from the exchange
     pub fn apply_delta(&self, delta: OrderDelta) -> Result<()> {
-        let shard = self.shard_for(delta.token_id.as_str());
+        let key = Self::existing_key(&delta.token_id)?;
+        let shard = self.shard_for(&key);
         let mut books = shard.books.write();
 
         // Find the book for this token (must already exist)
-        let book = books.get_mut(&delta.token_id).ok_or_else(|| {
-            PolyfillError::market_data(
-                format!("No book found for token: {}", delta.token_id),
-                crate::errors::MarketDataErrorKind::TokenNotFound,
-            )
-        })?;
+        let book = books
+            .get_mut(&key)
+            .ok_or_else(|| book_not_found(&delta.token_id))?;
 
         // Apply the update to the specific book
         book.apply_delta(delta)
@@ -1120,35 +1659,65 @@ impl OrderBookManager {
     /// This is the preferred way to ingest `StreamMessage::Book` updates into
     /// the in-memory order books (avoids rebuilding snapshots via per-level deltas).
     pub fn apply_book_update(&self, update: &BookUpdate) -> Result<()> {
-        let shard = self.shard_for(update.asset_id.as_str());
+        let key = TokenKey::new(&update.asset_id);
+        let shard = self.shard_for(&key);
         let mut books = shard.books.write();
 
-        if !books.contains_key(update.asset_id.as_str()) {
-            let token_id = update.asset_id.clone();
-            books.insert(token_id.clone(), OrderBook::new(token_id, self.max_depth));
-        }
-
+        let max_depth = self.max_depth;
         books
-            .get_mut(update.asset_id.as_str())
-            .ok_or_else(|| PolyfillError::internal_simple("Failed to insert order book"))?
+            .entry(key)
+            .or_insert_with_key(|key| OrderBook::new(key.clone(), max_depth))
             .apply_book_update(update)
     }
 
+    /// Start buffering WebSocket snapshots for `token_id` until it is seeded,
+    /// creating the book if needed.
+    ///
+    /// The race-free warmup is: `begin_warmup`, subscribe, fetch the REST
+    /// book (e.g. [`crate::ClobClient::get_order_book`]), then
+    /// [`Self::seed_snapshot`]. See [`OrderBook::seed_snapshot`] for ordering.
+    pub fn begin_warmup(&self, token_id: &str) {
+        let key = TokenKey::new(token_id);
+        let shard = self.shard_for(&key);
+        let max_depth = self.max_depth;
+        shard
+            .books
+            .write()
+            .entry(key)
+            .or_insert_with_key(|key| OrderBook::new(key.clone(), max_depth))
+            .begin_warmup();
+    }
+
+    /// Seed a book from a REST snapshot and replay its buffered updates
+    /// under a single lock, so no update is applied out of order
+    pub fn seed_snapshot(&self, summary: &OrderBookSummary) -> Result<usize> {
+        self.with_book_mut(&summary.asset_id, |book| book.seed_snapshot(summary))
+    }
+
+    /// Deltas that would bring the local book in line with a REST snapshot;
+    /// empty when the two agree. See [`OrderBook::diff`].
+    pub fn diff_snapshot(&self, summary: &OrderBookSummary) -> Result<Vec<OrderDelta>> {
+        let mut remote = OrderBook::new(summary.asset_id.as_str(), self.max_depth);
+        remote.seed_snapshot(summary)?;
+        self.with_book(&summary.asset_id, |book| book.diff(&remote))
+    }
+
+    /// Give up on seeding `token_id` and apply its buffered updates
+    pub fn end_warmup(&self, token_id: &str) -> Result<usize> {
+        self.with_book_mut(token_id, |book| Ok(book.end_warmup()))
+    }
+
     /// Get a book snapshot
     /// Returns a copy of the current book state that won't change
     pub fn get_book(&self, token_id: &str) -> Result<crate::types::OrderBook> {
-        let shard = self.shard_for(token_id);
+        let key = Self::existing_key(token_id)?;
+        let shard = self.shard_for(&key);
         let books = shard.books.read();
 
         books
-            .get(token_id)
+            .get(&key)
             .map(|book| book.snapshot()) // Create a snapshot copy
-            .ok_or_else(|| {
-                PolyfillError::market_data(
-                    format!("No book found for token: {}", token_id),
-                    crate::errors::MarketDataErrorKind::TokenNotFound,
-                )
-            })
+            .ok_or_else(|| book_not_found(token_id))
     }
 
     /// Get all available books
@@ -1163,6 +1732,29 @@ impl OrderBookManager {
         Ok(snapshots)
     }
 
+    /// Tokens whose books are missing one or both sides
+    pub fn one_sided_books(&self) -> Vec<(String, BookSides)> {
+        let mut one_sided = Vec::new();
+        for shard in self.shards.iter() {
+            let books = shard.books.read();
+            one_sided.extend(
+                books
+                    .values()
+                    .map(|book| (book.token_id.to_string(), book.sides()))
+                    .filter(|(_, sides)| *sides != BookSides::TwoSided),
+            );
+        }
+        one_sided
+    }
+
+    /// Drop the book for `token_id`; returns whether there was one
+    pub fn remove_book(&self, token_id: &str) -> bool {
+        let Some(key) = TokenKey::lookup(token_id) else {
+            return false;
+        };
+        self.shard_for(&key).books.write().remove(&key).is_some()
+    }
+
     /// Remove stale books
     /// Cleans up books that haven't been updated recently (probably disconnected)
     /// This prevents memory leaks from accumulating dead books
@@ -1197,6 +1789,7 @@ pub struct BookAnalytics {
     pub spread: Option<Decimal>,     // Current spread (ask - bid)
     pub spread_pct: Option<Decimal>, // Spread as percentage
     pub mid_price: Option<Decimal>,  // Current mid price
+    pub sides: BookSides,            // Which sides have liquidity
     pub volatility: Option<Decimal>, // Price volatility (if calculated)
 }
 
@@ -1213,7 +1806,7 @@ impl OrderBook {
         let total_ask_size = qty_to_decimal(total_ask_size_units);
 
         BookAnalytics {
-            token_id: self.token_id.clone(),
+            token_id: self.token_id.to_string(),
             timestamp: self.timestamp,
             bid_count,
             ask_count,
@@ -1222,6 +1815,7 @@ impl OrderBook {
             spread: self.spread(),
             spread_pct: self.spread_pct(),
             mid_price: self.mid_price(),
+            sides: self.sides(),
             volatility: self.calculate_volatility(),
         }
     }
@@ -1252,6 +1846,58 @@ mod tests {
         assert_eq!(book.asks.len(), 0); // Should start empty
     }
 
+    #[test]
+    fn test_diff_turns_one_book_into_another() {
+        let delta = |side, price, size, sequence| OrderDelta {
+            token_id: "test_token".to_string(),
+            timestamp: Utc::now(),
+            side,
+            price,
+            size,
+            sequence,
+        };
+        let mut local = OrderBook::new("test_token".to_string(), 10);
+        let mut remote = OrderBook::new("test_token".to_string(), 10);
+        let levels = |book: &mut OrderBook, levels: [(Side, Decimal, Decimal); 3]| {
+            for (i, (side, price, size)) in levels.into_iter().enumerate() {
+                book.apply_delta(delta(side, price, size, i as u64 + 1))
+                    .unwrap();
+            }
+        };
+        levels(
+            &mut local,
+            [
+                (Side::BUY, dec!(0.48), dec!(10)),
+                (Side::BUY, dec!(0.47), dec!(5)),
+                (Side::SELL, dec!(0.52), dec!(7)),
+            ],
+        );
+        levels(
+            &mut remote,
+            [
+                (Side::BUY, dec!(0.49), dec!(3)),
+                (Side::BUY, dec!(0.47), dec!(5)),
+                (Side::SELL, dec!(0.52), dec!(9)),
+            ],
+        );
+
+        let deltas = local.diff(&remote);
+        let changes: Vec<_> = deltas.iter().map(|d| (d.side, d.price, d.size)).collect();
+        assert_eq!(
+            changes,
+            [
+                (Side::BUY, dec!(0.49), dec!(3)),
+                (Side::BUY, dec!(0.48), dec!(0)),
+                (Side::SELL, dec!(0.52), dec!(9)),
+            ]
+        );
+        for delta in deltas {
+            local.apply_delta(delta).unwrap();
+        }
+        assert!(local.diff(&remote).is_empty());
+        assert_eq!(local.bids(None).len(), 2);
+    }
+
     #[test]
     fn test_set_tick_size_requires_exact_fixed_point_value() {
         let mut book = OrderBook::new("test_token".to_string(), 10);
@@ -1269,10 +1915,10 @@ mod tests {
     fn test_order_book_manager_routes_tokens_to_shards() {
         let shard_count = 4;
         let first_token = "test_token_0";
-        let first_shard = shard_index(first_token, shard_count);
+        let first_shard = shard_index(&TokenKey::new(first_token), shard_count);
         let second_token = (1..100)
             .map(|idx| format!("test_token_{idx}"))
-            .find(|token| shard_index(token, shard_count) != first_shard)
+            .find(|token| shard_index(&TokenKey::new(token), shard_count) != first_shard)
             .expect("test tokens should cover multiple shards");
 
         let manager = OrderBookManager::with_shard_count(10, shard_count);
@@ -1427,6 +2073,7 @@ mod tests {
                 },
             ],
             hash: None,
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -1443,6 +2090,7 @@ mod tests {
                 size: dec!(45),
             }],
             hash: None,
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -1455,6 +2103,60 @@ mod tests {
         assert_eq!(book.best_ask().unwrap().size, dec!(45));
     }
 
+    #[test]
+    fn test_level_ages_track_each_level() {
+        let mut book = OrderBook::new("test_token".to_string(), 10);
+        let snapshot = |timestamp, bid_size| BookUpdate {
+            asset_id: "test_token".to_string(),
+            market: "0xabc".to_string(),
+            timestamp,
+            bids: vec![
+                OrderSummary {
+                    price: dec!(0.49),
+                    size: bid_size,
+                },
+                OrderSummary {
+                    price: dec!(0.40),
+                    size: dec!(500),
+                },
+            ],
+            asks: vec![OrderSummary {
+                price: dec!(0.51),
+                size: dec!(30),
+            }],
+            hash: None,
+            extra: Default::default(),
+        };
+        let at = |ms| chrono::DateTime::<Utc>::from_timestamp_millis(ms).unwrap();
+
+        book.apply_book_update(&snapshot(1_000, dec!(20))).unwrap();
+        // Only the level whose size changed is refreshed by a later snapshot
+        book.apply_book_update(&snapshot(5_000, dec!(25))).unwrap();
+        let bids = book.level_ages(Side::BUY, None);
+        assert_eq!(bids[0].updated_at, at(5_000));
+        assert_eq!(bids[1].updated_at, at(1_000));
+        assert_eq!(bids[1].age_at(at(61_000)).as_secs(), 60);
+
+        book.apply_delta(OrderDelta {
+            token_id: "test_token".to_string(),
+            timestamp: at(7_000),
+            side: Side::SELL,
+            price: dec!(0.51),
+            size: dec!(10),
+            sequence: 1,
+        })
+        .unwrap();
+        assert_eq!(
+            book.level_updated_at(Side::SELL, dec!(0.51)),
+            Some(at(7_000))
+        );
+        assert_eq!(
+            book.level_updated_at(Side::BUY, dec!(0.40)),
+            Some(at(1_000))
+        );
+        assert_eq!(book.level_updated_at(Side::BUY, dec!(0.45)), None);
+    }
+
     #[test]
     fn test_ws_snapshot_timestamp_does_not_block_delta_sequence() {
         let mut book = OrderBook::new("test_token".to_string(), 10);
@@ -1523,6 +2225,7 @@ mod tests {
                 size: dec!(30),
             }],
             hash: None,
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -1556,6 +2259,7 @@ mod tests {
                 size: dec!(20),
             }],
             hash: Some("hash_a".to_string()),
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -1572,6 +2276,7 @@ mod tests {
                 size: dec!(40),
             }],
             hash: Some("hash_b".to_string()),
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -1593,6 +2298,7 @@ mod tests {
                 size: dec!(60),
             }],
             hash: Some("hash_b".to_string()),
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -1618,6 +2324,7 @@ mod tests {
                 size: dec!(20),
             }],
             hash: None,
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -1634,6 +2341,7 @@ mod tests {
                 size: dec!(40),
             }],
             hash: None,
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -1659,6 +2367,7 @@ mod tests {
                 size: dec!(20),
             }],
             hash: None,
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -1682,6 +2391,7 @@ mod tests {
                     size: dec!(21),
                 }],
                 hash: None,
+                extra: Default::default(),
             })
             .unwrap_err();
 
@@ -1734,6 +2444,7 @@ mod tests {
                 },
             ],
             hash: None,
+            extra: Default::default(),
         })
         .unwrap();
 
@@ -2032,4 +2743,95 @@ mod tests {
         assert!(spread_fast.is_some()); // Should have a spread
         assert!(mid_fast.is_some()); // Should have a mid price
     }
+
+    #[test]
+    fn test_fast_book_view() {
+        let manager = OrderBookManager::new(10);
+        manager.get_or_create_book("1").unwrap();
+        manager
+            .with_book_mut("1", |book| {
+                book.apply_bid_delta(Decimal::from_str("0.48").unwrap(), Decimal::from(10));
+                book.apply_ask_delta(Decimal::from_str("0.50").unwrap(), Decimal::from(5));
+                book.apply_ask_delta(Decimal::from_str("0.52").unwrap(), Decimal::from(15));
+                Ok(())
+            })
+            .unwrap();
+
+        manager
+            .with_fast_view("1", |view| {
+                assert_eq!(view.best_bid(), Some((Ticks(4800), Units(100_000))));
+                assert_eq!(view.spread(), Some(Ticks(200)));
+                assert_eq!(view.mid(), Some(Ticks(4900)));
+                assert_eq!(view.asks().len(), 2);
+                assert_eq!(view.size_at(Side::SELL, Ticks(5200)), Units(150_000));
+                assert_eq!(view.liquidity_to(Side::BUY, Ticks(5100)), Units(50_000));
+                // 5 @ 0.50 + 5 @ 0.52 averages 0.51
+                assert_eq!(
+                    view.fill_price(Side::BUY, Units(100_000)),
+                    Some(Ticks(5100))
+                );
+                assert_eq!(view.fill_price(Side::SELL, Units(200_000)), None);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_warmup_replays_updates_newer_than_seed() {
+        let manager = OrderBookManager::new(10);
+        manager.begin_warmup("warm");
+        let update = |timestamp: u64, bid: Decimal| BookUpdate {
+            asset_id: "warm".to_string(),
+            market: "0xabc".to_string(),
+            timestamp,
+            bids: vec![OrderSummary {
+                price: bid,
+                size: dec!(10),
+            }],
+            asks: vec![],
+            hash: None,
+            extra: Default::default(),
+        };
+
+        // Both race the REST request; only the one newer than it survives
+        manager.apply_book_update(&update(100, dec!(0.40))).unwrap();
+        manager
+            .with_book_mut("warm", |book| {
+                let level = ParsedBookLevel {
+                    side: Side::BUY,
+                    price_ticks: 4500,
+                    size_units: 100_000,
+                };
+                book.apply_ws_book_snapshot_fast("warm", 300, None, &[level])
+            })
+            .unwrap();
+        assert!(manager.get_book("warm").unwrap().bids.is_empty());
+
+        let summary = OrderBookSummary {
+            market: "0xabc".to_string(),
+            asset_id: "warm".to_string(),
+            hash: None,
+            timestamp: 200,
+            bids: vec![OrderSummary {
+                price: dec!(0.42),
+                size: dec!(10),
+            }],
+            asks: vec![],
+            min_order_size: dec!(5),
+            neg_risk: false,
+            tick_size: dec!(0.01),
+            last_trade_price: None,
+        };
+        assert_eq!(manager.seed_snapshot(&summary).unwrap(), 1);
+
+        manager
+            .with_book("warm", |book| {
+                assert!(!book.is_warming_up());
+                assert_eq!(book.best_bid().unwrap().price, dec!(0.45));
+                assert_eq!(book.last_snapshot_timestamp_ms, 300);
+            })
+            .unwrap();
+        // Live updates apply directly again
+        manager.apply_book_update(&update(400, dec!(0.46))).unwrap();
+        assert_eq!(manager.get_book("warm").unwrap().bids[0].price, dec!(0.46));
+    }
 }
@@ -0,0 +1,301 @@
+//! Order book invariant harness
+//!
+//! Proptest generators for streams of [`BookOp`]s (price level deltas and
+//! full snapshot resets, shuffled so stale updates arrive late) and a
+//! [`ReferenceBook`] that models what [`OrderBook`] should hold after them.
+//! [`run_ops`] applies a stream to both and checks [`check_invariants`]
+//! after every step:
+//!
+//! - both sides are strictly sorted, best first, with positive sizes
+//! - the book is not crossed
+//! - no side holds more than `max_depth` levels
+//! - the book's [`levels_fingerprint`] matches the reference
+//!
+//! Enabled with the `test-util` feature so crates extending the book can
+//! reuse the generators against their own wrappers.
+
+use crate::book::OrderBook;
+use crate::types::{BookLevel, BookUpdate, OrderDelta, OrderSummary, Side};
+use chrono::{DateTime, Utc};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+/// One update fed to a book
+#[derive(Debug, Clone)]
+pub enum BookOp {
+    Delta(OrderDelta),
+    Snapshot(BookUpdate),
+}
+
+impl BookOp {
+    /// Apply to `book`, the way a feed handler would
+    pub fn apply(&self, book: &mut OrderBook) -> crate::errors::Result<()> {
+        match self {
+            Self::Delta(delta) => book.apply_delta(delta.clone()),
+            Self::Snapshot(update) => book.apply_book_update(update),
+        }
+    }
+}
+
+/// Bid prices stay at or below 0.49 and asks at or above 0.51, so any
+/// sequence of updates leaves a valid book uncrossed
+pub fn arb_price(side: Side) -> impl Strategy<Value = Decimal> {
+    let cents = match side {
+        Side::BUY => 1..=49i64,
+        Side::SELL => 51..=99i64,
+    };
+    cents.prop_map(|cents| Decimal::new(cents, 2))
+}
+
+/// Sizes with up to two decimals; zero removes a level
+pub fn arb_size() -> impl Strategy<Value = Decimal> {
+    prop_oneof![
+        1 => Just(Decimal::ZERO),
+        4 => (1..=100_000i64).prop_map(|hundredths| Decimal::new(hundredths, 2)),
+    ]
+}
+
+fn arb_side() -> impl Strategy<Value = Side> {
+    prop_oneof![Just(Side::BUY), Just(Side::SELL)]
+}
+
+fn arb_levels(side: Side) -> impl Strategy<Value = Vec<OrderSummary>> {
+    prop::collection::vec(
+        (
+            arb_price(side),
+            (1..=100_000i64).prop_map(|hundredths| Decimal::new(hundredths, 2)),
+        )
+            .prop_map(|(price, size)| OrderSummary { price, size }),
+        0..12,
+    )
+}
+
+/// A delta with the given sequence number
+pub fn arb_delta(token_id: String, sequence: u64) -> impl Strategy<Value = OrderDelta> {
+    arb_side()
+        .prop_flat_map(|side| (Just(side), arb_price(side), arb_size()))
+        .prop_map(move |(side, price, size)| OrderDelta {
+            token_id: token_id.clone(),
+            timestamp: DateTime::<Utc>::from_timestamp_millis(sequence as i64).unwrap_or_default(),
+            side,
+            price,
+            size,
+            sequence,
+        })
+}
+
+/// A full snapshot at `timestamp`, with or without a hash
+pub fn arb_snapshot(token_id: String, timestamp: u64) -> impl Strategy<Value = BookUpdate> {
+    (
+        arb_levels(Side::BUY),
+        arb_levels(Side::SELL),
+        prop::option::of(0..4u8),
+    )
+        .prop_map(move |(bids, asks, hash)| BookUpdate {
+            asset_id: token_id.clone(),
+            market: "0xmarket".to_string(),
+            timestamp,
+            bids,
+            asks,
+            hash: hash.map(|hash| format!("0x{hash}")),
+            extra: Default::default(),
+        })
+}
+
+/// Up to `max_len` deltas and snapshots, generated in sequence order and
+/// then partly shuffled. Snapshot timestamps may repeat, with or without a
+/// different hash.
+pub fn arb_ops(token_id: &str, max_len: usize) -> impl Strategy<Value = Vec<BookOp>> {
+    let token_id = token_id.to_string();
+    prop::collection::vec((prop::bool::weighted(0.1), 0..2u64), 1..=max_len.max(1))
+        .prop_flat_map(move |kinds| {
+            let mut timestamp = 0;
+            let ops: Vec<BoxedStrategy<BookOp>> = kinds
+                .into_iter()
+                .enumerate()
+                .map(|(i, (snapshot, step))| {
+                    if snapshot {
+                        timestamp += step;
+                        arb_snapshot(token_id.clone(), timestamp)
+                            .prop_map(BookOp::Snapshot)
+                            .boxed()
+                    } else {
+                        arb_delta(token_id.clone(), i as u64 + 1)
+                            .prop_map(BookOp::Delta)
+                            .boxed()
+                    }
+                })
+                .collect();
+            ops
+        })
+        .prop_flat_map(|ops| prop_oneof![3 => Just(ops.clone()), 1 => Just(ops).prop_shuffle()])
+}
+
+/// Straightforward model of the state an [`OrderBook`] should reach
+#[derive(Debug, Clone)]
+pub struct ReferenceBook {
+    pub bids: BTreeMap<Decimal, Decimal>,
+    pub asks: BTreeMap<Decimal, Decimal>,
+    pub max_depth: usize,
+    last_delta_sequence: u64,
+    last_snapshot_ms: u64,
+    last_hash: Option<String>,
+}
+
+impl ReferenceBook {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            max_depth,
+            last_delta_sequence: 0,
+            last_snapshot_ms: 0,
+            last_hash: None,
+        }
+    }
+
+    pub fn apply(&mut self, op: &BookOp) {
+        match op {
+            BookOp::Delta(delta) => {
+                if delta.sequence <= self.last_delta_sequence {
+                    return;
+                }
+                self.last_delta_sequence = delta.sequence;
+                let side = match delta.side {
+                    Side::BUY => &mut self.bids,
+                    Side::SELL => &mut self.asks,
+                };
+                if delta.size.is_zero() {
+                    side.remove(&delta.price);
+                } else {
+                    side.insert(delta.price, delta.size);
+                }
+            },
+            BookOp::Snapshot(update) => {
+                let newer = update.timestamp > self.last_snapshot_ms
+                    || (update.timestamp == self.last_snapshot_ms
+                        && update.hash.is_some()
+                        && update.hash != self.last_hash);
+                if !newer {
+                    return;
+                }
+                self.last_snapshot_ms = update.timestamp;
+                self.last_hash = update.hash.clone();
+                self.bids = update.bids.iter().map(|l| (l.price, l.size)).collect();
+                self.asks = update.asks.iter().map(|l| (l.price, l.size)).collect();
+            },
+        }
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.bids.len() > self.max_depth {
+            self.bids.pop_first();
+        }
+        while self.asks.len() > self.max_depth {
+            self.asks.pop_last();
+        }
+    }
+
+    /// Bids best first
+    pub fn bid_levels(&self) -> Vec<BookLevel> {
+        self.bids
+            .iter()
+            .rev()
+            .map(|(&price, &size)| BookLevel { price, size })
+            .collect()
+    }
+
+    /// Asks best first
+    pub fn ask_levels(&self) -> Vec<BookLevel> {
+        self.asks
+            .iter()
+            .map(|(&price, &size)| BookLevel { price, size })
+            .collect()
+    }
+}
+
+/// Hash of both sides that ignores decimal scale (`0.5` and `0.50` match)
+pub fn levels_fingerprint(bids: &[BookLevel], asks: &[BookLevel]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for (tag, levels) in [(0u8, bids), (1u8, asks)] {
+        tag.hash(&mut hasher);
+        for level in levels {
+            level.price.normalize().to_string().hash(&mut hasher);
+            level.size.normalize().to_string().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// Check `book` against the invariants and `reference`
+pub fn check_invariants(
+    book: &OrderBook,
+    reference: &ReferenceBook,
+) -> std::result::Result<(), String> {
+    let (bids, asks) = (book.bids(None), book.asks(None));
+    if !bids.windows(2).all(|w| w[0].price > w[1].price) {
+        return Err(format!("bids not sorted descending: {bids:?}"));
+    }
+    if !asks.windows(2).all(|w| w[0].price < w[1].price) {
+        return Err(format!("asks not sorted ascending: {asks:?}"));
+    }
+    if let Some(level) = bids.iter().chain(&asks).find(|l| l.size <= Decimal::ZERO) {
+        return Err(format!("non-positive size at {}", level.price));
+    }
+    if !book.is_valid() {
+        return Err(format!(
+            "crossed book: bid {:?} >= ask {:?}",
+            bids.first(),
+            asks.first()
+        ));
+    }
+    if bids.len() > reference.max_depth || asks.len() > reference.max_depth {
+        return Err(format!(
+            "depth {}/{} exceeds {}",
+            bids.len(),
+            asks.len(),
+            reference.max_depth
+        ));
+    }
+    let (expected_bids, expected_asks) = (reference.bid_levels(), reference.ask_levels());
+    if levels_fingerprint(&bids, &asks) != levels_fingerprint(&expected_bids, &expected_asks) {
+        return Err(format!(
+            "book differs from reference:\n  bids {bids:?}\n  want {expected_bids:?}\n  asks {asks:?}\n  want {expected_asks:?}"
+        ));
+    }
+    Ok(())
+}
+
+/// Apply `ops` to `book` and a fresh reference, checking invariants after
+/// each one
+pub fn run_ops(book: &mut OrderBook, ops: &[BookOp]) -> std::result::Result<ReferenceBook, String> {
+    let mut reference = ReferenceBook::new(book.max_depth());
+    for (i, op) in ops.iter().enumerate() {
+        op.apply(book)
+            .map_err(|e| format!("op {i} rejected: {e}"))?;
+        reference.apply(op);
+        check_invariants(book, &reference).map_err(|e| format!("after op {i} {op:?}: {e}"))?;
+    }
+    Ok(reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(128))]
+
+        #[test]
+        fn test_book_matches_reference(depth in 1..8usize, ops in arb_ops("1", 64)) {
+            let mut book = OrderBook::new("1", depth);
+            if let Err(e) = run_ops(&mut book, &ops) {
+                return Err(TestCaseError::fail(e));
+            }
+        }
+    }
+}
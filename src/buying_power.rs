@@ -0,0 +1,302 @@
+//! Available buying power
+//!
+//! [`BuyingPowerCalculator`] combines the cached balance and allowance of an
+//! asset ([`BalanceCache`]), what resting orders already lock up
+//! ([`OrderTracker`]) and trades still settling ([`SettlementTracker`]) into
+//! the amount a new order can actually use:
+//!
+//! - buys draw on collateral: `min(balance, allowance)` minus what every
+//!   open buy holds back, shared across markets
+//! - sells draw on the token's shares: `min(balance, allowance)` minus what
+//!   open sells on that token hold back
+//!
+//! Proceeds of matched trades that have not been mined yet are credited by
+//! the balance cache but cannot be spent, so they are held back too. A fresh
+//! REST balance may not include them at all, which makes the figure
+//! conservative rather than optimistic. Checking orders against it before
+//! sending avoids "not enough balance / allowance" rejections in the middle
+//! of a burst.
+
+use crate::balances::{BalanceCache, BalanceKey, CachedBalance};
+use crate::errors::{PolyfillError, Result};
+use crate::reconcile::OrderTracker;
+use crate::types::{OrderArgs, Side, StreamMessage, TradeMessage};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// A matched trade that has not settled on chain yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingSettlement {
+    pub trade_id: String,
+    pub asset_id: String,
+    pub side: Side,
+    pub size: Decimal,
+    pub price: Decimal,
+}
+
+impl PendingSettlement {
+    /// Collateral (sells) or shares (buys) the trade will deliver
+    fn incoming(&self, key: &BalanceKey) -> Decimal {
+        match (key, self.side) {
+            (BalanceKey::Collateral, Side::SELL) => self.size * self.price,
+            (BalanceKey::Conditional(token_id), Side::BUY) if *token_id == self.asset_id => {
+                self.size
+            },
+            _ => Decimal::ZERO,
+        }
+    }
+}
+
+/// Trades reported `MATCHED` on the user channel and not yet mined
+#[derive(Debug, Default)]
+pub struct SettlementTracker {
+    pending: RwLock<HashMap<String, PendingSettlement>>,
+}
+
+impl SettlementTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a user-channel message; other messages are ignored
+    pub fn apply(&self, message: &StreamMessage) {
+        if let StreamMessage::Trade(trade) = message {
+            self.apply_trade(trade);
+        }
+    }
+
+    pub fn apply_trade(&self, trade: &TradeMessage) {
+        let mut pending = self.pending.write();
+        match trade.status.as_deref() {
+            None | Some("MATCHED") | Some("RETRYING") => {
+                pending
+                    .entry(trade.id.clone())
+                    .or_insert_with(|| PendingSettlement {
+                        trade_id: trade.id.clone(),
+                        asset_id: trade.asset_id.clone(),
+                        side: trade.side,
+                        size: trade.size,
+                        price: trade.price,
+                    });
+            },
+            // MINED, CONFIRMED and FAILED all end the wait
+            Some(_) => {
+                pending.remove(&trade.id);
+            },
+        }
+    }
+
+    pub fn pending(&self) -> Vec<PendingSettlement> {
+        self.pending.read().values().cloned().collect()
+    }
+
+    fn incoming(&self, key: &BalanceKey) -> Decimal {
+        self.pending
+            .read()
+            .values()
+            .map(|settlement| settlement.incoming(key))
+            .sum()
+    }
+}
+
+/// Breakdown of what one side of a token can still use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuyingPower {
+    pub token_id: String,
+    pub side: Side,
+    /// Cached balance of the asset drawn on
+    pub balance: Decimal,
+    /// Allowance for the configured spender, if known
+    pub allowance: Option<Decimal>,
+    /// Held by open orders
+    pub locked: Decimal,
+    /// Proceeds of unsettled trades
+    pub unsettled: Decimal,
+    /// Collateral for buys, shares for sells; never negative
+    pub available: Decimal,
+}
+
+impl BuyingPower {
+    /// Largest order size at `price` that fits
+    pub fn max_size(&self, price: Decimal) -> Decimal {
+        match self.side {
+            Side::BUY if price > Decimal::ZERO => self.available / price,
+            Side::BUY => Decimal::ZERO,
+            Side::SELL => self.available,
+        }
+    }
+}
+
+/// Buying power from cached balances, open orders and pending settlements
+#[derive(Debug, Clone)]
+pub struct BuyingPowerCalculator {
+    balances: Arc<BalanceCache>,
+    orders: Arc<OrderTracker>,
+    settlements: Arc<SettlementTracker>,
+    spender: Option<String>,
+}
+
+impl BuyingPowerCalculator {
+    pub fn new(
+        balances: Arc<BalanceCache>,
+        orders: Arc<OrderTracker>,
+        settlements: Arc<SettlementTracker>,
+    ) -> Self {
+        Self {
+            balances,
+            orders,
+            settlements,
+            spender: None,
+        }
+    }
+
+    /// Cap balances by the allowance granted to `spender` (the exchange
+    /// contract orders settle through). Without one, allowances are ignored.
+    pub fn with_spender(mut self, spender: impl Into<String>) -> Self {
+        self.spender = Some(spender.into());
+        self
+    }
+
+    /// Buying power for `side` of `token_id`; `None` until the balance it
+    /// draws on has been cached
+    pub fn buying_power(&self, token_id: &str, side: Side) -> Option<BuyingPower> {
+        let key = match side {
+            Side::BUY => BalanceKey::Collateral,
+            Side::SELL => BalanceKey::Conditional(token_id.to_string()),
+        };
+        let cached = self.balances.get(&key)?;
+        let allowance = self.allowance(&cached);
+        let locked = match side {
+            Side::BUY => self.orders.locked_total().collateral,
+            Side::SELL => self.orders.locked_for(token_id).shares,
+        };
+        let unsettled = self.settlements.incoming(&key);
+        let usable = allowance.map_or(cached.balance, |allowance| cached.balance.min(allowance));
+        Some(BuyingPower {
+            token_id: token_id.to_string(),
+            side,
+            balance: cached.balance,
+            allowance,
+            locked,
+            unsettled,
+            available: (usable - locked - unsettled).max(Decimal::ZERO),
+        })
+    }
+
+    /// Reject `order` if it needs more than is available
+    pub fn check(&self, order: &OrderArgs) -> Result<BuyingPower> {
+        let token_id = order.token_id.as_str();
+        let power = self.buying_power(token_id, order.side).ok_or_else(|| {
+            PolyfillError::validation(format!(
+                "No cached balance for {} {}",
+                order.side.as_str(),
+                token_id
+            ))
+        })?;
+        let needed = match order.side {
+            Side::BUY => order.size * order.price,
+            Side::SELL => order.size,
+        };
+        if needed > power.available {
+            return Err(PolyfillError::validation(format!(
+                "{} {} needs {} but only {} is available",
+                order.side.as_str(),
+                token_id,
+                needed,
+                power.available
+            )));
+        }
+        Ok(power)
+    }
+
+    fn allowance(&self, cached: &CachedBalance) -> Option<Decimal> {
+        let spender = self.spender.as_ref()?;
+        cached
+            .allowances
+            .iter()
+            .find(|(address, _)| address.eq_ignore_ascii_case(spender))
+            .or_else(|| cached.allowances.get_key_value(""))
+            .map(|(_, amount)| *amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reconcile::TrackedOrder;
+    use crate::types::TokenId;
+    use rust_decimal_macros::dec;
+
+    fn trade(id: &str, side: &str, status: &str) -> StreamMessage {
+        serde_json::from_str(&format!(
+            r#"{{"event_type":"trade","id":"{id}","market":"0xabc","asset_id":"1","side":"{side}",
+                "size":"10","price":"0.4","status":"{status}"}}"#
+        ))
+        .unwrap()
+    }
+
+    fn order(id: &str, side: Side, price: Decimal, size: Decimal) -> TrackedOrder {
+        TrackedOrder {
+            id: id.to_string(),
+            market: "0xabc".to_string(),
+            asset_id: "1".to_string(),
+            side,
+            price,
+            original_size: size,
+            size_matched: Decimal::ZERO,
+            expiration: None,
+            maker_amount: None,
+        }
+    }
+
+    #[test]
+    fn test_buying_power_subtracts_locked_and_unsettled() {
+        let balances = Arc::new(BalanceCache::default());
+        balances.insert(
+            BalanceKey::Collateral,
+            CachedBalance {
+                balance: dec!(100),
+                allowances: HashMap::from([("0xExchange".to_string(), dec!(80))]),
+                adjusted: false,
+            },
+        );
+        balances.insert(
+            BalanceKey::Conditional("1".to_string()),
+            CachedBalance {
+                balance: dec!(50),
+                allowances: HashMap::new(),
+                adjusted: false,
+            },
+        );
+        let orders = Arc::new(OrderTracker::new());
+        orders.insert(order("b1", Side::BUY, dec!(0.5), dec!(40)));
+        orders.insert(order("s1", Side::SELL, dec!(0.6), dec!(15)));
+        let settlements = Arc::new(SettlementTracker::new());
+        settlements.apply(&trade("t1", "SELL", "MATCHED"));
+        settlements.apply(&trade("t2", "BUY", "MATCHED"));
+        settlements.apply(&trade("t2", "BUY", "CONFIRMED"));
+
+        let calculator = BuyingPowerCalculator::new(balances, orders, settlements.clone())
+            .with_spender("0xexchange");
+        // min(100, 80) - 40 * 0.5 - 10 * 0.4
+        let buy = calculator.buying_power("1", Side::BUY).unwrap();
+        assert_eq!(
+            (buy.locked, buy.unsettled, buy.available),
+            (dec!(20), dec!(4), dec!(56))
+        );
+        assert_eq!(buy.max_size(dec!(0.5)), dec!(112));
+        let sell = calculator.buying_power("1", Side::SELL).unwrap();
+        assert_eq!(sell.available, dec!(35));
+        assert!(calculator.buying_power("2", Side::SELL).is_none());
+
+        let mut args = OrderArgs::new(TokenId::new("1").unwrap(), dec!(0.5), dec!(112), Side::BUY);
+        assert!(calculator.check(&args).is_ok());
+        args.size = dec!(113);
+        assert!(calculator.check(&args).is_err());
+
+        settlements.apply(&trade("t1", "SELL", "MINED"));
+        assert!(settlements.pending().is_empty());
+    }
+}
@@ -0,0 +1,1097 @@
+//! Market-data capture
+//!
+//! This module normalizes WebSocket market data into three flat record types
+//! (book deltas, top-of-book and trades) and, with the `capture` feature,
+//! records them to Hive-partitioned Parquet files. A fourth dataset, price
+//! points, holds sampled price series such as those pulled by
+//! [`crate::backfill`]:
+//!
+//! ```text
+//! {root}/{dataset}/token_id={token}/date={YYYY-MM-DD}/hour={HH}/part-{first_ts_ms}.parquet
+//! ```
+//!
+//! Every file carries the schema version under [`SCHEMA_VERSION_KEY`] in its
+//! key-value metadata so readers can reject data written by a newer layout.
+//! [`crate::decode::book_delta_codec`] packs book rows into a compact binary
+//! stream for storing or shipping replays without Parquet.
+
+use crate::types::{Side, StreamMessage};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Version of the capture record layout
+pub const CAPTURE_SCHEMA_VERSION: u32 = 1;
+
+/// Parquet key-value metadata key holding [`CAPTURE_SCHEMA_VERSION`]
+pub const SCHEMA_VERSION_KEY: &str = "polyfill.capture.schema_version";
+
+/// Whether a book row belongs to a full snapshot or an incremental change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookEventKind {
+    /// Row of a full snapshot; all rows sharing a sequence replace the book
+    Snapshot,
+    /// Absolute size update for a single level (`size == 0` removes it)
+    Delta,
+}
+
+impl BookEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BookEventKind::Snapshot => "snapshot",
+            BookEventKind::Delta => "delta",
+        }
+    }
+}
+
+/// Normalized book change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookDeltaRecord {
+    pub token_id: String,
+    pub timestamp_ms: u64,
+    /// Per-token event counter assigned at capture time
+    pub sequence: u64,
+    pub kind: BookEventKind,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+/// Best bid/ask observation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TopOfBookRecord {
+    pub token_id: String,
+    pub timestamp_ms: u64,
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+}
+
+/// Executed trade (or last-trade-price print)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub token_id: String,
+    pub timestamp_ms: u64,
+    pub trade_id: Option<String>,
+    pub side: Option<Side>,
+    pub price: Decimal,
+    pub size: Option<Decimal>,
+}
+
+/// Sampled price observation (e.g. a `/prices-history` point)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricePointRecord {
+    pub token_id: String,
+    pub timestamp_ms: u64,
+    pub price: Decimal,
+}
+
+/// OHLCV bar aggregated from trades
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candle {
+    pub token_id: String,
+    /// Bucket start (inclusive), aligned to `interval_ms`
+    pub start_ms: u64,
+    pub interval_ms: u64,
+    pub open: Decimal,
+    pub high: Decimal,
+    pub low: Decimal,
+    pub close: Decimal,
+    /// Sum of trade sizes; prints without a size contribute nothing
+    pub volume: Decimal,
+    pub trade_count: u64,
+}
+
+impl Candle {
+    /// Aggregate trades into candles per token, ordered by token then time.
+    ///
+    /// Buckets without trades are omitted.
+    pub fn from_trades(trades: &[TradeRecord], interval: std::time::Duration) -> Vec<Candle> {
+        let interval_ms = (interval.as_millis() as u64).max(1);
+        let mut ordered: Vec<&TradeRecord> = trades.iter().collect();
+        ordered.sort_by(|a, b| {
+            a.token_id
+                .cmp(&b.token_id)
+                .then(a.timestamp_ms.cmp(&b.timestamp_ms))
+        });
+
+        let mut candles: Vec<Candle> = Vec::new();
+        for trade in ordered {
+            let start_ms = trade.timestamp_ms - trade.timestamp_ms % interval_ms;
+            let volume = trade.size.unwrap_or_default();
+            match candles.last_mut() {
+                Some(candle)
+                    if candle.token_id == trade.token_id && candle.start_ms == start_ms =>
+                {
+                    candle.high = candle.high.max(trade.price);
+                    candle.low = candle.low.min(trade.price);
+                    candle.close = trade.price;
+                    candle.volume += volume;
+                    candle.trade_count += 1;
+                },
+                _ => candles.push(Candle {
+                    token_id: trade.token_id.clone(),
+                    start_ms,
+                    interval_ms,
+                    open: trade.price,
+                    high: trade.price,
+                    low: trade.price,
+                    close: trade.price,
+                    volume,
+                    trade_count: 1,
+                }),
+            }
+        }
+        candles
+    }
+}
+
+/// Records produced from one or more stream messages
+#[derive(Debug, Clone, Default)]
+pub struct CaptureBatch {
+    pub deltas: Vec<BookDeltaRecord>,
+    pub top_of_book: Vec<TopOfBookRecord>,
+    pub trades: Vec<TradeRecord>,
+}
+
+impl CaptureBatch {
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty() && self.top_of_book.is_empty() && self.trades.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.deltas.len() + self.top_of_book.len() + self.trades.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.deltas.clear();
+        self.top_of_book.clear();
+        self.trades.clear();
+    }
+}
+
+/// Converts stream messages into capture records
+#[derive(Debug, Default)]
+pub struct CaptureNormalizer {
+    sequences: HashMap<String, u64>,
+}
+
+impl CaptureNormalizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn next_sequence(&mut self, token_id: &str) -> u64 {
+        let sequence = self.sequences.entry(token_id.to_string()).or_insert(0);
+        *sequence += 1;
+        *sequence
+    }
+
+    /// Append the records derived from `message` to `out`
+    pub fn normalize(&mut self, message: &StreamMessage, out: &mut CaptureBatch) {
+        match message {
+            StreamMessage::Book(book) => {
+                let sequence = self.next_sequence(&book.asset_id);
+                let levels = book
+                    .bids
+                    .iter()
+                    .map(|level| (Side::BUY, level))
+                    .chain(book.asks.iter().map(|level| (Side::SELL, level)));
+                for (side, level) in levels {
+                    out.deltas.push(BookDeltaRecord {
+                        token_id: book.asset_id.clone(),
+                        timestamp_ms: book.timestamp,
+                        sequence,
+                        kind: BookEventKind::Snapshot,
+                        side,
+                        price: level.price,
+                        size: level.size,
+                    });
+                }
+
+                out.top_of_book.push(TopOfBookRecord {
+                    token_id: book.asset_id.clone(),
+                    timestamp_ms: book.timestamp,
+                    best_bid: book.bids.iter().map(|l| l.price).max(),
+                    best_ask: book.asks.iter().map(|l| l.price).min(),
+                });
+            },
+            StreamMessage::PriceChange(change) => {
+                for entry in &change.price_changes {
+                    let sequence = self.next_sequence(&entry.asset_id);
+                    if let Some(size) = entry.size {
+                        out.deltas.push(BookDeltaRecord {
+                            token_id: entry.asset_id.clone(),
+                            timestamp_ms: change.timestamp,
+                            sequence,
+                            kind: BookEventKind::Delta,
+                            side: entry.side,
+                            price: entry.price,
+                            size,
+                        });
+                    }
+                    if entry.best_bid.is_some() || entry.best_ask.is_some() {
+                        out.top_of_book.push(TopOfBookRecord {
+                            token_id: entry.asset_id.clone(),
+                            timestamp_ms: change.timestamp,
+                            best_bid: entry.best_bid,
+                            best_ask: entry.best_ask,
+                        });
+                    }
+                }
+            },
+            StreamMessage::BestBidAsk(bba) => out.top_of_book.push(TopOfBookRecord {
+                token_id: bba.asset_id.clone(),
+                timestamp_ms: bba.timestamp,
+                best_bid: Some(bba.best_bid),
+                best_ask: Some(bba.best_ask),
+            }),
+            StreamMessage::LastTradePrice(trade) => out.trades.push(TradeRecord {
+                token_id: trade.asset_id.clone(),
+                timestamp_ms: trade.timestamp,
+                trade_id: None,
+                side: trade.side,
+                price: trade.price,
+                size: trade.size,
+            }),
+            StreamMessage::Trade(trade) => out.trades.push(TradeRecord {
+                token_id: trade.asset_id.clone(),
+                timestamp_ms: trade
+                    .matchtime
+                    .or(trade.timestamp)
+                    .or(trade.last_update)
+                    .map(normalize_timestamp_ms)
+                    .unwrap_or_else(crate::utils::time::now_millis),
+                trade_id: Some(trade.id.clone()),
+                side: Some(trade.side),
+                price: trade.price,
+                size: Some(trade.size),
+            }),
+            _ => {},
+        }
+    }
+}
+
+/// User-channel timestamps are sometimes in seconds; normalize to milliseconds
+pub(crate) fn normalize_timestamp_ms(timestamp: u64) -> u64 {
+    if timestamp < 100_000_000_000 {
+        timestamp * 1000
+    } else {
+        timestamp
+    }
+}
+
+#[cfg(feature = "capture")]
+pub use self::parquet_io::{
+    read_dataset, CaptureRow, CaptureService, Dataset, ParquetCaptureWriter,
+};
+
+#[cfg(feature = "capture")]
+mod parquet_io {
+    use super::*;
+    use crate::errors::{PolyfillError, Result};
+    use arrow_array::{Array, ArrayRef, Float64Array, RecordBatch, StringArray, UInt64Array};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef};
+    use chrono::{DateTime, Datelike, Timelike};
+    use futures::{Stream, StreamExt};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::metadata::KeyValue;
+    use parquet::file::properties::WriterProperties;
+    use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+    use std::fs::File;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use tracing::{debug, warn};
+
+    /// Capture datasets, one directory each under the capture root
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Dataset {
+        BookDeltas,
+        TopOfBook,
+        Trades,
+        Prices,
+    }
+
+    impl Dataset {
+        pub fn dir_name(&self) -> &'static str {
+            match self {
+                Dataset::BookDeltas => "book_deltas",
+                Dataset::TopOfBook => "top_of_book",
+                Dataset::Trades => "trades",
+                Dataset::Prices => "prices",
+            }
+        }
+    }
+
+    /// A record type that can be stored in a capture dataset
+    pub trait CaptureRow: Sized {
+        const DATASET: Dataset;
+
+        fn schema() -> SchemaRef;
+        fn token_id(&self) -> &str;
+        fn timestamp_ms(&self) -> u64;
+        fn to_batch(rows: &[Self]) -> Result<RecordBatch>;
+        fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>>;
+    }
+
+    fn to_f64(value: Decimal) -> f64 {
+        value.to_f64().unwrap_or(f64::NAN)
+    }
+
+    fn from_f64(value: f64) -> Result<Decimal> {
+        Decimal::from_f64(value)
+            .map(|d| d.round_dp(6).normalize())
+            .ok_or_else(|| PolyfillError::parse(format!("Invalid decimal value: {value}"), None))
+    }
+
+    fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a T> {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<T>())
+            .ok_or_else(|| PolyfillError::parse(format!("Missing capture column: {name}"), None))
+    }
+
+    fn opt_f64(array: &Float64Array, i: usize) -> Result<Option<Decimal>> {
+        if array.is_null(i) {
+            Ok(None)
+        } else {
+            from_f64(array.value(i)).map(Some)
+        }
+    }
+
+    fn opt_str(array: &StringArray, i: usize) -> Option<&str> {
+        (!array.is_null(i)).then(|| array.value(i))
+    }
+
+    impl CaptureRow for BookDeltaRecord {
+        const DATASET: Dataset = Dataset::BookDeltas;
+
+        fn schema() -> SchemaRef {
+            Arc::new(Schema::new(vec![
+                Field::new("token_id", DataType::Utf8, false),
+                Field::new("timestamp_ms", DataType::UInt64, false),
+                Field::new("sequence", DataType::UInt64, false),
+                Field::new("kind", DataType::Utf8, false),
+                Field::new("side", DataType::Utf8, false),
+                Field::new("price", DataType::Float64, false),
+                Field::new("size", DataType::Float64, false),
+            ]))
+        }
+
+        fn token_id(&self) -> &str {
+            &self.token_id
+        }
+
+        fn timestamp_ms(&self) -> u64 {
+            self.timestamp_ms
+        }
+
+        fn to_batch(rows: &[Self]) -> Result<RecordBatch> {
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|r| r.token_id.as_str()),
+                )),
+                Arc::new(UInt64Array::from_iter_values(
+                    rows.iter().map(|r| r.timestamp_ms),
+                )),
+                Arc::new(UInt64Array::from_iter_values(
+                    rows.iter().map(|r| r.sequence),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|r| r.kind.as_str()),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|r| r.side.as_str()),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    rows.iter().map(|r| to_f64(r.price)),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    rows.iter().map(|r| to_f64(r.size)),
+                )),
+            ];
+            Ok(RecordBatch::try_new(Self::schema(), columns)?)
+        }
+
+        fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>> {
+            let token_id = column::<StringArray>(batch, "token_id")?;
+            let timestamp = column::<UInt64Array>(batch, "timestamp_ms")?;
+            let sequence = column::<UInt64Array>(batch, "sequence")?;
+            let kind = column::<StringArray>(batch, "kind")?;
+            let side = column::<StringArray>(batch, "side")?;
+            let price = column::<Float64Array>(batch, "price")?;
+            let size = column::<Float64Array>(batch, "size")?;
+
+            (0..batch.num_rows())
+                .map(|i| {
+                    Ok(BookDeltaRecord {
+                        token_id: token_id.value(i).to_string(),
+                        timestamp_ms: timestamp.value(i),
+                        sequence: sequence.value(i),
+                        kind: kind_from_str(kind.value(i))?,
+                        side: side_from_str(side.value(i))?,
+                        price: from_f64(price.value(i))?,
+                        size: from_f64(size.value(i))?,
+                    })
+                })
+                .collect()
+        }
+    }
+
+    impl CaptureRow for TopOfBookRecord {
+        const DATASET: Dataset = Dataset::TopOfBook;
+
+        fn schema() -> SchemaRef {
+            Arc::new(Schema::new(vec![
+                Field::new("token_id", DataType::Utf8, false),
+                Field::new("timestamp_ms", DataType::UInt64, false),
+                Field::new("best_bid", DataType::Float64, true),
+                Field::new("best_ask", DataType::Float64, true),
+            ]))
+        }
+
+        fn token_id(&self) -> &str {
+            &self.token_id
+        }
+
+        fn timestamp_ms(&self) -> u64 {
+            self.timestamp_ms
+        }
+
+        fn to_batch(rows: &[Self]) -> Result<RecordBatch> {
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|r| r.token_id.as_str()),
+                )),
+                Arc::new(UInt64Array::from_iter_values(
+                    rows.iter().map(|r| r.timestamp_ms),
+                )),
+                Arc::new(Float64Array::from_iter(
+                    rows.iter().map(|r| r.best_bid.map(to_f64)),
+                )),
+                Arc::new(Float64Array::from_iter(
+                    rows.iter().map(|r| r.best_ask.map(to_f64)),
+                )),
+            ];
+            Ok(RecordBatch::try_new(Self::schema(), columns)?)
+        }
+
+        fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>> {
+            let token_id = column::<StringArray>(batch, "token_id")?;
+            let timestamp = column::<UInt64Array>(batch, "timestamp_ms")?;
+            let best_bid = column::<Float64Array>(batch, "best_bid")?;
+            let best_ask = column::<Float64Array>(batch, "best_ask")?;
+
+            (0..batch.num_rows())
+                .map(|i| {
+                    Ok(TopOfBookRecord {
+                        token_id: token_id.value(i).to_string(),
+                        timestamp_ms: timestamp.value(i),
+                        best_bid: opt_f64(best_bid, i)?,
+                        best_ask: opt_f64(best_ask, i)?,
+                    })
+                })
+                .collect()
+        }
+    }
+
+    impl CaptureRow for TradeRecord {
+        const DATASET: Dataset = Dataset::Trades;
+
+        fn schema() -> SchemaRef {
+            Arc::new(Schema::new(vec![
+                Field::new("token_id", DataType::Utf8, false),
+                Field::new("timestamp_ms", DataType::UInt64, false),
+                Field::new("trade_id", DataType::Utf8, true),
+                Field::new("side", DataType::Utf8, true),
+                Field::new("price", DataType::Float64, false),
+                Field::new("size", DataType::Float64, true),
+            ]))
+        }
+
+        fn token_id(&self) -> &str {
+            &self.token_id
+        }
+
+        fn timestamp_ms(&self) -> u64 {
+            self.timestamp_ms
+        }
+
+        fn to_batch(rows: &[Self]) -> Result<RecordBatch> {
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|r| r.token_id.as_str()),
+                )),
+                Arc::new(UInt64Array::from_iter_values(
+                    rows.iter().map(|r| r.timestamp_ms),
+                )),
+                Arc::new(StringArray::from_iter(
+                    rows.iter().map(|r| r.trade_id.as_deref()),
+                )),
+                Arc::new(StringArray::from_iter(
+                    rows.iter().map(|r| r.side.map(|s| s.as_str())),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    rows.iter().map(|r| to_f64(r.price)),
+                )),
+                Arc::new(Float64Array::from_iter(
+                    rows.iter().map(|r| r.size.map(to_f64)),
+                )),
+            ];
+            Ok(RecordBatch::try_new(Self::schema(), columns)?)
+        }
+
+        fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>> {
+            let token_id = column::<StringArray>(batch, "token_id")?;
+            let timestamp = column::<UInt64Array>(batch, "timestamp_ms")?;
+            let trade_id = column::<StringArray>(batch, "trade_id")?;
+            let side = column::<StringArray>(batch, "side")?;
+            let price = column::<Float64Array>(batch, "price")?;
+            let size = column::<Float64Array>(batch, "size")?;
+
+            (0..batch.num_rows())
+                .map(|i| {
+                    Ok(TradeRecord {
+                        token_id: token_id.value(i).to_string(),
+                        timestamp_ms: timestamp.value(i),
+                        trade_id: opt_str(trade_id, i).map(str::to_string),
+                        side: opt_str(side, i).map(side_from_str).transpose()?,
+                        price: from_f64(price.value(i))?,
+                        size: opt_f64(size, i)?,
+                    })
+                })
+                .collect()
+        }
+    }
+
+    impl CaptureRow for PricePointRecord {
+        const DATASET: Dataset = Dataset::Prices;
+
+        fn schema() -> SchemaRef {
+            Arc::new(Schema::new(vec![
+                Field::new("token_id", DataType::Utf8, false),
+                Field::new("timestamp_ms", DataType::UInt64, false),
+                Field::new("price", DataType::Float64, false),
+            ]))
+        }
+
+        fn token_id(&self) -> &str {
+            &self.token_id
+        }
+
+        fn timestamp_ms(&self) -> u64 {
+            self.timestamp_ms
+        }
+
+        fn to_batch(rows: &[Self]) -> Result<RecordBatch> {
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(StringArray::from_iter_values(
+                    rows.iter().map(|r| r.token_id.as_str()),
+                )),
+                Arc::new(UInt64Array::from_iter_values(
+                    rows.iter().map(|r| r.timestamp_ms),
+                )),
+                Arc::new(Float64Array::from_iter_values(
+                    rows.iter().map(|r| to_f64(r.price)),
+                )),
+            ];
+            Ok(RecordBatch::try_new(Self::schema(), columns)?)
+        }
+
+        fn from_batch(batch: &RecordBatch) -> Result<Vec<Self>> {
+            let token_id = column::<StringArray>(batch, "token_id")?;
+            let timestamp = column::<UInt64Array>(batch, "timestamp_ms")?;
+            let price = column::<Float64Array>(batch, "price")?;
+
+            (0..batch.num_rows())
+                .map(|i| {
+                    Ok(PricePointRecord {
+                        token_id: token_id.value(i).to_string(),
+                        timestamp_ms: timestamp.value(i),
+                        price: from_f64(price.value(i))?,
+                    })
+                })
+                .collect()
+        }
+    }
+
+    /// Make a token ID safe to use as a path component
+    fn sanitize(component: &str) -> String {
+        component
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
+    fn side_from_str(value: &str) -> Result<Side> {
+        match value {
+            "BUY" => Ok(Side::BUY),
+            "SELL" => Ok(Side::SELL),
+            other => Err(PolyfillError::parse(
+                format!("Invalid side in capture file: {other}"),
+                None,
+            )),
+        }
+    }
+
+    fn kind_from_str(value: &str) -> Result<BookEventKind> {
+        match value {
+            "snapshot" => Ok(BookEventKind::Snapshot),
+            "delta" => Ok(BookEventKind::Delta),
+            other => Err(PolyfillError::parse(
+                format!("Invalid book event kind in capture file: {other}"),
+                None,
+            )),
+        }
+    }
+
+    const HOUR_MS: u64 = 3_600_000;
+
+    fn partition_dir(root: &Path, dataset: Dataset, token_id: &str, hour_start_ms: u64) -> PathBuf {
+        let hour = DateTime::from_timestamp_millis(hour_start_ms as i64).unwrap_or_default();
+        root.join(dataset.dir_name())
+            .join(format!("token_id={}", sanitize(token_id)))
+            .join(format!(
+                "date={:04}-{:02}-{:02}",
+                hour.year(),
+                hour.month(),
+                hour.day()
+            ))
+            .join(format!("hour={:02}", hour.hour()))
+    }
+
+    struct PartitionWriter<T> {
+        hour_start_ms: u64,
+        path: PathBuf,
+        writer: ArrowWriter<File>,
+        buffer: Vec<T>,
+    }
+
+    struct DatasetWriter<T> {
+        partitions: HashMap<String, PartitionWriter<T>>,
+    }
+
+    impl<T: CaptureRow> DatasetWriter<T> {
+        fn new() -> Self {
+            Self {
+                partitions: HashMap::new(),
+            }
+        }
+
+        fn write(&mut self, root: &Path, row_group_size: usize, row: T) -> Result<()> {
+            let hour_start_ms = row.timestamp_ms() - row.timestamp_ms() % HOUR_MS;
+            let rotate = self
+                .partitions
+                .get(row.token_id())
+                .is_some_and(|p| p.hour_start_ms != hour_start_ms);
+            if rotate {
+                if let Some(partition) = self.partitions.remove(row.token_id()) {
+                    Self::close_partition(partition)?;
+                }
+            }
+
+            if !self.partitions.contains_key(row.token_id()) {
+                let partition = Self::open_partition(root, &row, hour_start_ms)?;
+                self.partitions
+                    .insert(row.token_id().to_string(), partition);
+            }
+
+            let partition = self
+                .partitions
+                .get_mut(row.token_id())
+                .expect("partition was just inserted");
+            partition.buffer.push(row);
+            if partition.buffer.len() >= row_group_size {
+                let batch = T::to_batch(&partition.buffer)?;
+                partition.writer.write(&batch)?;
+                partition.writer.flush()?;
+                partition.buffer.clear();
+            }
+            Ok(())
+        }
+
+        fn open_partition(root: &Path, row: &T, hour_start_ms: u64) -> Result<PartitionWriter<T>> {
+            let dir = partition_dir(root, T::DATASET, row.token_id(), hour_start_ms);
+            std::fs::create_dir_all(&dir).map_err(|e| {
+                PolyfillError::internal(format!("Failed to create {}", dir.display()), e)
+            })?;
+
+            let mut path = dir.join(format!("part-{}.parquet", row.timestamp_ms()));
+            let mut attempt = 1;
+            while path.exists() {
+                path = dir.join(format!("part-{}-{}.parquet", row.timestamp_ms(), attempt));
+                attempt += 1;
+            }
+
+            let file = File::create(&path).map_err(|e| {
+                PolyfillError::internal(format!("Failed to create {}", path.display()), e)
+            })?;
+            let props = WriterProperties::builder()
+                .set_compression(Compression::SNAPPY)
+                .set_key_value_metadata(Some(vec![KeyValue::new(
+                    SCHEMA_VERSION_KEY.to_string(),
+                    CAPTURE_SCHEMA_VERSION.to_string(),
+                )]))
+                .build();
+            let writer = ArrowWriter::try_new(file, T::schema(), Some(props))?;
+            debug!("Opened capture partition {}", path.display());
+
+            Ok(PartitionWriter {
+                hour_start_ms,
+                path,
+                writer,
+                buffer: Vec::new(),
+            })
+        }
+
+        fn close_partition(mut partition: PartitionWriter<T>) -> Result<()> {
+            if !partition.buffer.is_empty() {
+                let batch = T::to_batch(&partition.buffer)?;
+                partition.writer.write(&batch)?;
+            }
+            partition.writer.close()?;
+            debug!("Closed capture partition {}", partition.path.display());
+            Ok(())
+        }
+
+        fn close_all(&mut self) -> Result<()> {
+            for (_, partition) in self.partitions.drain() {
+                Self::close_partition(partition)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Writes capture records to partitioned Parquet files.
+    ///
+    /// Files only become readable once closed: partitions close when the hour
+    /// rolls over, on [`ParquetCaptureWriter::flush`], and on drop.
+    pub struct ParquetCaptureWriter {
+        root: PathBuf,
+        row_group_size: usize,
+        deltas: DatasetWriter<BookDeltaRecord>,
+        top_of_book: DatasetWriter<TopOfBookRecord>,
+        trades: DatasetWriter<TradeRecord>,
+        prices: DatasetWriter<PricePointRecord>,
+    }
+
+    impl std::fmt::Debug for ParquetCaptureWriter {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("ParquetCaptureWriter")
+                .field("root", &self.root)
+                .field("row_group_size", &self.row_group_size)
+                .finish()
+        }
+    }
+
+    impl ParquetCaptureWriter {
+        /// Create a writer rooted at `root` (created on first write)
+        pub fn new(root: impl Into<PathBuf>) -> Self {
+            Self {
+                root: root.into(),
+                row_group_size: 8192,
+                deltas: DatasetWriter::new(),
+                top_of_book: DatasetWriter::new(),
+                trades: DatasetWriter::new(),
+                prices: DatasetWriter::new(),
+            }
+        }
+
+        /// Rows buffered per partition before a row group is written
+        pub fn with_row_group_size(mut self, rows: usize) -> Self {
+            self.row_group_size = rows.max(1);
+            self
+        }
+
+        /// Capture root directory
+        pub fn root(&self) -> &Path {
+            &self.root
+        }
+
+        pub fn write_delta(&mut self, record: BookDeltaRecord) -> Result<()> {
+            self.deltas.write(&self.root, self.row_group_size, record)
+        }
+
+        pub fn write_top_of_book(&mut self, record: TopOfBookRecord) -> Result<()> {
+            self.top_of_book
+                .write(&self.root, self.row_group_size, record)
+        }
+
+        pub fn write_trade(&mut self, record: TradeRecord) -> Result<()> {
+            self.trades.write(&self.root, self.row_group_size, record)
+        }
+
+        pub fn write_price(&mut self, record: PricePointRecord) -> Result<()> {
+            self.prices.write(&self.root, self.row_group_size, record)
+        }
+
+        /// Write every record in `batch`, leaving it empty
+        pub fn write_batch(&mut self, batch: &mut CaptureBatch) -> Result<()> {
+            for record in batch.deltas.drain(..) {
+                self.write_delta(record)?;
+            }
+            for record in batch.top_of_book.drain(..) {
+                self.write_top_of_book(record)?;
+            }
+            for record in batch.trades.drain(..) {
+                self.write_trade(record)?;
+            }
+            Ok(())
+        }
+
+        /// Close all open files so they are readable; later writes start new parts
+        pub fn flush(&mut self) -> Result<()> {
+            self.deltas.close_all()?;
+            self.top_of_book.close_all()?;
+            self.trades.close_all()?;
+            self.prices.close_all()
+        }
+    }
+
+    impl Drop for ParquetCaptureWriter {
+        fn drop(&mut self) {
+            if let Err(e) = self.flush() {
+                warn!("Failed to close capture files: {}", e);
+            }
+        }
+    }
+
+    /// Records a market-data stream to Parquet
+    #[derive(Debug)]
+    pub struct CaptureService {
+        normalizer: CaptureNormalizer,
+        writer: ParquetCaptureWriter,
+        batch: CaptureBatch,
+    }
+
+    impl CaptureService {
+        pub fn new(writer: ParquetCaptureWriter) -> Self {
+            Self {
+                normalizer: CaptureNormalizer::new(),
+                writer,
+                batch: CaptureBatch::default(),
+            }
+        }
+
+        /// Normalize and record a single message
+        pub fn record(&mut self, message: &StreamMessage) -> Result<()> {
+            self.normalizer.normalize(message, &mut self.batch);
+            self.writer.write_batch(&mut self.batch)
+        }
+
+        /// Record a stream until it ends, then close all files.
+        ///
+        /// Stream errors are logged and skipped. File writes happen inline, so
+        /// run this on its own task.
+        pub async fn run<S>(&mut self, mut stream: S) -> Result<()>
+        where
+            S: Stream<Item = Result<StreamMessage>> + Unpin,
+        {
+            while let Some(message) = stream.next().await {
+                match message {
+                    Ok(message) => self.record(&message)?,
+                    Err(e) => warn!("Capture stream error: {}", e),
+                }
+            }
+            self.writer.flush()
+        }
+
+        /// Close all open files so they are readable
+        pub fn flush(&mut self) -> Result<()> {
+            self.writer.flush()
+        }
+    }
+
+    fn collect_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => {
+                return Err(PolyfillError::internal(
+                    format!("Failed to read {}", dir.display()),
+                    e,
+                ))
+            },
+        };
+
+        for entry in entries {
+            let path = entry
+                .map_err(|e| PolyfillError::internal("Failed to read capture directory", e))?
+                .path();
+            if path.is_dir() {
+                collect_files(&path, out)?;
+            } else if path.extension().is_some_and(|ext| ext == "parquet") {
+                out.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Read every record of `T` captured for `token_id` under `root`, in time order
+    pub fn read_dataset<T: CaptureRow>(root: impl AsRef<Path>, token_id: &str) -> Result<Vec<T>> {
+        let dir = root
+            .as_ref()
+            .join(T::DATASET.dir_name())
+            .join(format!("token_id={}", sanitize(token_id)));
+        let mut files = Vec::new();
+        collect_files(&dir, &mut files)?;
+        files.sort();
+
+        let mut rows = Vec::new();
+        for path in files {
+            let file = File::open(&path).map_err(|e| {
+                PolyfillError::internal(format!("Failed to open {}", path.display()), e)
+            })?;
+            let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+
+            let version = builder
+                .metadata()
+                .file_metadata()
+                .key_value_metadata()
+                .and_then(|kv| kv.iter().find(|kv| kv.key == SCHEMA_VERSION_KEY))
+                .and_then(|kv| kv.value.as_deref())
+                .and_then(|v| v.parse::<u32>().ok())
+                .unwrap_or(0);
+            if version > CAPTURE_SCHEMA_VERSION {
+                return Err(PolyfillError::config(format!(
+                    "{} uses capture schema v{version}, newer than supported v{CAPTURE_SCHEMA_VERSION}",
+                    path.display()
+                )));
+            }
+
+            for batch in builder.build()? {
+                rows.extend(T::from_batch(&batch?)?);
+            }
+        }
+
+        rows.sort_by_key(|row| row.timestamp_ms());
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn book_and_change() -> Vec<StreamMessage> {
+        crate::decode::parse_stream_messages(
+            r#"[{"event_type":"book","asset_id":"1","market":"0xabc","timestamp":"1700000000000","bids":[{"price":"0.48","size":"10"},{"price":"0.49","size":"5"}],"asks":[{"price":"0.51","size":"7"}]},
+               {"event_type":"price_change","market":"0xabc","timestamp":"1700000001000","price_changes":[{"asset_id":"1","price":"0.49","size":"0","side":"BUY","best_bid":"0.48","best_ask":"0.51"}]},
+               {"event_type":"last_trade_price","asset_id":"1","market":"0xabc","price":"0.51","side":"BUY","size":"3","timestamp":"1700000002000"}]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_normalizer_produces_records() {
+        let mut normalizer = CaptureNormalizer::new();
+        let mut batch = CaptureBatch::default();
+        for message in book_and_change() {
+            normalizer.normalize(&message, &mut batch);
+        }
+
+        assert_eq!(batch.deltas.len(), 4);
+        assert!(batch.deltas[..3]
+            .iter()
+            .all(|d| d.kind == BookEventKind::Snapshot && d.sequence == 1));
+        assert_eq!(batch.deltas[3].kind, BookEventKind::Delta);
+        assert_eq!(batch.deltas[3].sequence, 2);
+        assert_eq!(batch.deltas[3].size, dec!(0));
+
+        assert_eq!(batch.top_of_book.len(), 2);
+        assert_eq!(batch.top_of_book[0].best_bid, Some(dec!(0.49)));
+        assert_eq!(batch.top_of_book[0].best_ask, Some(dec!(0.51)));
+
+        assert_eq!(batch.trades.len(), 1);
+        assert_eq!(batch.trades[0].size, Some(dec!(3)));
+        assert_eq!(batch.len(), 7);
+    }
+
+    #[test]
+    fn test_candles_from_trades() {
+        let trade = |ts: u64, price: Decimal, size: Option<Decimal>| TradeRecord {
+            token_id: "1".to_string(),
+            timestamp_ms: ts,
+            trade_id: None,
+            side: None,
+            price,
+            size,
+        };
+        let trades = vec![
+            trade(61_000, dec!(0.52), Some(dec!(1))),
+            trade(1_000, dec!(0.50), Some(dec!(2))),
+            trade(30_000, dec!(0.55), None),
+            trade(59_000, dec!(0.49), Some(dec!(4))),
+        ];
+
+        let candles = Candle::from_trades(&trades, std::time::Duration::from_secs(60));
+        assert_eq!(candles.len(), 2);
+        assert_eq!(candles[0].start_ms, 0);
+        assert_eq!(candles[0].open, dec!(0.50));
+        assert_eq!(candles[0].high, dec!(0.55));
+        assert_eq!(candles[0].low, dec!(0.49));
+        assert_eq!(candles[0].close, dec!(0.49));
+        assert_eq!(candles[0].volume, dec!(6));
+        assert_eq!(candles[0].trade_count, 3);
+        assert_eq!(candles[1].start_ms, 60_000);
+        assert_eq!(candles[1].trade_count, 1);
+    }
+
+    #[cfg(feature = "capture")]
+    #[test]
+    fn test_parquet_round_trip_with_hourly_partitions() {
+        let root = std::env::temp_dir().join(format!("polyfill-capture-{}", uuid::Uuid::new_v4()));
+        let mut service = CaptureService::new(ParquetCaptureWriter::new(&root));
+        for message in book_and_change() {
+            service.record(&message).unwrap();
+        }
+        // Next hour goes to a new partition
+        service
+            .record(&StreamMessage::LastTradePrice(
+                crate::types::LastTradePrice {
+                    asset_id: "1".to_string(),
+                    market: "0xabc".to_string(),
+                    price: dec!(0.52),
+                    side: None,
+                    size: None,
+                    fee_rate_bps: None,
+                    timestamp: 1_700_003_600_000,
+                    extra: Default::default(),
+                },
+            ))
+            .unwrap();
+        service.flush().unwrap();
+
+        let deltas: Vec<BookDeltaRecord> = read_dataset(&root, "1").unwrap();
+        assert_eq!(deltas.len(), 4);
+        assert_eq!(deltas[0].price, dec!(0.48));
+        assert_eq!(deltas[3].kind, BookEventKind::Delta);
+
+        let tops: Vec<TopOfBookRecord> = read_dataset(&root, "1").unwrap();
+        assert_eq!(tops.len(), 2);
+
+        let trades: Vec<TradeRecord> = read_dataset(&root, "1").unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].side, Some(Side::BUY));
+        assert_eq!(trades[1].size, None);
+
+        let trade_hours = std::fs::read_dir(root.join("trades/token_id=1/date=2023-11-14"))
+            .unwrap()
+            .count();
+        assert_eq!(trade_hours, 2);
+
+        assert!(read_dataset::<TradeRecord>(&root, "unknown")
+            .unwrap()
+            .is_empty());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,687 @@
+//! On-chain transaction management
+//!
+//! Approvals, transfers and redemptions are plain contract calls, but on
+//! Polygon they routinely sit in the mempool when the base fee spikes.
+//! [`TxManager`] sends them with EIP-1559 fees derived from recent blocks,
+//! hands out nonces locally so back-to-back calls don't collide, replaces a
+//! transaction with bumped fees when it stays unmined, and waits for the
+//! configured number of confirmations. Every step is broadcast as a
+//! [`TxEvent`].
+//!
+//! Chain state is read over JSON-RPC with [`RpcClient`]. Signing is left to a
+//! [`TransactionSender`] wrapping the bot's wallet.
+
+use crate::errors::{PolyfillError, Result};
+use crate::redeem::{RedeemCall, RedemptionSender};
+use alloy_primitives::{Address, U256};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Unsigned contract call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainTransaction {
+    pub from: Address,
+    pub to: Address,
+    pub data: Vec<u8>,
+}
+
+/// Nonce, gas and fees a transaction is sent with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxParams {
+    pub nonce: u64,
+    pub gas_limit: u64,
+    pub fees: FeeEstimate,
+}
+
+/// Signs and broadcasts transactions
+pub trait TransactionSender: Send + Sync {
+    /// Sign `transaction` as an EIP-1559 transaction with `params` and
+    /// broadcast it, returning the transaction hash
+    fn send_transaction(
+        &self,
+        transaction: &ChainTransaction,
+        params: &TxParams,
+    ) -> impl Future<Output = Result<String>> + Send;
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponse {
+    /// `null` is a valid result, e.g. for an unmined receipt
+    #[serde(default)]
+    result: Value,
+    #[serde(default)]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FeeHistory {
+    base_fee_per_gas: Vec<U256>,
+    #[serde(default)]
+    reward: Vec<Vec<U256>>,
+}
+
+/// Mined transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxReceipt {
+    pub tx_hash: String,
+    pub block_number: u64,
+    /// Whether the call succeeded
+    pub success: bool,
+}
+
+/// Minimal JSON-RPC client
+#[derive(Debug, Clone)]
+pub struct RpcClient {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.into(),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Call `method` and return its `result`
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let body = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+        let response = self.http.post(&self.url).json(&body).send().await?;
+        if !response.status().is_success() {
+            return Err(
+                PolyfillError::api_from_response("POST", response, "RPC request failed").await,
+            );
+        }
+        let response: RpcResponse = response
+            .json()
+            .await
+            .map_err(|e| PolyfillError::parse(format!("Invalid RPC response: {e}"), None))?;
+        if let Some(error) = response.error {
+            return Err(PolyfillError::api(
+                400,
+                format!("{method} failed ({}): {}", error.code, error.message),
+            ));
+        }
+        Ok(response.result)
+    }
+
+    /// Call a method whose result is a hex quantity
+    pub async fn quantity(&self, method: &str, params: Value) -> Result<U256> {
+        let result = self.call(method, params).await?;
+        parse_quantity(method, &result)
+    }
+
+    pub async fn block_number(&self) -> Result<u64> {
+        to_u64(
+            "eth_blockNumber",
+            self.quantity("eth_blockNumber", json!([])).await?,
+        )
+    }
+
+    /// Next nonce of `address`, counting pending transactions
+    pub async fn pending_nonce(&self, address: Address) -> Result<u64> {
+        let nonce = self
+            .quantity("eth_getTransactionCount", json!([address, "pending"]))
+            .await?;
+        to_u64("eth_getTransactionCount", nonce)
+    }
+
+    /// Receipt of `tx_hash`, or `None` while it is unmined
+    pub async fn receipt(&self, tx_hash: &str) -> Result<Option<TxReceipt>> {
+        let result = self
+            .call("eth_getTransactionReceipt", json!([tx_hash]))
+            .await?;
+        if result.is_null() {
+            return Ok(None);
+        }
+        let block_number = to_u64(
+            "eth_getTransactionReceipt",
+            parse_quantity("eth_getTransactionReceipt", &result["blockNumber"])?,
+        )?;
+        let status = parse_quantity("eth_getTransactionReceipt", &result["status"])?;
+        Ok(Some(TxReceipt {
+            tx_hash: tx_hash.to_string(),
+            block_number,
+            success: status == U256::from(1),
+        }))
+    }
+
+    async fn fee_history(&self, blocks: u64, percentile: u8) -> Result<FeeHistory> {
+        let result = self
+            .call(
+                "eth_feeHistory",
+                json!([format!("{blocks:#x}"), "latest", [percentile]]),
+            )
+            .await?;
+        serde_json::from_value(result)
+            .map_err(|e| PolyfillError::parse(format!("Invalid fee history: {e}"), None))
+    }
+}
+
+fn parse_quantity(method: &str, value: &Value) -> Result<U256> {
+    let hex = value
+        .as_str()
+        .ok_or_else(|| PolyfillError::parse(format!("{method} returned {value}"), None))?;
+    U256::from_str(hex)
+        .map_err(|e| PolyfillError::parse(format!("{method} returned '{hex}': {e}"), None))
+}
+
+fn to_u64(method: &str, value: U256) -> Result<u64> {
+    u64::try_from(value)
+        .map_err(|_| PolyfillError::parse(format!("{method} returned {value}"), None))
+}
+
+/// EIP-1559 fee caps in wei
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeEstimate {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+impl FeeEstimate {
+    /// Fees raised by `percent`, at least matching `floor`
+    fn bumped(&self, percent: u64, floor: &FeeEstimate) -> Self {
+        let bump = |fee: U256| fee * U256::from(percent) / U256::from(100);
+        Self {
+            max_fee_per_gas: bump(self.max_fee_per_gas).max(floor.max_fee_per_gas),
+            max_priority_fee_per_gas: bump(self.max_priority_fee_per_gas)
+                .max(floor.max_priority_fee_per_gas),
+        }
+    }
+}
+
+/// Tuning for [`TxManager`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxConfig {
+    /// Blocks of fee history sampled per estimate
+    pub fee_history_blocks: u64,
+    /// Priority fee percentile taken from recent blocks
+    pub priority_percentile: u8,
+    /// Lowest priority fee used; Polygon validators ignore less than 25 gwei
+    pub min_priority_fee: U256,
+    /// `max_fee_per_gas` allows the next base fee to grow by this percentage
+    pub base_fee_multiplier: u64,
+    /// Fees are never raised above this
+    pub max_fee_cap: U256,
+    /// Fee increase of a replacement in percent; nodes require at least 110
+    pub replacement_bump: u64,
+    /// Replace a transaction unmined for this long
+    pub stuck_after: Duration,
+    pub max_replacements: u32,
+    /// Blocks on top of the inclusion block before a receipt is final
+    pub confirmations: u64,
+    pub poll_interval: Duration,
+    /// Give up waiting after this long
+    pub timeout: Duration,
+}
+
+impl Default for TxConfig {
+    fn default() -> Self {
+        let gwei = U256::from(1_000_000_000u64);
+        Self {
+            fee_history_blocks: 10,
+            priority_percentile: 50,
+            min_priority_fee: U256::from(30) * gwei,
+            base_fee_multiplier: 200,
+            max_fee_cap: U256::from(2_000) * gwei,
+            replacement_bump: 125,
+            stuck_after: Duration::from_secs(60),
+            max_replacements: 5,
+            confirmations: 3,
+            poll_interval: Duration::from_secs(2),
+            timeout: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+/// Lifecycle of a managed transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxEvent {
+    Submitted {
+        tx_hash: String,
+        params: TxParams,
+    },
+    /// Re-sent with the same nonce and higher fees
+    Replaced {
+        old_hash: String,
+        tx_hash: String,
+        params: TxParams,
+    },
+    Confirmed {
+        receipt: TxReceipt,
+        confirmations: u64,
+    },
+    /// Mined, but the call reverted
+    Reverted {
+        receipt: TxReceipt,
+    },
+    TimedOut {
+        tx_hash: String,
+        nonce: u64,
+    },
+}
+
+/// Transaction sent but not yet confirmed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingTx {
+    pub transaction: ChainTransaction,
+    pub params: TxParams,
+    /// Every hash sent under this nonce, latest last
+    pub hashes: Vec<String>,
+}
+
+/// Sends transactions and sees them through to confirmation
+pub struct TxManager<S> {
+    rpc: RpcClient,
+    sender: S,
+    from: Address,
+    config: TxConfig,
+    next_nonce: Mutex<Option<u64>>,
+    events: broadcast::Sender<TxEvent>,
+}
+
+impl<S> std::fmt::Debug for TxManager<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TxManager")
+            .field("from", &self.from)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl<S: TransactionSender> TxManager<S> {
+    /// Manager for transactions `sender` signs as `from`
+    pub fn new(rpc: RpcClient, sender: S, from: Address) -> Self {
+        let (events, _) = broadcast::channel(256);
+        Self {
+            rpc,
+            sender,
+            from,
+            config: TxConfig::default(),
+            next_nonce: Mutex::new(None),
+            events,
+        }
+    }
+
+    pub fn with_config(mut self, config: TxConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TxEvent> {
+        self.events.subscribe()
+    }
+
+    fn emit(&self, event: TxEvent) {
+        let _ = self.events.send(event);
+    }
+
+    /// Fees for inclusion in the next few blocks
+    pub async fn estimate_fees(&self) -> Result<FeeEstimate> {
+        let history = self
+            .rpc
+            .fee_history(
+                self.config.fee_history_blocks,
+                self.config.priority_percentile,
+            )
+            .await?;
+        // The last entry is the base fee of the next block
+        let base_fee = history.base_fee_per_gas.last().copied().unwrap_or_default();
+        let mut rewards: Vec<U256> = history
+            .reward
+            .iter()
+            .filter_map(|block| block.first().copied())
+            .collect();
+        rewards.sort_unstable();
+        let priority = rewards
+            .get(rewards.len() / 2)
+            .copied()
+            .unwrap_or_default()
+            .max(self.config.min_priority_fee);
+        let max_fee =
+            base_fee * U256::from(self.config.base_fee_multiplier) / U256::from(100) + priority;
+        Ok(FeeEstimate {
+            max_fee_per_gas: max_fee.min(self.config.max_fee_cap),
+            max_priority_fee_per_gas: priority.min(self.config.max_fee_cap),
+        })
+    }
+
+    /// Reserve the next nonce, starting from the chain's pending count
+    pub async fn next_nonce(&self) -> Result<u64> {
+        let chain = self.rpc.pending_nonce(self.from).await?;
+        let mut next = self.next_nonce.lock();
+        let nonce = next.map_or(chain, |local| local.max(chain));
+        *next = Some(nonce + 1);
+        Ok(nonce)
+    }
+
+    /// Forget locally reserved nonces, e.g. after transactions were sent
+    /// from the same wallet elsewhere
+    pub fn reset_nonce(&self) {
+        *self.next_nonce.lock() = None;
+    }
+
+    /// Send `transaction` with fresh fees and the next nonce
+    pub async fn submit(
+        &self,
+        transaction: &ChainTransaction,
+        gas_limit: u64,
+    ) -> Result<PendingTx> {
+        if transaction.from != self.from {
+            return Err(PolyfillError::validation(format!(
+                "Transaction from {} sent through the manager for {}",
+                transaction.from, self.from
+            )));
+        }
+        let fees = self.estimate_fees().await?;
+        let params = TxParams {
+            nonce: self.next_nonce().await?,
+            gas_limit,
+            fees,
+        };
+        let tx_hash = match self.sender.send_transaction(transaction, &params).await {
+            Ok(tx_hash) => tx_hash,
+            Err(e) => {
+                // The nonce may not have been used; refetch next time
+                self.reset_nonce();
+                return Err(e);
+            },
+        };
+        info!("Submitted {} with nonce {}", tx_hash, params.nonce);
+        self.emit(TxEvent::Submitted {
+            tx_hash: tx_hash.clone(),
+            params,
+        });
+        Ok(PendingTx {
+            transaction: transaction.clone(),
+            params,
+            hashes: vec![tx_hash],
+        })
+    }
+
+    /// Wait until `pending` is mined and confirmed, replacing it with bumped
+    /// fees whenever it stays unmined for [`TxConfig::stuck_after`]
+    pub async fn confirm(&self, mut pending: PendingTx) -> Result<TxReceipt> {
+        let started = Instant::now();
+        let mut last_sent = Instant::now();
+        let mut replacements = 0;
+        loop {
+            if let Some(receipt) = self.find_receipt(&pending).await? {
+                return self.await_confirmations(receipt).await;
+            }
+            if started.elapsed() >= self.config.timeout {
+                let tx_hash = pending.hashes.last().cloned().unwrap_or_default();
+                self.emit(TxEvent::TimedOut {
+                    tx_hash: tx_hash.clone(),
+                    nonce: pending.params.nonce,
+                });
+                return Err(PolyfillError::timeout(
+                    self.config.timeout,
+                    format!("confirmation of {tx_hash}"),
+                ));
+            }
+            if last_sent.elapsed() >= self.config.stuck_after
+                && replacements < self.config.max_replacements
+            {
+                if self.replace(&mut pending).await? {
+                    replacements += 1;
+                }
+                last_sent = Instant::now();
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+        }
+    }
+
+    /// Submit `transaction` and wait for it to be confirmed
+    pub async fn send_and_confirm(
+        &self,
+        transaction: &ChainTransaction,
+        gas_limit: u64,
+    ) -> Result<TxReceipt> {
+        let pending = self.submit(transaction, gas_limit).await?;
+        self.confirm(pending).await
+    }
+
+    async fn find_receipt(&self, pending: &PendingTx) -> Result<Option<TxReceipt>> {
+        // Any of the replacements may be the one that got mined
+        for tx_hash in pending.hashes.iter().rev() {
+            if let Some(receipt) = self.rpc.receipt(tx_hash).await? {
+                return Ok(Some(receipt));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Re-send with bumped fees; `false` if fees are already at the cap
+    async fn replace(&self, pending: &mut PendingTx) -> Result<bool> {
+        let fees = pending
+            .params
+            .fees
+            .bumped(self.config.replacement_bump, &self.estimate_fees().await?);
+        if fees.max_fee_per_gas > self.config.max_fee_cap {
+            debug!(
+                "Not replacing nonce {}: fee cap reached",
+                pending.params.nonce
+            );
+            return Ok(false);
+        }
+        let params = TxParams {
+            fees,
+            ..pending.params
+        };
+        let old_hash = pending.hashes.last().cloned().unwrap_or_default();
+        let tx_hash = match self
+            .sender
+            .send_transaction(&pending.transaction, &params)
+            .await
+        {
+            Ok(tx_hash) => tx_hash,
+            Err(e) => {
+                // Commonly the original was mined in the meantime
+                warn!("Replacing {} failed: {}", old_hash, e);
+                return Ok(false);
+            },
+        };
+        info!("Replaced {} with {}", old_hash, tx_hash);
+        pending.params = params;
+        pending.hashes.push(tx_hash.clone());
+        self.emit(TxEvent::Replaced {
+            old_hash,
+            tx_hash,
+            params,
+        });
+        Ok(true)
+    }
+
+    async fn await_confirmations(&self, receipt: TxReceipt) -> Result<TxReceipt> {
+        if !receipt.success {
+            self.emit(TxEvent::Reverted {
+                receipt: receipt.clone(),
+            });
+            return Err(PolyfillError::api(
+                400,
+                format!("Transaction {} reverted", receipt.tx_hash),
+            ));
+        }
+        loop {
+            let head = self.rpc.block_number().await?;
+            let confirmations = (head + 1).saturating_sub(receipt.block_number);
+            if confirmations >= self.config.confirmations {
+                self.emit(TxEvent::Confirmed {
+                    receipt: receipt.clone(),
+                    confirmations,
+                });
+                return Ok(receipt);
+            }
+            tokio::time::sleep(self.config.poll_interval).await;
+        }
+    }
+}
+
+/// Redemptions sent through a manager are replaced while stuck and only
+/// reported once confirmed
+impl<S: TransactionSender> RedemptionSender for TxManager<S> {
+    async fn send_redemption(&self, call: &RedeemCall) -> Result<String> {
+        let transaction = ChainTransaction {
+            from: self.from,
+            to: call.to,
+            data: call.data.clone(),
+        };
+        let gas = self
+            .rpc
+            .quantity(
+                "eth_estimateGas",
+                json!([{
+                    "from": transaction.from,
+                    "to": transaction.to,
+                    "data": format!("0x{}", alloy_primitives::hex::encode(&transaction.data)),
+                }]),
+            )
+            .await?;
+        let gas_limit = to_u64("eth_estimateGas", gas)?.saturating_mul(120) / 100;
+        Ok(self
+            .send_and_confirm(&transaction, gas_limit)
+            .await?
+            .tx_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    const FROM: Address = Address::repeat_byte(0x11);
+
+    struct CountingSender(Mutex<Vec<TxParams>>);
+
+    impl TransactionSender for CountingSender {
+        async fn send_transaction(
+            &self,
+            _transaction: &ChainTransaction,
+            params: &TxParams,
+        ) -> Result<String> {
+            let mut sent = self.0.lock();
+            sent.push(*params);
+            Ok(format!("0x{:02x}", sent.len()))
+        }
+    }
+
+    async fn mock_rpc(server: &mut Server, matcher: Value, result: Value) {
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(matcher))
+            .with_header("content-type", "application/json")
+            .with_body(json!({"jsonrpc": "2.0", "id": 1, "result": result}).to_string())
+            .create_async()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_stuck_transaction_is_replaced_and_confirmed() {
+        let mut server = Server::new_async().await;
+        // Base fee 100 gwei next block, 40 gwei median tip
+        mock_rpc(
+            &mut server,
+            json!({"method": "eth_feeHistory"}),
+            json!({
+                "baseFeePerGas": ["0x174876e800", "0x174876e800"],
+                "reward": [["0x9502f9000"]]
+            }),
+        )
+        .await;
+        mock_rpc(
+            &mut server,
+            json!({"method": "eth_getTransactionCount"}),
+            json!("0x7"),
+        )
+        .await;
+        mock_rpc(
+            &mut server,
+            json!({"method": "eth_getTransactionReceipt", "params": ["0x01"]}),
+            Value::Null,
+        )
+        .await;
+        mock_rpc(
+            &mut server,
+            json!({"method": "eth_getTransactionReceipt", "params": ["0x02"]}),
+            json!({"blockNumber": "0x10", "status": "0x1"}),
+        )
+        .await;
+        mock_rpc(
+            &mut server,
+            json!({"method": "eth_blockNumber"}),
+            json!("0x12"),
+        )
+        .await;
+
+        let manager = TxManager::new(
+            RpcClient::new(server.url()),
+            CountingSender(Mutex::new(Vec::new())),
+            FROM,
+        )
+        .with_config(TxConfig {
+            stuck_after: Duration::ZERO,
+            poll_interval: Duration::from_millis(5),
+            ..TxConfig::default()
+        });
+        let mut events = manager.subscribe();
+
+        let transaction = ChainTransaction {
+            from: FROM,
+            to: Address::repeat_byte(0x22),
+            data: vec![1, 2, 3],
+        };
+        let receipt = manager
+            .send_and_confirm(&transaction, 50_000)
+            .await
+            .unwrap();
+        assert_eq!(receipt.tx_hash, "0x02");
+
+        let sent = manager.sender.0.lock().clone();
+        assert_eq!(sent.len(), 2);
+        let gwei = U256::from(1_000_000_000u64);
+        assert_eq!(sent[0].nonce, 7);
+        assert_eq!(sent[0].fees.max_priority_fee_per_gas, U256::from(40) * gwei);
+        assert_eq!(sent[0].fees.max_fee_per_gas, U256::from(240) * gwei);
+        assert_eq!(sent[1].nonce, 7);
+        assert_eq!(sent[1].fees.max_fee_per_gas, U256::from(300) * gwei);
+
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            TxEvent::Submitted { .. }
+        ));
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            TxEvent::Replaced { .. }
+        ));
+        assert_eq!(
+            events.recv().await.unwrap(),
+            TxEvent::Confirmed {
+                receipt,
+                confirmations: 3,
+            }
+        );
+        // Nonces are handed out locally after the first lookup
+        assert_eq!(manager.next_nonce().await.unwrap(), 8);
+    }
+}
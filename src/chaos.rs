@@ -0,0 +1,593 @@
+//! Fault injection for REST and WebSocket transports
+//!
+//! [`ChaosClob`] wraps any [`ClobApi`] and [`ChaosStream`] wraps any
+//! [`MarketStream`], injecting latency spikes, 5xx and 429 bursts, dropped
+//! frames and truncated JSON according to a [`ChaosConfig`]. Faults are drawn
+//! from a seedable RNG, so a failing scenario can be replayed exactly while
+//! retry, resync and kill-switch logic is hardened against it.
+//!
+//! Every probability defaults to zero, so a default config is a pass-through.
+
+use crate::api::ClobApi;
+use crate::errors::{PolyfillError, Result};
+use crate::sim::SimRng;
+use crate::stream::{MarketStream, StreamStats};
+use crate::types::{
+    CancelOrdersResponse, CreateOrderOptions, Market, MidpointResponse, OpenOrder, OpenOrderParams,
+    OrderArgs, OrderBookSummary, PostOrderOptions, PostOrderResponse, PriceResponse, Side,
+    SpreadResponse, StreamMessage, Subscription, TokenId, TradeParams,
+};
+use futures::{ready, Stream, StreamExt};
+use parking_lot::Mutex;
+use rand::Rng;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Fault probabilities and shapes; all faults are disabled by default
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    /// Probability that a request or frame is delayed by `latency_spike`
+    pub latency_spike_probability: f64,
+    pub latency_spike: Duration,
+    /// Probability that a REST call starts a burst of 5xx responses
+    pub server_error_probability: f64,
+    /// Status returned during a server error burst
+    pub server_error_status: u16,
+    /// Probability that a REST call starts a burst of 429 responses
+    pub rate_limit_probability: f64,
+    /// Number of consecutive calls that fail once a burst starts
+    pub burst_length: u32,
+    /// Probability that a WebSocket frame is silently dropped
+    pub drop_probability: f64,
+    /// Probability that a response or frame arrives as truncated JSON
+    pub truncate_probability: f64,
+    /// RNG seed; `None` draws faults from OS entropy
+    pub seed: Option<u64>,
+}
+
+impl Default for ChaosConfig {
+    fn default() -> Self {
+        Self {
+            latency_spike_probability: 0.0,
+            latency_spike: Duration::from_millis(500),
+            server_error_probability: 0.0,
+            server_error_status: 503,
+            rate_limit_probability: 0.0,
+            burst_length: 3,
+            drop_probability: 0.0,
+            truncate_probability: 0.0,
+            seed: None,
+        }
+    }
+}
+
+impl ChaosConfig {
+    pub fn with_latency_spikes(mut self, probability: f64, spike: Duration) -> Self {
+        self.latency_spike_probability = probability;
+        self.latency_spike = spike;
+        self
+    }
+
+    pub fn with_server_errors(mut self, probability: f64, status: u16) -> Self {
+        self.server_error_probability = probability;
+        self.server_error_status = status;
+        self
+    }
+
+    pub fn with_rate_limits(mut self, probability: f64) -> Self {
+        self.rate_limit_probability = probability;
+        self
+    }
+
+    pub fn with_burst_length(mut self, burst_length: u32) -> Self {
+        self.burst_length = burst_length.max(1);
+        self
+    }
+
+    pub fn with_drops(mut self, probability: f64) -> Self {
+        self.drop_probability = probability;
+        self
+    }
+
+    pub fn with_truncation(mut self, probability: f64) -> Self {
+        self.truncate_probability = probability;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Counts of injected faults
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChaosStats {
+    pub latency_spikes: u64,
+    pub server_errors: u64,
+    pub rate_limited: u64,
+    pub dropped_frames: u64,
+    pub truncated: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RestFault {
+    ServerError,
+    RateLimited,
+    Truncated,
+}
+
+#[derive(Debug)]
+struct ChaosState {
+    rng: SimRng,
+    /// Active burst and the number of calls it still covers
+    burst: Option<(RestFault, u32)>,
+    stats: ChaosStats,
+}
+
+/// Shared fault source for both wrappers
+#[derive(Debug)]
+struct Injector {
+    config: ChaosConfig,
+    state: Mutex<ChaosState>,
+}
+
+impl Injector {
+    fn new(config: ChaosConfig) -> Self {
+        let rng = config
+            .seed
+            .map(SimRng::from_seed)
+            .unwrap_or_else(SimRng::from_entropy);
+        Self {
+            config,
+            state: Mutex::new(ChaosState {
+                rng,
+                burst: None,
+                stats: ChaosStats::default(),
+            }),
+        }
+    }
+
+    fn roll(state: &mut ChaosState, probability: f64) -> bool {
+        probability > 0.0 && state.rng.gen_bool(probability.min(1.0))
+    }
+
+    fn latency(&self, state: &mut ChaosState) -> Option<Duration> {
+        if Self::roll(state, self.config.latency_spike_probability) {
+            state.stats.latency_spikes += 1;
+            Some(self.config.latency_spike)
+        } else {
+            None
+        }
+    }
+
+    /// Decide the delay and fault for the next REST call
+    fn plan_request(&self) -> (Option<Duration>, Option<RestFault>) {
+        let mut state = self.state.lock();
+        let delay = self.latency(&mut state);
+
+        let fault = match state.burst.take() {
+            Some((fault, remaining)) => {
+                if remaining > 1 {
+                    state.burst = Some((fault, remaining - 1));
+                }
+                Some(fault)
+            },
+            None => {
+                let fault = if Self::roll(&mut state, self.config.server_error_probability) {
+                    Some(RestFault::ServerError)
+                } else if Self::roll(&mut state, self.config.rate_limit_probability) {
+                    Some(RestFault::RateLimited)
+                } else {
+                    None
+                };
+                if let Some(fault) = fault {
+                    if self.config.burst_length > 1 {
+                        state.burst = Some((fault, self.config.burst_length - 1));
+                    }
+                }
+                fault.or_else(|| {
+                    Self::roll(&mut state, self.config.truncate_probability)
+                        .then_some(RestFault::Truncated)
+                })
+            },
+        };
+
+        match fault {
+            Some(RestFault::ServerError) => state.stats.server_errors += 1,
+            Some(RestFault::RateLimited) => state.stats.rate_limited += 1,
+            Some(RestFault::Truncated) => state.stats.truncated += 1,
+            None => {},
+        }
+        (delay, fault)
+    }
+
+    fn stats(&self) -> ChaosStats {
+        self.state.lock().stats.clone()
+    }
+}
+
+/// Parse error produced by the real decoder for a body cut off mid-object
+fn truncated_response_error() -> PolyfillError {
+    match crate::decode::fast_parse::parse_json_fast_owned::<serde_json::Value>(
+        br#"{"data":[{"price":"0.5"#,
+    ) {
+        Err(e) => e,
+        Ok(_) => PolyfillError::parse("Truncated JSON response", None),
+    }
+}
+
+/// Parse error for a WebSocket frame cut off halfway through
+fn truncated_frame_error(message: &StreamMessage) -> PolyfillError {
+    let text = serde_json::to_string(message).unwrap_or_default();
+    let mut cut = text.len() / 2;
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    match crate::decode::parse_stream_messages(&text[..cut]) {
+        Err(e) => e,
+        Ok(_) => PolyfillError::parse("Truncated JSON frame", None),
+    }
+}
+
+/// [`ClobApi`] wrapper that injects REST faults.
+///
+/// 5xx and 429 faults fail the call without reaching the inner API.
+/// Truncation is applied to the response after the inner call completes, so
+/// side effects such as a posted order still happen, as they would when a
+/// real response is cut off in transit.
+#[derive(Debug)]
+pub struct ChaosClob<A> {
+    inner: A,
+    injector: Injector,
+}
+
+impl<A: ClobApi> ChaosClob<A> {
+    pub fn new(inner: A, config: ChaosConfig) -> Self {
+        Self {
+            inner,
+            injector: Injector::new(config),
+        }
+    }
+
+    pub fn inner(&self) -> &A {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> A {
+        self.inner
+    }
+
+    /// Faults injected so far
+    pub fn stats(&self) -> ChaosStats {
+        self.injector.stats()
+    }
+
+    async fn call<T, F>(&self, request: F) -> Result<T>
+    where
+        F: Future<Output = Result<T>>,
+    {
+        let (delay, fault) = self.injector.plan_request();
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+
+        match fault {
+            Some(RestFault::ServerError) => Err(PolyfillError::api(
+                self.injector.config.server_error_status,
+                "Injected server error",
+            )),
+            Some(RestFault::RateLimited) => Err(PolyfillError::api(429, "Injected rate limit")),
+            Some(RestFault::Truncated) => {
+                request.await?;
+                Err(truncated_response_error())
+            },
+            None => request.await,
+        }
+    }
+}
+
+impl<A: ClobApi> ClobApi for ChaosClob<A> {
+    async fn get_server_time(&self) -> Result<u64> {
+        self.call(self.inner.get_server_time()).await
+    }
+
+    async fn get_order_book(&self, token_id: &str) -> Result<OrderBookSummary> {
+        self.call(self.inner.get_order_book(token_id)).await
+    }
+
+    async fn get_order_books(&self, token_ids: &[String]) -> Result<Vec<OrderBookSummary>> {
+        self.call(self.inner.get_order_books(token_ids)).await
+    }
+
+    async fn get_midpoint(&self, token_id: &str) -> Result<MidpointResponse> {
+        self.call(self.inner.get_midpoint(token_id)).await
+    }
+
+    async fn get_spread(&self, token_id: &str) -> Result<SpreadResponse> {
+        self.call(self.inner.get_spread(token_id)).await
+    }
+
+    async fn get_price(&self, token_id: &str, side: Side) -> Result<PriceResponse> {
+        self.call(self.inner.get_price(token_id, side)).await
+    }
+
+    async fn get_tick_size(&self, token_id: &str) -> Result<Decimal> {
+        self.call(self.inner.get_tick_size(token_id)).await
+    }
+
+    async fn get_neg_risk(&self, token_id: &str) -> Result<bool> {
+        self.call(self.inner.get_neg_risk(token_id)).await
+    }
+
+    async fn get_fee_rate_bps(&self, token_id: &str) -> Result<u32> {
+        self.call(self.inner.get_fee_rate_bps(token_id)).await
+    }
+
+    async fn get_market(&self, condition_id: &str) -> Result<Market> {
+        self.call(self.inner.get_market(condition_id)).await
+    }
+
+    async fn create_and_post_order(
+        &self,
+        order_args: &OrderArgs,
+        create_options: Option<&CreateOrderOptions>,
+        post_options: Option<&PostOrderOptions>,
+    ) -> Result<PostOrderResponse> {
+        self.call(
+            self.inner
+                .create_and_post_order(order_args, create_options, post_options),
+        )
+        .await
+    }
+
+    async fn cancel(&self, order_id: &str) -> Result<CancelOrdersResponse> {
+        self.call(self.inner.cancel(order_id)).await
+    }
+
+    async fn cancel_orders(&self, order_ids: &[String]) -> Result<CancelOrdersResponse> {
+        self.call(self.inner.cancel_orders(order_ids)).await
+    }
+
+    async fn cancel_all(&self) -> Result<CancelOrdersResponse> {
+        self.call(self.inner.cancel_all()).await
+    }
+
+    async fn cancel_condition_orders(&self, condition_id: &str) -> Result<CancelOrdersResponse> {
+        self.call(self.inner.cancel_condition_orders(condition_id))
+            .await
+    }
+
+    async fn cancel_asset_orders(&self, token_id: &TokenId) -> Result<CancelOrdersResponse> {
+        self.call(self.inner.cancel_asset_orders(token_id)).await
+    }
+
+    async fn get_orders(
+        &self,
+        params: Option<&OpenOrderParams>,
+        next_cursor: Option<&str>,
+    ) -> Result<Vec<OpenOrder>> {
+        self.call(self.inner.get_orders(params, next_cursor)).await
+    }
+
+    async fn get_order(&self, order_id: &str) -> Result<OpenOrder> {
+        self.call(self.inner.get_order(order_id)).await
+    }
+
+    async fn get_trades(
+        &self,
+        trade_params: Option<&TradeParams>,
+        next_cursor: Option<&str>,
+    ) -> Result<Vec<Value>> {
+        self.call(self.inner.get_trades(trade_params, next_cursor))
+            .await
+    }
+}
+
+/// [`MarketStream`] wrapper that injects latency, dropped frames and
+/// truncated JSON into the message flow
+#[derive(Debug)]
+pub struct ChaosStream<S> {
+    inner: S,
+    injector: Injector,
+    /// Delayed item and the timer releasing it
+    delayed: Option<(Pin<Box<tokio::time::Sleep>>, Result<StreamMessage>)>,
+}
+
+impl<S: MarketStream + Unpin> ChaosStream<S> {
+    pub fn new(inner: S, config: ChaosConfig) -> Self {
+        Self {
+            inner,
+            injector: Injector::new(config),
+            delayed: None,
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Faults injected so far
+    pub fn chaos_stats(&self) -> ChaosStats {
+        self.injector.stats()
+    }
+}
+
+impl<S: MarketStream + Unpin> Stream for ChaosStream<S> {
+    type Item = Result<StreamMessage>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some((sleep, _)) = self.delayed.as_mut() {
+                ready!(sleep.as_mut().poll(cx));
+                let (_, item) = self.delayed.take().expect("delayed item present");
+                return Poll::Ready(Some(item));
+            }
+
+            let Some(item) = ready!(self.inner.poll_next_unpin(cx)) else {
+                return Poll::Ready(None);
+            };
+
+            let config = &self.injector.config;
+            let mut state = self.injector.state.lock();
+            let item = match item {
+                Ok(message) => {
+                    if Injector::roll(&mut state, config.drop_probability) {
+                        state.stats.dropped_frames += 1;
+                        continue;
+                    }
+                    if Injector::roll(&mut state, config.truncate_probability) {
+                        state.stats.truncated += 1;
+                        Err(truncated_frame_error(&message))
+                    } else {
+                        Ok(message)
+                    }
+                },
+                Err(e) => Err(e),
+            };
+            let delay = self.injector.latency(&mut state);
+            drop(state);
+
+            match delay {
+                Some(delay) => {
+                    self.delayed = Some((Box::pin(tokio::time::sleep(delay)), item));
+                },
+                None => return Poll::Ready(Some(item)),
+            }
+        }
+    }
+}
+
+impl<S: MarketStream + Unpin> MarketStream for ChaosStream<S> {
+    fn subscribe(&mut self, subscription: Subscription) -> Result<()> {
+        self.inner.subscribe(subscription)
+    }
+
+    fn unsubscribe(&mut self, token_ids: &[String]) -> Result<()> {
+        self.inner.unsubscribe(token_ids)
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn get_stats(&self) -> StreamStats {
+        let mut stats = self.inner.get_stats();
+        stats.dropped_messages += self.injector.stats().dropped_frames;
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FakeClob, FakeToken};
+    use crate::stream::MockStream;
+    use crate::types::BookUpdate;
+    use rust_decimal_macros::dec;
+
+    fn fake() -> FakeClob {
+        let fake = FakeClob::new();
+        fake.set_token(
+            "1",
+            FakeToken::new("0xabc").with_book(&[(dec!(0.48), dec!(10))], &[(dec!(0.52), dec!(10))]),
+        );
+        fake
+    }
+
+    fn mock_stream(count: u64) -> MockStream {
+        let mut stream = MockStream::new();
+        for timestamp in 0..count {
+            stream.add_message(StreamMessage::Book(BookUpdate {
+                asset_id: "1".to_string(),
+                market: "0xabc".to_string(),
+                timestamp,
+                bids: vec![],
+                asks: vec![],
+                hash: None,
+                extra: Default::default(),
+            }));
+        }
+        stream
+    }
+
+    #[tokio::test]
+    async fn test_default_config_is_pass_through() {
+        let chaos = ChaosClob::new(fake(), ChaosConfig::default());
+        for _ in 0..20 {
+            assert!(chaos.get_order_book("1").await.is_ok());
+        }
+        assert_eq!(chaos.stats(), ChaosStats::default());
+    }
+
+    #[tokio::test]
+    async fn test_error_bursts_and_truncation() {
+        let config = ChaosConfig::default()
+            .with_rate_limits(1.0)
+            .with_burst_length(3)
+            .with_seed(1);
+        let chaos = ChaosClob::new(fake(), config);
+        for _ in 0..3 {
+            assert!(matches!(
+                chaos.get_tick_size("1").await,
+                Err(PolyfillError::Api { status: 429, .. })
+            ));
+        }
+        assert_eq!(chaos.stats().rate_limited, 3);
+        assert!(chaos.inner().calls().is_empty());
+
+        // Truncated responses still reach the exchange
+        let chaos = ChaosClob::new(fake(), ChaosConfig::default().with_truncation(1.0));
+        let order = OrderArgs::new("1".parse().unwrap(), dec!(0.49), dec!(10), Side::BUY);
+        assert!(matches!(
+            chaos.create_and_post_order(&order, None, None).await,
+            Err(PolyfillError::Parse { .. })
+        ));
+        assert_eq!(chaos.inner().open_orders().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_seeded_faults_are_reproducible() {
+        let config = ChaosConfig::default()
+            .with_server_errors(0.3, 502)
+            .with_burst_length(1)
+            .with_seed(99);
+        let run = |chaos: ChaosClob<FakeClob>| async move {
+            let mut outcomes = Vec::new();
+            for _ in 0..30 {
+                outcomes.push(chaos.get_midpoint("1").await.is_ok());
+            }
+            outcomes
+        };
+        let first = run(ChaosClob::new(fake(), config.clone())).await;
+        let second = run(ChaosClob::new(fake(), config)).await;
+        assert_eq!(first, second);
+        assert!(first.contains(&true) && first.contains(&false));
+    }
+
+    #[tokio::test]
+    async fn test_stream_drops_and_truncates_frames() {
+        let mut stream = ChaosStream::new(mock_stream(10), ChaosConfig::default().with_drops(1.0));
+        assert!(stream.next().await.is_none());
+        assert_eq!(stream.chaos_stats().dropped_frames, 10);
+        assert_eq!(stream.get_stats().dropped_messages, 10);
+
+        let config = ChaosConfig::default()
+            .with_truncation(1.0)
+            .with_latency_spikes(1.0, Duration::from_millis(5));
+        let stream = ChaosStream::new(mock_stream(3), config);
+        let items: Vec<_> = stream.collect().await;
+        assert_eq!(items.len(), 3);
+        assert!(items
+            .iter()
+            .all(|item| matches!(item, Err(PolyfillError::Parse { .. }))));
+    }
+}
@@ -6,12 +6,13 @@
 use crate::auth::{
     create_l1_headers, create_l2_headers, create_l2_headers_with_body_bytes, PreparedApiCredentials,
 };
-use crate::errors::{PolyfillError, Result};
+use crate::compat::{ApiDescriptor, ClobApiVersion, CompatibilityReport, Endpoint};
+use crate::errors::{ApiErrorContext, PolyfillError, Result};
 use crate::http_config::{create_colocated_client, create_internet_client, prewarm_connections};
 use crate::types::{
-    BuilderFeeRateResponse, CancelOrdersResponse, ClientConfig, ClobMarketInfo, CreateOrderOptions,
-    MarketOrderArgs, OrderArgs, OrderType, PostOrder, PostOrderOptions, PostOrderResponse, Side,
-    SignedOrderRequest,
+    BookDepth, BuilderFeeRateResponse, CancelOrdersResponse, ClientConfig, ClobMarketInfo,
+    CreateOrderOptions, MarketOrderArgs, MarketRemainder, OrderArgs, OrderType, PartialMarketOrder,
+    PostOrder, PostOrderOptions, PostOrderResponse, Side, SignedOrderRequest, TokenId,
 };
 use alloy_primitives::{Address, U256};
 use alloy_signer_local::PrivateKeySigner;
@@ -26,6 +27,7 @@ use serde_json::Value;
 use std::net::{IpAddr, SocketAddr};
 use std::str::FromStr;
 use std::time::Duration;
+use tracing::warn;
 
 // Re-export types for compatibility
 pub use crate::types::{ApiCredentials as ApiCreds, MarketOrderArgs as ClientMarketOrderArgs};
@@ -63,10 +65,26 @@ fn polymarket_default_headers() -> HeaderMap {
     headers
 }
 
+/// Tokens fetched at once by [`ClobClient::preload_order_metadata`]
+pub const PRELOAD_CONCURRENCY: usize = 8;
+
+/// Orders accepted per request by the batch endpoint
+pub const MAX_BATCH_ORDERS: usize = 15;
+
+/// Initial size of the per-thread request body buffer
+const BODY_BUFFER_CAPACITY: usize = 4096;
+
+thread_local! {
+    static BODY_BUFFER: std::cell::RefCell<bytes::BytesMut> =
+        std::cell::RefCell::new(bytes::BytesMut::with_capacity(BODY_BUFFER_CAPACITY));
+}
+
 fn build_http_client(
     host: &str,
     timeout: Option<Duration>,
     max_connections: Option<usize>,
+    dns_cache: Option<&crate::dns::DnsCache>,
+    compression: bool,
 ) -> Client {
     let max_connections = max_connections.unwrap_or(10);
     let mut builder = reqwest::ClientBuilder::new()
@@ -76,12 +94,19 @@ fn build_http_client(
         .http2_initial_stream_window_size(512 * 1024)
         .tcp_nodelay(true)
         .pool_max_idle_per_host(max_connections)
-        .pool_idle_timeout(Duration::from_secs(90));
+        .pool_idle_timeout(Duration::from_secs(90))
+        // Sets Accept-Encoding and decodes the body while it is read
+        .gzip(compression)
+        .deflate(compression);
 
     if let Some(timeout) = timeout {
         builder = builder.timeout(timeout);
     }
 
+    if let Some(cache) = dns_cache {
+        builder = builder.dns_resolver(cache.clone());
+    }
+
     if let Ok(resolve_ip) = std::env::var("POLYMARKET_RESOLVE_IP") {
         if let Ok(ip) = resolve_ip.parse::<IpAddr>() {
             if let Some(hostname) = extract_hostname(host) {
@@ -108,17 +133,46 @@ fn extract_hostname(host: &str) -> Option<&str> {
         .filter(|hostname| !hostname.is_empty())
 }
 
-/// Main client for interacting with Polymarket API
+/// Optional component installed after construction, shared by every clone
+type SharedSlot<T> = std::sync::Arc<parking_lot::RwLock<Option<std::sync::Arc<T>>>>;
+
+/// Main client for interacting with Polymarket API.
+///
+/// Cloning is cheap: clones share the connection pool, signer, API
+/// credentials, event handlers, caches and rate limiter, so a client can be
+/// handed to several tasks without a surrounding lock.
+#[derive(Clone)]
 pub struct ClobClient {
     pub http_client: Client,
     pub base_url: String,
     chain_id: u64,
-    signer: Option<PrivateKeySigner>,
-    api_creds: Option<PreparedApiCredentials>,
+    signer: std::sync::Arc<parking_lot::RwLock<Option<std::sync::Arc<PrivateKeySigner>>>>,
+    api_creds: std::sync::Arc<parking_lot::RwLock<Option<std::sync::Arc<PreparedApiCredentials>>>>,
     builder_code: Option<String>,
-    order_builder: Option<crate::orders::OrderBuilder>,
+    order_builder:
+        std::sync::Arc<parking_lot::RwLock<Option<std::sync::Arc<crate::orders::OrderBuilder>>>>,
     #[allow(dead_code)]
     connection_manager: Option<std::sync::Arc<crate::connection_manager::ConnectionManager>>,
+    event_handlers: std::sync::Arc<crate::handlers::EventHandlers>,
+    order_books: SharedSlot<crate::book::OrderBookManager>,
+    balance_cache: std::sync::Arc<crate::balances::BalanceCache>,
+    metadata_cache: std::sync::Arc<crate::metadata::MetadataCache>,
+    reconnect_config: SharedSlot<crate::stream::ReconnectConfig>,
+    ws_connector: SharedSlot<dyn crate::ws_transport::WsConnector>,
+    dns_cache: Option<crate::dns::DnsCache>,
+    rate_limiter: SharedSlot<crate::utils::rate_limit::TokenBucket>,
+    duplicate_guard: SharedSlot<crate::dedup::DuplicateGuard>,
+    retry_policy: SharedSlot<crate::utils::retry::RetryPolicy>,
+    response_cache: SharedSlot<crate::http_cache::ResponseCache>,
+    price_band: SharedSlot<crate::price_band::PriceBand>,
+    resolution_guard: SharedSlot<crate::resolution::ResolutionGuard>,
+    schedule_guard: SharedSlot<crate::schedule::ScheduleGuard>,
+    activity: SharedSlot<crate::activity::ActivityMetrics>,
+    audit: SharedSlot<crate::audit::AuditLog>,
+    run_recorder: SharedSlot<crate::run_report::RunRecorder>,
+    strategy: Option<std::sync::Arc<str>>,
+    user_channels: std::sync::Arc<parking_lot::Mutex<Vec<tokio::task::AbortHandle>>>,
+    api: std::sync::Arc<parking_lot::RwLock<ApiDescriptor>>,
 }
 
 #[derive(Default)]
@@ -157,30 +211,65 @@ impl ClobClient {
             ),
         ));
 
-        let order_builder = auth
-            .signer
-            .clone()
-            .map(|signer| crate::orders::OrderBuilder::new(signer, auth.sig_type, auth.funder));
+        let order_builder = auth.signer.clone().map(|signer| {
+            std::sync::Arc::new(crate::orders::OrderBuilder::new(
+                signer,
+                auth.sig_type,
+                auth.funder,
+            ))
+        });
 
         Self {
             http_client,
             base_url: host.to_string(),
             chain_id,
-            signer: auth.signer,
-            api_creds: auth.api_creds,
+            signer: std::sync::Arc::new(parking_lot::RwLock::new(
+                auth.signer.map(std::sync::Arc::new),
+            )),
+            api_creds: std::sync::Arc::new(parking_lot::RwLock::new(
+                auth.api_creds.map(std::sync::Arc::new),
+            )),
             builder_code: auth.builder_code,
-            order_builder,
+            order_builder: std::sync::Arc::new(parking_lot::RwLock::new(order_builder)),
             connection_manager,
+            event_handlers: std::sync::Arc::new(crate::handlers::EventHandlers::new()),
+            order_books: SharedSlot::default(),
+            balance_cache: std::sync::Arc::new(crate::balances::BalanceCache::default()),
+            metadata_cache: std::sync::Arc::new(crate::metadata::MetadataCache::default()),
+            reconnect_config: SharedSlot::default(),
+            ws_connector: SharedSlot::default(),
+            dns_cache: None,
+            rate_limiter: SharedSlot::default(),
+            duplicate_guard: SharedSlot::default(),
+            retry_policy: SharedSlot::default(),
+            response_cache: SharedSlot::default(),
+            price_band: SharedSlot::default(),
+            resolution_guard: SharedSlot::default(),
+            schedule_guard: SharedSlot::default(),
+            activity: SharedSlot::default(),
+            audit: SharedSlot::default(),
+            run_recorder: SharedSlot::default(),
+            strategy: None,
+            user_channels: std::sync::Arc::default(),
+            api: std::sync::Arc::default(),
         }
     }
 
     /// Create a new client with optimized HTTP/2 settings (benchmarked 11.4% faster)
     /// Connection prewarming is explicit through [`ClobClient::prewarm_connections`].
     pub fn new(host: &str) -> Self {
-        let http_client = build_http_client(host, None, None);
+        let http_client = build_http_client(host, None, None, None, true);
         Self::build_client(host, 137, http_client, ClientAuthConfig::default())
     }
 
+    /// Create a client from config, taking a missing key or API credentials
+    /// from [`CredentialsChain::default`](crate::credentials::CredentialsChain).
+    pub fn from_default_credentials(config: ClientConfig) -> Result<Self> {
+        Self::from_config(
+            config.with_credentials(&crate::credentials::CredentialsChain::default())?,
+        )
+    }
+
     /// Create a V2-native client from config.
     pub fn from_config(config: ClientConfig) -> Result<Self> {
         let signer = match config.private_key.as_deref() {
@@ -212,10 +301,15 @@ impl ClobClient {
             _ => explicit_funder,
         };
 
-        let http_client =
-            build_http_client(&config.base_url, config.timeout, config.max_connections);
+        let http_client = build_http_client(
+            &config.base_url,
+            config.timeout,
+            config.max_connections,
+            config.dns_cache.as_ref(),
+            config.compression,
+        );
 
-        Ok(Self::build_client(
+        let mut client = Self::build_client(
             &config.base_url,
             config.chain,
             http_client,
@@ -229,7 +323,9 @@ impl ClobClient {
                 sig_type,
                 funder,
             },
-        ))
+        );
+        client.dns_cache = config.dns_cache;
+        Ok(client)
     }
 
     /// Create a client optimized for co-located environments
@@ -285,11 +381,81 @@ impl ClobClient {
     }
 
     /// Set API credentials
-    pub fn set_api_creds(&mut self, api_creds: ApiCreds) -> Result<()> {
-        self.api_creds = Some(PreparedApiCredentials::try_new(api_creds)?);
+    ///
+    /// Credentials are shared with every clone of this client.
+    pub fn set_api_creds(&self, api_creds: ApiCreds) -> Result<()> {
+        let prepared = PreparedApiCredentials::try_new(api_creds)?;
+        *self.api_creds.write() = Some(std::sync::Arc::new(prepared));
         Ok(())
     }
 
+    fn prepared_api_creds(&self) -> Option<std::sync::Arc<PreparedApiCredentials>> {
+        self.api_creds.read().clone()
+    }
+
+    /// Replace the API credentials used by subsequent requests, returning
+    /// the previous ones so they can be revoked.
+    ///
+    /// Requests already in flight finish with the credentials they started
+    /// with, and open streams stay connected. The change is seen by every
+    /// clone of this client.
+    pub fn swap_api_creds(&self, api_creds: ApiCreds) -> Result<Option<ApiCreds>> {
+        let prepared = PreparedApiCredentials::try_new(api_creds)?;
+        let previous = self
+            .api_creds
+            .write()
+            .replace(std::sync::Arc::new(prepared));
+        Ok(previous.map(|previous| previous.credentials().clone()))
+    }
+
+    /// Sign subsequent orders and L1 requests with `signer`, returning the
+    /// previous signer's address.
+    ///
+    /// Like [`Self::swap_api_creds`], in-flight requests complete with the
+    /// old key and every clone switches at once. Orders keep their
+    /// signature type; a funder that defaulted to the old signer's address
+    /// moves to the new one.
+    pub fn swap_signer(&self, signer: PrivateKeySigner) -> Result<Option<Address>> {
+        let mut current = self.signer.write();
+        let mut builder = self.order_builder.write();
+        let next = match builder.as_deref() {
+            Some(builder) => builder.clone().with_signer(signer.clone()),
+            None => crate::orders::OrderBuilder::new(signer.clone(), None, None),
+        };
+        *builder = Some(std::sync::Arc::new(next));
+        let previous = current.replace(std::sync::Arc::new(signer));
+        Ok(previous.map(|previous| previous.address()))
+    }
+
+    fn current_signer(&self) -> Option<std::sync::Arc<PrivateKeySigner>> {
+        self.signer.read().clone()
+    }
+
+    fn current_order_builder(&self) -> Option<std::sync::Arc<crate::orders::OrderBuilder>> {
+        self.order_builder.read().clone()
+    }
+
+    /// A clone submitting orders under `strategy`: duplicate checks use the
+    /// strategy's tolerance and the schedule guard its trading schedule.
+    /// Everything else is shared with `self`.
+    pub fn for_strategy(&self, strategy: &str) -> Self {
+        Self {
+            strategy: Some(std::sync::Arc::from(strategy)),
+            ..self.clone()
+        }
+    }
+
+    /// A clone sharing the connection pool and caches but no signer or API
+    /// credentials
+    pub(crate) fn without_credentials(&self) -> Self {
+        Self {
+            signer: Default::default(),
+            api_creds: Default::default(),
+            order_builder: Default::default(),
+            ..self.clone()
+        }
+    }
+
     /// Start background keep-alive to maintain warm connection
     /// Sends periodic lightweight requests to prevent connection drops
     pub async fn start_keepalive(&self, interval: std::time::Duration) {
@@ -315,10 +481,579 @@ impl ClobClient {
         Ok(())
     }
 
+    /// Draw authenticated requests from `limiter`.
+    ///
+    /// Share one limiter (e.g. from a
+    /// [`RateLimitRegistry`](crate::utils::rate_limit::RateLimitRegistry)) between
+    /// every client using the same API key so they stay within one budget.
+    /// The limiter is shared with every clone of this client, including
+    /// clones made before this call.
+    pub fn set_rate_limiter(&self, limiter: std::sync::Arc<crate::utils::rate_limit::TokenBucket>) {
+        *self.rate_limiter.write() = Some(limiter);
+    }
+
+    pub fn rate_limiter(&self) -> Option<std::sync::Arc<crate::utils::rate_limit::TokenBucket>> {
+        self.rate_limiter.read().clone()
+    }
+
+    /// Retry failed requests according to `policy`.
+    ///
+    /// Connection errors, timeouts, 5xx and 429 responses are retried with
+    /// backoff while attempts and the policy's budget last. A `Retry-After`
+    /// longer than the backoff is waited out, and one longer than the
+    /// policy's maximum delay ends the retries. Retried authenticated
+    /// requests draw from the rate limiter again. Without a policy every
+    /// request is sent once. Shared with every clone of this client.
+    pub fn set_retry_policy(&self, policy: crate::utils::retry::RetryPolicy) {
+        *self.retry_policy.write() = Some(std::sync::Arc::new(policy));
+    }
+
+    async fn execute(&self, request: reqwest::Request) -> reqwest::Result<Response> {
+        let Some(policy) = self.retry_policy.read().clone() else {
+            return self.http_client.execute(request).await;
+        };
+        let config = policy.config(endpoint_class(request.method(), request.url().path()));
+        policy.budget.deposit();
+
+        let mut attempt = 1;
+        loop {
+            let next = if attempt < config.max_attempts {
+                request.try_clone()
+            } else {
+                None
+            };
+            // Last attempt, or a streaming body that cannot be replayed
+            let Some(next) = next else {
+                return self.http_client.execute(request).await;
+            };
+            let outcome = self.http_client.execute(next).await;
+            let (retryable, retry_after) = match &outcome {
+                Ok(response) => (
+                    response.status().is_server_error()
+                        || response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS,
+                    retry_after(response),
+                ),
+                // The errors PolyfillError::from maps to retryable kinds
+                Err(e) => (e.is_timeout() || e.is_connect() || e.is_request(), None),
+            };
+            if !retryable || retry_after.is_some_and(|wait| wait > config.max_delay) {
+                return outcome;
+            }
+            if !policy.budget.try_withdraw() {
+                return outcome;
+            }
+            let backoff = config.backoff(attempt as u32);
+            tokio::time::sleep(retry_after.map_or(backoff, |wait| wait.max(backoff))).await;
+            // The first attempt was throttled by the caller
+            if request.headers().contains_key(crate::auth::POLY_SIG_HEADER) {
+                self.throttle().await;
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Revalidate market, simplified-market and CLOB market info responses
+    /// against `cache` with `If-None-Match` instead of downloading them again.
+    ///
+    /// Every clone of this client uses the cache, including clones made
+    /// before this call.
+    pub fn set_response_cache(&self, cache: std::sync::Arc<crate::http_cache::ResponseCache>) {
+        *self.response_cache.write() = Some(cache);
+    }
+
+    pub fn response_cache(&self) -> Option<std::sync::Arc<crate::http_cache::ResponseCache>> {
+        self.response_cache.read().clone()
+    }
+
+    /// Send a GET for a slow-changing resource through the response cache
+    async fn get_cacheable<T>(&self, request: RequestBuilder, context: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let mut request = request.build()?;
+        let cache = self.response_cache();
+        let cache = cache.as_deref();
+        let key = request.url().to_string();
+        if let Some(etag) = cache.and_then(|cache| cache.etag(&key)) {
+            if let Ok(etag) = HeaderValue::from_str(&etag) {
+                request
+                    .headers_mut()
+                    .insert(reqwest::header::IF_NONE_MATCH, etag);
+            }
+        }
+
+        let unconditional = request.try_clone().map(|mut request| {
+            request.headers_mut().remove(reqwest::header::IF_NONE_MATCH);
+            request
+        });
+        let mut response = self.execute(request).await?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(body) = cache.and_then(|cache| cache.revalidated(&key)) {
+                return crate::decode::fast_parse::parse_json_fast(&mut body.to_vec());
+            }
+            // The entry was evicted while the request was in flight, so
+            // fetch the full body again instead of surfacing the 304
+            if let Some(request) = unconditional {
+                response = self.execute(request).await?;
+            }
+        }
+        if !response.status().is_success() {
+            return Err(PolyfillError::api_from_response("GET", response, context).await);
+        }
+
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string);
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| PolyfillError::network(format!("Failed to read response body: {e}"), e))?;
+        match (cache, etag) {
+            (Some(cache), Some(etag)) => cache.insert(&key, etag, body.clone()),
+            (Some(cache), None) => cache.record_miss(),
+            (None, _) => {},
+        }
+        crate::decode::fast_parse::parse_json_fast(&mut body.to_vec())
+    }
+
+    /// Wait for the rate limiter, if one is set
+    async fn throttle(&self) {
+        if let Some(limiter) = self.rate_limiter() {
+            limiter.acquire().await;
+        }
+    }
+
+    /// Use locally maintained books for pre-trade estimates, on this client
+    /// and every clone of it
+    pub fn set_order_books(&self, books: std::sync::Arc<crate::book::OrderBookManager>) {
+        *self.order_books.write() = Some(books);
+    }
+
+    /// Sign orders with salts and timestamps from `nonces`, e.g. a manager
+    /// that persists across restarts, on this client and every clone of it
+    pub fn set_nonce_manager(&self, nonces: std::sync::Arc<crate::nonce::NonceManager>) {
+        let mut current = self.order_builder.write();
+        if let Some(builder) = current.take() {
+            let builder = (*builder).clone().with_nonce_manager(nonces);
+            *current = Some(std::sync::Arc::new(builder));
+        }
+    }
+
+    /// Reject look-alike orders and reused client IDs before posting, on
+    /// this client and every clone of it
+    pub fn set_duplicate_guard(&self, guard: std::sync::Arc<crate::dedup::DuplicateGuard>) {
+        *self.duplicate_guard.write() = Some(guard);
+    }
+
+    /// Register an order with the duplicate guard until its post is settled
+    fn reserve_duplicate(
+        &self,
+        client_id: Option<&str>,
+        order_args: &OrderArgs,
+    ) -> Result<Option<crate::dedup::DuplicateReservation>> {
+        self.duplicate_guard
+            .read()
+            .as_ref()
+            .map(|guard| guard.reserve(self.strategy.as_deref(), client_id, order_args))
+            .transpose()
+    }
+
+    /// [`Self::reserve_duplicate`] for a market order
+    fn reserve_market_duplicate(
+        &self,
+        order_args: &MarketOrderArgs,
+    ) -> Result<Option<crate::dedup::DuplicateReservation>> {
+        self.duplicate_guard
+            .read()
+            .as_ref()
+            .map(|guard| guard.reserve_market(self.strategy.as_deref(), None, order_args))
+            .transpose()
+    }
+
+    /// Keep a duplicate reservation if the order was accepted or its outcome
+    /// is unknown; otherwise release it
+    fn settle_duplicate(
+        reservation: Option<crate::dedup::DuplicateReservation>,
+        result: std::result::Result<&PostOrderResponse, &PolyfillError>,
+    ) {
+        let Some(reservation) = reservation else {
+            return;
+        };
+        let may_have_posted = match result {
+            Ok(response) => response.success,
+            Err(e) => e.is_retryable(),
+        };
+        if may_have_posted {
+            reservation.commit();
+        } else {
+            reservation.release();
+        }
+    }
+
+    /// Reject signed orders priced outside `band` before posting them. The
+    /// deviation check uses books set with [`Self::set_order_books`]. Shared
+    /// with every clone of this client.
+    pub fn set_price_band(&self, band: crate::price_band::PriceBand) {
+        *self.price_band.write() = Some(std::sync::Arc::new(band));
+    }
+
+    fn check_price_band(&self, order: &SignedOrderRequest) -> Result<()> {
+        let Some(band) = self.price_band.read().clone() else {
+            return Ok(());
+        };
+        // Without a cached tick size, only the widest band is enforced
+        let tick_size = self
+            .metadata_cache
+            .tick_size(&order.token_id)
+            .unwrap_or_else(|| Decimal::new(1, 4));
+        let mid = self.order_books.read().as_ref().and_then(|books| {
+            books
+                .with_book(&order.token_id, |book| book.mid_price())
+                .ok()
+                .flatten()
+        });
+        band.check_signed(order, tick_size, mid)
+    }
+
+    /// Reject orders for tokens `guard` has halted ahead of resolution, on
+    /// this client and every clone of it
+    pub fn set_resolution_guard(&self, guard: std::sync::Arc<crate::resolution::ResolutionGuard>) {
+        *self.resolution_guard.write() = Some(guard);
+    }
+
+    fn check_resolution_guard(&self, order: &SignedOrderRequest) -> Result<()> {
+        match self.resolution_guard.read().as_deref() {
+            Some(guard) => guard.check(&order.token_id),
+            None => Ok(()),
+        }
+    }
+
+    /// Reject orders outside the schedule of `guard`, on this client and
+    /// every clone of it. Clones from [`Self::for_strategy`] are held to
+    /// their strategy's schedule.
+    pub fn set_schedule_guard(&self, guard: std::sync::Arc<crate::schedule::ScheduleGuard>) {
+        *self.schedule_guard.write() = Some(guard);
+    }
+
+    fn check_schedule(&self) -> Result<()> {
+        match self.schedule_guard.read().as_deref() {
+            Some(guard) => guard.check(self.strategy.as_deref()),
+            None => Ok(()),
+        }
+    }
+
+    /// Count accepted orders and cancels in `metrics`, on this client and
+    /// every clone of it
+    pub fn set_activity_metrics(&self, metrics: std::sync::Arc<crate::activity::ActivityMetrics>) {
+        *self.activity.write() = Some(metrics);
+    }
+
+    pub fn activity_metrics(&self) -> Option<std::sync::Arc<crate::activity::ActivityMetrics>> {
+        self.activity.read().clone()
+    }
+
+    /// Write every order submission and cancel, and their outcomes, to
+    /// `log`, on this client and every clone of it
+    pub fn set_audit_log(&self, log: std::sync::Arc<crate::audit::AuditLog>) {
+        *self.audit.write() = Some(log);
+    }
+
+    pub fn audit_log(&self) -> Option<std::sync::Arc<crate::audit::AuditLog>> {
+        self.audit.read().clone()
+    }
+
+    /// Run a cancel request, recording it and its outcome in the audit log
+    async fn audited_cancel<T>(
+        &self,
+        endpoint: &str,
+        body: Value,
+        request: impl std::future::Future<Output = Result<T>>,
+        outcome: impl FnOnce(&T) -> CancelOrdersResponse,
+    ) -> Result<T> {
+        let Some(audit) = self.audit_log() else {
+            return request.await;
+        };
+        audit.record(crate::audit::AuditEvent::CancelSubmitted {
+            endpoint: endpoint.to_string(),
+            body,
+        })?;
+        let result = request.await;
+        match &result {
+            Ok(response) => audit.record_cancel_result(endpoint, Ok(&outcome(response))),
+            Err(e) => audit.record_cancel_result(endpoint, Err(e)),
+        }
+        result
+    }
+
+    /// Record order posts, cancels, post latencies and failures, and user
+    /// channel reconnects in `recorder`, on this client and every clone of it
+    pub fn set_run_recorder(&self, recorder: std::sync::Arc<crate::run_report::RunRecorder>) {
+        let previous = self.run_recorder.write().replace(recorder);
+        // The reconnect handler reads the slot, so replacing the recorder
+        // must not register a second one
+        if previous.is_none() {
+            let slot = self.run_recorder.clone();
+            self.on_reconnect(move |_| {
+                if let Some(recorder) = slot.read().as_deref() {
+                    recorder.record_reconnect();
+                }
+            });
+        }
+    }
+
+    pub fn run_recorder(&self) -> Option<std::sync::Arc<crate::run_report::RunRecorder>> {
+        self.run_recorder.read().clone()
+    }
+
+    fn record_posted(&self, responses: &[PostOrderResponse]) {
+        let posted = responses.iter().filter(|r| r.success).count();
+        if let Some(activity) = self.activity.read().as_deref() {
+            activity.record_orders(posted);
+        }
+        if let Some(recorder) = self.run_recorder.read().as_deref() {
+            recorder.record_orders(posted);
+        }
+    }
+
+    fn record_cancelled(&self, response: &CancelOrdersResponse) {
+        if let Some(activity) = self.activity.read().as_deref() {
+            activity.record_cancels(response.canceled.len());
+        }
+        if let Some(recorder) = self.run_recorder.read().as_deref() {
+            recorder.record_cancels(response.canceled.len());
+        }
+    }
+
+    /// Record the latency of a post request, or its failure
+    fn record_post(&self, kind: &str, started: std::time::Instant, error: Option<&PolyfillError>) {
+        let Some(recorder) = self.run_recorder() else {
+            return;
+        };
+        match error {
+            None => recorder.record_latency(kind, started.elapsed()),
+            Some(e) => recorder.record_error(e),
+        }
+    }
+
+    /// Reconnect user channels started from this client or any clone of it
+    /// with `config` instead of ending them on disconnect
+    pub fn set_reconnect_config(&self, config: crate::stream::ReconnectConfig) {
+        *self.reconnect_config.write() = Some(std::sync::Arc::new(config));
+    }
+
+    /// Open user channel connections through `connector`, on this client and
+    /// every clone of it
+    pub fn set_ws_connector(&self, connector: impl crate::ws_transport::WsConnector + 'static) {
+        *self.ws_connector.write() = Some(std::sync::Arc::new(connector));
+    }
+
+    /// Pin the CLOB API version for this client and every clone of it;
+    /// endpoints it lacks fail fast
+    pub fn set_api_version(&self, version: ClobApiVersion) {
+        *self.api.write() = ApiDescriptor::new(version);
+    }
+
+    pub fn api_descriptor(&self) -> ApiDescriptor {
+        *self.api.read()
+    }
+
+    /// Probe the public endpoints of the pinned API version and check that
+    /// their responses carry the fields this client parses.
+    ///
+    /// Token and market endpoints are probed with the first sampling market.
+    /// Fails with one error listing every mismatch; call it at startup.
+    pub async fn check_compatibility(&self) -> Result<CompatibilityReport> {
+        let api = self.api_descriptor();
+        let mut report = CompatibilityReport {
+            version: api.version(),
+            ..CompatibilityReport::default()
+        };
+
+        let time = api.require(Endpoint::Time)?;
+        report.record(time, self.probe(time.path.to_string(), &[]).await);
+
+        let markets_spec = api.require(Endpoint::SamplingMarkets)?;
+        let markets = self.probe(markets_spec.path.to_string(), &[]).await;
+        let ids = markets.as_ref().ok().and_then(|markets| {
+            markets["data"].as_array()?.iter().find_map(|market| {
+                Some((
+                    market["condition_id"].as_str()?.to_string(),
+                    market["tokens"][0]["token_id"].as_str()?.to_string(),
+                ))
+            })
+        });
+        let markets_ok = markets.is_ok();
+        report.record(markets_spec, markets);
+
+        let Some((condition_id, token_id)) = ids else {
+            if markets_ok {
+                report.failures.push((
+                    Endpoint::SamplingMarkets,
+                    "no market to probe token endpoints with".to_string(),
+                ));
+            }
+            return report.into_result();
+        };
+        for spec in api.endpoints() {
+            let response = match spec.endpoint {
+                Endpoint::Book | Endpoint::TickSize | Endpoint::NegRisk | Endpoint::FeeRate => {
+                    self.probe(spec.path.to_string(), &[("token_id", &token_id)])
+                        .await
+                },
+                Endpoint::MarketByToken => self.probe(spec.path_for(&token_id), &[]).await,
+                Endpoint::ClobMarket => self.probe(spec.path_for(&condition_id), &[]).await,
+                Endpoint::Time | Endpoint::SamplingMarkets => continue,
+            };
+            report.record(spec, response);
+        }
+        report.into_result()
+    }
+
+    async fn probe(&self, path: String, query: &[(&str, &str)]) -> Result<Value> {
+        let response = self
+            .http_client
+            .get(format!("{}{}", self.base_url, path))
+            .query(query)
+            .send_retrying(self)
+            .await?;
+        if !response.status().is_success() {
+            return Err(PolyfillError::api_from_response("GET", response, "Probe failed").await);
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))
+    }
+
+    /// DNS cache from [`ClientConfig::dns_cache`], if any
+    pub fn dns_cache(&self) -> Option<&crate::dns::DnsCache> {
+        self.dns_cache.as_ref()
+    }
+
+    /// Register a callback for user channel disconnects and failed
+    /// reconnects. Shared with every clone, and also reaches channels that
+    /// are already running.
+    pub fn on_disconnect<F>(&self, handler: F)
+    where
+        F: Fn(&crate::stream::DisconnectEvent) + Send + Sync + 'static,
+    {
+        self.event_handlers.on_disconnect(handler);
+    }
+
+    /// Register a callback for user channel reconnects, shared like
+    /// [`Self::on_disconnect`]
+    pub fn on_reconnect<F>(&self, handler: F)
+    where
+        F: Fn(&crate::stream::ReconnectEvent) + Send + Sync + 'static,
+    {
+        self.event_handlers.on_reconnect(handler);
+    }
+
+    /// Register a callback for fills on the user channel.
+    ///
+    /// Callbacks run on the task started by [`ClobClient::start_user_channel`].
+    /// A panicking callback is only isolated in builds that unwind; the
+    /// release profile aborts on panic. See [`crate::handlers::EventHandlers`].
+    pub fn on_fill<F>(&self, handler: F)
+    where
+        F: Fn(&crate::types::TradeMessage) + Send + Sync + 'static,
+    {
+        self.event_handlers.on_fill(handler);
+    }
+
+    /// Register a callback for order updates on the user channel
+    pub fn on_order_update<F>(&self, handler: F)
+    where
+        F: Fn(&crate::types::OrderMessage) + Send + Sync + 'static,
+    {
+        self.event_handlers.on_order_update(handler);
+    }
+
+    /// Shared registry of account event handlers
+    pub fn event_handlers(&self) -> std::sync::Arc<crate::handlers::EventHandlers> {
+        self.event_handlers.clone()
+    }
+
+    /// Subscribe to the user channel and dispatch events to registered handlers
+    /// on a background task.
+    ///
+    /// Fills and cancellations on the channel also keep the client's
+    /// [`crate::balances::BalanceCache`] current.
+    ///
+    /// An empty `markets` list subscribes to all markets.
+    pub async fn start_user_channel(
+        &self,
+        markets: Vec<String>,
+    ) -> Result<tokio::task::JoinHandle<Result<()>>> {
+        self.start_user_channel_at(&crate::stream::WsEndpoint::default(), markets)
+            .await
+    }
+
+    /// Like [`ClobClient::start_user_channel`], against a custom WebSocket host
+    pub async fn start_user_channel_at(
+        &self,
+        endpoint: &crate::stream::WsEndpoint,
+        markets: Vec<String>,
+    ) -> Result<tokio::task::JoinHandle<Result<()>>> {
+        let api_creds = &self
+            .prepared_api_creds()
+            .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
+
+        let mut stream = crate::stream::WebSocketStream::new(&endpoint.user_url())
+            .with_auth(api_creds.credentials().clone());
+        if let Some(config) = self.reconnect_config.read().as_deref() {
+            stream = stream.with_reconnect_config(config.clone());
+        }
+        let connector = self.ws_connector.read().clone();
+        if let Some(connector) = connector {
+            stream = stream.with_connector(connector);
+        } else if let Some(cache) = &self.dns_cache {
+            stream = stream.with_connector(
+                crate::ws_transport::TungsteniteConnector::new().with_dns_cache(cache.clone()),
+            );
+        }
+        let handlers = self.event_handlers.clone();
+        stream.on_disconnect(move |event| handlers.notify_disconnect(event));
+        let handlers = self.event_handlers.clone();
+        stream.on_reconnect(move |event| handlers.notify_reconnect(event));
+        stream.subscribe_user_channel(markets).await?;
+
+        let handlers = self.event_handlers.clone();
+        let balance_cache = self.balance_cache.clone();
+        let stream = futures::StreamExt::inspect(stream, move |message| {
+            if let Ok(message) = message {
+                balance_cache.apply(message);
+            }
+        });
+        let task = tokio::spawn(async move { handlers.run(stream).await });
+        self.user_channels.lock().push(task.abort_handle());
+        Ok(task)
+    }
+
+    /// Fail unless a signer is configured (L1: wallet-signed endpoints)
+    pub fn assert_level_1_auth(&self) -> Result<()> {
+        if self.current_signer().is_none() {
+            return Err(PolyfillError::auth("Signer not set"));
+        }
+        Ok(())
+    }
+
+    /// Fail unless a signer and API credentials are configured
+    /// (L2: HMAC-signed endpoints)
+    pub fn assert_level_2_auth(&self) -> Result<()> {
+        self.assert_level_1_auth()?;
+        if self.prepared_api_creds().is_none() {
+            return Err(PolyfillError::auth("API credentials not set"));
+        }
+        Ok(())
+    }
+
     /// Get the wallet address
     pub fn get_address(&self) -> Option<String> {
         use alloy_primitives::hex;
-        self.signer
+        self.current_signer()
             .as_ref()
             .map(|s| hex::encode_prefixed(s.address().as_slice()))
     }
@@ -341,12 +1076,46 @@ impl ClobClient {
         Some(config.exchange)
     }
 
+    /// Exchange contract for the configured chain; neg-risk markets settle
+    /// through a separate exchange
+    pub fn exchange_address(&self, neg_risk: bool) -> Result<Address> {
+        self.contract_address(neg_risk, |config| &config.exchange)
+    }
+
+    /// Collateral (USDC) token for the configured chain
+    pub fn collateral_address(&self) -> Result<Address> {
+        self.contract_address(false, |config| &config.collateral)
+    }
+
+    /// Conditional tokens (CTF) contract for the configured chain
+    pub fn conditional_tokens_address(&self) -> Result<Address> {
+        self.contract_address(false, |config| &config.conditional_tokens)
+    }
+
+    fn contract_address(
+        &self,
+        neg_risk: bool,
+        field: impl FnOnce(&crate::orders::ContractConfig) -> &String,
+    ) -> Result<Address> {
+        let config =
+            crate::orders::get_contract_config(self.chain_id, neg_risk).ok_or_else(|| {
+                PolyfillError::config(format!(
+                    "No contract config for chain {} (neg_risk: {neg_risk})",
+                    self.chain_id
+                ))
+            })?;
+        let address = field(&config);
+        address
+            .parse()
+            .map_err(|e| PolyfillError::config(format!("Invalid contract address {address}: {e}")))
+    }
+
     /// Test basic connectivity
     pub async fn get_ok(&self) -> bool {
         match self
             .http_client
             .get(format!("{}/ok", self.base_url))
-            .send()
+            .send_retrying(self)
             .await
         {
             Ok(response) => response.status().is_success(),
@@ -359,14 +1128,16 @@ impl ClobClient {
         let response = self
             .http_client
             .get(format!("{}/time", self.base_url))
-            .send()
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get server time",
-            ));
+            )
+            .await);
         }
 
         let time_text = response.text().await?;
@@ -380,56 +1151,258 @@ impl ClobClient {
 
     /// Get order book for a token
     pub async fn get_order_book(&self, token_id: &str) -> Result<OrderBookSummary> {
-        let response = self
+        self.fetch_order_book(token_id, BookDepth::Full).await
+    }
+
+    /// Get the order book for a token, limited to `depth` levels per side.
+    ///
+    /// [`BookDepth::Top`] passes the limit to the server as `depth` and trims
+    /// the response to the best levels either way, so callers watching only
+    /// the top of book do not hold hundreds of deep levels.
+    pub async fn get_order_book_with_depth(
+        &self,
+        token_id: &TokenId,
+        depth: BookDepth,
+    ) -> Result<OrderBookSummary> {
+        self.fetch_order_book(token_id, depth).await
+    }
+
+    async fn fetch_order_book(&self, token_id: &str, depth: BookDepth) -> Result<OrderBookSummary> {
+        let mut request = self
             .http_client
             .get(format!("{}/book", self.base_url))
-            .query(&[("token_id", token_id)])
-            .send()
-            .await?;
+            .query(&[("token_id", token_id)]);
+        if let BookDepth::Top(levels) = depth {
+            request = request.query(&[("depth", levels)]);
+        }
+        let response = request.send_retrying(self).await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get order book",
-            ));
+            )
+            .await);
         }
 
-        Self::parse_json_response(response).await
+        let mut book: OrderBookSummary = Self::parse_json_response(response).await?;
+        if let BookDepth::Top(levels) = depth {
+            book.truncate_depth(levels);
+        }
+        Ok(book)
+    }
+
+    /// Estimate executing a market order of `size` before committing to it.
+    ///
+    /// Uses the local book registered with [`ClobClient::set_order_books`]
+    /// when it tracks `token_id`, otherwise fetches a fresh snapshot.
+    pub async fn estimate_execution(
+        &self,
+        token_id: &TokenId,
+        side: Side,
+        size: Decimal,
+    ) -> Result<crate::book::ExecutionEstimate> {
+        if size <= Decimal::ZERO {
+            return Err(PolyfillError::validation("Size must be positive"));
+        }
+
+        if let Ok(books) = self.local_books() {
+            if let Ok(estimate) =
+                books.with_book(token_id, |book| book.estimate_execution(side, size))
+            {
+                return Ok(estimate);
+            }
+        }
+
+        let summary = self.get_order_book(token_id).await?;
+        Ok(crate::book::ExecutionEstimate::from_summary(
+            &summary, side, size,
+        ))
+    }
+
+    /// Midpoint of the local book registered with
+    /// [`ClobClient::set_order_books`], without a request.
+    ///
+    /// Check the quote's age before trusting it; [`ClobClient::get_midpoint`]
+    /// is the fallback when the book is missing or stale.
+    pub fn local_midpoint(&self, token_id: &TokenId) -> Result<crate::book::LocalQuote> {
+        self.local_quote(token_id, "midpoint", crate::book::OrderBook::mid_price)
+    }
+
+    /// Spread of the local book, see [`ClobClient::local_midpoint`]
+    pub fn local_spread(&self, token_id: &TokenId) -> Result<crate::book::LocalQuote> {
+        self.local_quote(token_id, "spread", crate::book::OrderBook::spread)
+    }
+
+    /// Books registered with [`ClobClient::set_order_books`]
+    pub(crate) fn local_books(&self) -> Result<std::sync::Arc<crate::book::OrderBookManager>> {
+        self.order_books.read().clone().ok_or_else(|| {
+            PolyfillError::market_data(
+                "No local order books registered",
+                crate::errors::MarketDataErrorKind::BookUnavailable,
+            )
+        })
+    }
+
+    /// Fetch the full book of each token, with at most `max_in_flight`
+    /// requests outstanding.
+    ///
+    /// Unlike [`Self::get_order_books`], every token is its own request, so a
+    /// bad token fails alone while the rest still arrive.
+    pub async fn get_order_books_concurrent(
+        &self,
+        token_ids: &[TokenId],
+        max_in_flight: usize,
+    ) -> std::collections::HashMap<TokenId, Result<OrderBookSummary>> {
+        use futures::StreamExt;
+
+        futures::stream::iter(token_ids.iter().cloned())
+            .map(|token_id| async move {
+                let book = self.get_order_book(&token_id).await;
+                (token_id, book)
+            })
+            .buffer_unordered(max_in_flight.max(1))
+            .collect()
+            .await
+    }
+
+    /// Seed the local books of `token_ids` from REST snapshots fetched with
+    /// [`Self::get_order_books_concurrent`], creating books as needed.
+    ///
+    /// Returns the tokens that could not be fetched or seeded. Updates
+    /// buffered by [`crate::book::OrderBookManager::begin_warmup`] are
+    /// replayed on top of each snapshot.
+    pub async fn seed_order_books(
+        &self,
+        token_ids: &[TokenId],
+        max_in_flight: usize,
+    ) -> Result<std::collections::HashMap<TokenId, PolyfillError>> {
+        let books = self.local_books()?;
+        let mut failed = std::collections::HashMap::new();
+        let snapshots = self
+            .get_order_books_concurrent(token_ids, max_in_flight)
+            .await;
+        for (token_id, summary) in snapshots {
+            let seeded = summary.and_then(|summary| {
+                books.get_or_create_book(&token_id)?;
+                books.seed_snapshot(&summary)
+            });
+            if let Err(e) = seeded {
+                failed.insert(token_id, e);
+            }
+        }
+        Ok(failed)
+    }
+
+    /// Compare every local book with a REST snapshot and re-seed books that
+    /// drifted, e.g. after missed updates.
+    ///
+    /// Returns the tokens whose books were corrected. A local book newer than
+    /// its snapshot is left alone.
+    pub async fn audit_order_books(&self) -> Result<Vec<String>> {
+        let books = self.local_books()?;
+        let token_ids: Vec<String> = books
+            .get_all_books()?
+            .into_iter()
+            .map(|book| book.token_id)
+            .collect();
+        if token_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut corrected = Vec::new();
+        for summary in self.get_order_books(&token_ids).await? {
+            // Removed since listing, or an unusable snapshot
+            let Ok(drift) = books.diff_snapshot(&summary) else {
+                continue;
+            };
+            if drift.is_empty() {
+                continue;
+            }
+            if let Err(e) = books.seed_snapshot(&summary) {
+                warn!("Failed to re-seed book for {}: {}", summary.asset_id, e);
+                continue;
+            }
+            if books
+                .diff_snapshot(&summary)
+                .is_ok_and(|drift| drift.is_empty())
+            {
+                warn!(
+                    "Re-seeded drifted book for {} ({} levels differed)",
+                    summary.asset_id,
+                    drift.len()
+                );
+                corrected.push(summary.asset_id);
+            }
+        }
+        Ok(corrected)
+    }
+
+    /// Scheduler for periodic maintenance jobs bound to this client
+    pub fn maintenance(&self) -> crate::maintenance::MaintenanceScheduler {
+        crate::maintenance::MaintenanceScheduler::new(self.clone())
+    }
+
+    fn local_quote(
+        &self,
+        token_id: &str,
+        name: &str,
+        price: impl FnOnce(&crate::book::OrderBook) -> Option<Decimal>,
+    ) -> Result<crate::book::LocalQuote> {
+        self.local_books()?.with_book(token_id, |book| {
+            let value = price(book).ok_or_else(|| {
+                PolyfillError::market_data(
+                    format!("No {name} for {token_id}: book is one-sided or empty"),
+                    crate::errors::MarketDataErrorKind::IncompleteData,
+                )
+            })?;
+            Ok(crate::book::LocalQuote {
+                value,
+                updated_at: book.timestamp,
+            })
+        })?
     }
 
     /// Get midpoint for a token
+    ///
+    /// Polling this is slow and spends rate limit; when streaming the book,
+    /// prefer [`ClobClient::local_midpoint`].
     pub async fn get_midpoint(&self, token_id: &str) -> Result<MidpointResponse> {
         let response = self
             .http_client
             .get(format!("{}/midpoint", self.base_url))
             .query(&[("token_id", token_id)])
-            .send()
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get midpoint",
-            ));
+            )
+            .await);
         }
 
         Self::parse_json_response(response).await
     }
 
     /// Get spread for a token
+    ///
+    /// When streaming the book, prefer [`ClobClient::local_spread`].
     pub async fn get_spread(&self, token_id: &str) -> Result<SpreadResponse> {
         let response = self
             .http_client
             .get(format!("{}/spread", self.base_url))
             .query(&[("token_id", token_id)])
-            .send()
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
-                "Failed to get spread",
-            ));
+            return Err(
+                PolyfillError::api_from_response("GET", response, "Failed to get spread").await,
+            );
         }
 
         let spread: SpreadResponse = response.json().await?;
@@ -450,14 +1423,16 @@ impl ClobClient {
             .http_client
             .post(format!("{}/spreads", self.base_url))
             .json(&request_data)
-            .send()
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "POST",
+                response,
                 "Failed to get batch spreads",
-            ));
+            )
+            .await);
         }
 
         response
@@ -472,14 +1447,13 @@ impl ClobClient {
             .http_client
             .get(format!("{}/price", self.base_url))
             .query(&[("token_id", token_id), ("side", side.as_str())])
-            .send()
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
-                "Failed to get price",
-            ));
+            return Err(
+                PolyfillError::api_from_response("GET", response, "Failed to get price").await,
+            );
         }
 
         let price: PriceResponse = response.json().await?;
@@ -487,17 +1461,23 @@ impl ClobClient {
     }
 
     async fn get_market_by_token(&self, token_id: &str) -> Result<MarketByTokenResponse> {
+        let path = self
+            .api_descriptor()
+            .require(Endpoint::MarketByToken)?
+            .path_for(token_id);
         let response = self
             .http_client
-            .get(format!("{}/markets-by-token/{}", self.base_url, token_id))
-            .send()
+            .get(format!("{}{}", self.base_url, path))
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get market by token",
-            ));
+            )
+            .await);
         }
 
         response
@@ -508,52 +1488,39 @@ impl ClobClient {
 
     /// Get V2 CLOB-level market info for a condition ID.
     pub async fn get_clob_market_info(&self, condition_id: &str) -> Result<ClobMarketInfo> {
-        let response = self
-            .http_client
-            .get(format!("{}/clob-markets/{}", self.base_url, condition_id))
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
-                "Failed to get clob market info",
-            ));
-        }
-
-        response
-            .json::<ClobMarketInfo>()
+        let path = self
+            .api_descriptor()
+            .require(Endpoint::ClobMarket)?
+            .path_for(condition_id);
+        let request = self.http_client.get(format!("{}{}", self.base_url, path));
+        self.get_cacheable(request, "Failed to get clob market info")
             .await
-            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))
     }
 
     /// Get V2 builder fee rates for a bytes32 builder code.
     pub async fn get_builder_fee_rate(&self, builder_code: &str) -> Result<BuilderFeeRateResponse> {
         crate::orders::validate_bytes32_hex("builder_code", builder_code)?;
 
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let endpoint = format!("/fees/builder-fees/{builder_code}");
+        self.throttle().await;
         let headers = create_l2_headers::<Value>(signer, api_creds, "GET", &endpoint, None)?;
         let req = self.create_request_with_headers(Method::GET, &endpoint, headers.into_iter());
 
-        let response = req.send().await?;
+        let response = req.send_retrying(self).await?;
         if !response.status().is_success() {
-            let status = response.status().as_u16();
-            let body = response.text().await.unwrap_or_default();
-            let message = if body.is_empty() {
-                "Failed to get builder fee rate".to_string()
-            } else {
-                format!("Failed to get builder fee rate: {body}")
-            };
-            return Err(PolyfillError::api(status, message));
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
+                "Failed to get builder fee rate",
+            )
+            .await);
         }
 
         response
@@ -606,9 +1573,10 @@ impl ClobClient {
             request = request.query(&[("fidelity", fidelity)]);
         }
 
-        let response = request.send().await?;
+        let response = request.send_retrying(self).await?;
         if !response.status().is_success() {
             let status = response.status().as_u16();
+            let context = ApiErrorContext::from_response("GET", &response);
             let body = response.text().await.unwrap_or_default();
             let message = serde_json::from_str::<Value>(&body)
                 .ok()
@@ -617,14 +1585,10 @@ impl ClobClient {
                         .and_then(Value::as_str)
                         .map(|s| s.to_string())
                 })
-                .unwrap_or_else(|| {
-                    if body.is_empty() {
-                        "Failed to get prices history".to_string()
-                    } else {
-                        body
-                    }
-                });
-            return Err(PolyfillError::api(status, message));
+                .unwrap_or_else(|| "Failed to get prices history".to_string());
+            return Err(
+                PolyfillError::api(status, message).with_api_context(context.with_body(&body))
+            );
         }
 
         Ok(response.json::<PricesHistoryResponse>().await?)
@@ -658,9 +1622,10 @@ impl ClobClient {
             request = request.query(&[("fidelity", fidelity)]);
         }
 
-        let response = request.send().await?;
+        let response = request.send_retrying(self).await?;
         if !response.status().is_success() {
             let status = response.status().as_u16();
+            let context = ApiErrorContext::from_response("GET", &response);
             let body = response.text().await.unwrap_or_default();
             let message = serde_json::from_str::<Value>(&body)
                 .ok()
@@ -669,14 +1634,10 @@ impl ClobClient {
                         .and_then(Value::as_str)
                         .map(|s| s.to_string())
                 })
-                .unwrap_or_else(|| {
-                    if body.is_empty() {
-                        "Failed to get prices history".to_string()
-                    } else {
-                        body
-                    }
-                });
-            return Err(PolyfillError::api(status, message));
+                .unwrap_or_else(|| "Failed to get prices history".to_string());
+            return Err(
+                PolyfillError::api(status, message).with_api_context(context.with_body(&body))
+            );
         }
 
         Ok(response.json::<PricesHistoryResponse>().await?)
@@ -688,14 +1649,16 @@ impl ClobClient {
             .http_client
             .get(format!("{}/tick-size", self.base_url))
             .query(&[("token_id", token_id)])
-            .send()
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get tick size",
-            ));
+            )
+            .await);
         }
 
         let tick_size_response: Value = response.json().await?;
@@ -718,15 +1681,17 @@ impl ClobClient {
             .http_client
             .get(format!("{}/fee-rate", self.base_url))
             .query(&[("token_id", token_id)])
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get fee rate",
-            ));
+            )
+            .await);
         }
 
         let fee_rate: crate::types::FeeRateResponse = response
@@ -738,21 +1703,22 @@ impl ClobClient {
 
     /// Create a new API key
     pub async fn create_api_key(&self, nonce: Option<U256>) -> Result<ApiCreds> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
 
         let headers = create_l1_headers(signer, nonce)?;
         let req =
             self.create_request_with_headers(Method::POST, "/auth/api-key", headers.into_iter());
 
-        let response = req.send().await?;
+        let response = req.send_retrying(self).await?;
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "POST",
+                response,
                 "Failed to create API key",
-            ));
+            )
+            .await);
         }
 
         Ok(response.json::<ApiCreds>().await?)
@@ -760,9 +1726,8 @@ impl ClobClient {
 
     /// Derive an existing API key
     pub async fn derive_api_key(&self, nonce: Option<U256>) -> Result<ApiCreds> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
 
         let headers = create_l1_headers(signer, nonce)?;
@@ -772,12 +1737,14 @@ impl ClobClient {
             headers.into_iter(),
         );
 
-        let response = req.send().await?;
+        let response = req.send_retrying(self).await?;
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to derive API key",
-            ));
+            )
+            .await);
         }
 
         Ok(response.json::<ApiCreds>().await?)
@@ -796,17 +1763,16 @@ impl ClobClient {
 
     /// Get all API keys for the authenticated user
     pub async fn get_api_keys(&self) -> Result<Vec<String>> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::config("Signer not configured"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::config("API credentials not configured"))?;
 
         let method = Method::GET;
         let endpoint = "/auth/api-keys";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -819,7 +1785,7 @@ impl ClobClient {
                     .map(|(k, v)| (HeaderName::from_static(k), v.parse().unwrap()))
                     .collect(),
             )
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -833,17 +1799,16 @@ impl ClobClient {
 
     /// Delete the current API key
     pub async fn delete_api_key(&self) -> Result<String> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::config("Signer not configured"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::config("API credentials not configured"))?;
 
         let method = Method::DELETE;
         let endpoint = "/auth/api-key";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -856,7 +1821,7 @@ impl ClobClient {
                     .map(|(k, v)| (HeaderName::from_static(k), v.parse().unwrap()))
                     .collect(),
             )
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -879,9 +1844,20 @@ impl ClobClient {
         headers.fold(req, |r, (k, v)| r.header(HeaderName::from_static(k), v))
     }
 
-    fn serialize_json_body<T: ?Sized + Serialize>(body: &T) -> Result<Vec<u8>> {
-        serde_json::to_vec(body)
-            .map_err(|e| PolyfillError::parse(format!("Failed to serialize body: {e}"), None))
+    /// Serialize a request body once for both the HMAC and the HTTP body.
+    ///
+    /// Bodies are written into a per-thread buffer whose allocation is reclaimed
+    /// once the previous request has released its body, so steady-state order
+    /// posting does not allocate for serialization.
+    fn serialize_json_body<T: ?Sized + Serialize>(body: &T) -> Result<bytes::Bytes> {
+        BODY_BUFFER.with(|buffer| {
+            let mut buffer = buffer.borrow_mut();
+            buffer.reserve(BODY_BUFFER_CAPACITY);
+            serde_json::to_writer(bytes::BufMut::writer(&mut *buffer), body).map_err(|e| {
+                PolyfillError::parse(format!("Failed to serialize body: {e}"), None)
+            })?;
+            Ok(buffer.split().freeze())
+        })
     }
 
     fn create_request_with_json_bytes(
@@ -889,7 +1865,7 @@ impl ClobClient {
         method: Method,
         endpoint: &str,
         headers: impl Iterator<Item = (&'static str, String)>,
-        body_bytes: Vec<u8>,
+        body_bytes: bytes::Bytes,
     ) -> RequestBuilder {
         self.create_request_with_headers(method, endpoint, headers)
             .header(CONTENT_TYPE, "application/json")
@@ -902,14 +1878,16 @@ impl ClobClient {
             .http_client
             .get(format!("{}/neg-risk", self.base_url))
             .query(&[("token_id", token_id)])
-            .send()
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get neg risk",
-            ));
+            )
+            .await);
         }
 
         let neg_risk_response: Value = response.json().await?;
@@ -920,13 +1898,136 @@ impl ClobClient {
         Ok(neg_risk)
     }
 
+    /// Tick size and neg-risk cache used when building orders
+    pub fn metadata_cache(&self) -> std::sync::Arc<crate::metadata::MetadataCache> {
+        self.metadata_cache.clone()
+    }
+
+    async fn cached_tick_size(&self, token_id: &str) -> Result<Decimal> {
+        if let Some(tick_size) = self.metadata_cache.tick_size(token_id) {
+            return Ok(tick_size);
+        }
+        let tick_size = self.get_tick_size(token_id).await?;
+        self.metadata_cache.set_tick_size(token_id, tick_size);
+        Ok(tick_size)
+    }
+
+    async fn cached_neg_risk(&self, token_id: &str) -> Result<bool> {
+        if let Some(neg_risk) = self.metadata_cache.neg_risk(token_id) {
+            return Ok(neg_risk);
+        }
+        let neg_risk = self.get_neg_risk(token_id).await?;
+        self.metadata_cache.set_neg_risk(token_id, neg_risk);
+        Ok(neg_risk)
+    }
+
+    /// Fetch tick sizes and neg-risk flags for a watchlist ahead of trading.
+    ///
+    /// Tokens are fetched concurrently, [`PRELOAD_CONCURRENCY`] at a time;
+    /// values already cached are not fetched again. Fails on the first token
+    /// that cannot be fetched, keeping whatever was cached before it.
+    pub async fn preload_order_metadata(&self, token_ids: &[TokenId]) -> Result<()> {
+        use futures::{StreamExt, TryStreamExt};
+
+        futures::stream::iter(token_ids)
+            .map(Ok)
+            .try_for_each_concurrent(PRELOAD_CONCURRENCY, |token_id| async move {
+                futures::try_join!(
+                    self.cached_tick_size(token_id),
+                    self.cached_neg_risk(token_id)
+                )
+                .map(|_| ())
+            })
+            .await
+    }
+
+    /// Fetch the current trading parameters of `token_id`'s market
+    pub async fn get_market_metadata(
+        &self,
+        token_id: &TokenId,
+    ) -> Result<crate::metadata::MarketMetadata> {
+        let book = self.get_order_book(token_id).await?;
+        let (market, fee_rate_bps) = futures::try_join!(
+            self.get_market(&book.market),
+            self.get_fee_rate_bps(token_id)
+        )?;
+        Ok(crate::metadata::MarketMetadata {
+            tick_size: book.tick_size,
+            min_order_size: book.min_order_size,
+            neg_risk: book.neg_risk,
+            accepting_orders: market.accepting_orders,
+            fee_rate_bps,
+        })
+    }
+
+    /// Re-fetch market metadata for `token_ids` into the metadata cache.
+    ///
+    /// Returns the tokens whose metadata changed since the last refresh.
+    /// Tokens that fail to refresh are logged and keep their cached values.
+    pub async fn refresh_market_metadata(
+        &self,
+        token_ids: &[TokenId],
+    ) -> Vec<crate::metadata::MetadataChange> {
+        use futures::StreamExt;
+
+        let refreshes: Vec<_> = token_ids
+            .iter()
+            .map(|token_id| self.refresh_token_metadata(token_id))
+            .collect();
+        futures::stream::iter(refreshes)
+            .buffer_unordered(PRELOAD_CONCURRENCY)
+            .filter_map(futures::future::ready)
+            .collect()
+            .await
+    }
+
+    async fn refresh_token_metadata(
+        &self,
+        token_id: &TokenId,
+    ) -> Option<crate::metadata::MetadataChange> {
+        match self.get_market_metadata(token_id).await {
+            Ok(metadata) => self.metadata_cache.update(token_id, metadata),
+            Err(e) => {
+                warn!("Failed to refresh metadata for {}: {}", token_id, e);
+                None
+            },
+        }
+    }
+
+    /// Refresh market metadata for `token_ids` every `interval` on a
+    /// background task, calling `on_change` for each change.
+    ///
+    /// The first refresh runs immediately and only fills the cache. Abort the
+    /// returned handle to stop.
+    pub fn spawn_metadata_refresh<F>(
+        &self,
+        token_ids: Vec<TokenId>,
+        interval: Duration,
+        on_change: F,
+    ) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(&crate::metadata::MetadataChange) + Send + Sync + 'static,
+    {
+        let client = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                for change in client.refresh_market_metadata(&token_ids).await {
+                    on_change(&change);
+                }
+            }
+        })
+    }
+
     /// Resolve tick size for an order
     async fn resolve_tick_size(
         &self,
         token_id: &str,
         tick_size: Option<Decimal>,
     ) -> Result<Decimal> {
-        let min_tick_size = self.get_tick_size(token_id).await?;
+        let min_tick_size = self.cached_tick_size(token_id).await?;
 
         match tick_size {
             None => Ok(min_tick_size),
@@ -957,7 +2058,7 @@ impl ClobClient {
         let tick_size = self.resolve_tick_size(token_id, tick_size).await?;
         let neg_risk = match neg_risk {
             Some(nr) => nr,
-            None => self.get_neg_risk(token_id).await?,
+            None => self.cached_neg_risk(token_id).await?,
         };
 
         Ok(CreateOrderOptions {
@@ -984,14 +2085,13 @@ impl ClobClient {
     /// parsed token ID, exchange address, normalized bytes32 values, and EIP-712 domain.
     pub async fn prepare_order_path(
         &self,
-        token_id: &str,
+        token_id: &TokenId,
         options: Option<&CreateOrderOptions>,
         builder_code: Option<&str>,
         metadata: Option<&str>,
     ) -> Result<crate::orders::PreparedOrderPath> {
-        let order_builder = self
-            .order_builder
-            .as_ref()
+        let order_builder = &self
+            .current_order_builder()
             .ok_or_else(|| PolyfillError::auth("Order builder not initialized"))?;
 
         let create_order_options = self.get_filled_order_options(token_id, options).await?;
@@ -999,7 +2099,7 @@ impl ClobClient {
 
         order_builder.prepare_order_path(
             self.chain_id,
-            token_id,
+            token_id.clone(),
             create_order_options.tick_size.expect("Should be filled"),
             create_order_options.neg_risk.expect("Should be filled"),
             builder_code,
@@ -1013,9 +2113,8 @@ impl ClobClient {
         order_args: &OrderArgs,
         options: Option<&CreateOrderOptions>,
     ) -> Result<SignedOrderRequest> {
-        let order_builder = self
-            .order_builder
-            .as_ref()
+        let order_builder = &self
+            .current_order_builder()
             .ok_or_else(|| PolyfillError::auth("Order builder not initialized"))?;
 
         let create_order_options = self
@@ -1047,9 +2146,8 @@ impl ClobClient {
         order_type: OrderType,
     ) -> Result<Decimal> {
         let book = self.get_order_book(token_id).await?;
-        let order_builder = self
-            .order_builder
-            .as_ref()
+        let order_builder = &self
+            .current_order_builder()
             .ok_or_else(|| PolyfillError::auth("Order builder not initialized"))?;
 
         // Convert OrderSummary to BookLevel
@@ -1081,9 +2179,8 @@ impl ClobClient {
         order_args: &MarketOrderArgs,
         options: Option<&CreateOrderOptions>,
     ) -> Result<SignedOrderRequest> {
-        let order_builder = self
-            .order_builder
-            .as_ref()
+        let order_builder = &self
+            .current_order_builder()
             .ok_or_else(|| PolyfillError::auth("Order builder not initialized"))?;
 
         let create_order_options = self
@@ -1172,38 +2269,45 @@ impl ClobClient {
         order: SignedOrderRequest,
         options: Option<&PostOrderOptions>,
     ) -> Result<PostOrderResponse> {
-        let signer = self
-            .signer
-            .as_ref()
+        let Some(audit) = self.audit_log() else {
+            return self.send_order(order, options).await;
+        };
+        let options = options.copied().unwrap_or_default();
+        Self::validate_post_options(&order, &options)?;
+        let digests = audit.record_submitted([(
+            &order,
+            options.order_type,
+            options.post_only,
+            options.defer_exec,
+        )])?;
+        let result = self.send_order(order, Some(&options)).await;
+        audit.record_results(&digests, result.as_ref().map(std::slice::from_ref));
+        result
+    }
+
+    async fn send_order(
+        &self,
+        order: SignedOrderRequest,
+        options: Option<&PostOrderOptions>,
+    ) -> Result<PostOrderResponse> {
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
         let options = options.copied().unwrap_or_default();
-
-        if options.post_only && matches!(options.order_type, OrderType::FOK | OrderType::FAK) {
-            return Err(PolyfillError::validation(
-                "post_only is not supported for FOK/FAK orders",
-            ));
-        }
-        let expiration = order.expiration.parse::<u64>().map_err(|e| {
-            PolyfillError::validation(format!(
-                "Invalid order expiration '{}': {e}",
-                order.expiration
-            ))
-        })?;
-        if expiration > 0 && options.order_type != OrderType::GTD {
-            return Err(PolyfillError::validation(
-                "expiration is only supported for GTD orders",
-            ));
-        }
+        Self::validate_post_options(&order, &options)?;
+        self.check_schedule()?;
+        self.check_resolution_guard(&order)?;
+        self.check_price_band(&order)?;
 
         // Owner field must reference the credential principal identifier
         // to maintain consistency with the authentication context layer
         let body = PostOrder::new(order, api_creds.api_key.clone(), options);
         let body_bytes = Self::serialize_json_body(&body)?;
 
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -1218,22 +2322,183 @@ impl ClobClient {
             body_bytes,
         );
 
-        let response = req.send().await?;
+        let started = std::time::Instant::now();
+        let response = req.send_retrying(self).await.map_err(PolyfillError::from);
+        self.record_post("post_order", started, response.as_ref().err());
+        let response = response?;
         if !response.status().is_success() {
-            let status = response.status().as_u16();
-            let body = response.text().await.unwrap_or_default();
-            let message = if body.is_empty() {
-                "Failed to post order".to_string()
-            } else {
-                format!("Failed to post order: {}", body)
-            };
-            return Err(PolyfillError::api(status, message));
+            let e =
+                PolyfillError::api_from_response("POST", response, "Failed to post order").await;
+            self.record_post("post_order", started, Some(&e));
+            return Err(e);
         }
 
-        response
+        let response = response
             .json::<PostOrderResponse>()
             .await
-            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))
+            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))?;
+        self.record_posted(std::slice::from_ref(&response));
+        Ok(response)
+    }
+
+    fn validate_post_options(order: &SignedOrderRequest, options: &PostOrderOptions) -> Result<()> {
+        if options.post_only && matches!(options.order_type, OrderType::FOK | OrderType::FAK) {
+            return Err(PolyfillError::validation(
+                "post_only is not supported for FOK/FAK orders",
+            ));
+        }
+        let expiration = order.expiration.parse::<u64>().map_err(|e| {
+            PolyfillError::validation(format!(
+                "Invalid order expiration '{}': {e}",
+                order.expiration
+            ))
+        })?;
+        if expiration > 0 && options.order_type != OrderType::GTD {
+            return Err(PolyfillError::validation(
+                "expiration is only supported for GTD orders",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Post several orders through the batch endpoint.
+    ///
+    /// Orders are sent in batches of [`MAX_BATCH_ORDERS`]; responses come back
+    /// in the order of `orders`. Each response carries its own `success` flag,
+    /// so one rejected order does not fail the call.
+    pub async fn post_orders(
+        &self,
+        orders: Vec<(SignedOrderRequest, PostOrderOptions)>,
+    ) -> Result<Vec<PostOrderResponse>> {
+        let Some(audit) = self.audit_log() else {
+            return self.send_orders(orders).await;
+        };
+        for (order, options) in &orders {
+            Self::validate_post_options(order, options)?;
+        }
+
+        // One batch at a time, so each outcome is recorded against the orders
+        // it belongs to
+        let mut orders = orders;
+        let mut responses = Vec::with_capacity(orders.len());
+        while !orders.is_empty() {
+            let rest = orders.split_off(orders.len().min(MAX_BATCH_ORDERS));
+            let digests = audit.record_submitted(orders.iter().map(|(order, options)| {
+                (
+                    order,
+                    options.order_type,
+                    options.post_only,
+                    options.defer_exec,
+                )
+            }))?;
+            let result = self.send_orders(orders).await;
+            audit.record_results(&digests, result.as_deref());
+            responses.extend(result?);
+            orders = rest;
+        }
+        Ok(responses)
+    }
+
+    async fn send_orders(
+        &self,
+        orders: Vec<(SignedOrderRequest, PostOrderOptions)>,
+    ) -> Result<Vec<PostOrderResponse>> {
+        let signer = &self
+            .current_signer()
+            .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
+        let api_creds = &self
+            .prepared_api_creds()
+            .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
+        self.check_schedule()?;
+        for (order, options) in &orders {
+            Self::validate_post_options(order, options)?;
+            self.check_resolution_guard(order)?;
+            self.check_price_band(order)?;
+        }
+
+        let mut body: Vec<PostOrder> = orders
+            .into_iter()
+            .map(|(order, options)| PostOrder::new(order, api_creds.api_key.clone(), options))
+            .collect();
+        let mut responses = Vec::with_capacity(body.len());
+        while !body.is_empty() {
+            let rest = body.split_off(body.len().min(MAX_BATCH_ORDERS));
+            let body_bytes = Self::serialize_json_body(&body)?;
+            body = rest;
+
+            self.throttle().await;
+            let headers = create_l2_headers_with_body_bytes(
+                signer,
+                api_creds,
+                "POST",
+                "/orders",
+                Some(&body_bytes),
+            )?;
+            let req = self.create_request_with_json_bytes(
+                Method::POST,
+                "/orders",
+                headers.into_iter(),
+                body_bytes,
+            );
+
+            let started = std::time::Instant::now();
+            let response = req.send_retrying(self).await.map_err(PolyfillError::from);
+            self.record_post("post_orders", started, response.as_ref().err());
+            let response = response?;
+            if !response.status().is_success() {
+                let e = PolyfillError::api_from_response("POST", response, "Failed to post orders")
+                    .await;
+                self.record_post("post_orders", started, Some(&e));
+                return Err(e);
+            }
+            let batch = response
+                .json::<Vec<PostOrderResponse>>()
+                .await
+                .map_err(|e| {
+                    PolyfillError::parse(format!("Failed to parse response: {e}"), None)
+                })?;
+            self.record_posted(&batch);
+            responses.extend(batch);
+        }
+        Ok(responses)
+    }
+
+    /// Create several limit orders and post them in batches
+    pub async fn create_and_post_orders(
+        &self,
+        orders: &[OrderArgs],
+        create_options: Option<&CreateOrderOptions>,
+        post_options: Option<&PostOrderOptions>,
+    ) -> Result<Vec<PostOrderResponse>> {
+        let post_options = post_options.copied().unwrap_or_default();
+        let mut signed = Vec::with_capacity(orders.len());
+        let mut reservations = Vec::with_capacity(orders.len());
+        for order_args in orders {
+            reservations.push(self.reserve_duplicate(None, order_args)?);
+            signed.push((
+                self.create_order(order_args, create_options).await?,
+                post_options,
+            ));
+        }
+        // From here on a cancelled post may have placed the orders
+        let reservations: Vec<_> = reservations
+            .into_iter()
+            .map(|reservation| reservation.map(crate::dedup::DuplicateReservation::in_flight))
+            .collect();
+        let result = self.post_orders(signed).await;
+        match &result {
+            Ok(responses) => {
+                for (reservation, response) in reservations.into_iter().zip(responses) {
+                    Self::settle_duplicate(reservation, Ok(response));
+                }
+            },
+            Err(e) => {
+                for reservation in reservations {
+                    Self::settle_duplicate(reservation, Err(e));
+                }
+            },
+        }
+        result
     }
 
     /// Create and post an order in one call
@@ -1243,8 +2508,82 @@ impl ClobClient {
         create_options: Option<&CreateOrderOptions>,
         post_options: Option<&PostOrderOptions>,
     ) -> Result<PostOrderResponse> {
+        let reservation = self.reserve_duplicate(None, order_args)?;
         let order = self.create_order(order_args, create_options).await?;
-        self.post_order(order, post_options).await
+        let reservation = reservation.map(crate::dedup::DuplicateReservation::in_flight);
+        let result = self.post_order(order, post_options).await;
+        Self::settle_duplicate(reservation, result.as_ref());
+        result
+    }
+
+    /// Create and post an order exactly once under `client_id`.
+    ///
+    /// The signed order is written to `journal` before it is sent, and the
+    /// outcome after. A rejected order is recorded as failed; if the outcome
+    /// is unknown (e.g. a timeout) the intent stays pending for
+    /// [`crate::journal::OrderJournal::reconcile`]. Reusing a `client_id`
+    /// fails without posting.
+    pub async fn post_order_journaled(
+        &self,
+        journal: &crate::journal::OrderJournal,
+        client_id: &str,
+        order_args: &OrderArgs,
+        create_options: Option<&CreateOrderOptions>,
+        post_options: Option<&PostOrderOptions>,
+    ) -> Result<PostOrderResponse> {
+        let reservation = self.reserve_duplicate(Some(client_id), order_args)?;
+        let order = self.create_order(order_args, create_options).await?;
+        let options = post_options.copied().unwrap_or_default();
+        let intent = crate::journal::OrderIntent {
+            client_id: client_id.to_string(),
+            recorded_at: chrono::Utc::now(),
+            token_id: order_args.token_id.to_string(),
+            side: order_args.side,
+            price: order_args.price,
+            size: order_args.size,
+            order,
+            order_type: options.order_type,
+            post_only: options.post_only,
+            defer_exec: options.defer_exec,
+        };
+        journal.record_intent(&intent)?;
+        let reservation = reservation.map(crate::dedup::DuplicateReservation::in_flight);
+        let result = self.post_intent(journal, &intent).await;
+        Self::settle_duplicate(reservation, result.as_ref());
+        result
+    }
+
+    /// Post a pending intent again, e.g. one reconciliation could not find.
+    ///
+    /// The same signed order is sent, so if the first attempt did reach the
+    /// exchange this one is rejected rather than placing a duplicate.
+    pub async fn resubmit_intent(
+        &self,
+        journal: &crate::journal::OrderJournal,
+        intent: &crate::journal::OrderIntent,
+    ) -> Result<PostOrderResponse> {
+        self.post_intent(journal, intent).await
+    }
+
+    async fn post_intent(
+        &self,
+        journal: &crate::journal::OrderJournal,
+        intent: &crate::journal::OrderIntent,
+    ) -> Result<PostOrderResponse> {
+        let result = self
+            .post_order(intent.order.clone(), Some(&intent.post_options()))
+            .await;
+        match &result {
+            Ok(response) if response.success => {
+                journal.record_completed(&intent.client_id, &response.order_id)?
+            },
+            Ok(response) => journal.record_failed(&intent.client_id, &response.error_msg)?,
+            Err(e) if e.is_retryable() => {
+                warn!("Outcome of order {} unknown: {}", intent.client_id, e)
+            },
+            Err(e) => journal.record_failed(&intent.client_id, &e.to_string())?,
+        }
+        result
     }
 
     /// Create and post a market order in one call.
@@ -1259,24 +2598,127 @@ impl ClobClient {
             post_only: false,
             defer_exec: false,
         });
+        let reservation = self.reserve_market_duplicate(order_args)?;
         let order = self.create_market_order(order_args, create_options).await?;
-        self.post_order(order, Some(&post_options)).await
+        let reservation = reservation.map(crate::dedup::DuplicateReservation::in_flight);
+        let result = self.post_order(order, Some(&post_options)).await;
+        Self::settle_duplicate(reservation, result.as_ref());
+        result
+    }
+
+    /// Create and post a market order, executing only what the book can fill
+    /// within `price_limit` instead of failing when depth runs out.
+    ///
+    /// The executed amount is taken from the response's making amount, so
+    /// the book moving between the snapshot and the post is accounted for.
+    /// With [`MarketRemainder::Rest`] the unfilled part is posted as a GTC
+    /// limit order; otherwise it is returned in
+    /// [`PartialMarketOrder::remainder`]. Nothing is rested when an accepted
+    /// order does not report what it executed.
+    pub async fn create_and_post_market_order_partial(
+        &self,
+        order_args: &MarketOrderArgs,
+        create_options: Option<&CreateOrderOptions>,
+        remainder: MarketRemainder,
+    ) -> Result<PartialMarketOrder> {
+        let book = self.get_order_book(&order_args.token_id).await?;
+        let levels: Vec<crate::types::BookLevel> = match order_args.side {
+            Side::BUY => book.asks,
+            Side::SELL => book.bids,
+        }
+        .into_iter()
+        .map(|s| crate::types::BookLevel {
+            price: s.price,
+            size: s.size,
+        })
+        .collect();
+        let split = crate::orders::split_market_amount(
+            &levels,
+            order_args.amount,
+            order_args.side,
+            order_args.price_limit,
+        );
+
+        let mut outcome = PartialMarketOrder {
+            requested: order_args.amount,
+            executed: Decimal::ZERO,
+            remainder: order_args.amount,
+            execution: None,
+            follow_up: None,
+        };
+        let mut unknown_fill = false;
+        if !split.fillable.is_zero() {
+            let mut args = order_args.clone();
+            args.amount = split.fillable;
+            let response = self
+                .create_and_post_market_order(&args, create_options, None)
+                .await?;
+            if response.success {
+                // Collateral given on buys, shares given on sells: the same
+                // units as the requested amount
+                match Decimal::from_str(&response.making_amount) {
+                    Ok(made) => {
+                        outcome.executed = made.clamp(Decimal::ZERO, split.fillable);
+                        outcome.remainder -= outcome.executed;
+                    },
+                    Err(_) => {
+                        warn!(
+                            "Order {} did not report its making amount; not resting the remainder",
+                            response.order_id
+                        );
+                        unknown_fill = true;
+                    },
+                }
+            }
+            outcome.execution = Some(response);
+        }
+
+        if remainder == MarketRemainder::Rest && !unknown_fill && !outcome.remainder.is_zero() {
+            let price = order_args
+                .price_limit
+                .or(split.worst_price)
+                .ok_or_else(|| {
+                    PolyfillError::validation("No price to rest the remainder at: book is empty")
+                })?;
+            let size = match order_args.side {
+                Side::BUY => outcome.remainder / price,
+                Side::SELL => outcome.remainder,
+            }
+            .trunc_with_scale(2);
+            if !size.is_zero() {
+                let args =
+                    OrderArgs::new(order_args.token_id.clone(), price, size, order_args.side);
+                let response = self
+                    .create_and_post_order(&args, create_options, None)
+                    .await?;
+                if response.success {
+                    outcome.remainder = Decimal::ZERO;
+                }
+                outcome.follow_up = Some(response);
+            }
+        }
+        Ok(outcome)
     }
 
     /// Cancel an order
     pub async fn cancel(&self, order_id: &str) -> Result<CancelOrdersResponse> {
-        let signer = self
-            .signer
-            .as_ref()
+        let body = serde_json::json!({ "orderID": order_id });
+        self.audited_cancel("/order", body, self.send_cancel(order_id), Clone::clone)
+            .await
+    }
+
+    async fn send_cancel(&self, order_id: &str) -> Result<CancelOrdersResponse> {
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let body = std::collections::HashMap::from([("orderID", order_id)]);
         let body_bytes = Self::serialize_json_body(&body)?;
 
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -1291,32 +2733,46 @@ impl ClobClient {
             body_bytes,
         );
 
-        let response = req.send().await?;
+        let response = req.send_retrying(self).await?;
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "DELETE",
+                response,
                 "Failed to cancel order",
-            ));
+            )
+            .await);
         }
 
-        response
+        let response = response
             .json::<CancelOrdersResponse>()
             .await
-            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))
+            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))?;
+        self.record_cancelled(&response);
+        Ok(response)
     }
 
     /// Cancel multiple orders
     pub async fn cancel_orders(&self, order_ids: &[String]) -> Result<CancelOrdersResponse> {
-        let signer = self
-            .signer
-            .as_ref()
+        let body = serde_json::json!(order_ids);
+        self.audited_cancel(
+            "/orders",
+            body,
+            self.send_cancel_orders(order_ids),
+            Clone::clone,
+        )
+        .await
+    }
+
+    async fn send_cancel_orders(&self, order_ids: &[String]) -> Result<CancelOrdersResponse> {
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let body_bytes = Self::serialize_json_body(order_ids)?;
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -1331,47 +2787,149 @@ impl ClobClient {
             body_bytes,
         );
 
-        let response = req.send().await?;
+        let response = req.send_retrying(self).await?;
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "DELETE",
+                response,
                 "Failed to cancel orders",
-            ));
+            )
+            .await);
         }
 
-        response
+        let response = response
             .json::<CancelOrdersResponse>()
             .await
-            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))
+            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))?;
+        self.record_cancelled(&response);
+        Ok(response)
     }
 
     /// Cancel all orders
     pub async fn cancel_all(&self) -> Result<CancelOrdersResponse> {
-        let signer = self
-            .signer
-            .as_ref()
+        self.audited_cancel(
+            "/cancel-all",
+            Value::Null,
+            self.send_cancel_all(),
+            Clone::clone,
+        )
+        .await
+    }
+
+    async fn send_cancel_all(&self) -> Result<CancelOrdersResponse> {
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
+        self.throttle().await;
         let headers = create_l2_headers::<Value>(signer, api_creds, "DELETE", "/cancel-all", None)?;
         let req =
             self.create_request_with_headers(Method::DELETE, "/cancel-all", headers.into_iter());
 
-        let response = req.send().await?;
+        let response = req.send_retrying(self).await?;
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "DELETE",
+                response,
                 "Failed to cancel all orders",
+            )
+            .await);
+        }
+
+        let response = response
+            .json::<CancelOrdersResponse>()
+            .await
+            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))?;
+        self.record_cancelled(&response);
+        Ok(response)
+    }
+
+    /// Cancel every open order in one market (by condition ID), leaving
+    /// orders in other markets alone
+    pub async fn cancel_condition_orders(
+        &self,
+        condition_id: &str,
+    ) -> Result<CancelOrdersResponse> {
+        self.cancel_scoped(&[("market", condition_id)]).await
+    }
+
+    /// Cancel every open order on one outcome token
+    pub async fn cancel_asset_orders(&self, token_id: &TokenId) -> Result<CancelOrdersResponse> {
+        self.cancel_scoped(&[("asset_id", token_id.as_str())]).await
+    }
+
+    /// Cancel through `/cancel-market-orders` filtered on every field of
+    /// `filters`
+    async fn cancel_scoped(&self, filters: &[(&str, &str)]) -> Result<CancelOrdersResponse> {
+        let body: serde_json::Map<String, Value> = filters
+            .iter()
+            .map(|&(field, value)| (field.to_string(), Value::from(value)))
+            .collect();
+        self.audited_cancel(
+            "/cancel-market-orders",
+            Value::Object(body),
+            self.send_cancel_scoped(filters),
+            Clone::clone,
+        )
+        .await
+    }
+
+    async fn send_cancel_scoped(&self, filters: &[(&str, &str)]) -> Result<CancelOrdersResponse> {
+        // An empty filter would match every market
+        if filters.is_empty() {
+            return Err(PolyfillError::validation(
+                "Scoped cancel needs a market or asset_id",
             ));
         }
+        if let Some((field, _)) = filters.iter().find(|(_, value)| value.trim().is_empty()) {
+            return Err(PolyfillError::validation(format!(
+                "Scoped cancel needs a non-empty {field}"
+            )));
+        }
+        let signer = &self
+            .current_signer()
+            .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
+        let api_creds = &self
+            .prepared_api_creds()
+            .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
-        response
+        let endpoint = "/cancel-market-orders";
+        let body: std::collections::HashMap<&str, &str> = filters.iter().copied().collect();
+        let body_bytes = Self::serialize_json_body(&body)?;
+        self.throttle().await;
+        let headers = create_l2_headers_with_body_bytes(
+            signer,
+            api_creds,
+            "DELETE",
+            endpoint,
+            Some(&body_bytes),
+        )?;
+        let req = self.create_request_with_json_bytes(
+            Method::DELETE,
+            endpoint,
+            headers.into_iter(),
+            body_bytes,
+        );
+
+        let response = req.send_retrying(self).await?;
+        if !response.status().is_success() {
+            return Err(PolyfillError::api_from_response(
+                "DELETE",
+                response,
+                "Failed to cancel market orders",
+            )
+            .await);
+        }
+
+        let response = response
             .json::<CancelOrdersResponse>()
             .await
-            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))
+            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {e}"), None))?;
+        self.record_cancelled(&response);
+        Ok(response)
     }
 
     /// Get open orders with optional filtering
@@ -1387,13 +2945,11 @@ impl ClobClient {
         params: Option<&crate::types::OpenOrderParams>,
         next_cursor: Option<&str>,
     ) -> Result<Vec<crate::types::OpenOrder>> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::GET;
@@ -1411,6 +2967,7 @@ impl ClobClient {
 
         while next_cursor != "LTE=" {
             // END_CURSOR
+            self.throttle().await;
             let req = self
                 .http_client
                 .request(method.clone(), format!("{}{}", self.base_url, endpoint))
@@ -1423,7 +2980,7 @@ impl ClobClient {
                 .fold(req, |r, (k, v)| r.header(HeaderName::from_static(k), v));
 
             let page = r
-                .send()
+                .send_retrying(self)
                 .await
                 .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?
                 .json::<DataPage<crate::types::OpenOrder>>()
@@ -1454,13 +3011,11 @@ impl ClobClient {
         trade_params: Option<&crate::types::TradeParams>,
         next_cursor: Option<&str>,
     ) -> Result<Vec<Value>> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::GET;
@@ -1478,6 +3033,7 @@ impl ClobClient {
 
         while next_cursor != "LTE=" {
             // END_CURSOR
+            self.throttle().await;
             let req = self
                 .http_client
                 .request(method.clone(), format!("{}{}", self.base_url, endpoint))
@@ -1490,7 +3046,7 @@ impl ClobClient {
                 .fold(req, |r, (k, v)| r.header(HeaderName::from_static(k), v));
 
             let page = r
-                .send()
+                .send_retrying(self)
                 .await
                 .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?
                 .json::<DataPage<Value>>()
@@ -1517,19 +3073,17 @@ impl ClobClient {
         &self,
         params: Option<crate::types::BalanceAllowanceParams>,
     ) -> Result<Value> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let mut params = params.unwrap_or_default();
         if params.signature_type.is_none() {
             params.set_signature_type(
-                self.order_builder
+                self.current_order_builder()
                     .as_ref()
                     .expect("OrderBuilder not set")
                     .get_sig_type(),
@@ -1540,27 +3094,181 @@ impl ClobClient {
 
         let method = Method::GET;
         let endpoint = "/balance-allowance";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
         let response = self
             .http_client
-            .request(method, format!("{}{}", self.base_url, endpoint))
-            .headers(
-                headers
-                    .into_iter()
-                    .map(|(k, v)| (HeaderName::from_static(k), v.parse().unwrap()))
-                    .collect(),
+            .request(method, format!("{}{}", self.base_url, endpoint))
+            .headers(
+                headers
+                    .into_iter()
+                    .map(|(k, v)| (HeaderName::from_static(k), v.parse().unwrap()))
+                    .collect(),
+            )
+            .query(&query_params)
+            .send_retrying(self)
+            .await
+            .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
+
+        response
+            .json::<Value>()
+            .await
+            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {}", e), None))
+    }
+
+    /// Balance cache fed by the user channel
+    pub fn balance_cache(&self) -> std::sync::Arc<crate::balances::BalanceCache> {
+        self.balance_cache.clone()
+    }
+
+    /// Balance and allowances for `key`, served from the cache when fresh.
+    ///
+    /// Misses fetch `/balance-allowance` and populate the cache.
+    pub async fn get_cached_balance(
+        &self,
+        key: &crate::balances::BalanceKey,
+    ) -> Result<crate::balances::CachedBalance> {
+        if let Some(balance) = self.balance_cache.get(key) {
+            return Ok(balance);
+        }
+
+        let response = self.get_balance_allowance(Some(key.params())).await?;
+        let balance = crate::balances::CachedBalance::from_response(&response)?;
+        self.balance_cache.insert(key.clone(), balance.clone());
+        Ok(balance)
+    }
+
+    /// Cancel every open order, wait for the book to confirm it, and close
+    /// the client's connections.
+    ///
+    /// Orders that the exchange refused to cancel, that matched while
+    /// cancelling, or that are still open after `options.timeout` are listed
+    /// in the report. User channels started by this client and the
+    /// keep-alive task are stopped even when some orders could not be
+    /// cancelled.
+    pub async fn safe_shutdown(
+        &self,
+        options: &crate::types::ShutdownOptions,
+    ) -> Result<crate::types::ShutdownReport> {
+        let open_orders = self.get_orders(None, None).await?;
+        let response = self.cancel_all().await?;
+
+        let deadline = tokio::time::Instant::now() + options.timeout;
+        let still_open = loop {
+            let remaining: Vec<_> = self
+                .get_orders(None, None)
+                .await?
+                .into_iter()
+                .filter(|order| open_orders.iter().any(|open| open.id == order.id))
+                .collect();
+            if remaining.is_empty() || tokio::time::Instant::now() >= deadline {
+                break remaining;
+            }
+            tokio::time::sleep(options.poll_interval).await;
+        };
+
+        let mut filled = Vec::new();
+        for open in &open_orders {
+            if response.canceled.contains(&open.id) {
+                continue;
+            }
+            match self.get_order(&open.id).await {
+                Ok(order) if order.size_matched > open.size_matched => filled.push(order),
+                Ok(_) => {},
+                Err(e) => warn!("Could not fetch final state of {}: {}", open.id, e),
+            }
+        }
+
+        self.stop_keepalive().await;
+        for channel in self.user_channels.lock().drain(..) {
+            channel.abort();
+        }
+
+        Ok(crate::types::ShutdownReport {
+            open_orders,
+            cancelled: response.canceled,
+            not_cancelled: response.not_canceled,
+            filled,
+            still_open,
+        })
+    }
+
+    /// Gather balances, open orders, recent fills and (optionally) data API
+    /// positions concurrently.
+    ///
+    /// Fails if any of the requests fails, so a successful summary is
+    /// always complete.
+    pub async fn account_summary(
+        &self,
+        options: &crate::types::AccountSummaryOptions,
+    ) -> Result<crate::types::AccountSummary> {
+        let after = chrono::Utc::now()
+            - chrono::Duration::from_std(options.fills_lookback).unwrap_or_default();
+        let trade_params = crate::types::TradeParams {
+            id: None,
+            maker_address: None,
+            market: None,
+            asset_id: None,
+            before: None,
+            after: Some(after.timestamp().max(0) as u64),
+        };
+
+        let positions = async {
+            match &options.positions_user {
+                Some(user) => self
+                    .get_data_api_positions(&options.data_api_url, user)
+                    .await
+                    .map(Some),
+                None => Ok(None),
+            }
+        };
+
+        let (balance_allowance, open_orders, recent_fills, positions) = tokio::try_join!(
+            self.get_balance_allowance(Some(crate::types::BalanceAllowanceParams {
+                asset_type: Some(crate::types::AssetType::COLLATERAL),
+                ..Default::default()
+            })),
+            self.get_orders(None, None),
+            self.get_trades(Some(&trade_params), None),
+            positions,
+        )?;
+
+        Ok(crate::types::AccountSummary {
+            balance_allowance,
+            open_orders,
+            recent_fills,
+            positions,
+            fetched_at: chrono::Utc::now(),
+        })
+    }
+
+    async fn get_data_api_positions(
+        &self,
+        data_api_url: &str,
+        user: &str,
+    ) -> Result<Vec<crate::types::DataApiPosition>> {
+        let response = self
+            .http_client
+            .get(format!("{}/positions", data_api_url.trim_end_matches('/')))
+            .query(&[("user", user)])
+            .send_retrying(self)
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
+                "Failed to get positions",
             )
-            .query(&query_params)
-            .send()
-            .await
-            .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
+            .await);
+        }
 
         response
-            .json::<Value>()
+            .json()
             .await
-            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {}", e), None))
+            .map_err(|e| PolyfillError::parse(format!("Failed to parse positions: {e}"), None))
     }
 
     /// Set up notifications for order fills and other events
@@ -1572,17 +3280,16 @@ impl ClobClient {
     ///
     /// The signature proves you own the account and want to receive notifications.
     pub async fn get_notifications(&self) -> Result<Value> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::GET;
         let endpoint = "/notifications";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -1598,13 +3305,13 @@ impl ClobClient {
             .query(&[(
                 "signature_type",
                 &self
-                    .order_builder
+                    .current_order_builder()
                     .as_ref()
                     .expect("OrderBuilder not set")
                     .get_sig_type()
                     .to_string(),
             )])
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -1634,14 +3341,16 @@ impl ClobClient {
             .http_client
             .post(format!("{}/midpoints", self.base_url))
             .json(&request_data)
-            .send()
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "POST",
+                response,
                 "Failed to get batch midpoints",
-            ));
+            )
+            .await);
         }
 
         let midpoints: std::collections::HashMap<String, Decimal> = response.json().await?;
@@ -1671,14 +3380,16 @@ impl ClobClient {
             .http_client
             .post(format!("{}/prices", self.base_url))
             .json(&request_data)
-            .send()
+            .send_retrying(self)
             .await?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "POST",
+                response,
                 "Failed to get batch prices",
-            ));
+            )
+            .await);
         }
 
         let prices: std::collections::HashMap<String, std::collections::HashMap<Side, Decimal>> =
@@ -1697,7 +3408,7 @@ impl ClobClient {
             .http_client
             .post(format!("{}/books", self.base_url))
             .json(&request_data)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -1709,17 +3420,16 @@ impl ClobClient {
 
     /// Get single order by ID
     pub async fn get_order(&self, order_id: &str) -> Result<crate::types::OpenOrder> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::config("Signer not configured"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::config("API credentials not configured"))?;
 
         let method = Method::GET;
         let endpoint = &format!("/data/order/{}", order_id);
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -1732,7 +3442,7 @@ impl ClobClient {
                     .map(|(k, v)| (HeaderName::from_static(k), v.parse().unwrap()))
                     .collect(),
             )
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -1748,7 +3458,7 @@ impl ClobClient {
             .http_client
             .get(format!("{}/last-trade-price", self.base_url))
             .query(&[("token_id", token_id)])
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -1769,7 +3479,7 @@ impl ClobClient {
             .http_client
             .post(format!("{}/last-trades-prices", self.base_url))
             .json(&request_data)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -1779,62 +3489,36 @@ impl ClobClient {
             .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {}", e), None))
     }
 
-    /// Cancel market orders with optional filters
+    /// Cancel market orders with optional filters.
+    ///
+    /// Kept for polymarket-rs-client compatibility; prefer
+    /// [`Self::cancel_condition_orders`] or [`Self::cancel_asset_orders`],
+    /// which return a typed response. At least one filter is required.
     pub async fn cancel_market_orders(
         &self,
         market: Option<&str>,
         asset_id: Option<&str>,
     ) -> Result<Value> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or_else(|| PolyfillError::config("Signer not configured"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
-            .ok_or_else(|| PolyfillError::config("API credentials not configured"))?;
-
-        let method = Method::DELETE;
-        let endpoint = "/cancel-market-orders";
-        let body = std::collections::HashMap::from([
-            ("market", market.unwrap_or("")),
-            ("asset_id", asset_id.unwrap_or("")),
-        ]);
-        let body_bytes = Self::serialize_json_body(&body)?;
-
-        let headers = create_l2_headers_with_body_bytes(
-            signer,
-            api_creds,
-            method.as_str(),
-            endpoint,
-            Some(&body_bytes),
-        )?;
-
-        let response = self
-            .create_request_with_json_bytes(method, endpoint, headers.into_iter(), body_bytes)
-            .send()
-            .await
-            .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
-
-        response
-            .json::<Value>()
-            .await
-            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {}", e), None))
+        let filters: Vec<(&str, &str)> = [("market", market), ("asset_id", asset_id)]
+            .into_iter()
+            .filter_map(|(field, value)| Some((field, value?)))
+            .collect();
+        let response = self.cancel_scoped(&filters).await?;
+        Ok(serde_json::to_value(response)?)
     }
 
     /// Drop (delete) notifications by IDs
     pub async fn drop_notifications(&self, ids: &[String]) -> Result<Value> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::config("Signer not configured"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::config("API credentials not configured"))?;
 
         let method = Method::DELETE;
         let endpoint = "/notifications";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -1848,7 +3532,7 @@ impl ClobClient {
                     .collect(),
             )
             .query(&[("ids", ids.join(","))])
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -1863,19 +3547,17 @@ impl ClobClient {
         &self,
         params: Option<crate::types::BalanceAllowanceParams>,
     ) -> Result<Value> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::config("Signer not configured"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::config("API credentials not configured"))?;
 
         let mut params = params.unwrap_or_default();
         if params.signature_type.is_none() {
             params.set_signature_type(
-                self.order_builder
+                self.current_order_builder()
                     .as_ref()
                     .expect("OrderBuilder not set")
                     .get_sig_type(),
@@ -1886,6 +3568,7 @@ impl ClobClient {
 
         let method = Method::GET;
         let endpoint = "/balance-allowance/update";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -1899,7 +3582,7 @@ impl ClobClient {
                     .collect(),
             )
             .query(&query_params)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -1911,17 +3594,16 @@ impl ClobClient {
 
     /// Check if an order is scoring
     pub async fn is_order_scoring(&self, order_id: &str) -> Result<bool> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::config("Signer not configured"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::config("API credentials not configured"))?;
 
         let method = Method::GET;
         let endpoint = "/order-scoring";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -1935,7 +3617,7 @@ impl ClobClient {
                     .collect(),
             )
             .query(&[("order_id", order_id)])
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -1952,18 +3634,17 @@ impl ClobClient {
         &self,
         order_ids: &[&str],
     ) -> Result<std::collections::HashMap<String, bool>> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::config("Signer not configured"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::config("API credentials not configured"))?;
 
         let method = Method::POST;
         let endpoint = "/orders-scoring";
         let body_bytes = Self::serialize_json_body(order_ids)?;
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -1974,7 +3655,7 @@ impl ClobClient {
 
         let response = self
             .create_request_with_json_bytes(method, endpoint, headers.into_iter(), body_bytes)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -1993,18 +3674,17 @@ impl ClobClient {
         &self,
         request: &crate::types::RfqCreateRequest,
     ) -> Result<crate::types::RfqCreateRequestResponse> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::POST;
         let endpoint = "/rfq/request";
         let body_bytes = Self::serialize_json_body(request)?;
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -2015,15 +3695,17 @@ impl ClobClient {
 
         let response = self
             .create_request_with_json_bytes(method, endpoint, headers.into_iter(), body_bytes)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "POST",
+                response,
                 "Failed to create RFQ request",
-            ));
+            )
+            .await);
         }
 
         response
@@ -2034,13 +3716,11 @@ impl ClobClient {
 
     /// Cancel an RFQ request.
     pub async fn cancel_rfq_request(&self, request_id: &str) -> Result<()> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::DELETE;
@@ -2049,6 +3729,7 @@ impl ClobClient {
             request_id: request_id.to_string(),
         };
         let body_bytes = Self::serialize_json_body(&body)?;
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -2059,15 +3740,17 @@ impl ClobClient {
 
         let response = self
             .create_request_with_json_bytes(method, endpoint, headers.into_iter(), body_bytes)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "DELETE",
+                response,
                 "Failed to cancel RFQ request",
-            ));
+            )
+            .await);
         }
 
         Ok(())
@@ -2078,17 +3761,16 @@ impl ClobClient {
         &self,
         params: Option<&crate::types::RfqRequestsParams>,
     ) -> Result<crate::types::RfqListResponse<crate::types::RfqRequestData>> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::GET;
         let endpoint = "/rfq/data/requests";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -2097,15 +3779,17 @@ impl ClobClient {
         let response = self
             .create_request_with_headers(method, endpoint, headers.into_iter())
             .query(&query_params)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get RFQ requests",
-            ));
+            )
+            .await);
         }
 
         response
@@ -2119,18 +3803,17 @@ impl ClobClient {
         &self,
         quote: &crate::types::RfqCreateQuote,
     ) -> Result<crate::types::RfqCreateQuoteResponse> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::POST;
         let endpoint = "/rfq/quote";
         let body_bytes = Self::serialize_json_body(quote)?;
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -2141,15 +3824,17 @@ impl ClobClient {
 
         let response = self
             .create_request_with_json_bytes(method, endpoint, headers.into_iter(), body_bytes)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "POST",
+                response,
                 "Failed to create RFQ quote",
-            ));
+            )
+            .await);
         }
 
         response
@@ -2160,13 +3845,11 @@ impl ClobClient {
 
     /// Cancel an RFQ quote.
     pub async fn cancel_rfq_quote(&self, quote_id: &str) -> Result<()> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::DELETE;
@@ -2175,6 +3858,7 @@ impl ClobClient {
             quote_id: quote_id.to_string(),
         };
         let body_bytes = Self::serialize_json_body(&body)?;
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -2185,15 +3869,17 @@ impl ClobClient {
 
         let response = self
             .create_request_with_json_bytes(method, endpoint, headers.into_iter(), body_bytes)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "DELETE",
+                response,
                 "Failed to cancel RFQ quote",
-            ));
+            )
+            .await);
         }
 
         Ok(())
@@ -2204,17 +3890,16 @@ impl ClobClient {
         &self,
         params: Option<&crate::types::RfqQuotesParams>,
     ) -> Result<crate::types::RfqListResponse<crate::types::RfqQuoteData>> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::GET;
         let endpoint = "/rfq/data/requester/quotes";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -2223,15 +3908,17 @@ impl ClobClient {
         let response = self
             .create_request_with_headers(method, endpoint, headers.into_iter())
             .query(&query_params)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get RFQ requester quotes",
-            ));
+            )
+            .await);
         }
 
         response
@@ -2245,17 +3932,16 @@ impl ClobClient {
         &self,
         params: Option<&crate::types::RfqQuotesParams>,
     ) -> Result<crate::types::RfqListResponse<crate::types::RfqQuoteData>> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::GET;
         let endpoint = "/rfq/data/quoter/quotes";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
@@ -2264,15 +3950,17 @@ impl ClobClient {
         let response = self
             .create_request_with_headers(method, endpoint, headers.into_iter())
             .query(&query_params)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get RFQ quoter quotes",
-            ));
+            )
+            .await);
         }
 
         response
@@ -2283,32 +3971,33 @@ impl ClobClient {
 
     /// Get best quote for a request.
     pub async fn get_rfq_best_quote(&self, request_id: &str) -> Result<crate::types::RfqQuoteData> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::GET;
         let endpoint = "/rfq/data/best-quote";
+        self.throttle().await;
         let headers =
             create_l2_headers::<Value>(signer, api_creds, method.as_str(), endpoint, None)?;
 
         let response = self
             .create_request_with_headers(method, endpoint, headers.into_iter())
             .query(&[("requestId", request_id)])
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "GET",
+                response,
                 "Failed to get RFQ best quote",
-            ));
+            )
+            .await);
         }
 
         response
@@ -2322,18 +4011,17 @@ impl ClobClient {
         &self,
         body: &crate::types::RfqOrderExecutionRequest,
     ) -> Result<()> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::POST;
         let endpoint = "/rfq/request/accept";
         let body_bytes = Self::serialize_json_body(body)?;
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -2344,15 +4032,17 @@ impl ClobClient {
 
         let response = self
             .create_request_with_json_bytes(method, endpoint, headers.into_iter(), body_bytes)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "POST",
+                response,
                 "Failed to accept RFQ quote",
-            ));
+            )
+            .await);
         }
 
         Ok(())
@@ -2363,18 +4053,17 @@ impl ClobClient {
         &self,
         body: &crate::types::RfqOrderExecutionRequest,
     ) -> Result<crate::types::RfqApproveOrderResponse> {
-        let signer = self
-            .signer
-            .as_ref()
+        let signer = &self
+            .current_signer()
             .ok_or_else(|| PolyfillError::auth("Signer not set"))?;
-        let api_creds = self
-            .api_creds
-            .as_ref()
+        let api_creds = &self
+            .prepared_api_creds()
             .ok_or_else(|| PolyfillError::auth("API credentials not set"))?;
 
         let method = Method::POST;
         let endpoint = "/rfq/quote/approve";
         let body_bytes = Self::serialize_json_body(body)?;
+        self.throttle().await;
         let headers = create_l2_headers_with_body_bytes(
             signer,
             api_creds,
@@ -2385,15 +4074,17 @@ impl ClobClient {
 
         let response = self
             .create_request_with_json_bytes(method, endpoint, headers.into_iter(), body_bytes)
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
         if !response.status().is_success() {
-            return Err(PolyfillError::api(
-                response.status().as_u16(),
+            return Err(PolyfillError::api_from_response(
+                "POST",
+                response,
                 "Failed to approve RFQ order",
-            ));
+            )
+            .await);
         }
 
         response
@@ -2409,15 +4100,12 @@ impl ClobClient {
     ) -> Result<crate::types::MarketsResponse> {
         let next_cursor = next_cursor.unwrap_or("MA=="); // INITIAL_CURSOR
 
-        let response = self
+        let request = self
             .http_client
             .get(format!("{}/sampling-markets", self.base_url))
-            .query(&[("next_cursor", next_cursor)])
-            .send()
+            .query(&[("next_cursor", next_cursor)]);
+        self.get_cacheable(request, "Failed to get sampling markets")
             .await
-            .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
-
-        Self::parse_json_response(response).await
     }
 
     /// Get sampling simplified markets with pagination
@@ -2427,15 +4115,12 @@ impl ClobClient {
     ) -> Result<crate::types::SimplifiedMarketsResponse> {
         let next_cursor = next_cursor.unwrap_or("MA=="); // INITIAL_CURSOR
 
-        let response = self
+        let request = self
             .http_client
             .get(format!("{}/sampling-simplified-markets", self.base_url))
-            .query(&[("next_cursor", next_cursor)])
-            .send()
+            .query(&[("next_cursor", next_cursor)]);
+        self.get_cacheable(request, "Failed to get sampling simplified markets")
             .await
-            .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
-
-        Self::parse_json_response(response).await
     }
 
     /// Get markets with pagination
@@ -2445,15 +4130,11 @@ impl ClobClient {
     ) -> Result<crate::types::MarketsResponse> {
         let next_cursor = next_cursor.unwrap_or("MA=="); // INITIAL_CURSOR
 
-        let response = self
+        let request = self
             .http_client
             .get(format!("{}/markets", self.base_url))
-            .query(&[("next_cursor", next_cursor)])
-            .send()
-            .await
-            .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
-
-        Self::parse_json_response(response).await
+            .query(&[("next_cursor", next_cursor)]);
+        self.get_cacheable(request, "Failed to get markets").await
     }
 
     /// Get simplified markets with pagination
@@ -2463,30 +4144,20 @@ impl ClobClient {
     ) -> Result<crate::types::SimplifiedMarketsResponse> {
         let next_cursor = next_cursor.unwrap_or("MA=="); // INITIAL_CURSOR
 
-        let response = self
+        let request = self
             .http_client
             .get(format!("{}/simplified-markets", self.base_url))
-            .query(&[("next_cursor", next_cursor)])
-            .send()
+            .query(&[("next_cursor", next_cursor)]);
+        self.get_cacheable(request, "Failed to get simplified markets")
             .await
-            .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
-
-        Self::parse_json_response(response).await
     }
 
     /// Get single market by condition ID
     pub async fn get_market(&self, condition_id: &str) -> Result<crate::types::Market> {
-        let response = self
+        let request = self
             .http_client
-            .get(format!("{}/markets/{}", self.base_url, condition_id))
-            .send()
-            .await
-            .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
-
-        response
-            .json::<crate::types::Market>()
-            .await
-            .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {}", e), None))
+            .get(format!("{}/markets/{}", self.base_url, condition_id));
+        self.get_cacheable(request, "Failed to get market").await
     }
 
     /// Get market trades events
@@ -2497,7 +4168,7 @@ impl ClobClient {
                 "{}/live-activity/events/{}",
                 self.base_url, condition_id
             ))
-            .send()
+            .send_retrying(self)
             .await
             .map_err(|e| PolyfillError::network(format!("Request failed: {}", e), e))?;
 
@@ -2506,6 +4177,99 @@ impl ClobClient {
             .await
             .map_err(|e| PolyfillError::parse(format!("Failed to parse response: {}", e), None))
     }
+
+    /// Bound the next call by `deadline` instead of the client-wide timeout.
+    ///
+    /// Order entry usually wants to fail within hundreds of milliseconds
+    /// while listings can take seconds; a deadline lets each call choose.
+    pub fn with_deadline(&self, deadline: Duration) -> Deadline<'_> {
+        Deadline {
+            client: self,
+            deadline,
+        }
+    }
+}
+
+/// Hot client calls bounded by a per-call deadline.
+///
+/// Created with [`ClobClient::with_deadline`]. The deadline covers the whole
+/// call, including signing and reading the response, and an expired call
+/// fails with [`PolyfillError::Timeout`]. A posted order may still reach the
+/// exchange when its deadline expires after sending; reconcile before
+/// retrying.
+#[derive(Clone, Copy)]
+pub struct Deadline<'a> {
+    client: &'a ClobClient,
+    deadline: Duration,
+}
+
+impl Deadline<'_> {
+    async fn run<T>(
+        &self,
+        operation: &str,
+        call: impl std::future::Future<Output = Result<T>>,
+    ) -> Result<T> {
+        tokio::time::timeout(self.deadline, call)
+            .await
+            .map_err(|_| PolyfillError::timeout(self.deadline, operation))?
+    }
+
+    pub async fn get_order_book(&self, token_id: &str) -> Result<OrderBookSummary> {
+        self.run("get_order_book", self.client.get_order_book(token_id))
+            .await
+    }
+
+    pub async fn get_order_book_with_depth(
+        &self,
+        token_id: &TokenId,
+        depth: BookDepth,
+    ) -> Result<OrderBookSummary> {
+        self.run(
+            "get_order_book",
+            self.client.get_order_book_with_depth(token_id, depth),
+        )
+        .await
+    }
+
+    pub async fn get_order_books(&self, token_ids: &[String]) -> Result<Vec<OrderBookSummary>> {
+        self.run("get_order_books", self.client.get_order_books(token_ids))
+            .await
+    }
+
+    pub async fn post_order(
+        &self,
+        order: SignedOrderRequest,
+        options: Option<&PostOrderOptions>,
+    ) -> Result<PostOrderResponse> {
+        self.run("post_order", self.client.post_order(order, options))
+            .await
+    }
+
+    pub async fn create_and_post_order(
+        &self,
+        order_args: &OrderArgs,
+        create_options: Option<&CreateOrderOptions>,
+        post_options: Option<&PostOrderOptions>,
+    ) -> Result<PostOrderResponse> {
+        self.run(
+            "create_and_post_order",
+            self.client
+                .create_and_post_order(order_args, create_options, post_options),
+        )
+        .await
+    }
+
+    pub async fn cancel(&self, order_id: &str) -> Result<CancelOrdersResponse> {
+        self.run("cancel", self.client.cancel(order_id)).await
+    }
+
+    pub async fn get_markets(
+        &self,
+        next_cursor: Option<&str>,
+    ) -> Result<crate::types::MarketsResponse> {
+        self.run("get_markets", self.client.get_markets(next_cursor))
+            .await
+    }
 }
 
 // Re-export types from the canonical location in types.rs
@@ -2520,18 +4284,70 @@ pub use crate::types::{
 // Re-export for compatibility
 pub type PolyfillClient = ClobClient;
 
+/// Sends requests through [`ClobClient::set_retry_policy`]
+trait RetryingSend {
+    async fn send_retrying(self, client: &ClobClient) -> reqwest::Result<Response>;
+}
+
+impl RetryingSend for reqwest::RequestBuilder {
+    async fn send_retrying(self, client: &ClobClient) -> reqwest::Result<Response> {
+        client.execute(self.build()?).await
+    }
+}
+
+/// Delay a response asks for in its `Retry-After` header, given in seconds
+/// or as an HTTP date
+fn retry_after(response: &Response) -> Option<std::time::Duration> {
+    let value = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(std::time::Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Batch reads are POSTs; every other POST changes state
+const READ_POST_PATHS: [&str; 5] = [
+    "/books",
+    "/midpoints",
+    "/prices",
+    "/spreads",
+    "/last-trades-prices",
+];
+
+fn endpoint_class(method: &Method, path: &str) -> crate::utils::retry::EndpointClass {
+    use crate::utils::retry::EndpointClass;
+    match *method {
+        Method::GET | Method::HEAD => EndpointClass::Read,
+        Method::DELETE => EndpointClass::Cancel,
+        Method::POST if READ_POST_PATHS.iter().any(|read| path.ends_with(read)) => {
+            EndpointClass::Read
+        },
+        _ => EndpointClass::Write,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{ClobClient, OrderArgs as ClientOrderArgs};
+    use super::{ClobClient, OrderArgs as ClientOrderArgs, MAX_BATCH_ORDERS};
     use crate::types::{
-        CreateOrderOptions, OrderType, PostOrderOptions, PricesHistoryInterval, RfqCreateQuote,
-        RfqCreateRequest, RfqOrderExecutionRequest, RfqQuotesParams, RfqRequestsParams, Side,
-        SignedOrderRequest,
+        BookDepth, CreateOrderOptions, OrderType, PostOrderOptions, PricesHistoryInterval,
+        RfqCreateQuote, RfqCreateRequest, RfqOrderExecutionRequest, RfqQuotesParams,
+        RfqRequestsParams, Side, SignedOrderRequest,
     };
-    use crate::{ApiCredentials, ClientConfig, PolyfillError};
+    use crate::{ApiCredentials, ClientConfig, ClobApiVersion, PolyfillError};
     use mockito::{Matcher, Server};
     use rust_decimal::Decimal;
-    use serde_json::json;
+    use serde_json::{json, Value};
     use std::str::FromStr;
     use tokio;
 
@@ -2595,15 +4411,15 @@ mod tests {
     async fn test_client_creation() {
         let client = create_test_client("https://test.example.com");
         assert_eq!(client.base_url, "https://test.example.com");
-        assert!(client.signer.is_none());
-        assert!(client.api_creds.is_none());
+        assert!(client.current_signer().is_none());
+        assert!(client.prepared_api_creds().is_none());
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_client_from_config_with_signer() {
         let client = create_test_client_with_auth("https://test.example.com");
         assert_eq!(client.base_url, "https://test.example.com");
-        assert!(client.signer.is_some());
+        assert!(client.current_signer().is_some());
         assert_eq!(client.chain_id, 137);
     }
 
@@ -2627,15 +4443,15 @@ mod tests {
         .expect("configured client");
 
         assert_eq!(client.base_url, "https://test.example.com");
-        assert!(client.signer.is_some());
-        assert!(client.api_creds.is_some());
+        assert!(client.current_signer().is_some());
+        assert!(client.prepared_api_creds().is_some());
         assert_eq!(client.chain_id, 137);
     }
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_set_api_creds() {
-        let mut client = create_test_client("https://test.example.com");
-        assert!(client.api_creds.is_none());
+        let client = create_test_client("https://test.example.com");
+        assert!(client.prepared_api_creds().is_none());
 
         let api_creds = ApiCredentials {
             api_key: "test_key".to_string(),
@@ -2644,8 +4460,76 @@ mod tests {
         };
 
         client.set_api_creds(api_creds.clone()).unwrap();
-        assert!(client.api_creds.is_some());
-        assert_eq!(client.api_creds.unwrap().api_key, "test_key");
+        assert!(client.prepared_api_creds().is_some());
+        assert_eq!(client.prepared_api_creds().unwrap().api_key, "test_key");
+    }
+
+    #[test]
+    fn test_swap_credentials_reaches_clones() {
+        let client = create_test_client_with_l2_auth("https://test.example.com");
+        let clone = client.clone();
+        let old_address = client.get_address().unwrap();
+
+        let signer = alloy_signer_local::PrivateKeySigner::from_str(
+            "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318",
+        )
+        .unwrap();
+        let new_address = signer.address();
+        let previous = client.swap_signer(signer).unwrap().unwrap();
+        assert_eq!(
+            alloy_primitives::hex::encode_prefixed(previous.as_slice()),
+            old_address
+        );
+        let expected = alloy_primitives::hex::encode_prefixed(new_address.as_slice());
+        assert_eq!(clone.get_address().unwrap(), expected);
+
+        let previous = client
+            .swap_api_creds(ApiCredentials {
+                api_key: "rotated_key".to_string(),
+                secret: "dGVzdF9zZWNyZXRfa2V5XzEyMzQ1".to_string(),
+                passphrase: "test_passphrase".to_string(),
+            })
+            .unwrap();
+        assert_eq!(previous.unwrap().api_key, "test_key");
+        assert_eq!(clone.prepared_api_creds().unwrap().api_key, "rotated_key");
+    }
+
+    #[test]
+    fn test_body_buffer_is_reused_once_released() {
+        let body = serde_json::json!({"orderID": "0xabc", "owner": "key"});
+        let first = ClobClient::serialize_json_body(&body).unwrap();
+        assert_eq!(first, serde_json::to_vec(&body).unwrap());
+
+        // Still held by an in-flight request: the next body gets fresh memory
+        let second = ClobClient::serialize_json_body(&body).unwrap();
+        assert_ne!(first.as_ptr(), second.as_ptr());
+
+        let reused = second.as_ptr();
+        drop(first);
+        drop(second);
+        let third = ClobClient::serialize_json_body(&body).unwrap();
+        assert_eq!(third.as_ptr(), reused);
+    }
+
+    #[test]
+    fn test_clones_share_credentials_across_tasks() {
+        fn assert_send_sync<T: Clone + Send + Sync + 'static>() {}
+        assert_send_sync::<ClobClient>();
+
+        let client = create_test_client("https://test.example.com");
+        let clone = client.clone();
+        std::thread::spawn(move || {
+            clone
+                .set_api_creds(ApiCredentials {
+                    api_key: "rotated_key".to_string(),
+                    secret: "dGVzdF9zZWNyZXRfa2V5XzEyMzQ1".to_string(),
+                    passphrase: "test_passphrase".to_string(),
+                })
+                .unwrap();
+        })
+        .join()
+        .unwrap();
+        assert_eq!(client.prepared_api_creds().unwrap().api_key, "rotated_key");
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -2674,7 +4558,7 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_set_api_creds_rejects_invalid_api_secret() {
-        let mut client = create_test_client("https://test.example.com");
+        let client = create_test_client("https://test.example.com");
         let api_creds = ApiCredentials {
             api_key: "test_key".to_string(),
             secret: "not valid base64!".to_string(),
@@ -2683,7 +4567,7 @@ mod tests {
 
         let err = client.set_api_creds(api_creds).unwrap_err();
 
-        assert!(client.api_creds.is_none());
+        assert!(client.prepared_api_creds().is_none());
         assert!(err.to_string().contains("Failed to decode base64 secret"));
     }
 
@@ -2813,18 +4697,52 @@ mod tests {
         let result = client.get_order_book("0x123").await;
 
         mock.assert_async().await;
-        assert!(result.is_ok());
-        let book = result.unwrap();
-        assert_eq!(book.market, "0x123");
-        assert_eq!(book.bids.len(), 1);
-        assert_eq!(book.asks.len(), 1);
-        assert_eq!(book.min_order_size, Decimal::from_str("1").unwrap());
-        assert!(!book.neg_risk);
-        assert_eq!(book.tick_size, Decimal::from_str("0.01").unwrap());
-        assert_eq!(
-            book.last_trade_price,
-            Some(Decimal::from_str("0.755").unwrap())
-        );
+        assert!(result.is_ok());
+        let book = result.unwrap();
+        assert_eq!(book.market, "0x123");
+        assert_eq!(book.bids.len(), 1);
+        assert_eq!(book.asks.len(), 1);
+        assert_eq!(book.min_order_size, Decimal::from_str("1").unwrap());
+        assert!(!book.neg_risk);
+        assert_eq!(book.tick_size, Decimal::from_str("0.01").unwrap());
+        assert_eq!(
+            book.last_trade_price,
+            Some(Decimal::from_str("0.755").unwrap())
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_order_book_with_depth_keeps_best_levels() {
+        let mut server = Server::new_async().await;
+        // The exchange lists bids ascending and asks descending (best last)
+        let mock = server
+            .mock("GET", "/book")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("token_id".into(), "1".into()),
+                Matcher::UrlEncoded("depth".into(), "2".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{"market":"0xabc","asset_id":"1","timestamp":"1",
+                    "bids":[{"price":"0.40","size":"1"},{"price":"0.45","size":"2"},{"price":"0.47","size":"3"}],
+                    "asks":[{"price":"0.60","size":"4"},{"price":"0.55","size":"5"},{"price":"0.50","size":"6"}],
+                    "min_order_size":"5","neg_risk":false,"tick_size":"0.01"}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = create_test_client(&server.url());
+        let book = client
+            .get_order_book_with_depth(&"1".parse().unwrap(), BookDepth::Top(2))
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        let prices = |levels: &[crate::types::OrderSummary]| -> Vec<String> {
+            levels.iter().map(|level| level.price.to_string()).collect()
+        };
+        assert_eq!(prices(&book.bids), ["0.45", "0.47"]);
+        assert_eq!(prices(&book.asks), ["0.55", "0.50"]);
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -3004,6 +4922,7 @@ mod tests {
             ))
             .with_status(404)
             .with_header("content-type", "application/json")
+            .with_header("x-request-id", "req-123")
             .with_body(r#"{"error": "Market not found"}"#)
             .create_async()
             .await;
@@ -3015,11 +4934,57 @@ mod tests {
         assert!(result.is_err());
 
         let error = result.unwrap_err();
-        // The error should be either Network or Api error
+        assert!(matches!(error, PolyfillError::Api { status: 404, .. }));
+        let context = error.api_context().unwrap();
+        assert_eq!(context.endpoint, "/book");
+        assert_eq!(context.request_id.as_deref(), Some("req-123"));
+        assert_eq!(
+            error.to_string(),
+            r#"API error (404): Failed to get order book (GET /book, request id req-123, body: {"error": "Market not found"})"#
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_check_compatibility_reports_missing_fields() {
+        let mut server = Server::new_async().await;
+        let mut json = |path: &str, body: &str| {
+            server
+                .mock("GET", path)
+                .match_query(Matcher::Any)
+                .with_status(200)
+                .with_header("content-type", "application/json")
+                .with_body(body)
+        };
+        let mocks = [
+            json("/time", "1700000000"),
+            json(
+                "/sampling-markets",
+                r#"{"data":[{"condition_id":"0xabc","tokens":[{"token_id":"1"}]}],"next_cursor":"LTE="}"#,
+            ),
+            json(
+                "/book",
+                r#"{"market":"0xabc","asset_id":"1","timestamp":"0","bids":[],"asks":[],"min_order_size":"5","neg_risk":false}"#,
+            ),
+            json("/tick-size", r#"{"minimum_tick_size":0.01}"#),
+            json("/neg-risk", r#"{"neg_risk":false}"#),
+            json("/fee-rate", r#"{"base_fee":0}"#),
+        ];
+        for mock in mocks {
+            mock.create_async().await;
+        }
+
+        let client = create_test_client(&server.url());
+        client.clone().set_api_version(ClobApiVersion::V1);
+        let err = client.check_compatibility().await.unwrap_err().to_string();
         assert!(
-            matches!(error, PolyfillError::Network { .. })
-                || matches!(error, PolyfillError::Api { .. })
+            err.contains("Book: GET /book is missing tick_size"),
+            "{err}"
         );
+        assert!(!err.contains("TickSize"));
+
+        // V2-only endpoints fail fast when pinned to v1
+        let err = client.get_clob_market_info("0xabc").await.unwrap_err();
+        assert!(err.to_string().contains("not available on CLOB API v1"));
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -3085,15 +5050,43 @@ mod tests {
         let client = create_test_client("https://test.example.com");
 
         // Test initial state
-        assert!(client.signer.is_none());
-        assert!(client.api_creds.is_none());
+        assert!(client.current_signer().is_none());
+        assert!(client.prepared_api_creds().is_none());
 
         // Test with auth
         let auth_client = create_test_client_with_auth("https://test.example.com");
-        assert!(auth_client.signer.is_some());
+        assert!(auth_client.current_signer().is_some());
         assert_eq!(auth_client.chain_id, 137);
     }
 
+    #[test]
+    fn test_contract_address_accessors() {
+        let client = create_test_client_with_auth("https://test.example.com");
+        let exchange = client.exchange_address(false).unwrap();
+        assert_eq!(
+            Some(exchange.to_checksum(None)),
+            client.get_exchange_address()
+        );
+        assert_ne!(client.exchange_address(true).unwrap(), exchange);
+        assert_eq!(
+            client.collateral_address().unwrap(),
+            "0xC011a7E12a19f7B1f670d46F03B03f3342E82DFB"
+                .parse::<alloy_primitives::Address>()
+                .unwrap()
+        );
+        assert!(client.conditional_tokens_address().is_ok());
+
+        let amoy = ClobClient::from_config(ClientConfig {
+            chain: 80002,
+            ..ClientConfig::default()
+        })
+        .unwrap();
+        assert!(matches!(
+            amoy.collateral_address(),
+            Err(PolyfillError::Config { .. })
+        ));
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_get_ok() {
         let mut server = Server::new_async().await;
@@ -3161,6 +5154,275 @@ mod tests {
         assert!(prices.contains_key("0x456"));
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_retry_policy_retries_reads() {
+        use crate::utils::retry::{EndpointClass, RetryConfig, RetryPolicy};
+        use reqwest::Method;
+
+        let mut server = Server::new_async().await;
+        let unavailable = server
+            .mock("GET", "/time")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("GET", "/time")
+            .with_status(200)
+            .with_body("1234567890")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = create_test_client(&server.url());
+        // Set through a clone, the policy still applies here
+        client
+            .clone()
+            .set_retry_policy(RetryPolicy::default().with_read(RetryConfig {
+                initial_delay: std::time::Duration::from_millis(1),
+                jitter: false,
+                ..RetryConfig::default()
+            }));
+        assert_eq!(client.get_server_time().await.unwrap(), 1234567890);
+        unavailable.assert_async().await;
+        ok.assert_async().await;
+
+        // Batch reads are POSTs but safe to repeat; order placement is not
+        let class = super::endpoint_class;
+        assert_eq!(class(&Method::POST, "/books"), EndpointClass::Read);
+        assert_eq!(class(&Method::POST, "/order"), EndpointClass::Write);
+        assert_eq!(class(&Method::DELETE, "/orders"), EndpointClass::Cancel);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_retries_honour_retry_after_and_rate_limiter() {
+        use crate::utils::retry::{RetryConfig, RetryPolicy};
+
+        let mut server = Server::new_async().await;
+        let limited = server
+            .mock("GET", "/auth/api-keys")
+            .with_status(429)
+            .with_header("retry-after", "1")
+            .expect(1)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("GET", "/auth/api-keys")
+            .with_status(200)
+            .with_body(r#"{"apiKeys":["key"]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = create_test_client_with_l2_auth(&server.url());
+        let limiter = std::sync::Arc::new(crate::utils::rate_limit::TokenBucket::new(2, 1));
+        client.set_rate_limiter(limiter.clone());
+        client.set_retry_policy(RetryPolicy::default().with_read(RetryConfig {
+            initial_delay: std::time::Duration::from_millis(1),
+            jitter: false,
+            ..RetryConfig::default()
+        }));
+
+        let started = std::time::Instant::now();
+        assert_eq!(client.get_api_keys().await.unwrap(), vec!["key"]);
+        assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+        limited.assert_async().await;
+        ok.assert_async().await;
+        // One token for the first attempt, one for the retry, one refilled
+        // while waiting out Retry-After
+        assert!(limiter.try_consume());
+        assert!(!limiter.try_consume());
+
+        // A Retry-After beyond the policy's maximum delay is not waited out
+        server.reset();
+        let limited = server
+            .mock("GET", "/time")
+            .with_status(429)
+            .with_header("retry-after", "3600")
+            .expect(1)
+            .create_async()
+            .await;
+        let started = std::time::Instant::now();
+        assert!(client.get_server_time().await.is_err());
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        limited.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_response_cache_revalidates_with_etag() {
+        let mut server = Server::new_async().await;
+        let body = r#"{"limit": 1, "count": 0, "next_cursor": "LTE=", "data": []}"#;
+        let full = server
+            .mock("GET", "/simplified-markets")
+            .match_query(Matcher::Any)
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"v1\"")
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await;
+        let not_modified = server
+            .mock("GET", "/simplified-markets")
+            .match_query(Matcher::Any)
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = create_test_client(&server.url());
+        let clone = client.clone();
+        client.set_response_cache(std::sync::Arc::new(
+            crate::http_cache::ResponseCache::default(),
+        ));
+        let first = client.get_simplified_markets(None).await.unwrap();
+        // A clone made before the cache was installed revalidates through it
+        let second = clone.get_simplified_markets(None).await.unwrap();
+        assert_eq!(first.next_cursor, second.next_cursor);
+        full.assert_async().await;
+        not_modified.assert_async().await;
+
+        let stats = client.response_cache().unwrap().stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (1, 1, 1));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_response_cache_refetches_when_entry_evicted_mid_request() {
+        let mut server = Server::new_async().await;
+        let body = r#"{"limit": 1, "count": 0, "next_cursor": "LTE=", "data": []}"#;
+        let cache = std::sync::Arc::new(crate::http_cache::ResponseCache::default());
+        let full = server
+            .mock("GET", "/simplified-markets")
+            .match_query(Matcher::Any)
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"v1\"")
+            .with_body(body)
+            .expect(2)
+            .create_async()
+            .await;
+        let evicting = std::sync::Arc::clone(&cache);
+        let not_modified = server
+            .mock("GET", "/simplified-markets")
+            .match_query(Matcher::Any)
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .with_body_from_request(move |_| {
+                evicting.clear();
+                Vec::new()
+            })
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = create_test_client(&server.url());
+        client.set_response_cache(cache);
+        client.get_simplified_markets(None).await.unwrap();
+        let again = client.get_simplified_markets(None).await.unwrap();
+        assert_eq!(again.next_cursor.as_deref(), Some("LTE="));
+        full.assert_async().await;
+        not_modified.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_compressed_responses_are_negotiated() {
+        // "1234567890" deflated with zlib framing
+        const DEFLATED: [u8; 18] = [
+            120, 156, 51, 52, 50, 54, 49, 53, 51, 183, 176, 52, 0, 0, 11, 44, 2, 14,
+        ];
+        let mut server = Server::new_async().await;
+        let compressed = server
+            .mock("GET", "/time")
+            .match_header(
+                "accept-encoding",
+                Matcher::Regex("gzip.*deflate".to_string()),
+            )
+            .with_header("content-encoding", "deflate")
+            .with_body(DEFLATED)
+            .expect(1)
+            .create_async()
+            .await;
+        let plain = server
+            .mock("GET", "/time")
+            .match_header("accept-encoding", Matcher::Missing)
+            .with_body("1234567890")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = create_test_client(&server.url());
+        assert_eq!(client.get_server_time().await.unwrap(), 1234567890);
+
+        let uncompressed = ClobClient::from_config(ClientConfig {
+            base_url: server.url(),
+            compression: false,
+            ..ClientConfig::default()
+        })
+        .unwrap();
+        assert_eq!(uncompressed.get_server_time().await.unwrap(), 1234567890);
+        compressed.assert_async().await;
+        plain.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_order_books_concurrent_keys_results_by_token() {
+        let mut server = Server::new_async().await;
+        let mut mocks = Vec::new();
+        for token in ["1", "2"] {
+            let body = json!({
+                "market": "0xmarket",
+                "asset_id": token,
+                "hash": "0xabc",
+                "timestamp": "1234567890",
+                "bids": [{"price": "0.40", "size": "10"}],
+                "asks": [{"price": "0.42", "size": "10"}],
+                "min_order_size": "1",
+                "neg_risk": false,
+                "tick_size": "0.01"
+            });
+            mocks.push(
+                server
+                    .mock("GET", "/book")
+                    .match_query(Matcher::UrlEncoded("token_id".into(), token.into()))
+                    .with_body(body.to_string())
+                    // Fetched, then fetched again to seed
+                    .expect(2)
+                    .create_async()
+                    .await,
+            );
+        }
+        server
+            .mock("GET", "/book")
+            .match_query(Matcher::UrlEncoded("token_id".into(), "3".into()))
+            .with_status(404)
+            .with_body(r#"{"error": "No orderbook exists for the requested token id"}"#)
+            .create_async()
+            .await;
+
+        let tokens: Vec<crate::types::TokenId> = ["1", "2", "3"]
+            .iter()
+            .map(|token| crate::types::TokenId::new(token).unwrap())
+            .collect();
+        let client = create_test_client(&server.url());
+        let books = client.get_order_books_concurrent(&tokens, 2).await;
+        assert_eq!(books.len(), 3);
+        assert_eq!(books[&tokens[1]].as_ref().unwrap().asset_id, "2");
+        assert!(books[&tokens[2]].is_err());
+
+        let manager = std::sync::Arc::new(crate::book::OrderBookManager::new(10));
+        client.set_order_books(manager.clone());
+        let failed = client.seed_order_books(&tokens, 8).await.unwrap();
+        assert_eq!(failed.keys().collect::<Vec<_>>(), [&tokens[2]]);
+        let best_bid = manager
+            .with_book("1", |book| book.best_bid().map(|level| level.price))
+            .unwrap();
+        assert_eq!(best_bid, Some(Decimal::from_str("0.40").unwrap()));
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_get_server_time() {
         let mut server = Server::new_async().await;
@@ -3315,25 +5577,21 @@ mod tests {
 
     #[tokio::test(flavor = "multi_thread")]
     async fn test_order_args_creation() {
-        // Test OrderArgs creation and default values
+        // Test OrderArgs creation
         let order_args = ClientOrderArgs::new(
-            "0x123",
+            "123".parse().unwrap(),
             Decimal::from_str("0.75").unwrap(),
             Decimal::from_str("100.0").unwrap(),
             Side::BUY,
         );
 
-        assert_eq!(order_args.token_id, "0x123");
+        assert_eq!(order_args.token_id, "123");
         assert_eq!(order_args.price, Decimal::from_str("0.75").unwrap());
         assert_eq!(order_args.size, Decimal::from_str("100.0").unwrap());
         assert_eq!(order_args.side, Side::BUY);
 
-        // Test default
-        let default_args = ClientOrderArgs::default();
-        assert_eq!(default_args.token_id, "");
-        assert_eq!(default_args.price, Decimal::ZERO);
-        assert_eq!(default_args.size, Decimal::ZERO);
-        assert_eq!(default_args.side, Side::BUY);
+        // Hex ids are not token ids
+        assert!("0x123".parse::<crate::types::TokenId>().is_err());
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -3366,7 +5624,7 @@ mod tests {
         };
 
         let prepared = client
-            .prepare_order_path("123456", Some(&options), None, None)
+            .prepare_order_path(&"123456".parse().unwrap(), Some(&options), None, None)
             .await
             .unwrap();
         let order = prepared
@@ -3387,6 +5645,120 @@ mod tests {
         assert_eq!(order.metadata, crate::orders::BYTES32_ZERO);
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_preload_order_metadata_primes_cache() {
+        let mut server = Server::new_async().await;
+        let tick_size_mock = server
+            .mock("GET", "/tick-size")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"minimum_tick_size":"0.01"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let neg_risk_mock = server
+            .mock("GET", "/neg-risk")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"neg_risk":true}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = create_test_client_with_auth(&server.url());
+        let tokens: Vec<crate::types::TokenId> = vec!["1".parse().unwrap(), "2".parse().unwrap()];
+        client.preload_order_metadata(&tokens).await.unwrap();
+        // Already cached: neither preloading again nor preparing an order fetches
+        client.preload_order_metadata(&tokens).await.unwrap();
+        client
+            .prepare_order_path(&tokens[1], None, None, None)
+            .await
+            .unwrap();
+
+        tick_size_mock.assert_async().await;
+        neg_risk_mock.assert_async().await;
+        assert_eq!(
+            client.metadata_cache().tick_size("2"),
+            Some(Decimal::from_str("0.01").unwrap())
+        );
+        assert_eq!(client.metadata_cache().neg_risk("1"), Some(true));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_refresh_market_metadata_reports_changes() {
+        let mut server = Server::new_async().await;
+        let market = r#"{
+            "condition_id": "0xabc",
+            "tokens": [
+                {"token_id": "1", "outcome": "Yes", "price": 0.5},
+                {"token_id": "2", "outcome": "No", "price": 0.5}
+            ],
+            "rewards": {"rates": null, "min_size": 0, "max_spread": 0},
+            "min_incentive_size": null,
+            "max_incentive_spread": null,
+            "active": true,
+            "closed": false,
+            "question_id": "0xdef",
+            "minimum_order_size": 5,
+            "minimum_tick_size": 0.01,
+            "description": "",
+            "category": null,
+            "end_date_iso": null,
+            "game_start_time": null,
+            "question": "",
+            "market_slug": "",
+            "seconds_delay": 0,
+            "icon": "",
+            "fpmm": "",
+            "accepting_orders": true
+        }"#;
+        server
+            .mock("GET", "/book")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"market":"0xabc","asset_id":"1","timestamp":"0","bids":[],"asks":[],
+                    "min_order_size":"5","neg_risk":false,"tick_size":"0.01"}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/markets/0xabc")
+            .with_status(200)
+            .with_body(market)
+            .create_async()
+            .await;
+        let fee = server
+            .mock("GET", "/fee-rate")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"base_fee":0}"#)
+            .create_async()
+            .await;
+
+        let client = create_test_client(&server.url());
+        let tokens: Vec<crate::types::TokenId> = vec!["1".parse().unwrap()];
+        assert!(client.refresh_market_metadata(&tokens).await.is_empty());
+        let cached = client.metadata_cache().market("1").unwrap();
+        assert!(cached.accepting_orders);
+        assert_eq!(client.metadata_cache().neg_risk("1"), Some(false));
+
+        fee.remove_async().await;
+        server
+            .mock("GET", "/fee-rate")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_body(r#"{"base_fee":100}"#)
+            .create_async()
+            .await;
+        let changes = client.refresh_market_metadata(&tokens).await;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].previous, cached);
+        assert_eq!(changes[0].current.fee_rate_bps, 100);
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_get_clob_market_info_success() {
         let mut server = Server::new_async().await;
@@ -3484,39 +5856,196 @@ mod tests {
             .with_status(200)
             .with_header("content-type", "application/json")
             .with_body(
-                r#"{
-                    "success":true,
-                    "orderID":"order-1",
-                    "status":"live",
-                    "makingAmount":"100",
-                    "takingAmount":"250",
-                    "transactionsHashes":["0xabc"],
-                    "tradeIds":["trade-1"],
-                    "errorMsg":""
-                }"#,
+                r#"{
+                    "success":true,
+                    "orderID":"order-1",
+                    "status":"live",
+                    "makingAmount":"100",
+                    "takingAmount":"250",
+                    "transactionsHashes":["0xabc"],
+                    "tradeIds":["trade-1"],
+                    "errorMsg":""
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let client = create_test_client_with_l2_auth(&server.url());
+        let response = client
+            .post_order(
+                sample_signed_order(),
+                Some(&PostOrderOptions {
+                    order_type: OrderType::GTD,
+                    post_only: true,
+                    defer_exec: true,
+                }),
+            )
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert!(response.success);
+        assert_eq!(response.order_id, "order-1");
+        assert_eq!(response.status, "live");
+        assert_eq!(response.transactions_hashes, vec!["0xabc".to_string()]);
+        assert_eq!(response.trade_ids, vec!["trade-1".to_string()]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_post_orders_splits_into_batches() {
+        let mut server = Server::new_async().await;
+        // Answer each batch with one response per order, tagged by batch size
+        let mock = server
+            .mock("POST", "/orders")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body_from_request(|request| {
+                let orders: Vec<Value> = serde_json::from_slice(request.body().unwrap()).unwrap();
+                let entry = json!({
+                    "success": true,
+                    "orderID": format!("batch-{}", orders.len()),
+                    "status": "live",
+                    "makingAmount": "100",
+                    "takingAmount": "250"
+                });
+                Value::Array(vec![entry; orders.len()])
+                    .to_string()
+                    .into_bytes()
+            })
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = create_test_client_with_l2_auth(&server.url());
+        let options = PostOrderOptions {
+            order_type: OrderType::GTD,
+            ..PostOrderOptions::default()
+        };
+        let responses = client
+            .post_orders(vec![(sample_signed_order(), options); MAX_BATCH_ORDERS + 2])
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+        assert_eq!(responses.len(), MAX_BATCH_ORDERS + 2);
+        assert_eq!(responses[0].order_id, format!("batch-{MAX_BATCH_ORDERS}"));
+        assert_eq!(responses[MAX_BATCH_ORDERS].order_id, "batch-2");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_recorder_records_posts_and_cancels() {
+        let mut server = Server::new_async().await;
+        let _post = server
+            .mock("POST", "/order")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"success":true,"orderID":"order-1","status":"live","makingAmount":"100","takingAmount":"250"}"#,
+            )
+            .create_async()
+            .await;
+        let _batch = server
+            .mock("POST", "/orders")
+            .with_status(400)
+            .with_body(r#"{"error":"invalid order"}"#)
+            .create_async()
+            .await;
+        let _cancel = server
+            .mock("DELETE", "/cancel-all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"canceled":["order-1","order-2"],"notCanceled":{}}"#)
+            .create_async()
+            .await;
+
+        let recorder = std::sync::Arc::new(crate::run_report::RunRecorder::new());
+        let client = create_test_client_with_l2_auth(&server.url());
+        // Installed through a clone, and twice, the recorder still covers
+        // this client and counts each reconnect once
+        client.clone().set_run_recorder(recorder.clone());
+        client.set_run_recorder(recorder.clone());
+        let options = PostOrderOptions {
+            order_type: OrderType::GTD,
+            ..PostOrderOptions::default()
+        };
+        client
+            .post_order(sample_signed_order(), Some(&options))
+            .await
+            .unwrap();
+        let batch = vec![(sample_signed_order(), options)];
+        assert!(client.post_orders(batch).await.is_err());
+        client.cancel_all().await.unwrap();
+
+        client
+            .event_handlers()
+            .notify_reconnect(&crate::stream::ReconnectEvent {
+                attempts: 1,
+                cause: "reset".to_string(),
+                downtime: std::time::Duration::from_millis(5),
+            });
+
+        let report = recorder.report();
+        assert_eq!((report.orders_placed, report.orders_cancelled), (1, 2));
+        assert_eq!(report.errors.get("api"), Some(&1));
+        assert_eq!(report.latency["post_order"].count, 1);
+        assert_eq!(report.reconnects, 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_audit_log_records_submissions_and_outcomes() {
+        let mut server = Server::new_async().await;
+        let _post = server
+            .mock("POST", "/order")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"success":true,"orderID":"order-1","status":"live","makingAmount":"100","takingAmount":"250"}"#,
             )
             .create_async()
             .await;
+        let _cancel = server
+            .mock("DELETE", "/cancel-all")
+            .with_status(500)
+            .create_async()
+            .await;
 
+        let dir = std::env::temp_dir().join(format!("polyfill-audit-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("audit.jsonl");
         let client = create_test_client_with_l2_auth(&server.url());
-        let response = client
-            .post_order(
-                sample_signed_order(),
-                Some(&PostOrderOptions {
-                    order_type: OrderType::GTD,
-                    post_only: true,
-                    defer_exec: true,
-                }),
-            )
+        // Installed through a clone, the log still covers this client
+        client.clone().set_audit_log(std::sync::Arc::new(
+            crate::audit::AuditLog::open(&path).unwrap(),
+        ));
+        let options = PostOrderOptions {
+            order_type: OrderType::GTD,
+            ..PostOrderOptions::default()
+        };
+        client
+            .post_order(sample_signed_order(), Some(&options))
             .await
             .unwrap();
-
-        mock.assert_async().await;
-        assert!(response.success);
-        assert_eq!(response.order_id, "order-1");
-        assert_eq!(response.status, "live");
-        assert_eq!(response.transactions_hashes, vec!["0xabc".to_string()]);
-        assert_eq!(response.trade_ids, vec!["trade-1".to_string()]);
+        assert!(client.cancel_all().await.is_err());
+
+        let entries = crate::audit::verify(&path).unwrap();
+        let digest = crate::audit::order_digest(&sample_signed_order()).unwrap();
+        assert_eq!(entries.len(), 4);
+        assert!(matches!(
+            &entries[0].event,
+            crate::audit::AuditEvent::OrderSubmitted { digest: d, order, .. }
+                if *d == digest && order.signature == "0xdeadbeef"
+        ));
+        assert!(matches!(
+            &entries[1].event,
+            crate::audit::AuditEvent::OrderResult { digest: d, success: true, order_id: Some(id), .. }
+                if *d == digest && id == "order-1"
+        ));
+        assert!(matches!(
+            &entries[3].event,
+            crate::audit::AuditEvent::CancelResult { endpoint, error: Some(_), .. }
+                if endpoint == "/cancel-all"
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -3555,6 +6084,31 @@ mod tests {
         assert!(matches!(err, PolyfillError::Validation { .. }));
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_clones_share_rate_limit_budget() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("DELETE", "/order")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"canceled":["order-1"],"notCanceled":{}}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let registry = crate::utils::rate_limit::RateLimitRegistry::new(2, 1);
+        let client = create_test_client_with_l2_auth(&server.url());
+        // Clones made before the limiter is installed share it too
+        let clone = client.clone();
+        client.set_rate_limiter(registry.limiter("test_key"));
+
+        client.cancel("order-1").await.unwrap();
+        clone.cancel("order-1").await.unwrap();
+        // Both requests drew from the one bucket registered for the key
+        assert!(!registry.limiter("test_key").try_consume());
+        assert!(registry.limiter("other_key").try_consume());
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn test_cancel_endpoints_parse_typed_responses() {
         let mut server = Server::new_async().await;
@@ -3583,6 +6137,8 @@ mod tests {
             .await;
 
         let client = create_test_client_with_l2_auth(&server.url());
+        let activity = std::sync::Arc::new(crate::activity::ActivityMetrics::default());
+        client.clone().set_activity_metrics(activity.clone());
         let cancel = client.cancel("order-1").await.unwrap();
         let cancel_many = client
             .cancel_orders(&["order-1".to_string(), "order-2".to_string()])
@@ -3599,6 +6155,148 @@ mod tests {
             Some(&"already filled".to_string())
         );
         assert_eq!(cancel_all.canceled, vec!["order-9".to_string()]);
+        // Orders the venue refused to cancel are not counted
+        assert_eq!(activity.snapshot().cancels, 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scoped_cancels_send_a_single_filter() {
+        let mut server = Server::new_async().await;
+        let market_mock = server
+            .mock("DELETE", "/cancel-market-orders")
+            .match_body(Matcher::JsonString(r#"{"market":"0xabc"}"#.to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"canceled":["order-1","order-2"],"notCanceled":{}}"#)
+            .create_async()
+            .await;
+        let asset_mock = server
+            .mock("DELETE", "/cancel-market-orders")
+            .match_body(Matcher::JsonString(r#"{"asset_id":"123"}"#.to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"canceled":["order-3"],"notCanceled":{}}"#)
+            .create_async()
+            .await;
+
+        let client = create_test_client_with_l2_auth(&server.url());
+        let market = client.cancel_condition_orders("0xabc").await.unwrap();
+        let asset = client
+            .cancel_asset_orders(&"123".parse().unwrap())
+            .await
+            .unwrap();
+        market_mock.assert_async().await;
+        asset_mock.assert_async().await;
+        assert_eq!(market.canceled.len(), 2);
+        assert_eq!(asset.canceled, vec!["order-3".to_string()]);
+
+        // An empty scope never reaches the venue
+        assert!(matches!(
+            client.cancel_condition_orders(" ").await,
+            Err(PolyfillError::Validation { .. })
+        ));
+        assert!(matches!(
+            client.cancel_market_orders(None, None).await,
+            Err(PolyfillError::Validation { .. })
+        ));
+        assert!(matches!(
+            ClobClient::new(&server.url())
+                .cancel_condition_orders("0xabc")
+                .await,
+            Err(PolyfillError::Auth { .. })
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_safe_shutdown_reports_orders_left_behind() {
+        fn order_json(id: &str, size_matched: &str, status: &str) -> String {
+            format!(
+                r#"{{
+                    "associate_trades": [],
+                    "id": "{id}",
+                    "status": "{status}",
+                    "market": "market-1",
+                    "original_size": "10",
+                    "outcome": "Yes",
+                    "maker_address": "0x1111111111111111111111111111111111111111",
+                    "owner": "0x2222222222222222222222222222222222222222",
+                    "price": "0.55",
+                    "side": "BUY",
+                    "size_matched": "{size_matched}",
+                    "asset_id": "asset-1",
+                    "expiration": "0",
+                    "type": "GTC",
+                    "created_at": "1713916800"
+                }}"#
+            )
+        }
+
+        let mut server = Server::new_async().await;
+        let snapshot = server
+            .mock("GET", "/data/orders")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"data":[{},{},{}],"next_cursor":"LTE="}}"#,
+                order_json("order-1", "0", "LIVE"),
+                order_json("order-2", "0", "LIVE"),
+                order_json("order-3", "0", "LIVE"),
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/data/orders")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(format!(
+                r#"{{"data":[{}],"next_cursor":"LTE="}}"#,
+                order_json("order-3", "0", "LIVE"),
+            ))
+            .create_async()
+            .await;
+        let cancel = server
+            .mock("DELETE", "/cancel-all")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"canceled":["order-1"],"notCanceled":{"order-2":"matched","order-3":"busy"}}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/data/order/order-2")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(order_json("order-2", "10", "MATCHED"))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/data/order/order-3")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(order_json("order-3", "0", "LIVE"))
+            .create_async()
+            .await;
+
+        let client = create_test_client_with_l2_auth(&server.url());
+        let options = crate::types::ShutdownOptions::default()
+            .with_timeout(std::time::Duration::from_millis(50))
+            .with_poll_interval(std::time::Duration::from_millis(10));
+        let report = client.safe_shutdown(&options).await.unwrap();
+
+        snapshot.assert_async().await;
+        cancel.assert_async().await;
+        assert_eq!(report.open_orders.len(), 3);
+        assert_eq!(report.cancelled, vec!["order-1".to_string()]);
+        assert_eq!(report.not_cancelled.len(), 2);
+        assert_eq!(report.filled.len(), 1);
+        assert_eq!(report.filled[0].id, "order-2");
+        assert_eq!(report.still_open.len(), 1);
+        assert_eq!(report.still_open[0].id, "order-3");
+        assert!(!report.is_clean());
     }
 
     #[tokio::test(flavor = "multi_thread")]
@@ -4067,4 +6765,375 @@ mod tests {
         assert_eq!(approved.trade_ids, vec!["t1".to_string(), "t2".to_string()]);
         approve_mock.assert_async().await;
     }
+
+    #[tokio::test]
+    async fn test_on_fill_registers_with_shared_handlers() {
+        let client = create_test_client("http://localhost");
+        let fills = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = fills.clone();
+        client.on_fill(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        });
+
+        let trade: crate::types::StreamMessage = serde_json::from_str(
+            r#"{"event_type":"trade","id":"t1","market":"0xabc","asset_id":"1","side":"BUY","size":"1","price":"0.5"}"#,
+        )
+        .unwrap();
+        assert_eq!(client.event_handlers().dispatch(&trade), 1);
+        assert_eq!(fills.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        // Connection callbacks registered on a clone reach the original
+        let reconnects = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = reconnects.clone();
+        client.clone().on_reconnect(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        });
+        client
+            .event_handlers()
+            .notify_reconnect(&crate::stream::ReconnectEvent {
+                attempts: 1,
+                cause: "reset".to_string(),
+                downtime: std::time::Duration::from_millis(5),
+            });
+        assert_eq!(reconnects.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        // Starting the user channel requires L2 credentials
+        assert!(client.start_user_channel(vec![]).await.is_err());
+    }
+    #[tokio::test]
+    async fn test_deadline_overrides_client_timeout() {
+        let mut server = Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/book")
+            .match_query(Matcher::UrlEncoded("token_id".into(), "0x123".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_chunked_body(|w| {
+                std::thread::sleep(std::time::Duration::from_millis(200));
+                std::io::Write::write_all(
+                    w,
+                    br#"{"market":"0xabc","asset_id":"0x123","timestamp":"1","bids":[],"asks":[],
+                        "min_order_size":"1","neg_risk":false,"tick_size":"0.01"}"#,
+                )
+            })
+            .expect_at_least(1)
+            .create_async()
+            .await;
+
+        let client = create_test_client(&server.url());
+        let err = client
+            .with_deadline(std::time::Duration::from_millis(20))
+            .get_order_book("0x123")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PolyfillError::Timeout { .. }));
+
+        let book = client
+            .with_deadline(std::time::Duration::from_secs(5))
+            .get_order_book("0x123")
+            .await
+            .unwrap();
+        assert_eq!(book.asset_id, "0x123");
+    }
+
+    #[tokio::test]
+    async fn test_estimate_execution_prefers_local_book() {
+        let mut server = Server::new_async().await;
+        // REST snapshots list asks worst-first
+        let mock = server
+            .mock("GET", "/book")
+            .match_query(Matcher::UrlEncoded("token_id".into(), "123".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"market":"0xabc","asset_id":"123","timestamp":"1",
+                    "bids":[{"price":"0.48","size":"100"}],
+                    "asks":[{"price":"0.52","size":"50"},{"price":"0.50","size":"10"}],
+                    "min_order_size":"1","neg_risk":false,"tick_size":"0.01"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = create_test_client(&server.url());
+        let token: crate::types::TokenId = "123".parse().unwrap();
+        let estimate = client
+            .estimate_execution(&token, Side::BUY, Decimal::from(100))
+            .await
+            .unwrap();
+        assert_eq!(estimate.filled_size, Decimal::from(60));
+        assert_eq!(estimate.unfilled_size, Decimal::from(40));
+        assert_eq!(
+            estimate.best_price,
+            Some(Decimal::from_str("0.50").unwrap())
+        );
+        assert_eq!(
+            estimate.worst_price,
+            Some(Decimal::from_str("0.52").unwrap())
+        );
+        // (10 * 0.50 + 50 * 0.52) / 60 = 0.516..., ~333 bps over 0.50
+        assert_eq!(estimate.slippage_bps.round(), Decimal::from(333));
+        assert!(!estimate.is_fully_filled());
+
+        let books = std::sync::Arc::new(crate::book::OrderBookManager::new(10));
+        books
+            .apply_book_update(&crate::types::BookUpdate {
+                asset_id: "123".to_string(),
+                market: "0xabc".to_string(),
+                timestamp: 1,
+                bids: vec![crate::types::OrderSummary {
+                    price: Decimal::from_str("0.49").unwrap(),
+                    size: Decimal::from(20),
+                }],
+                asks: vec![],
+                hash: None,
+                extra: Default::default(),
+            })
+            .unwrap();
+        client.set_order_books(books);
+        let estimate = client
+            .estimate_execution(&token, Side::SELL, Decimal::from(5))
+            .await
+            .unwrap();
+        assert!(estimate.is_fully_filled());
+        assert_eq!(
+            estimate.average_price,
+            Some(Decimal::from_str("0.49").unwrap())
+        );
+        assert_eq!(estimate.slippage_bps, Decimal::ZERO);
+
+        assert!(client
+            .estimate_execution(&token, Side::BUY, Decimal::ZERO)
+            .await
+            .is_err());
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_local_midpoint_and_spread() {
+        let client = create_test_client("https://test.example.com");
+        let token: crate::types::TokenId = "1".parse().unwrap();
+        assert!(matches!(
+            client.local_midpoint(&token),
+            Err(PolyfillError::MarketData { .. })
+        ));
+
+        let books = std::sync::Arc::new(crate::book::OrderBookManager::new(10));
+        let timestamp = chrono::Utc::now().timestamp_millis() as u64;
+        books
+            .apply_book_update(&crate::types::BookUpdate {
+                asset_id: "1".to_string(),
+                market: "0xabc".to_string(),
+                timestamp,
+                bids: vec![crate::types::OrderSummary {
+                    price: Decimal::from_str("0.48").unwrap(),
+                    size: Decimal::from(20),
+                }],
+                asks: vec![crate::types::OrderSummary {
+                    price: Decimal::from_str("0.52").unwrap(),
+                    size: Decimal::from(20),
+                }],
+                hash: None,
+                extra: Default::default(),
+            })
+            .unwrap();
+        // Registered through a clone, the books are visible here too
+        client.clone().set_order_books(books);
+
+        let mid = client.local_midpoint(&token).unwrap();
+        assert_eq!(mid.value, Decimal::from_str("0.50").unwrap());
+        assert_eq!(mid.updated_at.timestamp_millis() as u64, timestamp);
+        assert!(!mid.is_stale(std::time::Duration::from_secs(60)));
+        assert_eq!(
+            client.local_spread(&token).unwrap().value,
+            Decimal::from_str("0.04").unwrap()
+        );
+        assert!(client.local_spread(&"2".parse().unwrap()).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_duplicate_guard_blocks_before_posting() {
+        let client = create_test_client("https://test.example.com");
+        let strategy = client.for_strategy("mm");
+        let guard = std::sync::Arc::new(crate::dedup::DuplicateGuard::default());
+        // Installed after the strategy clone was taken, it still covers it
+        client.set_duplicate_guard(guard.clone());
+        let args = ClientOrderArgs::new(
+            "123".parse().unwrap(),
+            Decimal::from_str("0.5").unwrap(),
+            Decimal::from(10),
+            Side::BUY,
+        );
+
+        let is_duplicate = |err: &PolyfillError| {
+            matches!(
+                err,
+                PolyfillError::Order {
+                    kind: crate::errors::OrderErrorKind::DuplicateOrder,
+                    ..
+                }
+            )
+        };
+
+        guard.check_order(Some("mm"), None, &args).unwrap();
+        let err = strategy
+            .create_and_post_order(&args, None, None)
+            .await
+            .unwrap_err();
+        assert!(is_duplicate(&err));
+
+        // Orders that fail before reaching the venue do not block a retry
+        for _ in 0..2 {
+            let err = client
+                .create_and_post_order(&args, None, None)
+                .await
+                .unwrap_err();
+            assert!(!is_duplicate(&err), "{err}");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_audit_order_books_reseeds_drifted_books() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/books")
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"[{"market": "0xabc", "asset_id": "1", "timestamp": "2000",
+                    "bids": [{"price": "0.49", "size": "5"}, {"price": "0.50", "size": "10"}],
+                    "asks": [{"price": "0.53", "size": "10"}],
+                    "min_order_size": "1", "neg_risk": false, "tick_size": "0.01"}]"#,
+            )
+            .create_async()
+            .await;
+
+        let client = create_test_client(&server.url());
+        let books = std::sync::Arc::new(crate::book::OrderBookManager::new(10));
+        books
+            .apply_book_update(&crate::types::BookUpdate {
+                asset_id: "1".to_string(),
+                market: "0xabc".to_string(),
+                timestamp: 1000,
+                bids: vec![crate::types::OrderSummary {
+                    price: Decimal::from_str("0.48").unwrap(),
+                    size: Decimal::from(20),
+                }],
+                asks: vec![crate::types::OrderSummary {
+                    price: Decimal::from_str("0.52").unwrap(),
+                    size: Decimal::from(20),
+                }],
+                hash: None,
+                extra: Default::default(),
+            })
+            .unwrap();
+        client.set_order_books(books.clone());
+
+        assert_eq!(client.audit_order_books().await.unwrap(), vec!["1"]);
+        mock.assert_async().await;
+        let book = books.get_book("1").unwrap();
+        assert_eq!(book.bids[0].price, Decimal::from_str("0.50").unwrap());
+        // Now in sync
+        assert!(client.audit_order_books().await.unwrap().is_empty());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_account_summary_gathers_all_sections() {
+        let mut server = Server::new_async().await;
+        let balance = server
+            .mock("GET", "/balance-allowance")
+            .match_query(Matcher::UrlEncoded(
+                "asset_type".into(),
+                "COLLATERAL".into(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"balance":"1000000","allowances":{}}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let orders = server
+            .mock("GET", "/data/orders")
+            .match_query(Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data":[],"next_cursor":"LTE="}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let trades = server
+            .mock("GET", "/data/trades")
+            .match_query(Matcher::Regex("after=\\d+".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"data":[{"id":"trade-1"}],"next_cursor":"LTE="}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let positions = server
+            .mock("GET", "/positions")
+            .match_query(Matcher::UrlEncoded("user".into(), "0xproxy".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"[{"proxyWallet":"0xproxy","asset":"123","conditionId":"0xabc",
+                    "size":12.5,"avgPrice":0.4,"curPrice":0.55,"redeemable":false}]"#,
+            )
+            .create_async()
+            .await;
+
+        let client = create_test_client_with_l2_auth(&server.url());
+        let options = crate::types::AccountSummaryOptions {
+            data_api_url: server.url(),
+            ..Default::default()
+        };
+        let summary = client.account_summary(&options).await.unwrap();
+        assert_eq!(summary.balance_allowance["balance"], "1000000");
+        assert!(summary.open_orders.is_empty());
+        assert_eq!(summary.recent_fills.len(), 1);
+        assert!(summary.positions.is_none());
+
+        let summary = client
+            .account_summary(&options.with_positions("0xproxy"))
+            .await
+            .unwrap();
+        let positions_list = summary.positions.unwrap();
+        assert_eq!(positions_list[0].asset, "123");
+        assert_eq!(positions_list[0].size, Decimal::from_str("12.5").unwrap());
+
+        balance.assert_async().await;
+        orders.assert_async().await;
+        trades.assert_async().await;
+        positions.assert_async().await;
+    }
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_get_cached_balance_hits_rest_once() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/balance-allowance")
+            .match_query(Matcher::UrlEncoded(
+                "asset_type".into(),
+                "COLLATERAL".into(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"balance":"25000000","allowances":{}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = create_test_client_with_l2_auth(&server.url());
+        let key = crate::balances::BalanceKey::Collateral;
+        let first = client.get_cached_balance(&key).await.unwrap();
+        assert_eq!(first.balance, Decimal::from(25));
+
+        let trade: crate::types::StreamMessage = serde_json::from_str(
+            r#"{"event_type":"trade","id":"t1","market":"0xabc","asset_id":"1","side":"BUY","size":"10","price":"0.5"}"#,
+        )
+        .unwrap();
+        client.balance_cache().apply(&trade);
+        let second = client.get_cached_balance(&key).await.unwrap();
+        assert_eq!(second.balance, Decimal::from(20));
+        assert!(second.adjusted);
+        mock.assert_async().await;
+    }
 }
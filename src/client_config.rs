@@ -0,0 +1,513 @@
+//! Loading [`ClientConfig`] from the environment and config files
+//!
+//! | Variable                        | File key           | Field                  |
+//! |---------------------------------|--------------------|------------------------|
+//! | `POLYMARKET_HOST`               | `host`             | `base_url`             |
+//! | `POLYMARKET_CHAIN_ID`           | `chain_id`         | `chain`                |
+//! | `POLYMARKET_PRIVATE_KEY`        | `private_key`      | `private_key`          |
+//! | `POLYMARKET_PRIVATE_KEY_FILE`   | `private_key_file` | `private_key`, read from the file |
+//! | `POLYMARKET_API_KEY`            | `api_key`          | `api_credentials`      |
+//! | `POLYMARKET_API_SECRET`         | `api_secret`       | `api_credentials`      |
+//! | `POLYMARKET_API_PASSPHRASE`     | `api_passphrase`   | `api_credentials`      |
+//! | `POLYMARKET_BUILDER_CODE`       | `builder_code`     | `builder_code`         |
+//! | `POLYMARKET_SIGNATURE_TYPE`     | `signature_type`   | `signature_type`       |
+//! | `POLYMARKET_FUNDER`             | `funder`           | `funder`               |
+//! | `POLYMARKET_TIMEOUT_SECS`       | `timeout_secs`     | `timeout`              |
+//! | `POLYMARKET_MAX_CONNECTIONS`    | `max_connections`  | `max_connections`      |
+//! | `POLYMARKET_COMPRESSION`        | `compression`      | `compression`          |
+//!
+//! `POLYMARKET_SECRET`, `POLYMARKET_PASSPHRASE` and `POLYMARKET_FUNDER_ADDRESS`
+//! are accepted as older spellings. Empty variables count as unset.
+//!
+//! Each layer only overrides what it sets, so a checked-in file can hold the
+//! defaults while the environment supplies secrets:
+//!
+//! ```rust,no_run
+//! use polyfill_rs::ClientConfig;
+//!
+//! # fn main() -> polyfill_rs::Result<()> {
+//! let config = ClientConfig::from_file_and_env("polyfill.toml")?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Files are TOML (with the `toml` feature) or JSON, picked by extension.
+//! Errors name the variable or file key at fault and never echo key
+//! material.
+
+use crate::errors::{PolyfillError, Result};
+use crate::types::{ApiCredentials, ClientConfig};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+pub const ENV_HOST: &str = "POLYMARKET_HOST";
+pub const ENV_CHAIN_ID: &str = "POLYMARKET_CHAIN_ID";
+pub const ENV_PRIVATE_KEY: &str = "POLYMARKET_PRIVATE_KEY";
+pub const ENV_PRIVATE_KEY_FILE: &str = "POLYMARKET_PRIVATE_KEY_FILE";
+pub const ENV_API_KEY: &str = "POLYMARKET_API_KEY";
+pub const ENV_API_SECRET: &str = "POLYMARKET_API_SECRET";
+pub const ENV_API_PASSPHRASE: &str = "POLYMARKET_API_PASSPHRASE";
+pub const ENV_BUILDER_CODE: &str = "POLYMARKET_BUILDER_CODE";
+pub const ENV_SIGNATURE_TYPE: &str = "POLYMARKET_SIGNATURE_TYPE";
+pub const ENV_FUNDER: &str = "POLYMARKET_FUNDER";
+pub const ENV_TIMEOUT_SECS: &str = "POLYMARKET_TIMEOUT_SECS";
+pub const ENV_MAX_CONNECTIONS: &str = "POLYMARKET_MAX_CONNECTIONS";
+pub const ENV_COMPRESSION: &str = "POLYMARKET_COMPRESSION";
+
+/// A setting with its file key and environment variables, preferred first
+struct Field {
+    key: &'static str,
+    env: &'static [&'static str],
+}
+
+const FIELDS: &[Field] = &[
+    Field {
+        key: "host",
+        env: &[ENV_HOST],
+    },
+    Field {
+        key: "chain_id",
+        env: &[ENV_CHAIN_ID],
+    },
+    Field {
+        key: "private_key",
+        env: &[ENV_PRIVATE_KEY],
+    },
+    Field {
+        key: "private_key_file",
+        env: &[ENV_PRIVATE_KEY_FILE],
+    },
+    Field {
+        key: "api_key",
+        env: &[ENV_API_KEY],
+    },
+    Field {
+        key: "api_secret",
+        env: &[ENV_API_SECRET, "POLYMARKET_SECRET"],
+    },
+    Field {
+        key: "api_passphrase",
+        env: &[ENV_API_PASSPHRASE, "POLYMARKET_PASSPHRASE"],
+    },
+    Field {
+        key: "builder_code",
+        env: &[ENV_BUILDER_CODE],
+    },
+    Field {
+        key: "signature_type",
+        env: &[ENV_SIGNATURE_TYPE],
+    },
+    Field {
+        key: "funder",
+        env: &[ENV_FUNDER, "POLYMARKET_FUNDER_ADDRESS"],
+    },
+    Field {
+        key: "timeout_secs",
+        env: &[ENV_TIMEOUT_SECS],
+    },
+    Field {
+        key: "max_connections",
+        env: &[ENV_MAX_CONNECTIONS],
+    },
+    Field {
+        key: "compression",
+        env: &[ENV_COMPRESSION],
+    },
+];
+
+/// A raw setting and where it came from, e.g. `POLYMARKET_CHAIN_ID`
+pub(crate) struct Setting {
+    pub(crate) source: String,
+    pub(crate) value: String,
+}
+
+/// One configuration layer, keyed by file key
+pub(crate) type Layer = BTreeMap<&'static str, Setting>;
+
+impl ClientConfig {
+    /// Defaults overridden by the `POLYMARKET_*` environment variables
+    pub fn from_env() -> Result<Self> {
+        Self::default().with_env_overrides()
+    }
+
+    /// Defaults overridden by a TOML or JSON config file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Self::default().apply(file_layer(path.as_ref())?)
+    }
+
+    /// Defaults overridden by a config file, in turn overridden by the
+    /// environment. API credentials may be split between the two.
+    pub fn from_file_and_env(path: impl AsRef<Path>) -> Result<Self> {
+        let layers = [file_layer(path.as_ref())?, env_layer(env_var)];
+        Self::default().apply(merge(layers))
+    }
+
+    /// Override the settings given in `POLYMARKET_*` environment variables
+    pub fn with_env_overrides(self) -> Result<Self> {
+        self.apply(env_layer(env_var))
+    }
+
+    pub(crate) fn apply(mut self, mut layer: Layer) -> Result<Self> {
+        if let Some(host) = layer.remove("host") {
+            let url = url::Url::parse(&host.value).map_err(|e| {
+                PolyfillError::config(format!("{} is not a valid URL: {e}", host.source))
+            })?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(PolyfillError::config(format!(
+                    "{} must be an http or https URL",
+                    host.source
+                )));
+            }
+            self.base_url = host.value.trim_end_matches('/').to_string();
+        }
+        if let Some(chain) = layer.remove("chain_id") {
+            self.chain = parse_positive(&chain)?;
+        }
+
+        match (
+            layer.remove("private_key"),
+            layer.remove("private_key_file"),
+        ) {
+            (Some(key), Some(file)) => {
+                return Err(PolyfillError::config(format!(
+                    "Set only one of {} and {}",
+                    key.source, file.source
+                )))
+            },
+            (Some(key), None) => {
+                self.private_key = Some(check_private_key(&key.source, key.value)?)
+            },
+            (None, Some(file)) => {
+                let key = std::fs::read_to_string(&file.value).map_err(|e| {
+                    PolyfillError::config(format!(
+                        "{}: failed to read {}: {e}",
+                        file.source, file.value
+                    ))
+                })?;
+                self.private_key = Some(check_private_key(&file.source, key)?);
+            },
+            (None, None) => {},
+        }
+
+        let current = self.api_credentials.take();
+        let mut parts = [
+            ("api_key", current.as_ref().map(|c| c.api_key.clone())),
+            ("api_secret", current.as_ref().map(|c| c.secret.clone())),
+            (
+                "api_passphrase",
+                current.as_ref().map(|c| c.passphrase.clone()),
+            ),
+        ];
+        for (key, part) in parts.iter_mut() {
+            if let Some(setting) = layer.remove(*key) {
+                *part = Some(setting.value);
+            }
+        }
+        self.api_credentials = match parts {
+            [(_, Some(api_key)), (_, Some(secret)), (_, Some(passphrase))] => {
+                Some(ApiCredentials {
+                    api_key,
+                    secret,
+                    passphrase,
+                })
+            },
+            [(_, None), (_, None), (_, None)] => None,
+            parts => {
+                let missing: Vec<&str> = parts
+                    .iter()
+                    .filter(|(_, part)| part.is_none())
+                    .map(|(key, _)| *key)
+                    .collect();
+                return Err(PolyfillError::config(format!(
+                    "Incomplete API credentials: missing {}",
+                    missing.join(", ")
+                )));
+            },
+        };
+
+        if let Some(code) = layer.remove("builder_code") {
+            self.builder_code = Some(code.value);
+        }
+        if let Some(signature_type) = layer.remove("signature_type") {
+            let value: u8 = signature_type.value.parse().map_err(|_| {
+                PolyfillError::config(format!(
+                    "{} must be 0, 1, 2 or 3, got {:?}",
+                    signature_type.source, signature_type.value
+                ))
+            })?;
+            crate::orders::sig_type_from_u8(value).map_err(|_| {
+                PolyfillError::config(format!(
+                    "{} must be 0, 1, 2 or 3, got {value}",
+                    signature_type.source
+                ))
+            })?;
+            self.signature_type = Some(value);
+        }
+        if let Some(funder) = layer.remove("funder") {
+            funder
+                .value
+                .parse::<alloy_primitives::Address>()
+                .map_err(|e| {
+                    PolyfillError::config(format!("{} is not a valid address: {e}", funder.source))
+                })?;
+            self.funder = Some(funder.value);
+        }
+        if let Some(timeout) = layer.remove("timeout_secs") {
+            self.timeout = Some(Duration::from_secs(parse_positive(&timeout)?));
+        }
+        if let Some(max_connections) = layer.remove("max_connections") {
+            self.max_connections = Some(parse_positive(&max_connections)? as usize);
+        }
+        if let Some(compression) = layer.remove("compression") {
+            self.compression = match compression.value.to_ascii_lowercase().as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                _ => {
+                    return Err(PolyfillError::config(format!(
+                        "{} must be true or false, got {:?}",
+                        compression.source, compression.value
+                    )))
+                },
+            };
+        }
+        Ok(self)
+    }
+}
+
+pub(crate) fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+pub(crate) fn env_layer(lookup: impl Fn(&str) -> Option<String>) -> Layer {
+    let mut layer = Layer::new();
+    for field in FIELDS {
+        let found = field.env.iter().find_map(|name| {
+            lookup(name)
+                .filter(|value| !value.is_empty())
+                .map(|value| (*name, value))
+        });
+        if let Some((name, value)) = found {
+            layer.insert(
+                field.key,
+                Setting {
+                    source: name.to_string(),
+                    value,
+                },
+            );
+        }
+    }
+    layer
+}
+
+pub(crate) fn file_layer(path: &Path) -> Result<Layer> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        PolyfillError::config(format!("Failed to read config {}: {e}", path.display()))
+    })?;
+    let document = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => parse_toml(&contents),
+        _ => serde_json::from_str(&contents)
+            .map_err(|e| PolyfillError::config(format!("Invalid JSON: {e}"))),
+    }
+    .map_err(|e| PolyfillError::config(format!("Config {}: {e}", path.display())))?;
+    document_layer(document, &path.display().to_string(), true)
+}
+
+/// Settings from a parsed document; unknown keys are rejected if `strict`
+/// and skipped otherwise
+pub(crate) fn document_layer(
+    document: serde_json::Value,
+    origin: &str,
+    strict: bool,
+) -> Result<Layer> {
+    let serde_json::Value::Object(entries) = document else {
+        return Err(PolyfillError::config(format!(
+            "Config {origin} must be a table of settings"
+        )));
+    };
+
+    let mut layer = Layer::new();
+    for (key, value) in entries {
+        let source = format!("`{key}` in {origin}");
+        let Some(field) = FIELDS.iter().find(|field| field.key == key) else {
+            if strict {
+                return Err(PolyfillError::config(format!(
+                    "Unknown config key {source}"
+                )));
+            }
+            continue;
+        };
+        let value = match value {
+            serde_json::Value::String(s) => s,
+            serde_json::Value::Number(n) => n.to_string(),
+            serde_json::Value::Bool(b) => b.to_string(),
+            _ => {
+                return Err(PolyfillError::config(format!(
+                    "{source} must be a string, number or boolean"
+                )))
+            },
+        };
+        layer.insert(field.key, Setting { source, value });
+    }
+    Ok(layer)
+}
+
+/// Stack layers, later ones winning. An inline key and a key file replace
+/// each other rather than conflicting across layers.
+fn merge(layers: impl IntoIterator<Item = Layer>) -> Layer {
+    let mut merged = Layer::new();
+    for layer in layers {
+        if layer.contains_key("private_key") && !layer.contains_key("private_key_file") {
+            merged.remove("private_key_file");
+        }
+        if layer.contains_key("private_key_file") && !layer.contains_key("private_key") {
+            merged.remove("private_key");
+        }
+        merged.extend(layer);
+    }
+    merged
+}
+
+fn parse_positive(setting: &Setting) -> Result<u64> {
+    match setting.value.parse::<u64>() {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(PolyfillError::config(format!(
+            "{} must be a positive integer, got {:?}",
+            setting.source, setting.value
+        ))),
+    }
+}
+
+/// Check that `key` parses without putting it in the error
+fn check_private_key(source: &str, key: String) -> Result<String> {
+    let key = key.trim().to_string();
+    key.parse::<alloy_signer_local::PrivateKeySigner>()
+        .map_err(|_| PolyfillError::config(format!("{source} is not a valid private key")))?;
+    Ok(key)
+}
+
+/// Parse a TOML document into the equivalent JSON value
+#[cfg(feature = "toml")]
+pub(crate) fn parse_toml(s: &str) -> Result<serde_json::Value> {
+    let document: toml_edit::DocumentMut = s
+        .parse()
+        .map_err(|e| PolyfillError::config(format!("Invalid TOML: {e}")))?;
+    Ok(toml_table_to_json(document.as_table()))
+}
+
+#[cfg(not(feature = "toml"))]
+pub(crate) fn parse_toml(_s: &str) -> Result<serde_json::Value> {
+    Err(PolyfillError::config("TOML files need the `toml` feature"))
+}
+
+#[cfg(feature = "toml")]
+fn toml_table_to_json(table: &dyn toml_edit::TableLike) -> serde_json::Value {
+    serde_json::Value::Object(
+        table
+            .iter()
+            .filter_map(|(key, item)| Some((key.to_string(), toml_item_to_json(item)?)))
+            .collect(),
+    )
+}
+
+#[cfg(feature = "toml")]
+fn toml_item_to_json(item: &toml_edit::Item) -> Option<serde_json::Value> {
+    match item {
+        toml_edit::Item::None => None,
+        toml_edit::Item::Value(value) => Some(toml_value_to_json(value)),
+        toml_edit::Item::Table(table) => Some(toml_table_to_json(table)),
+        toml_edit::Item::ArrayOfTables(tables) => Some(serde_json::Value::Array(
+            tables
+                .iter()
+                .map(|table| toml_table_to_json(table))
+                .collect(),
+        )),
+    }
+}
+
+#[cfg(feature = "toml")]
+fn toml_value_to_json(value: &toml_edit::Value) -> serde_json::Value {
+    use toml_edit::Value;
+    match value {
+        Value::String(s) => s.value().clone().into(),
+        Value::Integer(i) => (*i.value()).into(),
+        Value::Float(f) => (*f.value()).into(),
+        Value::Boolean(b) => (*b.value()).into(),
+        Value::Datetime(d) => d.value().to_string().into(),
+        Value::Array(array) => array.iter().map(toml_value_to_json).collect(),
+        Value::InlineTable(table) => toml_table_to_json(table),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+
+    #[test]
+    fn test_layers_override_and_errors_name_the_field() {
+        let dir = std::env::temp_dir().join(format!("polyfill-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("client.json");
+        std::fs::write(
+            &path,
+            r#"{"host":"https://clob.example.com/","chain_id":80002,"api_key":"key","timeout_secs":5,"compression":false}"#,
+        )
+        .unwrap();
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("POLYMARKET_SECRET", "secret"),
+            (ENV_API_PASSPHRASE, "pass"),
+            (ENV_PRIVATE_KEY, KEY),
+            (ENV_MAX_CONNECTIONS, ""),
+        ]);
+
+        let layers = [
+            file_layer(&path).unwrap(),
+            env_layer(|name| env.get(name).map(|value| value.to_string())),
+        ];
+        let config = ClientConfig::default().apply(merge(layers)).unwrap();
+        assert_eq!(config.base_url, "https://clob.example.com");
+        assert_eq!(config.chain, 80002);
+        assert_eq!(config.timeout, Some(Duration::from_secs(5)));
+        assert_eq!(config.max_connections, Some(100));
+        assert!(!config.compression);
+        assert_eq!(config.private_key.as_deref(), Some(KEY));
+        let creds = config.api_credentials.unwrap();
+        assert_eq!(
+            (creds.api_key.as_str(), creds.secret.as_str()),
+            ("key", "secret")
+        );
+
+        let err = ClientConfig::default()
+            .apply(env_layer(|name| {
+                (name == ENV_CHAIN_ID).then(|| "polygon".to_string())
+            }))
+            .unwrap_err();
+        assert!(err.to_string().contains("POLYMARKET_CHAIN_ID"), "{err}");
+
+        let err = ClientConfig::default()
+            .apply(env_layer(|name| {
+                (name == ENV_PRIVATE_KEY).then(|| "0xnotakey".to_string())
+            }))
+            .unwrap_err();
+        assert!(err.to_string().contains("POLYMARKET_PRIVATE_KEY"), "{err}");
+        assert!(!err.to_string().contains("0xnotakey"), "{err}");
+
+        let err = ClientConfig::default()
+            .apply(env_layer(|name| {
+                (name == ENV_API_KEY).then(|| "key".to_string())
+            }))
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("api_secret, api_passphrase"),
+            "{err}"
+        );
+
+        std::fs::write(&path, r#"{"chain":137}"#).unwrap();
+        let err = ClientConfig::from_file(&path).unwrap_err();
+        assert!(err.to_string().contains("`chain`"), "{err}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
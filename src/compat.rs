@@ -0,0 +1,256 @@
+//! CLOB API versions and compatibility checks
+//!
+//! [`ApiDescriptor`] records the paths and required response fields of the
+//! endpoints the client depends on for a given [`ClobApiVersion`]. Pin the
+//! version with [`crate::ClobClient::set_api_version`]; endpoints missing
+//! from the pinned version fail with a clear error instead of a 404 or a
+//! parse failure. [`crate::ClobClient::check_compatibility`] probes the live
+//! API against the descriptor, so breaking upstream changes show up at
+//! startup rather than mid-trading.
+
+use crate::errors::{PolyfillError, Result};
+use serde_json::Value;
+
+/// Known CLOB API versions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ClobApiVersion {
+    V1,
+    /// Current API: V2 order signing, `/clob-markets` and `/markets-by-token`
+    #[default]
+    V2,
+}
+
+impl std::fmt::Display for ClobApiVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClobApiVersion::V1 => f.write_str("v1"),
+            ClobApiVersion::V2 => f.write_str("v2"),
+        }
+    }
+}
+
+/// Endpoints covered by the descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endpoint {
+    Time,
+    SamplingMarkets,
+    Book,
+    TickSize,
+    NegRisk,
+    FeeRate,
+    MarketByToken,
+    ClobMarket,
+}
+
+/// Path, required response fields and first version of one endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndpointSpec {
+    pub endpoint: Endpoint,
+    pub method: &'static str,
+    /// Path template; `{id}` is replaced by a token or condition id
+    pub path: &'static str,
+    /// Top-level fields the client needs in a successful response
+    pub required_fields: &'static [&'static str],
+    pub since: ClobApiVersion,
+}
+
+impl EndpointSpec {
+    /// Path with `{id}` filled in
+    pub fn path_for(&self, id: &str) -> String {
+        self.path.replace("{id}", id)
+    }
+
+    /// Required fields absent from `response`
+    pub fn missing_fields(&self, response: &Value) -> Vec<&'static str> {
+        self.required_fields
+            .iter()
+            .copied()
+            .filter(|field| response.get(field).is_none())
+            .collect()
+    }
+}
+
+const ENDPOINTS: &[EndpointSpec] = &[
+    EndpointSpec {
+        endpoint: Endpoint::Time,
+        method: "GET",
+        path: "/time",
+        required_fields: &[],
+        since: ClobApiVersion::V1,
+    },
+    EndpointSpec {
+        endpoint: Endpoint::SamplingMarkets,
+        method: "GET",
+        path: "/sampling-markets",
+        required_fields: &["data", "next_cursor"],
+        since: ClobApiVersion::V1,
+    },
+    EndpointSpec {
+        endpoint: Endpoint::Book,
+        method: "GET",
+        path: "/book",
+        required_fields: &[
+            "market",
+            "asset_id",
+            "timestamp",
+            "bids",
+            "asks",
+            "min_order_size",
+            "neg_risk",
+            "tick_size",
+        ],
+        since: ClobApiVersion::V1,
+    },
+    EndpointSpec {
+        endpoint: Endpoint::TickSize,
+        method: "GET",
+        path: "/tick-size",
+        required_fields: &["minimum_tick_size"],
+        since: ClobApiVersion::V1,
+    },
+    EndpointSpec {
+        endpoint: Endpoint::NegRisk,
+        method: "GET",
+        path: "/neg-risk",
+        required_fields: &["neg_risk"],
+        since: ClobApiVersion::V1,
+    },
+    EndpointSpec {
+        endpoint: Endpoint::FeeRate,
+        method: "GET",
+        path: "/fee-rate",
+        required_fields: &[],
+        since: ClobApiVersion::V1,
+    },
+    EndpointSpec {
+        endpoint: Endpoint::MarketByToken,
+        method: "GET",
+        path: "/markets-by-token/{id}",
+        required_fields: &["condition_id"],
+        since: ClobApiVersion::V2,
+    },
+    EndpointSpec {
+        endpoint: Endpoint::ClobMarket,
+        method: "GET",
+        path: "/clob-markets/{id}",
+        required_fields: &["t"],
+        since: ClobApiVersion::V2,
+    },
+];
+
+/// Endpoints available in one CLOB API version
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApiDescriptor {
+    version: ClobApiVersion,
+}
+
+impl ApiDescriptor {
+    pub const fn new(version: ClobApiVersion) -> Self {
+        Self { version }
+    }
+
+    pub fn version(&self) -> ClobApiVersion {
+        self.version
+    }
+
+    pub fn endpoints(&self) -> impl Iterator<Item = &'static EndpointSpec> + '_ {
+        ENDPOINTS.iter().filter(|spec| spec.since <= self.version)
+    }
+
+    pub fn endpoint(&self, endpoint: Endpoint) -> Option<&'static EndpointSpec> {
+        self.endpoints().find(|spec| spec.endpoint == endpoint)
+    }
+
+    /// The spec for `endpoint`, or an error if this version lacks it
+    pub fn require(&self, endpoint: Endpoint) -> Result<&'static EndpointSpec> {
+        self.endpoint(endpoint).ok_or_else(|| {
+            PolyfillError::config(format!(
+                "{endpoint:?} is not available on CLOB API {}",
+                self.version
+            ))
+        })
+    }
+}
+
+/// Outcome of [`crate::ClobClient::check_compatibility`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompatibilityReport {
+    pub version: ClobApiVersion,
+    /// Endpoints that answered with the expected shape
+    pub passed: Vec<Endpoint>,
+    /// Endpoints that failed, with the reason
+    pub failures: Vec<(Endpoint, String)>,
+}
+
+impl CompatibilityReport {
+    pub fn is_compatible(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// `Ok` if every probe passed, otherwise one error listing the failures
+    pub fn into_result(self) -> Result<Self> {
+        if self.is_compatible() {
+            return Ok(self);
+        }
+        let failures: Vec<String> = self
+            .failures
+            .iter()
+            .map(|(endpoint, reason)| format!("{endpoint:?}: {reason}"))
+            .collect();
+        Err(PolyfillError::config(format!(
+            "CLOB API is not compatible with {}: {}",
+            self.version,
+            failures.join("; ")
+        )))
+    }
+
+    pub(crate) fn record(&mut self, spec: &EndpointSpec, response: Result<Value>) {
+        let reason = match response {
+            Ok(value) => {
+                let missing = spec.missing_fields(&value);
+                if missing.is_empty() {
+                    self.passed.push(spec.endpoint);
+                    return;
+                }
+                format!(
+                    "{} {} is missing {}",
+                    spec.method,
+                    spec.path,
+                    missing.join(", ")
+                )
+            },
+            Err(e) => format!("{} {} failed: {e}", spec.method, spec.path),
+        };
+        self.failures.push((spec.endpoint, reason));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_descriptor_versions_and_field_checks() {
+        let v1 = ApiDescriptor::new(ClobApiVersion::V1);
+        assert!(v1.endpoint(Endpoint::Book).is_some());
+        assert!(v1.require(Endpoint::ClobMarket).is_err());
+
+        let v2 = ApiDescriptor::default();
+        let spec = v2.require(Endpoint::MarketByToken).unwrap();
+        assert_eq!(spec.path_for("42"), "/markets-by-token/42");
+
+        let mut report = CompatibilityReport::default();
+        report.record(
+            v2.require(Endpoint::TickSize).unwrap(),
+            Ok(json!({"minimum_tick_size": 0.01})),
+        );
+        report.record(
+            v2.require(Endpoint::NegRisk).unwrap(),
+            Ok(json!({"negRisk": true})),
+        );
+        assert_eq!(report.passed, vec![Endpoint::TickSize]);
+        let err = report.into_result().unwrap_err().to_string();
+        assert!(err.contains("GET /neg-risk is missing neg_risk"));
+    }
+}
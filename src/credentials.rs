@@ -0,0 +1,365 @@
+//! Credential providers
+//!
+//! A [`CredentialsProvider`] supplies the signing key and L2 API credentials
+//! a client needs, so production deployments can keep them in a secret
+//! manager while development reads them from the environment.
+//! [`CredentialsChain::default`] tries, in order:
+//!
+//! 1. `POLYMARKET_*` environment variables ([`EnvProvider`])
+//! 2. the config file named by `POLYMARKET_CREDENTIALS_FILE` ([`FileProvider`])
+//! 3. one file per value in `POLYMARKET_SECRETS_DIR`, e.g. mounted Docker or
+//!    Kubernetes secrets ([`SecretsDirProvider`])
+//! 4. the JSON printed by `POLYMARKET_CREDENTIALS_COMMAND`, e.g. a Vault or
+//!    SSM CLI call ([`CommandProvider`])
+//!
+//! The key and the API credentials each come from the first provider that
+//! has them. Values use the keys of [`crate::client_config`]: `private_key`,
+//! `api_key`, `api_secret` and `api_passphrase`. Encrypted keystores can be
+//! read through a command that decrypts them.
+//!
+//! ```text
+//! POLYMARKET_CREDENTIALS_COMMAND="vault kv get -format=json -field=data secret/polymarket"
+//! ```
+
+use crate::client_config::{document_layer, env_layer, env_var, file_layer, Layer, Setting};
+use crate::errors::{PolyfillError, Result};
+use crate::types::{ApiCredentials, ClientConfig};
+use std::path::PathBuf;
+
+pub const ENV_CREDENTIALS_FILE: &str = "POLYMARKET_CREDENTIALS_FILE";
+pub const ENV_SECRETS_DIR: &str = "POLYMARKET_SECRETS_DIR";
+pub const ENV_CREDENTIALS_COMMAND: &str = "POLYMARKET_CREDENTIALS_COMMAND";
+
+const CREDENTIAL_KEYS: &[&str] = &[
+    "private_key",
+    "private_key_file",
+    "api_key",
+    "api_secret",
+    "api_passphrase",
+];
+
+/// Key material found by a provider
+#[derive(Clone, Default)]
+pub struct Credentials {
+    pub private_key: Option<String>,
+    pub api_credentials: Option<ApiCredentials>,
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field(
+                "private_key",
+                &self.private_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field(
+                "api_key",
+                &self.api_credentials.as_ref().map(|creds| &creds.api_key),
+            )
+            .finish()
+    }
+}
+
+impl Credentials {
+    pub fn is_empty(&self) -> bool {
+        self.private_key.is_none() && self.api_credentials.is_none()
+    }
+
+    /// Fill whatever is missing from `other`
+    pub fn or(self, other: Credentials) -> Self {
+        Self {
+            private_key: self.private_key.or(other.private_key),
+            api_credentials: self.api_credentials.or(other.api_credentials),
+        }
+    }
+
+    /// Validate and extract the credential settings of a layer
+    fn from_layer(mut layer: Layer) -> Result<Self> {
+        layer.retain(|key, _| CREDENTIAL_KEYS.contains(key));
+        let config = ClientConfig::default().apply(layer)?;
+        Ok(Self {
+            private_key: config.private_key,
+            api_credentials: config.api_credentials,
+        })
+    }
+}
+
+/// A source of key material
+pub trait CredentialsProvider: Send + Sync {
+    /// Names the provider in errors
+    fn name(&self) -> String;
+
+    /// Whatever credentials this source holds; empty if it has none
+    fn credentials(&self) -> Result<Credentials>;
+}
+
+/// Reads the `POLYMARKET_*` environment variables
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvProvider;
+
+impl CredentialsProvider for EnvProvider {
+    fn name(&self) -> String {
+        "environment".to_string()
+    }
+
+    fn credentials(&self) -> Result<Credentials> {
+        Credentials::from_layer(env_layer(env_var))
+    }
+}
+
+/// Reads a TOML or JSON config file
+#[derive(Debug, Clone)]
+pub struct FileProvider {
+    path: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CredentialsProvider for FileProvider {
+    fn name(&self) -> String {
+        format!("file {}", self.path.display())
+    }
+
+    fn credentials(&self) -> Result<Credentials> {
+        Credentials::from_layer(file_layer(&self.path)?)
+    }
+}
+
+/// Reads one value per file, named after its key (`private_key`,
+/// `api_key`, ...). A missing directory holds no credentials.
+#[derive(Debug, Clone)]
+pub struct SecretsDirProvider {
+    dir: PathBuf,
+}
+
+impl SecretsDirProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl CredentialsProvider for SecretsDirProvider {
+    fn name(&self) -> String {
+        format!("secrets directory {}", self.dir.display())
+    }
+
+    fn credentials(&self) -> Result<Credentials> {
+        let mut layer = Layer::new();
+        for key in CREDENTIAL_KEYS {
+            if *key == "private_key_file" {
+                continue;
+            }
+            let path = self.dir.join(key);
+            match std::fs::read_to_string(&path) {
+                Ok(value) => {
+                    layer.insert(
+                        *key,
+                        Setting {
+                            source: path.display().to_string(),
+                            value: value.trim().to_string(),
+                        },
+                    );
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+                Err(e) => {
+                    return Err(PolyfillError::config(format!(
+                        "Failed to read {}: {e}",
+                        path.display()
+                    )))
+                },
+            }
+        }
+        Credentials::from_layer(layer)
+    }
+}
+
+/// Runs a program that prints credentials as a JSON object.
+///
+/// Keys other than the credential keys are ignored, so a secret manager's
+/// full record can be printed as is.
+#[derive(Debug, Clone)]
+pub struct CommandProvider {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandProvider {
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn with_args(mut self, args: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Split a command line on whitespace; no shell quoting is applied
+    pub fn from_command_line(command: &str) -> Option<Self> {
+        let mut words = command.split_whitespace();
+        Some(Self::new(words.next()?).with_args(words))
+    }
+}
+
+impl CredentialsProvider for CommandProvider {
+    fn name(&self) -> String {
+        format!("command `{}`", self.program)
+    }
+
+    fn credentials(&self) -> Result<Credentials> {
+        let output = std::process::Command::new(&self.program)
+            .args(&self.args)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .map_err(|e| PolyfillError::config(format!("Failed to run {}: {e}", self.name())))?;
+        if !output.status.success() {
+            return Err(PolyfillError::config(format!(
+                "{} exited with {}: {}",
+                self.name(),
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        let document = serde_json::from_slice(&output.stdout).map_err(|e| {
+            PolyfillError::config(format!("{} did not print JSON: {e}", self.name()))
+        })?;
+        Credentials::from_layer(document_layer(document, &self.name(), false)?)
+    }
+}
+
+/// Providers tried in order
+pub struct CredentialsChain {
+    providers: Vec<Box<dyn CredentialsProvider>>,
+}
+
+impl CredentialsChain {
+    /// An empty chain
+    pub fn new() -> Self {
+        Self {
+            providers: Vec::new(),
+        }
+    }
+
+    pub fn with_provider(mut self, provider: impl CredentialsProvider + 'static) -> Self {
+        self.providers.push(Box::new(provider));
+        self
+    }
+}
+
+impl Default for CredentialsChain {
+    /// Environment, then the file, secrets directory and command named by
+    /// their `POLYMARKET_*` variables, when set
+    fn default() -> Self {
+        let mut chain = Self::new().with_provider(EnvProvider);
+        if let Some(path) = env_var(ENV_CREDENTIALS_FILE).filter(|path| !path.is_empty()) {
+            chain = chain.with_provider(FileProvider::new(path));
+        }
+        if let Some(dir) = env_var(ENV_SECRETS_DIR).filter(|dir| !dir.is_empty()) {
+            chain = chain.with_provider(SecretsDirProvider::new(dir));
+        }
+        if let Some(command) = env_var(ENV_CREDENTIALS_COMMAND)
+            .as_deref()
+            .and_then(CommandProvider::from_command_line)
+        {
+            chain = chain.with_provider(command);
+        }
+        chain
+    }
+}
+
+impl CredentialsProvider for CredentialsChain {
+    fn name(&self) -> String {
+        let names: Vec<String> = self.providers.iter().map(|p| p.name()).collect();
+        format!("chain [{}]", names.join(", "))
+    }
+
+    /// Stops at the first provider that fails, so a broken source is never
+    /// silently skipped
+    fn credentials(&self) -> Result<Credentials> {
+        let mut found = Credentials::default();
+        for provider in &self.providers {
+            if found.private_key.is_some() && found.api_credentials.is_some() {
+                break;
+            }
+            let credentials = provider.credentials().map_err(|e| {
+                PolyfillError::config(format!("Credentials from {}: {e}", provider.name()))
+            })?;
+            found = found.or(credentials);
+        }
+        Ok(found)
+    }
+}
+
+impl ClientConfig {
+    /// Take the key and API credentials from `provider` where the config
+    /// has none
+    pub fn with_credentials(mut self, provider: &dyn CredentialsProvider) -> Result<Self> {
+        if self.private_key.is_some() && self.api_credentials.is_some() {
+            return Ok(self);
+        }
+        let found = provider.credentials()?;
+        self.private_key = self.private_key.or(found.private_key);
+        self.api_credentials = self.api_credentials.or(found.api_credentials);
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+
+    #[test]
+    fn test_chain_takes_each_piece_from_the_first_provider_with_it() {
+        let dir = std::env::temp_dir().join(format!("polyfill-creds-{}", uuid::Uuid::new_v4()));
+        let secrets = dir.join("secrets");
+        std::fs::create_dir_all(&secrets).unwrap();
+        std::fs::write(secrets.join("private_key"), format!("{KEY}\n")).unwrap();
+        let file = dir.join("creds.json");
+        std::fs::write(
+            &file,
+            r#"{"api_key":"key","api_secret":"secret","api_passphrase":"pass"}"#,
+        )
+        .unwrap();
+
+        let chain = CredentialsChain::new()
+            .with_provider(SecretsDirProvider::new(dir.join("missing")))
+            .with_provider(SecretsDirProvider::new(&secrets))
+            .with_provider(FileProvider::new(&file))
+            .with_provider(FileProvider::new(dir.join("never-read.json")));
+        let config = ClientConfig::default().with_credentials(&chain).unwrap();
+        assert_eq!(config.private_key.as_deref(), Some(KEY));
+        assert_eq!(config.api_credentials.unwrap().passphrase, "pass");
+        assert!(!format!("{:?}", chain.credentials().unwrap()).contains(KEY));
+
+        std::fs::write(secrets.join("api_key"), "only-key").unwrap();
+        let err = CredentialsChain::new()
+            .with_provider(SecretsDirProvider::new(&secrets))
+            .credentials()
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("api_secret, api_passphrase"),
+            "{err}"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_command_provider_reads_json_output() {
+        let provider = CommandProvider::new("echo")
+            .with_args([format!(r#"{{"private_key":"{KEY}","lease_id":"abc"}}"#)]);
+        let credentials = provider.credentials().unwrap();
+        assert_eq!(credentials.private_key.as_deref(), Some(KEY));
+        assert!(credentials.api_credentials.is_none());
+
+        assert!(CommandProvider::new("false").credentials().is_err());
+    }
+}
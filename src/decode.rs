@@ -133,8 +133,8 @@ pub mod deserializers {
 
     /// Deserialize an optional Decimal from string/number/null.
     ///
-    /// This compatibility helper accepts multiple API shapes by first decoding into
-    /// `serde_json::Value`. It is not intended for allocation-sensitive hot paths.
+    /// Strings go through [`fast_parse::parse_decimal`] straight from the
+    /// input, without buffering the value.
     ///
     /// - `null` => `None`
     /// - `""` => `None`
@@ -145,60 +145,140 @@ pub mod deserializers {
     where
         D: Deserializer<'de>,
     {
-        let value = serde_json::Value::deserialize(deserializer)?;
-        match value {
-            serde_json::Value::Null => Ok(None),
-            serde_json::Value::String(s) => {
-                let s = s.trim();
-                if s.is_empty() {
-                    Ok(None)
-                } else {
-                    s.parse::<Decimal>()
-                        .map(Some)
-                        .map_err(serde::de::Error::custom)
-                }
-            },
-            serde_json::Value::Number(n) => Decimal::from_str(&n.to_string())
-                .map(Some)
-                .map_err(serde::de::Error::custom),
-            other => Err(serde::de::Error::custom(format!(
-                "Expected decimal as string/number/null, got {other}"
-            ))),
-        }
+        deserializer.deserialize_any(DecimalVisitor { lenient: false })
     }
 
     /// Like `optional_decimal_from_string`, but returns `None` on parse errors.
-    ///
-    /// This compatibility helper accepts multiple API shapes by first decoding into
-    /// `serde_json::Value`. It is not intended for allocation-sensitive hot paths.
     pub fn optional_decimal_from_string_default_on_error<'de, D>(
         deserializer: D,
     ) -> std::result::Result<Option<Decimal>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let value = serde_json::Value::deserialize(deserializer)?;
-        match value {
-            serde_json::Value::Null => Ok(None),
-            serde_json::Value::String(s) => {
-                let s = s.trim();
-                if s.is_empty() {
-                    Ok(None)
-                } else {
-                    Ok(s.parse::<Decimal>().ok())
-                }
-            },
-            serde_json::Value::Number(n) => Ok(Decimal::from_str(&n.to_string()).ok()),
-            _ => Ok(None),
+        deserializer.deserialize_any(DecimalVisitor { lenient: true })
+    }
+
+    /// Decimal given as a string or JSON number; `lenient` maps anything
+    /// unparseable to `None` instead of an error
+    struct DecimalVisitor {
+        lenient: bool,
+    }
+
+    impl DecimalVisitor {
+        fn finish<E: serde::de::Error>(
+            &self,
+            parsed: std::result::Result<Decimal, String>,
+        ) -> std::result::Result<Option<Decimal>, E> {
+            match parsed {
+                Ok(value) => Ok(Some(value)),
+                Err(_) if self.lenient => Ok(None),
+                Err(e) => Err(E::custom(e)),
+            }
+        }
+
+        fn other<E: serde::de::Error>(
+            &self,
+            unexpected: serde::de::Unexpected<'_>,
+        ) -> std::result::Result<Option<Decimal>, E> {
+            if self.lenient {
+                Ok(None)
+            } else {
+                Err(E::invalid_type(unexpected, self))
+            }
+        }
+    }
+
+    impl<'de> serde::de::Visitor<'de> for DecimalVisitor {
+        type Value = Option<Decimal>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("a decimal as string, number or null")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> std::result::Result<Self::Value, E> {
+            let v = v.trim();
+            if v.is_empty() {
+                return Ok(None);
+            }
+            self.finish(fast_parse::parse_decimal(v).map_err(|e| e.to_string()))
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> std::result::Result<Self::Value, E> {
+            Ok(Some(Decimal::from(v)))
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, v: i64) -> std::result::Result<Self::Value, E> {
+            Ok(Some(Decimal::from(v)))
+        }
+
+        fn visit_f64<E: serde::de::Error>(self, v: f64) -> std::result::Result<Self::Value, E> {
+            use rust_decimal::prelude::FromPrimitive;
+            use std::io::Write;
+
+            // Shortest round-trip form, so 0.57 decodes as 0.57 rather than
+            // the binary expansion of the nearest double. Formatted on the
+            // stack to keep the decode path allocation-free.
+            let mut buf = [0u8; 64];
+            let mut cursor = std::io::Cursor::new(&mut buf[..]);
+            let written = write!(cursor, "{v}")
+                .is_ok()
+                .then(|| cursor.position() as usize);
+            let shortest =
+                written.and_then(|len| super::fast_parse::parse_plain_decimal(&buf[..len]));
+            self.finish(
+                shortest
+                    .or_else(|| Decimal::from_f64(v))
+                    .ok_or_else(|| format!("Invalid decimal: {v}")),
+            )
+        }
+
+        fn visit_bool<E: serde::de::Error>(self, v: bool) -> std::result::Result<Self::Value, E> {
+            self.other(serde::de::Unexpected::Bool(v))
+        }
+
+        fn visit_unit<E: serde::de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_none<E: serde::de::Error>(self) -> std::result::Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> std::result::Result<Self::Value, D::Error> {
+            deserializer.deserialize_any(self)
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> std::result::Result<Self::Value, A::Error> {
+            if !self.lenient {
+                return self.other(serde::de::Unexpected::Seq);
+            }
+            while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+            Ok(None)
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> std::result::Result<Self::Value, A::Error> {
+            if !self.lenient {
+                return self.other(serde::de::Unexpected::Map);
+            }
+            while map
+                .next_entry::<serde::de::IgnoredAny, serde::de::IgnoredAny>()?
+                .is_some()
+            {}
+            Ok(None)
         }
     }
 
     /// Deserialize a Decimal from string/number.
     ///
-    /// This compatibility helper accepts multiple API shapes through
-    /// `optional_decimal_from_string`. It is not intended for allocation-sensitive
-    /// hot paths.
-    ///
     /// - `""` => error
     /// - invalid values => error
     pub fn decimal_from_string<'de, D>(deserializer: D) -> std::result::Result<Decimal, D::Error>
@@ -211,10 +291,6 @@ pub mod deserializers {
     }
 
     /// Deserialize a Decimal from string/number/null, defaulting missing-ish values to zero.
-    ///
-    /// This compatibility helper accepts multiple API shapes through
-    /// `optional_decimal_from_string`. It is not intended for allocation-sensitive
-    /// hot paths.
     pub fn decimal_from_string_or_zero<'de, D>(
         deserializer: D,
     ) -> std::result::Result<Decimal, D::Error>
@@ -484,6 +560,112 @@ impl Decoder<Market> for RawMarketResponse {
     }
 }
 
+/// `event_type` tag of a WebSocket message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamEventType {
+    Book,
+    PriceChange,
+    TickSizeChange,
+    LastTradePrice,
+    BestBidAsk,
+    NewMarket,
+    MarketResolved,
+    Trade,
+    Order,
+}
+
+impl StreamEventType {
+    pub const ALL: [StreamEventType; 9] = [
+        StreamEventType::Book,
+        StreamEventType::PriceChange,
+        StreamEventType::TickSizeChange,
+        StreamEventType::LastTradePrice,
+        StreamEventType::BestBidAsk,
+        StreamEventType::NewMarket,
+        StreamEventType::MarketResolved,
+        StreamEventType::Trade,
+        StreamEventType::Order,
+    ];
+
+    /// The event type for a wire tag, or `None` if it is not modelled
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.as_str() == tag)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StreamEventType::Book => "book",
+            StreamEventType::PriceChange => "price_change",
+            StreamEventType::TickSizeChange => "tick_size_change",
+            StreamEventType::LastTradePrice => "last_trade_price",
+            StreamEventType::BestBidAsk => "best_bid_ask",
+            StreamEventType::NewMarket => "new_market",
+            StreamEventType::MarketResolved => "market_resolved",
+            StreamEventType::Trade => "trade",
+            StreamEventType::Order => "order",
+        }
+    }
+
+    /// Whether the event is sent on the market channel rather than the
+    /// authenticated user channel
+    pub fn is_market(&self) -> bool {
+        !matches!(self, StreamEventType::Trade | StreamEventType::Order)
+    }
+}
+
+/// One WebSocket text frame: a single event object or a batch array.
+///
+/// Each event is dispatched on its `event_type` tag. Market-channel events
+/// that carry the tag as `type` instead are accepted too; on the user
+/// channel `type` is a field of its own (`PLACEMENT`, `TRADE`, ...) and is
+/// never read as the tag.
+#[derive(Debug, Clone, Copy)]
+pub struct StreamFrame<'a>(pub &'a [u8]);
+
+impl Decoder<Vec<StreamMessage>> for StreamFrame<'_> {
+    fn decode(&self) -> Result<Vec<StreamMessage>> {
+        match serde_json::from_slice(self.0)? {
+            Value::Object(map) => Ok(decode_stream_event(map)?.into_iter().collect()),
+            // A bad entry does not fail the rest of the batch
+            Value::Array(events) => Ok(events
+                .into_iter()
+                .filter_map(|event| match event {
+                    Value::Object(map) => decode_stream_event(map).ok().flatten(),
+                    _ => None,
+                })
+                .collect()),
+            _ => Ok(vec![]),
+        }
+    }
+}
+
+/// Decode one event object; `None` when it is untagged or of an unknown type
+fn decode_stream_event(mut map: serde_json::Map<String, Value>) -> Result<Option<StreamMessage>> {
+    let tagged = map
+        .get("event_type")
+        .and_then(Value::as_str)
+        .map(StreamEventType::from_tag);
+    let event_type = match tagged {
+        Some(event_type) => event_type,
+        None => {
+            let legacy = map
+                .get("type")
+                .and_then(Value::as_str)
+                .and_then(StreamEventType::from_tag)
+                .filter(StreamEventType::is_market);
+            if let Some(event_type) = legacy {
+                map.remove("type");
+                map.insert("event_type".to_string(), event_type.as_str().into());
+            }
+            legacy
+        },
+    };
+    if event_type.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_value(Value::Object(map))?))
+}
+
 /// Ergonomic WebSocket message parsing (official `event_type` shape).
 ///
 /// Polymarket WebSocket servers may send either a single JSON object or a batch array.
@@ -491,9 +673,9 @@ impl Decoder<Market> for RawMarketResponse {
 /// - Unknown/unsupported `event_type`s are ignored.
 /// - Invalid entries inside a batch are skipped (do not fail the whole batch).
 ///
-/// This is the compatibility parser for general stream consumers. It parses into
-/// `serde_json::Value` first so it can inspect event types and skip unknown messages.
-/// For allocation-sensitive WS `book` updates, use
+/// This is the compatibility parser for general stream consumers; see [`StreamFrame`].
+/// It parses into `serde_json::Value` first so it can inspect event types and skip
+/// unknown messages. For allocation-sensitive WS `book` updates, use
 /// [`crate::ws_hot_path::WsBookUpdateProcessor`] instead.
 pub fn parse_stream_messages(raw: &str) -> Result<Vec<StreamMessage>> {
     parse_stream_messages_bytes(raw.as_bytes())
@@ -501,46 +683,7 @@ pub fn parse_stream_messages(raw: &str) -> Result<Vec<StreamMessage>> {
 
 /// See `parse_stream_messages`.
 pub fn parse_stream_messages_bytes(bytes: &[u8]) -> Result<Vec<StreamMessage>> {
-    let value: Value = serde_json::from_slice(bytes)?;
-
-    match value {
-        Value::Object(map) => {
-            let event_type = map.get("event_type").and_then(Value::as_str);
-            match event_type {
-                None => Ok(vec![]),
-                Some(_) => {
-                    let msg: StreamMessage = serde_json::from_value(Value::Object(map))?;
-                    match msg {
-                        StreamMessage::Unknown => Ok(vec![]),
-                        other => Ok(vec![other]),
-                    }
-                },
-            }
-        },
-        Value::Array(arr) => Ok(arr
-            .into_iter()
-            .filter_map(|elem| {
-                let Value::Object(map) = elem else {
-                    return None;
-                };
-
-                let event_type = map.get("event_type").and_then(Value::as_str)?;
-                // Skip unknown event types early (forward compatibility).
-                match event_type {
-                    "book" | "price_change" | "tick_size_change" | "last_trade_price"
-                    | "best_bid_ask" | "new_market" | "market_resolved" | "trade" | "order" => {},
-                    _ => return None,
-                }
-
-                match serde_json::from_value::<StreamMessage>(Value::Object(map)) {
-                    Ok(StreamMessage::Unknown) => None,
-                    Ok(msg) => Some(msg),
-                    Err(_) => None,
-                }
-            })
-            .collect()),
-        _ => Ok(vec![]),
-    }
+    StreamFrame(bytes).decode()
 }
 
 /// Batch parsing utilities for high-throughput scenarios
@@ -620,13 +763,145 @@ impl Default for BatchDecoder {
 pub mod fast_parse {
     use super::*;
 
-    /// Fast decimal parsing for prices
+    /// Fast decimal parsing for prices.
+    ///
+    /// Plain `[-]digits[.digits]` strings of up to 28 digits are accumulated
+    /// straight into the mantissa; anything else falls back to
+    /// [`Decimal::from_str`].
     #[inline]
     pub fn parse_decimal(s: &str) -> Result<Decimal> {
+        if let Some(value) = parse_plain_decimal(s.as_bytes()) {
+            return Ok(value);
+        }
         Decimal::from_str(s)
             .map_err(|e| PolyfillError::parse(format!("Invalid decimal: {}", e), None))
     }
 
+    #[inline]
+    pub(crate) fn parse_plain_decimal(s: &[u8]) -> Option<Decimal> {
+        let (negative, digits) = match s.split_first()? {
+            (b'-', rest) => (true, rest),
+            (b'+', rest) => (false, rest),
+            _ => (false, s),
+        };
+
+        let mut mantissa = 0i128;
+        let mut scale = 0u32;
+        let mut digit_count = 0u32;
+        let mut seen_dot = false;
+        for &byte in digits {
+            match byte {
+                b'0'..=b'9' => {
+                    digit_count += 1;
+                    if digit_count > 28 {
+                        return None;
+                    }
+                    mantissa = mantissa * 10 + (byte - b'0') as i128;
+                    if seen_dot {
+                        scale += 1;
+                    }
+                },
+                b'.' if !seen_dot => seen_dot = true,
+                _ => return None,
+            }
+        }
+        if digit_count == 0 {
+            return None;
+        }
+
+        let mantissa = if negative { -mantissa } else { mantissa };
+        Decimal::try_from_i128_with_scale(mantissa, scale).ok()
+    }
+
+    /// Parse a non-negative decimal string straight into 4dp fixed-point units.
+    ///
+    /// Rejects values with non-zero digits beyond the 4th decimal place rather
+    /// than rounding them.
+    #[inline]
+    pub fn parse_scaled_4dp(value: &str) -> Result<u64> {
+        if value.is_empty() {
+            return Err(PolyfillError::parse("invalid decimal", None));
+        }
+
+        let mut whole = 0u64;
+        let mut frac = 0u64;
+        let mut frac_digits = 0u8;
+        let mut seen_dot = false;
+        let mut seen_digit = false;
+
+        for &byte in value.as_bytes() {
+            match byte {
+                b'0'..=b'9' => {
+                    seen_digit = true;
+                    let digit = (byte - b'0') as u64;
+                    if seen_dot {
+                        if frac_digits >= 4 {
+                            if digit != 0 {
+                                return Err(PolyfillError::parse("too many decimal places", None));
+                            }
+                        } else {
+                            frac = frac
+                                .checked_mul(10)
+                                .and_then(|x| x.checked_add(digit))
+                                .ok_or_else(|| {
+                                    PolyfillError::parse("scaled value overflow", None)
+                                })?;
+                            frac_digits += 1;
+                        }
+                    } else {
+                        whole = whole
+                            .checked_mul(10)
+                            .and_then(|x| x.checked_add(digit))
+                            .ok_or_else(|| PolyfillError::parse("scaled value overflow", None))?;
+                    }
+                },
+                b'.' if !seen_dot => {
+                    seen_dot = true;
+                },
+                _ => return Err(PolyfillError::parse("invalid decimal", None)),
+            }
+        }
+
+        if !seen_digit {
+            return Err(PolyfillError::parse("invalid decimal", None));
+        }
+
+        while frac_digits < 4 {
+            frac *= 10;
+            frac_digits += 1;
+        }
+
+        whole
+            .checked_mul(SCALE_FACTOR as u64)
+            .and_then(|x| x.checked_add(frac))
+            .ok_or_else(|| PolyfillError::parse("scaled value overflow", None))
+    }
+
+    /// Parse a price string into ticks
+    #[inline]
+    pub fn parse_price_ticks(value: &str) -> Result<Price> {
+        let scaled = parse_scaled_4dp(value)?;
+        if scaled < MIN_PRICE_TICKS as u64 {
+            return Err(PolyfillError::validation("Invalid price"));
+        }
+        if scaled > MAX_PRICE_TICKS as u64 {
+            return Err(PolyfillError::validation("Invalid price"));
+        }
+
+        Ok(scaled as Price)
+    }
+
+    /// Parse a size string into fixed-point quantity units
+    #[inline]
+    pub fn parse_qty_units(value: &str) -> Result<Qty> {
+        let scaled = parse_scaled_4dp(value)?;
+        if scaled > MAX_QTY as u64 {
+            return Err(PolyfillError::validation("Invalid size"));
+        }
+
+        Ok(scaled as Qty)
+    }
+
     /// Fast address parsing
     #[inline]
     pub fn parse_address(s: &str) -> Result<Address> {
@@ -674,24 +949,473 @@ pub mod fast_parse {
     /// Parse Side enum
     #[inline]
     pub fn parse_side(s: &str) -> Result<Side> {
-        match s.to_uppercase().as_str() {
-            "BUY" => Ok(Side::BUY),
-            "SELL" => Ok(Side::SELL),
-            _ => Err(PolyfillError::parse(format!("Invalid side: {}", s), None)),
+        s.parse()
+    }
+}
+
+/// Compact binary encoding of recorded book changes.
+///
+/// [`BookDeltaRecord`](crate::capture::BookDeltaRecord) streams are stored
+/// per token as delta-of-delta timestamps and prices, sequence deltas and
+/// varint sizes, so a typical row takes a handful of bytes instead of the
+/// hundred or so its JSON needs. Token ids are written once and referenced
+/// by index afterwards. Decoded prices and sizes compare equal to the
+/// originals but may carry extra trailing zeros.
+pub mod book_delta_codec {
+    use super::*;
+    use crate::capture::{BookDeltaRecord, BookEventKind};
+    use std::collections::HashMap;
+
+    /// Leading bytes of every encoded stream
+    pub const MAGIC: &[u8; 4] = b"PFBD";
+    /// Encoding version written after [`MAGIC`]
+    pub const VERSION: u8 = 1;
+
+    const KIND_DELTA: u8 = 1;
+    const SIDE_SELL: u8 = 1 << 1;
+    const PRICE_SCALE: u8 = 1 << 2;
+    const SIZE_SCALE: u8 = 1 << 3;
+    const MAX_SCALE: u32 = 28;
+
+    /// Per-token predictor state shared by the encoder and decoder
+    #[derive(Debug, Default, Clone)]
+    struct TokenState {
+        timestamp: i128,
+        timestamp_delta: i128,
+        sequence: i128,
+        price: i128,
+        price_delta: i128,
+        price_scale: u32,
+        size_scale: u32,
+    }
+
+    impl TokenState {
+        fn rescale_price(&mut self, scale: u32) -> Result<()> {
+            let factor = pow10(scale - self.price_scale)?;
+            self.price = checked(self.price.checked_mul(factor))?;
+            self.price_delta = checked(self.price_delta.checked_mul(factor))?;
+            self.price_scale = scale;
+            Ok(())
+        }
+    }
+
+    /// Streaming encoder; records may interleave tokens
+    #[derive(Debug)]
+    pub struct BookDeltaEncoder {
+        tokens: HashMap<String, usize>,
+        states: Vec<TokenState>,
+        out: Vec<u8>,
+    }
+
+    impl Default for BookDeltaEncoder {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl BookDeltaEncoder {
+        pub fn new() -> Self {
+            let mut out = MAGIC.to_vec();
+            out.push(VERSION);
+            Self {
+                tokens: HashMap::new(),
+                states: Vec::new(),
+                out,
+            }
+        }
+
+        pub fn encode(&mut self, record: &BookDeltaRecord) -> Result<()> {
+            if record.size.is_sign_negative() && !record.size.is_zero() {
+                return Err(PolyfillError::validation(format!(
+                    "Cannot encode negative size {} for {}",
+                    record.size, record.token_id
+                )));
+            }
+            let index = match self.tokens.get(&record.token_id) {
+                Some(index) => {
+                    write_varint(&mut self.out, *index as u128);
+                    *index
+                },
+                None => {
+                    let index = self.states.len();
+                    write_varint(&mut self.out, index as u128);
+                    write_varint(&mut self.out, record.token_id.len() as u128);
+                    self.out.extend_from_slice(record.token_id.as_bytes());
+                    self.tokens.insert(record.token_id.clone(), index);
+                    self.states.push(TokenState::default());
+                    index
+                },
+            };
+            let state = &mut self.states[index];
+
+            let mut flags = 0;
+            if record.kind == BookEventKind::Delta {
+                flags |= KIND_DELTA;
+            }
+            if record.side == Side::SELL {
+                flags |= SIDE_SELL;
+            }
+            let price_scale = record.price.scale();
+            let size_scale = record.size.scale();
+            if price_scale > state.price_scale {
+                flags |= PRICE_SCALE;
+                state.rescale_price(price_scale)?;
+            }
+            if size_scale > state.size_scale {
+                flags |= SIZE_SCALE;
+                state.size_scale = size_scale;
+            }
+            self.out.push(flags);
+            if flags & PRICE_SCALE != 0 {
+                self.out.push(price_scale as u8);
+            }
+            if flags & SIZE_SCALE != 0 {
+                self.out.push(size_scale as u8);
+            }
+
+            let timestamp = record.timestamp_ms as i128;
+            let timestamp_delta = timestamp - state.timestamp;
+            write_signed(&mut self.out, timestamp_delta - state.timestamp_delta);
+            state.timestamp = timestamp;
+            state.timestamp_delta = timestamp_delta;
+
+            let sequence = record.sequence as i128;
+            write_signed(&mut self.out, sequence - state.sequence);
+            state.sequence = sequence;
+
+            let price = mantissa_at(record.price, state.price_scale)?;
+            let price_delta = price - state.price;
+            write_signed(&mut self.out, price_delta - state.price_delta);
+            state.price = price;
+            state.price_delta = price_delta;
+
+            write_varint(
+                &mut self.out,
+                mantissa_at(record.size, state.size_scale)?.unsigned_abs(),
+            );
+            Ok(())
+        }
+
+        /// Encoded bytes so far
+        pub fn len(&self) -> usize {
+            self.out.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.out.len() == MAGIC.len() + 1
+        }
+
+        pub fn finish(self) -> Vec<u8> {
+            self.out
         }
     }
+
+    /// Iterates the records of an encoded stream
+    #[derive(Debug)]
+    pub struct BookDeltaDecoder<'a> {
+        input: &'a [u8],
+        pos: usize,
+        tokens: Vec<String>,
+        states: Vec<TokenState>,
+        failed: bool,
+    }
+
+    impl<'a> BookDeltaDecoder<'a> {
+        pub fn new(input: &'a [u8]) -> Result<Self> {
+            if input.len() < MAGIC.len() + 1 || &input[..MAGIC.len()] != MAGIC {
+                return Err(PolyfillError::parse(
+                    "Not an encoded book delta stream",
+                    None,
+                ));
+            }
+            if input[MAGIC.len()] != VERSION {
+                return Err(PolyfillError::parse(
+                    format!(
+                        "Unsupported book delta encoding version {}",
+                        input[MAGIC.len()]
+                    ),
+                    None,
+                ));
+            }
+            Ok(Self {
+                input,
+                pos: MAGIC.len() + 1,
+                tokens: Vec::new(),
+                states: Vec::new(),
+                failed: false,
+            })
+        }
+
+        fn byte(&mut self) -> Result<u8> {
+            let byte = *self
+                .input
+                .get(self.pos)
+                .ok_or_else(|| truncated(self.pos))?;
+            self.pos += 1;
+            Ok(byte)
+        }
+
+        fn varint(&mut self) -> Result<u128> {
+            let mut value: u128 = 0;
+            for shift in (0..128).step_by(7) {
+                let byte = self.byte()?;
+                value |= u128::from(byte & 0x7f) << shift;
+                if byte & 0x80 == 0 {
+                    return Ok(value);
+                }
+            }
+            Err(PolyfillError::parse(
+                format!("Varint too long at byte {}", self.pos),
+                None,
+            ))
+        }
+
+        fn signed(&mut self) -> Result<i128> {
+            let value = self.varint()?;
+            Ok((value >> 1) as i128 ^ -((value & 1) as i128))
+        }
+
+        fn scale(&mut self) -> Result<u32> {
+            let scale = u32::from(self.byte()?);
+            if scale > MAX_SCALE {
+                return Err(PolyfillError::parse(
+                    format!("Invalid decimal scale {scale}"),
+                    None,
+                ));
+            }
+            Ok(scale)
+        }
+
+        fn record(&mut self) -> Result<BookDeltaRecord> {
+            let index = self.varint()? as usize;
+            if index == self.tokens.len() {
+                let len = self.varint()? as usize;
+                let end = self
+                    .pos
+                    .checked_add(len)
+                    .filter(|end| *end <= self.input.len())
+                    .ok_or_else(|| truncated(self.pos))?;
+                let token_id = std::str::from_utf8(&self.input[self.pos..end])
+                    .map_err(|e| PolyfillError::parse("Token id is not UTF-8", Some(Box::new(e))))?
+                    .to_string();
+                self.pos = end;
+                self.tokens.push(token_id);
+                self.states.push(TokenState::default());
+            } else if index > self.tokens.len() {
+                return Err(PolyfillError::parse(
+                    format!("Unknown token reference {index}"),
+                    None,
+                ));
+            }
+
+            let flags = self.byte()?;
+            if flags & PRICE_SCALE != 0 {
+                let scale = self.scale()?;
+                let state = &mut self.states[index];
+                if scale < state.price_scale {
+                    return Err(PolyfillError::parse("Price scale went down", None));
+                }
+                state.rescale_price(scale)?;
+            }
+            if flags & SIZE_SCALE != 0 {
+                self.states[index].size_scale = self.scale()?;
+            }
+
+            let timestamp_dod = self.signed()?;
+            let sequence_delta = self.signed()?;
+            let price_dod = self.signed()?;
+            let size = self.varint()?;
+
+            let state = &mut self.states[index];
+            state.timestamp_delta += timestamp_dod;
+            state.timestamp += state.timestamp_delta;
+            state.sequence += sequence_delta;
+            state.price_delta += price_dod;
+            state.price += state.price_delta;
+
+            Ok(BookDeltaRecord {
+                token_id: self.tokens[index].clone(),
+                timestamp_ms: u64::try_from(state.timestamp)
+                    .map_err(|_| PolyfillError::parse("Timestamp out of range", None))?,
+                sequence: u64::try_from(state.sequence)
+                    .map_err(|_| PolyfillError::parse("Sequence out of range", None))?,
+                kind: if flags & KIND_DELTA != 0 {
+                    BookEventKind::Delta
+                } else {
+                    BookEventKind::Snapshot
+                },
+                side: if flags & SIDE_SELL != 0 {
+                    Side::SELL
+                } else {
+                    Side::BUY
+                },
+                price: decimal(state.price, state.price_scale)?,
+                size: decimal(
+                    i128::try_from(size)
+                        .map_err(|_| PolyfillError::parse("Size out of range", None))?,
+                    state.size_scale,
+                )?,
+            })
+        }
+    }
+
+    impl Iterator for BookDeltaDecoder<'_> {
+        type Item = Result<BookDeltaRecord>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            if self.failed || self.pos >= self.input.len() {
+                return None;
+            }
+            let record = self.record();
+            self.failed = record.is_err();
+            Some(record)
+        }
+    }
+
+    /// Encode `records` in one go
+    pub fn encode_book_deltas(records: &[BookDeltaRecord]) -> Result<Vec<u8>> {
+        let mut encoder = BookDeltaEncoder::new();
+        for record in records {
+            encoder.encode(record)?;
+        }
+        Ok(encoder.finish())
+    }
+
+    /// Decode a whole stream produced by [`BookDeltaEncoder`]
+    pub fn decode_book_deltas(input: &[u8]) -> Result<Vec<BookDeltaRecord>> {
+        BookDeltaDecoder::new(input)?.collect()
+    }
+
+    fn write_varint(out: &mut Vec<u8>, mut value: u128) {
+        while value >= 0x80 {
+            out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        out.push(value as u8);
+    }
+
+    fn write_signed(out: &mut Vec<u8>, value: i128) {
+        write_varint(out, ((value << 1) ^ (value >> 127)) as u128);
+    }
+
+    fn pow10(exp: u32) -> Result<i128> {
+        checked(10i128.checked_pow(exp))
+    }
+
+    fn checked(value: Option<i128>) -> Result<i128> {
+        value.ok_or_else(|| PolyfillError::parse("Decimal out of range", None))
+    }
+
+    fn mantissa_at(value: Decimal, scale: u32) -> Result<i128> {
+        checked(value.mantissa().checked_mul(pow10(scale - value.scale())?))
+    }
+
+    fn decimal(mantissa: i128, scale: u32) -> Result<Decimal> {
+        Decimal::try_from_i128_with_scale(mantissa, scale)
+            .map_err(|e| PolyfillError::parse(format!("Decimal out of range: {e}"), None))
+    }
+
+    fn truncated(pos: usize) -> PolyfillError {
+        PolyfillError::parse(format!("Book delta stream truncated at byte {pos}"), None)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_book_delta_codec_round_trips_compactly() {
+        use crate::capture::{BookDeltaRecord, BookEventKind};
+        use book_delta_codec::*;
+
+        let token_a =
+            "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+        let mut records = Vec::new();
+        for i in 0..200u64 {
+            for (token_id, price) in [(token_a, "0.5"), ("42", "0.505")] {
+                records.push(BookDeltaRecord {
+                    token_id: token_id.to_string(),
+                    timestamp_ms: 1_700_000_000_000 + i * 250,
+                    sequence: i + 1,
+                    kind: if i == 0 {
+                        BookEventKind::Snapshot
+                    } else {
+                        BookEventKind::Delta
+                    },
+                    side: if i % 2 == 0 { Side::BUY } else { Side::SELL },
+                    price: Decimal::from_str(price).unwrap() + Decimal::new(i as i64 % 7, 2),
+                    size: Decimal::new(i as i64 * 125, 1),
+                });
+            }
+        }
+
+        let encoded = encode_book_deltas(&records).unwrap();
+        assert_eq!(decode_book_deltas(&encoded).unwrap(), records);
+        let json: usize = records
+            .iter()
+            .map(|r| serde_json::to_vec(r).unwrap().len())
+            .sum();
+        assert!(encoded.len() * 10 < json, "{} vs {}", encoded.len(), json);
+
+        let truncated = &encoded[..encoded.len() - 1];
+        let decoded: Vec<_> = BookDeltaDecoder::new(truncated).unwrap().collect();
+        assert_eq!(decoded.len(), records.len());
+        assert!(decoded.last().unwrap().is_err());
+        assert!(BookDeltaDecoder::new(b"nope").is_err());
+    }
+
     #[test]
     fn test_parse_decimal() {
         let result = fast_parse::parse_decimal("123.456").unwrap();
         assert_eq!(result, Decimal::from_str("123.456").unwrap());
     }
 
+    #[test]
+    fn test_decimal_fast_path_matches_from_str() {
+        for raw in [
+            "0.5",
+            "-12.3400",
+            "+7",
+            ".25",
+            "1.",
+            "0.0001",
+            "79228162514264337593543950335",
+            "1e5",
+        ] {
+            assert_eq!(
+                fast_parse::parse_decimal(raw).unwrap(),
+                Decimal::from_str(raw).unwrap(),
+                "{raw}"
+            );
+        }
+        assert!(fast_parse::parse_decimal("-").is_err());
+
+        #[derive(Deserialize)]
+        struct Level {
+            #[serde(deserialize_with = "deserializers::decimal_from_string")]
+            price: Decimal,
+            #[serde(
+                default,
+                deserialize_with = "deserializers::optional_decimal_from_string_default_on_error"
+            )]
+            size: Option<Decimal>,
+        }
+        let level: Level = serde_json::from_str(r#"{"price":0.57,"size":{"bad":1}}"#).unwrap();
+        assert_eq!(level.price.to_string(), "0.57");
+        assert_eq!(level.size, None);
+        let level: Level =
+            serde_json::from_str(r#"{"price":0.1,"size":0.30000000000000004}"#).unwrap();
+        assert_eq!(level.price.to_string(), "0.1");
+        assert_eq!(level.size.unwrap().to_string(), "0.30000000000000004");
+        assert!(serde_json::from_str::<Level>(r#"{"price":1e300}"#).is_err());
+        let level: Level = serde_json::from_str(r#"{"price":" 0.3 ","size":"abc"}"#).unwrap();
+        assert_eq!(level.price, Decimal::from_str("0.3").unwrap());
+        assert_eq!(level.size, None);
+        assert!(serde_json::from_str::<Level>(r#"{"price":true}"#).is_err());
+        assert!(serde_json::from_str::<Level>(r#"{"price":""}"#).is_err());
+    }
+
     #[test]
     fn test_parse_side() {
         assert_eq!(fast_parse::parse_side("BUY").unwrap(), Side::BUY);
@@ -720,4 +1444,125 @@ mod tests {
         let messages = parse_stream_messages_bytes(empty_sides).unwrap();
         assert_eq!(messages.len(), 1);
     }
+
+    /// Market-channel payloads as sent by the venue
+    const MARKET_FIXTURES: [(StreamEventType, &str); 5] = [
+        (
+            StreamEventType::Book,
+            include_str!("../tests/fixtures/ws/book.json"),
+        ),
+        (
+            StreamEventType::PriceChange,
+            include_str!("../tests/fixtures/ws/price_change.json"),
+        ),
+        (
+            StreamEventType::TickSizeChange,
+            include_str!("../tests/fixtures/ws/tick_size_change.json"),
+        ),
+        (
+            StreamEventType::LastTradePrice,
+            include_str!("../tests/fixtures/ws/last_trade_price.json"),
+        ),
+        (
+            StreamEventType::BestBidAsk,
+            include_str!("../tests/fixtures/ws/best_bid_ask.json"),
+        ),
+    ];
+
+    #[test]
+    fn stream_frame_decodes_market_fixtures() {
+        for (event_type, raw) in MARKET_FIXTURES {
+            let messages = StreamFrame(raw.as_bytes()).decode().unwrap();
+            assert_eq!(messages.len(), 1, "{raw}");
+            assert_eq!(messages[0].event_type(), Some(event_type));
+            // Every field of the fixture is modelled
+            assert_eq!(messages[0].extra().map(|e| e.len()), Some(0), "{raw}");
+        }
+
+        let [StreamMessage::Book(book)] = &parse_stream_messages(MARKET_FIXTURES[0].1).unwrap()[..]
+        else {
+            panic!("expected a book");
+        };
+        assert_eq!((book.bids.len(), book.asks.len()), (3, 3));
+        assert_eq!(book.bids[0].price, Decimal::from_str("0.48").unwrap());
+        assert_eq!(book.timestamp, 1_757_908_892_351);
+
+        let [StreamMessage::PriceChange(change)] =
+            &parse_stream_messages(MARKET_FIXTURES[1].1).unwrap()[..]
+        else {
+            panic!("expected a price change");
+        };
+        assert_eq!(change.price_changes.len(), 2);
+        assert_eq!(change.price_changes[1].side, Side::SELL);
+        assert_eq!(
+            change.price_changes[1].best_ask,
+            Decimal::from_str("0.5").ok()
+        );
+
+        let [StreamMessage::LastTradePrice(trade)] =
+            &parse_stream_messages(MARKET_FIXTURES[3].1).unwrap()[..]
+        else {
+            panic!("expected a trade");
+        };
+        assert_eq!(trade.size, Decimal::from_str("219.217767").ok());
+        assert_eq!(trade.side, Some(Side::BUY));
+    }
+
+    #[test]
+    fn stream_frame_dispatches_on_tag() {
+        // Batches keep good entries around unknown, untagged and malformed ones
+        let batch = format!(
+            r#"[{},{{"event_type":"new_feature","x":1}},{{"asset_id":"1"}},{{"event_type":"book"}},{}]"#,
+            MARKET_FIXTURES[2].1.trim(),
+            MARKET_FIXTURES[4].1.trim()
+        );
+        let messages = parse_stream_messages(&batch).unwrap();
+        let tags: Vec<_> = messages
+            .iter()
+            .filter_map(StreamMessage::event_type)
+            .collect();
+        assert_eq!(
+            tags,
+            vec![StreamEventType::TickSizeChange, StreamEventType::BestBidAsk]
+        );
+
+        // A market event tagged with `type` is accepted...
+        let legacy = MARKET_FIXTURES[4].1.replace("\"event_type\"", "\"type\"");
+        let messages = parse_stream_messages(&legacy).unwrap();
+        assert_eq!(messages[0].event_type(), Some(StreamEventType::BestBidAsk));
+        assert!(messages[0].extra().unwrap().is_empty());
+
+        // ...but a user event's `type` is a field, not the tag
+        let untagged_order = r#"{"id":"o1","market":"0xabc","asset_id":"1","side":"BUY","price":"0.5","type":"PLACEMENT"}"#;
+        assert!(parse_stream_messages(untagged_order).unwrap().is_empty());
+        assert!(parse_stream_messages(r#"{"type":"order"}"#)
+            .unwrap()
+            .is_empty());
+
+        // A single malformed event is an error
+        assert!(parse_stream_messages(r#"{"event_type":"book"}"#).is_err());
+    }
+
+    #[test]
+    fn stream_messages_keep_unknown_fields_and_round_trip() {
+        let raw = br#"{"event_type":"price_change","market":"0xabc","timestamp":"1000","sequence":7,"price_changes":[{"asset_id":"1","price":"0.5","size":"10","side":"BUY","maker":"0x1"}]}"#;
+        let messages = parse_stream_messages_bytes(raw).unwrap();
+        let [StreamMessage::PriceChange(change)] = messages.as_slice() else {
+            panic!("expected a price change, got {messages:?}");
+        };
+        assert_eq!(change.extra["sequence"], 7);
+        assert_eq!(change.price_changes[0].extra["maker"], "0x1");
+        assert_eq!(messages[0].extra(), Some(&change.extra));
+
+        let recorded = serde_json::to_string(&messages[0]).unwrap();
+        let replayed = parse_stream_messages(&recorded).unwrap();
+        assert_eq!(
+            serde_json::to_value(&replayed[0]).unwrap(),
+            serde_json::to_value(&messages[0]).unwrap()
+        );
+        assert_eq!(
+            serde_json::from_str::<Value>(&recorded).unwrap()["sequence"],
+            7
+        );
+    }
 }
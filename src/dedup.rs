@@ -0,0 +1,400 @@
+//! Duplicate order guard
+//!
+//! A strategy loop that fires twice on the same signal places the same order
+//! twice. [`DuplicateGuard`] remembers recently submitted orders and rejects
+//! a new one with [`OrderErrorKind::DuplicateOrder`] if it reuses a client ID
+//! or matches a recent order's token, side, price and size within the
+//! strategy's [`DuplicateTolerance`]. Install it on a client with
+//! [`crate::ClobClient::set_duplicate_guard`], or call
+//! [`DuplicateGuard::check`] directly from a strategy.
+//!
+//! An order that may still fail is registered with
+//! [`DuplicateGuard::reserve`]; the reservation blocks look-alikes while the
+//! order is in flight and can be released again, so a rejected order does
+//! not block an identical retry. Once marked in flight, a reservation is only
+//! released explicitly: dropping it, e.g. when a timeout cancels the post,
+//! keeps it, since the order may already be live.
+
+use crate::errors::{OrderErrorKind, PolyfillError, Result};
+use crate::types::{MarketOrderArgs, OrderArgs, Side};
+use parking_lot::{Mutex, RwLock};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// When two orders count as duplicates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DuplicateTolerance {
+    /// How long a submitted order blocks look-alikes
+    pub window: Duration,
+    /// Largest price difference still treated as the same price
+    pub price_tolerance: Decimal,
+    /// Largest size difference still treated as the same size
+    pub size_tolerance: Decimal,
+}
+
+impl Default for DuplicateTolerance {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(2),
+            price_tolerance: Decimal::ZERO,
+            size_tolerance: Decimal::ZERO,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Fingerprint {
+    id: u64,
+    strategy: Option<String>,
+    token_id: String,
+    side: Side,
+    price: Decimal,
+    size: Decimal,
+    expires_at: Instant,
+}
+
+/// Registry of recent client IDs and order fingerprints
+#[derive(Debug)]
+pub struct DuplicateGuard {
+    default_tolerance: DuplicateTolerance,
+    strategies: RwLock<HashMap<String, DuplicateTolerance>>,
+    client_id_window: Duration,
+    client_ids: Mutex<HashMap<String, Instant>>,
+    recent: Mutex<VecDeque<Fingerprint>>,
+    next_id: AtomicU64,
+}
+
+impl Default for DuplicateGuard {
+    fn default() -> Self {
+        Self::new(DuplicateTolerance::default())
+    }
+}
+
+impl DuplicateGuard {
+    /// Guard using `tolerance` for strategies without their own
+    pub fn new(tolerance: DuplicateTolerance) -> Self {
+        Self {
+            default_tolerance: tolerance,
+            strategies: RwLock::new(HashMap::new()),
+            client_id_window: Duration::from_secs(60 * 60),
+            client_ids: Mutex::new(HashMap::new()),
+            recent: Mutex::new(VecDeque::new()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    /// How long a client ID stays reserved (default one hour)
+    pub fn with_client_id_window(mut self, window: Duration) -> Self {
+        self.client_id_window = window;
+        self
+    }
+
+    /// Use `tolerance` for orders submitted under `strategy`
+    pub fn set_tolerance(&self, strategy: &str, tolerance: DuplicateTolerance) {
+        self.strategies
+            .write()
+            .insert(strategy.to_string(), tolerance);
+    }
+
+    fn tolerance(&self, strategy: Option<&str>) -> DuplicateTolerance {
+        strategy
+            .and_then(|strategy| self.strategies.read().get(strategy).copied())
+            .unwrap_or(self.default_tolerance)
+    }
+
+    /// Check an order about to be submitted and remember it.
+    ///
+    /// Fingerprints are only compared within the same strategy; client IDs
+    /// are global.
+    pub fn check(
+        &self,
+        strategy: Option<&str>,
+        client_id: Option<&str>,
+        token_id: &str,
+        side: Side,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<()> {
+        self.register(strategy, client_id, token_id, side, price, size)?;
+        Ok(())
+    }
+
+    /// Like [`Self::check_order`], but the order is forgotten again when the
+    /// returned reservation is released, or dropped before it is in flight
+    pub fn reserve(
+        self: &Arc<Self>,
+        strategy: Option<&str>,
+        client_id: Option<&str>,
+        args: &OrderArgs,
+    ) -> Result<DuplicateReservation> {
+        let id = self.register(
+            strategy,
+            client_id,
+            args.token_id.as_str(),
+            args.side,
+            args.price,
+            args.size,
+        )?;
+        Ok(self.reservation(id, client_id))
+    }
+
+    /// [`Self::reserve`] for a market order, fingerprinted by its amount and
+    /// price limit (zero without one)
+    pub fn reserve_market(
+        self: &Arc<Self>,
+        strategy: Option<&str>,
+        client_id: Option<&str>,
+        args: &MarketOrderArgs,
+    ) -> Result<DuplicateReservation> {
+        let id = self.register(
+            strategy,
+            client_id,
+            args.token_id.as_str(),
+            args.side,
+            args.price_limit.unwrap_or_default(),
+            args.amount,
+        )?;
+        Ok(self.reservation(id, client_id))
+    }
+
+    fn reservation(self: &Arc<Self>, id: u64, client_id: Option<&str>) -> DuplicateReservation {
+        DuplicateReservation {
+            guard: Arc::clone(self),
+            id,
+            client_id: client_id.map(str::to_string),
+            state: ReservationState::Reserved,
+        }
+    }
+
+    fn register(
+        &self,
+        strategy: Option<&str>,
+        client_id: Option<&str>,
+        token_id: &str,
+        side: Side,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<u64> {
+        let now = Instant::now();
+        let tolerance = self.tolerance(strategy);
+
+        let mut client_ids = self.client_ids.lock();
+        client_ids.retain(|_, expires_at| *expires_at > now);
+        if let Some(client_id) = client_id {
+            if client_ids.contains_key(client_id) {
+                return Err(PolyfillError::order(
+                    format!("Client ID {client_id} was used recently"),
+                    OrderErrorKind::DuplicateOrder,
+                ));
+            }
+        }
+
+        let mut recent = self.recent.lock();
+        recent.retain(|fingerprint| fingerprint.expires_at > now);
+        let duplicate = recent.iter().any(|fingerprint| {
+            fingerprint.strategy.as_deref() == strategy
+                && fingerprint.token_id == token_id
+                && fingerprint.side == side
+                && (fingerprint.price - price).abs() <= tolerance.price_tolerance
+                && (fingerprint.size - size).abs() <= tolerance.size_tolerance
+        });
+        if duplicate {
+            return Err(PolyfillError::order(
+                format!(
+                    "Duplicate {} order for {} at {} x {} within {:?}",
+                    side.as_str(),
+                    token_id,
+                    price,
+                    size,
+                    tolerance.window
+                ),
+                OrderErrorKind::DuplicateOrder,
+            ));
+        }
+
+        if let Some(client_id) = client_id {
+            client_ids.insert(client_id.to_string(), now + self.client_id_window);
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        recent.push_back(Fingerprint {
+            id,
+            strategy: strategy.map(str::to_string),
+            token_id: token_id.to_string(),
+            side,
+            price,
+            size,
+            expires_at: now + tolerance.window,
+        });
+        Ok(id)
+    }
+
+    fn release(&self, id: u64, client_id: Option<&str>) {
+        if let Some(client_id) = client_id {
+            self.client_ids.lock().remove(client_id);
+        }
+        self.recent
+            .lock()
+            .retain(|fingerprint| fingerprint.id != id);
+    }
+
+    /// [`Self::check`] for limit order arguments
+    pub fn check_order(
+        &self,
+        strategy: Option<&str>,
+        client_id: Option<&str>,
+        args: &OrderArgs,
+    ) -> Result<()> {
+        self.check(
+            strategy,
+            client_id,
+            args.token_id.as_str(),
+            args.side,
+            args.price,
+            args.size,
+        )
+    }
+
+    /// Forget everything, e.g. after the strategy was reset
+    pub fn clear(&self) {
+        self.client_ids.lock().clear();
+        self.recent.lock().clear();
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReservationState {
+    /// Not sent yet; released on drop
+    Reserved,
+    /// Possibly live; kept on drop
+    InFlight,
+    Settled,
+}
+
+/// Order registered with a [`DuplicateGuard`]. Dropped before it is marked
+/// in flight, it is released; afterwards only [`Self::release`] frees it.
+#[derive(Debug)]
+#[must_use = "dropping a reservation before it is in flight releases it"]
+pub struct DuplicateReservation {
+    guard: Arc<DuplicateGuard>,
+    id: u64,
+    client_id: Option<String>,
+    state: ReservationState,
+}
+
+impl DuplicateReservation {
+    /// Mark the order as sent, so dropping the reservation, e.g. when the
+    /// post is cancelled, keeps it registered
+    pub fn in_flight(mut self) -> Self {
+        self.state = ReservationState::InFlight;
+        self
+    }
+
+    /// Keep the order registered, e.g. once the venue accepted it
+    pub fn commit(mut self) {
+        self.state = ReservationState::Settled;
+    }
+
+    /// Forget the order, e.g. once the venue rejected it
+    pub fn release(mut self) {
+        self.guard.release(self.id, self.client_id.as_deref());
+        self.state = ReservationState::Settled;
+    }
+}
+
+impl Drop for DuplicateReservation {
+    fn drop(&mut self) {
+        if self.state == ReservationState::Reserved {
+            self.guard.release(self.id, self.client_id.as_deref());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_rejects_duplicates_within_tolerance() {
+        let guard = DuplicateGuard::default();
+        guard.set_tolerance(
+            "mm",
+            DuplicateTolerance {
+                window: Duration::from_millis(20),
+                price_tolerance: dec!(0.01),
+                size_tolerance: dec!(1),
+            },
+        );
+
+        guard
+            .check(Some("mm"), Some("a"), "1", Side::BUY, dec!(0.50), dec!(10))
+            .unwrap();
+        let err = guard
+            .check(Some("mm"), None, "1", Side::BUY, dec!(0.51), dec!(11))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PolyfillError::Order {
+                kind: OrderErrorKind::DuplicateOrder,
+                ..
+            }
+        ));
+        // Other side, other strategy and a wider price are fine
+        guard
+            .check(Some("mm"), None, "1", Side::SELL, dec!(0.50), dec!(10))
+            .unwrap();
+        guard
+            .check(None, None, "1", Side::BUY, dec!(0.50), dec!(10))
+            .unwrap();
+        guard
+            .check(Some("mm"), None, "1", Side::BUY, dec!(0.52), dec!(10))
+            .unwrap();
+
+        std::thread::sleep(Duration::from_millis(30));
+        guard
+            .check(Some("mm"), None, "1", Side::BUY, dec!(0.50), dec!(10))
+            .unwrap();
+        // Client IDs outlive the fingerprint window
+        assert!(guard
+            .check(Some("mm"), Some("a"), "2", Side::BUY, dec!(0.1), dec!(1))
+            .is_err());
+
+        // A released reservation does not block a retry; a committed one does
+        let guard = Arc::new(guard);
+        let args = OrderArgs::new("3".parse().unwrap(), dec!(0.4), dec!(5), Side::BUY);
+        let reservation = guard.reserve(None, Some("b"), &args).unwrap();
+        assert!(guard.reserve(None, None, &args).is_err());
+        drop(reservation);
+        guard.reserve(None, Some("b"), &args).unwrap().commit();
+        assert!(guard.reserve(None, None, &args).is_err());
+        assert!(guard
+            .check(None, Some("b"), "4", Side::BUY, dec!(0.1), dec!(1))
+            .is_err());
+
+        // Dropped in flight, e.g. by a timeout, the order may be live
+        let args = OrderArgs::new("5".parse().unwrap(), dec!(0.4), dec!(5), Side::BUY);
+        drop(guard.reserve(None, None, &args).unwrap().in_flight());
+        assert!(guard.reserve(None, None, &args).is_err());
+        guard.clear();
+        guard
+            .reserve(None, None, &args)
+            .unwrap()
+            .in_flight()
+            .release();
+        guard.reserve(None, None, &args).unwrap().commit();
+
+        let market = MarketOrderArgs::new(
+            "5".parse().unwrap(),
+            dec!(20),
+            Side::BUY,
+            crate::types::OrderType::FOK,
+        );
+        let reservation = guard.reserve_market(None, None, &market).unwrap();
+        assert!(guard.reserve_market(None, None, &market).is_err());
+        drop(reservation);
+        guard.reserve_market(None, None, &market).unwrap().commit();
+    }
+}
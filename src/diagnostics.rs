@@ -0,0 +1,180 @@
+//! Parse-failure diagnostics
+//!
+//! A message that fails to parse in production is usually a schema change on
+//! the venue's side, and a log line rarely holds enough to reproduce it.
+//! [`DiagnosticsCapture`] keeps the last few raw messages and, on a parse
+//! failure, writes a [`ParseFailureDump`] with the payload, the error, that
+//! context and the active subscriptions to a directory:
+//!
+//! ```text
+//! {dir}/parse-failure-{timestamp_ms}-{n}.json
+//! ```
+//!
+//! Only the newest `max_files` dumps are kept. Enable it on a stream with
+//! [`crate::WebSocketStream::with_diagnostics`].
+
+use crate::errors::{PolyfillError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+const DUMP_PREFIX: &str = "parse-failure-";
+
+/// Everything captured about one parse failure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParseFailureDump {
+    pub captured_at: DateTime<Utc>,
+    pub error: String,
+    /// The payload that failed to parse
+    pub payload: String,
+    /// Raw messages received before it, oldest first
+    pub recent_messages: Vec<String>,
+    /// Active subscriptions, e.g. `MARKET:123`
+    pub subscriptions: Vec<String>,
+}
+
+/// Writes rotating parse-failure dumps with recent message context
+#[derive(Debug, Clone)]
+pub struct DiagnosticsCapture {
+    dir: PathBuf,
+    context_messages: usize,
+    max_files: usize,
+    recent: VecDeque<String>,
+    written: u64,
+}
+
+impl DiagnosticsCapture {
+    /// Dump into `dir`, keeping 20 messages of context and 50 files
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            context_messages: 20,
+            max_files: 50,
+            recent: VecDeque::new(),
+            written: 0,
+        }
+    }
+
+    /// Raw messages kept as context for each dump
+    pub fn with_context_messages(mut self, messages: usize) -> Self {
+        self.context_messages = messages;
+        self
+    }
+
+    /// Dumps kept before the oldest are deleted
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files.max(1);
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Remember a raw message that parsed, as context for later failures
+    pub fn record_message(&mut self, raw: &str) {
+        if self.context_messages == 0 {
+            return;
+        }
+        if self.recent.len() >= self.context_messages {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(raw.to_string());
+    }
+
+    /// Write a dump for `payload` and rotate old ones; returns its path
+    pub fn capture(
+        &mut self,
+        payload: &str,
+        error: &str,
+        subscriptions: Vec<String>,
+    ) -> Result<PathBuf> {
+        let dump = ParseFailureDump {
+            captured_at: Utc::now(),
+            error: error.to_string(),
+            payload: payload.to_string(),
+            recent_messages: self.recent.iter().cloned().collect(),
+            subscriptions,
+        };
+        std::fs::create_dir_all(&self.dir).map_err(|e| io_error(&self.dir, e))?;
+        self.written += 1;
+        let path = self.dir.join(format!(
+            "{DUMP_PREFIX}{}-{}.json",
+            dump.captured_at.timestamp_millis(),
+            self.written
+        ));
+        let body = serde_json::to_vec_pretty(&dump)?;
+        std::fs::write(&path, body).map_err(|e| io_error(&path, e))?;
+        self.rotate()?;
+        Ok(path)
+    }
+
+    /// Dumps in the directory, oldest first
+    pub fn dumps(&self) -> Result<Vec<PathBuf>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(io_error(&self.dir, e)),
+        };
+        let mut dumps: Vec<(u64, u64, PathBuf)> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter_map(|path| {
+                let name = path.file_name()?.to_str()?;
+                let (timestamp, n) = name
+                    .strip_prefix(DUMP_PREFIX)?
+                    .strip_suffix(".json")?
+                    .split_once('-')?;
+                Some((timestamp.parse().ok()?, n.parse().ok()?, path))
+            })
+            .collect();
+        dumps.sort();
+        Ok(dumps.into_iter().map(|(_, _, path)| path).collect())
+    }
+
+    fn rotate(&self) -> Result<()> {
+        let dumps = self.dumps()?;
+        let excess = dumps.len().saturating_sub(self.max_files);
+        for path in &dumps[..excess] {
+            std::fs::remove_file(path).map_err(|e| io_error(path, e))?;
+        }
+        Ok(())
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> PolyfillError {
+    PolyfillError::internal(format!("Diagnostics dump {} failed", path.display()), e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_keeps_context_and_rotates() {
+        let dir = std::env::temp_dir().join(format!("polyfill-diag-{}", uuid::Uuid::new_v4()));
+        let mut capture = DiagnosticsCapture::new(&dir)
+            .with_context_messages(2)
+            .with_max_files(2);
+        for raw in ["a", "b", "c"] {
+            capture.record_message(raw);
+        }
+
+        let first = capture
+            .capture("{bad", "EOF while parsing", vec!["MARKET:1".to_string()])
+            .unwrap();
+        let dump: ParseFailureDump =
+            serde_json::from_slice(&std::fs::read(&first).unwrap()).unwrap();
+        assert_eq!(dump.payload, "{bad");
+        assert_eq!(dump.recent_messages, vec!["b", "c"]);
+        assert_eq!(dump.subscriptions, vec!["MARKET:1"]);
+
+        capture.capture("{bad2", "x", Vec::new()).unwrap();
+        let last = capture.capture("{bad3", "x", Vec::new()).unwrap();
+        let dumps = capture.dumps().unwrap();
+        assert_eq!(dumps.len(), 2);
+        assert!(!dumps.contains(&first));
+        assert_eq!(dumps.last(), Some(&last));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
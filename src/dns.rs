@@ -0,0 +1,321 @@
+//! DNS pre-resolution and pinning
+//!
+//! Resolvers are often the slowest link while the exchange is flapping, so
+//! [`DnsCache`] resolves the CLOB and WebSocket hosts up front, refreshes them
+//! in the background and answers later lookups from memory. A failed refresh
+//! keeps the last good addresses, so reconnects never wait on DNS.
+//!
+//! Pass the cache as [`crate::ClientConfig::dns_cache`] to route REST
+//! requests through it (the user channel picks it up too), or give it to
+//! [`crate::TungsteniteConnector::with_dns_cache`] for market streams.
+
+use crate::errors::{PolyfillError, Result};
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tracing::{debug, warn};
+
+/// Hosts behind [`crate::DEFAULT_BASE_URL`] and [`crate::DEFAULT_WS_BASE_URL`]
+pub const POLYMARKET_HOSTS: [&str; 2] = [
+    "clob.polymarket.com",
+    "ws-subscriptions-clob.polymarket.com",
+];
+
+/// Head start given to each address before the next one is dialed (RFC 8305)
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone)]
+struct CachedHost {
+    addrs: Arc<[IpAddr]>,
+    resolved_at: Instant,
+    pinned: bool,
+}
+
+/// Shared cache of resolved host addresses.
+///
+/// Clones share the same entries.
+#[derive(Debug, Clone)]
+pub struct DnsCache {
+    hosts: Arc<RwLock<HashMap<String, CachedHost>>>,
+    refresh_interval: Duration,
+}
+
+impl Default for DnsCache {
+    fn default() -> Self {
+        Self {
+            hosts: Arc::default(),
+            refresh_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl DnsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How often [`DnsCache::spawn_refresh`] re-resolves cached hosts
+    pub fn with_refresh_interval(mut self, interval: Duration) -> Self {
+        self.refresh_interval = interval;
+        self
+    }
+
+    /// Resolve `hosts` now, failing if any of them does not resolve
+    pub async fn prime<I, S>(&self, hosts: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for host in hosts {
+            let host = host.as_ref().to_ascii_lowercase();
+            let addrs = lookup(&host).await?;
+            self.store(host, addrs, false);
+        }
+        Ok(())
+    }
+
+    /// Always answer `host` with `addrs`; refreshes leave it alone
+    pub fn pin(&self, host: &str, addrs: &[IpAddr]) {
+        self.store(host.to_ascii_lowercase(), addrs.into(), true);
+    }
+
+    /// Cached addresses for `host`, without touching DNS
+    pub fn cached(&self, host: &str) -> Option<Arc<[IpAddr]>> {
+        self.hosts
+            .read()
+            .get(&host.to_ascii_lowercase())
+            .map(|entry| entry.addrs.clone())
+    }
+
+    /// Cached addresses for `host`, resolving and caching it on a miss
+    pub async fn resolve(&self, host: &str) -> Result<Arc<[IpAddr]>> {
+        if let Some(addrs) = self.cached(host) {
+            return Ok(addrs);
+        }
+        let host = host.to_ascii_lowercase();
+        let addrs = lookup(&host).await?;
+        self.store(host, addrs.clone(), false);
+        Ok(addrs)
+    }
+
+    /// Re-resolve every cached host that is not pinned.
+    ///
+    /// A host that fails to resolve keeps its previous addresses.
+    pub async fn refresh(&self) {
+        let stale: Vec<String> = self
+            .hosts
+            .read()
+            .iter()
+            .filter(|(_, entry)| !entry.pinned)
+            .map(|(host, _)| host.clone())
+            .collect();
+        for host in stale {
+            match lookup(&host).await {
+                Ok(addrs) => self.store(host, addrs, false),
+                Err(e) => warn!("Keeping cached addresses for {}: {}", host, e),
+            }
+        }
+    }
+
+    /// Refresh on a background task every refresh interval.
+    ///
+    /// Abort the returned handle to stop.
+    pub fn spawn_refresh(&self) -> tokio::task::JoinHandle<()> {
+        let cache = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(cache.refresh_interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                cache.refresh().await;
+            }
+        })
+    }
+
+    /// Age of the cached addresses for `host`
+    pub fn age(&self, host: &str) -> Option<Duration> {
+        self.hosts
+            .read()
+            .get(&host.to_ascii_lowercase())
+            .map(|entry| entry.resolved_at.elapsed())
+    }
+
+    fn store(&self, host: String, addrs: Arc<[IpAddr]>, pinned: bool) {
+        let mut hosts = self.hosts.write();
+        if !pinned && hosts.get(&host).is_some_and(|entry| entry.pinned) {
+            return;
+        }
+        debug!("Cached {} addresses for {}", addrs.len(), host);
+        hosts.insert(
+            host,
+            CachedHost {
+                addrs,
+                resolved_at: Instant::now(),
+                pinned,
+            },
+        );
+    }
+}
+
+impl reqwest::dns::Resolve for DnsCache {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let cache = self.clone();
+        Box::pin(async move {
+            let addrs = DnsCache::resolve(&cache, name.as_str()).await?;
+            let addrs: reqwest::dns::Addrs = Box::new(
+                addrs
+                    .iter()
+                    .map(|ip| SocketAddr::new(*ip, 0))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+async fn lookup(host: &str) -> Result<Arc<[IpAddr]>> {
+    if let Ok(ip) = host
+        .trim_matches(|c| c == '[' || c == ']')
+        .parse::<IpAddr>()
+    {
+        return Ok(Arc::from([ip]));
+    }
+    let addrs: Vec<IpAddr> = tokio::net::lookup_host((host, 0))
+        .await
+        .map_err(|e| PolyfillError::network(format!("Failed to resolve {host}"), e))?
+        .map(|addr| addr.ip())
+        .collect();
+    if addrs.is_empty() {
+        return Err(PolyfillError::network(
+            format!("Failed to resolve {host}"),
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses"),
+        ));
+    }
+    Ok(addrs.into())
+}
+
+/// Connect to the first of `addrs` that answers, happy-eyeballs style.
+///
+/// Address families are interleaved and each attempt gets a short head start
+/// before the next one is dialed, so a dead address or a broken IPv6 path
+/// costs a few hundred milliseconds rather than a full connect timeout.
+pub async fn connect_happy_eyeballs(addrs: &[IpAddr], port: u16) -> std::io::Result<TcpStream> {
+    let addrs: Vec<SocketAddr> = addrs.iter().map(|ip| SocketAddr::new(*ip, port)).collect();
+    connect_happy_eyeballs_with(&addrs, TcpStream::connect).await
+}
+
+/// [`connect_happy_eyeballs`] with a custom dial, e.g. to tune the socket
+pub(crate) async fn connect_happy_eyeballs_with<F, Fut>(
+    addrs: &[SocketAddr],
+    dial: F,
+) -> std::io::Result<TcpStream>
+where
+    F: Fn(SocketAddr) -> Fut,
+    Fut: std::future::Future<Output = std::io::Result<TcpStream>>,
+{
+    let mut pending = interleave_families(addrs).into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut last_error = None;
+
+    loop {
+        if attempts.is_empty() {
+            match pending.next() {
+                Some(addr) => attempts.push(dial(addr)),
+                None => {
+                    return Err(last_error.unwrap_or_else(|| {
+                        std::io::Error::new(std::io::ErrorKind::NotFound, "no addresses to dial")
+                    }))
+                },
+            }
+        }
+
+        tokio::select! {
+            Some(result) = attempts.next() => match result {
+                Ok(stream) => return Ok(stream),
+                Err(e) => {
+                    last_error = Some(e);
+                    if let Some(addr) = pending.next() {
+                        attempts.push(dial(addr));
+                    }
+                },
+            },
+            _ = tokio::time::sleep(CONNECTION_ATTEMPT_DELAY), if pending.len() > 0 => {
+                if let Some(addr) = pending.next() {
+                    attempts.push(dial(addr));
+                }
+            },
+        }
+    }
+}
+
+/// Alternate address families, starting with the family of the first address
+fn interleave_families(addrs: &[SocketAddr]) -> Vec<SocketAddr> {
+    let Some(first) = addrs.first() else {
+        return Vec::new();
+    };
+    let (mut preferred, mut other): (Vec<SocketAddr>, Vec<SocketAddr>) = addrs
+        .iter()
+        .partition(|addr| addr.is_ipv6() == first.is_ipv6());
+    preferred.reverse();
+    other.reverse();
+
+    let mut ordered = Vec::with_capacity(addrs.len());
+    while let Some(addr) = preferred.pop() {
+        ordered.push(addr);
+        if let Some(addr) = other.pop() {
+            ordered.push(addr);
+        }
+    }
+    other.reverse();
+    ordered.extend(other);
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pinned_hosts_skip_dns() {
+        let cache = DnsCache::new();
+        let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+        cache.pin("Exchange.invalid", &[loopback]);
+
+        // `.invalid` never resolves; the pin answers and survives refreshes
+        assert_eq!(
+            &*cache.resolve("exchange.invalid").await.unwrap(),
+            &[loopback]
+        );
+        cache.refresh().await;
+        assert_eq!(&*cache.cached("exchange.invalid").unwrap(), &[loopback]);
+
+        cache.prime(["127.0.0.2"]).await.unwrap();
+        assert!(cache.age("127.0.0.2").is_some());
+        assert!(cache.prime(["exchange2.invalid"]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_happy_eyeballs_skips_dead_address() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // Nothing listens on 127.0.0.2; the attempt fails and the next wins
+        let dead: IpAddr = "127.0.0.2".parse().unwrap();
+        let live: IpAddr = "127.0.0.1".parse().unwrap();
+        let v6: IpAddr = "::1".parse().unwrap();
+        let order = interleave_families(&[dead, live, v6].map(|ip| SocketAddr::new(ip, port)));
+        let order: Vec<IpAddr> = order.iter().map(SocketAddr::ip).collect();
+        assert_eq!(order, vec![dead, v6, live]);
+
+        let addrs = [dead, live];
+        let stream = connect_happy_eyeballs(&addrs, port).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap().port(), port);
+    }
+}
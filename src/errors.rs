@@ -18,12 +18,16 @@ pub enum PolyfillError {
         source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
 
-    /// API errors from Polymarket
-    #[error("API error ({status}): {message}")]
+    /// API errors from Polymarket. Build one with [`PolyfillError::api`]; more
+    /// fields may be added.
+    #[error("API error ({status}): {message}{}", ApiContextDisplay(.context))]
+    #[non_exhaustive]
     Api {
         status: u16,
         message: String,
         error_code: Option<String>,
+        /// Request details, when the error came from an HTTP response
+        context: Option<Box<ApiErrorContext>>,
     },
 
     /// Authentication/authorization errors
@@ -66,6 +70,10 @@ pub enum PolyfillError {
         operation: String,
     },
 
+    /// Operation aborted through a cancellation token
+    #[error("Cancelled: {operation}")]
+    Cancelled { operation: String },
+
     /// Rate limiting errors
     #[error("Rate limit exceeded: {message}")]
     RateLimit {
@@ -206,6 +214,7 @@ impl PolyfillError {
             PolyfillError::Config { .. } => "config",
             PolyfillError::Parse { .. } => "parse",
             PolyfillError::Timeout { .. } => "timeout",
+            PolyfillError::Cancelled { .. } => "cancelled",
             PolyfillError::RateLimit { .. } => "rate_limit",
             PolyfillError::Stream { .. } => "stream",
             PolyfillError::Validation { .. } => "validation",
@@ -231,6 +240,36 @@ impl PolyfillError {
             status,
             message: message.into(),
             error_code: None,
+            context: None,
+        }
+    }
+
+    /// API error for a failed `response`, recording the request method,
+    /// endpoint, `x-request-id` header and the start of the body
+    pub async fn api_from_response(
+        method: &str,
+        response: reqwest::Response,
+        message: impl Into<String>,
+    ) -> Self {
+        let status = response.status().as_u16();
+        let context = ApiErrorContext::from_response(method, &response);
+        let body = response.text().await.unwrap_or_default();
+        Self::api(status, message).with_api_context(context.with_body(&body))
+    }
+
+    /// Attach request details to an [`PolyfillError::Api`]; other errors are
+    /// returned unchanged
+    pub fn with_api_context(mut self, api_context: ApiErrorContext) -> Self {
+        if let Self::Api { context, .. } = &mut self {
+            *context = Some(Box::new(api_context));
+        }
+        self
+    }
+
+    pub fn api_context(&self) -> Option<&ApiErrorContext> {
+        match self {
+            Self::Api { context, .. } => context.as_deref(),
+            _ => None,
         }
     }
 
@@ -285,6 +324,12 @@ impl PolyfillError {
         }
     }
 
+    pub fn cancelled(operation: impl Into<String>) -> Self {
+        Self::Cancelled {
+            operation: operation.into(),
+        }
+    }
+
     pub fn rate_limit(message: impl Into<String>) -> Self {
         Self::RateLimit {
             message: message.into(),
@@ -371,6 +416,27 @@ impl From<tokio_tungstenite::tungstenite::Error> for PolyfillError {
     }
 }
 
+#[cfg(feature = "state")]
+impl From<rusqlite::Error> for PolyfillError {
+    fn from(err: rusqlite::Error) -> Self {
+        PolyfillError::internal("State store error", err)
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow_schema::ArrowError> for PolyfillError {
+    fn from(err: arrow_schema::ArrowError) -> Self {
+        PolyfillError::internal("Arrow error", err)
+    }
+}
+
+#[cfg(feature = "capture")]
+impl From<parquet::errors::ParquetError> for PolyfillError {
+    fn from(err: parquet::errors::ParquetError) -> Self {
+        PolyfillError::internal("Parquet error", err)
+    }
+}
+
 // Manual Clone implementation since Box<dyn Error> doesn't implement Clone
 impl Clone for PolyfillError {
     fn clone(&self) -> Self {
@@ -383,10 +449,12 @@ impl Clone for PolyfillError {
                 status,
                 message,
                 error_code,
+                context,
             } => PolyfillError::Api {
                 status: *status,
                 message: message.clone(),
                 error_code: error_code.clone(),
+                context: context.clone(),
             },
             PolyfillError::Auth { message, kind } => PolyfillError::Auth {
                 message: message.clone(),
@@ -414,6 +482,9 @@ impl Clone for PolyfillError {
                 duration: *duration,
                 operation: operation.clone(),
             },
+            PolyfillError::Cancelled { operation } => PolyfillError::Cancelled {
+                operation: operation.clone(),
+            },
             PolyfillError::RateLimit {
                 message,
                 retry_after,
@@ -437,5 +508,145 @@ impl Clone for PolyfillError {
     }
 }
 
+/// Header Polymarket uses to identify a request in its logs
+const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest response body kept on an API error
+const MAX_ERROR_BODY_LEN: usize = 512;
+
+/// Request details attached to [`PolyfillError::Api`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ApiErrorContext {
+    pub method: String,
+    /// Request path, without host or query
+    pub endpoint: String,
+    pub request_id: Option<String>,
+    /// Response body, truncated to 512 bytes
+    pub body: Option<String>,
+}
+
+impl ApiErrorContext {
+    pub fn new(method: impl Into<String>, endpoint: impl Into<String>) -> Self {
+        Self {
+            method: method.into(),
+            endpoint: endpoint.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Method, path and request id of `response`; add the body separately
+    pub fn from_response(method: &str, response: &reqwest::Response) -> Self {
+        Self::new(method, response.url().path()).with_request_id(
+            response
+                .headers()
+                .get(REQUEST_ID_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+        )
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// Keep the start of `body`; empty bodies are dropped
+    pub fn with_body(mut self, body: &str) -> Self {
+        let body = body.trim();
+        self.body = (!body.is_empty()).then(|| {
+            if body.len() <= MAX_ERROR_BODY_LEN {
+                return body.to_string();
+            }
+            let mut end = MAX_ERROR_BODY_LEN;
+            while !body.is_char_boundary(end) {
+                end -= 1;
+            }
+            format!("{}...", &body[..end])
+        });
+        self
+    }
+}
+
+impl std::fmt::Display for ApiErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.endpoint)?;
+        if let Some(request_id) = &self.request_id {
+            write!(f, ", request id {request_id}")?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, ", body: {body}")?;
+        }
+        Ok(())
+    }
+}
+
+struct ApiContextDisplay<'a>(&'a Option<Box<ApiErrorContext>>);
+
+impl std::fmt::Display for ApiContextDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(context) => write!(f, " ({context})"),
+            None => Ok(()),
+        }
+    }
+}
+
 /// Result type alias for convenience
 pub type Result<T> = std::result::Result<T, PolyfillError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_error_body_truncates_on_char_boundary() {
+        // 511 ASCII bytes, then a 3-byte char straddling the limit
+        let body = format!("{}€tail", "a".repeat(511));
+        let context = ApiErrorContext::new("GET", "/book").with_body(&body);
+        assert_eq!(context.body, Some(format!("{}...", "a".repeat(511))));
+
+        let short = ApiErrorContext::new("GET", "/book").with_body("  not found \n");
+        assert_eq!(short.body.as_deref(), Some("not found"));
+        assert_eq!(
+            ApiErrorContext::new("GET", "/book").with_body(" ").body,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_error_from_response_records_request() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/book")
+            .match_query(mockito::Matcher::Any)
+            .with_status(400)
+            .with_header("x-request-id", "req-42")
+            .with_body(r#"{"error":"invalid token id"}"#)
+            .create_async()
+            .await;
+
+        let response = reqwest::get(format!("{}/book?token_id=1", server.url()))
+            .await
+            .unwrap();
+        let err =
+            PolyfillError::api_from_response("GET", response, "Failed to get order book").await;
+        assert!(matches!(err, PolyfillError::Api { status: 400, .. }));
+        let context = err.api_context().unwrap();
+        assert_eq!(
+            (context.method.as_str(), context.endpoint.as_str()),
+            ("GET", "/book")
+        );
+        assert_eq!(context.request_id.as_deref(), Some("req-42"));
+        assert_eq!(
+            err.to_string(),
+            r#"API error (400): Failed to get order book (GET /book, request id req-42, body: {"error":"invalid token id"})"#
+        );
+
+        // Without context the message is unchanged
+        assert_eq!(
+            PolyfillError::api(503, "unavailable").to_string(),
+            "API error (503): unavailable"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! GTD order expiration sweeping
+//!
+//! The exchange expires GTD orders on its own, but nothing on the user
+//! channel is guaranteed to say so, which leaves expired orders open in an
+//! [`OrderTracker`]. [`ExpirySweeper`] drops tracked orders once their
+//! expiration has passed, checks each against the exchange, and cancels any
+//! that are somehow still live so no phantom order outlives its expiry.
+//!
+//! It can also keep rolling quotes: GTD orders that are posted again with a
+//! fresh expiration whenever the previous one leaves the book, whether it
+//! expired, filled or was cancelled.
+//!
+//! The exchange applies a one-minute security threshold to GTD expirations:
+//! an order stops being live [`GTD_SECURITY_THRESHOLD`] before its
+//! `expiration`, and the sweeper treats it as expired from then on.
+
+use crate::api::ClobApi;
+use crate::errors::{OrderErrorKind, PolyfillError, Result};
+use crate::reconcile::{OrderTracker, TrackedOrder};
+use crate::sim::SharedClock;
+use crate::types::{OrderArgs, OrderType, PostOrderOptions};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// How long before its `expiration` the exchange stops a GTD order
+pub const GTD_SECURITY_THRESHOLD: Duration = Duration::from_secs(60);
+
+/// A GTD quote kept on the book by reposting it as it expires
+#[derive(Debug, Clone, PartialEq)]
+pub struct RollingQuote {
+    /// The order to post; its `expiration` is set on every post
+    pub args: OrderArgs,
+    /// How long each posted order stays live
+    pub lifetime: Duration,
+}
+
+impl RollingQuote {
+    pub fn new(args: OrderArgs, lifetime: Duration) -> Self {
+        Self { args, lifetime }
+    }
+}
+
+/// What a sweep did
+#[derive(Debug, Clone)]
+pub enum ExpiryEvent {
+    /// Expired and dropped from the tracker.
+    ///
+    /// `status` is the exchange's final status when it could be fetched.
+    Expired {
+        order: TrackedOrder,
+        status: Option<String>,
+    },
+    /// Still live on the exchange after its expiry; cancelled and dropped
+    CancelledLive { order: TrackedOrder },
+    /// A rolling quote was posted with a new expiration
+    QuotePosted {
+        key: String,
+        order_id: String,
+        expiration: u64,
+    },
+}
+
+#[derive(Debug)]
+struct QuoteSlot {
+    quote: RollingQuote,
+    /// The quote's live order, `None` until it is (re)posted
+    order_id: Option<String>,
+}
+
+/// Sweeps expired GTD orders out of an [`OrderTracker`]
+#[derive(Debug)]
+pub struct ExpirySweeper<A> {
+    api: Arc<A>,
+    tracker: Arc<OrderTracker>,
+    clock: SharedClock,
+    grace: Duration,
+    quotes: Mutex<HashMap<String, QuoteSlot>>,
+}
+
+impl<A: ClobApi> ExpirySweeper<A> {
+    pub fn new(api: Arc<A>, tracker: Arc<OrderTracker>) -> Self {
+        Self {
+            api,
+            tracker,
+            clock: crate::sim::system_clock(),
+            grace: Duration::from_secs(5),
+            quotes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Time allowed for the exchange to expire an order before it is
+    /// checked
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    pub fn tracker(&self) -> &Arc<OrderTracker> {
+        &self.tracker
+    }
+
+    /// Keep `quote` posted under `key`; it is posted on the next sweep and
+    /// again whenever its order is no longer tracked. Replacing a quote
+    /// leaves its current order to expire.
+    pub fn add_rolling_quote(&self, key: impl Into<String>, quote: RollingQuote) {
+        self.quotes.lock().insert(
+            key.into(),
+            QuoteSlot {
+                quote,
+                order_id: None,
+            },
+        );
+    }
+
+    /// Stop reposting a quote; its current order is left to expire
+    pub fn remove_rolling_quote(&self, key: &str) -> Option<RollingQuote> {
+        self.quotes.lock().remove(key).map(|slot| slot.quote)
+    }
+
+    /// Sweep expired orders once and repost rolling quotes that need it
+    pub async fn sweep_once(&self) -> Result<Vec<ExpiryEvent>> {
+        let now = self.clock.now_millis() / 1000;
+        let cutoff = (now + GTD_SECURITY_THRESHOLD.as_secs()).saturating_sub(self.grace.as_secs());
+        let mut events = Vec::new();
+
+        for order in self.tracker.expired_by(cutoff) {
+            self.tracker.remove(&order.id);
+            let status = match self.api.get_order(&order.id).await {
+                Ok(exchange) => Some(exchange.status),
+                Err(e) => {
+                    debug!("Could not fetch final status of {}: {}", order.id, e);
+                    None
+                },
+            };
+            let live = status
+                .as_deref()
+                .is_some_and(|status| status.eq_ignore_ascii_case("LIVE"));
+            if !live {
+                events.push(ExpiryEvent::Expired { order, status });
+                continue;
+            }
+
+            match self.api.cancel(&order.id).await {
+                Ok(response) if response.canceled.contains(&order.id) => {
+                    warn!("Order {} was still live after expiring", order.id);
+                    events.push(ExpiryEvent::CancelledLive { order });
+                },
+                Ok(response) => {
+                    let reason = response.not_canceled.get(&order.id);
+                    warn!("Could not cancel expired order {}: {:?}", order.id, reason);
+                    self.tracker.insert(order);
+                },
+                Err(e) => {
+                    warn!("Could not cancel expired order {}: {}", order.id, e);
+                    self.tracker.insert(order);
+                },
+            }
+        }
+
+        let due: Vec<(String, RollingQuote)> = {
+            let mut quotes = self.quotes.lock();
+            let mut due = Vec::new();
+            for (key, slot) in quotes.iter_mut() {
+                let expired = slot
+                    .order_id
+                    .as_ref()
+                    .is_some_and(|id| self.tracker.get(id).is_none());
+                if expired {
+                    slot.order_id = None;
+                }
+                if slot.order_id.is_none() {
+                    due.push((key.clone(), slot.quote.clone()));
+                }
+            }
+            due
+        };
+        for (key, quote) in due {
+            match self.post_quote(&quote, now).await {
+                Ok((order_id, expiration)) => {
+                    if let Some(slot) = self.quotes.lock().get_mut(&key) {
+                        slot.order_id = Some(order_id.clone());
+                    }
+                    events.push(ExpiryEvent::QuotePosted {
+                        key,
+                        order_id,
+                        expiration,
+                    });
+                },
+                Err(e) => warn!("Could not post rolling quote {}: {}", key, e),
+            }
+        }
+        Ok(events)
+    }
+
+    /// Sweep every `interval` until the receiver is dropped.
+    ///
+    /// Failed sweeps are logged and retried at the next tick.
+    pub async fn run(self, interval: Duration, events: mpsc::UnboundedSender<ExpiryEvent>) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            ticker.tick().await;
+            if events.is_closed() {
+                return;
+            }
+            match self.sweep_once().await {
+                Ok(found) => {
+                    for event in found {
+                        if events.send(event).is_err() {
+                            return;
+                        }
+                    }
+                },
+                Err(e) => warn!("Order expiry sweep failed: {}", e),
+            }
+        }
+    }
+
+    /// Post a quote expiring `lifetime` from `now` and track it
+    async fn post_quote(&self, quote: &RollingQuote, now: u64) -> Result<(String, u64)> {
+        let expiration = now + GTD_SECURITY_THRESHOLD.as_secs() + quote.lifetime.as_secs();
+        let mut args = quote.args.clone();
+        args.expiration = Some(expiration);
+        let options = PostOrderOptions {
+            order_type: OrderType::GTD,
+            ..PostOrderOptions::default()
+        };
+        let response = self
+            .api
+            .create_and_post_order(&args, None, Some(&options))
+            .await?;
+        if !response.success || response.order_id.is_empty() {
+            return Err(PolyfillError::order(
+                format!("Rolling quote rejected: {}", response.error_msg),
+                OrderErrorKind::ExecutionFailed,
+            ));
+        }
+
+        match self.api.get_order(&response.order_id).await {
+            Ok(order) => self.tracker.track(&order),
+            Err(e) => {
+                debug!(
+                    "Tracking {} without exchange details: {}",
+                    response.order_id, e
+                );
+                self.tracker.insert(TrackedOrder {
+                    id: response.order_id.clone(),
+                    market: String::new(),
+                    asset_id: args.token_id.to_string(),
+                    side: args.side,
+                    price: args.price,
+                    original_size: args.size,
+                    size_matched: Decimal::ZERO,
+                    expiration: Some(expiration),
+                    maker_amount: None,
+                });
+            },
+        }
+        Ok((response.order_id, expiration))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FakeClob, FakeToken};
+    use crate::sim::VirtualClock;
+    use crate::types::Side;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_sweep_expires_cancels_and_rolls() {
+        let clock = VirtualClock::new(1_000_000_000);
+        let fake = Arc::new(FakeClob::new().with_clock(clock.shared()));
+        fake.set_token("1", FakeToken::new("0xabc"));
+        let tracker = Arc::new(OrderTracker::new());
+        let sweeper = ExpirySweeper::new(fake.clone(), tracker.clone()).with_clock(clock.shared());
+        let start = 1_000_000;
+
+        let bid = OrderArgs::new("1".parse().unwrap(), dec!(0.4), dec!(10), Side::BUY);
+        sweeper.add_rolling_quote("bid", RollingQuote::new(bid, Duration::from_secs(30)));
+        let events = sweeper.sweep_once().await.unwrap();
+        let [ExpiryEvent::QuotePosted {
+            order_id: quote_id,
+            expiration,
+            ..
+        }] = events.as_slice()
+        else {
+            panic!("expected the quote to be posted, got {events:?}");
+        };
+        assert_eq!(*expiration, start + 90);
+        assert_eq!(tracker.get(quote_id).unwrap().expiration, Some(start + 90));
+
+        // A one-off GTD order the exchange expires on its own
+        let mut ask = OrderArgs::new("1".parse().unwrap(), dec!(0.6), dec!(10), Side::SELL);
+        ask.expiration = Some(start + 70);
+        let posted = fake.create_and_post_order(&ask, None, None).await.unwrap();
+        tracker.track(&fake.get_order(&posted.order_id).await.unwrap());
+
+        clock.advance(Duration::from_secs(10));
+        assert!(sweeper.sweep_once().await.unwrap().is_empty());
+
+        fake.cancel(&posted.order_id).await.unwrap();
+        clock.advance(Duration::from_secs(6));
+        let events = sweeper.sweep_once().await.unwrap();
+        assert!(matches!(
+            events.as_slice(),
+            [ExpiryEvent::Expired { order, status: None }] if order.id == posted.order_id
+        ));
+
+        // The quote outlives its expiry on the exchange: cancelled and rolled
+        clock.advance(Duration::from_secs(20));
+        let events = sweeper.sweep_once().await.unwrap();
+        assert!(
+            matches!(&events[0], ExpiryEvent::CancelledLive { order } if &order.id == quote_id)
+        );
+        let ExpiryEvent::QuotePosted { expiration, .. } = &events[1] else {
+            panic!("expected the quote to be reposted, got {events:?}");
+        };
+        assert_eq!(*expiration, start + 36 + 90);
+        assert_eq!(fake.open_orders().len(), 1);
+        assert_eq!(tracker.open_orders().len(), 1);
+    }
+}
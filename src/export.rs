@@ -0,0 +1,398 @@
+//! Arrow export for analytics types
+//!
+//! [`ToRecordBatch`] converts live client structs (book snapshots, trade
+//! tapes, candles and fill histories) into Arrow [`RecordBatch`]es that can be
+//! handed to DataFusion, Polars (via its Arrow interop) or written to
+//! Parquet/IPC without a custom serialization layer.
+//!
+//! Prices and sizes are exported as `Float64` and timestamps as UTC
+//! millisecond `Timestamp` columns.
+
+use crate::capture::{Candle, TradeRecord};
+use crate::errors::Result;
+use crate::types::{FillEvent, OrderBook, TradeMessage};
+use arrow_array::{
+    ArrayRef, Float64Array, RecordBatch, StringArray, TimestampMillisecondArray, UInt32Array,
+    UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::sync::Arc;
+
+/// Convert a value into an Arrow record batch
+pub trait ToRecordBatch {
+    /// Schema of the produced batches
+    fn arrow_schema() -> SchemaRef;
+
+    fn to_record_batch(&self) -> Result<RecordBatch>;
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+fn timestamp_field(name: &str, nullable: bool) -> Field {
+    Field::new(
+        name,
+        DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+        nullable,
+    )
+}
+
+fn timestamps(values: impl IntoIterator<Item = Option<i64>>) -> ArrayRef {
+    Arc::new(TimestampMillisecondArray::from_iter(values).with_timezone("UTC"))
+}
+
+fn strings<'a>(values: impl IntoIterator<Item = &'a str>) -> ArrayRef {
+    Arc::new(StringArray::from_iter_values(values))
+}
+
+fn decimals(values: impl IntoIterator<Item = Decimal>) -> ArrayRef {
+    Arc::new(Float64Array::from_iter_values(
+        values.into_iter().map(to_f64),
+    ))
+}
+
+/// One row per level: `side`, `level` (0 = best), `price`, `size`
+impl ToRecordBatch for OrderBook {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("token_id", DataType::Utf8, false),
+            timestamp_field("timestamp", false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("level", DataType::UInt32, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("size", DataType::Float64, false),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        let levels: Vec<(&str, u32, Decimal, Decimal)> = self
+            .bids
+            .iter()
+            .enumerate()
+            .map(|(i, l)| ("BUY", i as u32, l.price, l.size))
+            .chain(
+                self.asks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, l)| ("SELL", i as u32, l.price, l.size)),
+            )
+            .collect();
+        let timestamp = self.timestamp.timestamp_millis();
+
+        let columns = vec![
+            strings(levels.iter().map(|_| self.token_id.as_str())),
+            timestamps(levels.iter().map(|_| Some(timestamp))),
+            strings(levels.iter().map(|l| l.0)),
+            Arc::new(UInt32Array::from_iter_values(levels.iter().map(|l| l.1))) as ArrayRef,
+            decimals(levels.iter().map(|l| l.2)),
+            decimals(levels.iter().map(|l| l.3)),
+        ];
+        Ok(RecordBatch::try_new(Self::arrow_schema(), columns)?)
+    }
+}
+
+/// Trade tape from the user channel
+impl ToRecordBatch for [TradeMessage] {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("trade_id", DataType::Utf8, false),
+            Field::new("market", DataType::Utf8, false),
+            Field::new("token_id", DataType::Utf8, false),
+            timestamp_field("timestamp", true),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("size", DataType::Float64, false),
+            Field::new("status", DataType::Utf8, true),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        let columns = vec![
+            strings(self.iter().map(|t| t.id.as_str())),
+            strings(self.iter().map(|t| t.market.as_str())),
+            strings(self.iter().map(|t| t.asset_id.as_str())),
+            timestamps(self.iter().map(|t| {
+                t.matchtime
+                    .or(t.timestamp)
+                    .or(t.last_update)
+                    .map(|ts| crate::capture::normalize_timestamp_ms(ts) as i64)
+            })),
+            strings(self.iter().map(|t| t.side.as_str())),
+            decimals(self.iter().map(|t| t.price)),
+            decimals(self.iter().map(|t| t.size)),
+            Arc::new(StringArray::from_iter(
+                self.iter().map(|t| t.status.as_deref()),
+            )) as ArrayRef,
+        ];
+        Ok(RecordBatch::try_new(Self::arrow_schema(), columns)?)
+    }
+}
+
+/// Trade tape from the capture pipeline
+impl ToRecordBatch for [TradeRecord] {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("token_id", DataType::Utf8, false),
+            timestamp_field("timestamp", false),
+            Field::new("trade_id", DataType::Utf8, true),
+            Field::new("side", DataType::Utf8, true),
+            Field::new("price", DataType::Float64, false),
+            Field::new("size", DataType::Float64, true),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        let columns = vec![
+            strings(self.iter().map(|t| t.token_id.as_str())),
+            timestamps(self.iter().map(|t| Some(t.timestamp_ms as i64))),
+            Arc::new(StringArray::from_iter(
+                self.iter().map(|t| t.trade_id.as_deref()),
+            )) as ArrayRef,
+            Arc::new(StringArray::from_iter(
+                self.iter().map(|t| t.side.map(|s| s.as_str())),
+            )),
+            decimals(self.iter().map(|t| t.price)),
+            Arc::new(Float64Array::from_iter(
+                self.iter().map(|t| t.size.map(to_f64)),
+            )),
+        ];
+        Ok(RecordBatch::try_new(Self::arrow_schema(), columns)?)
+    }
+}
+
+impl ToRecordBatch for [Candle] {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("token_id", DataType::Utf8, false),
+            timestamp_field("start", false),
+            Field::new("interval_ms", DataType::UInt64, false),
+            Field::new("open", DataType::Float64, false),
+            Field::new("high", DataType::Float64, false),
+            Field::new("low", DataType::Float64, false),
+            Field::new("close", DataType::Float64, false),
+            Field::new("volume", DataType::Float64, false),
+            Field::new("trade_count", DataType::UInt64, false),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        let columns = vec![
+            strings(self.iter().map(|c| c.token_id.as_str())),
+            timestamps(self.iter().map(|c| Some(c.start_ms as i64))),
+            Arc::new(UInt64Array::from_iter_values(
+                self.iter().map(|c| c.interval_ms),
+            )) as ArrayRef,
+            decimals(self.iter().map(|c| c.open)),
+            decimals(self.iter().map(|c| c.high)),
+            decimals(self.iter().map(|c| c.low)),
+            decimals(self.iter().map(|c| c.close)),
+            decimals(self.iter().map(|c| c.volume)),
+            Arc::new(UInt64Array::from_iter_values(
+                self.iter().map(|c| c.trade_count),
+            )),
+        ];
+        Ok(RecordBatch::try_new(Self::arrow_schema(), columns)?)
+    }
+}
+
+/// Fill history, e.g. from [`crate::fill::FillEngine`] results
+impl ToRecordBatch for [FillEvent] {
+    fn arrow_schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("fill_id", DataType::Utf8, false),
+            Field::new("order_id", DataType::Utf8, false),
+            Field::new("token_id", DataType::Utf8, false),
+            timestamp_field("timestamp", false),
+            Field::new("side", DataType::Utf8, false),
+            Field::new("price", DataType::Float64, false),
+            Field::new("size", DataType::Float64, false),
+            Field::new("fee", DataType::Float64, false),
+            Field::new("maker_address", DataType::Utf8, false),
+            Field::new("taker_address", DataType::Utf8, false),
+        ]))
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        let makers: Vec<String> = self.iter().map(|f| f.maker_address.to_string()).collect();
+        let takers: Vec<String> = self.iter().map(|f| f.taker_address.to_string()).collect();
+        let columns = vec![
+            strings(self.iter().map(|f| f.id.as_str())),
+            strings(self.iter().map(|f| f.order_id.as_str())),
+            strings(self.iter().map(|f| f.token_id.as_str())),
+            timestamps(self.iter().map(|f| Some(f.timestamp.timestamp_millis()))),
+            strings(self.iter().map(|f| f.side.as_str())),
+            decimals(self.iter().map(|f| f.price)),
+            decimals(self.iter().map(|f| f.size)),
+            decimals(self.iter().map(|f| f.fee)),
+            strings(makers.iter().map(String::as_str)),
+            strings(takers.iter().map(String::as_str)),
+        ];
+        Ok(RecordBatch::try_new(Self::arrow_schema(), columns)?)
+    }
+}
+
+impl ToRecordBatch for Vec<TradeMessage> {
+    fn arrow_schema() -> SchemaRef {
+        <[TradeMessage]>::arrow_schema()
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        self.as_slice().to_record_batch()
+    }
+}
+
+impl ToRecordBatch for Vec<TradeRecord> {
+    fn arrow_schema() -> SchemaRef {
+        <[TradeRecord]>::arrow_schema()
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        self.as_slice().to_record_batch()
+    }
+}
+
+impl ToRecordBatch for Vec<Candle> {
+    fn arrow_schema() -> SchemaRef {
+        <[Candle]>::arrow_schema()
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        self.as_slice().to_record_batch()
+    }
+}
+
+impl ToRecordBatch for Vec<FillEvent> {
+    fn arrow_schema() -> SchemaRef {
+        <[FillEvent]>::arrow_schema()
+    }
+
+    fn to_record_batch(&self) -> Result<RecordBatch> {
+        self.as_slice().to_record_batch()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BookLevel, Side};
+    use arrow_array::Array;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_order_book_batch_has_one_row_per_level() {
+        let book = OrderBook {
+            token_id: "1".to_string(),
+            timestamp: chrono::Utc::now(),
+            bids: vec![
+                BookLevel {
+                    price: dec!(0.49),
+                    size: dec!(10),
+                },
+                BookLevel {
+                    price: dec!(0.48),
+                    size: dec!(5),
+                },
+            ],
+            asks: vec![BookLevel {
+                price: dec!(0.51),
+                size: dec!(7),
+            }],
+            sequence: 1,
+            last_delta_sequence: 1,
+            last_snapshot_timestamp_ms: 0,
+        };
+
+        let batch = book.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 3);
+        assert_eq!(batch.schema(), OrderBook::arrow_schema());
+
+        let level = batch
+            .column_by_name("level")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<UInt32Array>()
+            .unwrap();
+        assert_eq!(level.values(), &[0, 1, 0]);
+        let price = batch
+            .column_by_name("price")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(price.value(2), 0.51);
+    }
+
+    #[test]
+    fn test_trade_tapes_and_candles_export() {
+        let trades = vec![
+            TradeRecord {
+                token_id: "1".to_string(),
+                timestamp_ms: 1_000,
+                trade_id: Some("t1".to_string()),
+                side: Some(Side::BUY),
+                price: dec!(0.5),
+                size: Some(dec!(2)),
+            },
+            TradeRecord {
+                token_id: "1".to_string(),
+                timestamp_ms: 2_000,
+                trade_id: None,
+                side: None,
+                price: dec!(0.6),
+                size: None,
+            },
+        ];
+
+        let batch = trades.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        assert!(batch.column_by_name("size").unwrap().is_null(1));
+
+        let candles = Candle::from_trades(&trades, std::time::Duration::from_secs(60));
+        let batch = candles.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        let close = batch
+            .column_by_name("close")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(close.value(0), 0.6);
+
+        let messages: Vec<TradeMessage> = vec![serde_json::from_str(
+            r#"{"event_type":"trade","id":"t1","market":"0xabc","asset_id":"1","side":"BUY","size":"10","price":"0.5","matchtime":"1700000000"}"#,
+        )
+        .unwrap()];
+        let batch = messages.to_record_batch().unwrap();
+        let ts = batch
+            .column_by_name("timestamp")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<TimestampMillisecondArray>()
+            .unwrap();
+        assert_eq!(ts.value(0), 1_700_000_000_000);
+    }
+
+    #[test]
+    fn test_fill_history_export() {
+        let fills = vec![FillEvent {
+            id: "f1".to_string(),
+            order_id: "o1".to_string(),
+            token_id: "1".to_string(),
+            side: Side::SELL,
+            price: dec!(0.4),
+            size: dec!(3),
+            timestamp: chrono::Utc::now(),
+            maker_address: alloy_primitives::Address::ZERO,
+            taker_address: alloy_primitives::Address::ZERO,
+            fee: dec!(0.01),
+        }];
+
+        let batch = fills.to_record_batch().unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.num_columns(), 10);
+        assert!(<[FillEvent]>::arrow_schema().field_with_name("fee").is_ok());
+    }
+}
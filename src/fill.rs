@@ -4,14 +4,26 @@
 //! bookkeeping. It is not a hot-path matching or execution engine: market-order
 //! simulation materializes book levels as `Decimal` values, creates UUID-backed
 //! fill IDs, clones order identifiers, and stores owned fill history.
+//!
+//! Timestamps come from an injectable [`crate::sim::Clock`] and fill IDs from a seedable
+//! [`SimRng`], so runs with a [`crate::sim::VirtualClock`] and a fixed seed are
+//! fully reproducible.
+//!
+//! [`ShadowFillEngine`] runs the same bookkeeping continuously against live
+//! books, reporting what configured hypothetical orders would have filled at
+//! and the resulting shadow PnL without placing any orders.
 
 use crate::errors::{PolyfillError, Result};
+use crate::intern::{TokenKey, TokenMap};
+use crate::sim::{SharedClock, SimRng};
 use crate::types::*;
 use crate::utils::math;
 use alloy_primitives::Address;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
 /// Fill execution result
@@ -55,6 +67,10 @@ pub struct FillEngine {
     fee_rate_bps: u32,
     /// Track fills by order ID
     fills: HashMap<String, Vec<FillEvent>>,
+    /// Time source for fill timestamps
+    clock: SharedClock,
+    /// Randomness source for fill IDs
+    rng: SimRng,
 }
 
 impl FillEngine {
@@ -65,9 +81,29 @@ impl FillEngine {
             max_slippage_pct,
             fee_rate_bps,
             fills: HashMap::new(),
+            clock: crate::sim::system_clock(),
+            rng: SimRng::from_entropy(),
         }
     }
 
+    /// Use `clock` for fill timestamps
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Draw fill IDs from a deterministic RNG seeded with `seed`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = SimRng::from_seed(seed);
+        self
+    }
+
+    /// Draw fill IDs from `rng`
+    pub fn with_rng(mut self, rng: SimRng) -> Self {
+        self.rng = rng;
+        self
+    }
+
     /// Simulate executing a market order against an order book.
     ///
     /// This method allocates and converts internal book levels back to `Decimal`
@@ -79,7 +115,7 @@ impl FillEngine {
         order: &MarketOrderRequest,
         book: &crate::book::OrderBook,
     ) -> Result<FillResult> {
-        let start_time = Utc::now();
+        let start_time = self.clock.now();
 
         // Validate order
         self.validate_market_order(order)?;
@@ -124,7 +160,7 @@ impl FillEngine {
             let fee = self.calculate_fee(fill_cost);
 
             let fill = FillEvent {
-                id: uuid::Uuid::new_v4().to_string(),
+                id: self.rng.next_uuid().to_string(),
                 order_id: order
                     .client_id
                     .clone()
@@ -133,7 +169,7 @@ impl FillEngine {
                 side: order.side,
                 price: level.price,
                 size: fill_size,
-                timestamp: Utc::now(),
+                timestamp: self.clock.now(),
                 maker_address: Address::ZERO, // TODO: Get from level
                 taker_address: Address::ZERO, // TODO: Get from order
                 fee,
@@ -222,7 +258,7 @@ impl FillEngine {
         order: &OrderRequest,
         book: &crate::book::OrderBook,
     ) -> Result<FillResult> {
-        let start_time = Utc::now();
+        let start_time = self.clock.now();
 
         // Validate order
         self.validate_limit_order(order)?;
@@ -263,7 +299,7 @@ impl FillEngine {
 
         // Simulate immediate fill
         let fill = FillEvent {
-            id: uuid::Uuid::new_v4().to_string(),
+            id: self.rng.next_uuid().to_string(),
             order_id: order
                 .client_id
                 .clone()
@@ -272,7 +308,7 @@ impl FillEngine {
             side: order.side,
             price: order.price,
             size: order.size,
-            timestamp: Utc::now(),
+            timestamp: self.clock.now(),
             maker_address: Address::ZERO,
             taker_address: Address::ZERO,
             fee: self.calculate_fee(order.price * order.size),
@@ -554,6 +590,299 @@ pub struct FillProcessorStats {
     pub processed_volume: Decimal,
 }
 
+/// Hypothetical limit order evaluated by a [`ShadowFillEngine`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowOrder {
+    pub id: String,
+    pub token_id: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+}
+
+impl ShadowOrder {
+    pub fn new(
+        id: impl Into<String>,
+        token_id: impl Into<String>,
+        side: Side,
+        price: Decimal,
+        size: Decimal,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            token_id: token_id.into(),
+            side,
+            price,
+            size,
+        }
+    }
+}
+
+/// Shadow profit and loss for one token, marked to the live mid price
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShadowPnl {
+    pub token_id: String,
+    /// Signed position; negative when net short
+    pub position: Decimal,
+    pub average_price: Decimal,
+    pub realized_pnl: Decimal,
+    /// Zero when the book has no mid price
+    pub unrealized_pnl: Decimal,
+    pub fees: Decimal,
+    pub mark_price: Option<Decimal>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ShadowPnl {
+    /// Realized plus unrealized PnL, net of fees
+    pub fn net_pnl(&self) -> Decimal {
+        self.realized_pnl + self.unrealized_pnl - self.fees
+    }
+}
+
+/// Event emitted while shadow-trading against live books
+#[derive(Debug, Clone)]
+pub enum ShadowUpdate {
+    /// A shadow order would have filled
+    Fill(FillEvent),
+    /// PnL of a token whose position changed or was re-marked
+    Pnl(ShadowPnl),
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ShadowPosition {
+    pub(crate) size: Decimal,
+    average_price: Decimal,
+    pub(crate) realized_pnl: Decimal,
+    fees: Decimal,
+    /// Mid price at the last evaluation
+    mark_price: Option<Decimal>,
+}
+
+impl ShadowPosition {
+    pub(crate) fn apply_fill(&mut self, side: Side, size: Decimal, price: Decimal, fee: Decimal) {
+        let signed = match side {
+            Side::BUY => size,
+            Side::SELL => -size,
+        };
+        self.fees += fee;
+
+        if self.size.is_zero() || self.size.is_sign_positive() == signed.is_sign_positive() {
+            let held = self.size.abs();
+            self.average_price = (self.average_price * held + price * size) / (held + size);
+            self.size += signed;
+            return;
+        }
+
+        let closing = size.min(self.size.abs());
+        let direction = if self.size.is_sign_positive() {
+            Decimal::ONE
+        } else {
+            -Decimal::ONE
+        };
+        self.realized_pnl += closing * (price - self.average_price) * direction;
+        self.size += signed;
+        if self.size.is_zero() {
+            self.average_price = Decimal::ZERO;
+        } else if closing < size {
+            // Flipped through flat; the remainder opened at this price
+            self.average_price = price;
+        }
+    }
+
+    fn pnl(&self, token_id: &str, timestamp: DateTime<Utc>) -> ShadowPnl {
+        let mark_price = self.mark_price;
+        ShadowPnl {
+            token_id: token_id.to_string(),
+            position: self.size,
+            average_price: self.average_price,
+            realized_pnl: self.realized_pnl,
+            unrealized_pnl: mark_price
+                .map(|mark| (mark - self.average_price) * self.size)
+                .unwrap_or_default(),
+            fees: self.fees,
+            mark_price,
+            timestamp,
+        }
+    }
+}
+
+/// Continuously evaluates hypothetical orders against live books.
+///
+/// Each evaluation fills a shadow order against every level of the live book
+/// at or through its limit price. Shadow fills do not deplete the live book,
+/// so a level that persists across evaluations keeps filling an order until
+/// its size is exhausted; treat the resulting PnL as an optimistic bound.
+/// Fills are recorded in the wrapped [`FillEngine`], so its clock, RNG, fee
+/// rate and statistics all apply.
+#[derive(Debug)]
+pub struct ShadowFillEngine {
+    engine: FillEngine,
+    /// Shadow orders with their filled size
+    orders: Vec<(ShadowOrder, Decimal)>,
+    positions: TokenMap<ShadowPosition>,
+}
+
+impl ShadowFillEngine {
+    pub fn new(engine: FillEngine) -> Self {
+        Self {
+            engine,
+            orders: Vec::new(),
+            positions: TokenMap::default(),
+        }
+    }
+
+    /// Start shadowing `order`, replacing any order with the same ID
+    pub fn add_order(&mut self, order: ShadowOrder) {
+        self.remove_order(&order.id);
+        self.orders.push((order, Decimal::ZERO));
+    }
+
+    /// Stop shadowing an order. Returns `false` if it was not registered.
+    pub fn remove_order(&mut self, order_id: &str) -> bool {
+        let before = self.orders.len();
+        self.orders.retain(|(order, _)| order.id != order_id);
+        self.orders.len() != before
+    }
+
+    /// Size of a shadow order that has not filled yet
+    pub fn remaining(&self, order_id: &str) -> Option<Decimal> {
+        self.orders
+            .iter()
+            .find(|(order, _)| order.id == order_id)
+            .map(|(order, filled)| order.size - filled)
+    }
+
+    /// The wrapped fill engine holding all shadow fills
+    pub fn engine(&self) -> &FillEngine {
+        &self.engine
+    }
+
+    /// Match every open shadow order against `books` once.
+    ///
+    /// Emits the fills that occurred followed by a PnL update for every
+    /// token with a shadow position. Tokens without a book are skipped.
+    pub fn evaluate(&mut self, books: &crate::book::OrderBookManager) -> Vec<ShadowUpdate> {
+        let mut updates = Vec::new();
+
+        for index in 0..self.orders.len() {
+            let (order, filled) = &self.orders[index];
+            let remaining = order.size - filled;
+            if remaining <= Decimal::ZERO {
+                continue;
+            }
+            let order = order.clone();
+            let Some(token) = TokenKey::lookup(&order.token_id) else {
+                continue;
+            };
+            let Ok(fills) =
+                books.with_book_by_key(&token, |book| self.match_order(&order, remaining, book))
+            else {
+                continue;
+            };
+
+            for fill in fills {
+                self.orders[index].1 += fill.size;
+                self.positions
+                    .entry(token.clone())
+                    .or_default()
+                    .apply_fill(fill.side, fill.size, fill.price, fill.fee);
+                self.engine
+                    .fills
+                    .entry(fill.order_id.clone())
+                    .or_default()
+                    .push(fill.clone());
+                updates.push(ShadowUpdate::Fill(fill));
+            }
+        }
+
+        let timestamp = self.engine.clock.now();
+        for (token_id, position) in &mut self.positions {
+            if let Ok(mid) = books.with_book_by_key(token_id, |book| book.mid_price()) {
+                position.mark_price = mid;
+            }
+            updates.push(ShadowUpdate::Pnl(position.pnl(token_id, timestamp)));
+        }
+
+        updates
+    }
+
+    /// Current PnL of a token, marked to the mid seen at the last evaluation
+    pub fn pnl(&self, token_id: &str) -> Option<ShadowPnl> {
+        self.positions
+            .get(&TokenKey::lookup(token_id)?)
+            .map(|position| position.pnl(token_id, self.engine.clock.now()))
+    }
+
+    /// Evaluate every `interval` until the receiver is dropped.
+    ///
+    /// Returns the engine so the caller can inspect final fills and positions.
+    pub async fn run(
+        mut self,
+        books: Arc<crate::book::OrderBookManager>,
+        interval: std::time::Duration,
+        updates: mpsc::UnboundedSender<ShadowUpdate>,
+    ) -> Self {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            ticker.tick().await;
+            if updates.is_closed() {
+                return self;
+            }
+            for update in self.evaluate(&books) {
+                if updates.send(update).is_err() {
+                    return self;
+                }
+            }
+        }
+    }
+
+    fn match_order(
+        &mut self,
+        order: &ShadowOrder,
+        mut remaining: Decimal,
+        book: &crate::book::OrderBook,
+    ) -> Vec<FillEvent> {
+        let levels = match order.side {
+            Side::BUY => book.asks(None),
+            Side::SELL => book.bids(None),
+        };
+
+        let mut fills = Vec::new();
+        for level in levels {
+            let crosses = match order.side {
+                Side::BUY => level.price <= order.price,
+                Side::SELL => level.price >= order.price,
+            };
+            if !crosses || remaining.is_zero() {
+                break;
+            }
+
+            let size = remaining.min(level.size);
+            remaining -= size;
+            fills.push(FillEvent {
+                id: self.engine.rng.next_uuid().to_string(),
+                order_id: order.id.clone(),
+                token_id: order.token_id.clone(),
+                side: order.side,
+                price: level.price,
+                size,
+                timestamp: self.engine.clock.now(),
+                maker_address: Address::ZERO,
+                taker_address: Address::ZERO,
+                fee: self.engine.calculate_fee(size * level.price),
+            });
+        }
+
+        if !fills.is_empty() {
+            debug!("Shadow order {} matched {} level(s)", order.id, fills.len());
+        }
+        fills
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -652,4 +981,131 @@ mod tests {
         // Check that the fill was added to pending
         assert_eq!(processor.pending_fills.len(), 1);
     }
+
+    #[test]
+    fn test_seeded_engine_with_virtual_clock_is_deterministic() {
+        use crate::sim::Clock;
+
+        let run = || {
+            let clock = crate::sim::VirtualClock::new(1_700_000_000_000);
+            let mut engine = FillEngine::new(dec!(1), dec!(50), 10)
+                .with_clock(clock.shared())
+                .with_seed(42);
+            let mut book = crate::book::OrderBook::new("test".to_string(), 10);
+            for (sequence, price, size) in [(1, dec!(0.50), dec!(10)), (2, dec!(0.51), dec!(20))] {
+                book.apply_delta(OrderDelta {
+                    token_id: "test".to_string(),
+                    timestamp: clock.now(),
+                    side: Side::SELL,
+                    price,
+                    size,
+                    sequence,
+                })
+                .unwrap();
+            }
+
+            let order = MarketOrderRequest {
+                token_id: "test".to_string(),
+                side: Side::BUY,
+                amount: dec!(15),
+                slippage_tolerance: None,
+                client_id: Some("o1".to_string()),
+            };
+            engine.execute_market_order(&order, &book).unwrap()
+        };
+
+        let (a, b) = (run(), run());
+        assert_eq!(a.fills.len(), 2);
+        assert_eq!(a.timestamp.timestamp_millis(), 1_700_000_000_000);
+        for (x, y) in a.fills.iter().zip(&b.fills) {
+            assert_eq!(x.id, y.id);
+            assert_eq!(x.timestamp, y.timestamp);
+        }
+    }
+    #[test]
+    fn test_shadow_engine_fills_crossing_orders_and_marks_pnl() {
+        let books = crate::book::OrderBookManager::new(10);
+        let update =
+            |timestamp, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]| BookUpdate {
+                asset_id: "test".to_string(),
+                market: "0xabc".to_string(),
+                timestamp,
+                bids: bids
+                    .iter()
+                    .map(|&(price, size)| OrderSummary { price, size })
+                    .collect(),
+                asks: asks
+                    .iter()
+                    .map(|&(price, size)| OrderSummary { price, size })
+                    .collect(),
+                hash: None,
+                extra: Default::default(),
+            };
+        books
+            .apply_book_update(&update(
+                1,
+                &[(dec!(0.48), dec!(100))],
+                &[(dec!(0.50), dec!(10)), (dec!(0.52), dec!(50))],
+            ))
+            .unwrap();
+
+        let mut shadow = ShadowFillEngine::new(FillEngine::new(dec!(1), dec!(50), 0).with_seed(7));
+        shadow.add_order(ShadowOrder::new(
+            "bid",
+            "test",
+            Side::BUY,
+            dec!(0.51),
+            dec!(25),
+        ));
+        shadow.add_order(ShadowOrder::new(
+            "ask",
+            "test",
+            Side::SELL,
+            dec!(0.60),
+            dec!(10),
+        ));
+        shadow.add_order(ShadowOrder::new(
+            "other",
+            "missing",
+            Side::BUY,
+            dec!(0.99),
+            dec!(5),
+        ));
+
+        let updates = shadow.evaluate(&books);
+        let fills: Vec<_> = updates
+            .iter()
+            .filter_map(|u| match u {
+                ShadowUpdate::Fill(fill) => Some(fill),
+                ShadowUpdate::Pnl(_) => None,
+            })
+            .collect();
+        assert_eq!(fills.len(), 1);
+        assert_eq!(fills[0].price, dec!(0.50));
+        assert_eq!(fills[0].size, dec!(10));
+        assert_eq!(shadow.remaining("bid"), Some(dec!(15)));
+        assert_eq!(shadow.remaining("ask"), Some(dec!(10)));
+        assert_eq!(shadow.engine().get_fills("bid").unwrap().len(), 1);
+
+        // Mid 0.49 marks the 10 @ 0.50 long at -0.10
+        let pnl = shadow.pnl("test").unwrap();
+        assert_eq!(pnl.position, dec!(10));
+        assert_eq!(pnl.mark_price, Some(dec!(0.49)));
+        assert_eq!(pnl.unrealized_pnl, dec!(-0.10));
+
+        // Bid rallies through the shadow ask: the long is closed at 0.61
+        books
+            .apply_book_update(&update(
+                2,
+                &[(dec!(0.61), dec!(100))],
+                &[(dec!(0.63), dec!(5))],
+            ))
+            .unwrap();
+        shadow.evaluate(&books);
+        let pnl = shadow.pnl("test").unwrap();
+        assert_eq!(pnl.position, Decimal::ZERO);
+        assert_eq!(pnl.realized_pnl, dec!(1.10));
+        assert_eq!(pnl.net_pnl(), dec!(1.10));
+        assert_eq!(shadow.remaining("ask"), Some(Decimal::ZERO));
+    }
 }
@@ -0,0 +1,344 @@
+//! Fill probability for passive quotes
+//!
+//! A resting order fills once the shares queued ahead of it are gone, either
+//! traded away by aggressive orders or cancelled. [`FillProbabilityEstimator`]
+//! learns both from the market channel:
+//!
+//! - the trade tape gives the rate and mean size of aggressive trades that
+//!   reach a price, i.e. trade at it or through it
+//! - book updates give the rate at which resting size is cancelled: a level
+//!   shrinking by more than what traded there
+//!
+//! Trades are modelled as a Poisson process over the horizon, and the queue
+//! ahead decays at the cancel rate. The probability of a fill is the chance
+//! that enough trades arrive to consume what is left of the queue plus the
+//! order itself. It is a rough, stationary model meant for ranking quote
+//! levels against each other, not a calibrated forecast.
+
+use crate::types::{Side, StreamMessage};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy)]
+struct TapeTrade {
+    timestamp: u64,
+    /// Side of the aggressive order
+    side: Side,
+    price: Decimal,
+    size: Decimal,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Cancel {
+    timestamp: u64,
+    /// Share of the level that was cancelled
+    fraction: f64,
+}
+
+#[derive(Debug, Default)]
+struct TokenFlow {
+    trades: VecDeque<TapeTrade>,
+    cancels: VecDeque<Cancel>,
+    /// Resting size per level, keyed by resting side
+    levels: HashMap<(Side, Decimal), Decimal>,
+    /// Size traded per level since the level last changed
+    traded: HashMap<(Side, Decimal), Decimal>,
+    first_seen: Option<u64>,
+    last_seen: u64,
+}
+
+impl TokenFlow {
+    fn touch(&mut self, timestamp: u64, window_ms: u64) {
+        self.first_seen.get_or_insert(timestamp);
+        self.last_seen = self.last_seen.max(timestamp);
+        let cutoff = self.last_seen.saturating_sub(window_ms);
+        while self.trades.front().is_some_and(|t| t.timestamp < cutoff) {
+            self.trades.pop_front();
+        }
+        while self.cancels.front().is_some_and(|c| c.timestamp < cutoff) {
+            self.cancels.pop_front();
+        }
+    }
+
+    /// Seconds of history backing the rates
+    fn observed_secs(&self, window_ms: u64) -> f64 {
+        let span = self.last_seen - self.first_seen.unwrap_or(self.last_seen);
+        span.min(window_ms) as f64 / 1000.0
+    }
+}
+
+/// A prospective resting order
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PassiveQuote {
+    /// Side of the resting order
+    pub side: Side,
+    pub price: Decimal,
+    /// Shares resting ahead at the same price
+    pub queue_ahead: Decimal,
+    pub size: Decimal,
+}
+
+/// Result of [`FillProbabilityEstimator::estimate`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FillEstimate {
+    /// Probability the order fills completely within the horizon
+    pub probability: f64,
+    /// Aggressive trades per second reaching the quote's price
+    pub trade_rate: f64,
+    pub mean_trade_size: f64,
+    /// Share of a level cancelled per second
+    pub cancel_rate: f64,
+    /// Queue ahead expected to remain after cancellations over the horizon
+    pub effective_queue: f64,
+}
+
+/// Learns trade and cancel rates per token from the market channel
+#[derive(Debug)]
+pub struct FillProbabilityEstimator {
+    window: Duration,
+    tokens: HashMap<String, TokenFlow>,
+}
+
+impl Default for FillProbabilityEstimator {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(15 * 60))
+    }
+}
+
+impl FillProbabilityEstimator {
+    /// Estimator using the last `window` of market data
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            tokens: HashMap::new(),
+        }
+    }
+
+    fn window_ms(&self) -> u64 {
+        self.window.as_millis() as u64
+    }
+
+    /// Feed one market-channel message; other messages are ignored
+    pub fn observe(&mut self, message: &StreamMessage) {
+        match message {
+            StreamMessage::Book(book) => {
+                let window_ms = self.window_ms();
+                let flow = self.tokens.entry(book.asset_id.clone()).or_default();
+                flow.touch(book.timestamp, window_ms);
+                flow.levels.clear();
+                flow.traded.clear();
+                for (side, levels) in [(Side::BUY, &book.bids), (Side::SELL, &book.asks)] {
+                    for level in levels {
+                        flow.levels.insert((side, level.price), level.size);
+                    }
+                }
+            },
+            StreamMessage::PriceChange(change) => {
+                for entry in &change.price_changes {
+                    if let Some(size) = entry.size {
+                        self.record_level(
+                            &entry.asset_id,
+                            entry.side,
+                            entry.price,
+                            size,
+                            change.timestamp,
+                        );
+                    }
+                }
+            },
+            StreamMessage::LastTradePrice(trade) => {
+                if let (Some(side), Some(size)) = (trade.side, trade.size) {
+                    self.record_trade(&trade.asset_id, side, trade.price, size, trade.timestamp);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Record a trade; `side` is the aggressor's side
+    pub fn record_trade(
+        &mut self,
+        token_id: &str,
+        side: Side,
+        price: Decimal,
+        size: Decimal,
+        timestamp: u64,
+    ) {
+        let window_ms = self.window_ms();
+        let flow = self.tokens.entry(token_id.to_string()).or_default();
+        flow.touch(timestamp, window_ms);
+        flow.trades.push_back(TapeTrade {
+            timestamp,
+            side,
+            price,
+            size,
+        });
+        *flow.traded.entry((side.opposite(), price)).or_default() += size;
+    }
+
+    /// Record the new resting size at a level. A decrease beyond what traded
+    /// there since the last update counts as cancellation.
+    pub fn record_level(
+        &mut self,
+        token_id: &str,
+        side: Side,
+        price: Decimal,
+        size: Decimal,
+        timestamp: u64,
+    ) {
+        let window_ms = self.window_ms();
+        let flow = self.tokens.entry(token_id.to_string()).or_default();
+        flow.touch(timestamp, window_ms);
+        let key = (side, price);
+        let traded = flow.traded.remove(&key).unwrap_or_default();
+        let previous = flow.levels.insert(key, size).unwrap_or_default();
+        let cancelled = previous - size - traded;
+        if cancelled > Decimal::ZERO && previous > Decimal::ZERO {
+            flow.cancels.push_back(Cancel {
+                timestamp,
+                fraction: (cancelled / previous).to_f64().unwrap_or(0.0),
+            });
+        }
+        if size.is_zero() {
+            flow.levels.remove(&key);
+        }
+    }
+
+    /// Probability that `quote` fills within `horizon`, or `None` without any
+    /// history for `token_id`
+    pub fn estimate(
+        &self,
+        token_id: &str,
+        quote: &PassiveQuote,
+        horizon: Duration,
+    ) -> Option<FillEstimate> {
+        let flow = self.tokens.get(token_id)?;
+        let window_ms = self.window_ms();
+        let observed = flow.observed_secs(window_ms);
+        if observed <= 0.0 {
+            return None;
+        }
+
+        // Aggressive orders on the other side that traded at or through the quote
+        let reaching: Vec<f64> = flow
+            .trades
+            .iter()
+            .filter(|trade| {
+                trade.side == quote.side.opposite()
+                    && match quote.side {
+                        Side::BUY => trade.price <= quote.price,
+                        Side::SELL => trade.price >= quote.price,
+                    }
+            })
+            .filter_map(|trade| trade.size.to_f64())
+            .collect();
+        let trade_rate = reaching.len() as f64 / observed;
+        let mean_trade_size = if reaching.is_empty() {
+            0.0
+        } else {
+            reaching.iter().sum::<f64>() / reaching.len() as f64
+        };
+        let cancel_rate = flow.cancels.iter().map(|c| c.fraction).sum::<f64>() / observed;
+
+        let horizon_secs = horizon.as_secs_f64();
+        let queue = quote.queue_ahead.to_f64().unwrap_or(0.0).max(0.0);
+        let effective_queue = queue * (-cancel_rate * horizon_secs).exp();
+        let needed = effective_queue + quote.size.to_f64().unwrap_or(0.0).max(0.0);
+
+        let probability = if needed <= 0.0 {
+            1.0
+        } else if mean_trade_size <= 0.0 {
+            0.0
+        } else {
+            let trades_needed = (needed / mean_trade_size).ceil() as u64;
+            poisson_tail(trade_rate * horizon_secs, trades_needed)
+        };
+        Some(FillEstimate {
+            probability,
+            trade_rate,
+            mean_trade_size,
+            cancel_rate,
+            effective_queue,
+        })
+    }
+
+    /// Forget a token's history
+    pub fn reset(&mut self, token_id: &str) {
+        self.tokens.remove(token_id);
+    }
+}
+
+/// `P(N >= k)` for `N ~ Poisson(mean)`
+fn poisson_tail(mean: f64, k: u64) -> f64 {
+    if k == 0 {
+        return 1.0;
+    }
+    if mean <= 0.0 {
+        return 0.0;
+    }
+    // Terms in log space so large means don't underflow
+    let ln_mean = mean.ln();
+    let mut ln_term = -mean;
+    let mut cdf = ln_term.exp();
+    for i in 1..k {
+        ln_term += ln_mean - (i as f64).ln();
+        cdf += ln_term.exp();
+    }
+    (1.0 - cdf).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_probability_falls_with_queue_and_rises_with_horizon() {
+        let mut estimator = FillProbabilityEstimator::default();
+        estimator.record_level("1", Side::BUY, dec!(0.50), dec!(100), 0);
+        // A 10-share sell hits the 0.50 bid every 10 seconds for 10 minutes
+        for i in 1..=60 {
+            let timestamp = i * 10_000;
+            estimator.record_trade("1", Side::SELL, dec!(0.50), dec!(10), timestamp);
+            // Traded size is not counted as cancelled
+            estimator.record_level("1", Side::BUY, dec!(0.50), dec!(100), timestamp);
+        }
+
+        let quote = |queue_ahead| PassiveQuote {
+            side: Side::BUY,
+            price: dec!(0.50),
+            queue_ahead,
+            size: dec!(10),
+        };
+        let minute = Duration::from_secs(60);
+        let front = estimator.estimate("1", &quote(dec!(0)), minute).unwrap();
+        let back = estimator.estimate("1", &quote(dec!(100)), minute).unwrap();
+        assert!((front.trade_rate - 0.1).abs() < 1e-9);
+        assert_eq!(front.cancel_rate, 0.0);
+        assert!(front.probability > 0.99);
+        assert!(back.probability < 0.1);
+        let patient = estimator
+            .estimate("1", &quote(dec!(100)), Duration::from_secs(600))
+            .unwrap();
+        assert!(patient.probability > 0.5);
+
+        // Sells don't reach a bid below where they trade, and asks see no buys
+        let below = PassiveQuote {
+            price: dec!(0.49),
+            ..quote(dec!(0))
+        };
+        assert_eq!(
+            estimator.estimate("1", &below, minute).unwrap().probability,
+            0.0
+        );
+
+        // Cancellations shrink the queue ahead
+        estimator.record_level("1", Side::BUY, dec!(0.50), dec!(50), 610_000);
+        let thinned = estimator.estimate("1", &quote(dec!(100)), minute).unwrap();
+        assert!(thinned.cancel_rate > 0.0);
+        assert!(thinned.effective_queue < 100.0);
+        assert!(estimator.estimate("2", &quote(dec!(0)), minute).is_none());
+    }
+}
@@ -0,0 +1,277 @@
+//! Fixed-point prices and sizes
+//!
+//! The order book stores prices as [`Price`] ticks and sizes as [`Qty`] units,
+//! both at [`SCALE_FACTOR`] (4 decimal places). [`Ticks`] and [`Units`] wrap
+//! those integers so latency-sensitive code can stay in integer math from
+//! feed to order without mixing the two up or reaching for `Decimal`.
+//! Convert at the edges with [`Ticks::from_decimal`] / [`Units::to_decimal`]
+//! and read books through [`crate::FastBookView`].
+//!
+//! Both types are `#[repr(transparent)]` and convert freely to and from the
+//! raw aliases used by the `*_fast` methods.
+
+use crate::errors::{PolyfillError, Result};
+use crate::types::{
+    decimal_to_price_exact, decimal_to_price_lossy, decimal_to_qty, price_to_decimal,
+    qty_to_decimal, Price, Qty, MAX_QTY, SCALE_FACTOR,
+};
+use rust_decimal::Decimal;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+/// Price in ticks of 0.0001
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ticks(pub Price);
+
+impl Ticks {
+    pub const ZERO: Ticks = Ticks(0);
+    /// A price of 1.0, the payout of a winning share
+    pub const ONE: Ticks = Ticks(SCALE_FACTOR as Price);
+
+    /// Exact conversion; fails on fractional ticks or prices outside the
+    /// valid range
+    pub fn from_decimal(price: Decimal) -> Result<Self> {
+        decimal_to_price_exact(price)
+            .map(Ticks)
+            .map_err(|e| PolyfillError::validation(format!("Invalid price {price}: {e}")))
+    }
+
+    /// Rounds to the nearest tick instead of rejecting fractional prices
+    pub fn from_decimal_lossy(price: Decimal) -> Result<Self> {
+        decimal_to_price_lossy(price)
+            .map(Ticks)
+            .map_err(|e| PolyfillError::validation(format!("Invalid price {price}: {e}")))
+    }
+
+    pub fn to_decimal(self) -> Decimal {
+        price_to_decimal(self.0)
+    }
+
+    #[inline]
+    pub fn get(self) -> Price {
+        self.0
+    }
+
+    #[inline]
+    pub fn checked_add(self, other: Ticks) -> Option<Ticks> {
+        self.0.checked_add(other.0).map(Ticks)
+    }
+
+    #[inline]
+    pub fn checked_sub(self, other: Ticks) -> Option<Ticks> {
+        self.0.checked_sub(other.0).map(Ticks)
+    }
+
+    #[inline]
+    pub fn saturating_sub(self, other: Ticks) -> Ticks {
+        Ticks(self.0.saturating_sub(other.0))
+    }
+
+    /// Price of the complementary outcome (`1 - self`)
+    #[inline]
+    pub fn complement(self) -> Option<Ticks> {
+        Ticks::ONE.checked_sub(self)
+    }
+
+    /// Whether the price is a multiple of `tick_size`
+    #[inline]
+    pub fn is_aligned(self, tick_size: Ticks) -> bool {
+        tick_size.0 == 0 || self.0.is_multiple_of(tick_size.0)
+    }
+
+    /// Value of `size` shares at this price, in units of collateral
+    #[inline]
+    pub fn notional(self, size: Units) -> Units {
+        let value = (self.0 as i128 * size.0 as i128) / SCALE_FACTOR as i128;
+        Units(value.clamp(i64::MIN as i128, i64::MAX as i128) as Qty)
+    }
+}
+
+impl From<Price> for Ticks {
+    fn from(ticks: Price) -> Self {
+        Ticks(ticks)
+    }
+}
+
+impl From<Ticks> for Price {
+    fn from(ticks: Ticks) -> Self {
+        ticks.0
+    }
+}
+
+impl From<Ticks> for Decimal {
+    fn from(ticks: Ticks) -> Self {
+        ticks.to_decimal()
+    }
+}
+
+impl TryFrom<Decimal> for Ticks {
+    type Error = PolyfillError;
+
+    fn try_from(price: Decimal) -> Result<Self> {
+        Ticks::from_decimal(price)
+    }
+}
+
+impl std::fmt::Display for Ticks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.to_decimal(), f)
+    }
+}
+
+/// Signed size in units of 0.0001 shares (or collateral, for notionals)
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Units(pub Qty);
+
+impl Units {
+    pub const ZERO: Units = Units(0);
+    pub const MAX: Units = Units(MAX_QTY);
+
+    /// Rounds to the nearest unit; fails if the size is out of range
+    pub fn from_decimal(size: Decimal) -> Result<Self> {
+        decimal_to_qty(size)
+            .map(Units)
+            .map_err(|e| PolyfillError::validation(format!("Invalid size {size}: {e}")))
+    }
+
+    pub fn to_decimal(self) -> Decimal {
+        qty_to_decimal(self.0)
+    }
+
+    #[inline]
+    pub fn get(self) -> Qty {
+        self.0
+    }
+
+    #[inline]
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    #[inline]
+    pub fn abs(self) -> Units {
+        Units(self.0.abs())
+    }
+
+    #[inline]
+    pub fn min(self, other: Units) -> Units {
+        Units(self.0.min(other.0))
+    }
+
+    #[inline]
+    pub fn checked_add(self, other: Units) -> Option<Units> {
+        self.0.checked_add(other.0).map(Units)
+    }
+
+    #[inline]
+    pub fn checked_sub(self, other: Units) -> Option<Units> {
+        self.0.checked_sub(other.0).map(Units)
+    }
+}
+
+impl Add for Units {
+    type Output = Units;
+
+    #[inline]
+    fn add(self, other: Units) -> Units {
+        Units(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Units {
+    #[inline]
+    fn add_assign(&mut self, other: Units) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Units {
+    type Output = Units;
+
+    #[inline]
+    fn sub(self, other: Units) -> Units {
+        Units(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Units {
+    #[inline]
+    fn sub_assign(&mut self, other: Units) {
+        self.0 -= other.0;
+    }
+}
+
+impl Neg for Units {
+    type Output = Units;
+
+    #[inline]
+    fn neg(self) -> Units {
+        Units(-self.0)
+    }
+}
+
+impl std::iter::Sum for Units {
+    fn sum<I: Iterator<Item = Units>>(iter: I) -> Units {
+        Units(iter.map(|units| units.0).sum())
+    }
+}
+
+impl From<Qty> for Units {
+    fn from(units: Qty) -> Self {
+        Units(units)
+    }
+}
+
+impl From<Units> for Qty {
+    fn from(units: Units) -> Self {
+        units.0
+    }
+}
+
+impl From<Units> for Decimal {
+    fn from(units: Units) -> Self {
+        units.to_decimal()
+    }
+}
+
+impl TryFrom<Decimal> for Units {
+    type Error = PolyfillError;
+
+    fn try_from(size: Decimal) -> Result<Self> {
+        Units::from_decimal(size)
+    }
+}
+
+impl std::fmt::Display for Units {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.to_decimal(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_conversions_round_trip() {
+        let price = Ticks::from_decimal(dec!(0.6543)).unwrap();
+        assert_eq!(price, Ticks(6543));
+        assert_eq!(price.to_decimal(), dec!(0.6543));
+        assert_eq!(price.complement(), Some(Ticks(3457)));
+        assert!(price.is_aligned(Ticks(1)));
+        assert!(!price.is_aligned(Ticks(10)));
+        assert!(Ticks::from_decimal(dec!(0.00005)).is_err());
+        assert_eq!(
+            Ticks::from_decimal_lossy(dec!(0.65435)).unwrap(),
+            Ticks(6544)
+        );
+
+        let size = Units::from_decimal(dec!(100)).unwrap();
+        assert_eq!(size, Units(1_000_000));
+        assert_eq!((size - Units(500_000)).to_decimal(), dec!(50));
+        assert_eq!(price.notional(size).to_decimal(), dec!(65.43));
+        assert_eq!(Decimal::from(-size), dec!(-100));
+    }
+}
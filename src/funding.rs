@@ -0,0 +1,330 @@
+//! USDC funding between the owner wallet and the proxy wallet
+//!
+//! Orders trade out of the Polymarket proxy wallet, while funds usually
+//! arrive in the owner EOA. [`Funding`] builds the two transfers needed to
+//! script the "fund → trade → withdraw" loop:
+//!
+//! - deposit: a plain collateral `transfer` from the EOA to the proxy
+//! - withdraw: a `transfer` from the proxy back to the EOA, wrapped in a
+//!   call through the proxy wallet factory, which only the owner may make
+//!
+//! Before returning a [`FundingPlan`] it checks the source's collateral
+//! balance over JSON-RPC, estimates gas and checks the EOA can pay for it.
+//! [`Funding::execute`] sends the plan through a [`TxManager`]. Gnosis Safe
+//! proxies need a signed `execTransaction` and are not covered.
+
+use crate::chain::{ChainTransaction, RpcClient, TransactionSender, TxManager, TxReceipt};
+use crate::errors::{PolyfillError, Result};
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::{sol, SolCall};
+use rust_decimal::Decimal;
+use serde_json::json;
+use std::str::FromStr;
+
+/// Polymarket proxy wallet factory on Polygon
+pub const PROXY_WALLET_FACTORY: &str = "0xaB45c5A4B0c941a2F231C04C3f49182e1A254052";
+
+/// Collateral has 6 decimals
+const COLLATERAL_DECIMALS: u32 = 6;
+/// The native gas token has 18 decimals
+const NATIVE_DECIMALS: u32 = 18;
+
+sol! {
+    function balanceOf(address owner) returns (uint256);
+
+    function transfer(address to, uint256 amount) returns (bool);
+
+    struct ProxyCall {
+        uint8 typeCode;
+        address to;
+        uint256 value;
+        bytes data;
+    }
+
+    function proxy(ProxyCall[] calls) payable returns (bytes[]);
+}
+
+/// `CALL` in the factory's call type enum
+const PROXY_CALL_TYPE_CALL: u8 = 1;
+
+/// Gas needed by a transaction at the current gas price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasEstimate {
+    pub gas_limit: u64,
+    /// Gas price in wei
+    pub gas_price: U256,
+    /// `gas_limit * gas_price` in the native token
+    pub cost: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FundingDirection {
+    /// EOA to proxy wallet
+    Deposit,
+    /// Proxy wallet to EOA
+    Withdraw,
+}
+
+/// Checked transfer ready to be sent
+#[derive(Debug, Clone, PartialEq)]
+pub struct FundingPlan {
+    pub direction: FundingDirection,
+    pub amount: Decimal,
+    pub transaction: ChainTransaction,
+    pub gas: GasEstimate,
+}
+
+/// Collateral transfers over a JSON-RPC endpoint
+#[derive(Debug, Clone)]
+pub struct Funding {
+    rpc: RpcClient,
+    collateral: Address,
+    proxy_factory: Address,
+    /// Multiplier applied to `eth_estimateGas`, in percent
+    gas_headroom: u64,
+}
+
+impl Funding {
+    /// Funding through `rpc_url` using the collateral configured for `chain_id`
+    pub fn new(rpc_url: impl Into<String>, chain_id: u64) -> Result<Self> {
+        let contracts = crate::orders::get_contract_config(chain_id, false).ok_or_else(|| {
+            PolyfillError::config(format!("No contracts configured for chain {chain_id}"))
+        })?;
+        Ok(Self {
+            rpc: RpcClient::new(rpc_url),
+            collateral: parse_address("collateral", &contracts.collateral)?,
+            proxy_factory: parse_address("proxy factory", PROXY_WALLET_FACTORY)?,
+            gas_headroom: 120,
+        })
+    }
+
+    pub fn with_collateral(mut self, collateral: Address) -> Self {
+        self.collateral = collateral;
+        self
+    }
+
+    pub fn with_proxy_factory(mut self, factory: Address) -> Self {
+        self.proxy_factory = factory;
+        self
+    }
+
+    /// Pad gas estimates by `percent` of the estimate (default 120)
+    pub fn with_gas_headroom(mut self, percent: u64) -> Self {
+        self.gas_headroom = percent.max(100);
+        self
+    }
+
+    /// Collateral balance of `owner`
+    pub async fn balance(&self, owner: Address) -> Result<Decimal> {
+        let data = balanceOfCall { owner }.abi_encode();
+        let call = json!({"to": self.collateral, "data": hex_data(&data)});
+        let units = self
+            .rpc
+            .quantity("eth_call", json!([call, "latest"]))
+            .await?;
+        from_units(units, COLLATERAL_DECIMALS)
+    }
+
+    /// Native gas token balance of `owner`
+    pub async fn native_balance(&self, owner: Address) -> Result<Decimal> {
+        let wei = self
+            .rpc
+            .quantity("eth_getBalance", json!([owner, "latest"]))
+            .await?;
+        from_units(wei, NATIVE_DECIMALS)
+    }
+
+    pub async fn estimate_gas(&self, transaction: &ChainTransaction) -> Result<GasEstimate> {
+        let call = json!({
+            "from": transaction.from,
+            "to": transaction.to,
+            "data": hex_data(&transaction.data),
+        });
+        let estimate = self.rpc.quantity("eth_estimateGas", json!([call])).await?;
+        let gas_price = self.rpc.quantity("eth_gasPrice", json!([])).await?;
+        let gas_limit = u64::try_from(estimate)
+            .map_err(|_| PolyfillError::parse(format!("Gas estimate {estimate} too large"), None))?
+            .saturating_mul(self.gas_headroom)
+            / 100;
+        Ok(GasEstimate {
+            gas_limit,
+            gas_price,
+            cost: from_units(gas_price * U256::from(gas_limit), NATIVE_DECIMALS)?,
+        })
+    }
+
+    /// Plan moving `amount` of collateral from `eoa` into `proxy_wallet`
+    pub async fn deposit(
+        &self,
+        eoa: Address,
+        proxy_wallet: Address,
+        amount: Decimal,
+    ) -> Result<FundingPlan> {
+        let data = transferCall {
+            to: proxy_wallet,
+            amount: to_units(amount)?,
+        }
+        .abi_encode();
+        let transaction = ChainTransaction {
+            from: eoa,
+            to: self.collateral,
+            data,
+        };
+        self.plan(FundingDirection::Deposit, eoa, eoa, amount, transaction)
+            .await
+    }
+
+    /// Plan moving `amount` of collateral from `proxy_wallet` back to its
+    /// owner `eoa`
+    pub async fn withdraw(
+        &self,
+        eoa: Address,
+        proxy_wallet: Address,
+        amount: Decimal,
+    ) -> Result<FundingPlan> {
+        let transfer = transferCall {
+            to: eoa,
+            amount: to_units(amount)?,
+        }
+        .abi_encode();
+        let data = proxyCall {
+            calls: vec![ProxyCall {
+                typeCode: PROXY_CALL_TYPE_CALL,
+                to: self.collateral,
+                value: U256::ZERO,
+                data: transfer.into(),
+            }],
+        }
+        .abi_encode();
+        let transaction = ChainTransaction {
+            from: eoa,
+            to: self.proxy_factory,
+            data,
+        };
+        self.plan(
+            FundingDirection::Withdraw,
+            proxy_wallet,
+            eoa,
+            amount,
+            transaction,
+        )
+        .await
+    }
+
+    async fn plan(
+        &self,
+        direction: FundingDirection,
+        source: Address,
+        payer: Address,
+        amount: Decimal,
+        transaction: ChainTransaction,
+    ) -> Result<FundingPlan> {
+        if amount <= Decimal::ZERO {
+            return Err(PolyfillError::validation(
+                "Transfer amount must be positive",
+            ));
+        }
+        let available = self.balance(source).await?;
+        if available < amount {
+            return Err(PolyfillError::validation(format!(
+                "Insufficient collateral in {source}: {available} < {amount}"
+            )));
+        }
+        let gas = self.estimate_gas(&transaction).await?;
+        let native = self.native_balance(payer).await?;
+        if native < gas.cost {
+            return Err(PolyfillError::validation(format!(
+                "Insufficient gas funds in {payer}: {native} < {}",
+                gas.cost
+            )));
+        }
+        Ok(FundingPlan {
+            direction,
+            amount,
+            transaction,
+            gas,
+        })
+    }
+
+    /// Send a checked plan through `manager` and wait for confirmation
+    pub async fn execute<S: TransactionSender>(
+        &self,
+        manager: &TxManager<S>,
+        plan: &FundingPlan,
+    ) -> Result<TxReceipt> {
+        manager
+            .send_and_confirm(&plan.transaction, plan.gas.gas_limit)
+            .await
+    }
+}
+
+fn parse_address(name: &str, value: &str) -> Result<Address> {
+    Address::from_str(value)
+        .map_err(|e| PolyfillError::config(format!("Invalid {name} address: {e}")))
+}
+
+fn hex_data(data: &[u8]) -> String {
+    format!("0x{}", alloy_primitives::hex::encode(data))
+}
+
+fn to_units(amount: Decimal) -> Result<U256> {
+    let units = (amount * Decimal::from(10u64.pow(COLLATERAL_DECIMALS))).trunc();
+    u128::try_from(units)
+        .map(U256::from)
+        .map_err(|_| PolyfillError::validation(format!("Invalid transfer amount {amount}")))
+}
+
+fn from_units(units: U256, decimals: u32) -> Result<Decimal> {
+    u128::try_from(units)
+        .ok()
+        .and_then(|units| i128::try_from(units).ok())
+        .and_then(|units| Decimal::try_from_i128_with_scale(units, decimals).ok())
+        .map(|value| value.normalize())
+        .ok_or_else(|| PolyfillError::parse(format!("Amount {units} out of range"), None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+    use rust_decimal_macros::dec;
+
+    const EOA: Address = Address::repeat_byte(0x11);
+    const PROXY: Address = Address::repeat_byte(0x22);
+
+    async fn mock_rpc(server: &mut Server, method: &str, result: &str) -> mockito::Mock {
+        server
+            .mock("POST", "/")
+            .match_body(Matcher::PartialJson(json!({ "method": method })))
+            .with_header("content-type", "application/json")
+            .with_body(json!({"jsonrpc": "2.0", "id": 1, "result": result}).to_string())
+            .create_async()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_plans_check_balances_and_gas() {
+        let mut server = Server::new_async().await;
+        // 25 USDC, 0.1 POL, 50k gas at 100 gwei
+        mock_rpc(&mut server, "eth_call", "0x17d7840").await;
+        mock_rpc(&mut server, "eth_getBalance", "0x16345785d8a0000").await;
+        mock_rpc(&mut server, "eth_estimateGas", "0xc350").await;
+        mock_rpc(&mut server, "eth_gasPrice", "0x174876e800").await;
+        let funding = Funding::new(server.url(), 137).unwrap();
+
+        let plan = funding.deposit(EOA, PROXY, dec!(10)).await.unwrap();
+        assert_eq!(plan.gas.gas_limit, 60_000);
+        assert_eq!(plan.gas.cost, dec!(0.006));
+        let call = transferCall::abi_decode(&plan.transaction.data).unwrap();
+        assert_eq!(call.to, PROXY);
+        assert_eq!(call.amount, U256::from(10_000_000));
+
+        let plan = funding.withdraw(EOA, PROXY, dec!(5.5)).await.unwrap();
+        assert_eq!(plan.transaction.to, funding.proxy_factory);
+        let call = proxyCall::abi_decode(&plan.transaction.data).unwrap();
+        assert_eq!(call.calls[0].to, funding.collateral);
+
+        let err = funding.deposit(EOA, PROXY, dec!(30)).await.unwrap_err();
+        assert!(err.to_string().contains("Insufficient collateral"));
+    }
+}
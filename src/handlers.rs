@@ -0,0 +1,330 @@
+//! Typed callbacks for account events
+//!
+//! This module lets simple bots react to fills and order updates from the
+//! user channel without owning the stream polling loop. Handlers are invoked
+//! from the consumer task. Disconnect and reconnect callbacks for the
+//! channel are kept here too, so every clone of a client shares them.
+//!
+//! When panics unwind, as in dev and test builds, a panicking handler is
+//! caught and counted so it cannot take down the task or prevent other
+//! handlers from running. The crate's release profile sets
+//! `panic = "abort"`, so there a panicking handler aborts the process;
+//! handlers must not rely on isolation in production.
+
+use crate::errors::Result;
+use crate::stream::{DisconnectEvent, DisconnectHandler, ReconnectEvent, ReconnectHandler};
+use crate::types::{OrderMessage, StreamMessage, TradeMessage};
+use futures::{Stream, StreamExt};
+use parking_lot::RwLock;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, warn};
+
+/// Callback invoked for every user trade (fill)
+pub type FillHandler = Arc<dyn Fn(&TradeMessage) + Send + Sync>;
+
+/// Callback invoked for every user order update
+pub type OrderUpdateHandler = Arc<dyn Fn(&OrderMessage) + Send + Sync>;
+
+/// Callback invoked for every dispatched message, whatever its type
+pub type MessageHandler = Arc<dyn Fn(&StreamMessage) + Send + Sync>;
+
+/// Registry of account event handlers
+#[derive(Default)]
+pub struct EventHandlers {
+    fill_handlers: RwLock<Vec<FillHandler>>,
+    order_handlers: RwLock<Vec<OrderUpdateHandler>>,
+    message_handlers: RwLock<Vec<MessageHandler>>,
+    disconnect_handlers: RwLock<Vec<DisconnectHandler>>,
+    reconnect_handlers: RwLock<Vec<ReconnectHandler>>,
+    panics: AtomicU64,
+}
+
+impl std::fmt::Debug for EventHandlers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventHandlers")
+            .field("fill_handlers", &self.fill_handlers.read().len())
+            .field("order_handlers", &self.order_handlers.read().len())
+            .field("message_handlers", &self.message_handlers.read().len())
+            .field(
+                "disconnect_handlers",
+                &self.disconnect_handlers.read().len(),
+            )
+            .field("reconnect_handlers", &self.reconnect_handlers.read().len())
+            .field("panics", &self.panic_count())
+            .finish()
+    }
+}
+
+impl EventHandlers {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a callback for user trades
+    pub fn on_fill<F>(&self, handler: F)
+    where
+        F: Fn(&TradeMessage) + Send + Sync + 'static,
+    {
+        self.fill_handlers.write().push(Arc::new(handler));
+    }
+
+    /// Register a callback for user order updates
+    pub fn on_order_update<F>(&self, handler: F)
+    where
+        F: Fn(&OrderMessage) + Send + Sync + 'static,
+    {
+        self.order_handlers.write().push(Arc::new(handler));
+    }
+
+    /// Register a callback for every message, e.g. to pick up
+    /// `market_resolved` events that have no typed hook
+    pub fn on_message<F>(&self, handler: F)
+    where
+        F: Fn(&StreamMessage) + Send + Sync + 'static,
+    {
+        self.message_handlers.write().push(Arc::new(handler));
+    }
+
+    /// Register a callback for disconnects and failed reconnects
+    pub fn on_disconnect<F>(&self, handler: F)
+    where
+        F: Fn(&DisconnectEvent) + Send + Sync + 'static,
+    {
+        self.disconnect_handlers.write().push(Arc::new(handler));
+    }
+
+    /// Register a callback for restored connections
+    pub fn on_reconnect<F>(&self, handler: F)
+    where
+        F: Fn(&ReconnectEvent) + Send + Sync + 'static,
+    {
+        self.reconnect_handlers.write().push(Arc::new(handler));
+    }
+
+    /// Run the disconnect handlers
+    pub fn notify_disconnect(&self, event: &DisconnectEvent) {
+        let handlers = self.disconnect_handlers.read().clone();
+        for handler in &handlers {
+            self.invoke("disconnect", || handler(event));
+        }
+    }
+
+    /// Run the reconnect handlers
+    pub fn notify_reconnect(&self, event: &ReconnectEvent) {
+        let handlers = self.reconnect_handlers.read().clone();
+        for handler in &handlers {
+            self.invoke("reconnect", || handler(event));
+        }
+    }
+
+    /// Check if no handlers are registered
+    pub fn is_empty(&self) -> bool {
+        self.fill_handlers.read().is_empty()
+            && self.order_handlers.read().is_empty()
+            && self.message_handlers.read().is_empty()
+            && self.disconnect_handlers.read().is_empty()
+            && self.reconnect_handlers.read().is_empty()
+    }
+
+    /// Number of handler invocations that panicked. Always 0 when panics
+    /// abort.
+    pub fn panic_count(&self) -> u64 {
+        self.panics.load(Ordering::Relaxed)
+    }
+
+    /// Dispatch a stream message to the matching handlers.
+    ///
+    /// Returns the number of handlers that completed without panicking.
+    pub fn dispatch(&self, message: &StreamMessage) -> usize {
+        // Snapshot the lists so handlers may register further handlers
+        let handlers = self.message_handlers.read().clone();
+        let generic = handlers
+            .iter()
+            .filter(|handler| self.invoke("message", || handler(message)))
+            .count();
+        let typed = match message {
+            StreamMessage::Trade(trade) => {
+                let handlers = self.fill_handlers.read().clone();
+                handlers
+                    .iter()
+                    .filter(|handler| self.invoke("fill", || handler(trade)))
+                    .count()
+            },
+            StreamMessage::Order(order) => {
+                let handlers = self.order_handlers.read().clone();
+                handlers
+                    .iter()
+                    .filter(|handler| self.invoke("order update", || handler(order)))
+                    .count()
+            },
+            _ => 0,
+        };
+        generic + typed
+    }
+
+    fn invoke(&self, kind: &str, f: impl FnOnce()) -> bool {
+        match catch_unwind(AssertUnwindSafe(f)) {
+            Ok(()) => true,
+            Err(_) => {
+                self.panics.fetch_add(1, Ordering::Relaxed);
+                error!("{} handler panicked; continuing", kind);
+                false
+            },
+        }
+    }
+
+    /// Drive a user-channel stream to completion, dispatching every message.
+    ///
+    /// Stream errors are logged and skipped; the loop ends when the stream does.
+    pub async fn run<S>(&self, stream: S) -> Result<()>
+    where
+        S: Stream<Item = Result<StreamMessage>> + Unpin,
+    {
+        self.run_until_cancelled(stream, &CancellationToken::new())
+            .await
+    }
+
+    /// Like [`EventHandlers::run`], but returns as soon as `cancel` fires
+    pub async fn run_until_cancelled<S>(
+        &self,
+        mut stream: S,
+        cancel: &CancellationToken,
+    ) -> Result<()>
+    where
+        S: Stream<Item = Result<StreamMessage>> + Unpin,
+    {
+        while let Some(Some(message)) = cancel.run_until_cancelled(stream.next()).await {
+            match message {
+                Ok(message) => {
+                    self.dispatch(&message);
+                },
+                Err(e) => warn!("User channel error: {}", e),
+            }
+        }
+
+        debug!("User channel stream ended");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MockStream;
+    use std::sync::atomic::AtomicUsize;
+
+    fn trade_message() -> StreamMessage {
+        serde_json::from_str(
+            r#"{"event_type":"trade","id":"t1","market":"0xabc","asset_id":"1","side":"BUY","size":"10","price":"0.5"}"#,
+        )
+        .unwrap()
+    }
+
+    fn order_message() -> StreamMessage {
+        serde_json::from_str(
+            r#"{"event_type":"order","id":"o1","market":"0xabc","asset_id":"1","side":"SELL","price":"0.6"}"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_dispatch_routes_by_event_type() {
+        let handlers = EventHandlers::new();
+        let fills = Arc::new(AtomicUsize::new(0));
+        let orders = Arc::new(AtomicUsize::new(0));
+
+        let counter = fills.clone();
+        handlers.on_fill(move |trade| {
+            assert_eq!(trade.id, "t1");
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let counter = orders.clone();
+        handlers.on_order_update(move |order| {
+            assert_eq!(order.id, "o1");
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert_eq!(handlers.dispatch(&trade_message()), 1);
+        assert_eq!(handlers.dispatch(&order_message()), 1);
+        assert_eq!(handlers.dispatch(&StreamMessage::Unknown), 0);
+        assert_eq!(fills.load(Ordering::Relaxed), 1);
+        assert_eq!(orders.load(Ordering::Relaxed), 1);
+
+        let messages = Arc::new(AtomicUsize::new(0));
+        let counter = messages.clone();
+        handlers.on_message(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(handlers.dispatch(&trade_message()), 2);
+        assert_eq!(handlers.dispatch(&StreamMessage::Unknown), 1);
+        assert_eq!(messages.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_panicking_handler_is_isolated() {
+        let handlers = EventHandlers::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        handlers.on_fill(|_| panic!("handler bug"));
+        let counter = calls.clone();
+        handlers.on_fill(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert_eq!(handlers.dispatch(&trade_message()), 1);
+        assert_eq!(handlers.dispatch(&trade_message()), 1);
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+        assert_eq!(handlers.panic_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_consumes_stream() {
+        let handlers = EventHandlers::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        handlers.on_fill(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        let mut stream = MockStream::new();
+        stream.add_message(trade_message());
+        stream.add_error(crate::errors::PolyfillError::stream(
+            "boom",
+            crate::errors::StreamErrorKind::MessageCorrupted,
+        ));
+        stream.add_message(trade_message());
+
+        handlers.run(stream).await.unwrap();
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_run_until_cancelled_stops_waiting() {
+        let handlers = EventHandlers::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        handlers.on_fill(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        let mut stream = MockStream::new();
+        stream.add_message(trade_message());
+        stream.add_delay(std::time::Duration::from_secs(3600));
+        stream.add_message(trade_message());
+
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+            trigger.cancel();
+        });
+
+        let started = tokio::time::Instant::now();
+        handlers.run_until_cancelled(stream, &cancel).await.unwrap();
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
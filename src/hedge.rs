@@ -0,0 +1,301 @@
+//! YES/NO complement hedging
+//!
+//! A YES share and a NO share of the same market together always pay 1, so
+//! exposure to a binary market is the difference between the two holdings.
+//! Instead of selling into a thin book, exposure is usually cut by buying the
+//! complement: buy NO against YES exposure and YES against NO exposure.
+//! [`ComplementHedger`] computes those orders for a target net exposure and
+//! posts a whole portfolio's worth in one batch.
+
+use crate::client::ClobClient;
+use crate::errors::{PolyfillError, Result};
+use crate::recovery::Position;
+use crate::types::{Market, OrderArgs, PostOrderOptions, PostOrderResponse, Side, TokenId};
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Holdings in both outcomes of one binary market
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryExposure {
+    pub condition_id: String,
+    pub yes_token_id: String,
+    pub no_token_id: String,
+    pub yes_size: Decimal,
+    pub no_size: Decimal,
+}
+
+impl BinaryExposure {
+    /// Exposure to `market` from the matching entries of `positions`.
+    ///
+    /// The YES token is the one whose outcome is "Yes", falling back to the
+    /// first token for markets with other outcome names. Fails if the two
+    /// tokens are not distinct, non-empty ids.
+    pub fn from_market(market: &Market, positions: &[Position]) -> Result<Self> {
+        let [first, second] = &market.tokens;
+        if first.token_id.is_empty() || first.token_id == second.token_id {
+            return Err(PolyfillError::validation(format!(
+                "Market {} does not have two distinct tokens",
+                market.condition_id
+            )));
+        }
+        let (yes, no) = if second.outcome.eq_ignore_ascii_case("yes") {
+            (second, first)
+        } else {
+            (first, second)
+        };
+        let yes_token_id = yes.token_id.clone();
+        let no_token_id = no.token_id.clone();
+        let size_of = |token_id: &str| {
+            positions
+                .iter()
+                .filter(|position| position.token_id == token_id)
+                .map(|position| position.size)
+                .sum()
+        };
+        Ok(Self {
+            condition_id: market.condition_id.clone(),
+            yes_size: size_of(&yes_token_id),
+            no_size: size_of(&no_token_id),
+            yes_token_id,
+            no_token_id,
+        })
+    }
+
+    /// Net YES exposure in shares; negative means net NO
+    pub fn net(&self) -> Decimal {
+        self.yes_size - self.no_size
+    }
+}
+
+/// Complement order bringing one market to its target exposure
+#[derive(Debug, Clone, PartialEq)]
+pub struct HedgeOrder {
+    pub condition_id: String,
+    pub order: OrderArgs,
+    pub net_before: Decimal,
+    pub net_after: Decimal,
+}
+
+/// Calculator for complement hedges
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplementHedger {
+    min_order_size: Decimal,
+    size_decimals: u32,
+}
+
+impl Default for ComplementHedger {
+    fn default() -> Self {
+        Self {
+            min_order_size: Decimal::from(5),
+            size_decimals: 2,
+        }
+    }
+}
+
+impl ComplementHedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gaps smaller than this many shares are left alone
+    pub fn with_min_order_size(mut self, min_order_size: Decimal) -> Self {
+        self.min_order_size = min_order_size;
+        self
+    }
+
+    /// Hedge sizes are rounded down to this many decimal places
+    pub fn with_size_decimals(mut self, size_decimals: u32) -> Self {
+        self.size_decimals = size_decimals;
+        self
+    }
+
+    /// Order moving `exposure` to `target_net`, buying YES at `yes_price` or
+    /// NO at `no_price`. `None` if the gap is below the minimum order size.
+    pub fn hedge(
+        &self,
+        exposure: &BinaryExposure,
+        target_net: Decimal,
+        yes_price: Decimal,
+        no_price: Decimal,
+    ) -> Result<Option<HedgeOrder>> {
+        let net_before = exposure.net();
+        let gap = net_before - target_net;
+        let (token_id, price) = if gap > Decimal::ZERO {
+            (&exposure.no_token_id, no_price)
+        } else {
+            (&exposure.yes_token_id, yes_price)
+        };
+        if price <= Decimal::ZERO || price >= Decimal::ONE {
+            return Err(PolyfillError::validation(format!(
+                "Hedge price for {token_id} must be between 0 and 1, got {price}"
+            )));
+        }
+
+        let Some(size) = self.order_size(gap) else {
+            return Ok(None);
+        };
+        let net_after = if gap > Decimal::ZERO {
+            net_before - size
+        } else {
+            net_before + size
+        };
+        Ok(Some(HedgeOrder {
+            condition_id: exposure.condition_id.clone(),
+            order: OrderArgs::new(TokenId::new(token_id)?, price, size, Side::BUY),
+            net_before,
+            net_after,
+        }))
+    }
+
+    /// Hedges moving every market in `exposures` to `target_net`.
+    ///
+    /// `price` returns the price to pay for a token, usually its best ask.
+    /// It is only asked for tokens that need buying, and fails the plan if it
+    /// has no price for one.
+    pub fn plan<F>(
+        &self,
+        exposures: &[BinaryExposure],
+        target_net: Decimal,
+        price: F,
+    ) -> Result<Vec<HedgeOrder>>
+    where
+        F: Fn(&str) -> Option<Decimal>,
+    {
+        let mut hedges = Vec::new();
+        for exposure in exposures {
+            let gap = exposure.net() - target_net;
+            if self.order_size(gap).is_none() {
+                continue;
+            }
+            let needs = if gap > Decimal::ZERO {
+                &exposure.no_token_id
+            } else {
+                &exposure.yes_token_id
+            };
+            let token_price = price(needs).ok_or_else(|| {
+                PolyfillError::validation(format!("No hedge price for token {needs}"))
+            })?;
+            if let Some(hedge) = self.hedge(exposure, target_net, token_price, token_price)? {
+                hedges.push(hedge);
+            }
+        }
+        Ok(hedges)
+    }
+
+    /// Size of the order closing `gap`, `None` if below the minimum
+    fn order_size(&self, gap: Decimal) -> Option<Decimal> {
+        let size = gap
+            .abs()
+            .round_dp_with_strategy(self.size_decimals, RoundingStrategy::ToZero);
+        (!size.is_zero() && size >= self.min_order_size).then_some(size)
+    }
+
+    /// Sign `hedges` and post them through the batch endpoint
+    pub async fn execute(
+        &self,
+        client: &ClobClient,
+        hedges: &[HedgeOrder],
+        post_options: Option<&PostOrderOptions>,
+    ) -> Result<Vec<PostOrderResponse>> {
+        let orders: Vec<OrderArgs> = hedges.iter().map(|hedge| hedge.order.clone()).collect();
+        client
+            .create_and_post_orders(&orders, None, post_options)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn exposure(yes_size: Decimal, no_size: Decimal) -> BinaryExposure {
+        BinaryExposure {
+            condition_id: "0xabc".to_string(),
+            yes_token_id: "1".to_string(),
+            no_token_id: "2".to_string(),
+            yes_size,
+            no_size,
+        }
+    }
+
+    #[test]
+    fn test_hedge_buys_the_complement() {
+        let hedger = ComplementHedger::new();
+
+        let hedge = hedger
+            .hedge(
+                &exposure(dec!(100), dec!(20)),
+                dec!(0),
+                dec!(0.6),
+                dec!(0.41),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(hedge.order.token_id.as_str(), "2");
+        assert_eq!(hedge.order.side, Side::BUY);
+        assert_eq!(hedge.order.price, dec!(0.41));
+        assert_eq!(hedge.order.size, dec!(80));
+        assert_eq!(hedge.net_after, dec!(0));
+
+        let hedge = hedger
+            .hedge(
+                &exposure(dec!(0), dec!(30)),
+                dec!(10.555),
+                dec!(0.6),
+                dec!(0.41),
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(hedge.order.token_id.as_str(), "1");
+        assert_eq!(hedge.order.size, dec!(40.55));
+
+        // Gap under the minimum order size
+        assert!(hedger
+            .hedge(
+                &exposure(dec!(12), dec!(10)),
+                dec!(0),
+                dec!(0.6),
+                dec!(0.41)
+            )
+            .unwrap()
+            .is_none());
+
+        let prices = |token: &str| (token == "2").then_some(dec!(0.3));
+        // Token "1" has no price but the second market needs no hedge
+        let plan = hedger
+            .plan(
+                &[exposure(dec!(50), dec!(0)), exposure(dec!(5), dec!(7))],
+                dec!(0),
+                prices,
+            )
+            .unwrap();
+        assert_eq!(plan.len(), 1);
+        assert!(hedger
+            .plan(&[exposure(dec!(0), dec!(50))], dec!(0), prices)
+            .is_err());
+
+        let mut market: Market = serde_json::from_value(serde_json::json!({
+            "condition_id": "0xabc",
+            "tokens": [
+                {"token_id": "7", "outcome": "No", "price": 0.4},
+                {"token_id": "8", "outcome": "Yes", "price": 0.6}
+            ],
+            "rewards": {"rates": null, "min_size": 0, "max_spread": 0},
+            "min_incentive_size": null, "max_incentive_spread": null,
+            "active": true, "closed": false, "question_id": "0x1",
+            "minimum_order_size": 5, "minimum_tick_size": 0.01,
+            "description": "", "category": null, "end_date_iso": null,
+            "game_start_time": null, "question": "", "market_slug": "",
+            "seconds_delay": 0, "icon": "", "fpmm": ""
+        }))
+        .unwrap();
+        let mut position = Position::new("8");
+        position.size = dec!(10);
+        let positions = [position];
+        let binary = BinaryExposure::from_market(&market, &positions).unwrap();
+        assert_eq!(binary.yes_token_id, "8");
+        assert_eq!(binary.net(), dec!(10));
+        market.tokens[1].token_id = "7".to_string();
+        assert!(BinaryExposure::from_market(&market, &positions).is_err());
+    }
+}
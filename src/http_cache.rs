@@ -0,0 +1,156 @@
+//! Conditional-request cache for slow-changing REST responses
+//!
+//! Loops that walk every market re-download the same market and
+//! simplified-market pages over and over. [`ResponseCache`] keeps the body
+//! and `ETag` of recent responses, keyed by URL, so the client can send
+//! `If-None-Match` and reuse the cached body when the venue answers
+//! `304 Not Modified`. It holds at most `capacity` responses and evicts the
+//! least recently used one first. Enable it with
+//! [`crate::ClobClient::set_response_cache`].
+
+use bytes::Bytes;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+#[derive(Debug)]
+struct Entry {
+    etag: String,
+    body: Bytes,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    entries: HashMap<String, Entry>,
+    clock: u64,
+    hits: u64,
+    misses: u64,
+}
+
+/// Hit counts of a [`ResponseCache`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResponseCacheStats {
+    pub entries: usize,
+    /// Requests answered with `304 Not Modified`
+    pub hits: u64,
+    /// Requests that downloaded a full body
+    pub misses: u64,
+}
+
+/// LRU cache of response bodies and their `ETag`s
+#[derive(Debug)]
+pub struct ResponseCache {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(Inner::default()),
+        }
+    }
+
+    /// `ETag` of the cached response for `url`
+    pub fn etag(&self, url: &str) -> Option<String> {
+        self.inner
+            .lock()
+            .entries
+            .get(url)
+            .map(|entry| entry.etag.clone())
+    }
+
+    /// Cached body for `url` after a `304`, counting a hit
+    pub fn revalidated(&self, url: &str) -> Option<Bytes> {
+        let mut inner = self.inner.lock();
+        inner.clock += 1;
+        let clock = inner.clock;
+        let entry = inner.entries.get_mut(url)?;
+        entry.last_used = clock;
+        let body = entry.body.clone();
+        inner.hits += 1;
+        Some(body)
+    }
+
+    /// Store a full response, evicting the least recently used one if full
+    pub fn insert(&self, url: &str, etag: String, body: Bytes) {
+        let mut inner = self.inner.lock();
+        inner.clock += 1;
+        inner.misses += 1;
+        let last_used = inner.clock;
+        if !inner.entries.contains_key(url) && inner.entries.len() >= self.capacity {
+            let oldest = inner
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone());
+            if let Some(oldest) = oldest {
+                inner.entries.remove(&oldest);
+            }
+        }
+        inner.entries.insert(
+            url.to_string(),
+            Entry {
+                etag,
+                body,
+                last_used,
+            },
+        );
+    }
+
+    /// Count a full download the venue sent without an `ETag`
+    pub fn record_miss(&self) {
+        self.inner.lock().misses += 1;
+    }
+
+    pub fn remove(&self, url: &str) {
+        self.inner.lock().entries.remove(url);
+    }
+
+    pub fn clear(&self) {
+        self.inner.lock().entries.clear();
+    }
+
+    pub fn stats(&self) -> ResponseCacheStats {
+        let inner = self.inner.lock();
+        ResponseCacheStats {
+            entries: inner.entries.len(),
+            hits: inner.hits,
+            misses: inner.misses,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_response_cache_evicts_least_recently_used() {
+        let cache = ResponseCache::new(2);
+        cache.insert("/a", "\"1\"".to_string(), Bytes::from_static(b"a"));
+        cache.insert("/b", "\"2\"".to_string(), Bytes::from_static(b"b"));
+        // Touch /a so /b is the oldest
+        assert_eq!(cache.revalidated("/a").unwrap(), "a");
+        cache.insert("/c", "\"3\"".to_string(), Bytes::from_static(b"c"));
+
+        assert_eq!(cache.etag("/a").as_deref(), Some("\"1\""));
+        assert!(cache.etag("/b").is_none());
+        assert!(cache.revalidated("/b").is_none());
+        assert_eq!(
+            cache.stats(),
+            ResponseCacheStats {
+                entries: 2,
+                hits: 1,
+                misses: 3,
+            }
+        );
+    }
+}
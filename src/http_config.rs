@@ -38,6 +38,7 @@ pub fn create_optimized_client() -> Result<Client, reqwest::Error> {
         .http2_initial_stream_window_size(512 * 1024) // 512KB - benchmarked optimal
         // Compression - all algorithms enabled by default in reqwest
         .gzip(true) // Ensure gzip is enabled
+        .deflate(true)
         // User agent for identification
         .user_agent(concat!(
             "polyfill-rs/",
@@ -69,6 +70,7 @@ pub fn create_colocated_client() -> Result<Client, reqwest::Error> {
         .http2_keep_alive_while_idle(true)
         // Disable compression in co-located environments (CPU vs network tradeoff)
         .gzip(false)
+        .deflate(false)
         .no_brotli() // Disable brotli compression
         .user_agent(concat!(
             "polyfill-rs/",
@@ -0,0 +1,226 @@
+//! Parallel book ingestion
+//!
+//! A single task applying every WebSocket update becomes the bottleneck for
+//! watchlists of hundreds of tokens. [`ShardedIngest`] runs one worker task
+//! per [`OrderBookManager`] shard and routes each update to the worker owning
+//! its token's shard, so workers never contend for a shard lock and updates
+//! for one token are applied in arrival order. Worker queues are bounded:
+//! when a worker falls behind, [`ShardedIngest::submit`] waits, which in turn
+//! stops the socket reader from pulling more messages.
+//!
+//! Size the pool with [`OrderBookManager::with_shard_count`], typically to the
+//! number of cores.
+
+use crate::book::OrderBookManager;
+use crate::errors::{PolyfillError, Result};
+use crate::intern::TokenKey;
+use crate::types::{BookUpdate, OrderDelta, StreamMessage};
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Updates queued per worker before [`ShardedIngest::submit`] waits
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// Counters across all workers
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct IngestStats {
+    /// Book snapshots and deltas applied
+    pub applied: u64,
+    /// Updates rejected by their book
+    pub failed: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    applied: AtomicU64,
+    failed: AtomicU64,
+}
+
+enum Work {
+    Snapshot(BookUpdate),
+    /// Sequence is assigned by the worker
+    Delta(TokenKey, OrderDelta),
+}
+
+/// Pool of book workers, one per shard
+#[derive(Debug)]
+pub struct ShardedIngest {
+    books: Arc<OrderBookManager>,
+    senders: Vec<mpsc::Sender<Work>>,
+    workers: Vec<JoinHandle<()>>,
+    counters: Arc<Counters>,
+}
+
+impl ShardedIngest {
+    /// Spawn one worker per shard of `books`, each queueing up to
+    /// `queue_capacity` updates. Must be called within a Tokio runtime.
+    pub fn spawn(books: Arc<OrderBookManager>, queue_capacity: usize) -> Self {
+        let counters = Arc::new(Counters::default());
+        let (senders, workers) = (0..books.shard_count())
+            .map(|_| {
+                let (tx, rx) = mpsc::channel(queue_capacity.max(1));
+                let worker = tokio::spawn(run_worker(books.clone(), rx, counters.clone()));
+                (tx, worker)
+            })
+            .unzip();
+        Self {
+            books,
+            senders,
+            workers,
+            counters,
+        }
+    }
+
+    pub fn worker_count(&self) -> usize {
+        self.workers.len()
+    }
+
+    pub fn stats(&self) -> IngestStats {
+        IngestStats {
+            applied: self.counters.applied.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Route the book updates in `message` to their workers, waiting while a
+    /// worker's queue is full. Other messages are ignored.
+    pub async fn submit(&self, message: StreamMessage) -> Result<()> {
+        match message {
+            StreamMessage::Book(update) => {
+                let key = TokenKey::new(&update.asset_id);
+                self.send(&key, Work::Snapshot(update)).await
+            },
+            StreamMessage::PriceChange(change) => {
+                let timestamp = DateTime::from_timestamp_millis(change.timestamp as i64)
+                    .unwrap_or_else(Utc::now);
+                for entry in change.price_changes {
+                    let Some(size) = entry.size else {
+                        continue;
+                    };
+                    let key = TokenKey::new(&entry.asset_id);
+                    let delta = OrderDelta {
+                        token_id: entry.asset_id,
+                        timestamp,
+                        side: entry.side,
+                        price: entry.price,
+                        size,
+                        sequence: 0,
+                    };
+                    self.send(&key, Work::Delta(key.clone(), delta)).await?;
+                }
+                Ok(())
+            },
+            _ => Ok(()),
+        }
+    }
+
+    async fn send(&self, key: &TokenKey, work: Work) -> Result<()> {
+        self.senders[self.books.shard_of(key)]
+            .send(work)
+            .await
+            .map_err(|_| PolyfillError::internal_simple("Book ingest worker stopped"))
+    }
+
+    /// Feed a market-channel stream into the workers until it ends.
+    ///
+    /// Stream errors are logged and skipped.
+    pub async fn run<S>(&self, mut stream: S) -> Result<()>
+    where
+        S: Stream<Item = Result<StreamMessage>> + Unpin,
+    {
+        while let Some(message) = stream.next().await {
+            match message {
+                Ok(message) => self.submit(message).await?,
+                Err(e) => warn!("Market channel error: {}", e),
+            }
+        }
+        debug!("Market channel stream ended");
+        Ok(())
+    }
+
+    /// Stop accepting updates and wait until every queued one is applied
+    pub async fn shutdown(self) -> IngestStats {
+        drop(self.senders);
+        for worker in self.workers {
+            if let Err(e) = worker.await {
+                warn!("Book ingest worker failed: {}", e);
+            }
+        }
+        IngestStats {
+            applied: self.counters.applied.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+async fn run_worker(
+    books: Arc<OrderBookManager>,
+    mut rx: mpsc::Receiver<Work>,
+    counters: Arc<Counters>,
+) {
+    while let Some(work) = rx.recv().await {
+        let result = match work {
+            Work::Snapshot(update) => books.apply_book_update(&update),
+            Work::Delta(key, mut delta) => books.with_book_mut_by_key(&key, |book| {
+                delta.sequence = book.last_delta_sequence + 1;
+                book.apply_delta(delta)
+            }),
+        };
+        match result {
+            Ok(()) => counters.applied.fetch_add(1, Ordering::Relaxed),
+            Err(e) => {
+                debug!("Dropping book update: {}", e);
+                counters.failed.fetch_add(1, Ordering::Relaxed)
+            },
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn message(json: &str) -> StreamMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_updates_apply_in_order_per_token() {
+        let books = Arc::new(OrderBookManager::with_shard_count(10, 4));
+        let ingest = ShardedIngest::spawn(books.clone(), 2);
+        assert_eq!(ingest.worker_count(), 4);
+
+        let tokens: Vec<String> = (0..16).map(|i| format!("token-{i}")).collect();
+        let mut messages = Vec::new();
+        for token in &tokens {
+            messages.push(Ok(message(&format!(
+                r#"{{"event_type":"book","asset_id":"{token}","market":"0xabc","timestamp":"1",
+                    "bids":[{{"price":"0.40","size":"10"}}],"asks":[{{"price":"0.60","size":"10"}}]}}"#
+            ))));
+        }
+        for size in 1..=20 {
+            for token in &tokens {
+                messages.push(Ok(message(&format!(
+                    r#"{{"event_type":"price_change","market":"0xabc","timestamp":"2",
+                        "price_changes":[{{"asset_id":"{token}","price":"0.45","size":"{size}","side":"BUY"}}]}}"#
+                ))));
+            }
+        }
+        ingest.run(futures::stream::iter(messages)).await.unwrap();
+
+        let stats = ingest.shutdown().await;
+        assert_eq!(stats.applied, 16 * 21);
+        assert_eq!(stats.failed, 0);
+        for token in &tokens {
+            let book = books.get_book(token).unwrap();
+            assert_eq!(book.bids[0].price, dec!(0.45));
+            assert_eq!(book.bids[0].size, dec!(20));
+        }
+    }
+}
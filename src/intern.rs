@@ -0,0 +1,240 @@
+//! Interned token identifiers
+//!
+//! Token ids are 70+ character decimal strings that would otherwise be cloned
+//! into every book, fill and position. [`TokenKey`] interns each id once per
+//! process: clones are a reference-count bump, equality is usually a pointer
+//! comparison, and the hash is computed once so maps keyed by it
+//! ([`TokenMap`]) never rehash the string. Public APIs keep taking and
+//! returning plain strings; keys are created at those edges. Interned ids
+//! are never evicted, which is fine for the bounded set of tradable tokens.
+
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::sync::{Arc, OnceLock};
+
+/// Hash of a token id, as stored in [`crate::book::OrderBook::token_id_hash`]
+/// and [`crate::types::FastOrderDelta::token_id_hash`]
+#[inline]
+pub fn token_id_hash(token_id: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    token_id.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Interned token id with its precomputed hash
+#[derive(Clone)]
+pub struct TokenKey {
+    id: Arc<str>,
+    hash: u64,
+}
+
+impl TokenKey {
+    /// Intern `token_id` in the process-wide table
+    pub fn new(token_id: &str) -> Self {
+        TokenInterner::global().intern(token_id)
+    }
+
+    /// The key for `token_id` if it has been interned, without inserting it
+    pub fn lookup(token_id: &str) -> Option<Self> {
+        TokenInterner::global().get(token_id)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    /// Same value as [`token_id_hash`] of the id
+    #[inline]
+    pub fn hash_u64(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl PartialEq for TokenKey {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && (Arc::ptr_eq(&self.id, &other.id) || self.id == other.id)
+    }
+}
+
+impl Eq for TokenKey {}
+
+impl Hash for TokenKey {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl PartialEq<str> for TokenKey {
+    fn eq(&self, other: &str) -> bool {
+        &*self.id == other
+    }
+}
+
+impl PartialEq<&str> for TokenKey {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.id == *other
+    }
+}
+
+impl PartialEq<String> for TokenKey {
+    fn eq(&self, other: &String) -> bool {
+        *self.id == **other
+    }
+}
+
+impl std::ops::Deref for TokenKey {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl AsRef<str> for TokenKey {
+    fn as_ref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl std::fmt::Debug for TokenKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&*self.id, f)
+    }
+}
+
+impl std::fmt::Display for TokenKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+impl From<&str> for TokenKey {
+    fn from(token_id: &str) -> Self {
+        Self::new(token_id)
+    }
+}
+
+impl From<String> for TokenKey {
+    fn from(token_id: String) -> Self {
+        Self::new(&token_id)
+    }
+}
+
+impl From<&String> for TokenKey {
+    fn from(token_id: &String) -> Self {
+        Self::new(token_id)
+    }
+}
+
+impl From<TokenKey> for String {
+    fn from(key: TokenKey) -> Self {
+        key.id.to_string()
+    }
+}
+
+/// Passes a [`TokenKey`]'s precomputed hash straight through
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokenKeyHasher(u64);
+
+impl Hasher for TokenKeyHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    #[inline]
+    fn write_u64(&mut self, value: u64) {
+        self.0 = value;
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        // Only reached if something other than a TokenKey is hashed
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// Map keyed by [`TokenKey`] that reuses the precomputed hash
+pub type TokenMap<V> = HashMap<TokenKey, V, BuildHasherDefault<TokenKeyHasher>>;
+
+/// Table of interned token ids
+#[derive(Debug, Default)]
+pub struct TokenInterner {
+    keys: RwLock<HashMap<Arc<str>, u64>>,
+}
+
+impl TokenInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process-wide table used by [`TokenKey::new`]
+    pub fn global() -> &'static TokenInterner {
+        static GLOBAL: OnceLock<TokenInterner> = OnceLock::new();
+        GLOBAL.get_or_init(TokenInterner::new)
+    }
+
+    pub fn intern(&self, token_id: &str) -> TokenKey {
+        if let Some(key) = self.get(token_id) {
+            return key;
+        }
+        let mut keys = self.keys.write();
+        if let Some((id, hash)) = keys.get_key_value(token_id) {
+            return TokenKey {
+                id: id.clone(),
+                hash: *hash,
+            };
+        }
+        let id: Arc<str> = Arc::from(token_id);
+        let hash = token_id_hash(token_id);
+        keys.insert(id.clone(), hash);
+        TokenKey { id, hash }
+    }
+
+    pub fn get(&self, token_id: &str) -> Option<TokenKey> {
+        self.keys
+            .read()
+            .get_key_value(token_id)
+            .map(|(id, hash)| TokenKey {
+                id: id.clone(),
+                hash: *hash,
+            })
+    }
+
+    /// Number of distinct ids interned
+    pub fn len(&self) -> usize {
+        self.keys.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.read().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interned_keys_share_storage() {
+        let interner = TokenInterner::new();
+        let id = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+        let a = interner.intern(id);
+        let owned = String::from(id);
+        let b = interner.intern(owned.as_str());
+        assert!(Arc::ptr_eq(&a.id, &b.id));
+        assert_eq!(a, b);
+        assert_eq!(a, id);
+        assert_eq!(a.hash_u64(), token_id_hash(id));
+        assert_eq!(interner.len(), 1);
+        assert!(interner.get("other").is_none());
+
+        let mut map: TokenMap<u32> = TokenMap::default();
+        map.insert(a, 1);
+        assert_eq!(map.get(&b), Some(&1));
+    }
+}
@@ -0,0 +1,531 @@
+//! Order-intent write-ahead log
+//!
+//! A crash between posting an order and recording the response leaves a bot
+//! unable to tell whether the order exists: resubmitting risks a duplicate,
+//! skipping it risks a lost order. [`OrderJournal`] closes that gap. The
+//! signed order is appended and synced to disk under a caller-chosen
+//! `client_id` before it is posted, and the outcome is appended when the
+//! response arrives (see [`crate::ClobClient::post_order_journaled`]).
+//!
+//! On restart, intents without an outcome are [`OrderJournal::pending`].
+//! [`OrderJournal::reconcile`] looks for each one in `get_orders` and
+//! `get_trades`. Intents that never reached the exchange can be posted again
+//! with [`crate::ClobClient::resubmit_intent`]. That re-sends the exact
+//! signed order, so the exchange rejects it as a duplicate if it did land.
+
+use crate::api::ClobApi;
+use crate::errors::{PolyfillError, Result};
+use crate::types::{OpenOrder, OrderType, PostOrderOptions, Side, SignedOrderRequest, TradeParams};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::{info, warn};
+
+/// Allowed difference between local and exchange clocks when matching
+/// intents to orders and trades
+const CLOCK_SKEW_SECS: i64 = 5;
+
+/// An order about to be posted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderIntent {
+    pub client_id: String,
+    pub recorded_at: DateTime<Utc>,
+    pub token_id: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    /// Exactly what is posted
+    pub order: SignedOrderRequest,
+    pub order_type: OrderType,
+    pub post_only: bool,
+    #[serde(default)]
+    pub defer_exec: bool,
+}
+
+impl OrderIntent {
+    pub fn post_options(&self) -> PostOrderOptions {
+        PostOrderOptions {
+            order_type: self.order_type,
+            post_only: self.post_only,
+            defer_exec: self.defer_exec,
+        }
+    }
+}
+
+/// How [`OrderJournal::reconcile`] settled a pending intent
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum Resolution {
+    /// Found among open orders
+    Open { client_id: String, order_id: String },
+    /// Found among trades, so it was (at least partly) filled
+    Traded {
+        client_id: String,
+        order_id: String,
+        trade_id: String,
+    },
+    /// Not found on the exchange; still pending
+    Missing(OrderIntent),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
+enum Record {
+    Intent(OrderIntent),
+    Completed { client_id: String, order_id: String },
+    Failed { client_id: String, reason: String },
+}
+
+#[derive(Debug)]
+struct Inner {
+    file: File,
+    pending: HashMap<String, OrderIntent>,
+    /// Client ID to exchange order ID, if the order was placed
+    settled: HashMap<String, Option<String>>,
+}
+
+/// Durable, append-only log of order intents and their outcomes
+#[derive(Debug)]
+pub struct OrderJournal {
+    path: PathBuf,
+    inner: Mutex<Inner>,
+}
+
+impl OrderJournal {
+    /// Open (or create) the journal at `path` and replay it
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut pending = HashMap::new();
+        let mut settled = HashMap::new();
+
+        let body = match std::fs::read_to_string(&path) {
+            Ok(body) => body,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(io_error(&path, e)),
+        };
+        let mut valid_len = 0;
+        let mut lines = body.split_inclusive('\n').peekable();
+        while let Some(line) = lines.next() {
+            let record = match serde_json::from_str::<Record>(line) {
+                Ok(record) => record,
+                Err(_) if line.trim().is_empty() => {
+                    valid_len += line.len();
+                    continue;
+                },
+                // A crash mid-append leaves a torn last line
+                Err(e) if lines.peek().is_none() => {
+                    warn!("Dropping incomplete last journal entry: {}", e);
+                    break;
+                },
+                Err(e) => return Err(e.into()),
+            };
+            valid_len += line.len();
+            match record {
+                Record::Intent(intent) => {
+                    pending.insert(intent.client_id.clone(), intent);
+                },
+                Record::Completed {
+                    client_id,
+                    order_id,
+                } => {
+                    pending.remove(&client_id);
+                    settled.insert(client_id, Some(order_id));
+                },
+                Record::Failed { client_id, .. } => {
+                    pending.remove(&client_id);
+                    settled.insert(client_id, None);
+                },
+            }
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| io_error(&path, e))?;
+        if valid_len < body.len() {
+            // Appends must start on a fresh line
+            file.set_len(valid_len as u64)
+                .map_err(|e| io_error(&path, e))?;
+        }
+        if !pending.is_empty() {
+            info!("Order journal has {} pending intents", pending.len());
+        }
+        Ok(Self {
+            path,
+            inner: Mutex::new(Inner {
+                file,
+                pending,
+                settled,
+            }),
+        })
+    }
+
+    /// Intents posted (or about to be) without a recorded outcome, oldest
+    /// first
+    pub fn pending(&self) -> Vec<OrderIntent> {
+        let mut pending: Vec<_> = self.inner.lock().pending.values().cloned().collect();
+        pending.sort_by_key(|intent| intent.recorded_at);
+        pending
+    }
+
+    /// Exchange order ID recorded for `client_id`, if it was placed
+    pub fn order_id(&self, client_id: &str) -> Option<String> {
+        self.inner.lock().settled.get(client_id).cloned().flatten()
+    }
+
+    /// Durably record an intent; fails if `client_id` was used before
+    pub fn record_intent(&self, intent: &OrderIntent) -> Result<()> {
+        let mut inner = self.inner.lock();
+        if inner.pending.contains_key(&intent.client_id)
+            || inner.settled.contains_key(&intent.client_id)
+        {
+            return Err(PolyfillError::validation(format!(
+                "Client ID {} was already submitted",
+                intent.client_id
+            )));
+        }
+        self.append(&mut inner, &Record::Intent(intent.clone()))?;
+        inner
+            .pending
+            .insert(intent.client_id.clone(), intent.clone());
+        Ok(())
+    }
+
+    /// Record that the intent's order was placed as `order_id`
+    pub fn record_completed(&self, client_id: &str, order_id: &str) -> Result<()> {
+        let mut inner = self.inner.lock();
+        self.append(
+            &mut inner,
+            &Record::Completed {
+                client_id: client_id.to_string(),
+                order_id: order_id.to_string(),
+            },
+        )?;
+        inner.pending.remove(client_id);
+        inner
+            .settled
+            .insert(client_id.to_string(), Some(order_id.to_string()));
+        Ok(())
+    }
+
+    /// Record that the intent's order was rejected or abandoned
+    pub fn record_failed(&self, client_id: &str, reason: &str) -> Result<()> {
+        let mut inner = self.inner.lock();
+        self.append(
+            &mut inner,
+            &Record::Failed {
+                client_id: client_id.to_string(),
+                reason: reason.to_string(),
+            },
+        )?;
+        inner.pending.remove(client_id);
+        inner.settled.insert(client_id.to_string(), None);
+        Ok(())
+    }
+
+    fn append(&self, inner: &mut Inner, record: &Record) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        inner
+            .file
+            .write_all(&line)
+            .and_then(|()| inner.file.sync_data())
+            .map_err(|e| io_error(&self.path, e))
+    }
+
+    /// Look for every pending intent on the exchange and record the ones
+    /// found as completed.
+    ///
+    /// Intents are matched on token, side, price, size and time, oldest
+    /// first, and each exchange order is claimed at most once. Filled orders
+    /// no longer open are matched against the user's side of trades: the
+    /// taker order of taker trades, filled at the limit price or better, or
+    /// a maker order at exactly the limit price. An intent stays
+    /// [`Resolution::Missing`] when trades of more than one order match it;
+    /// resubmitting it is safe, since the exchange rejects the signed order
+    /// if it already landed.
+    pub async fn reconcile<A: ClobApi>(&self, api: &A) -> Result<Vec<Resolution>> {
+        let pending = self.pending();
+        let Some(oldest) = pending.first().map(|intent| intent.recorded_at) else {
+            return Ok(Vec::new());
+        };
+
+        let mut claimed: HashSet<String> = self
+            .inner
+            .lock()
+            .settled
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        let open_orders = api.get_orders(None, None).await?;
+        let params = TradeParams {
+            id: None,
+            maker_address: None,
+            market: None,
+            asset_id: None,
+            before: None,
+            after: Some((oldest.timestamp() - CLOCK_SKEW_SECS).max(0) as u64),
+        };
+        let trades = api.get_trades(Some(&params), None).await?;
+
+        let mut resolutions = Vec::with_capacity(pending.len());
+        for intent in pending {
+            let since = intent.recorded_at.timestamp() - CLOCK_SKEW_SECS;
+            if let Some(order) = open_orders
+                .iter()
+                .find(|order| !claimed.contains(&order.id) && order_matches(&intent, order, since))
+            {
+                claimed.insert(order.id.clone());
+                self.record_completed(&intent.client_id, &order.id)?;
+                resolutions.push(Resolution::Open {
+                    client_id: intent.client_id,
+                    order_id: order.id.clone(),
+                });
+                continue;
+            }
+
+            let mut fills = trades
+                .iter()
+                .flat_map(|trade| matching_fills(&intent, trade, since))
+                .filter(|(order_id, _)| !claimed.contains(*order_id));
+            let traded = fills.next().and_then(|(order_id, trade_id)| {
+                if fills.any(|(other, _)| other != order_id) {
+                    warn!(
+                        "Trades of several orders match intent {}; leaving it pending",
+                        intent.client_id
+                    );
+                    return None;
+                }
+                Some((order_id.to_string(), trade_id.to_string()))
+            });
+            match traded {
+                Some((order_id, trade_id)) => {
+                    claimed.insert(order_id.clone());
+                    self.record_completed(&intent.client_id, &order_id)?;
+                    resolutions.push(Resolution::Traded {
+                        client_id: intent.client_id,
+                        order_id,
+                        trade_id,
+                    });
+                },
+                None => resolutions.push(Resolution::Missing(intent)),
+            }
+        }
+        Ok(resolutions)
+    }
+}
+
+fn order_matches(intent: &OrderIntent, order: &OpenOrder, since: i64) -> bool {
+    order.asset_id == intent.token_id
+        && order.side == intent.side
+        && order.price == intent.price
+        && order.original_size == intent.size
+        && order.created_at as i64 >= since
+}
+
+/// `(order_id, trade_id)` of the user's orders in `trade` that could be
+/// `intent`
+fn matching_fills<'a>(
+    intent: &OrderIntent,
+    trade: &'a Value,
+    since: i64,
+) -> Vec<(&'a str, &'a str)> {
+    let field = |value: &'a Value, name: &str| value.get(name).and_then(Value::as_str);
+    let decimal = |value: &'a Value, name: &str| {
+        field(value, name).and_then(|number| Decimal::from_str(number).ok())
+    };
+    let matched_at = field(trade, "match_time")
+        .and_then(|time| i64::from_str(time).ok())
+        .unwrap_or_default();
+    let Some(trade_id) = field(trade, "id").filter(|_| matched_at >= since) else {
+        return Vec::new();
+    };
+    let same_leg = |leg: &'a Value| {
+        field(leg, "asset_id") == Some(intent.token_id.as_str())
+            && field(leg, "side").and_then(|side| Side::from_str(side).ok()) == Some(intent.side)
+    };
+    // Maker legs of other users share the trade
+    let mine = |leg: &'a Value| {
+        ["owner", "maker_address"]
+            .iter()
+            .any(|key| field(trade, key).is_some() && field(leg, key) == field(trade, key))
+    };
+
+    // The top-level fields describe the taker, the user's own order unless
+    // the user was a maker
+    if field(trade, "trader_side") != Some("MAKER") {
+        let within_limit = decimal(trade, "price").is_some_and(|price| match intent.side {
+            Side::BUY => price <= intent.price,
+            Side::SELL => price >= intent.price,
+        });
+        let within_size = decimal(trade, "size").is_some_and(|size| size <= intent.size);
+        return match field(trade, "taker_order_id") {
+            Some(order_id) if same_leg(trade) && within_limit && within_size => {
+                vec![(order_id, trade_id)]
+            },
+            _ => Vec::new(),
+        };
+    }
+    trade
+        .get("maker_orders")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|leg| {
+            mine(leg)
+                && same_leg(leg)
+                && decimal(leg, "price") == Some(intent.price)
+                && decimal(leg, "matched_amount").is_some_and(|size| size <= intent.size)
+        })
+        .filter_map(|leg| Some((field(leg, "order_id")?, trade_id)))
+        .collect()
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> PolyfillError {
+    PolyfillError::internal(format!("Order journal {} failed", path.display()), e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FakeClob, FakeToken};
+    use crate::types::OrderArgs;
+    use rust_decimal_macros::dec;
+
+    fn intent(client_id: &str, price: Decimal) -> OrderIntent {
+        OrderIntent {
+            client_id: client_id.to_string(),
+            recorded_at: Utc::now(),
+            token_id: "1".to_string(),
+            side: Side::BUY,
+            price,
+            size: dec!(10),
+            order: serde_json::from_value(serde_json::json!({
+                "salt": 1, "maker": "0x0", "signer": "0x0", "tokenId": "1",
+                "makerAmount": "5000000", "takerAmount": "10000000", "expiration": "0",
+                "side": "BUY", "signatureType": 0, "timestamp": "0", "metadata": "0x0",
+                "builder": "0x0", "signature": "0x0"
+            }))
+            .unwrap(),
+            order_type: OrderType::GTC,
+            post_only: false,
+            defer_exec: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pending_intents_survive_restart_and_reconcile() {
+        let path =
+            std::env::temp_dir().join(format!("polyfill-journal-{}.log", uuid::Uuid::new_v4()));
+        let fake = FakeClob::new();
+        fake.set_token("1", FakeToken::new("0xabc"));
+
+        {
+            let journal = OrderJournal::open(&path).unwrap();
+            journal.record_intent(&intent("landed", dec!(0.5))).unwrap();
+            journal.record_intent(&intent("lost", dec!(0.4))).unwrap();
+            journal
+                .record_intent(&intent("rejected", dec!(0.3)))
+                .unwrap();
+            journal.record_failed("rejected", "invalid price").unwrap();
+            assert!(journal.record_intent(&intent("landed", dec!(0.5))).is_err());
+            // Crash after the exchange accepted "landed" but before its response
+            fake.create_and_post_order(
+                &OrderArgs::new("1".parse().unwrap(), dec!(0.5), dec!(10), Side::BUY),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        // Torn write at the end of the log
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"{\"type\":\"intent\",\"client").unwrap();
+
+        let journal = OrderJournal::open(&path).unwrap();
+        assert_eq!(journal.pending().len(), 2);
+        let resolutions = journal.reconcile(&fake).await.unwrap();
+        assert!(
+            matches!(&resolutions[0], Resolution::Open { client_id, .. } if client_id == "landed")
+        );
+        assert!(
+            matches!(&resolutions[1], Resolution::Missing(intent) if intent.client_id == "lost")
+        );
+        assert!(journal.order_id("landed").is_some());
+        assert_eq!(journal.pending().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_matches_trades_on_the_users_order() {
+        let path =
+            std::env::temp_dir().join(format!("polyfill-journal-{}.log", uuid::Uuid::new_v4()));
+        let journal = OrderJournal::open(&path).unwrap();
+        for (client_id, price, size) in [
+            ("unrelated", dec!(0.45), dec!(10)),
+            ("maker", dec!(0.40), dec!(10)),
+            ("taker", dec!(0.55), dec!(10)),
+            ("ambiguous", dec!(0.35), dec!(20)),
+        ] {
+            let mut intent = intent(client_id, price);
+            intent.size = size;
+            journal.record_intent(&intent).unwrap();
+        }
+        let now = Utc::now().timestamp().to_string();
+        let fake = FakeClob::new();
+        let taker = |id: &str, order_id: &str, price: &str, size: &str| {
+            serde_json::json!({
+                "id": id, "taker_order_id": order_id, "asset_id": "1", "side": "BUY",
+                "price": price, "size": size, "match_time": now, "trader_side": "TAKER"
+            })
+        };
+        // Another order's fill above the limit, and one too large
+        fake.add_trade(taker("t1", "other-1", "0.60", "10"));
+        fake.add_trade(taker("t2", "other-2", "0.30", "25"));
+        // The user made the 0.40 bid; the taker's order is someone else's
+        fake.add_trade(serde_json::json!({
+            "id": "t3", "taker_order_id": "counterparty", "asset_id": "1", "side": "SELL",
+            "price": "0.40", "size": "10", "match_time": now, "trader_side": "MAKER",
+            "owner": "me",
+            "maker_orders": [
+                {"order_id": "someone-else", "owner": "other", "asset_id": "1",
+                 "side": "BUY", "price": "0.40", "matched_amount": "4"},
+                {"order_id": "mine", "owner": "me", "asset_id": "1",
+                 "side": "BUY", "price": "0.40", "matched_amount": "6"}
+            ]
+        }));
+        // Filled below its 0.55 limit
+        fake.add_trade(taker("t4", "taker-order", "0.52", "10"));
+        // Two orders at 0.35 or better could each be the intent
+        fake.add_trade(taker("t5", "cheap-1", "0.35", "20"));
+        fake.add_trade(taker("t6", "cheap-2", "0.34", "20"));
+
+        let resolutions = journal.reconcile(&fake).await.unwrap();
+        assert!(
+            matches!(&resolutions[0], Resolution::Missing(intent) if intent.client_id == "unrelated")
+        );
+        assert!(matches!(
+            &resolutions[1],
+            Resolution::Traded { order_id, trade_id, .. } if order_id == "mine" && trade_id == "t3"
+        ));
+        assert!(matches!(
+            &resolutions[2],
+            Resolution::Traded { order_id, .. } if order_id == "taker-order"
+        ));
+        assert!(
+            matches!(&resolutions[3], Resolution::Missing(intent) if intent.client_id == "ambiguous")
+        );
+        assert_eq!(journal.pending().len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,269 @@
+//! Price ladder views for dashboards
+//!
+//! [`BookView`] turns a book snapshot into what a ladder UI draws: levels
+//! with cumulative depth, markers for the user's own resting orders and the
+//! most recent trades. It serializes to JSON as is, so a dashboard can take
+//! it straight off a channel or an HTTP endpoint.
+//!
+//! ```rust,no_run
+//! use polyfill_rs::{BookView, OrderBookManager, TradeTape};
+//! use polyfill_rs::reconcile::OrderTracker;
+//!
+//! # fn example(books: &OrderBookManager, tracker: &OrderTracker, tape: &TradeTape) -> polyfill_rs::Result<()> {
+//! let view = BookView::from_snapshot(&books.get_book("123")?, Some(10))
+//!     .with_my_orders(&tracker.open_orders())
+//!     .with_trades(tape.trades("123"));
+//! println!("{}", serde_json::to_string(&view)?);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::reconcile::TrackedOrder;
+use crate::types::{BookLevel, LastTradePrice, OrderBook, Side, StreamMessage};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// One price level on the ladder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LadderLevel {
+    pub price: Decimal,
+    pub size: Decimal,
+    /// Size at this level and every level closer to the top of the book
+    pub cumulative_size: Decimal,
+    /// Cost of taking everything down to and including this level
+    pub cumulative_notional: Decimal,
+    /// Remaining size of the user's own orders resting here
+    pub my_size: Decimal,
+    /// Ids of the user's own orders resting here
+    pub my_orders: Vec<String>,
+}
+
+/// A recent trade to mark on the ladder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeMarker {
+    pub price: Decimal,
+    pub size: Option<Decimal>,
+    /// Taker side, when the venue reports it
+    pub side: Option<Side>,
+    /// Milliseconds since the epoch
+    pub timestamp: u64,
+}
+
+impl From<&LastTradePrice> for TradeMarker {
+    fn from(trade: &LastTradePrice) -> Self {
+        Self {
+            price: trade.price,
+            size: trade.size,
+            side: trade.side,
+            timestamp: trade.timestamp,
+        }
+    }
+}
+
+/// A serializable price ladder for one token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BookView {
+    pub token_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub best_bid: Option<Decimal>,
+    pub best_ask: Option<Decimal>,
+    pub mid: Option<Decimal>,
+    pub spread: Option<Decimal>,
+    /// Best bid first
+    pub bids: Vec<LadderLevel>,
+    /// Best ask first
+    pub asks: Vec<LadderLevel>,
+    /// Most recent trade first
+    pub trades: Vec<TradeMarker>,
+}
+
+impl BookView {
+    /// Build a ladder from a snapshot, keeping `depth` levels per side
+    pub fn from_snapshot(book: &OrderBook, depth: Option<usize>) -> Self {
+        let depth = depth.unwrap_or(usize::MAX);
+        let best_bid = book.bids.first().map(|level| level.price);
+        let best_ask = book.asks.first().map(|level| level.price);
+        let (mid, spread) = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) => (Some((bid + ask) / Decimal::TWO), Some(ask - bid)),
+            _ => (None, None),
+        };
+        Self {
+            token_id: book.token_id.clone(),
+            timestamp: book.timestamp,
+            best_bid,
+            best_ask,
+            mid,
+            spread,
+            bids: ladder(&book.bids, depth),
+            asks: ladder(&book.asks, depth),
+            trades: Vec::new(),
+        }
+    }
+
+    /// Mark the user's resting orders for this token.
+    ///
+    /// Orders priced outside the levels in the view are not shown.
+    pub fn with_my_orders<'a>(
+        mut self,
+        orders: impl IntoIterator<Item = &'a TrackedOrder>,
+    ) -> Self {
+        for order in orders {
+            if order.asset_id != self.token_id {
+                continue;
+            }
+            let levels = match order.side {
+                Side::BUY => &mut self.bids,
+                Side::SELL => &mut self.asks,
+            };
+            if let Some(level) = levels.iter_mut().find(|level| level.price == order.price) {
+                level.my_size += order.original_size - order.size_matched;
+                level.my_orders.push(order.id.clone());
+            }
+        }
+        self
+    }
+
+    /// Attach recent trades, most recent first
+    pub fn with_trades(mut self, trades: impl IntoIterator<Item = TradeMarker>) -> Self {
+        self.trades = trades.into_iter().collect();
+        self.trades
+            .sort_by_key(|trade| std::cmp::Reverse(trade.timestamp));
+        self
+    }
+}
+
+fn ladder(levels: &[BookLevel], depth: usize) -> Vec<LadderLevel> {
+    let mut cumulative_size = Decimal::ZERO;
+    let mut cumulative_notional = Decimal::ZERO;
+    levels
+        .iter()
+        .take(depth)
+        .map(|level| {
+            cumulative_size += level.size;
+            cumulative_notional += level.price * level.size;
+            LadderLevel {
+                price: level.price,
+                size: level.size,
+                cumulative_size,
+                cumulative_notional,
+                my_size: Decimal::ZERO,
+                my_orders: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Keeps the last trades seen per token for [`BookView::with_trades`]
+#[derive(Debug, Clone)]
+pub struct TradeTape {
+    capacity: usize,
+    trades: std::collections::HashMap<String, VecDeque<TradeMarker>>,
+}
+
+impl TradeTape {
+    /// Keep the last `capacity` trades of each token
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            trades: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Record a trade if `message` is one
+    pub fn observe(&mut self, message: &StreamMessage) {
+        if let StreamMessage::LastTradePrice(trade) = message {
+            self.record(&trade.asset_id, TradeMarker::from(trade));
+        }
+    }
+
+    pub fn record(&mut self, token_id: &str, trade: TradeMarker) {
+        let trades = self.trades.entry(token_id.to_string()).or_default();
+        if trades.len() >= self.capacity {
+            trades.pop_front();
+        }
+        trades.push_back(trade);
+    }
+
+    /// Trades kept for `token_id`, most recent first
+    pub fn trades(&self, token_id: &str) -> Vec<TradeMarker> {
+        self.trades
+            .get(token_id)
+            .map(|trades| trades.iter().rev().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn level(price: Decimal, size: Decimal) -> BookLevel {
+        BookLevel { price, size }
+    }
+
+    #[test]
+    fn test_book_view_accumulates_depth_and_marks_orders() {
+        let book = OrderBook {
+            token_id: "1".to_string(),
+            timestamp: Utc::now(),
+            bids: vec![level(dec!(0.50), dec!(10)), level(dec!(0.49), dec!(20))],
+            asks: vec![
+                level(dec!(0.52), dec!(5)),
+                level(dec!(0.53), dec!(5)),
+                level(dec!(0.54), dec!(5)),
+            ],
+            sequence: 0,
+            last_delta_sequence: 0,
+            last_snapshot_timestamp_ms: 0,
+        };
+        let mine = TrackedOrder {
+            id: "order-1".to_string(),
+            market: "0xabc".to_string(),
+            asset_id: "1".to_string(),
+            side: Side::BUY,
+            price: dec!(0.49),
+            original_size: dec!(8),
+            size_matched: dec!(3),
+            expiration: None,
+            maker_amount: None,
+        };
+        let mut other_token = mine.clone();
+        other_token.asset_id = "2".to_string();
+
+        let mut tape = TradeTape::new(2);
+        for (price, timestamp) in [(dec!(0.50), 1), (dec!(0.51), 2), (dec!(0.52), 3)] {
+            tape.record(
+                "1",
+                TradeMarker {
+                    price,
+                    size: None,
+                    side: None,
+                    timestamp,
+                },
+            );
+        }
+
+        let view = BookView::from_snapshot(&book, Some(2))
+            .with_my_orders([&mine, &other_token])
+            .with_trades(tape.trades("1"));
+
+        assert_eq!(view.mid, Some(dec!(0.51)));
+        assert_eq!(view.spread, Some(dec!(0.02)));
+        assert_eq!(view.asks.len(), 2);
+        assert_eq!(view.bids[1].cumulative_size, dec!(30));
+        assert_eq!(view.bids[1].cumulative_notional, dec!(14.80));
+        assert_eq!(view.bids[1].my_size, dec!(5));
+        assert_eq!(view.bids[1].my_orders, vec!["order-1".to_string()]);
+        assert!(view.bids[0].my_orders.is_empty());
+        let timestamps: Vec<u64> = view.trades.iter().map(|trade| trade.timestamp).collect();
+        assert_eq!(timestamps, vec![3, 2]);
+
+        let json = serde_json::to_value(&view).unwrap();
+        assert_eq!(json["bids"][1]["cumulative_size"], "30");
+        let back: BookView = serde_json::from_value(json).unwrap();
+        assert_eq!(back, view);
+    }
+}
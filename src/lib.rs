@@ -37,7 +37,7 @@
 //!
 //!     // Create and post order
 //!     let order_args = OrderArgs::new(
-//!         "token_id",
+//!         "123456".parse()?,
 //!         Decimal::from_str("0.75").unwrap(),
 //!         Decimal::from_str("100.0").unwrap(),
 //!         Side::BUY,
@@ -78,6 +78,7 @@ use tracing::info;
 // Global constants
 pub const DEFAULT_CHAIN_ID: u64 = 137; // Polygon
 pub const DEFAULT_BASE_URL: &str = "https://clob.polymarket.com";
+pub const DEFAULT_WS_BASE_URL: &str = "wss://ws-subscriptions-clob.polymarket.com";
 pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
 pub const DEFAULT_MAX_RETRIES: u32 = 3;
 pub const DEFAULT_RATE_LIMIT_RPS: u32 = 100;
@@ -90,6 +91,8 @@ pub fn init() {
 
 // Re-export main types
 pub use crate::types::{
+    AccountSummary,
+    AccountSummaryOptions,
     ApiCredentials,
     // Additional compatibility types
     ApiKeysResponse,
@@ -101,12 +104,15 @@ pub use crate::types::{
     BatchMidpointResponse,
     BatchPriceRequest,
     BatchPriceResponse,
+    BookDepth,
     BookParams,
     ClientConfig,
     ClientResult,
+    DataApiPosition,
     FeeRateResponse,
     FillEvent,
     Market,
+    MarketRemainder,
     MarketSnapshot,
     MarketsResponse,
     MidpointResponse,
@@ -122,6 +128,7 @@ pub use crate::types::{
     OrderStatus,
     OrderSummary,
     OrderType,
+    PartialMarketOrder,
     PriceResponse,
     PricesHistoryInterval,
     PricesHistoryResponse,
@@ -139,6 +146,8 @@ pub use crate::types::{
     RfqQuotesParams,
     RfqRequestData,
     RfqRequestsParams,
+    ShutdownOptions,
+    ShutdownReport,
     Side,
     SimplifiedMarket,
     SimplifiedMarketsResponse,
@@ -146,6 +155,7 @@ pub use crate::types::{
     StreamMessage,
     TickSizeResponse,
     Token,
+    TokenId,
     TokenPrice,
     TradeParams,
     WssAuth,
@@ -154,38 +164,174 @@ pub use crate::types::{
 };
 
 // Re-export client
-pub use crate::client::{ClobClient, PolyfillClient};
+pub use crate::client::{ClobClient, Deadline, PolyfillClient};
+pub use crate::compat::{ClobApiVersion, CompatibilityReport};
 
 // Re-export compatibility types (for easy migration from polymarket-rs-client)
 pub use crate::types::OrderArgs;
 
 // Re-export error types
-pub use crate::errors::{PolyfillError, Result};
+pub use crate::errors::{ApiErrorContext, PolyfillError, Result};
+pub use tokio_util::sync::CancellationToken;
 
 // Re-export advanced components
-pub use crate::book::{OrderBook as OrderBookImpl, OrderBookManager};
-pub use crate::decode::Decoder;
-pub use crate::fill::{FillEngine, FillResult};
-pub use crate::stream::{MarketStream, StreamManager, WebSocketBookApplier, WebSocketStream};
+pub use crate::activity::{ActivityMetrics, ActivitySnapshot, ActivityThresholds, ActivityWarning};
+pub use crate::alerts::{Alert, AlertCondition, AlertEngine, AlertId};
+pub use crate::audit::{AuditEntry, AuditEvent, AuditLog};
+pub use crate::basket::{BasketExecution, BasketLeg, NegRiskBasket};
+pub use crate::book::{
+    BookSides, ExecutionEstimate, FastBookView, LevelAge, LocalQuote, OrderBook as OrderBookImpl,
+    OrderBookManager,
+};
+pub use crate::buying_power::{
+    BuyingPower, BuyingPowerCalculator, PendingSettlement, SettlementTracker,
+};
+pub use crate::chain::{RpcClient, TransactionSender, TxEvent, TxManager};
+pub use crate::credentials::{
+    CommandProvider, Credentials, CredentialsChain, CredentialsProvider, EnvProvider, FileProvider,
+    SecretsDirProvider,
+};
+pub use crate::decode::{Decoder, StreamEventType, StreamFrame};
+pub use crate::dedup::{DuplicateGuard, DuplicateReservation, DuplicateTolerance};
+pub use crate::diagnostics::{DiagnosticsCapture, ParseFailureDump};
+pub use crate::dns::DnsCache;
+pub use crate::fill::{FillEngine, FillResult, ShadowFillEngine};
+pub use crate::fill_probability::{FillEstimate, FillProbabilityEstimator, PassiveQuote};
+pub use crate::fixed::{Ticks, Units};
+pub use crate::funding::{Funding, FundingPlan};
+pub use crate::handlers::EventHandlers;
+pub use crate::hedge::{BinaryExposure, ComplementHedger, HedgeOrder};
+pub use crate::http_cache::{ResponseCache, ResponseCacheStats};
+pub use crate::ingest::{IngestStats, ShardedIngest};
+pub use crate::intern::TokenKey;
+pub use crate::journal::{OrderIntent, OrderJournal, Resolution};
+pub use crate::ladder::{BookView, LadderLevel, TradeMarker, TradeTape};
+pub use crate::maintenance::{JobStats, MaintenanceHandle, MaintenanceScheduler};
+pub use crate::mark::{Mark, MarkMethod, MarkPrice, MarkSource, OneSidedFallback};
+pub use crate::metadata::{MarketMetadata, MetadataCache, MetadataChange};
+pub use crate::nonce::{NonceManager, NonceStats};
+pub use crate::opportunity::{
+    Opportunity, OpportunityDetector, OpportunityKind, OpportunityRules, SuggestedOrder,
+};
+pub use crate::orders::{verify_signed_order, OrderVerification};
+pub use crate::price_band::PriceBand;
+pub use crate::price_feed::{
+    Basis, BookFeed, FnFeed, ManualFeed, PriceFeed, ReferenceLink, ReferencePrice, ReferencePrices,
+};
+pub use crate::quote_analytics::{
+    DistanceBucket, LifetimeStats, MarkoutStats, QuoteAnalytics, QuoteAnalyticsConfig,
+    QuoteAnalyticsReport,
+};
+pub use crate::recovery::{load_state, save_state, RuntimeState};
+pub use crate::redeem::{RedeemAutomation, RedeemPolicy, RedemptionEvent, RedemptionSender};
+pub use crate::report::{
+    MarketStats, PeriodStats, ReportFill, ReportPeriod, TradeReport, TradeStats,
+};
+pub use crate::resolution::{HaltReason, ResolutionGuard};
+pub use crate::roles::{MarketDataClient, TradingClient};
+pub use crate::run_report::{LatencySummary, RunRecorder, RunReport};
+pub use crate::runtime::LowLatencyConfig;
+pub use crate::schedule::{Blackout, ScheduleGuard, TradingSchedule, TradingWindow};
+pub use crate::stream::{
+    DisconnectEvent, MarketStream, ReconnectConfig, ReconnectEvent, StreamManager,
+    SubscriptionState, SubscriptionStatus, TokenTraffic, TokenTrafficStats, WebSocketBookApplier,
+    WebSocketStream, WsEndpoint, UNATTRIBUTED_TOKEN, WS_MARKET_PATH, WS_USER_PATH,
+};
+pub use crate::tradability::{
+    TradabilityInputs, TradabilityRules, TradabilityScore, TradabilityWeights, UpdateRate,
+};
+pub use crate::trade_flow::{TradeCluster, TradeFlowConfig, TradeFlowDetector, TradeFlowEvent};
+pub use crate::units::{Cents, Percent, Probability};
+pub use crate::watchlist::{Watchlist, WatchlistChange, WatchlistWatcher};
+pub use crate::webhook::{WebhookConfig, WebhookEvent, WebhookForwarder};
+pub use crate::window::{ClosedWindow, StreamWindows, WindowSource, WindowSpec, WindowStats};
+pub use crate::ws_corpus::{CorpusEntry, CorpusReport, ReplayCorpus, ReplayOutcome};
 pub use crate::ws_hot_path::{WsBookApplyStats, WsBookUpdateProcessor};
+pub use crate::ws_transport::{
+    BoxedTransport, SocketOptions, TungsteniteConnector, WsConnector, WsTransport,
+};
 
 // Re-export utilities
 pub use crate::utils::{crypto, math, rate_limit, retry, time, url};
 
 // Module declarations
+pub mod activity;
+pub mod alerts;
+pub mod api;
+pub mod audit;
 pub mod auth;
+pub mod backfill;
+pub mod balances;
+pub mod basket;
 pub mod book;
+#[cfg(any(test, feature = "test-util"))]
+pub mod book_fuzz;
+pub mod buying_power;
+pub mod capture;
+pub mod chain;
+pub mod chaos;
 pub mod client;
+pub mod client_config;
+pub mod compat;
 pub mod connection_manager;
+pub mod credentials;
 pub mod decode;
+pub mod dedup;
+pub mod diagnostics;
+pub mod dns;
 pub mod errors;
+pub mod expiry;
+#[cfg(feature = "arrow")]
+pub mod export;
 pub mod fill;
+pub mod fill_probability;
+pub mod fixed;
+pub mod funding;
+pub mod handlers;
+pub mod hedge;
+pub mod http_cache;
 pub mod http_config;
+pub mod ingest;
+pub mod intern;
+pub mod journal;
+pub mod ladder;
+pub mod maintenance;
+pub mod mark;
+pub mod metadata;
+pub mod nonce;
+pub mod opportunity;
 pub mod orders;
+pub mod price_band;
+pub mod price_feed;
+pub mod quote_analytics;
+pub mod reconcile;
+pub mod reconstruct;
+pub mod recovery;
+pub mod redeem;
+pub mod report;
+pub mod resolution;
+pub mod roles;
+pub mod run_report;
+pub mod runtime;
+pub mod schedule;
+pub mod sim;
+#[cfg(feature = "simulator")]
+pub mod simulator;
+pub mod sizing;
+#[cfg(feature = "state")]
+pub mod state;
 pub mod stream;
+pub mod tradability;
+pub mod trade_flow;
 pub mod types;
+pub mod units;
 pub mod utils;
+pub mod watchlist;
+pub mod webhook;
+pub mod window;
+pub mod ws_corpus;
 pub mod ws_hot_path;
+pub mod ws_transport;
 
 // Benchmarks
 #[cfg(test)]
@@ -237,22 +383,28 @@ mod tests {
     #[test]
     fn test_order_args_creation() {
         let args = OrderArgs::new(
-            "test_token",
+            "123456".parse().unwrap(),
             Decimal::from_str("0.75").unwrap(),
             Decimal::from_str("100.0").unwrap(),
             Side::BUY,
         );
 
-        assert_eq!(args.token_id, "test_token");
+        assert_eq!(args.token_id, "123456");
+        assert_eq!(
+            args.token_id.to_u256(),
+            alloy_primitives::U256::from(123456u64)
+        );
         assert_eq!(args.side, Side::BUY);
     }
 
     #[test]
-    fn test_order_args_default() {
-        let args = OrderArgs::default();
-        assert_eq!(args.token_id, "");
-        assert_eq!(args.price, Decimal::ZERO);
-        assert_eq!(args.size, Decimal::ZERO);
-        assert_eq!(args.side, Side::BUY);
+    fn test_token_id_rejects_malformed_ids() {
+        assert!("".parse::<TokenId>().is_err());
+        assert!("test_token".parse::<TokenId>().is_err());
+        assert!(serde_json::from_str::<TokenId>("\"12a\"").is_err());
+
+        let id: TokenId = serde_json::from_str("\"42\"").unwrap();
+        assert_eq!(id, TokenId::from(alloy_primitives::U256::from(42u64)));
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"42\"");
     }
 }
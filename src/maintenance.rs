@@ -0,0 +1,333 @@
+//! Periodic maintenance jobs
+//!
+//! Long-running bots need the same housekeeping loops: dropping stale books,
+//! refreshing market metadata, reconciling orders and auditing local books
+//! against REST snapshots. [`MaintenanceScheduler`] runs them at their own
+//! intervals on background tasks. Each wait is stretched by a random jitter
+//! so jobs across processes don't hit the API in lockstep, and a run that is
+//! due while the previous one is still going is skipped instead of piling up.
+//!
+//! Build one bound to a client with [`crate::ClobClient::maintenance`]:
+//!
+//! ```no_run
+//! # use polyfill_rs::ClobClient;
+//! # use std::time::Duration;
+//! # fn example(client: &ClobClient) {
+//! let handle = client
+//!     .maintenance()
+//!     .with_stale_book_cleanup(Duration::from_secs(60), Duration::from_secs(300))
+//!     .with_snapshot_audit(Duration::from_secs(120))
+//!     .start();
+//! // Jobs stop when the handle is dropped
+//! # drop(handle);
+//! # }
+//! ```
+
+use crate::api::ClobApi;
+use crate::client::ClobClient;
+use crate::errors::Result;
+use crate::expiry::ExpirySweeper;
+use crate::reconcile::Reconciler;
+use crate::types::TokenId;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use rand::Rng;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+type JobFn = Arc<dyn Fn() -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+struct Job {
+    name: String,
+    interval: Duration,
+    run: JobFn,
+    running: AtomicBool,
+    runs: AtomicU64,
+    failures: AtomicU64,
+    skipped: AtomicU64,
+}
+
+impl std::fmt::Debug for Job {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Job")
+            .field("name", &self.name)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+/// Counters of one maintenance job
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JobStats {
+    pub name: String,
+    pub runs: u64,
+    pub failures: u64,
+    /// Runs skipped because the previous run was still in progress
+    pub skipped: u64,
+}
+
+/// Builder for a set of periodic maintenance jobs
+pub struct MaintenanceScheduler {
+    client: ClobClient,
+    jobs: Vec<Arc<Job>>,
+    jitter: Duration,
+}
+
+impl std::fmt::Debug for MaintenanceScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MaintenanceScheduler")
+            .field(
+                "jobs",
+                &self.jobs.iter().map(|j| &j.name).collect::<Vec<_>>(),
+            )
+            .field("jitter", &self.jitter)
+            .finish()
+    }
+}
+
+impl MaintenanceScheduler {
+    /// Create a scheduler whose built-in jobs use `client`
+    pub fn new(client: ClobClient) -> Self {
+        Self {
+            client,
+            jobs: Vec::new(),
+            jitter: Duration::from_secs(1),
+        }
+    }
+
+    /// Maximum random delay added to every wait between runs
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Run `job` every `interval`; errors are logged and counted
+    pub fn with_job<F, Fut>(mut self, name: &str, interval: Duration, job: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.jobs.push(Arc::new(Job {
+            name: name.to_string(),
+            interval,
+            run: Arc::new(move || job().boxed()),
+            running: AtomicBool::new(false),
+            runs: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+            skipped: AtomicU64::new(0),
+        }));
+        self
+    }
+
+    /// Drop local books not updated within `max_age`
+    pub fn with_stale_book_cleanup(self, interval: Duration, max_age: Duration) -> Self {
+        let client = self.client.clone();
+        self.with_job("stale_book_cleanup", interval, move || {
+            let books = client.local_books();
+            async move {
+                let removed = books?.cleanup_stale_books(max_age)?;
+                if removed > 0 {
+                    info!("Removed {} stale order books", removed);
+                }
+                Ok(())
+            }
+        })
+    }
+
+    /// Keep cached market metadata for `token_ids` current
+    pub fn with_metadata_refresh(self, interval: Duration, token_ids: Vec<TokenId>) -> Self {
+        let client = self.client.clone();
+        let token_ids = Arc::new(token_ids);
+        self.with_job("metadata_refresh", interval, move || {
+            let client = client.clone();
+            let token_ids = token_ids.clone();
+            async move {
+                for change in client.refresh_market_metadata(&token_ids).await {
+                    info!("Market metadata for {} changed", change.token_id);
+                }
+                Ok(())
+            }
+        })
+    }
+
+    /// Heal the reconciler's order tracker against the exchange
+    pub fn with_reconciliation<A>(self, interval: Duration, reconciler: Arc<Reconciler<A>>) -> Self
+    where
+        A: ClobApi + 'static,
+    {
+        self.with_job("reconciliation", interval, move || {
+            let reconciler = reconciler.clone();
+            async move {
+                reconciler.reconcile_once().await?;
+                Ok(())
+            }
+        })
+    }
+
+    /// Drop expired GTD orders and repost the sweeper's rolling quotes
+    pub fn with_expiry_sweep<A>(self, interval: Duration, sweeper: Arc<ExpirySweeper<A>>) -> Self
+    where
+        A: ClobApi + 'static,
+    {
+        self.with_job("expiry_sweep", interval, move || {
+            let sweeper = sweeper.clone();
+            async move {
+                sweeper.sweep_once().await?;
+                Ok(())
+            }
+        })
+    }
+
+    /// Re-seed local books whose top of book drifted from REST
+    pub fn with_snapshot_audit(self, interval: Duration) -> Self {
+        let client = self.client.clone();
+        self.with_job("snapshot_audit", interval, move || {
+            let client = client.clone();
+            async move {
+                client.audit_order_books().await?;
+                Ok(())
+            }
+        })
+    }
+
+    /// Start every job on its own background task.
+    ///
+    /// Must be called within a Tokio runtime. The first run of each job is
+    /// one interval (plus jitter) after starting.
+    pub fn start(self) -> MaintenanceHandle {
+        let cancel = CancellationToken::new();
+        for job in &self.jobs {
+            let job = job.clone();
+            let cancel = cancel.clone();
+            let jitter = self.jitter;
+            tokio::spawn(async move {
+                loop {
+                    let wait = job.interval + random_jitter(jitter);
+                    tokio::select! {
+                        _ = cancel.cancelled() => return,
+                        _ = tokio::time::sleep(wait) => {},
+                    }
+                    trigger(&job);
+                }
+            });
+        }
+        MaintenanceHandle {
+            jobs: self.jobs,
+            cancel,
+        }
+    }
+}
+
+fn random_jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(rand::thread_rng().gen_range(0..=max.as_millis() as u64))
+}
+
+/// Start a run of `job` unless one is already in progress
+fn trigger(job: &Arc<Job>) -> bool {
+    if job.running.swap(true, Ordering::AcqRel) {
+        job.skipped.fetch_add(1, Ordering::Relaxed);
+        debug!("Skipping {}: previous run still in progress", job.name);
+        return false;
+    }
+    let job = job.clone();
+    tokio::spawn(async move {
+        // Clears `running` even if the job panics
+        let _guard = RunningGuard(&job.running);
+        job.runs.fetch_add(1, Ordering::Relaxed);
+        if let Err(e) = (job.run)().await {
+            job.failures.fetch_add(1, Ordering::Relaxed);
+            warn!("Maintenance job {} failed: {}", job.name, e);
+        }
+    });
+    true
+}
+
+struct RunningGuard<'a>(&'a AtomicBool);
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+/// Running maintenance jobs; dropping it stops them
+#[derive(Debug)]
+pub struct MaintenanceHandle {
+    jobs: Vec<Arc<Job>>,
+    cancel: CancellationToken,
+}
+
+impl MaintenanceHandle {
+    /// Run the job named `name` now, outside its schedule.
+    ///
+    /// Returns `false` if there is no such job or it is already running.
+    pub fn run_now(&self, name: &str) -> bool {
+        self.jobs
+            .iter()
+            .find(|job| job.name == name)
+            .is_some_and(trigger)
+    }
+
+    pub fn stats(&self) -> Vec<JobStats> {
+        self.jobs
+            .iter()
+            .map(|job| JobStats {
+                name: job.name.clone(),
+                runs: job.runs.load(Ordering::Relaxed),
+                failures: job.failures.load(Ordering::Relaxed),
+                skipped: job.skipped.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Stop scheduling runs; runs in progress finish on their own
+    pub fn stop(&self) {
+        self.cancel.cancel();
+    }
+}
+
+impl Drop for MaintenanceHandle {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_overlapping_runs_are_skipped() {
+        let handle = MaintenanceScheduler::new(ClobClient::new("http://localhost"))
+            .with_jitter(Duration::ZERO)
+            .with_job("slow", Duration::from_secs(10), || async {
+                tokio::time::sleep(Duration::from_secs(25)).await;
+                Ok(())
+            })
+            .with_job("failing", Duration::from_secs(10), || async {
+                Err(crate::errors::PolyfillError::internal_simple("boom"))
+            })
+            .start();
+
+        tokio::time::sleep(Duration::from_secs(45)).await;
+        let stats = handle.stats();
+        // Starts at 10s and 40s; the 20s and 30s ticks overlap the first run
+        assert_eq!(stats[0].runs, 2);
+        assert_eq!(stats[0].skipped, 2);
+        assert_eq!(stats[1].runs, 4);
+        assert_eq!(stats[1].failures, 4);
+
+        assert!(!handle.run_now("slow"));
+        assert!(!handle.run_now("missing"));
+        handle.stop();
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(handle.stats()[1].runs, 4);
+    }
+}
@@ -0,0 +1,252 @@
+//! Mark price methodologies
+//!
+//! PnL marking and trigger logic need one price per book, and desks disagree
+//! on which. A [`MarkPrice`] picks the method:
+//!
+//! - [`MarkMethod::Mid`]: halfway between best bid and ask
+//! - [`MarkMethod::SizeWeightedMid`]: the touch prices weighted by the size
+//!   on the opposite side, leaning toward the side about to be taken out
+//! - [`MarkMethod::LastTrade`]: the last trade, pulled inside the touch so a
+//!   stale print never marks through the book
+//!
+//! Mid and size-weighted mid need both sides. When one side has emptied,
+//! common near resolution, [`OneSidedFallback`] decides what happens instead
+//! of the mark silently going missing, and [`MarkPrice::mark_with_source`]
+//! reports which input the mark came from. With [`MarkPrice::with_clamp`]
+//! the result is also kept inside `[tick, 1 - tick]`.
+
+use crate::book::{BookSides, OrderBook};
+use crate::types::BookLevel;
+use rust_decimal::Decimal;
+
+/// Tick used for clamping books that have not seen a tick size yet
+const FALLBACK_TICK: Decimal = Decimal::from_parts(1, 0, 0, false, 4);
+
+/// How a book is reduced to one price
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MarkMethod {
+    #[default]
+    Mid,
+    SizeWeightedMid,
+    /// Last trade clamped to `[best bid, best ask]`; mid when there is no
+    /// trade yet
+    LastTrade,
+}
+
+/// What to mark a book at when only one side has liquidity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OneSidedFallback {
+    /// No mark
+    #[default]
+    None,
+    /// The last trade, clamped against the remaining side
+    LastTrade,
+    /// The remaining side's best price
+    FarTouch,
+}
+
+/// Input a mark was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarkSource {
+    Mid,
+    SizeWeightedMid,
+    LastTrade,
+    FarTouch,
+}
+
+/// A mark and how it was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mark {
+    pub price: Decimal,
+    pub source: MarkSource,
+    pub sides: BookSides,
+}
+
+/// Mark price configuration
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MarkPrice {
+    pub method: MarkMethod,
+    pub clamp: bool,
+    pub one_sided: OneSidedFallback,
+}
+
+impl MarkPrice {
+    pub fn new(method: MarkMethod) -> Self {
+        Self {
+            method,
+            ..Self::default()
+        }
+    }
+
+    /// Keep marks inside `[tick, 1 - tick]`
+    pub fn with_clamp(mut self, clamp: bool) -> Self {
+        self.clamp = clamp;
+        self
+    }
+
+    /// Mark one-sided books with `fallback` instead of giving up
+    pub fn with_one_sided(mut self, fallback: OneSidedFallback) -> Self {
+        self.one_sided = fallback;
+        self
+    }
+
+    /// Mark `book`, given the last trade price if known
+    pub fn mark(&self, book: &OrderBook, last_trade: Option<Decimal>) -> Option<Decimal> {
+        self.mark_with_source(book, last_trade)
+            .map(|mark| mark.price)
+    }
+
+    /// Like [`Self::mark`], also reporting the book's sides and which input
+    /// produced the mark
+    pub fn mark_with_source(&self, book: &OrderBook, last_trade: Option<Decimal>) -> Option<Mark> {
+        let sides = book.sides();
+        let bid = book.best_bid();
+        let ask = book.best_ask();
+        let (price, source) = match (self.method, last_trade) {
+            (MarkMethod::LastTrade, Some(last)) => {
+                (inside_touch(last, bid, ask), MarkSource::LastTrade)
+            },
+            _ if sides.is_one_sided() => match (self.one_sided, last_trade) {
+                (OneSidedFallback::None, _) => return None,
+                (OneSidedFallback::LastTrade, last) => {
+                    (inside_touch(last?, bid, ask), MarkSource::LastTrade)
+                },
+                (OneSidedFallback::FarTouch, _) => (bid.or(ask)?.price, MarkSource::FarTouch),
+            },
+            (MarkMethod::SizeWeightedMid, _) => {
+                let (bid, ask) = (bid?, ask?);
+                let total = bid.size + ask.size;
+                if total.is_zero() {
+                    (book.mid_price()?, MarkSource::Mid)
+                } else {
+                    (
+                        (bid.price * ask.size + ask.price * bid.size) / total,
+                        MarkSource::SizeWeightedMid,
+                    )
+                }
+            },
+            (MarkMethod::Mid | MarkMethod::LastTrade, _) => (book.mid_price()?, MarkSource::Mid),
+        };
+        let price = if self.clamp {
+            let tick = book
+                .fast_view()
+                .tick_size()
+                .map(|tick| tick.to_decimal())
+                .filter(|tick| !tick.is_zero())
+                .unwrap_or(FALLBACK_TICK);
+            price.clamp(tick, Decimal::ONE - tick)
+        } else {
+            price
+        };
+        Some(Mark {
+            price,
+            source,
+            sides,
+        })
+    }
+}
+
+/// `price` pulled into `[bid, ask]`, using whichever sides exist
+fn inside_touch(price: Decimal, bid: Option<BookLevel>, ask: Option<BookLevel>) -> Decimal {
+    let price = bid.map_or(price, |bid| price.max(bid.price));
+    ask.map_or(price, |ask| price.min(ask.price))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderDelta, Side};
+    use chrono::Utc;
+    use rust_decimal_macros::dec;
+
+    fn book(levels: &[(Side, Decimal, Decimal)]) -> OrderBook {
+        let mut book = OrderBook::new("1", 10);
+        for (i, &(side, price, size)) in levels.iter().enumerate() {
+            book.apply_delta(OrderDelta {
+                token_id: "1".to_string(),
+                timestamp: Utc::now(),
+                side,
+                price,
+                size,
+                sequence: i as u64 + 1,
+            })
+            .unwrap();
+        }
+        book
+    }
+
+    #[test]
+    fn test_mark_methods() {
+        let two_sided = book(&[
+            (Side::BUY, dec!(0.40), dec!(300)),
+            (Side::SELL, dec!(0.44), dec!(100)),
+        ]);
+        let mark = |method, last| MarkPrice::new(method).mark(&two_sided, last);
+        assert_eq!(mark(MarkMethod::Mid, None), Some(dec!(0.42)));
+        // Heavy bid pulls the mark toward the ask
+        assert_eq!(mark(MarkMethod::SizeWeightedMid, None), Some(dec!(0.43)));
+        assert_eq!(
+            mark(MarkMethod::LastTrade, Some(dec!(0.41))),
+            Some(dec!(0.41))
+        );
+        assert_eq!(
+            mark(MarkMethod::LastTrade, Some(dec!(0.90))),
+            Some(dec!(0.44))
+        );
+        assert_eq!(mark(MarkMethod::LastTrade, None), Some(dec!(0.42)));
+
+        // Near resolution only a bid at the edge is left
+        let mut one_sided = book(&[(Side::BUY, dec!(0.99), dec!(50))]);
+        one_sided.set_tick_size(dec!(0.01)).unwrap();
+        assert_eq!(MarkPrice::default().mark(&one_sided, None), None);
+        let last_trade = MarkPrice::new(MarkMethod::LastTrade);
+        assert_eq!(
+            last_trade.mark(&one_sided, Some(dec!(0.95))),
+            Some(dec!(0.99))
+        );
+        assert_eq!(last_trade.mark(&one_sided, Some(dec!(1))), Some(dec!(1)));
+        assert_eq!(
+            last_trade.with_clamp(true).mark(&one_sided, Some(dec!(1))),
+            Some(dec!(0.99))
+        );
+    }
+
+    #[test]
+    fn test_one_sided_fallbacks() {
+        let ask_only = book(&[(Side::SELL, dec!(0.03), dec!(80))]);
+        assert_eq!(ask_only.sides(), BookSides::AskOnly);
+        assert_eq!(ask_only.mid_price(), None);
+
+        let mid = MarkPrice::default();
+        assert_eq!(mid.mark_with_source(&ask_only, Some(dec!(0.02))), None);
+
+        let far_touch = mid.with_one_sided(OneSidedFallback::FarTouch);
+        assert_eq!(
+            far_touch.mark_with_source(&ask_only, None),
+            Some(Mark {
+                price: dec!(0.03),
+                source: MarkSource::FarTouch,
+                sides: BookSides::AskOnly,
+            })
+        );
+
+        let last_trade = mid.with_one_sided(OneSidedFallback::LastTrade);
+        assert_eq!(last_trade.mark(&ask_only, None), None);
+        let mark = last_trade
+            .mark_with_source(&ask_only, Some(dec!(0.05)))
+            .unwrap();
+        assert_eq!(
+            (mark.price, mark.source),
+            (dec!(0.03), MarkSource::LastTrade)
+        );
+
+        // Two-sided books ignore the fallback
+        let two_sided = book(&[
+            (Side::BUY, dec!(0.01), dec!(10)),
+            (Side::SELL, dec!(0.03), dec!(10)),
+        ]);
+        let mark = far_touch.mark_with_source(&two_sided, None).unwrap();
+        assert_eq!((mark.price, mark.source), (dec!(0.02), MarkSource::Mid));
+        assert_eq!(book(&[]).sides(), BookSides::Empty);
+    }
+}
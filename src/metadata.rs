@@ -0,0 +1,173 @@
+//! Cached order metadata
+//!
+//! Every order needs the token's tick size and neg-risk flag. Fetching both
+//! per order costs two serial round trips, so [`MetadataCache`] keeps them per
+//! token: neg-risk never changes for a market and is kept indefinitely, while
+//! tick sizes expire after a maximum age. Feed market-channel messages to
+//! [`MetadataCache::apply`] to pick up `tick_size_change` events as they
+//! happen. Warm the cache for a watchlist with
+//! [`crate::ClobClient::preload_order_metadata`].
+//!
+//! Long-running bots can also keep the full [`MarketMetadata`] of their
+//! markets current with [`crate::ClobClient::spawn_metadata_refresh`], which
+//! re-fetches it on an interval and reports each [`MetadataChange`].
+
+use crate::intern::{TokenKey, TokenMap};
+use crate::types::StreamMessage;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Trading parameters of one token's market
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketMetadata {
+    pub tick_size: Decimal,
+    pub min_order_size: Decimal,
+    pub neg_risk: bool,
+    pub accepting_orders: bool,
+    pub fee_rate_bps: u32,
+}
+
+/// Market metadata that differs from what was cached before a refresh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetadataChange {
+    pub token_id: String,
+    pub previous: MarketMetadata,
+    pub current: MarketMetadata,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    tick_size: Option<(Decimal, Instant)>,
+    neg_risk: Option<bool>,
+    market: Option<MarketMetadata>,
+}
+
+/// Order and market metadata per token
+#[derive(Debug)]
+pub struct MetadataCache {
+    entries: RwLock<TokenMap<Entry>>,
+    tick_size_max_age: Duration,
+}
+
+impl Default for MetadataCache {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(300))
+    }
+}
+
+impl MetadataCache {
+    /// Create a cache whose tick sizes expire `tick_size_max_age` after they
+    /// were fetched
+    pub fn new(tick_size_max_age: Duration) -> Self {
+        Self {
+            entries: RwLock::new(TokenMap::default()),
+            tick_size_max_age,
+        }
+    }
+
+    /// Cached tick size, if present and not expired
+    pub fn tick_size(&self, token_id: &str) -> Option<Decimal> {
+        let key = TokenKey::lookup(token_id)?;
+        let entries = self.entries.read();
+        let (tick_size, fetched_at) = entries.get(&key)?.tick_size?;
+        (fetched_at.elapsed() < self.tick_size_max_age).then_some(tick_size)
+    }
+
+    pub fn neg_risk(&self, token_id: &str) -> Option<bool> {
+        let key = TokenKey::lookup(token_id)?;
+        self.entries.read().get(&key)?.neg_risk
+    }
+
+    /// Last refreshed market metadata, with any tick size change applied
+    pub fn market(&self, token_id: &str) -> Option<MarketMetadata> {
+        let key = TokenKey::lookup(token_id)?;
+        self.entries.read().get(&key)?.market
+    }
+
+    pub fn set_tick_size(&self, token_id: &str, tick_size: Decimal) {
+        let mut entries = self.entries.write();
+        let entry = entries.entry(TokenKey::new(token_id)).or_default();
+        entry.tick_size = Some((tick_size, Instant::now()));
+        if let Some(market) = &mut entry.market {
+            market.tick_size = tick_size;
+        }
+    }
+
+    pub fn set_neg_risk(&self, token_id: &str, neg_risk: bool) {
+        self.entries
+            .write()
+            .entry(TokenKey::new(token_id))
+            .or_default()
+            .neg_risk = Some(neg_risk);
+    }
+
+    /// Store freshly fetched market metadata, also refreshing the tick size
+    /// and neg-risk used for orders.
+    ///
+    /// Returns the change if different metadata was cached before.
+    pub fn update(&self, token_id: &str, metadata: MarketMetadata) -> Option<MetadataChange> {
+        let mut entries = self.entries.write();
+        let entry = entries.entry(TokenKey::new(token_id)).or_default();
+        entry.tick_size = Some((metadata.tick_size, Instant::now()));
+        entry.neg_risk = Some(metadata.neg_risk);
+        let previous = entry.market.replace(metadata)?;
+        (previous != metadata).then(|| MetadataChange {
+            token_id: token_id.to_string(),
+            previous,
+            current: metadata,
+        })
+    }
+
+    /// Drop everything cached for `token_id`
+    pub fn invalidate(&self, token_id: &str) {
+        if let Some(key) = TokenKey::lookup(token_id) {
+            self.entries.write().remove(&key);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.entries.write().clear();
+    }
+
+    /// Apply a `tick_size_change` event; other messages are ignored
+    pub fn apply(&self, message: &StreamMessage) {
+        if let StreamMessage::TickSizeChange(change) = message {
+            debug!(
+                "Tick size for {} changed from {} to {}",
+                change.asset_id, change.old_tick_size, change.new_tick_size
+            );
+            self.set_tick_size(&change.asset_id, change.new_tick_size);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_tick_sizes_expire_and_follow_changes() {
+        let cache = MetadataCache::new(Duration::ZERO);
+        cache.set_tick_size("1", dec!(0.01));
+        cache.set_neg_risk("1", true);
+        assert_eq!(cache.tick_size("1"), None);
+        assert_eq!(cache.neg_risk("1"), Some(true));
+
+        let cache = MetadataCache::default();
+        cache.set_tick_size("1", dec!(0.01));
+        let change: StreamMessage = serde_json::from_str(
+            r#"{"event_type":"tick_size_change","asset_id":"1","market":"0xabc",
+                "old_tick_size":"0.01","new_tick_size":"0.001","timestamp":"1"}"#,
+        )
+        .unwrap();
+        cache.apply(&change);
+        assert_eq!(cache.tick_size("1"), Some(dec!(0.001)));
+
+        cache.invalidate("1");
+        assert_eq!(cache.tick_size("1"), None);
+        assert_eq!(cache.neg_risk("2"), None);
+    }
+}
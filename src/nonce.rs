@@ -0,0 +1,207 @@
+//! Order salts and signing timestamps
+//!
+//! Every signed payload carries a value that must not repeat: the salt of
+//! an order and the timestamp of L1 and L2 headers. [`NonceManager`] hands
+//! them out from one place, so they only ever move forward across threads.
+//!
+//! Salts are `now_ms * 1000` plus a per-manager offset, bumped past the last
+//! salt issued, so they are unique within a process, sort in signing order
+//! and show roughly when an order was signed. They stay below 2^53 so
+//! JavaScript consumers read them exactly.
+//!
+//! Without persistence a restart relies on the wall clock having moved on.
+//! [`NonceManager::with_state_file`] also records a ceiling above every salt
+//! handed out, rewritten about once a minute, and starts above it after a
+//! restart, so a clock that steps back cannot repeat a salt.
+
+use crate::errors::{PolyfillError, Result};
+use crate::utils::time::now_millis;
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+/// Salts per millisecond of wall-clock time
+const SALTS_PER_MS: u64 = 1000;
+
+/// How far past the last salt a persisted ceiling reaches: one minute
+const CEILING_RESERVE: u64 = 60_000 * SALTS_PER_MS;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedState {
+    salt_ceiling: u64,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    last_salt: u64,
+    last_timestamp_ms: u64,
+    salts_issued: u64,
+    /// Salts at or above this must be persisted before use; 0 without a file
+    salt_ceiling: u64,
+}
+
+/// What a [`NonceManager`] has handed out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceStats {
+    pub salts_issued: u64,
+    pub last_salt: u64,
+    pub last_timestamp_ms: u64,
+    /// Persisted ceiling, if the manager has a state file
+    pub salt_ceiling: Option<u64>,
+}
+
+/// Monotonic salts and timestamps for signed payloads
+#[derive(Debug)]
+pub struct NonceManager {
+    offset: u64,
+    state: Mutex<State>,
+    state_file: Option<PathBuf>,
+}
+
+impl Default for NonceManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NonceManager {
+    /// A manager that keeps its state in memory
+    pub fn new() -> Self {
+        Self {
+            offset: rand::thread_rng().gen_range(0..SALTS_PER_MS),
+            state: Mutex::new(State::default()),
+            state_file: None,
+        }
+    }
+
+    /// A manager that persists its salt ceiling to `path`, starting above
+    /// the ceiling a previous run left there
+    pub fn with_state_file(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let persisted = match std::fs::read(&path) {
+            Ok(body) => serde_json::from_slice::<PersistedState>(&body)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => PersistedState::default(),
+            Err(e) => {
+                return Err(PolyfillError::internal(
+                    format!("Failed to read {}", path.display()),
+                    e,
+                ))
+            },
+        };
+        let manager = Self::new();
+        {
+            let mut state = manager.state.lock();
+            state.last_salt = persisted.salt_ceiling;
+        }
+        Ok(Self {
+            state_file: Some(path),
+            ..manager
+        })
+    }
+
+    /// The process-wide manager used by order builders and auth headers
+    /// unless one is configured
+    pub fn global() -> Arc<NonceManager> {
+        static GLOBAL: OnceLock<Arc<NonceManager>> = OnceLock::new();
+        GLOBAL.get_or_init(|| Arc::new(NonceManager::new())).clone()
+    }
+
+    /// A salt greater than every salt this manager issued before
+    pub fn next_salt(&self) -> Result<u64> {
+        let mut state = self.state.lock();
+        let salt = (now_millis() * SALTS_PER_MS + self.offset).max(state.last_salt + 1);
+        if let Some(path) = &self.state_file {
+            if salt >= state.salt_ceiling {
+                let ceiling = salt + CEILING_RESERVE;
+                persist(path, ceiling)?;
+                state.salt_ceiling = ceiling;
+            }
+        }
+        state.last_salt = salt;
+        state.salts_issued += 1;
+        Ok(salt)
+    }
+
+    /// Wall-clock milliseconds, never less than a previous reading
+    pub fn timestamp_ms(&self) -> u64 {
+        let mut state = self.state.lock();
+        let timestamp = now_millis().max(state.last_timestamp_ms);
+        state.last_timestamp_ms = timestamp;
+        timestamp
+    }
+
+    /// Wall-clock seconds, never less than a previous reading
+    pub fn timestamp_secs(&self) -> u64 {
+        self.timestamp_ms() / 1000
+    }
+
+    pub fn stats(&self) -> NonceStats {
+        let state = self.state.lock();
+        NonceStats {
+            salts_issued: state.salts_issued,
+            last_salt: state.last_salt,
+            last_timestamp_ms: state.last_timestamp_ms,
+            salt_ceiling: self.state_file.as_ref().map(|_| state.salt_ceiling),
+        }
+    }
+}
+
+/// Write the ceiling through a temporary file and a rename
+fn persist(path: &Path, salt_ceiling: u64) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let body = serde_json::to_vec(&PersistedState { salt_ceiling })?;
+    std::fs::write(&tmp, body)
+        .map_err(|e| PolyfillError::internal(format!("Failed to write {}", tmp.display()), e))?;
+    std::fs::rename(&tmp, path)
+        .map_err(|e| PolyfillError::internal(format!("Failed to replace {}", path.display()), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_salts_increase_across_threads_and_restarts() {
+        let manager = Arc::new(NonceManager::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let manager = manager.clone();
+                std::thread::spawn(move || {
+                    (0..500)
+                        .map(|_| manager.next_salt().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let mut salts = std::collections::HashSet::new();
+        for handle in handles {
+            let issued = handle.join().unwrap();
+            assert!(issued.windows(2).all(|pair| pair[0] < pair[1]));
+            salts.extend(issued);
+        }
+        assert_eq!(salts.len(), 2000);
+        assert!(salts.iter().all(|salt| *salt < 1 << 53));
+        assert_eq!(manager.stats().salts_issued, 2000);
+
+        let dir = std::env::temp_dir().join(format!("polyfill-nonce-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("nonces.json");
+        let first = NonceManager::with_state_file(&path).unwrap();
+        let salt = first.next_salt().unwrap();
+        let ceiling = first.stats().salt_ceiling.unwrap();
+        assert!(ceiling > salt);
+
+        // A restart starts above the ceiling, even if the clock stepped back
+        let restarted = NonceManager::with_state_file(&path).unwrap();
+        assert!(restarted.next_salt().unwrap() > ceiling);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let a = manager.timestamp_ms();
+        assert!(manager.timestamp_ms() >= a);
+    }
+}
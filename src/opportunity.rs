@@ -0,0 +1,427 @@
+//! Spread-capture opportunity detection
+//!
+//! [`OpportunityDetector`] keeps top of book for a set of watched tokens and
+//! checks every update against each token's [`OpportunityRules`]:
+//!
+//! - a spread at least `min_capture_spread` wide is worth quoting inside
+//! - a spread at most `max_take_spread` with enough depth is worth taking
+//! - a top-of-book level left behind by the last trade and unchanged for
+//!   longer than `stale_after` can be picked off
+//!
+//! Matches are emitted as [`Opportunity`] values with suggested orders.
+//! Nothing is sent to the exchange; the caller's strategy decides whether
+//! and how to act.
+
+use crate::book::{LevelAge, OrderBookManager};
+use crate::errors::Result;
+use crate::intern::TokenKey;
+use crate::types::{OrderArgs, OrderDelta, OrderType, Side, StreamMessage, TokenId};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// When a watched token counts as an opportunity
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpportunityRules {
+    /// Spreads at least this wide are reported for quoting inside
+    pub min_capture_spread: Option<Decimal>,
+    /// Spreads at most this wide are reported for taking
+    pub max_take_spread: Option<Decimal>,
+    /// A quote through the last trade and unchanged this long is stale
+    pub stale_after: Option<Duration>,
+    /// How far through the last trade a stale quote must be
+    pub stale_edge: Decimal,
+    /// Top-of-book size required before suggesting to take it
+    pub min_depth: Decimal,
+    /// Largest suggested order size
+    pub max_size: Decimal,
+    /// Price increment used to improve on the touch
+    pub tick_size: Decimal,
+    /// Minimum time between two reports of the same kind for a token
+    pub cooldown: Duration,
+}
+
+impl Default for OpportunityRules {
+    fn default() -> Self {
+        Self {
+            min_capture_spread: None,
+            max_take_spread: None,
+            stale_after: None,
+            stale_edge: Decimal::new(1, 2),
+            min_depth: Decimal::ZERO,
+            max_size: Decimal::from(100),
+            tick_size: Decimal::new(1, 2),
+            cooldown: Duration::from_secs(1),
+        }
+    }
+}
+
+impl OpportunityRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capture_spread(mut self, min_spread: Decimal) -> Self {
+        self.min_capture_spread = Some(min_spread);
+        self
+    }
+
+    pub fn with_take_spread(mut self, max_spread: Decimal) -> Self {
+        self.max_take_spread = Some(max_spread);
+        self
+    }
+
+    pub fn with_stale_quotes(mut self, stale_after: Duration, edge: Decimal) -> Self {
+        self.stale_after = Some(stale_after);
+        self.stale_edge = edge;
+        self
+    }
+
+    pub fn with_min_depth(mut self, min_depth: Decimal) -> Self {
+        self.min_depth = min_depth;
+        self
+    }
+
+    pub fn with_max_size(mut self, max_size: Decimal) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    pub fn with_tick_size(mut self, tick_size: Decimal) -> Self {
+        self.tick_size = tick_size;
+        self
+    }
+
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+}
+
+/// Condition that matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpportunityKind {
+    /// Spread wide enough to quote both sides inside it
+    WideSpread,
+    /// Spread tight and deep enough to take
+    TightSpread,
+    /// Quote not updated after the last trade moved through it
+    StaleQuote,
+}
+
+/// Order the detector suggests; the caller decides whether to send it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestedOrder {
+    pub side: Side,
+    pub price: Decimal,
+    pub size: Decimal,
+    pub order_type: OrderType,
+}
+
+impl SuggestedOrder {
+    pub fn to_order_args(&self, token_id: &str) -> Result<OrderArgs> {
+        Ok(OrderArgs::new(
+            TokenId::new(token_id)?,
+            self.price,
+            self.size,
+            self.side,
+        ))
+    }
+}
+
+/// A watched token meeting one of its rules
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opportunity {
+    pub token_id: String,
+    pub kind: OpportunityKind,
+    pub best_bid: Decimal,
+    pub best_ask: Decimal,
+    /// Milliseconds since the epoch, from the triggering message
+    pub timestamp: u64,
+    pub orders: Vec<SuggestedOrder>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    last_trade: Option<Decimal>,
+    last_reported: HashMap<OpportunityKind, u64>,
+}
+
+/// Watches tokens for spread, depth and staleness conditions
+#[derive(Debug)]
+pub struct OpportunityDetector {
+    books: OrderBookManager,
+    rules: HashMap<String, OpportunityRules>,
+    state: HashMap<String, TokenState>,
+    events: broadcast::Sender<Opportunity>,
+}
+
+impl Default for OpportunityDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpportunityDetector {
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(1024);
+        Self {
+            books: OrderBookManager::new(16),
+            rules: HashMap::new(),
+            state: HashMap::new(),
+            events,
+        }
+    }
+
+    /// Watch `token_id` under `rules`, replacing earlier rules
+    pub fn watch(&mut self, token_id: &str, rules: OpportunityRules) {
+        self.rules.insert(token_id.to_string(), rules);
+    }
+
+    pub fn unwatch(&mut self, token_id: &str) {
+        self.rules.remove(token_id);
+        self.state.remove(token_id);
+    }
+
+    /// Receive every opportunity reported from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Opportunity> {
+        self.events.subscribe()
+    }
+
+    /// Apply a market-channel message and report what it uncovered
+    pub fn observe(&mut self, message: &StreamMessage) -> Vec<Opportunity> {
+        let mut touched = Vec::new();
+        match message {
+            StreamMessage::Book(update)
+                if self.rules.contains_key(&update.asset_id)
+                    && self.books.apply_book_update(update).is_ok() =>
+            {
+                touched.push((update.asset_id.clone(), update.timestamp));
+            },
+            StreamMessage::PriceChange(change) => {
+                let timestamp = DateTime::from_timestamp_millis(change.timestamp as i64)
+                    .unwrap_or_else(Utc::now);
+                for entry in &change.price_changes {
+                    let Some(size) = entry.size else {
+                        continue;
+                    };
+                    if !self.rules.contains_key(&entry.asset_id) {
+                        continue;
+                    }
+                    let key = TokenKey::new(&entry.asset_id);
+                    let applied = self.books.with_book_mut_by_key(&key, |book| {
+                        book.apply_delta(OrderDelta {
+                            token_id: entry.asset_id.clone(),
+                            timestamp,
+                            side: entry.side,
+                            price: entry.price,
+                            size,
+                            sequence: book.last_delta_sequence + 1,
+                        })
+                    });
+                    if applied.is_ok() {
+                        touched.push((entry.asset_id.clone(), change.timestamp));
+                    }
+                }
+            },
+            StreamMessage::LastTradePrice(trade) if self.rules.contains_key(&trade.asset_id) => {
+                self.state_mut(&trade.asset_id).last_trade = Some(trade.price);
+                touched.push((trade.asset_id.clone(), trade.timestamp));
+            },
+            _ => {},
+        }
+        touched.dedup();
+        touched
+            .into_iter()
+            .flat_map(|(token_id, timestamp)| self.evaluate(&token_id, timestamp))
+            .collect()
+    }
+
+    /// Re-check every watched token at `now` (milliseconds since the epoch).
+    ///
+    /// Stale quotes are only noticed when something happens, so call this
+    /// periodically to catch books that went quiet.
+    pub fn check_all(&mut self, now: u64) -> Vec<Opportunity> {
+        let tokens: Vec<String> = self.rules.keys().cloned().collect();
+        tokens
+            .iter()
+            .flat_map(|token_id| self.evaluate(token_id, now))
+            .collect()
+    }
+
+    fn state_mut(&mut self, token_id: &str) -> &mut TokenState {
+        self.state.entry(token_id.to_string()).or_default()
+    }
+
+    fn evaluate(&mut self, token_id: &str, now: u64) -> Vec<Opportunity> {
+        let Some(rules) = self.rules.get(token_id).cloned() else {
+            return Vec::new();
+        };
+        let Ok((Some(bid), Some(ask))) = self.books.with_book(token_id, |book| {
+            let top = |side| book.level_ages(side, Some(1)).first().copied();
+            (top(Side::BUY), top(Side::SELL))
+        }) else {
+            return Vec::new();
+        };
+        let spread = ask.price - bid.price;
+        let state = self.state.entry(token_id.to_string()).or_default();
+
+        let mut found = Vec::new();
+        if let Some(min_spread) = rules.min_capture_spread {
+            let bid_price = bid.price + rules.tick_size;
+            let ask_price = ask.price - rules.tick_size;
+            if spread >= min_spread && bid_price < ask_price {
+                found.push((
+                    OpportunityKind::WideSpread,
+                    vec![
+                        suggest(Side::BUY, bid_price, rules.max_size, OrderType::GTC),
+                        suggest(Side::SELL, ask_price, rules.max_size, OrderType::GTC),
+                    ],
+                ));
+            }
+        }
+        if let Some(max_spread) = rules.max_take_spread {
+            if spread <= max_spread && ask.size >= rules.min_depth && ask.size > Decimal::ZERO {
+                found.push((
+                    OpportunityKind::TightSpread,
+                    vec![suggest(
+                        Side::BUY,
+                        ask.price,
+                        ask.size.min(rules.max_size),
+                        OrderType::FAK,
+                    )],
+                ));
+            }
+        }
+        if let (Some(stale_after), Some(last_trade)) = (rules.stale_after, state.last_trade) {
+            // Only the quote being picked off needs to be old, not the whole book
+            let stale_after = stale_after.as_millis() as i64;
+            let is_stale =
+                |level: &LevelAge| now as i64 - level.updated_at.timestamp_millis() >= stale_after;
+            if is_stale(&ask)
+                && ask.price <= last_trade - rules.stale_edge
+                && ask.size >= rules.min_depth
+            {
+                found.push((
+                    OpportunityKind::StaleQuote,
+                    vec![suggest(
+                        Side::BUY,
+                        ask.price,
+                        ask.size.min(rules.max_size),
+                        OrderType::FAK,
+                    )],
+                ));
+            } else if is_stale(&bid)
+                && bid.price >= last_trade + rules.stale_edge
+                && bid.size >= rules.min_depth
+            {
+                found.push((
+                    OpportunityKind::StaleQuote,
+                    vec![suggest(
+                        Side::SELL,
+                        bid.price,
+                        bid.size.min(rules.max_size),
+                        OrderType::FAK,
+                    )],
+                ));
+            }
+        }
+
+        let cooldown = rules.cooldown.as_millis() as u64;
+        let mut reported = Vec::new();
+        for (kind, orders) in found {
+            let last = state.last_reported.get(&kind).copied();
+            if last.is_some_and(|last| now < last.saturating_add(cooldown)) {
+                continue;
+            }
+            state.last_reported.insert(kind, now);
+            let opportunity = Opportunity {
+                token_id: token_id.to_string(),
+                kind,
+                best_bid: bid.price,
+                best_ask: ask.price,
+                timestamp: now,
+                orders,
+            };
+            let _ = self.events.send(opportunity.clone());
+            reported.push(opportunity);
+        }
+        reported
+    }
+}
+
+fn suggest(side: Side, price: Decimal, size: Decimal, order_type: OrderType) -> SuggestedOrder {
+    SuggestedOrder {
+        side,
+        price,
+        size,
+        order_type,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn message(json: String) -> StreamMessage {
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn book(bid: &str, ask: &str, timestamp: u64) -> StreamMessage {
+        message(format!(
+            r#"{{"event_type":"book","asset_id":"1","market":"0xabc","timestamp":"{timestamp}",
+                "bids":[{{"price":"{bid}","size":"50"}}],"asks":[{{"price":"{ask}","size":"20"}}]}}"#
+        ))
+    }
+
+    #[test]
+    fn test_detects_spread_and_stale_quotes() {
+        let mut detector = OpportunityDetector::new();
+        detector.watch(
+            "1",
+            OpportunityRules::new()
+                .with_capture_spread(dec!(0.05))
+                .with_take_spread(dec!(0.01))
+                .with_stale_quotes(Duration::from_secs(5), dec!(0.02))
+                .with_max_size(dec!(10)),
+        );
+        let mut events = detector.subscribe();
+
+        let found = detector.observe(&book("0.40", "0.50", 1_000));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, OpportunityKind::WideSpread);
+        assert_eq!(found[0].orders[0].price, dec!(0.41));
+        assert_eq!(found[0].orders[1].price, dec!(0.49));
+        assert_eq!(events.try_recv().unwrap(), found[0]);
+        // Same condition within the cooldown is not reported again
+        assert!(detector.observe(&book("0.40", "0.50", 1_500)).is_empty());
+
+        let found = detector.observe(&book("0.49", "0.50", 3_000));
+        assert_eq!(found[0].kind, OpportunityKind::TightSpread);
+        assert_eq!(found[0].orders[0].size, dec!(10));
+
+        // Trades move to 0.55 while the 0.50 ask sits untouched since the first
+        // snapshot; it is stale once that level is five seconds old
+        let trade = message(
+            r#"{"event_type":"last_trade_price","asset_id":"1","market":"0xabc",
+                "price":"0.55","side":"BUY","size":"5","timestamp":"3500"}"#
+                .to_string(),
+        );
+        assert!(detector.observe(&trade).is_empty());
+        let found = detector.check_all(6_000);
+        let kinds: Vec<_> = found.iter().map(|o| o.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![OpportunityKind::TightSpread, OpportunityKind::StaleQuote]
+        );
+        assert_eq!(found[1].orders[0].side, Side::BUY);
+        assert_eq!(found[1].orders[0].price, dec!(0.50));
+
+        // Unwatched tokens are ignored
+        detector.unwatch("1");
+        assert!(detector.observe(&book("0.40", "0.50", 9_000)).is_empty());
+    }
+}
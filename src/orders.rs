@@ -4,21 +4,22 @@
 //! for the Polymarket CLOB, including EIP-712 signature generation.
 
 use crate::auth::{
+    order_signing_hash, poly1271_wrapped_for, recover_poly1271_signer, recover_signer,
     sign_order_message, sign_order_message_with_domain, sign_poly1271_order_message_with_domain,
     PreparedOrderDomain, SignedOrderMessage,
 };
 use crate::errors::{PolyfillError, Result};
+use crate::nonce::NonceManager;
 use crate::types::{
-    CreateOrderOptions, MarketOrderArgs, OrderArgs, OrderType, Side, SignedOrderRequest,
+    CreateOrderOptions, MarketOrderArgs, OrderArgs, OrderType, Side, SignedOrderRequest, TokenId,
     SCALE_FACTOR,
 };
 use alloy_primitives::{keccak256, Address, B256, U256};
 use alloy_signer_local::PrivateKeySigner;
-use rand::Rng;
 use rust_decimal::Decimal;
 use rust_decimal::RoundingStrategy::{AwayFromZero, MidpointTowardZero, ToZero};
 use std::str::FromStr;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
 
 pub const BYTES32_ZERO: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
 
@@ -59,6 +60,7 @@ pub struct OrderBuilder {
     sig_type: SigType,
     funder: Address,
     funder_checksum: String,
+    nonces: Arc<NonceManager>,
 }
 
 /// Prepared low-latency order path for a single market/token configuration.
@@ -66,8 +68,7 @@ pub struct OrderBuilder {
 pub struct PreparedOrderPath {
     builder: OrderBuilder,
     chain_id: u64,
-    token_id: String,
-    token_id_u256: U256,
+    token_id: TokenId,
     round_config: RoundConfig,
     domain: PreparedOrderDomain,
     builder_bytes: B256,
@@ -131,11 +132,6 @@ fn exchange_address_for(chain_id: u64, neg_risk: bool) -> Result<Address> {
         .map_err(|e| PolyfillError::config(format!("Invalid exchange address: {}", e)))
 }
 
-fn parse_token_id(token_id: &str) -> Result<U256> {
-    U256::from_str_radix(token_id, 10)
-        .map_err(|e| PolyfillError::validation(format!("Incorrect tokenId format: {}", e)))
-}
-
 fn parse_optional_bytes32(name: &str, value: Option<&str>) -> Result<(B256, String)> {
     let normalized = normalize_optional_bytes32(name, value)?;
     let parsed = B256::from_str(&normalized)
@@ -156,6 +152,108 @@ pub fn sig_type_from_u8(signature_type: u8) -> Result<SigType> {
     }
 }
 
+/// What [`verify_signed_order`] established about an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderVerification {
+    /// Key that produced the signature. For POLY_1271 orders this is the
+    /// deposit wallet's owner, which can only be checked on chain.
+    pub recovered_signer: Address,
+    pub sig_type: SigType,
+    /// Signed for the neg-risk exchange rather than the standard one
+    pub neg_risk: bool,
+}
+
+/// Check a signed order locally, without posting it.
+///
+/// Recovers the signer from the EIP-712 signature under the standard and
+/// neg-risk exchange domains of `chain_id`, and checks that maker and signer
+/// fit the signature type: equal for EOA and POLY_1271 orders, and the
+/// derived proxy or Safe wallet of the signer for those types on Polygon.
+/// Errors say which check failed, which helps with "invalid signature"
+/// rejections of orders built by other tooling.
+pub fn verify_signed_order(order: &SignedOrderRequest, chain_id: u64) -> Result<OrderVerification> {
+    let sig_type = sig_type_from_u8(order.signature_type)?;
+    let maker = parse_order_field("maker", &order.maker, Address::from_str)?;
+    let signer = parse_order_field("signer", &order.signer, Address::from_str)?;
+    let non_empty = |value: &str| Some(value.to_string()).filter(|value| !value.is_empty());
+    let message = SignedOrderMessage {
+        salt: U256::from(order.salt),
+        maker,
+        signer,
+        token_id: parse_order_field("tokenId", &order.token_id, U256::from_str)?,
+        maker_amount: parse_order_field("makerAmount", &order.maker_amount, U256::from_str)?,
+        taker_amount: parse_order_field("takerAmount", &order.taker_amount, U256::from_str)?,
+        side: Side::from_str(&order.side)? as u8,
+        signature_type: order.signature_type,
+        timestamp: parse_order_field("timestamp", &order.timestamp, U256::from_str)?,
+        metadata: parse_optional_bytes32("metadata", non_empty(&order.metadata).as_deref())?.0,
+        builder: parse_optional_bytes32("builder", non_empty(&order.builder).as_deref())?.0,
+    };
+    let signature = alloy_primitives::hex::decode(&order.signature)
+        .map_err(|e| PolyfillError::validation(format!("Invalid order signature hex: {e}")))?;
+
+    let expected_maker = match sig_type {
+        SigType::Eoa | SigType::Poly1271 => Some(signer),
+        SigType::PolyProxy => derive_proxy_wallet(signer, chain_id).ok(),
+        SigType::PolyGnosisSafe => derive_safe_wallet(signer, chain_id).ok(),
+    };
+    if let Some(expected) = expected_maker.filter(|expected| *expected != maker) {
+        return Err(PolyfillError::validation(format!(
+            "Maker {maker} does not match {expected} expected for signer {signer} with {sig_type:?} signatures"
+        )));
+    }
+
+    let mut recovered = Vec::with_capacity(2);
+    for neg_risk in [false, true] {
+        let domain = PreparedOrderDomain::new(chain_id, exchange_address_for(chain_id, neg_risk)?);
+        let verification = |recovered_signer| OrderVerification {
+            recovered_signer,
+            sig_type,
+            neg_risk,
+        };
+        match sig_type {
+            SigType::Poly1271 => {
+                if poly1271_wrapped_for(&signature, &domain) {
+                    let owner = recover_poly1271_signer(
+                        &signature,
+                        message.clone(),
+                        &domain,
+                        maker,
+                        chain_id,
+                    )?;
+                    return Ok(verification(owner));
+                }
+            },
+            _ => {
+                let hash = order_signing_hash(message.clone(), &domain);
+                let address = recover_signer(&signature, &hash)?;
+                if address == signer {
+                    return Ok(verification(address));
+                }
+                recovered.push(address.to_string());
+            },
+        }
+    }
+    if sig_type == SigType::Poly1271 {
+        return Err(PolyfillError::validation(format!(
+            "POLY_1271 signature is wrapped for neither exchange on chain {chain_id}"
+        )));
+    }
+    Err(PolyfillError::validation(format!(
+        "Signature does not come from signer {signer} on chain {chain_id}: recovered {} under the standard exchange and {} under the neg-risk exchange",
+        recovered[0], recovered[1]
+    )))
+}
+
+fn parse_order_field<T, E: std::fmt::Display>(
+    name: &str,
+    value: &str,
+    parse: impl FnOnce(&str) -> std::result::Result<T, E>,
+) -> Result<T> {
+    parse(value)
+        .map_err(|e| PolyfillError::validation(format!("Invalid order {name} '{value}': {e}")))
+}
+
 pub fn derive_proxy_wallet(eoa_address: Address, chain_id: u64) -> Result<Address> {
     if chain_id != 137 {
         return Err(PolyfillError::config(
@@ -208,17 +306,6 @@ pub fn resolve_funder(
     }
 }
 
-/// Generate a random seed for order salt
-fn generate_seed() -> u64 {
-    let mut rng = rand::thread_rng();
-    let y: f64 = rng.gen();
-    let timestamp = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("Time went backwards")
-        .as_secs();
-    (timestamp as f64 * y) as u64
-}
-
 /// Convert decimal to token units (multiply by 1e6)
 fn decimal_to_token_units(amt: Decimal) -> Result<U256> {
     let mut amt = TOKEN_UNIT_SCALE * amt;
@@ -340,6 +427,59 @@ pub fn adjust_buy_amount_for_fees(
     Ok(adjusted)
 }
 
+/// How much of a market order the book can take within a price limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarketSplit {
+    /// Collateral (buys) or shares (sells) that can execute now
+    pub fillable: Decimal,
+    /// What is left of the requested amount
+    pub remainder: Decimal,
+    /// Worst level price the fillable part reaches
+    pub worst_price: Option<Decimal>,
+}
+
+/// Split `amount` into what `levels` can fill at or better than
+/// `price_limit` and the rest. Levels may come in any order.
+pub fn split_market_amount(
+    levels: &[crate::types::BookLevel],
+    amount: Decimal,
+    side: Side,
+    price_limit: Option<Decimal>,
+) -> MarketSplit {
+    let mut levels: Vec<&crate::types::BookLevel> = levels
+        .iter()
+        .filter(|level| level.size > Decimal::ZERO)
+        .filter(|level| match (side, price_limit) {
+            (Side::BUY, Some(limit)) => level.price <= limit,
+            (Side::SELL, Some(limit)) => level.price >= limit,
+            (_, None) => true,
+        })
+        .collect();
+    match side {
+        Side::BUY => levels.sort_by_key(|level| level.price),
+        Side::SELL => levels.sort_by_key(|level| std::cmp::Reverse(level.price)),
+    }
+
+    let mut fillable = Decimal::ZERO;
+    let mut worst_price = None;
+    for level in levels {
+        if fillable >= amount {
+            break;
+        }
+        let available = match side {
+            Side::BUY => level.size * level.price,
+            Side::SELL => level.size,
+        };
+        fillable = (fillable + available).min(amount);
+        worst_price = Some(level.price);
+    }
+    MarketSplit {
+        fillable,
+        remainder: amount - fillable,
+        worst_price,
+    }
+}
+
 impl OrderBuilder {
     /// Create a new order builder
     pub fn new(
@@ -360,9 +500,31 @@ impl OrderBuilder {
             sig_type,
             funder,
             funder_checksum,
+            nonces: NonceManager::global(),
         }
     }
 
+    /// Draw salts and timestamps from `nonces` instead of the process-wide
+    /// manager, e.g. one that persists across restarts
+    pub fn with_nonce_manager(mut self, nonces: Arc<NonceManager>) -> Self {
+        self.nonces = nonces;
+        self
+    }
+
+    /// Sign with `signer` from now on. A funder that defaulted to the old
+    /// signer's address follows the new one.
+    pub fn with_signer(mut self, signer: PrivateKeySigner) -> Self {
+        let signer_address = signer.address();
+        if self.funder == self.signer_address {
+            self.funder = signer_address;
+            self.funder_checksum = signer_address.to_checksum(None);
+        }
+        self.signer_checksum = signer_address.to_checksum(None);
+        self.signer_address = signer_address;
+        self.signer = signer;
+        self
+    }
+
     /// Get signature type as u8
     pub fn get_sig_type(&self) -> u8 {
         self.sig_type as u8
@@ -376,14 +538,12 @@ impl OrderBuilder {
     pub fn prepare_order_path(
         &self,
         chain_id: u64,
-        token_id: impl Into<String>,
+        token_id: TokenId,
         tick_size: Decimal,
         neg_risk: bool,
         builder_code: Option<&str>,
         metadata: Option<&str>,
     ) -> Result<PreparedOrderPath> {
-        let token_id = token_id.into();
-        let token_id_u256 = parse_token_id(&token_id)?;
         let round_config = *parse_round_config(tick_size)?;
         let exchange = exchange_address_for(chain_id, neg_risk)?;
         let domain = PreparedOrderDomain::new(chain_id, exchange);
@@ -394,7 +554,6 @@ impl OrderBuilder {
             builder: self.clone(),
             chain_id,
             token_id,
-            token_id_u256,
             round_config,
             domain,
             builder_bytes,
@@ -549,7 +708,7 @@ impl OrderBuilder {
         let exchange_address = exchange_address_for(chain_id, neg_risk)?;
 
         self.build_signed_order(
-            order_args.token_id.clone(),
+            &order_args.token_id,
             order_args.side,
             chain_id,
             exchange_address,
@@ -587,7 +746,7 @@ impl OrderBuilder {
         let exchange_address = exchange_address_for(chain_id, neg_risk)?;
 
         self.build_signed_order(
-            order_args.token_id.clone(),
+            &order_args.token_id,
             order_args.side,
             chain_id,
             exchange_address,
@@ -603,7 +762,7 @@ impl OrderBuilder {
     #[allow(clippy::too_many_arguments)]
     fn build_signed_order(
         &self,
-        token_id: String,
+        token_id: &TokenId,
         side: Side,
         chain_id: u64,
         exchange: Address,
@@ -613,13 +772,9 @@ impl OrderBuilder {
         builder_code: Option<&str>,
         metadata: Option<&str>,
     ) -> Result<SignedOrderRequest> {
-        let seed = generate_seed();
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_millis();
+        let seed = self.nonces.next_salt()?;
+        let timestamp = self.nonces.timestamp_ms();
 
-        let u256_token_id = parse_token_id(&token_id)?;
         let (builder_bytes, builder) = parse_optional_bytes32("builder_code", builder_code)?;
         let (metadata_bytes, metadata) = parse_optional_bytes32("metadata", metadata)?;
 
@@ -629,7 +784,7 @@ impl OrderBuilder {
             salt: U256::from(seed),
             maker: self.funder,
             signer: signer_address,
-            token_id: u256_token_id,
+            token_id: token_id.to_u256(),
             maker_amount,
             taker_amount,
             side: side as u8,
@@ -654,7 +809,7 @@ impl OrderBuilder {
             salt: seed,
             maker: self.funder_checksum.clone(),
             signer: signer_checksum,
-            token_id,
+            token_id: token_id.to_string(),
             maker_amount: maker_amount.to_string(),
             taker_amount: taker_amount.to_string(),
             expiration: expiration.to_string(),
@@ -728,11 +883,8 @@ impl PreparedOrderPath {
         taker_amount: U256,
         expiration: u64,
     ) -> Result<SignedOrderRequest> {
-        let seed = generate_seed();
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_millis();
+        let seed = self.builder.nonces.next_salt()?;
+        let timestamp = self.builder.nonces.timestamp_ms();
 
         let signer_address = self.builder.order_signer_address();
         let signer_checksum = self.builder.order_signer_checksum();
@@ -740,7 +892,7 @@ impl PreparedOrderPath {
             salt: U256::from(seed),
             maker: self.builder.funder,
             signer: signer_address,
-            token_id: self.token_id_u256,
+            token_id: self.token_id.to_u256(),
             maker_amount,
             taker_amount,
             side: side as u8,
@@ -765,7 +917,7 @@ impl PreparedOrderPath {
             salt: seed,
             maker: self.builder.funder_checksum.clone(),
             signer: signer_checksum,
-            token_id: self.token_id.clone(),
+            token_id: self.token_id.to_string(),
             maker_amount: maker_amount.to_string(),
             taker_amount: taker_amount.to_string(),
             expiration: expiration.to_string(),
@@ -801,8 +953,9 @@ mod tests {
 
     #[test]
     fn test_generate_seed() {
-        let seed1 = generate_seed();
-        let seed2 = generate_seed();
+        let nonces = NonceManager::new();
+        let seed1 = nonces.next_salt().unwrap();
+        let seed2 = nonces.next_salt().unwrap();
         assert_ne!(seed1, seed2);
     }
 
@@ -932,7 +1085,7 @@ mod tests {
             .create_order(
                 137,
                 &OrderArgs {
-                    token_id: "123456".to_string(),
+                    token_id: "123456".parse().unwrap(),
                     price: Decimal::from_str("0.45").unwrap(),
                     size: Decimal::from_str("12.34").unwrap(),
                     side: Side::BUY,
@@ -973,7 +1126,7 @@ mod tests {
             .create_order(
                 137,
                 &OrderArgs {
-                    token_id: "123456".to_string(),
+                    token_id: "123456".parse().unwrap(),
                     price: Decimal::from_str("0.50").unwrap(),
                     size: Decimal::from_str("10").unwrap(),
                     side: Side::BUY,
@@ -999,11 +1152,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_verify_signed_order_recovers_signer_and_exchange() {
+        let args = OrderArgs {
+            token_id: "123456".parse().unwrap(),
+            price: Decimal::from_str("0.50").unwrap(),
+            size: Decimal::from_str("10").unwrap(),
+            side: Side::BUY,
+            expiration: None,
+            builder_code: None,
+            metadata: None,
+        };
+        let options = |neg_risk| CreateOrderOptions {
+            tick_size: Some(Decimal::from_str("0.01").unwrap()),
+            neg_risk: Some(neg_risk),
+        };
+
+        let builder = test_builder();
+        let order = builder.create_order(137, &args, &options(true)).unwrap();
+        let verification = verify_signed_order(&order, 137).unwrap();
+        assert_eq!(verification.recovered_signer, builder.signer_address);
+        assert!(verification.neg_risk);
+
+        let mut tampered = order.clone();
+        tampered.maker_amount = "4000000".to_string();
+        let err = verify_signed_order(&tampered, 137).unwrap_err();
+        assert!(
+            err.to_string().contains("does not come from signer"),
+            "{err}"
+        );
+
+        let mut wrong_maker = order;
+        wrong_maker.maker = "0x000000000000000000000000000000000000d077".to_string();
+        assert!(verify_signed_order(&wrong_maker, 137).is_err());
+
+        let proxy_wallet = derive_proxy_wallet(builder.signer_address, 137).unwrap();
+        let proxy = OrderBuilder::new(
+            builder.signer.clone(),
+            Some(SigType::PolyProxy),
+            Some(proxy_wallet),
+        )
+        .create_order(137, &args, &options(false))
+        .unwrap();
+        assert!(!verify_signed_order(&proxy, 137).unwrap().neg_risk);
+
+        let funder = Address::from_str("0x000000000000000000000000000000000000d077").unwrap();
+        let wallet = OrderBuilder::new(
+            builder.signer.clone(),
+            Some(SigType::Poly1271),
+            Some(funder),
+        )
+        .create_order(137, &args, &options(false))
+        .unwrap();
+        let verification = verify_signed_order(&wallet, 137).unwrap();
+        assert_eq!(verification.recovered_signer, builder.signer_address);
+        assert_eq!(verification.sig_type, SigType::Poly1271);
+    }
+
     #[test]
     fn test_prepared_order_path_creates_equivalent_limit_order_fields() {
         let builder = test_builder();
         let args = OrderArgs {
-            token_id: "123456".to_string(),
+            token_id: "123456".parse().unwrap(),
             price: Decimal::from_str("0.45").unwrap(),
             size: Decimal::from_str("12.34").unwrap(),
             side: Side::BUY,
@@ -1050,7 +1260,7 @@ mod tests {
         let prepared = builder
             .prepare_order_path(
                 137,
-                "123456",
+                "123456".parse().unwrap(),
                 Decimal::from_str("0.01").unwrap(),
                 false,
                 None,
@@ -1077,7 +1287,7 @@ mod tests {
             .create_market_order(
                 137,
                 &MarketOrderArgs {
-                    token_id: "123456".to_string(),
+                    token_id: "123456".parse().unwrap(),
                     amount: Decimal::from_str("10.0").unwrap(),
                     side: Side::BUY,
                     order_type: OrderType::FAK,
@@ -1181,6 +1391,45 @@ mod tests {
         assert_eq!(price, Decimal::from_str("0.45").unwrap());
     }
 
+    #[test]
+    fn test_split_market_amount_within_price_limit() {
+        let level = |price: &str, size: &str| crate::types::BookLevel {
+            price: Decimal::from_str(price).unwrap(),
+            size: Decimal::from_str(size).unwrap(),
+        };
+        let asks = vec![
+            level("0.60", "100"),
+            level("0.50", "10"),
+            level("0.55", "20"),
+        ];
+
+        // 10 * 0.50 + 20 * 0.55 = 16 fits under 0.55; 0.60 is past the limit
+        let split = split_market_amount(
+            &asks,
+            Decimal::from(50),
+            Side::BUY,
+            Some(Decimal::from_str("0.55").unwrap()),
+        );
+        assert_eq!(split.fillable, Decimal::from(16));
+        assert_eq!(split.remainder, Decimal::from(34));
+        assert_eq!(split.worst_price, Some(Decimal::from_str("0.55").unwrap()));
+
+        let split = split_market_amount(&asks, Decimal::from(4), Side::BUY, None);
+        assert_eq!(
+            (split.fillable, split.remainder),
+            (Decimal::from(4), Decimal::ZERO)
+        );
+        assert_eq!(split.worst_price, Some(Decimal::from_str("0.50").unwrap()));
+
+        let split = split_market_amount(
+            &asks,
+            Decimal::from(5),
+            Side::SELL,
+            Some(Decimal::from_str("0.70").unwrap()),
+        );
+        assert_eq!((split.fillable, split.worst_price), (Decimal::ZERO, None));
+    }
+
     #[test]
     fn test_signed_order_json_uses_camel_case_wire_shape() {
         let builder = test_builder();
@@ -1188,7 +1437,7 @@ mod tests {
             .create_order(
                 137,
                 &OrderArgs {
-                    token_id: "123456".to_string(),
+                    token_id: "123456".parse().unwrap(),
                     price: Decimal::from_str("0.55").unwrap(),
                     size: Decimal::from_str("5.0").unwrap(),
                     side: Side::SELL,
@@ -1213,18 +1462,20 @@ mod tests {
     #[test]
     fn test_seed_generation_uniqueness() {
         let mut seeds = std::collections::HashSet::new();
+        let nonces = NonceManager::new();
 
         // Generate 1000 seeds and ensure they're all unique
         for _ in 0..1000 {
-            let seed = generate_seed();
+            let seed = nonces.next_salt().unwrap();
             assert!(seeds.insert(seed), "Duplicate seed generated");
         }
     }
 
     #[test]
     fn test_seed_generation_range() {
+        let nonces = NonceManager::new();
         for _ in 0..100 {
-            let seed = generate_seed();
+            let seed = nonces.next_salt().unwrap();
             // Seeds should be positive and within reasonable range
             assert!(seed > 0);
             assert!(seed < u64::MAX);
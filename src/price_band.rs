@@ -0,0 +1,162 @@
+//! Price protection bands
+//!
+//! A price typed as a percentage (`55` instead of `0.55`), a side flipped by
+//! a bug or a stale quote crossing a moved book all reach the exchange as
+//! valid orders. A [`PriceBand`] rejects them with
+//! [`OrderErrorKind::PriceConstraint`] before they are sent:
+//!
+//! - every price must sit inside `[tick, 1 - tick]`, optionally pulled in by
+//!   a number of ticks from each edge
+//! - with a maximum deviation set, a price may not be further than that
+//!   fraction of the local midpoint away from it; tokens without a local
+//!   book skip this check
+//!
+//! Install one on a client with [`crate::ClobClient::set_price_band`] to
+//! check every signed order before it is posted.
+
+use crate::errors::{OrderErrorKind, PolyfillError, Result};
+use crate::types::{Side, SignedOrderRequest};
+use rust_decimal::Decimal;
+
+/// Bounds on the price of outgoing orders
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PriceBand {
+    max_deviation: Option<Decimal>,
+    edge_margin_ticks: u32,
+}
+
+impl PriceBand {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject prices more than `fraction` (e.g. `0.1` for 10%) of the local
+    /// midpoint away from it
+    pub fn with_max_deviation(mut self, fraction: Decimal) -> Self {
+        self.max_deviation = Some(fraction);
+        self
+    }
+
+    /// Keep prices at least `ticks` ticks inside `[tick, 1 - tick]`
+    pub fn with_edge_margin(mut self, ticks: u32) -> Self {
+        self.edge_margin_ticks = ticks;
+        self
+    }
+
+    /// Check `price` for a token with `tick_size` and, if known, midpoint
+    /// `mid`
+    pub fn check(
+        &self,
+        token_id: &str,
+        side: Side,
+        price: Decimal,
+        tick_size: Decimal,
+        mid: Option<Decimal>,
+    ) -> Result<()> {
+        let margin = tick_size * Decimal::from(self.edge_margin_ticks);
+        let (low, high) = (tick_size + margin, Decimal::ONE - tick_size - margin);
+        if price < low || price > high {
+            return Err(PolyfillError::order(
+                format!(
+                    "{} {token_id} at {price} is outside the price band [{low}, {high}]",
+                    side.as_str()
+                ),
+                OrderErrorKind::PriceConstraint,
+            ));
+        }
+        if let (Some(fraction), Some(mid)) = (self.max_deviation, mid) {
+            if mid > Decimal::ZERO && (price - mid).abs() > mid * fraction {
+                return Err(PolyfillError::order(
+                    format!(
+                        "{} {token_id} at {price} is more than {}% away from mid {mid}",
+                        side.as_str(),
+                        (fraction * Decimal::ONE_HUNDRED).normalize()
+                    ),
+                    OrderErrorKind::PriceConstraint,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// [`Self::check`] against the price implied by a signed order's amounts
+    pub fn check_signed(
+        &self,
+        order: &SignedOrderRequest,
+        tick_size: Decimal,
+        mid: Option<Decimal>,
+    ) -> Result<()> {
+        let amount = |value: &str| {
+            value.parse::<Decimal>().map_err(|e| {
+                PolyfillError::parse(format!("Invalid order amount {value}: {e}"), None)
+            })
+        };
+        let side: Side = order.side.parse()?;
+        let (maker, taker) = (amount(&order.maker_amount)?, amount(&order.taker_amount)?);
+        let (size, collateral) = match side {
+            Side::BUY => (taker, maker),
+            Side::SELL => (maker, taker),
+        };
+        if size.is_zero() {
+            return Err(PolyfillError::validation("Signed order has zero size"));
+        }
+        self.check(&order.token_id, side, collateral / size, tick_size, mid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_price_band_rejects_fat_fingers() {
+        let band = PriceBand::new()
+            .with_max_deviation(dec!(0.2))
+            .with_edge_margin(1);
+        let tick = dec!(0.01);
+
+        assert!(band
+            .check("1", Side::BUY, dec!(0.55), tick, Some(dec!(0.50)))
+            .is_ok());
+        // Percent instead of probability
+        let err = band
+            .check("1", Side::BUY, dec!(55), tick, Some(dec!(0.50)))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PolyfillError::Order {
+                kind: OrderErrorKind::PriceConstraint,
+                ..
+            }
+        ));
+        assert!(band.check("1", Side::SELL, dec!(0.01), tick, None).is_err());
+        assert!(band.check("1", Side::SELL, dec!(0.02), tick, None).is_ok());
+        assert!(band
+            .check("1", Side::SELL, dec!(0.30), tick, Some(dec!(0.50)))
+            .is_err());
+        // Deviation is only checked against a known mid
+        assert!(band.check("1", Side::SELL, dec!(0.30), tick, None).is_ok());
+
+        let order = |side: &str, maker: &str, taker: &str| SignedOrderRequest {
+            salt: 1,
+            maker: String::new(),
+            signer: String::new(),
+            token_id: "1".to_string(),
+            maker_amount: maker.to_string(),
+            taker_amount: taker.to_string(),
+            expiration: "0".to_string(),
+            side: side.to_string(),
+            signature_type: 0,
+            timestamp: "0".to_string(),
+            metadata: String::new(),
+            builder: String::new(),
+            signature: String::new(),
+        };
+        // 5.5 USDC for 10 shares
+        let buy = order("BUY", "5500000", "10000000");
+        assert!(band.check_signed(&buy, tick, Some(dec!(0.5))).is_ok());
+        let sell = order("SELL", "10000000", "9900000");
+        assert!(band.check_signed(&sell, tick, None).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+//! External reference prices
+//!
+//! A [`PriceFeed`] supplies prices from outside the Polymarket book: an
+//! oracle, another prediction venue or anything a strategy computes itself.
+//! [`ReferencePrices`] links Polymarket tokens to a feed and instrument, so
+//! analytics and alerts can compare the local midpoint against it as a
+//! [`Basis`]. Adapters:
+//!
+//! - [`ManualFeed`]: prices pushed by the caller, e.g. from its own poller
+//! - [`FnFeed`]: a closure
+//! - [`BookFeed`]: midpoints of books in an [`OrderBookManager`], to use one
+//!   Polymarket market as the reference for another
+
+use crate::book::OrderBookManager;
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A price observed on another venue
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReferencePrice {
+    /// Name of the feed it came from
+    pub source: String,
+    pub price: Decimal,
+    /// When the feed observed it, in milliseconds
+    pub timestamp_ms: u64,
+}
+
+/// A source of reference prices keyed by instrument
+pub trait PriceFeed: Send + Sync {
+    fn name(&self) -> String;
+
+    /// Latest price for `key`, if the feed has one
+    fn price(&self, key: &str) -> Option<ReferencePrice>;
+}
+
+/// Prices set by the caller
+#[derive(Debug)]
+pub struct ManualFeed {
+    name: String,
+    prices: RwLock<HashMap<String, (Decimal, u64)>>,
+}
+
+impl ManualFeed {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            prices: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn set(&self, key: impl Into<String>, price: Decimal, timestamp_ms: u64) {
+        self.prices
+            .write()
+            .insert(key.into(), (price, timestamp_ms));
+    }
+
+    pub fn remove(&self, key: &str) -> bool {
+        self.prices.write().remove(key).is_some()
+    }
+}
+
+impl PriceFeed for ManualFeed {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn price(&self, key: &str) -> Option<ReferencePrice> {
+        let (price, timestamp_ms) = *self.prices.read().get(key)?;
+        Some(ReferencePrice {
+            source: self.name.clone(),
+            price,
+            timestamp_ms,
+        })
+    }
+}
+
+/// Prices computed by a closure returning `(price, timestamp_ms)`
+pub struct FnFeed<F> {
+    name: String,
+    f: F,
+}
+
+impl<F> FnFeed<F>
+where
+    F: Fn(&str) -> Option<(Decimal, u64)> + Send + Sync,
+{
+    pub fn new(name: impl Into<String>, f: F) -> Self {
+        Self {
+            name: name.into(),
+            f,
+        }
+    }
+}
+
+impl<F> std::fmt::Debug for FnFeed<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FnFeed").field("name", &self.name).finish()
+    }
+}
+
+impl<F> PriceFeed for FnFeed<F>
+where
+    F: Fn(&str) -> Option<(Decimal, u64)> + Send + Sync,
+{
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn price(&self, key: &str) -> Option<ReferencePrice> {
+        let (price, timestamp_ms) = (self.f)(key)?;
+        Some(ReferencePrice {
+            source: self.name.clone(),
+            price,
+            timestamp_ms,
+        })
+    }
+}
+
+/// Midpoints of locally maintained books, keyed by token id
+#[derive(Debug, Clone)]
+pub struct BookFeed {
+    books: Arc<OrderBookManager>,
+}
+
+impl BookFeed {
+    pub fn new(books: Arc<OrderBookManager>) -> Self {
+        Self { books }
+    }
+}
+
+impl PriceFeed for BookFeed {
+    fn name(&self) -> String {
+        "polymarket".to_string()
+    }
+
+    fn price(&self, key: &str) -> Option<ReferencePrice> {
+        self.books
+            .with_book(key, |book| {
+                Some(ReferencePrice {
+                    source: self.name(),
+                    price: book.mid_price()?,
+                    timestamp_ms: book.timestamp.timestamp_millis().max(0) as u64,
+                })
+            })
+            .ok()
+            .flatten()
+    }
+}
+
+/// Where a token's reference price comes from
+#[derive(Clone)]
+pub struct ReferenceLink {
+    feed: Arc<dyn PriceFeed>,
+    key: String,
+    complement: bool,
+    max_age: Option<Duration>,
+}
+
+impl std::fmt::Debug for ReferenceLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReferenceLink")
+            .field("feed", &self.feed.name())
+            .field("key", &self.key)
+            .field("complement", &self.complement)
+            .field("max_age", &self.max_age)
+            .finish()
+    }
+}
+
+impl ReferenceLink {
+    pub fn new(feed: Arc<dyn PriceFeed>, key: impl Into<String>) -> Self {
+        Self {
+            feed,
+            key: key.into(),
+            complement: false,
+            max_age: None,
+        }
+    }
+
+    /// The feed quotes the opposite outcome; use `1 - price`
+    pub fn complement(mut self) -> Self {
+        self.complement = true;
+        self
+    }
+
+    /// Ignore prices older than `max_age` at the time of comparison
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+}
+
+/// Local midpoint against its reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basis {
+    pub token_id: String,
+    pub mid: Decimal,
+    pub reference: ReferencePrice,
+    /// `mid - reference.price`
+    pub basis: Decimal,
+}
+
+/// Reference price links per Polymarket token
+#[derive(Debug, Default)]
+pub struct ReferencePrices {
+    links: RwLock<HashMap<String, ReferenceLink>>,
+}
+
+impl ReferencePrices {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `link` as the reference for `token_id`, replacing any earlier one
+    pub fn link(&self, token_id: impl Into<String>, link: ReferenceLink) {
+        self.links.write().insert(token_id.into(), link);
+    }
+
+    pub fn unlink(&self, token_id: &str) -> bool {
+        self.links.write().remove(token_id).is_some()
+    }
+
+    /// Reference price of `token_id`, unless missing or stale as of `now_ms`
+    pub fn reference(&self, token_id: &str, now_ms: u64) -> Option<ReferencePrice> {
+        let link = self.links.read().get(token_id)?.clone();
+        let mut reference = link.feed.price(&link.key)?;
+        if let Some(max_age) = link.max_age {
+            if now_ms.saturating_sub(reference.timestamp_ms) > max_age.as_millis() as u64 {
+                return None;
+            }
+        }
+        if link.complement {
+            reference.price = Decimal::ONE - reference.price;
+        }
+        Some(reference)
+    }
+
+    pub fn basis(&self, token_id: &str, mid: Decimal, now_ms: u64) -> Option<Basis> {
+        let reference = self.reference(token_id, now_ms)?;
+        Some(Basis {
+            token_id: token_id.to_string(),
+            mid,
+            basis: mid - reference.price,
+            reference,
+        })
+    }
+
+    /// [`Self::basis`] against the midpoint of the token's local book
+    pub fn basis_from_book(
+        &self,
+        books: &OrderBookManager,
+        token_id: &str,
+        now_ms: u64,
+    ) -> Option<Basis> {
+        let mid = books.with_book(token_id, |book| book.mid_price()).ok()??;
+        self.basis(token_id, mid, now_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_basis_against_linked_feeds() {
+        let venue = Arc::new(ManualFeed::new("other-venue"));
+        venue.set("BTC-100K-NO", dec!(0.40), 1_000);
+        let oracle = Arc::new(FnFeed::new("oracle", |key: &str| {
+            (key == "btc-100k").then_some((dec!(0.62), 5_000))
+        }));
+
+        let prices = ReferencePrices::new();
+        prices.link(
+            "yes",
+            ReferenceLink::new(venue.clone(), "BTC-100K-NO")
+                .complement()
+                .with_max_age(Duration::from_secs(10)),
+        );
+        prices.link("other", ReferenceLink::new(oracle, "btc-100k"));
+
+        let basis = prices.basis("yes", dec!(0.55), 2_000).unwrap();
+        assert_eq!(basis.reference.price, dec!(0.60));
+        assert_eq!(basis.reference.source, "other-venue");
+        assert_eq!(basis.basis, dec!(-0.05));
+        // Too old by now
+        assert!(prices.reference("yes", 20_000).is_none());
+        assert_eq!(
+            prices.basis("other", dec!(0.60), 0).unwrap().basis,
+            dec!(-0.02)
+        );
+        assert!(prices.basis("unlinked", dec!(0.5), 0).is_none());
+
+        let books = Arc::new(OrderBookManager::new(10));
+        books.get_or_create_book("yes").unwrap();
+        books
+            .apply_book_update(
+                &serde_json::from_str(
+                    r#"{"asset_id":"yes","market":"0xabc","timestamp":"1500",
+                        "bids":[{"price":"0.58","size":"10"}],"asks":[{"price":"0.62","size":"10"}]}"#,
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            BookFeed::new(books.clone()).price("yes").unwrap().price,
+            dec!(0.60)
+        );
+        assert_eq!(
+            prices.basis_from_book(&books, "yes", 2_000).unwrap().basis,
+            dec!(0.00)
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Time-in-force and fill-quality analytics for the client's own quotes
+//!
+//! [`QuoteAnalytics`] follows this client's orders on the user channel and
+//! the mid price on the market channel, and reports, in a
+//! [`QuoteAnalyticsReport`]:
+//!
+//! - how long resting orders lived before they filled or were cancelled
+//! - the share of orders that got filled, by distance from the mid when they
+//!   were placed
+//! - the markout of every fill: how far the mid moved in the order's favour
+//!   `markout_horizon` after it; a negative markout marks adverse selection
+//!
+//! Fills are taken from the `size_matched` of order updates, so both channels
+//! must be fed to [`QuoteAnalytics::observe`].
+
+use crate::sim::SharedClock;
+use crate::types::{OrderMessage, Side, StreamMessage};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::Serialize;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Bucketing and sampling parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteAnalyticsConfig {
+    /// Upper bounds of the distance-from-mid buckets, ascending; a last
+    /// bucket holds everything further out
+    pub distance_buckets: Vec<Decimal>,
+    /// Delay after a fill at which its markout is measured
+    pub markout_horizon: Duration,
+    /// Lifetimes kept per outcome for the median
+    pub max_samples: usize,
+}
+
+impl Default for QuoteAnalyticsConfig {
+    fn default() -> Self {
+        Self {
+            distance_buckets: vec![dec!(0.005), dec!(0.01), dec!(0.02), dec!(0.05)],
+            markout_horizon: Duration::from_secs(10),
+            max_samples: 10_000,
+        }
+    }
+}
+
+impl QuoteAnalyticsConfig {
+    pub fn with_distance_buckets(mut self, mut buckets: Vec<Decimal>) -> Self {
+        buckets.sort();
+        self.distance_buckets = buckets;
+        self
+    }
+
+    pub fn with_markout_horizon(mut self, horizon: Duration) -> Self {
+        self.markout_horizon = horizon;
+        self
+    }
+
+    pub fn with_max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples.max(1);
+        self
+    }
+}
+
+/// Summary of order lifetimes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct LifetimeStats {
+    pub count: u64,
+    pub mean: Option<Duration>,
+    /// Median of the most recent `max_samples` lifetimes
+    pub median: Option<Duration>,
+}
+
+/// Fill ratio of orders placed within one distance from the mid
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DistanceBucket {
+    /// Upper bound of the distance, `None` for the last bucket
+    pub max_distance: Option<Decimal>,
+    pub placed: u64,
+    /// Orders with at least one fill
+    pub filled: u64,
+}
+
+impl DistanceBucket {
+    pub fn fill_ratio(&self) -> Option<f64> {
+        (self.placed > 0).then(|| self.filled as f64 / self.placed as f64)
+    }
+}
+
+/// Markouts of fills measured so far
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MarkoutStats {
+    pub horizon: Duration,
+    pub fills: u64,
+    /// Fills after which the mid moved against the order
+    pub adverse: u64,
+    /// Mean mid move in the order's favour, in price units
+    pub mean_markout: Option<Decimal>,
+}
+
+impl MarkoutStats {
+    pub fn adverse_ratio(&self) -> Option<f64> {
+        (self.fills > 0).then(|| self.adverse as f64 / self.fills as f64)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QuoteAnalyticsReport {
+    pub filled_lifetimes: LifetimeStats,
+    pub cancelled_lifetimes: LifetimeStats,
+    /// Orders placed while the mid was unknown are not bucketed
+    pub by_distance: Vec<DistanceBucket>,
+    pub markouts: MarkoutStats,
+    /// Orders still resting
+    pub open_orders: usize,
+}
+
+#[derive(Debug, Default)]
+struct Lifetimes {
+    count: u64,
+    total: Duration,
+    recent: VecDeque<Duration>,
+}
+
+impl Lifetimes {
+    fn record(&mut self, lifetime: Duration, max_samples: usize) {
+        self.count += 1;
+        self.total += lifetime;
+        if self.recent.len() >= max_samples {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(lifetime);
+    }
+
+    fn stats(&self) -> LifetimeStats {
+        let mut sorted: Vec<Duration> = self.recent.iter().copied().collect();
+        sorted.sort();
+        LifetimeStats {
+            count: self.count,
+            mean: (self.count > 0).then(|| self.total / self.count as u32),
+            median: sorted.get(sorted.len() / 2).copied(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct RestingQuote {
+    placed_at: u64,
+    bucket: Option<usize>,
+    filled: Decimal,
+}
+
+#[derive(Debug)]
+struct PendingMarkout {
+    asset_id: String,
+    side: Side,
+    price: Decimal,
+    due_at: u64,
+}
+
+/// Lifetime, fill-ratio and markout analytics for one client's quotes
+#[derive(Debug)]
+pub struct QuoteAnalytics {
+    config: QuoteAnalyticsConfig,
+    clock: SharedClock,
+    mids: HashMap<String, Decimal>,
+    quotes: HashMap<String, RestingQuote>,
+    filled: Lifetimes,
+    cancelled: Lifetimes,
+    buckets: Vec<DistanceBucket>,
+    pending: Vec<PendingMarkout>,
+    markout_fills: u64,
+    markout_adverse: u64,
+    markout_total: Decimal,
+}
+
+impl Default for QuoteAnalytics {
+    fn default() -> Self {
+        Self::new(QuoteAnalyticsConfig::default())
+    }
+}
+
+impl QuoteAnalytics {
+    pub fn new(config: QuoteAnalyticsConfig) -> Self {
+        let buckets = config
+            .distance_buckets
+            .iter()
+            .map(|&bound| Some(bound))
+            .chain([None])
+            .map(|max_distance| DistanceBucket {
+                max_distance,
+                placed: 0,
+                filled: 0,
+            })
+            .collect();
+        Self {
+            config,
+            clock: crate::sim::system_clock(),
+            mids: HashMap::new(),
+            quotes: HashMap::new(),
+            filled: Lifetimes::default(),
+            cancelled: Lifetimes::default(),
+            buckets,
+            pending: Vec::new(),
+            markout_fills: 0,
+            markout_adverse: 0,
+            markout_total: Decimal::ZERO,
+        }
+    }
+
+    /// Time orders and fills with `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Feed a market- or user-channel message
+    pub fn observe(&mut self, message: &StreamMessage) {
+        let now = self.clock.now_millis();
+        match message {
+            StreamMessage::Book(book) => {
+                let best_bid = book.bids.iter().map(|level| level.price).max();
+                let best_ask = book.asks.iter().map(|level| level.price).min();
+                if let (Some(bid), Some(ask)) = (best_bid, best_ask) {
+                    self.set_mid(&book.asset_id, bid, ask);
+                }
+            },
+            StreamMessage::BestBidAsk(quote) => {
+                self.set_mid(&quote.asset_id, quote.best_bid, quote.best_ask)
+            },
+            StreamMessage::PriceChange(change) => {
+                for entry in &change.price_changes {
+                    if let (Some(bid), Some(ask)) = (entry.best_bid, entry.best_ask) {
+                        self.set_mid(&entry.asset_id, bid, ask);
+                    }
+                }
+            },
+            StreamMessage::Order(order) => self.apply_order(order, now),
+            _ => {},
+        }
+        self.resolve_markouts(now);
+    }
+
+    pub fn report(&self) -> QuoteAnalyticsReport {
+        QuoteAnalyticsReport {
+            filled_lifetimes: self.filled.stats(),
+            cancelled_lifetimes: self.cancelled.stats(),
+            by_distance: self.buckets.clone(),
+            markouts: MarkoutStats {
+                horizon: self.config.markout_horizon,
+                fills: self.markout_fills,
+                adverse: self.markout_adverse,
+                mean_markout: (self.markout_fills > 0)
+                    .then(|| self.markout_total / Decimal::from(self.markout_fills)),
+            },
+            open_orders: self.quotes.len(),
+        }
+    }
+
+    fn set_mid(&mut self, asset_id: &str, bid: Decimal, ask: Decimal) {
+        if bid > Decimal::ZERO && ask > bid {
+            self.mids
+                .insert(asset_id.to_string(), (bid + ask) / Decimal::TWO);
+        }
+    }
+
+    fn apply_order(&mut self, order: &OrderMessage, now: u64) {
+        if order.msg_type.as_deref() == Some("CANCELLATION") {
+            if let Some(quote) = self.quotes.remove(&order.id) {
+                let lifetime = Duration::from_millis(now.saturating_sub(quote.placed_at));
+                self.cancelled.record(lifetime, self.config.max_samples);
+            }
+            return;
+        }
+
+        let quote = match self.quotes.entry(order.id.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let bucket = self.mids.get(&order.asset_id).map(|mid| {
+                    let distance = (order.price - mid).abs();
+                    self.config
+                        .distance_buckets
+                        .iter()
+                        .position(|&bound| distance <= bound)
+                        .unwrap_or(self.config.distance_buckets.len())
+                });
+                if let Some(bucket) = bucket {
+                    self.buckets[bucket].placed += 1;
+                }
+                entry.insert(RestingQuote {
+                    placed_at: now,
+                    bucket,
+                    filled: Decimal::ZERO,
+                })
+            },
+        };
+
+        let matched = order.size_matched.unwrap_or(quote.filled);
+        if matched > quote.filled {
+            if quote.filled.is_zero() {
+                if let Some(bucket) = quote.bucket {
+                    self.buckets[bucket].filled += 1;
+                }
+            }
+            quote.filled = matched;
+            self.pending.push(PendingMarkout {
+                asset_id: order.asset_id.clone(),
+                side: order.side,
+                price: order.price,
+                due_at: now + self.config.markout_horizon.as_millis() as u64,
+            });
+        }
+
+        let done = order
+            .original_size
+            .is_some_and(|size| !size.is_zero() && quote.filled >= size);
+        if done {
+            let lifetime = Duration::from_millis(now.saturating_sub(quote.placed_at));
+            self.quotes.remove(&order.id);
+            self.filled.record(lifetime, self.config.max_samples);
+        }
+    }
+
+    /// Score fills whose horizon has passed against the current mid
+    fn resolve_markouts(&mut self, now: u64) {
+        let mids = &self.mids;
+        let mut resolved = Vec::new();
+        self.pending.retain(
+            |fill| match mids.get(&fill.asset_id).filter(|_| fill.due_at <= now) {
+                Some(&mid) => {
+                    let favour = match fill.side {
+                        Side::BUY => mid - fill.price,
+                        Side::SELL => fill.price - mid,
+                    };
+                    resolved.push(favour);
+                    false
+                },
+                None => true,
+            },
+        );
+        for markout in resolved {
+            self.markout_fills += 1;
+            self.markout_total += markout;
+            if markout < Decimal::ZERO {
+                self.markout_adverse += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+
+    fn message(json: &str) -> StreamMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn order(id: &str, kind: &str, price: &str, matched: &str) -> StreamMessage {
+        message(&format!(
+            r#"{{"event_type":"order","id":"{id}","market":"0xabc","asset_id":"1","side":"BUY","price":"{price}","type":"{kind}","original_size":"10","size_matched":"{matched}"}}"#
+        ))
+    }
+
+    fn mid(bid: &str, ask: &str) -> StreamMessage {
+        message(&format!(
+            r#"{{"event_type":"best_bid_ask","market":"0xabc","asset_id":"1","best_bid":"{bid}","best_ask":"{ask}","spread":"0.02","timestamp":"1"}}"#
+        ))
+    }
+
+    #[test]
+    fn test_lifetimes_fill_ratios_and_markouts() {
+        let clock = VirtualClock::new(0);
+        let mut analytics = QuoteAnalytics::default().with_clock(clock.shared());
+        analytics.observe(&mid("0.49", "0.51"));
+
+        // Joins the bid at the mid's edge, filled in two parts
+        analytics.observe(&order("near", "PLACEMENT", "0.49", "0"));
+        // Far from the mid, later cancelled
+        analytics.observe(&order("far", "PLACEMENT", "0.40", "0"));
+        clock.advance(Duration::from_secs(2));
+        analytics.observe(&order("near", "UPDATE", "0.49", "4"));
+        clock.advance(Duration::from_secs(2));
+        analytics.observe(&order("near", "UPDATE", "0.49", "10"));
+        clock.advance(Duration::from_secs(4));
+        analytics.observe(&order("far", "CANCELLATION", "0.40", "0"));
+
+        // The mid falls after the fills: both were adversely selected
+        clock.advance(Duration::from_secs(10));
+        analytics.observe(&mid("0.45", "0.47"));
+
+        let report = analytics.report();
+        assert_eq!(report.filled_lifetimes.count, 1);
+        assert_eq!(report.filled_lifetimes.mean, Some(Duration::from_secs(4)));
+        assert_eq!(
+            report.cancelled_lifetimes.median,
+            Some(Duration::from_secs(8))
+        );
+        assert_eq!(report.open_orders, 0);
+
+        let near = &report.by_distance[1];
+        assert_eq!(
+            (near.max_distance, near.placed, near.filled),
+            (Some(dec!(0.01)), 1, 1)
+        );
+        let far = report.by_distance.last().unwrap();
+        assert_eq!((far.placed, far.fill_ratio()), (1, Some(0.0)));
+
+        assert_eq!(report.markouts.fills, 2);
+        assert_eq!(report.markouts.adverse_ratio(), Some(1.0));
+        assert_eq!(report.markouts.mean_markout, Some(dec!(-0.03)));
+    }
+}
@@ -0,0 +1,557 @@
+//! Order state reconciliation
+//!
+//! [`OrderTracker`] keeps a local view of open orders and seen trades from
+//! user-channel events. Events missed during a reconnect leave that view
+//! wrong, so [`Reconciler`] periodically cross-checks it against
+//! `get_orders`/`get_trades`, heals the tracker to match the exchange, and
+//! reports every correction as a [`Discrepancy`].
+//!
+//! The tracker also knows what each open order holds back: collateral for
+//! buys, shares for sells ([`OrderTracker::locked_for`],
+//! [`OrderTracker::locked_total`]). Fills shrink it and cancels release it.
+
+use crate::api::ClobApi;
+use crate::errors::{PolyfillError, Result};
+use crate::types::{OpenOrder, OrderMessage, Side, SignedOrderRequest, StreamMessage, TradeParams};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+/// Local view of an open order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackedOrder {
+    pub id: String,
+    pub market: String,
+    pub asset_id: String,
+    pub side: Side,
+    pub price: Decimal,
+    pub original_size: Decimal,
+    pub size_matched: Decimal,
+    /// GTD expiration in seconds since the epoch
+    #[serde(default)]
+    pub expiration: Option<u64>,
+    /// What the signed order offers (collateral for buys, shares for
+    /// sells), when it was tracked from the signed payload
+    #[serde(default)]
+    pub maker_amount: Option<Decimal>,
+}
+
+impl TrackedOrder {
+    /// Track a just-posted order from its signed payload
+    pub fn from_signed(
+        order_id: impl Into<String>,
+        market: impl Into<String>,
+        order: &SignedOrderRequest,
+    ) -> Result<Self> {
+        let amount = |value: &str| {
+            value
+                .parse::<Decimal>()
+                .map(|units| units / Decimal::from(1_000_000))
+                .map_err(|e| {
+                    PolyfillError::parse(format!("Invalid order amount {value}: {e}"), None)
+                })
+        };
+        let side: Side = order.side.parse()?;
+        let maker_amount = amount(&order.maker_amount)?;
+        let taker_amount = amount(&order.taker_amount)?;
+        let (size, collateral) = match side {
+            Side::BUY => (taker_amount, maker_amount),
+            Side::SELL => (maker_amount, taker_amount),
+        };
+        if size.is_zero() {
+            return Err(PolyfillError::validation("Signed order has zero size"));
+        }
+        let expiration = order.expiration.parse::<u64>().unwrap_or_default();
+        Ok(Self {
+            id: order_id.into(),
+            market: market.into(),
+            asset_id: order.token_id.clone(),
+            side,
+            price: collateral / size,
+            original_size: size,
+            size_matched: Decimal::ZERO,
+            expiration: (expiration > 0).then_some(expiration),
+            maker_amount: Some(maker_amount),
+        })
+    }
+
+    /// Size not yet matched
+    pub fn remaining(&self) -> Decimal {
+        (self.original_size - self.size_matched).max(Decimal::ZERO)
+    }
+
+    /// Collateral (buys) or shares (sells) the order still holds back.
+    ///
+    /// The unmatched share of the signed maker amount when known, so the
+    /// exchange's rounding carries over; otherwise derived from price and
+    /// size.
+    pub fn locked(&self) -> Decimal {
+        match self.maker_amount {
+            Some(maker_amount) if !self.original_size.is_zero() => {
+                maker_amount * self.remaining() / self.original_size
+            },
+            _ => match self.side {
+                Side::BUY => self.remaining() * self.price,
+                Side::SELL => self.remaining(),
+            },
+        }
+    }
+}
+
+/// What open orders hold back
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockedAmounts {
+    /// Collateral held by buys
+    pub collateral: Decimal,
+    /// Shares held by sells
+    pub shares: Decimal,
+}
+
+impl LockedAmounts {
+    fn add(&mut self, order: &TrackedOrder) {
+        match order.side {
+            Side::BUY => self.collateral += order.locked(),
+            Side::SELL => self.shares += order.locked(),
+        }
+    }
+}
+
+impl From<&OpenOrder> for TrackedOrder {
+    fn from(order: &OpenOrder) -> Self {
+        Self {
+            id: order.id.clone(),
+            market: order.market.clone(),
+            asset_id: order.asset_id.clone(),
+            side: order.side,
+            price: order.price,
+            original_size: order.original_size,
+            size_matched: order.size_matched,
+            expiration: (order.expiration > 0).then_some(order.expiration),
+            maker_amount: None,
+        }
+    }
+}
+
+/// Open orders and seen trades, fed by the user channel
+#[derive(Debug, Default)]
+pub struct OrderTracker {
+    orders: RwLock<HashMap<String, TrackedOrder>>,
+    trades: RwLock<HashSet<String>>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a user-channel message; other messages are ignored
+    pub fn apply(&self, message: &StreamMessage) {
+        match message {
+            StreamMessage::Order(order) => self.apply_order_update(order),
+            StreamMessage::Trade(trade) => {
+                self.trades.write().insert(trade.id.clone());
+            },
+            _ => {},
+        }
+    }
+
+    fn apply_order_update(&self, update: &OrderMessage) {
+        let mut orders = self.orders.write();
+        if update.msg_type.as_deref() == Some("CANCELLATION") {
+            orders.remove(&update.id);
+            return;
+        }
+
+        let order = orders
+            .entry(update.id.clone())
+            .or_insert_with(|| TrackedOrder {
+                id: update.id.clone(),
+                market: update.market.clone(),
+                asset_id: update.asset_id.clone(),
+                side: update.side,
+                price: update.price,
+                original_size: Decimal::ZERO,
+                size_matched: Decimal::ZERO,
+                expiration: None,
+                maker_amount: None,
+            });
+        if let Some(expiration) = update.expiration {
+            order.expiration = (expiration > 0).then_some(expiration);
+        }
+        if let Some(original_size) = update.original_size {
+            order.original_size = original_size;
+        }
+        if let Some(size_matched) = update.size_matched {
+            order.size_matched = size_matched;
+        }
+        if !order.original_size.is_zero() && order.size_matched >= order.original_size {
+            orders.remove(&update.id);
+        }
+    }
+
+    /// Start tracking an order placed or fetched over REST
+    pub fn track(&self, order: &OpenOrder) {
+        self.orders
+            .write()
+            .insert(order.id.clone(), TrackedOrder::from(order));
+    }
+
+    /// Start tracking an order known only locally, e.g. one just posted
+    pub fn insert(&self, order: TrackedOrder) {
+        self.orders.write().insert(order.id.clone(), order);
+    }
+
+    /// Stop tracking an order, returning it if it was tracked
+    pub fn remove(&self, order_id: &str) -> Option<TrackedOrder> {
+        self.orders.write().remove(order_id)
+    }
+
+    /// Orders whose GTD expiration is at or before `now` (seconds since the
+    /// epoch)
+    pub fn expired_by(&self, now: u64) -> Vec<TrackedOrder> {
+        self.orders
+            .read()
+            .values()
+            .filter(|order| order.expiration.is_some_and(|at| at <= now))
+            .cloned()
+            .collect()
+    }
+
+    pub fn get(&self, order_id: &str) -> Option<TrackedOrder> {
+        self.orders.read().get(order_id).cloned()
+    }
+
+    pub fn open_orders(&self) -> Vec<TrackedOrder> {
+        self.orders.read().values().cloned().collect()
+    }
+
+    /// What open orders on `token_id` hold back
+    pub fn locked_for(&self, token_id: &str) -> LockedAmounts {
+        let mut locked = LockedAmounts::default();
+        for order in self.orders.read().values() {
+            if order.asset_id == token_id {
+                locked.add(order);
+            }
+        }
+        locked
+    }
+
+    /// [`Self::locked_for`] of every token with open orders
+    pub fn locked_by_token(&self) -> HashMap<String, LockedAmounts> {
+        let mut locked: HashMap<String, LockedAmounts> = HashMap::new();
+        for order in self.orders.read().values() {
+            locked.entry(order.asset_id.clone()).or_default().add(order);
+        }
+        locked
+    }
+
+    /// What all open orders hold back; collateral is shared across markets
+    pub fn locked_total(&self) -> LockedAmounts {
+        let mut locked = LockedAmounts::default();
+        for order in self.orders.read().values() {
+            locked.add(order);
+        }
+        locked
+    }
+
+    /// Whether a trade has been seen on the channel or reconciled
+    pub fn has_trade(&self, trade_id: &str) -> bool {
+        self.trades.read().contains(trade_id)
+    }
+
+    /// IDs of every trade seen so far
+    pub fn seen_trades(&self) -> Vec<String> {
+        self.trades.read().iter().cloned().collect()
+    }
+
+    /// Reload orders and seen trades saved before a restart
+    pub fn restore(
+        &self,
+        orders: impl IntoIterator<Item = TrackedOrder>,
+        trades: impl IntoIterator<Item = String>,
+    ) {
+        self.orders
+            .write()
+            .extend(orders.into_iter().map(|order| (order.id.clone(), order)));
+        self.trades.write().extend(trades);
+    }
+}
+
+/// A difference between the tracker and the exchange, already healed
+#[derive(Debug, Clone)]
+pub enum Discrepancy {
+    /// Open on the exchange but unknown locally; now tracked
+    UntrackedOrder(OpenOrder),
+    /// Tracked as open but no longer open on the exchange; now dropped.
+    ///
+    /// `status` is the exchange's final status when it could be fetched.
+    ClosedOrder {
+        order: TrackedOrder,
+        status: Option<String>,
+    },
+    /// Matched size differs; the tracker now holds the exchange's value
+    SizeMismatch {
+        order_id: String,
+        local: Decimal,
+        exchange: Decimal,
+    },
+    /// Trade reported by `get_trades` that never arrived on the channel
+    MissedTrade { trade_id: String, trade: Value },
+}
+
+/// Periodically heals an [`OrderTracker`] against the exchange
+#[derive(Debug)]
+pub struct Reconciler<A> {
+    api: Arc<A>,
+    tracker: Arc<OrderTracker>,
+    trade_lookback: Duration,
+}
+
+impl<A: ClobApi> Reconciler<A> {
+    pub fn new(api: Arc<A>, tracker: Arc<OrderTracker>) -> Self {
+        Self {
+            api,
+            tracker,
+            trade_lookback: Duration::from_secs(60 * 60),
+        }
+    }
+
+    /// How far back `get_trades` is checked for missed trades
+    pub fn with_trade_lookback(mut self, lookback: Duration) -> Self {
+        self.trade_lookback = lookback;
+        self
+    }
+
+    pub fn tracker(&self) -> &Arc<OrderTracker> {
+        &self.tracker
+    }
+
+    /// Cross-check once, heal the tracker and return what was corrected
+    pub async fn reconcile_once(&self) -> Result<Vec<Discrepancy>> {
+        let mut discrepancies = Vec::new();
+
+        let exchange_orders = self.api.get_orders(None, None).await?;
+        let exchange_ids: HashSet<&str> = exchange_orders.iter().map(|o| o.id.as_str()).collect();
+
+        for order in &exchange_orders {
+            match self.tracker.get(&order.id) {
+                None => {
+                    self.tracker.track(order);
+                    discrepancies.push(Discrepancy::UntrackedOrder(order.clone()));
+                },
+                Some(local) if local.size_matched != order.size_matched => {
+                    self.tracker.track(order);
+                    discrepancies.push(Discrepancy::SizeMismatch {
+                        order_id: order.id.clone(),
+                        local: local.size_matched,
+                        exchange: order.size_matched,
+                    });
+                },
+                Some(_) => {},
+            }
+        }
+
+        for local in self.tracker.open_orders() {
+            if exchange_ids.contains(local.id.as_str()) {
+                continue;
+            }
+            let status = match self.api.get_order(&local.id).await {
+                Ok(order) => Some(order.status),
+                Err(e) => {
+                    debug!("Could not fetch final status of {}: {}", local.id, e);
+                    None
+                },
+            };
+            self.tracker.orders.write().remove(&local.id);
+            discrepancies.push(Discrepancy::ClosedOrder {
+                order: local,
+                status,
+            });
+        }
+
+        let after = chrono::Utc::now()
+            - chrono::Duration::from_std(self.trade_lookback).unwrap_or_default();
+        let params = TradeParams {
+            id: None,
+            maker_address: None,
+            market: None,
+            asset_id: None,
+            before: None,
+            after: Some(after.timestamp().max(0) as u64),
+        };
+        for trade in self.api.get_trades(Some(&params), None).await? {
+            let Some(trade_id) = trade.get("id").and_then(Value::as_str) else {
+                continue;
+            };
+            if self.tracker.trades.write().insert(trade_id.to_string()) {
+                discrepancies.push(Discrepancy::MissedTrade {
+                    trade_id: trade_id.to_string(),
+                    trade,
+                });
+            }
+        }
+
+        if !discrepancies.is_empty() {
+            warn!(
+                "Reconciliation healed {} discrepancies",
+                discrepancies.len()
+            );
+        }
+        Ok(discrepancies)
+    }
+
+    /// Reconcile every `interval` until the receiver is dropped.
+    ///
+    /// Failed passes are logged and retried at the next tick.
+    pub async fn run(self, interval: Duration, events: mpsc::UnboundedSender<Discrepancy>) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            ticker.tick().await;
+            if events.is_closed() {
+                return;
+            }
+            match self.reconcile_once().await {
+                Ok(discrepancies) => {
+                    for discrepancy in discrepancies {
+                        if events.send(discrepancy).is_err() {
+                            return;
+                        }
+                    }
+                },
+                Err(e) => warn!("Order reconciliation failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FakeClob, FakeToken};
+    use crate::types::OrderArgs;
+    use rust_decimal_macros::dec;
+
+    fn message(json: &str) -> StreamMessage {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_tracker_follows_user_channel() {
+        let tracker = OrderTracker::new();
+        tracker.apply(&message(
+            r#"{"event_type":"order","id":"o1","market":"0xabc","asset_id":"1","side":"BUY","price":"0.5","type":"PLACEMENT","original_size":"10","size_matched":"0"}"#,
+        ));
+        tracker.apply(&message(
+            r#"{"event_type":"order","id":"o1","market":"0xabc","asset_id":"1","side":"BUY","price":"0.5","type":"UPDATE","size_matched":"4"}"#,
+        ));
+        assert_eq!(tracker.get("o1").unwrap().size_matched, dec!(4));
+
+        tracker.apply(&message(
+            r#"{"event_type":"trade","id":"t1","market":"0xabc","asset_id":"1","side":"BUY","size":"4","price":"0.5"}"#,
+        ));
+        assert!(tracker.has_trade("t1"));
+
+        tracker.apply(&message(
+            r#"{"event_type":"order","id":"o1","market":"0xabc","asset_id":"1","side":"BUY","price":"0.5","type":"CANCELLATION"}"#,
+        ));
+        assert!(tracker.open_orders().is_empty());
+    }
+
+    #[test]
+    fn test_locked_amounts_follow_fills_and_cancels() {
+        let tracker = OrderTracker::new();
+        let signed = SignedOrderRequest {
+            salt: 1,
+            maker: "0x1".to_string(),
+            signer: "0x1".to_string(),
+            token_id: "1".to_string(),
+            maker_amount: "5005000".to_string(),
+            taker_amount: "10000000".to_string(),
+            expiration: "0".to_string(),
+            side: "BUY".to_string(),
+            signature_type: 0,
+            timestamp: "0".to_string(),
+            metadata: String::new(),
+            builder: String::new(),
+            signature: "0xsig".to_string(),
+        };
+        let buy = TrackedOrder::from_signed("o1", "0xabc", &signed).unwrap();
+        assert_eq!((buy.original_size, buy.price), (dec!(10), dec!(0.5005)));
+        tracker.insert(buy);
+        tracker.apply(&message(
+            r#"{"event_type":"order","id":"s1","market":"0xabc","asset_id":"2","side":"SELL","price":"0.6","type":"PLACEMENT","original_size":"8","size_matched":"0"}"#,
+        ));
+        assert_eq!(tracker.locked_for("1").collateral, dec!(5.005));
+        assert_eq!(tracker.locked_by_token()["2"].shares, dec!(8));
+
+        tracker.apply(&message(
+            r#"{"event_type":"order","id":"o1","market":"0xabc","asset_id":"1","side":"BUY","price":"0.5005","type":"UPDATE","size_matched":"4"}"#,
+        ));
+        assert_eq!(
+            tracker.locked_total(),
+            LockedAmounts {
+                collateral: dec!(3.003),
+                shares: dec!(8),
+            }
+        );
+        tracker.apply(&message(
+            r#"{"event_type":"order","id":"s1","market":"0xabc","asset_id":"2","side":"SELL","price":"0.6","type":"CANCELLATION"}"#,
+        ));
+        assert_eq!(tracker.locked_for("2"), LockedAmounts::default());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_heals_missed_events() {
+        let fake = Arc::new(FakeClob::new());
+        fake.set_token("1", FakeToken::new("0xabc"));
+        let tracker = Arc::new(OrderTracker::new());
+        let reconciler = Reconciler::new(fake.clone(), tracker.clone());
+
+        // Placed while disconnected: the channel never reported it
+        let placed = fake
+            .create_and_post_order(
+                &OrderArgs::new("1".parse().unwrap(), dec!(0.5), dec!(10), Side::BUY),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let healed = reconciler.reconcile_once().await.unwrap();
+        assert!(matches!(&healed[..], [Discrepancy::UntrackedOrder(o)] if o.id == placed.order_id));
+        assert!(tracker.get(&placed.order_id).is_some());
+        assert!(reconciler.reconcile_once().await.unwrap().is_empty());
+
+        // Partially filled and a trade reported only over REST
+        let mut order = fake.open_orders().remove(0);
+        order.size_matched = dec!(3);
+        fake.insert_order(order);
+        fake.add_trade(serde_json::json!({"id": "t9", "size": "3"}));
+        let healed = reconciler.reconcile_once().await.unwrap();
+        assert_eq!(healed.len(), 2);
+        assert!(matches!(
+            &healed[0],
+            Discrepancy::SizeMismatch { exchange, .. } if *exchange == dec!(3)
+        ));
+        assert!(
+            matches!(&healed[1], Discrepancy::MissedTrade { trade_id, .. } if trade_id == "t9")
+        );
+        assert_eq!(tracker.get(&placed.order_id).unwrap().size_matched, dec!(3));
+
+        // Cancelled out of band
+        fake.cancel(&placed.order_id).await.unwrap();
+        let healed = reconciler.reconcile_once().await.unwrap();
+        assert!(matches!(
+            &healed[..],
+            [Discrepancy::ClosedOrder { status: None, .. }]
+        ));
+        assert!(tracker.open_orders().is_empty());
+    }
+}
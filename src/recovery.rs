@@ -0,0 +1,265 @@
+//! Crash-recovery snapshots
+//!
+//! [`save_state`] writes what a bot needs to pick up where it left off:
+//! tracked open orders and seen trades, positions, armed triggers and stream
+//! subscriptions. After a crash or upgrade, [`load_state`] reads it back and
+//! [`RuntimeState::resume`] rebuilds the [`OrderTracker`] and reconciles it
+//! against the exchange, so only what changed while the bot was down is
+//! corrected instead of rebuilding everything from scratch.
+//!
+//! The snapshot is a JSON file written through a temporary file and a rename,
+//! so a crash mid-save leaves the previous snapshot intact. API credentials
+//! are never written; resubscribe with the client's own.
+
+use crate::api::ClobApi;
+use crate::errors::{PolyfillError, Result};
+use crate::reconcile::{Discrepancy, OrderTracker, Reconciler, TrackedOrder};
+use crate::stream::{SubscriptionState, SubscriptionStatus};
+use crate::types::{Side, WssChannelType};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Arc;
+use tracing::info;
+
+/// Current snapshot format version
+pub const STATE_VERSION: u32 = 1;
+
+/// Net position in a single outcome token
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub token_id: String,
+    pub size: Decimal,
+    pub average_price: Decimal,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Position {
+    /// Flat position in `token_id`
+    pub fn new(token_id: &str) -> Self {
+        Self {
+            token_id: token_id.to_string(),
+            size: Decimal::ZERO,
+            average_price: Decimal::ZERO,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Apply a fill to the position.
+    ///
+    /// Buys increase the position at a size-weighted average price; sells
+    /// reduce it and keep the average price of what remains.
+    pub fn apply_fill(&mut self, side: Side, size: Decimal, price: Decimal) {
+        match side {
+            Side::BUY => {
+                let new_size = self.size + size;
+                if !new_size.is_zero() {
+                    self.average_price = (self.average_price * self.size + price * size) / new_size;
+                }
+                self.size = new_size;
+            },
+            Side::SELL => {
+                self.size -= size;
+                if self.size.is_zero() {
+                    self.average_price = Decimal::ZERO;
+                }
+            },
+        }
+        self.updated_at = Utc::now();
+    }
+}
+
+/// A trigger (stop, take-profit, alert, ...) that was armed before shutdown
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArmedTrigger {
+    pub id: String,
+    /// Caller-defined trigger kind, e.g. `"stop_loss"`
+    pub kind: String,
+    pub token_id: String,
+    /// Caller-defined trigger parameters
+    pub params: serde_json::Value,
+    pub armed_at: DateTime<Utc>,
+}
+
+/// A stream subscription to restore
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSubscription {
+    pub channel: WssChannelType,
+    /// Asset ID for the market channel, market ID for the user channel
+    pub id: String,
+}
+
+/// Runtime state saved by [`save_state`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeState {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    #[serde(default)]
+    pub orders: Vec<TrackedOrder>,
+    #[serde(default)]
+    pub seen_trades: Vec<String>,
+    #[serde(default)]
+    pub positions: Vec<Position>,
+    #[serde(default)]
+    pub triggers: Vec<ArmedTrigger>,
+    #[serde(default)]
+    pub subscriptions: Vec<SavedSubscription>,
+}
+
+impl RuntimeState {
+    /// Capture the orders and seen trades of `tracker`
+    pub fn capture(tracker: &OrderTracker) -> Self {
+        Self {
+            version: STATE_VERSION,
+            saved_at: Utc::now(),
+            orders: tracker.open_orders(),
+            seen_trades: tracker.seen_trades(),
+            positions: Vec::new(),
+            triggers: Vec::new(),
+            subscriptions: Vec::new(),
+        }
+    }
+
+    pub fn with_positions(mut self, positions: Vec<Position>) -> Self {
+        self.positions = positions;
+        self
+    }
+
+    pub fn with_triggers(mut self, triggers: Vec<ArmedTrigger>) -> Self {
+        self.triggers = triggers;
+        self
+    }
+
+    /// Save the stream's subscriptions, skipping failed ones
+    pub fn with_subscriptions(mut self, subscriptions: &[SubscriptionStatus]) -> Self {
+        self.subscriptions = subscriptions
+            .iter()
+            .filter(|status| !matches!(status.state, SubscriptionState::Failed(_)))
+            .map(|status| SavedSubscription {
+                channel: status.channel,
+                id: status.id.clone(),
+            })
+            .collect();
+        self
+    }
+
+    /// Asset IDs to resubscribe on the market channel
+    pub fn market_asset_ids(&self) -> Vec<String> {
+        self.subscribed(WssChannelType::Market)
+    }
+
+    /// Markets to resubscribe on the user channel
+    pub fn user_markets(&self) -> Vec<String> {
+        self.subscribed(WssChannelType::User)
+    }
+
+    fn subscribed(&self, channel: WssChannelType) -> Vec<String> {
+        self.subscriptions
+            .iter()
+            .filter(|subscription| subscription.channel == channel)
+            .map(|subscription| subscription.id.clone())
+            .collect()
+    }
+
+    /// Tracker holding the saved orders and seen trades
+    pub fn tracker(&self) -> OrderTracker {
+        let tracker = OrderTracker::new();
+        tracker.restore(
+            self.orders.iter().cloned(),
+            self.seen_trades.iter().cloned(),
+        );
+        tracker
+    }
+
+    /// Restore the tracker and reconcile it once against `api`.
+    ///
+    /// Returns the tracker, now matching the exchange, and what changed while
+    /// the bot was down.
+    pub async fn resume<A: ClobApi>(
+        &self,
+        api: Arc<A>,
+    ) -> Result<(Arc<OrderTracker>, Vec<Discrepancy>)> {
+        let tracker = Arc::new(self.tracker());
+        let discrepancies = Reconciler::new(api, tracker.clone())
+            .reconcile_once()
+            .await?;
+        info!(
+            "Resumed state saved at {}: {} orders, {} corrections",
+            self.saved_at,
+            tracker.open_orders().len(),
+            discrepancies.len()
+        );
+        Ok((tracker, discrepancies))
+    }
+}
+
+/// Atomically write `state` to `path`
+pub fn save_state(path: impl AsRef<Path>, state: &RuntimeState) -> Result<()> {
+    let path = path.as_ref();
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = std::path::PathBuf::from(tmp);
+
+    let body = serde_json::to_vec_pretty(state)?;
+    std::fs::write(&tmp, body)
+        .map_err(|e| PolyfillError::internal(format!("Failed to write {}", tmp.display()), e))?;
+    std::fs::rename(&tmp, path)
+        .map_err(|e| PolyfillError::internal(format!("Failed to replace {}", path.display()), e))
+}
+
+/// Read a snapshot written by [`save_state`]
+pub fn load_state(path: impl AsRef<Path>) -> Result<RuntimeState> {
+    let path = path.as_ref();
+    let body = std::fs::read(path)
+        .map_err(|e| PolyfillError::internal(format!("Failed to read {}", path.display()), e))?;
+    let state: RuntimeState = serde_json::from_slice(&body)?;
+    if state.version > STATE_VERSION {
+        return Err(PolyfillError::config(format!(
+            "State snapshot version {} is newer than supported version {STATE_VERSION}",
+            state.version
+        )));
+    }
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FakeClob, FakeToken};
+    use crate::types::OrderArgs;
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_save_load_and_resume() {
+        let fake = Arc::new(FakeClob::new());
+        fake.set_token("1", FakeToken::new("0xabc"));
+        let placed = fake
+            .create_and_post_order(
+                &OrderArgs::new("1".parse().unwrap(), dec!(0.5), dec!(10), Side::BUY),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let tracker = OrderTracker::new();
+        tracker.track(&fake.open_orders()[0]);
+        let mut position = Position::new("1");
+        position.apply_fill(Side::BUY, dec!(4), dec!(0.5));
+        let state = RuntimeState::capture(&tracker).with_positions(vec![position]);
+
+        let path =
+            std::env::temp_dir().join(format!("polyfill-runtime-{}.json", uuid::Uuid::new_v4()));
+        save_state(&path, &state).unwrap();
+        let loaded = load_state(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, state);
+
+        // Cancelled while the bot was down
+        fake.cancel(&placed.order_id).await.unwrap();
+        let (tracker, healed) = loaded.resume(fake).await.unwrap();
+        assert!(matches!(&healed[..], [Discrepancy::ClosedOrder { .. }]));
+        assert!(tracker.open_orders().is_empty());
+    }
+}
@@ -0,0 +1,408 @@
+//! Redeem-on-resolution automation
+//!
+//! Winning outcome tokens only turn into collateral once they are redeemed
+//! on-chain. [`RedeemAutomation`] watches the market channel for
+//! `market_resolved` events and, for every market the account holds, builds
+//! the `redeemPositions` call: on the Conditional Tokens contract for
+//! standard markets, on the neg-risk adapter for neg-risk ones. Once the
+//! [`RedeemPolicy`] allows it the call goes to a [`RedemptionSender`] and a
+//! [`RedemptionEvent`] with the transaction hash is broadcast.
+//!
+//! The crate does not broadcast transactions itself: the sender wraps
+//! whichever wallet and RPC provider the bot already uses. Holdings come from
+//! the data API, e.g. [`crate::ClobClient::account_summary`].
+
+use crate::errors::{PolyfillError, Result};
+use crate::types::{DataApiPosition, MarketResolved, StreamMessage};
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::SolCall;
+use futures::{Stream, StreamExt};
+use parking_lot::{Mutex, RwLock};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Neg-risk adapter on Polygon
+pub const NEG_RISK_ADAPTER: &str = "0xd91E80cF2E7be2e162c6513ceD06f1dD0dA35296";
+
+/// Token amounts are in 6-decimal base units
+const TOKEN_UNIT_SCALE: Decimal = Decimal::from_parts(1_000_000, 0, 0, false, 0);
+
+mod ctf {
+    alloy_sol_types::sol! {
+        function redeemPositions(
+            address collateralToken,
+            bytes32 parentCollectionId,
+            bytes32 conditionId,
+            uint256[] indexSets
+        );
+    }
+}
+
+mod neg_risk_adapter {
+    alloy_sol_types::sol! {
+        function redeemPositions(bytes32 conditionId, uint256[] amounts);
+    }
+}
+
+/// Transaction redeeming one resolved market
+#[derive(Debug, Clone, PartialEq)]
+pub struct RedeemCall {
+    pub condition_id: String,
+    pub winning_asset_id: String,
+    /// Winning shares held, each redeemed for 1 unit of collateral
+    pub amount: Decimal,
+    pub neg_risk: bool,
+    /// Contract to call
+    pub to: Address,
+    /// ABI-encoded calldata
+    pub data: Vec<u8>,
+}
+
+/// Submits redemption transactions on behalf of [`RedeemAutomation`]
+pub trait RedemptionSender: Send + Sync {
+    /// Sign and broadcast `call`, returning the transaction hash
+    fn send_redemption(&self, call: &RedeemCall) -> impl Future<Output = Result<String>> + Send;
+}
+
+type ConfirmFn = Arc<dyn Fn(&RedeemCall) -> bool + Send + Sync>;
+
+/// When a redeemable position is actually redeemed
+#[derive(Clone)]
+pub enum RedeemPolicy {
+    /// Redeem as soon as the market resolves
+    Automatic,
+    /// Redeem only if at least this many winning shares are held
+    MinAmount(Decimal),
+    /// Ask `confirm` before every redemption
+    Confirm(ConfirmFn),
+    /// Never send; only emit [`RedemptionEvent::Pending`]
+    Manual,
+}
+
+impl std::fmt::Debug for RedeemPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Automatic => write!(f, "Automatic"),
+            Self::MinAmount(amount) => f.debug_tuple("MinAmount").field(amount).finish(),
+            Self::Confirm(_) => write!(f, "Confirm(..)"),
+            Self::Manual => write!(f, "Manual"),
+        }
+    }
+}
+
+impl RedeemPolicy {
+    fn allows(&self, call: &RedeemCall) -> bool {
+        match self {
+            Self::Automatic => true,
+            Self::MinAmount(amount) => call.amount >= *amount,
+            Self::Confirm(confirm) => confirm(call),
+            Self::Manual => false,
+        }
+    }
+}
+
+/// What happened to a resolved market the account holds
+#[derive(Debug, Clone, PartialEq)]
+pub enum RedemptionEvent {
+    Redeemed {
+        call: RedeemCall,
+        tx_hash: String,
+    },
+    /// The policy held the redemption back
+    Pending {
+        call: RedeemCall,
+    },
+    Failed {
+        call: RedeemCall,
+        error: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Holding {
+    size: Decimal,
+    neg_risk: bool,
+}
+
+/// Redeems winning positions when their markets resolve
+pub struct RedeemAutomation<S> {
+    sender: S,
+    policy: RedeemPolicy,
+    collateral: Address,
+    conditional_tokens: Address,
+    neg_risk_adapter: Address,
+    holdings: RwLock<HashMap<String, Holding>>,
+    handled: Mutex<HashSet<String>>,
+    events: broadcast::Sender<RedemptionEvent>,
+}
+
+impl<S> std::fmt::Debug for RedeemAutomation<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedeemAutomation")
+            .field("policy", &self.policy)
+            .field("holdings", &self.holdings.read().len())
+            .finish()
+    }
+}
+
+fn parse_address(name: &str, value: &str) -> Result<Address> {
+    Address::from_str(value)
+        .map_err(|e| PolyfillError::config(format!("Invalid {name} address: {e}")))
+}
+
+impl<S: RedemptionSender> RedeemAutomation<S> {
+    /// Automation for `chain_id` with the [`RedeemPolicy::Manual`] policy
+    pub fn new(sender: S, chain_id: u64) -> Result<Self> {
+        let contracts = crate::orders::get_contract_config(chain_id, false).ok_or_else(|| {
+            PolyfillError::config(format!("No contracts configured for chain {chain_id}"))
+        })?;
+        let (events, _) = broadcast::channel(256);
+        Ok(Self {
+            sender,
+            policy: RedeemPolicy::Manual,
+            collateral: parse_address("collateral", &contracts.collateral)?,
+            conditional_tokens: parse_address("conditional tokens", &contracts.conditional_tokens)?,
+            neg_risk_adapter: parse_address("neg-risk adapter", NEG_RISK_ADAPTER)?,
+            holdings: RwLock::new(HashMap::new()),
+            handled: Mutex::new(HashSet::new()),
+            events,
+        })
+    }
+
+    pub fn with_policy(mut self, policy: RedeemPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Collateral token the standard markets were split from
+    pub fn with_collateral(mut self, collateral: Address) -> Self {
+        self.collateral = collateral;
+        self
+    }
+
+    pub fn with_neg_risk_adapter(mut self, adapter: Address) -> Self {
+        self.neg_risk_adapter = adapter;
+        self
+    }
+
+    /// Replace the tracked holdings with `positions`
+    pub fn update_positions(&self, positions: &[DataApiPosition]) {
+        *self.holdings.write() = positions
+            .iter()
+            .filter(|position| position.size > Decimal::ZERO)
+            .map(|position| {
+                (
+                    position.asset.clone(),
+                    Holding {
+                        size: position.size,
+                        neg_risk: position.negative_risk,
+                    },
+                )
+            })
+            .collect();
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<RedemptionEvent> {
+        self.events.subscribe()
+    }
+
+    /// Redemption for `resolved`, or `None` if no winning shares are held.
+    ///
+    /// Outcome slots follow the order of `resolved.asset_ids`.
+    pub fn redeem_call(&self, resolved: &MarketResolved) -> Result<Option<RedeemCall>> {
+        let holdings = self.holdings.read();
+        let Some(winning) = holdings.get(&resolved.winning_asset_id) else {
+            return Ok(None);
+        };
+        let condition_id = B256::from_str(&resolved.market).map_err(|e| {
+            PolyfillError::validation(format!("Invalid condition ID '{}': {e}", resolved.market))
+        })?;
+
+        let (to, data) = if winning.neg_risk {
+            let amounts = resolved
+                .asset_ids
+                .iter()
+                .map(|asset| token_units(holdings.get(asset).map_or(Decimal::ZERO, |h| h.size)))
+                .collect::<Result<Vec<_>>>()?;
+            let call = neg_risk_adapter::redeemPositionsCall {
+                conditionId: condition_id,
+                amounts,
+            };
+            (self.neg_risk_adapter, call.abi_encode())
+        } else {
+            let call = ctf::redeemPositionsCall {
+                collateralToken: self.collateral,
+                parentCollectionId: B256::ZERO,
+                conditionId: condition_id,
+                indexSets: (0..resolved.asset_ids.len())
+                    .map(|slot| U256::from(1u64) << slot)
+                    .collect(),
+            };
+            (self.conditional_tokens, call.abi_encode())
+        };
+        Ok(Some(RedeemCall {
+            condition_id: resolved.market.clone(),
+            winning_asset_id: resolved.winning_asset_id.clone(),
+            amount: winning.size,
+            neg_risk: winning.neg_risk,
+            to,
+            data,
+        }))
+    }
+
+    /// Handle one market-channel message; each market is handled once
+    pub async fn observe(&self, message: &StreamMessage) -> Option<RedemptionEvent> {
+        let StreamMessage::MarketResolved(resolved) = message else {
+            return None;
+        };
+        let call = match self.redeem_call(resolved) {
+            Ok(Some(call)) => call,
+            Ok(None) => return None,
+            Err(e) => {
+                warn!("Cannot redeem {}: {}", resolved.market, e);
+                return None;
+            },
+        };
+        if !self.handled.lock().insert(call.condition_id.clone()) {
+            return None;
+        }
+
+        let event = if !self.policy.allows(&call) {
+            debug!("Redemption of {} held back by policy", call.condition_id);
+            RedemptionEvent::Pending { call }
+        } else {
+            match self.sender.send_redemption(&call).await {
+                Ok(tx_hash) => {
+                    info!("Redeemed {} in {}", call.condition_id, tx_hash);
+                    RedemptionEvent::Redeemed { call, tx_hash }
+                },
+                Err(e) => {
+                    warn!("Redemption of {} failed: {}", call.condition_id, e);
+                    // Allow a retry on the next resolution message
+                    self.handled.lock().remove(&call.condition_id);
+                    RedemptionEvent::Failed {
+                        call,
+                        error: e.to_string(),
+                    }
+                },
+            }
+        };
+        let _ = self.events.send(event.clone());
+        Some(event)
+    }
+
+    /// Observe a market-channel stream until it ends.
+    ///
+    /// Stream errors are logged and skipped.
+    pub async fn run<T>(&self, mut stream: T)
+    where
+        T: Stream<Item = Result<StreamMessage>> + Unpin,
+    {
+        while let Some(message) = stream.next().await {
+            match message {
+                Ok(message) => {
+                    self.observe(&message).await;
+                },
+                Err(e) => warn!("Market channel error: {}", e),
+            }
+        }
+    }
+}
+
+fn token_units(size: Decimal) -> Result<U256> {
+    (size * TOKEN_UNIT_SCALE)
+        .trunc()
+        .to_u128()
+        .map(U256::from)
+        .ok_or_else(|| PolyfillError::validation(format!("Invalid token amount {size}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const CONDITION: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+    struct RecordingSender(Mutex<Vec<RedeemCall>>);
+
+    impl RedemptionSender for RecordingSender {
+        async fn send_redemption(&self, call: &RedeemCall) -> Result<String> {
+            self.0.lock().push(call.clone());
+            Ok("0xfeed".to_string())
+        }
+    }
+
+    fn resolved(winner: &str) -> StreamMessage {
+        serde_json::from_str(&format!(
+            r#"{{"event_type":"market_resolved","id":"1","market":"{CONDITION}",
+                "assets_ids":["11","12"],"outcomes":["Yes","No"],
+                "winning_asset_id":"{winner}","winning_outcome":"Yes","timestamp":"1"}}"#
+        ))
+        .unwrap()
+    }
+
+    fn position(asset: &str, size: Decimal, negative_risk: bool) -> DataApiPosition {
+        serde_json::from_value(serde_json::json!({
+            "asset": asset,
+            "conditionId": CONDITION,
+            "size": size,
+            "negativeRisk": negative_risk,
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_redeems_held_winners_once() {
+        let automation = RedeemAutomation::new(RecordingSender(Mutex::new(Vec::new())), 137)
+            .unwrap()
+            .with_policy(RedeemPolicy::MinAmount(dec!(1)));
+        let mut events = automation.subscribe();
+        automation.update_positions(&[position("11", dec!(2.5), false)]);
+
+        // Only losing shares held
+        assert!(automation.observe(&resolved("12")).await.is_none());
+
+        let event = automation.observe(&resolved("11")).await.unwrap();
+        let RedemptionEvent::Redeemed { call, tx_hash } = event else {
+            panic!("expected a redemption, got {event:?}");
+        };
+        assert_eq!(tx_hash, "0xfeed");
+        assert_eq!(
+            events.recv().await.unwrap(),
+            RedemptionEvent::Redeemed {
+                call: call.clone(),
+                tx_hash,
+            }
+        );
+        let decoded = ctf::redeemPositionsCall::abi_decode(&call.data).unwrap();
+        assert_eq!(decoded.indexSets, vec![U256::from(1), U256::from(2)]);
+        assert!(automation.observe(&resolved("11")).await.is_none());
+        assert_eq!(automation.sender.0.lock().len(), 1);
+
+        // Neg-risk markets redeem exact amounts through the adapter
+        automation.update_positions(&[
+            position("11", dec!(3), true),
+            position("12", dec!(0.5), true),
+        ]);
+        let call = automation
+            .redeem_call(match &resolved("11") {
+                StreamMessage::MarketResolved(resolved) => resolved,
+                _ => unreachable!(),
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(call.to, automation.neg_risk_adapter);
+        let decoded = neg_risk_adapter::redeemPositionsCall::abi_decode(&call.data).unwrap();
+        assert_eq!(
+            decoded.amounts,
+            vec![U256::from(3_000_000), U256::from(500_000)]
+        );
+    }
+}
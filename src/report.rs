@@ -0,0 +1,419 @@
+//! Fee and volume reports from trade history
+//!
+//! [`TradeReport::fetch`] pulls every page of `get_trades` and aggregates the
+//! user's fills into daily or weekly buckets and per-market breakdowns:
+//! fills, shares, notional volume, fees and realized PnL with a win rate.
+//! The same report can be built offline from exported trade JSON with
+//! [`TradeReport::from_trades`], and written out with
+//! [`TradeReport::buckets_csv`] and [`TradeReport::markets_csv`].
+//!
+//! Fees are estimated as notional × `fee_rate_bps`, as in
+//! [`crate::fill::FillEngine`]. PnL is realized with average cost per token
+//! when a fill reduces a position; a reducing fill with positive PnL counts
+//! as a win. Positions held to resolution and redeemed do not show up in
+//! trades and are not counted.
+
+use crate::api::ClobApi;
+use crate::errors::{PolyfillError, Result};
+use crate::fill::ShadowPosition;
+use crate::types::{Side, TradeParams};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Bucket width of a [`TradeReport`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportPeriod {
+    Daily,
+    /// Weeks starting on Monday, UTC
+    Weekly,
+}
+
+impl ReportPeriod {
+    fn start(self, at: DateTime<Utc>) -> NaiveDate {
+        let day = at.date_naive();
+        match self {
+            ReportPeriod::Daily => day,
+            ReportPeriod::Weekly => {
+                day - Duration::days(i64::from(day.weekday().num_days_from_monday()))
+            },
+        }
+    }
+}
+
+/// One of the user's fills within a trade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportFill {
+    pub trade_id: String,
+    /// Condition ID
+    pub market: String,
+    pub asset_id: String,
+    pub side: Side,
+    pub size: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+    pub matched_at: DateTime<Utc>,
+    /// Whether the user was the maker
+    pub maker: bool,
+}
+
+impl ReportFill {
+    /// The user's fills in a trade returned by `get_trades`.
+    ///
+    /// Taker trades are one fill. For maker trades the user's legs are the
+    /// `maker_orders` with the trade's `owner`, or else its `maker_address`.
+    pub fn from_trade(trade: &Value) -> Result<Vec<Self>> {
+        let id = str_field(trade, "id")?;
+        let market = str_field(trade, "market")?;
+        let matched_at = str_field(trade, "match_time")
+            .and_then(|time| parse_field::<i64>(trade, "match_time", time))
+            .and_then(|secs| {
+                DateTime::from_timestamp(secs, 0).ok_or_else(|| {
+                    PolyfillError::parse(format!("Trade {id} has an invalid match_time"), None)
+                })
+            })?;
+
+        let fill = |leg: &Value, size_key: &str| -> Result<ReportFill> {
+            let size: Decimal = parse_field(leg, size_key, str_field(leg, size_key)?)?;
+            let price: Decimal = parse_field(leg, "price", str_field(leg, "price")?)?;
+            let fee_rate_bps = match leg.get("fee_rate_bps").and_then(Value::as_str) {
+                Some(bps) => parse_field::<Decimal>(leg, "fee_rate_bps", bps)?,
+                None => Decimal::ZERO,
+            };
+            Ok(ReportFill {
+                trade_id: id.to_string(),
+                market: market.to_string(),
+                asset_id: str_field(leg, "asset_id")?.to_string(),
+                side: Side::from_str(str_field(leg, "side")?)?,
+                size,
+                price,
+                fee: size * price * fee_rate_bps / Decimal::from(10_000),
+                matched_at,
+                maker: size_key == "matched_amount",
+            })
+        };
+
+        if trade.get("trader_side").and_then(Value::as_str) != Some("MAKER") {
+            return Ok(vec![fill(trade, "size")?]);
+        }
+        let legs = trade
+            .get("maker_orders")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let mine = |key: &str| {
+            let value = trade.get(key).and_then(Value::as_str)?;
+            let legs: Vec<&Value> = legs
+                .iter()
+                .filter(|leg| leg.get(key).and_then(Value::as_str) == Some(value))
+                .collect();
+            (!legs.is_empty()).then_some(legs)
+        };
+        let legs = mine("owner")
+            .or_else(|| mine("maker_address"))
+            .ok_or_else(|| {
+                PolyfillError::parse(
+                    format!("Maker trade {id} has no maker order of the user"),
+                    None,
+                )
+            })?;
+        legs.into_iter()
+            .map(|leg| fill(leg, "matched_amount"))
+            .collect()
+    }
+
+    pub fn notional(&self) -> Decimal {
+        self.size * self.price
+    }
+}
+
+/// Totals over a set of fills
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeStats {
+    pub fills: u64,
+    pub shares: Decimal,
+    pub volume: Decimal,
+    pub fees: Decimal,
+    pub realized_pnl: Decimal,
+    /// Position-reducing fills
+    pub closes: u64,
+    /// Position-reducing fills with positive realized PnL
+    pub wins: u64,
+}
+
+impl TradeStats {
+    /// Share of closing fills that made money
+    pub fn win_rate(&self) -> Option<Decimal> {
+        (self.closes > 0).then(|| Decimal::from(self.wins) / Decimal::from(self.closes))
+    }
+
+    fn add(&mut self, fill: &ReportFill, realized: Option<Decimal>) {
+        self.fills += 1;
+        self.shares += fill.size;
+        self.volume += fill.notional();
+        self.fees += fill.fee;
+        if let Some(realized) = realized {
+            self.realized_pnl += realized;
+            self.closes += 1;
+            if realized > Decimal::ZERO {
+                self.wins += 1;
+            }
+        }
+    }
+}
+
+/// Stats of one daily or weekly bucket
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeriodStats {
+    pub period_start: NaiveDate,
+    #[serde(flatten)]
+    pub stats: TradeStats,
+}
+
+/// Stats of one market
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketStats {
+    pub market: String,
+    #[serde(flatten)]
+    pub stats: TradeStats,
+}
+
+/// Volume, fees and PnL aggregated from trade history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeReport {
+    pub period: ReportPeriod,
+    pub totals: TradeStats,
+    /// Oldest first; periods without fills are left out
+    pub periods: Vec<PeriodStats>,
+    /// Highest volume first
+    pub markets: Vec<MarketStats>,
+    /// Trades that could not be read, with the reason
+    pub skipped: Vec<String>,
+}
+
+impl TradeReport {
+    /// Fetch every trade matching `params` and report on them
+    pub async fn fetch<A: ClobApi>(
+        api: &A,
+        params: Option<&TradeParams>,
+        period: ReportPeriod,
+    ) -> Result<Self> {
+        let trades = api.get_trades(params, None).await?;
+        Ok(Self::from_trades(&trades, period))
+    }
+
+    /// Report on trades as returned by `get_trades`
+    pub fn from_trades(trades: &[Value], period: ReportPeriod) -> Self {
+        let mut fills = Vec::with_capacity(trades.len());
+        let mut skipped = Vec::new();
+        for trade in trades {
+            match ReportFill::from_trade(trade) {
+                Ok(trade_fills) => fills.extend(trade_fills),
+                Err(e) => skipped.push(e.to_string()),
+            }
+        }
+        let mut report = Self::from_fills(fills, period);
+        report.skipped = skipped;
+        report
+    }
+
+    /// Report on fills in any order
+    pub fn from_fills(fills: impl IntoIterator<Item = ReportFill>, period: ReportPeriod) -> Self {
+        let mut fills: Vec<ReportFill> = fills.into_iter().collect();
+        fills.sort_by(|a, b| {
+            a.matched_at
+                .cmp(&b.matched_at)
+                .then_with(|| a.trade_id.cmp(&b.trade_id))
+        });
+
+        let mut positions: HashMap<String, ShadowPosition> = HashMap::new();
+        let mut totals = TradeStats::default();
+        let mut periods: BTreeMap<NaiveDate, TradeStats> = BTreeMap::new();
+        let mut markets: HashMap<String, TradeStats> = HashMap::new();
+        for fill in &fills {
+            let position = positions.entry(fill.asset_id.clone()).or_default();
+            let before = position.realized_pnl;
+            let reduces = !position.size.is_zero()
+                && position.size.is_sign_positive() != (fill.side == Side::BUY);
+            position.apply_fill(fill.side, fill.size, fill.price, fill.fee);
+            let realized = reduces.then(|| position.realized_pnl - before);
+
+            totals.add(fill, realized);
+            periods
+                .entry(period.start(fill.matched_at))
+                .or_default()
+                .add(fill, realized);
+            markets
+                .entry(fill.market.clone())
+                .or_default()
+                .add(fill, realized);
+        }
+
+        let mut markets: Vec<MarketStats> = markets
+            .into_iter()
+            .map(|(market, stats)| MarketStats { market, stats })
+            .collect();
+        markets.sort_by(|a, b| {
+            b.stats
+                .volume
+                .cmp(&a.stats.volume)
+                .then_with(|| a.market.cmp(&b.market))
+        });
+        Self {
+            period,
+            totals,
+            periods: periods
+                .into_iter()
+                .map(|(period_start, stats)| PeriodStats {
+                    period_start,
+                    stats,
+                })
+                .collect(),
+            markets,
+            skipped: Vec::new(),
+        }
+    }
+
+    /// One row per period, oldest first
+    pub fn buckets_csv(&self) -> String {
+        let mut csv = format!("period_start,{STATS_HEADER}\n");
+        for period in &self.periods {
+            let _ = writeln!(csv, "{},{}", period.period_start, stats_row(&period.stats));
+        }
+        csv
+    }
+
+    /// One row per market, highest volume first
+    pub fn markets_csv(&self) -> String {
+        let mut csv = format!("market,{STATS_HEADER}\n");
+        for market in &self.markets {
+            let _ = writeln!(
+                csv,
+                "{},{}",
+                csv_field(&market.market),
+                stats_row(&market.stats)
+            );
+        }
+        csv
+    }
+}
+
+const STATS_HEADER: &str = "fills,shares,volume,fees,realized_pnl,closes,wins,win_rate";
+
+fn stats_row(stats: &TradeStats) -> String {
+    format!(
+        "{},{},{},{},{},{},{},{}",
+        stats.fills,
+        stats.shares.normalize(),
+        stats.volume.normalize(),
+        stats.fees.normalize(),
+        stats.realized_pnl.normalize(),
+        stats.closes,
+        stats.wins,
+        stats
+            .win_rate()
+            .map(|rate| rate.round_dp(4).normalize().to_string())
+            .unwrap_or_default()
+    )
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Result<&'a str> {
+    value.get(key).and_then(Value::as_str).ok_or_else(|| {
+        let id = value.get("id").and_then(Value::as_str).unwrap_or("?");
+        PolyfillError::parse(format!("Trade {id} is missing `{key}`"), None)
+    })
+}
+
+fn parse_field<T: FromStr>(value: &Value, key: &str, raw: &str) -> Result<T> {
+    raw.parse().map_err(|_| {
+        let id = value.get("id").and_then(Value::as_str).unwrap_or("?");
+        PolyfillError::parse(format!("Trade {id} has an invalid `{key}`: {raw}"), None)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use serde_json::json;
+
+    #[test]
+    fn test_report_buckets_fees_pnl_and_maker_legs() {
+        let trades = vec![
+            // Monday: buy 10 at 0.40 as taker, 1% fee
+            json!({
+                "id": "t1", "market": "0xm1", "asset_id": "a1", "side": "BUY",
+                "size": "10", "price": "0.40", "fee_rate_bps": "100",
+                "match_time": "1717372800", "trader_side": "TAKER", "owner": "me"
+            }),
+            // Tuesday: sell 5 at 0.60 as maker; the top-level side is the taker's
+            json!({
+                "id": "t2", "market": "0xm1", "asset_id": "a1", "side": "BUY",
+                "size": "8", "price": "0.60", "fee_rate_bps": "0",
+                "match_time": "1717459200", "trader_side": "MAKER", "owner": "taker",
+                "maker_address": "0xme",
+                "maker_orders": [
+                    {"owner": "other", "maker_address": "0xother", "asset_id": "a1",
+                     "side": "SELL", "matched_amount": "3", "price": "0.60", "fee_rate_bps": "0"},
+                    {"owner": "me", "maker_address": "0xme", "asset_id": "a1",
+                     "side": "SELL", "matched_amount": "5", "price": "0.60", "fee_rate_bps": "0"}
+                ]
+            }),
+            // Next Monday: sell the rest at a loss in another week
+            json!({
+                "id": "t3", "market": "0xm1", "asset_id": "a1", "side": "SELL",
+                "size": "5", "price": "0.30", "match_time": "1717977600",
+                "trader_side": "TAKER"
+            }),
+            json!({
+                "id": "t4", "market": "0xm2", "asset_id": "b1", "side": "BUY",
+                "size": "1", "price": "0.50", "match_time": "1717977600"
+            }),
+            json!({ "id": "broken", "market": "0xm2" }),
+        ];
+
+        let report = TradeReport::from_trades(&trades, ReportPeriod::Weekly);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].contains("broken"));
+        assert_eq!(report.totals.fills, 4);
+        assert_eq!(report.totals.fees, dec!(0.04));
+        assert_eq!(report.totals.realized_pnl, dec!(0.50));
+        assert_eq!(report.totals.win_rate(), Some(dec!(0.5)));
+
+        assert_eq!(report.periods.len(), 2);
+        assert_eq!(
+            report.periods[0].period_start,
+            NaiveDate::from_ymd_opt(2024, 6, 3).unwrap()
+        );
+        assert_eq!(report.periods[0].stats.volume, dec!(7.00));
+        assert_eq!(report.periods[1].stats.realized_pnl, dec!(-0.50));
+
+        assert_eq!(report.markets[0].market, "0xm1");
+        assert_eq!(report.markets[0].stats.shares, dec!(20));
+
+        let daily = TradeReport::from_trades(&trades, ReportPeriod::Daily);
+        assert_eq!(daily.periods.len(), 3);
+
+        let csv = report.buckets_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("period_start,fills,shares,volume,fees,realized_pnl,closes,wins,win_rate")
+        );
+        assert_eq!(lines.next(), Some("2024-06-03,2,15,7,0.04,1,1,1,1"));
+        assert!(report.markets_csv().contains("\n0xm2,1,1,0.5,0,0,0,0,\n"));
+    }
+}
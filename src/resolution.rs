@@ -0,0 +1,291 @@
+//! Resolution-time trading guards
+//!
+//! Books get chaotic as a market approaches resolution: liquidity vanishes,
+//! informed flow arrives and the exchange eventually stops accepting orders.
+//! A [`ResolutionGuard`] tracks each token's end date and trading status and
+//! halts a token once
+//!
+//! - it is within the configured window of its end date,
+//! - `accepting_orders` flips to false, from a market fetch or a
+//!   [`MetadataChange`], or
+//! - a `market_resolved` event arrives for it.
+//!
+//! Installed on a client with [`crate::ClobClient::set_resolution_guard`],
+//! orders for halted tokens are rejected before they are posted. With
+//! [`ResolutionGuard::with_cancel`], [`ResolutionGuard::sweep`] also cancels
+//! resting orders in newly halted tokens.
+
+use crate::api::ClobApi;
+use crate::errors::{OrderErrorKind, PolyfillError, Result};
+use crate::metadata::MetadataChange;
+use crate::sim::SharedClock;
+use crate::types::{Market, StreamMessage, TokenId};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tracing::warn;
+
+/// Why a token is halted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltReason {
+    /// Within the guard window of `end_date`
+    EndingSoon {
+        end_date: DateTime<Utc>,
+    },
+    NotAcceptingOrders,
+    Resolved,
+}
+
+#[derive(Debug, Clone)]
+struct TokenState {
+    end_date: Option<DateTime<Utc>>,
+    accepting_orders: bool,
+    resolved: bool,
+}
+
+/// Halts quoting in markets close to resolution
+#[derive(Debug)]
+pub struct ResolutionGuard {
+    window: Duration,
+    cancel: bool,
+    clock: SharedClock,
+    tokens: RwLock<HashMap<String, TokenState>>,
+    /// Tokens whose orders a sweep already cancelled
+    swept: RwLock<HashSet<String>>,
+}
+
+impl ResolutionGuard {
+    /// Halt tokens within `window` of their end date
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            cancel: false,
+            clock: crate::sim::system_clock(),
+            tokens: RwLock::new(HashMap::new()),
+            swept: RwLock::new(HashSet::new()),
+        }
+    }
+
+    /// Cancel resting orders of halted tokens on [`Self::sweep`]
+    pub fn with_cancel(mut self, cancel: bool) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Track both tokens of `market`
+    pub fn track_market(&self, market: &Market) {
+        let end_date = market
+            .end_date_iso
+            .as_deref()
+            .and_then(crate::tradability::parse_end_date);
+        let accepting_orders = market.active && !market.closed && market.accepting_orders;
+        for token in &market.tokens {
+            self.track(&token.token_id, end_date, accepting_orders);
+        }
+    }
+
+    /// Track `token_id`, replacing what was known about it
+    pub fn track(&self, token_id: &str, end_date: Option<DateTime<Utc>>, accepting_orders: bool) {
+        let mut tokens = self.tokens.write();
+        let resolved = tokens.get(token_id).is_some_and(|state| state.resolved);
+        tokens.insert(
+            token_id.to_string(),
+            TokenState {
+                end_date,
+                accepting_orders,
+                resolved,
+            },
+        );
+    }
+
+    /// Pick up an `accepting_orders` flip from a metadata refresh
+    pub fn apply_change(&self, change: &MetadataChange) {
+        if let Some(state) = self.tokens.write().get_mut(&change.token_id) {
+            state.accepting_orders = change.current.accepting_orders;
+        }
+    }
+
+    /// Halt the tokens of a `market_resolved` event; other messages are
+    /// ignored
+    pub fn apply(&self, message: &StreamMessage) {
+        let StreamMessage::MarketResolved(resolved) = message else {
+            return;
+        };
+        let mut tokens = self.tokens.write();
+        for token_id in &resolved.asset_ids {
+            tokens
+                .entry(token_id.clone())
+                .or_insert(TokenState {
+                    end_date: None,
+                    accepting_orders: false,
+                    resolved: false,
+                })
+                .resolved = true;
+        }
+    }
+
+    /// Why `token_id` is halted, if it is. Untracked tokens are not halted.
+    pub fn halt_reason(&self, token_id: &str) -> Option<HaltReason> {
+        let tokens = self.tokens.read();
+        Self::reason(tokens.get(token_id)?, self.clock.now(), self.window)
+    }
+
+    fn reason(state: &TokenState, now: DateTime<Utc>, window: Duration) -> Option<HaltReason> {
+        if state.resolved {
+            return Some(HaltReason::Resolved);
+        }
+        if !state.accepting_orders {
+            return Some(HaltReason::NotAcceptingOrders);
+        }
+        let end_date = state.end_date?;
+        let remaining = (end_date - now).to_std().unwrap_or_default();
+        (remaining <= window).then_some(HaltReason::EndingSoon { end_date })
+    }
+
+    /// Every halted token
+    pub fn halted(&self) -> Vec<(String, HaltReason)> {
+        let now = self.clock.now();
+        self.tokens
+            .read()
+            .iter()
+            .filter_map(|(token_id, state)| {
+                Some((token_id.clone(), Self::reason(state, now, self.window)?))
+            })
+            .collect()
+    }
+
+    /// Reject orders for halted tokens
+    pub fn check(&self, token_id: &str) -> Result<()> {
+        match self.halt_reason(token_id) {
+            Some(reason) => Err(PolyfillError::order(
+                format!("Trading in {token_id} is halted: {reason:?}"),
+                OrderErrorKind::MarketClosed,
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Cancel resting orders of tokens halted since the last sweep, when
+    /// cancelling is enabled. Returns the tokens whose orders were
+    /// cancelled; a token whose cancel failed is retried next sweep.
+    pub async fn sweep<A: ClobApi>(&self, api: &A) -> Result<Vec<(String, HaltReason)>> {
+        if !self.cancel {
+            return Ok(Vec::new());
+        }
+        let pending: Vec<_> = {
+            let swept = self.swept.read();
+            self.halted()
+                .into_iter()
+                .filter(|(token_id, _)| !swept.contains(token_id))
+                .collect()
+        };
+        let mut cancelled = Vec::new();
+        for (token_id, reason) in pending {
+            let asset = match TokenId::new(&token_id) {
+                Ok(asset) => asset,
+                Err(e) => {
+                    warn!("Cannot cancel orders in halted {}: {}", token_id, e);
+                    continue;
+                },
+            };
+            match api.cancel_asset_orders(&asset).await {
+                Ok(response) => {
+                    warn!(
+                        "Cancelled {} orders in {} ({:?})",
+                        response.canceled.len(),
+                        token_id,
+                        reason
+                    );
+                    self.swept.write().insert(token_id.clone());
+                    cancelled.push((token_id, reason));
+                },
+                Err(e) => warn!("Failed to cancel orders in halted {}: {}", token_id, e),
+            }
+        }
+        Ok(cancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{FakeClob, FakeToken};
+    use crate::sim::{Clock, VirtualClock};
+    use crate::types::{OrderArgs, Side};
+    use rust_decimal_macros::dec;
+
+    #[tokio::test]
+    async fn test_guard_halts_and_cancels_near_resolution() {
+        let clock = VirtualClock::new(1_700_000_000_000);
+        let guard = ResolutionGuard::new(Duration::from_secs(3600))
+            .with_cancel(true)
+            .with_clock(clock.shared());
+        let end_date = clock.now() + chrono::Duration::hours(2);
+        guard.track("1", Some(end_date), true);
+        guard.track("2", None, true);
+
+        let fake = FakeClob::new();
+        for token in ["1", "2"] {
+            fake.set_token(token, FakeToken::new(format!("0x{token}")));
+            let args = OrderArgs::new(token.parse().unwrap(), dec!(0.4), dec!(10), Side::BUY);
+            fake.create_and_post_order(&args, None, None).await.unwrap();
+        }
+        assert!(guard.check("1").is_ok());
+        assert!(guard.sweep(&fake).await.unwrap().is_empty());
+
+        clock.advance(Duration::from_secs(3601));
+        assert_eq!(
+            guard.halt_reason("1"),
+            Some(HaltReason::EndingSoon { end_date })
+        );
+        assert!(matches!(
+            guard.check("1"),
+            Err(PolyfillError::Order {
+                kind: OrderErrorKind::MarketClosed,
+                ..
+            })
+        ));
+        let cancelled = guard.sweep(&fake).await.unwrap();
+        assert_eq!(
+            cancelled,
+            [("1".to_string(), HaltReason::EndingSoon { end_date })]
+        );
+        assert_eq!(fake.open_orders().len(), 1);
+        // Already swept
+        assert!(guard.sweep(&fake).await.unwrap().is_empty());
+
+        let mut metadata = crate::metadata::MarketMetadata {
+            tick_size: dec!(0.01),
+            min_order_size: dec!(5),
+            neg_risk: false,
+            accepting_orders: true,
+            fee_rate_bps: 0,
+        };
+        let previous = metadata;
+        metadata.accepting_orders = false;
+        guard.apply_change(&MetadataChange {
+            token_id: "2".to_string(),
+            previous,
+            current: metadata,
+        });
+        assert_eq!(guard.halt_reason("2"), Some(HaltReason::NotAcceptingOrders));
+        assert_eq!(guard.sweep(&fake).await.unwrap().len(), 1);
+        assert!(fake.open_orders().is_empty());
+
+        assert_eq!(guard.halt_reason("3"), None);
+        let resolved: StreamMessage = serde_json::from_str(
+            r#"{"event_type":"market_resolved","id":"1","market":"0x3",
+                "assets_ids":["3","4"],"outcomes":["Yes","No"],
+                "winning_asset_id":"3","winning_outcome":"Yes","timestamp":"1"}"#,
+        )
+        .unwrap();
+        guard.apply(&resolved);
+        assert_eq!(guard.halt_reason("3"), Some(HaltReason::Resolved));
+    }
+}
@@ -0,0 +1,256 @@
+//! Read-only and trading client roles
+//!
+//! A [`ClobClient`] exposes every endpoint, so a service that only reads
+//! books can still place or cancel orders if it is handed credentials by
+//! mistake. The two roles here narrow that down:
+//!
+//! - [`MarketDataClient`] carries no signer or API credentials and only
+//!   exposes public market-data endpoints
+//! - [`TradingClient`] refuses to build without both, and hands out a
+//!   credential-free [`MarketDataClient`] over the same connection pool
+//!
+//! Larger deployments can give each role its own [`ClientConfig`], so a
+//! trading key never reaches market-data processes.
+
+use crate::client::ClobClient;
+use crate::errors::Result;
+use crate::types::{
+    BookDepth, BookParams, ClientConfig, ClobMarketInfo, Market, MarketsResponse, MidpointResponse,
+    OrderBookSummary, PriceResponse, PricesHistoryInterval, PricesHistoryResponse, Side,
+    SimplifiedMarketsResponse, SpreadResponse, TokenId,
+};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Client limited to public market data
+#[derive(Clone)]
+pub struct MarketDataClient {
+    client: ClobClient,
+}
+
+impl MarketDataClient {
+    pub fn new(host: &str) -> Self {
+        Self {
+            client: ClobClient::new(host),
+        }
+    }
+
+    /// Build from `config`, ignoring any private key or API credentials in it
+    pub fn from_config(config: ClientConfig) -> Result<Self> {
+        let config = ClientConfig {
+            private_key: None,
+            api_credentials: None,
+            ..config
+        };
+        Ok(Self {
+            client: ClobClient::from_config(config)?,
+        })
+    }
+
+    pub async fn get_ok(&self) -> bool {
+        self.client.get_ok().await
+    }
+
+    pub async fn get_server_time(&self) -> Result<u64> {
+        self.client.get_server_time().await
+    }
+
+    pub async fn get_order_book(&self, token_id: &str) -> Result<OrderBookSummary> {
+        self.client.get_order_book(token_id).await
+    }
+
+    pub async fn get_order_book_with_depth(
+        &self,
+        token_id: &TokenId,
+        depth: BookDepth,
+    ) -> Result<OrderBookSummary> {
+        self.client.get_order_book_with_depth(token_id, depth).await
+    }
+
+    pub async fn get_order_books(&self, token_ids: &[String]) -> Result<Vec<OrderBookSummary>> {
+        self.client.get_order_books(token_ids).await
+    }
+
+    pub async fn get_midpoint(&self, token_id: &str) -> Result<MidpointResponse> {
+        self.client.get_midpoint(token_id).await
+    }
+
+    pub async fn get_midpoints(&self, token_ids: &[String]) -> Result<HashMap<String, Decimal>> {
+        self.client.get_midpoints(token_ids).await
+    }
+
+    pub async fn get_spread(&self, token_id: &str) -> Result<SpreadResponse> {
+        self.client.get_spread(token_id).await
+    }
+
+    pub async fn get_spreads(&self, token_ids: &[String]) -> Result<HashMap<String, Decimal>> {
+        self.client.get_spreads(token_ids).await
+    }
+
+    pub async fn get_price(&self, token_id: &str, side: Side) -> Result<PriceResponse> {
+        self.client.get_price(token_id, side).await
+    }
+
+    pub async fn get_prices(
+        &self,
+        book_params: &[BookParams],
+    ) -> Result<HashMap<String, HashMap<Side, Decimal>>> {
+        self.client.get_prices(book_params).await
+    }
+
+    pub async fn get_last_trade_price(&self, token_id: &str) -> Result<Value> {
+        self.client.get_last_trade_price(token_id).await
+    }
+
+    pub async fn get_last_trade_prices(&self, token_ids: &[String]) -> Result<Value> {
+        self.client.get_last_trade_prices(token_ids).await
+    }
+
+    pub async fn get_prices_history_interval(
+        &self,
+        asset_id: &str,
+        interval: PricesHistoryInterval,
+        fidelity: Option<u32>,
+    ) -> Result<PricesHistoryResponse> {
+        self.client
+            .get_prices_history_interval(asset_id, interval, fidelity)
+            .await
+    }
+
+    pub async fn get_prices_history_range(
+        &self,
+        asset_id: &str,
+        start_ts: u64,
+        end_ts: u64,
+        fidelity: Option<u32>,
+    ) -> Result<PricesHistoryResponse> {
+        self.client
+            .get_prices_history_range(asset_id, start_ts, end_ts, fidelity)
+            .await
+    }
+
+    pub async fn get_tick_size(&self, token_id: &str) -> Result<Decimal> {
+        self.client.get_tick_size(token_id).await
+    }
+
+    pub async fn get_neg_risk(&self, token_id: &str) -> Result<bool> {
+        self.client.get_neg_risk(token_id).await
+    }
+
+    pub async fn get_market_metadata(
+        &self,
+        token_id: &TokenId,
+    ) -> Result<crate::metadata::MarketMetadata> {
+        self.client.get_market_metadata(token_id).await
+    }
+
+    pub async fn get_clob_market_info(&self, condition_id: &str) -> Result<ClobMarketInfo> {
+        self.client.get_clob_market_info(condition_id).await
+    }
+
+    pub async fn get_market(&self, condition_id: &str) -> Result<Market> {
+        self.client.get_market(condition_id).await
+    }
+
+    pub async fn get_markets(&self, next_cursor: Option<&str>) -> Result<MarketsResponse> {
+        self.client.get_markets(next_cursor).await
+    }
+
+    pub async fn get_simplified_markets(
+        &self,
+        next_cursor: Option<&str>,
+    ) -> Result<SimplifiedMarketsResponse> {
+        self.client.get_simplified_markets(next_cursor).await
+    }
+
+    pub async fn get_sampling_markets(&self, next_cursor: Option<&str>) -> Result<MarketsResponse> {
+        self.client.get_sampling_markets(next_cursor).await
+    }
+
+    pub async fn get_sampling_simplified_markets(
+        &self,
+        next_cursor: Option<&str>,
+    ) -> Result<SimplifiedMarketsResponse> {
+        self.client
+            .get_sampling_simplified_markets(next_cursor)
+            .await
+    }
+
+    pub async fn get_market_trades_events(&self, condition_id: &str) -> Result<Value> {
+        self.client.get_market_trades_events(condition_id).await
+    }
+}
+
+/// Client holding a signer and API credentials
+///
+/// Dereferences to the underlying [`ClobClient`] for every endpoint.
+#[derive(Clone)]
+pub struct TradingClient {
+    client: ClobClient,
+}
+
+impl TradingClient {
+    /// Wrap `client`; fails unless it has a signer and API credentials
+    pub fn new(client: ClobClient) -> Result<Self> {
+        client.assert_level_2_auth()?;
+        Ok(Self { client })
+    }
+
+    pub fn from_config(config: ClientConfig) -> Result<Self> {
+        Self::new(ClobClient::from_config(config)?)
+    }
+
+    /// A [`MarketDataClient`] sharing this client's connection pool but none
+    /// of its credentials
+    pub fn market_data(&self) -> MarketDataClient {
+        MarketDataClient {
+            client: self.client.without_credentials(),
+        }
+    }
+
+    pub fn into_inner(self) -> ClobClient {
+        self.client
+    }
+}
+
+impl std::ops::Deref for TradingClient {
+    type Target = ClobClient;
+
+    fn deref(&self) -> &ClobClient {
+        &self.client
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ApiCredentials;
+
+    const PRIVATE_KEY: &str = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+    #[test]
+    fn test_roles_scope_credentials() {
+        let config = ClientConfig {
+            base_url: "https://clob.example.com".to_string(),
+            private_key: Some(PRIVATE_KEY.to_string()),
+            api_credentials: Some(ApiCredentials {
+                api_key: "key".to_string(),
+                secret: "c2VjcmV0".to_string(),
+                passphrase: "pass".to_string(),
+            }),
+            ..ClientConfig::default()
+        };
+
+        let trading = TradingClient::from_config(config.clone()).unwrap();
+        assert!(trading.get_address().is_some());
+        let market_data = trading.market_data();
+        assert!(market_data.client.assert_level_1_auth().is_err());
+        // The trading client keeps its credentials
+        assert!(trading.assert_level_2_auth().is_ok());
+
+        let market_data = MarketDataClient::from_config(config).unwrap();
+        assert!(market_data.client.get_address().is_none());
+        assert!(TradingClient::new(ClobClient::new("https://clob.example.com")).is_err());
+    }
+}
@@ -0,0 +1,320 @@
+//! Run reports for ops tooling
+//!
+//! A [`RunRecorder`] accumulates what a bot did over a run: orders placed,
+//! filled and cancelled, traded volume, realized PnL, errors by category,
+//! request latencies and stream reconnects. [`RunRecorder::report`] turns
+//! that into a [`RunReport`], which serializes to JSON and can be written to
+//! disk at shutdown with [`RunReport::write_json`] or periodically with
+//! [`RunRecorder::spawn_snapshots`].
+//!
+//! Installed with [`crate::ClobClient::set_run_recorder`], a client records
+//! its own order posts, cancels, post latencies and failures, and
+//! user-channel reconnects. Fills and PnL come from the strategy through
+//! [`RunRecorder::record_fill`] and [`RunRecorder::record_pnl`].
+
+use crate::errors::{PolyfillError, Result};
+use crate::sim::SharedClock;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::warn;
+
+/// Latency percentiles of one request kind, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    /// Requests measured over the run; percentiles cover the most recent
+    /// samples only
+    pub count: u64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencySummary {
+    fn from_samples(count: u64, samples: &VecDeque<Duration>) -> Self {
+        let mut sorted: Vec<f64> = samples
+            .iter()
+            .map(|sample| sample.as_secs_f64() * 1000.0)
+            .collect();
+        sorted.sort_by(f64::total_cmp);
+        // Nearest rank
+        let percentile = |p: f64| {
+            let rank = (p * sorted.len() as f64).ceil() as usize;
+            sorted
+                .get(rank.saturating_sub(1))
+                .copied()
+                .unwrap_or_default()
+        };
+        Self {
+            count,
+            p50_ms: percentile(0.50),
+            p90_ms: percentile(0.90),
+            p99_ms: percentile(0.99),
+            max_ms: sorted.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Summary of a run, as written for ops tooling
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub started_at: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub uptime_secs: f64,
+    pub orders_placed: u64,
+    pub orders_filled: u64,
+    pub orders_cancelled: u64,
+    /// Shares traded
+    pub volume: Decimal,
+    /// Notional traded, price × size
+    pub notional: Decimal,
+    pub realized_pnl: Decimal,
+    /// Errors by [`PolyfillError::category`]
+    pub errors: BTreeMap<String, u64>,
+    /// Latencies by request kind, e.g. `post_order`
+    pub latency: BTreeMap<String, LatencySummary>,
+    pub reconnects: u64,
+}
+
+impl RunReport {
+    pub fn error_count(&self) -> u64 {
+        self.errors.values().sum()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Atomically write the report to `path` as JSON
+    pub fn write_json(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = PathBuf::from(tmp);
+
+        std::fs::write(&tmp, self.to_json()?).map_err(|e| {
+            PolyfillError::internal(format!("Failed to write {}", tmp.display()), e)
+        })?;
+        std::fs::rename(&tmp, path).map_err(|e| {
+            PolyfillError::internal(format!("Failed to replace {}", path.display()), e)
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Latencies {
+    count: u64,
+    samples: VecDeque<Duration>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    orders_placed: u64,
+    orders_filled: u64,
+    orders_cancelled: u64,
+    volume: Decimal,
+    notional: Decimal,
+    realized_pnl: Decimal,
+    errors: BTreeMap<String, u64>,
+    latency: HashMap<String, Latencies>,
+    reconnects: u64,
+}
+
+/// Collects the figures of a [`RunReport`]
+#[derive(Debug)]
+pub struct RunRecorder {
+    started_at: DateTime<Utc>,
+    clock: SharedClock,
+    max_samples: usize,
+    counters: Mutex<Counters>,
+}
+
+impl Default for RunRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunRecorder {
+    /// Start a run now
+    pub fn new() -> Self {
+        Self::with_clock(crate::sim::system_clock())
+    }
+
+    /// Start a run at the current time of `clock`
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            started_at: clock.now(),
+            clock,
+            max_samples: 10_000,
+            counters: Mutex::new(Counters::default()),
+        }
+    }
+
+    /// Latency samples kept per request kind for percentiles (default
+    /// 10,000)
+    pub fn with_max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples.max(1);
+        self
+    }
+
+    pub fn record_orders(&self, count: usize) {
+        self.counters.lock().orders_placed += count as u64;
+    }
+
+    pub fn record_cancels(&self, count: usize) {
+        self.counters.lock().orders_cancelled += count as u64;
+    }
+
+    /// Count a fill of `size` shares at `price`
+    pub fn record_fill(&self, size: Decimal, price: Decimal) {
+        let mut counters = self.counters.lock();
+        counters.orders_filled += 1;
+        counters.volume += size;
+        counters.notional += size * price;
+    }
+
+    /// Add realized PnL
+    pub fn record_pnl(&self, pnl: Decimal) {
+        self.counters.lock().realized_pnl += pnl;
+    }
+
+    pub fn record_error(&self, error: &PolyfillError) {
+        *self
+            .counters
+            .lock()
+            .errors
+            .entry(error.category().to_string())
+            .or_default() += 1;
+    }
+
+    pub fn record_latency(&self, kind: &str, latency: Duration) {
+        let mut counters = self.counters.lock();
+        let latencies = counters.latency.entry(kind.to_string()).or_default();
+        latencies.count += 1;
+        if latencies.samples.len() == self.max_samples {
+            latencies.samples.pop_front();
+        }
+        latencies.samples.push_back(latency);
+    }
+
+    pub fn record_reconnect(&self) {
+        self.counters.lock().reconnects += 1;
+    }
+
+    /// Report of the run so far
+    pub fn report(&self) -> RunReport {
+        let generated_at = self.clock.now();
+        let counters = self.counters.lock();
+        RunReport {
+            started_at: self.started_at,
+            generated_at,
+            uptime_secs: (generated_at - self.started_at)
+                .to_std()
+                .unwrap_or_default()
+                .as_secs_f64(),
+            orders_placed: counters.orders_placed,
+            orders_filled: counters.orders_filled,
+            orders_cancelled: counters.orders_cancelled,
+            volume: counters.volume,
+            notional: counters.notional,
+            realized_pnl: counters.realized_pnl,
+            errors: counters.errors.clone(),
+            latency: counters
+                .latency
+                .iter()
+                .map(|(kind, latencies)| {
+                    (
+                        kind.clone(),
+                        LatencySummary::from_samples(latencies.count, &latencies.samples),
+                    )
+                })
+                .collect(),
+            reconnects: counters.reconnects,
+        }
+    }
+
+    /// Write a report to `path` every `every` until the task is aborted.
+    /// Failed writes are logged and retried on the next tick.
+    pub fn spawn_snapshots(
+        self: &Arc<Self>,
+        path: impl Into<PathBuf>,
+        every: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let recorder = self.clone();
+        let path = path.into();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if let Err(e) = recorder.report().write_json(&path) {
+                    warn!("Failed to write run report: {}", e);
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_run_report_aggregates_and_writes_json() {
+        let clock = VirtualClock::new(1_700_000_000_000);
+        let recorder = RunRecorder::with_clock(clock.shared()).with_max_samples(100);
+        recorder.record_orders(3);
+        recorder.record_cancels(1);
+        recorder.record_fill(dec!(10), dec!(0.40));
+        recorder.record_fill(dec!(5), dec!(0.60));
+        recorder.record_pnl(dec!(1.5));
+        recorder.record_pnl(dec!(-0.5));
+        recorder.record_error(&PolyfillError::rate_limit("slow down"));
+        recorder.record_error(&PolyfillError::rate_limit("slow down"));
+        recorder.record_error(&PolyfillError::api(503, "unavailable"));
+        recorder.record_reconnect();
+        for ms in 1..=200 {
+            recorder.record_latency("post_order", Duration::from_millis(ms));
+        }
+        clock.advance(Duration::from_secs(90));
+
+        let report = recorder.report();
+        assert_eq!(report.uptime_secs, 90.0);
+        assert_eq!(
+            (
+                report.orders_placed,
+                report.orders_filled,
+                report.orders_cancelled
+            ),
+            (3, 2, 1)
+        );
+        assert_eq!(report.volume, dec!(15));
+        assert_eq!(report.notional, dec!(7));
+        assert_eq!(report.realized_pnl, dec!(1));
+        assert_eq!(report.errors["rate_limit"], 2);
+        assert_eq!(report.error_count(), 3);
+        assert_eq!(report.reconnects, 1);
+        // Only the last 100 samples (101..=200 ms) feed the percentiles
+        let latency = report.latency["post_order"];
+        assert_eq!(latency.count, 200);
+        assert_eq!(
+            (latency.p50_ms, latency.p99_ms, latency.max_ms),
+            (150.0, 199.0, 200.0)
+        );
+
+        let path = std::env::temp_dir().join(format!("polyfill-run-{}.json", uuid::Uuid::new_v4()));
+        report.write_json(&path).unwrap();
+        let written: RunReport = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(written, report);
+    }
+}
@@ -0,0 +1,161 @@
+//! Low-latency runtime profile
+//!
+//! The default multi-threaded Tokio runtime moves tasks between worker
+//! threads and parks idle workers, both of which show up as tail latency on
+//! the stream-to-book path. [`LowLatencyConfig`] instead runs the pipeline on
+//! a dedicated current-thread runtime: the socket, decoding and book updates
+//! all stay on one thread and one cache.
+//!
+//! With busy polling enabled the thread never sleeps. It checks the socket
+//! again as soon as it runs out of work, trading a fully used core for not
+//! paying the wake-up cost on every message. This only pays off when the
+//! thread has a core to itself, so pair it with OS-level pinning:
+//!
+//! - isolate a core from the scheduler (`isolcpus=3 nohz_full=3`)
+//! - pin the pipeline thread to it, e.g. `taskset -c 3` for the process or
+//!   the `core_affinity` crate from inside [`LowLatencyConfig::spawn`]
+//! - keep other runtimes and threads off that core
+//!
+//! ```no_run
+//! # use polyfill_rs::LowLatencyConfig;
+//! let pipeline = LowLatencyConfig::new()
+//!     .with_busy_poll(true)
+//!     .spawn(|| async {
+//!         // connect the stream and apply books here
+//!     })
+//!     .unwrap();
+//! pipeline.join().unwrap();
+//! ```
+
+use crate::errors::{PolyfillError, Result};
+use std::future::Future;
+
+/// Settings for a dedicated current-thread runtime
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LowLatencyConfig {
+    /// Spin instead of parking when there is no work
+    pub busy_poll: bool,
+    /// Tasks run between checks for socket and timer events
+    pub event_interval: u32,
+    /// Name of the thread started by [`LowLatencyConfig::spawn`]
+    pub thread_name: String,
+}
+
+impl Default for LowLatencyConfig {
+    fn default() -> Self {
+        Self {
+            busy_poll: false,
+            // Tokio's default of 61 lets a burst of tasks delay socket reads
+            event_interval: 1,
+            thread_name: "polyfill-pipeline".to_string(),
+        }
+    }
+}
+
+impl LowLatencyConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_busy_poll(mut self, busy_poll: bool) -> Self {
+        self.busy_poll = busy_poll;
+        self
+    }
+
+    pub fn with_event_interval(mut self, event_interval: u32) -> Self {
+        self.event_interval = event_interval.max(1);
+        self
+    }
+
+    pub fn with_thread_name(mut self, name: impl Into<String>) -> Self {
+        self.thread_name = name.into();
+        self
+    }
+
+    /// Build the current-thread runtime
+    pub fn build(&self) -> Result<tokio::runtime::Runtime> {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .event_interval(self.event_interval)
+            .build()
+            .map_err(|e| PolyfillError::internal("Failed to build low-latency runtime", e))
+    }
+
+    /// Run `future` to completion on the calling thread.
+    ///
+    /// The future does not need to be `Send`, so it may borrow books or hold
+    /// a [`crate::WebSocketBookApplier`]. Must not be called from within
+    /// another Tokio runtime.
+    pub fn block_on<F: Future>(&self, future: F) -> Result<F::Output> {
+        Ok(run_local(&self.build()?, self.busy_poll, future))
+    }
+
+    /// Run the future made by `make` on a new dedicated thread
+    pub fn spawn<M, F>(&self, make: M) -> Result<std::thread::JoinHandle<F::Output>>
+    where
+        M: FnOnce() -> F + Send + 'static,
+        F: Future,
+        F::Output: Send + 'static,
+    {
+        let busy_poll = self.busy_poll;
+        let runtime = self.build()?;
+        std::thread::Builder::new()
+            .name(self.thread_name.clone())
+            .spawn(move || run_local(&runtime, busy_poll, make()))
+            .map_err(|e| PolyfillError::internal("Failed to spawn pipeline thread", e))
+    }
+}
+
+fn run_local<F: Future>(
+    runtime: &tokio::runtime::Runtime,
+    busy_poll: bool,
+    future: F,
+) -> F::Output {
+    let local = tokio::task::LocalSet::new();
+    if !busy_poll {
+        return local.block_on(runtime, future);
+    }
+    local.block_on(runtime, async {
+        tokio::select! {
+            biased;
+            output = future => output,
+            never = spin() => match never {},
+        }
+    })
+}
+
+/// Keep the runtime awake: every yield lets it poll the I/O driver without
+/// blocking, then come straight back
+async fn spin() -> std::convert::Infallible {
+    loop {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_pipeline_runs_on_dedicated_thread() {
+        let config = LowLatencyConfig::new()
+            .with_busy_poll(true)
+            .with_thread_name("pipeline-test");
+        let handle = config
+            .spawn(|| async {
+                let (tx, rx) = tokio::sync::oneshot::channel();
+                tokio::task::spawn_local(async move {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    tx.send(std::thread::current().name().map(str::to_string))
+                });
+                rx.await.unwrap()
+            })
+            .unwrap();
+        assert_eq!(handle.join().unwrap().as_deref(), Some("pipeline-test"));
+
+        let books = crate::book::OrderBookManager::new(10);
+        let borrowed = LowLatencyConfig::new().block_on(async { books.shard_count() });
+        assert!(borrowed.unwrap() > 0);
+    }
+}
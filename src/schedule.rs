@@ -0,0 +1,267 @@
+//! Scheduled trading windows and blackouts
+//!
+//! Bots often should only trade during set hours, or stand aside around a
+//! known event such as a debate or a data release. A [`TradingSchedule`]
+//! lists the weekly windows trading is allowed in (all times UTC; no windows
+//! means always open) and blackout periods that override them. Schedules
+//! deserialize from config, e.g.
+//!
+//! ```json
+//! {"windows": [{"days": ["Mon", "Tue", "Wed", "Thu", "Fri"],
+//!               "start": "13:30:00", "end": "20:00:00"}],
+//!  "blackouts": [{"label": "FOMC", "start": "2026-01-28T18:45:00Z",
+//!                 "end": "2026-01-28T19:30:00Z"}]}
+//! ```
+//!
+//! [`ScheduleGuard`] holds a schedule per strategy and rejects orders
+//! outside it with [`OrderErrorKind::MarketClosed`]. Install it on a client
+//! with [`crate::ClobClient::set_schedule_guard`] to enforce the default
+//! schedule on every order, or call [`ScheduleGuard::check`] from a
+//! strategy.
+
+use crate::errors::{OrderErrorKind, PolyfillError, Result};
+use crate::sim::SharedClock;
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Daily time range on some weekdays, in UTC.
+///
+/// A window whose `end` is before its `start` runs past midnight and
+/// belongs to the day it starts on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradingWindow {
+    /// Days the window opens on; empty means every day
+    #[serde(default)]
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TradingWindow {
+    /// Every day from `start` to `end`
+    pub fn daily(start: NaiveTime, end: NaiveTime) -> Self {
+        Self {
+            days: Vec::new(),
+            start,
+            end,
+        }
+    }
+
+    /// Only open on `days`
+    pub fn with_days(mut self, days: impl IntoIterator<Item = Weekday>) -> Self {
+        self.days = days.into_iter().collect();
+        self
+    }
+
+    fn opens_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        let time = at.time();
+        if self.start <= self.end {
+            return self.opens_on(at.weekday()) && time >= self.start && time < self.end;
+        }
+        (self.opens_on(at.weekday()) && time >= self.start)
+            || (self.opens_on(at.weekday().pred()) && time < self.end)
+    }
+}
+
+/// Period no trading is allowed in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Blackout {
+    pub label: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Blackout {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        at >= self.start && at < self.end
+    }
+}
+
+/// When trading is allowed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradingSchedule {
+    #[serde(default)]
+    pub windows: Vec<TradingWindow>,
+    #[serde(default)]
+    pub blackouts: Vec<Blackout>,
+}
+
+impl TradingSchedule {
+    /// Always open
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_window(mut self, window: TradingWindow) -> Self {
+        self.windows.push(window);
+        self
+    }
+
+    pub fn with_blackout(
+        mut self,
+        label: impl Into<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Self {
+        self.blackouts.push(Blackout {
+            label: label.into(),
+            start,
+            end,
+        });
+        self
+    }
+
+    /// Black out `before` an event at `at` until `after` it
+    pub fn with_event(
+        self,
+        label: impl Into<String>,
+        at: DateTime<Utc>,
+        before: std::time::Duration,
+        after: std::time::Duration,
+    ) -> Self {
+        let before = chrono::Duration::from_std(before).unwrap_or_default();
+        let after = chrono::Duration::from_std(after).unwrap_or_default();
+        self.with_blackout(label, at - before, at + after)
+    }
+
+    /// Drop blackouts that ended before `now`
+    pub fn prune(&mut self, now: DateTime<Utc>) {
+        self.blackouts.retain(|blackout| blackout.end > now);
+    }
+
+    /// The blackout covering `at`, if any
+    pub fn blackout(&self, at: DateTime<Utc>) -> Option<&Blackout> {
+        self.blackouts.iter().find(|blackout| blackout.contains(at))
+    }
+
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        self.check(at).is_ok()
+    }
+
+    /// Reject trading at `at` outside the windows or inside a blackout
+    pub fn check(&self, at: DateTime<Utc>) -> Result<()> {
+        if let Some(blackout) = self.blackout(at) {
+            return Err(PolyfillError::order(
+                format!(
+                    "Trading is blacked out for {} until {}",
+                    blackout.label, blackout.end
+                ),
+                OrderErrorKind::MarketClosed,
+            ));
+        }
+        if !self.windows.is_empty() && !self.windows.iter().any(|window| window.contains(at)) {
+            return Err(PolyfillError::order(
+                format!("{at} is outside the trading windows"),
+                OrderErrorKind::MarketClosed,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Trading schedules per strategy
+#[derive(Debug)]
+pub struct ScheduleGuard {
+    default_schedule: TradingSchedule,
+    strategies: RwLock<HashMap<String, TradingSchedule>>,
+    clock: SharedClock,
+}
+
+impl Default for ScheduleGuard {
+    fn default() -> Self {
+        Self::new(TradingSchedule::default())
+    }
+}
+
+impl ScheduleGuard {
+    /// Guard using `schedule` for strategies without their own
+    pub fn new(schedule: TradingSchedule) -> Self {
+        Self {
+            default_schedule: schedule,
+            strategies: RwLock::new(HashMap::new()),
+            clock: crate::sim::system_clock(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Use `schedule` for orders submitted under `strategy`
+    pub fn set_schedule(&self, strategy: &str, schedule: TradingSchedule) {
+        self.strategies
+            .write()
+            .insert(strategy.to_string(), schedule);
+    }
+
+    /// Reject orders from `strategy` outside its schedule right now
+    pub fn check(&self, strategy: Option<&str>) -> Result<()> {
+        let now = self.clock.now();
+        let strategies = self.strategies.read();
+        strategy
+            .and_then(|strategy| strategies.get(strategy))
+            .unwrap_or(&self.default_schedule)
+            .check(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+
+    #[test]
+    fn test_schedule_windows_and_blackouts() {
+        let schedule: TradingSchedule = serde_json::from_str(
+            r#"{"windows": [{"days": ["Mon", "Tue", "Wed", "Thu", "Fri"],
+                             "start": "13:30:00", "end": "20:00:00"},
+                            {"days": ["Sat"], "start": "22:00:00", "end": "02:00:00"}],
+                "blackouts": [{"label": "FOMC", "start": "2026-01-28T18:45:00Z",
+                               "end": "2026-01-28T19:30:00Z"}]}"#,
+        )
+        .unwrap();
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+
+        // Wednesday
+        assert!(schedule.is_open(at("2026-01-28T14:00:00Z")));
+        assert!(!schedule.is_open(at("2026-01-28T12:00:00Z")));
+        assert!(!schedule.is_open(at("2026-01-28T20:00:00Z")));
+        assert_eq!(
+            schedule.blackout(at("2026-01-28T19:00:00Z")).unwrap().label,
+            "FOMC"
+        );
+        assert!(matches!(
+            schedule.check(at("2026-01-28T19:00:00Z")),
+            Err(PolyfillError::Order {
+                kind: OrderErrorKind::MarketClosed,
+                ..
+            })
+        ));
+        // Saturday night into Sunday
+        assert!(schedule.is_open(at("2026-01-31T23:00:00Z")));
+        assert!(schedule.is_open(at("2026-02-01T01:00:00Z")));
+        assert!(!schedule.is_open(at("2026-02-01T23:00:00Z")));
+
+        let clock = VirtualClock::new(at("2026-01-28T14:00:00Z").timestamp_millis() as u64);
+        let guard = ScheduleGuard::new(schedule).with_clock(clock.shared());
+        guard.set_schedule(
+            "news",
+            TradingSchedule::new().with_event(
+                "CPI",
+                at("2026-01-28T14:30:00Z"),
+                std::time::Duration::from_secs(3600),
+                std::time::Duration::from_secs(600),
+            ),
+        );
+        assert!(guard.check(None).is_ok());
+        assert!(guard.check(Some("other")).is_ok());
+        assert!(guard.check(Some("news")).is_err());
+    }
+}
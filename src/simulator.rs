@@ -0,0 +1,1598 @@
+//! In-process exchange simulator
+//!
+//! [`ExchangeSimulator`] binds a local HTTP server and WebSocket server that
+//! speak enough of the CLOB protocol for the real [`crate::ClobClient`] and
+//! [`crate::WebSocketStream`] to be pointed at them: market data endpoints,
+//! order posting with price-time matching, cancels, order and trade queries,
+//! and `market`/`user` WebSocket channels that publish book snapshots, price
+//! changes, trades and order updates as they happen.
+//!
+//! The simulator does not verify order signatures or L2 request signatures;
+//! the `POLY_API_KEY` header (and the `owner` field on posted orders) is
+//! trusted as the caller's identity. Resting liquidity can be seeded directly
+//! with [`ExchangeSimulator::seed_order`].
+
+use crate::errors::{PolyfillError, Result};
+use crate::sim::{system_clock, SharedClock};
+use crate::types::{
+    BookUpdate, LastTradePrice, OpenOrder, OrderBookSummary, OrderMessage, OrderSummary, OrderType,
+    PriceChange, PriceChangeEntry, Side, SignedOrderRequest, StreamMessage, TradeMessage,
+    WssSubscription,
+};
+use futures::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::debug;
+
+/// Owner recorded on liquidity seeded through [`ExchangeSimulator::seed_order`]
+pub const SIMULATOR_OWNER: &str = "simulator";
+
+/// Final cursor value of a paginated data endpoint
+const END_CURSOR: &str = "LTE=";
+
+/// Static parameters of a simulated market
+#[derive(Debug, Clone)]
+pub struct SimMarket {
+    /// Condition ID reported as `market` on books, orders and trades
+    pub condition_id: String,
+    pub tick_size: Decimal,
+    pub min_order_size: Decimal,
+    pub neg_risk: bool,
+    pub fee_rate_bps: u32,
+}
+
+impl SimMarket {
+    /// Market with Polymarket's common defaults (0.01 tick, 5 share minimum)
+    pub fn new(condition_id: impl Into<String>) -> Self {
+        Self {
+            condition_id: condition_id.into(),
+            tick_size: Decimal::new(1, 2),
+            min_order_size: Decimal::from(5),
+            neg_risk: false,
+            fee_rate_bps: 0,
+        }
+    }
+
+    pub fn with_tick_size(mut self, tick_size: Decimal) -> Self {
+        self.tick_size = tick_size;
+        self
+    }
+
+    pub fn with_min_order_size(mut self, min_order_size: Decimal) -> Self {
+        self.min_order_size = min_order_size;
+        self
+    }
+
+    pub fn with_neg_risk(mut self, neg_risk: bool) -> Self {
+        self.neg_risk = neg_risk;
+        self
+    }
+
+    pub fn with_fee_rate_bps(mut self, fee_rate_bps: u32) -> Self {
+        self.fee_rate_bps = fee_rate_bps;
+        self
+    }
+}
+
+/// Order accepted by the matching engine
+#[derive(Debug, Clone)]
+struct SimOrder {
+    id: String,
+    owner: String,
+    maker_address: String,
+    token_id: String,
+    market: String,
+    side: Side,
+    price: Decimal,
+    original_size: Decimal,
+    size_matched: Decimal,
+    status: &'static str,
+    order_type: OrderType,
+    /// Unix seconds; zero for no expiry
+    expiration: u64,
+    created_at: u64,
+    associate_trades: Vec<String>,
+}
+
+impl SimOrder {
+    fn remaining(&self) -> Decimal {
+        self.original_size - self.size_matched
+    }
+
+    fn to_open_order(&self) -> OpenOrder {
+        OpenOrder {
+            associate_trades: self.associate_trades.clone(),
+            id: self.id.clone(),
+            status: self.status.to_string(),
+            market: self.market.clone(),
+            original_size: self.original_size,
+            outcome: String::new(),
+            maker_address: self.maker_address.clone(),
+            owner: self.owner.clone(),
+            price: self.price,
+            side: self.side,
+            size_matched: self.size_matched,
+            asset_id: self.token_id.clone(),
+            expiration: self.expiration,
+            order_type: self.order_type,
+            created_at: self.created_at,
+        }
+    }
+
+    fn update_message(&self, msg_type: &str, timestamp_ms: u64) -> StreamMessage {
+        StreamMessage::Order(OrderMessage {
+            id: self.id.clone(),
+            market: self.market.clone(),
+            asset_id: self.token_id.clone(),
+            side: self.side,
+            price: self.price,
+            msg_type: Some(msg_type.to_string()),
+            original_size: Some(self.original_size),
+            size_matched: Some(self.size_matched),
+            timestamp: Some(timestamp_ms),
+            associate_trades: Some(self.associate_trades.clone()),
+            status: Some(self.status.to_string()),
+            expiration: Some(self.expiration),
+            extra: Default::default(),
+        })
+    }
+}
+
+/// Order submission after decoding the signed payload
+#[derive(Debug, Clone)]
+struct NewOrder {
+    owner: String,
+    maker_address: String,
+    token_id: String,
+    side: Side,
+    price: Decimal,
+    size: Decimal,
+    order_type: OrderType,
+    post_only: bool,
+    expiration: u64,
+}
+
+/// Result of a successful submission
+#[derive(Debug)]
+struct Submitted {
+    order_id: String,
+    status: &'static str,
+    making_amount: Decimal,
+    taking_amount: Decimal,
+    trade_ids: Vec<String>,
+}
+
+#[derive(Debug)]
+struct SimBook {
+    market: SimMarket,
+    /// Resting order IDs per price level, in time priority
+    bids: BTreeMap<Decimal, VecDeque<String>>,
+    asks: BTreeMap<Decimal, VecDeque<String>>,
+    last_trade_price: Option<Decimal>,
+}
+
+impl SimBook {
+    fn side(&mut self, side: Side) -> &mut BTreeMap<Decimal, VecDeque<String>> {
+        match side {
+            Side::BUY => &mut self.bids,
+            Side::SELL => &mut self.asks,
+        }
+    }
+}
+
+/// Event published to WebSocket connections
+#[derive(Debug, Clone)]
+struct SimEvent {
+    /// Owning API key for user-channel events; `None` for market data
+    owner: Option<String>,
+    message: StreamMessage,
+}
+
+#[derive(Debug, Default)]
+struct SimState {
+    books: HashMap<String, SimBook>,
+    orders: HashMap<String, SimOrder>,
+    /// Trades from each participant's perspective, keyed by owner
+    trades: Vec<(String, TradeMessage)>,
+    next_order_id: u64,
+    next_trade_id: u64,
+}
+
+impl SimState {
+    fn level_summaries(
+        &self,
+        levels: &BTreeMap<Decimal, VecDeque<String>>,
+        side: Side,
+    ) -> Vec<OrderSummary> {
+        let summary = |(&price, queue): (&Decimal, &VecDeque<String>)| OrderSummary {
+            price,
+            size: self.queue_size(queue),
+        };
+        match side {
+            Side::BUY => levels.iter().rev().map(summary).collect(),
+            Side::SELL => levels.iter().map(summary).collect(),
+        }
+    }
+
+    fn queue_size(&self, queue: &VecDeque<String>) -> Decimal {
+        queue
+            .iter()
+            .filter_map(|id| self.orders.get(id))
+            .map(SimOrder::remaining)
+            .sum()
+    }
+
+    fn level_size(&self, token_id: &str, side: Side, price: Decimal) -> Decimal {
+        let Some(book) = self.books.get(token_id) else {
+            return Decimal::ZERO;
+        };
+        let levels = match side {
+            Side::BUY => &book.bids,
+            Side::SELL => &book.asks,
+        };
+        levels
+            .get(&price)
+            .map(|queue| self.queue_size(queue))
+            .unwrap_or_default()
+    }
+
+    fn summary(&self, token_id: &str, timestamp_ms: u64) -> Option<OrderBookSummary> {
+        let book = self.books.get(token_id)?;
+        Some(OrderBookSummary {
+            market: book.market.condition_id.clone(),
+            asset_id: token_id.to_string(),
+            hash: None,
+            timestamp: timestamp_ms,
+            bids: self.level_summaries(&book.bids, Side::BUY),
+            asks: self.level_summaries(&book.asks, Side::SELL),
+            min_order_size: book.market.min_order_size,
+            neg_risk: book.market.neg_risk,
+            tick_size: book.market.tick_size,
+            last_trade_price: book.last_trade_price,
+        })
+    }
+
+    fn snapshot(&self, token_id: &str, timestamp_ms: u64) -> Option<StreamMessage> {
+        let summary = self.summary(token_id, timestamp_ms)?;
+        Some(StreamMessage::Book(BookUpdate {
+            asset_id: summary.asset_id,
+            market: summary.market,
+            timestamp: timestamp_ms,
+            bids: summary.bids,
+            asks: summary.asks,
+            hash: None,
+            extra: Default::default(),
+        }))
+    }
+
+    fn price_change(
+        &self,
+        token_id: &str,
+        side: Side,
+        price: Decimal,
+        timestamp_ms: u64,
+    ) -> SimEvent {
+        let market = self
+            .books
+            .get(token_id)
+            .map(|b| b.market.condition_id.clone())
+            .unwrap_or_default();
+        let best = |side| {
+            self.summary(token_id, timestamp_ms)
+                .and_then(|s| match side {
+                    Side::BUY => s.bids.first().map(|l| l.price),
+                    Side::SELL => s.asks.first().map(|l| l.price),
+                })
+        };
+        SimEvent {
+            owner: None,
+            message: StreamMessage::PriceChange(PriceChange {
+                market,
+                timestamp: timestamp_ms,
+                price_changes: vec![PriceChangeEntry {
+                    asset_id: token_id.to_string(),
+                    price,
+                    size: Some(self.level_size(token_id, side, price)),
+                    side,
+                    hash: None,
+                    best_bid: best(Side::BUY),
+                    best_ask: best(Side::SELL),
+                    extra: Default::default(),
+                }],
+                extra: Default::default(),
+            }),
+        }
+    }
+
+    /// Validate, match and (for resting order types) book an order
+    fn submit(
+        &mut self,
+        order: NewOrder,
+        now_ms: u64,
+        events: &mut Vec<SimEvent>,
+    ) -> std::result::Result<Submitted, String> {
+        let market = match self.books.get(&order.token_id) {
+            Some(book) => book.market.clone(),
+            None => return Err(format!("market not found for token {}", order.token_id)),
+        };
+        let tick = market.tick_size;
+        if order.price < tick
+            || order.price > Decimal::ONE - tick
+            || !(order.price % tick).is_zero()
+        {
+            return Err(format!(
+                "invalid price {} for tick size {}",
+                order.price, tick
+            ));
+        }
+        if order.size < market.min_order_size {
+            return Err(format!(
+                "size {} is below the minimum order size {}",
+                order.size, market.min_order_size
+            ));
+        }
+
+        let crosses = |level: Decimal| match order.side {
+            Side::BUY => level <= order.price,
+            Side::SELL => level >= order.price,
+        };
+        let opposite = order.side.opposite();
+        let available: Decimal = {
+            let book = &self.books[&order.token_id];
+            let levels = match opposite {
+                Side::BUY => &book.bids,
+                Side::SELL => &book.asks,
+            };
+            levels
+                .iter()
+                .filter(|(&price, _)| crosses(price))
+                .map(|(_, queue)| self.queue_size(queue))
+                .sum()
+        };
+        if order.post_only && !available.is_zero() {
+            return Err("post-only order would cross the book".to_string());
+        }
+        if order.order_type == OrderType::FOK && available < order.size {
+            return Err("FOK order could not be fully filled".to_string());
+        }
+        if order.order_type == OrderType::FAK && available.is_zero() {
+            return Err("no orders found to match with FAK order".to_string());
+        }
+
+        self.next_order_id += 1;
+        let order_id = format!("sim-order-{}", self.next_order_id);
+        let now_secs = now_ms / 1000;
+        let mut taker = SimOrder {
+            id: order_id.clone(),
+            owner: order.owner.clone(),
+            maker_address: order.maker_address.clone(),
+            token_id: order.token_id.clone(),
+            market: market.condition_id.clone(),
+            side: order.side,
+            price: order.price,
+            original_size: order.size,
+            size_matched: Decimal::ZERO,
+            status: "LIVE",
+            order_type: order.order_type,
+            expiration: order.expiration,
+            created_at: now_secs,
+            associate_trades: Vec::new(),
+        };
+
+        // Price-time matching against the opposite side
+        let mut fills: Vec<(String, Decimal, Decimal)> = Vec::new();
+        {
+            let book = self
+                .books
+                .get_mut(&order.token_id)
+                .expect("book checked above");
+            let levels = book.side(opposite);
+            while taker.remaining() > Decimal::ZERO {
+                let best = match opposite {
+                    Side::BUY => levels.keys().next_back().copied(),
+                    Side::SELL => levels.keys().next().copied(),
+                };
+                let Some(level_price) = best.filter(|&p| crosses(p)) else {
+                    break;
+                };
+                let queue = levels.get_mut(&level_price).expect("level exists");
+                while let Some(maker_id) = queue.front().cloned() {
+                    let maker = self
+                        .orders
+                        .get_mut(&maker_id)
+                        .expect("resting order exists");
+                    let quantity = taker.remaining().min(maker.remaining());
+                    maker.size_matched += quantity;
+                    taker.size_matched += quantity;
+                    fills.push((maker_id, level_price, quantity));
+                    if maker.remaining().is_zero() {
+                        maker.status = "MATCHED";
+                        queue.pop_front();
+                    }
+                    if taker.remaining().is_zero() {
+                        break;
+                    }
+                }
+                if queue.is_empty() {
+                    levels.remove(&level_price);
+                }
+            }
+        }
+
+        let mut trade_ids = Vec::with_capacity(fills.len());
+        let mut notional = Decimal::ZERO;
+        for (maker_id, price, quantity) in &fills {
+            self.next_trade_id += 1;
+            let trade_id = format!("sim-trade-{}", self.next_trade_id);
+            notional += price * quantity;
+            trade_ids.push(trade_id.clone());
+            taker.associate_trades.push(trade_id.clone());
+
+            let maker = self.orders.get_mut(maker_id).expect("maker exists");
+            maker.associate_trades.push(trade_id.clone());
+            let maker = maker.clone();
+
+            let trade = |side: Side| TradeMessage {
+                id: trade_id.clone(),
+                market: market.condition_id.clone(),
+                asset_id: order.token_id.clone(),
+                side,
+                size: *quantity,
+                price: *price,
+                status: Some("MATCHED".to_string()),
+                msg_type: Some("TRADE".to_string()),
+                last_update: Some(now_secs),
+                matchtime: Some(now_secs),
+                timestamp: Some(now_secs),
+                extra: Default::default(),
+            };
+            for (owner, side) in [(&order.owner, order.side), (&maker.owner, maker.side)] {
+                let message = trade(side);
+                self.trades.push((owner.clone(), message.clone()));
+                events.push(SimEvent {
+                    owner: Some(owner.clone()),
+                    message: StreamMessage::Trade(message),
+                });
+            }
+            events.push(SimEvent {
+                owner: Some(maker.owner.clone()),
+                message: maker.update_message("UPDATE", now_ms),
+            });
+            events.push(SimEvent {
+                owner: None,
+                message: StreamMessage::LastTradePrice(LastTradePrice {
+                    asset_id: order.token_id.clone(),
+                    market: market.condition_id.clone(),
+                    price: *price,
+                    side: Some(order.side),
+                    size: Some(*quantity),
+                    fee_rate_bps: Some(Decimal::from(market.fee_rate_bps)),
+                    timestamp: now_ms,
+                    extra: Default::default(),
+                }),
+            });
+        }
+        if let Some((_, price, _)) = fills.last() {
+            self.books
+                .get_mut(&order.token_id)
+                .expect("book exists")
+                .last_trade_price = Some(*price);
+        }
+        let mut touched: Vec<Decimal> = fills.iter().map(|(_, price, _)| *price).collect();
+        touched.dedup();
+        for price in touched {
+            events.push(self.price_change(&order.token_id, opposite, price, now_ms));
+        }
+
+        let rests = matches!(order.order_type, OrderType::GTC | OrderType::GTD)
+            && taker.remaining() > Decimal::ZERO;
+        taker.status = if rests { "LIVE" } else { "MATCHED" };
+        if rests {
+            self.books
+                .get_mut(&order.token_id)
+                .expect("book exists")
+                .side(order.side)
+                .entry(order.price)
+                .or_default()
+                .push_back(order_id.clone());
+        }
+        events.push(SimEvent {
+            owner: Some(order.owner.clone()),
+            message: taker.update_message("PLACEMENT", now_ms),
+        });
+        let filled = taker.size_matched;
+        self.orders.insert(order_id.clone(), taker);
+        if rests {
+            events.push(self.price_change(&order.token_id, order.side, order.price, now_ms));
+        }
+
+        let (making_amount, taking_amount) = match order.side {
+            Side::BUY => (notional, filled),
+            Side::SELL => (filled, notional),
+        };
+        Ok(Submitted {
+            order_id,
+            status: if rests { "live" } else { "matched" },
+            making_amount,
+            taking_amount,
+            trade_ids,
+        })
+    }
+
+    /// Cancel `ids` on behalf of `owner`, returning `(canceled, not_canceled)`
+    fn cancel(
+        &mut self,
+        owner: &str,
+        ids: impl IntoIterator<Item = String>,
+        now_ms: u64,
+        events: &mut Vec<SimEvent>,
+    ) -> (Vec<String>, HashMap<String, String>) {
+        let mut canceled = Vec::new();
+        let mut not_canceled = HashMap::new();
+        for id in ids {
+            match self.orders.get(&id) {
+                Some(order) if order.owner == owner && order.status == "LIVE" => {
+                    self.close(&id, "CANCELED", "CANCELLATION", now_ms, events);
+                    canceled.push(id);
+                },
+                Some(order) if order.owner == owner => {
+                    not_canceled.insert(id, "order already matched or canceled".to_string());
+                },
+                _ => {
+                    not_canceled.insert(id, "order not found".to_string());
+                },
+            }
+        }
+        (canceled, not_canceled)
+    }
+
+    /// Remove a live order from its book and mark it `status`
+    fn close(
+        &mut self,
+        id: &str,
+        status: &'static str,
+        msg_type: &str,
+        now_ms: u64,
+        events: &mut Vec<SimEvent>,
+    ) {
+        let Some(order) = self.orders.get_mut(id) else {
+            return;
+        };
+        order.status = status;
+        let order = order.clone();
+
+        if let Some(book) = self.books.get_mut(&order.token_id) {
+            let levels = book.side(order.side);
+            if let Some(queue) = levels.get_mut(&order.price) {
+                queue.retain(|resting| resting != id);
+                if queue.is_empty() {
+                    levels.remove(&order.price);
+                }
+            }
+        }
+        events.push(SimEvent {
+            owner: Some(order.owner.clone()),
+            message: order.update_message(msg_type, now_ms),
+        });
+        events.push(self.price_change(&order.token_id, order.side, order.price, now_ms));
+    }
+
+    /// Expire GTD orders whose expiration has passed
+    fn expire(&mut self, now_ms: u64, events: &mut Vec<SimEvent>) {
+        let now_secs = now_ms / 1000;
+        let expired: Vec<String> = self
+            .orders
+            .values()
+            .filter(|o| o.status == "LIVE" && o.expiration > 0 && o.expiration <= now_secs)
+            .map(|o| o.id.clone())
+            .collect();
+        for id in expired {
+            self.close(&id, "CANCELED", "CANCELLATION", now_ms, events);
+        }
+    }
+}
+
+/// State shared between the simulator handle and its server tasks
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<SimState>,
+    events: broadcast::Sender<SimEvent>,
+    clock: SharedClock,
+}
+
+impl Shared {
+    /// Run `f` against the state (after expiring GTD orders) and publish its events
+    fn with_state<T>(&self, f: impl FnOnce(&mut SimState, u64, &mut Vec<SimEvent>) -> T) -> T {
+        let now_ms = self.clock.now_millis();
+        let mut events = Vec::new();
+        let result = {
+            let mut state = self.state.lock();
+            state.expire(now_ms, &mut events);
+            f(&mut state, now_ms, &mut events)
+        };
+        for event in events {
+            // No receivers just means no WebSocket clients are connected
+            let _ = self.events.send(event);
+        }
+        result
+    }
+}
+
+/// A running in-process exchange.
+///
+/// Servers listen on ephemeral localhost ports and shut down when the
+/// simulator is dropped.
+#[derive(Debug)]
+pub struct ExchangeSimulator {
+    shared: Arc<Shared>,
+    http_addr: SocketAddr,
+    ws_addr: SocketAddr,
+    shutdown: watch::Sender<bool>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl ExchangeSimulator {
+    /// Start a simulator using the system clock
+    pub async fn start() -> Result<Self> {
+        Self::start_with_clock(system_clock()).await
+    }
+
+    /// Start a simulator that timestamps events and expires GTD orders using `clock`
+    pub async fn start_with_clock(clock: SharedClock) -> Result<Self> {
+        let http = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| PolyfillError::network("Failed to bind simulator HTTP port", e))?;
+        let ws = TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| PolyfillError::network("Failed to bind simulator WS port", e))?;
+        let http_addr = http
+            .local_addr()
+            .map_err(|e| PolyfillError::network("Failed to read simulator address", e))?;
+        let ws_addr = ws
+            .local_addr()
+            .map_err(|e| PolyfillError::network("Failed to read simulator address", e))?;
+
+        let (events, _) = broadcast::channel(4096);
+        let shared = Arc::new(Shared {
+            state: Mutex::new(SimState::default()),
+            events,
+            clock,
+        });
+        let (shutdown, _) = watch::channel(false);
+
+        let tasks = vec![
+            tokio::spawn(accept_loop(
+                http,
+                shared.clone(),
+                shutdown.subscribe(),
+                serve_http,
+            )),
+            tokio::spawn(accept_loop(
+                ws,
+                shared.clone(),
+                shutdown.subscribe(),
+                serve_ws,
+            )),
+        ];
+
+        Ok(Self {
+            shared,
+            http_addr,
+            ws_addr,
+            shutdown,
+            tasks,
+        })
+    }
+
+    /// Base URL to pass to [`crate::ClobClient`]
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.http_addr)
+    }
+
+    /// URL of the market WebSocket channel
+    pub fn market_ws_url(&self) -> String {
+        format!("ws://{}{}", self.ws_addr, crate::stream::WS_MARKET_PATH)
+    }
+
+    /// URL of the user WebSocket channel
+    pub fn user_ws_url(&self) -> String {
+        format!("ws://{}{}", self.ws_addr, crate::stream::WS_USER_PATH)
+    }
+
+    /// List a token, replacing any existing book for it
+    pub fn add_market(&self, token_id: impl Into<String>, market: SimMarket) {
+        let token_id = token_id.into();
+        self.shared.with_state(|state, now_ms, events| {
+            state.books.insert(
+                token_id.clone(),
+                SimBook {
+                    market,
+                    bids: BTreeMap::new(),
+                    asks: BTreeMap::new(),
+                    last_trade_price: None,
+                },
+            );
+            if let Some(snapshot) = state.snapshot(&token_id, now_ms) {
+                events.push(SimEvent {
+                    owner: None,
+                    message: snapshot,
+                });
+            }
+        });
+    }
+
+    /// Add a GTC order owned by [`SIMULATOR_OWNER`], matching it like any other
+    /// order; returns its order ID
+    pub fn seed_order(
+        &self,
+        token_id: &str,
+        side: Side,
+        price: Decimal,
+        size: Decimal,
+    ) -> Result<String> {
+        let order = NewOrder {
+            owner: SIMULATOR_OWNER.to_string(),
+            maker_address: String::new(),
+            token_id: token_id.to_string(),
+            side,
+            price,
+            size,
+            order_type: OrderType::GTC,
+            post_only: false,
+            expiration: 0,
+        };
+        self.shared
+            .with_state(|state, now_ms, events| state.submit(order, now_ms, events))
+            .map(|submitted| submitted.order_id)
+            .map_err(PolyfillError::validation)
+    }
+
+    /// Current book for a token, best levels first
+    pub fn book(&self, token_id: &str) -> Option<OrderBookSummary> {
+        let now_ms = self.shared.clock.now_millis();
+        self.shared.state.lock().summary(token_id, now_ms)
+    }
+
+    /// Order by ID, in any status
+    pub fn order(&self, order_id: &str) -> Option<OpenOrder> {
+        self.shared
+            .state
+            .lock()
+            .orders
+            .get(order_id)
+            .map(SimOrder::to_open_order)
+    }
+
+    /// Every executed trade, from the taker's perspective
+    pub fn trades(&self) -> Vec<TradeMessage> {
+        let state = self.shared.state.lock();
+        // Each fill is recorded for the taker first, then the maker
+        state
+            .trades
+            .iter()
+            .step_by(2)
+            .map(|(_, trade)| trade.clone())
+            .collect()
+    }
+}
+
+impl Drop for ExchangeSimulator {
+    fn drop(&mut self) {
+        let _ = self.shutdown.send(true);
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn accept_loop<F, Fut>(
+    listener: TcpListener,
+    shared: Arc<Shared>,
+    shutdown: watch::Receiver<bool>,
+    serve: F,
+) where
+    F: Fn(TcpStream, Arc<Shared>, watch::Receiver<bool>) -> Fut,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+    loop {
+        match listener.accept().await {
+            Ok((socket, _)) => {
+                let _ = socket.set_nodelay(true);
+                tokio::spawn(serve(socket, shared.clone(), shutdown.clone()));
+            },
+            Err(e) => debug!("Simulator accept failed: {}", e),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// HTTP
+// ----------------------------------------------------------------------------
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn param(&self, name: &str) -> std::result::Result<&str, Reply> {
+        self.query
+            .get(name)
+            .map(String::as_str)
+            .ok_or_else(|| Reply::error(400, format!("missing query parameter {name}")))
+    }
+
+    fn owner(&self) -> String {
+        self.headers
+            .get("poly_api_key")
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn json<T: for<'de> Deserialize<'de>>(&self) -> std::result::Result<T, Reply> {
+        serde_json::from_slice(&self.body)
+            .map_err(|e| Reply::error(400, format!("invalid request body: {e}")))
+    }
+}
+
+#[derive(Debug)]
+struct Reply {
+    status: u16,
+    body: String,
+}
+
+impl Reply {
+    fn json(value: Value) -> Self {
+        Self {
+            status: 200,
+            body: value.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }).to_string(),
+        }
+    }
+}
+
+/// Read one HTTP/1.1 request; `None` when the peer closed the connection
+async fn read_request(reader: &mut BufReader<TcpStream>) -> std::io::Result<Option<Request>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.to_string();
+    let query = url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let length = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+
+    Ok(Some(Request {
+        method,
+        path,
+        query,
+        headers,
+        body,
+    }))
+}
+
+async fn serve_http(socket: TcpStream, shared: Arc<Shared>, mut shutdown: watch::Receiver<bool>) {
+    let mut reader = BufReader::new(socket);
+    loop {
+        let request = tokio::select! {
+            request = read_request(&mut reader) => request,
+            _ = shutdown.changed() => return,
+        };
+        let request = match request {
+            Ok(Some(request)) => request,
+            Ok(None) => return,
+            Err(e) => {
+                debug!("Simulator HTTP read failed: {}", e);
+                return;
+            },
+        };
+
+        let reply = route(&shared, &request).unwrap_or_else(|reply| reply);
+        let response = format!(
+            "HTTP/1.1 {} {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+            reply.status,
+            reason(reply.status),
+            reply.body.len(),
+            reply.body
+        );
+        if reader
+            .get_mut()
+            .write_all(response.as_bytes())
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        _ => "Error",
+    }
+}
+
+fn route(shared: &Shared, request: &Request) -> std::result::Result<Reply, Reply> {
+    let now_ms = shared.clock.now_millis();
+    let book = |token_id: &str| {
+        let now_ms = shared.clock.now_millis();
+        shared
+            .state
+            .lock()
+            .summary(token_id, now_ms)
+            .ok_or_else(|| Reply::error(404, format!("No orderbook exists for token {token_id}")))
+    };
+    let market = |token_id: &str| {
+        shared
+            .state
+            .lock()
+            .books
+            .get(token_id)
+            .map(|b| b.market.clone())
+            .ok_or_else(|| Reply::error(404, format!("market not found for token {token_id}")))
+    };
+
+    let reply = match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/ok") => Reply::json(json!("OK")),
+        ("GET", "/time") => Reply::json(json!(now_ms / 1000)),
+        ("GET", "/book") => Reply::json(book_json(&book(request.param("token_id")?)?)),
+        ("POST", "/books") => {
+            #[derive(Deserialize)]
+            struct TokenRequest {
+                token_id: String,
+            }
+            let tokens: Vec<TokenRequest> = request.json()?;
+            let books = tokens
+                .iter()
+                .map(|t| book(&t.token_id).map(|b| book_json(&b)))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Reply::json(Value::Array(books))
+        },
+        ("GET", "/midpoint") => {
+            let (bid, ask) = top_of_book(&book(request.param("token_id")?)?)?;
+            Reply::json(json!({ "mid": ((bid + ask) / Decimal::TWO).to_string() }))
+        },
+        ("GET", "/spread") => {
+            let (bid, ask) = top_of_book(&book(request.param("token_id")?)?)?;
+            Reply::json(json!({ "spread": (ask - bid).to_string() }))
+        },
+        ("GET", "/price") => {
+            let summary = book(request.param("token_id")?)?;
+            let levels = match request.param("side")? {
+                side if side.eq_ignore_ascii_case("BUY") => &summary.bids,
+                _ => &summary.asks,
+            };
+            let level = levels
+                .first()
+                .ok_or_else(|| Reply::error(404, "No orders on requested side"))?;
+            Reply::json(json!({ "price": level.price.to_string() }))
+        },
+        ("GET", "/tick-size") => {
+            let market = market(request.param("token_id")?)?;
+            Reply::json(json!({ "minimum_tick_size": market.tick_size.to_string() }))
+        },
+        ("GET", "/neg-risk") => {
+            let market = market(request.param("token_id")?)?;
+            Reply::json(json!({ "neg_risk": market.neg_risk }))
+        },
+        ("GET", "/fee-rate") => {
+            let market = market(request.param("token_id")?)?;
+            Reply::json(json!({ "base_fee": market.fee_rate_bps }))
+        },
+        ("POST", "/order") => post_order(shared, request)?,
+        ("DELETE", "/order") => {
+            #[derive(Deserialize)]
+            struct CancelRequest {
+                #[serde(rename = "orderID")]
+                order_id: String,
+            }
+            let body: CancelRequest = request.json()?;
+            cancel_reply(shared, &request.owner(), vec![body.order_id])
+        },
+        ("DELETE", "/orders") => {
+            let ids: Vec<String> = request.json()?;
+            cancel_reply(shared, &request.owner(), ids)
+        },
+        ("DELETE", "/cancel-all") => {
+            let owner = request.owner();
+            let ids = shared
+                .state
+                .lock()
+                .orders
+                .values()
+                .filter(|o| o.owner == owner && o.status == "LIVE")
+                .map(|o| o.id.clone())
+                .collect();
+            cancel_reply(shared, &owner, ids)
+        },
+        ("GET", "/data/orders") => {
+            let owner = request.owner();
+            let filter = |key: &str, value: &str| {
+                request.query.get(key).is_none_or(|wanted| wanted == value)
+            };
+            let state = shared.state.lock();
+            let mut orders: Vec<&SimOrder> = state
+                .orders
+                .values()
+                .filter(|o| o.owner == owner && o.status == "LIVE")
+                .filter(|o| {
+                    filter("id", &o.id)
+                        && filter("asset_id", &o.token_id)
+                        && filter("market", &o.market)
+                })
+                .collect();
+            orders.sort_by(|a, b| a.id.cmp(&b.id));
+            let data = orders
+                .into_iter()
+                .map(|o| serde_json::to_value(o.to_open_order()).unwrap_or_default())
+                .collect();
+            Reply::json(json!({ "data": Value::Array(data), "next_cursor": END_CURSOR }))
+        },
+        ("GET", "/data/trades") => {
+            let owner = request.owner();
+            let state = shared.state.lock();
+            let data = state
+                .trades
+                .iter()
+                .rev()
+                .filter(|(trade_owner, _)| *trade_owner == owner)
+                .filter(|(_, t)| {
+                    request
+                        .query
+                        .get("asset_id")
+                        .is_none_or(|wanted| *wanted == t.asset_id)
+                })
+                .map(|(_, t)| serde_json::to_value(t).unwrap_or_default())
+                .collect();
+            Reply::json(json!({ "data": Value::Array(data), "next_cursor": END_CURSOR }))
+        },
+        ("GET", path) if path.starts_with("/data/order/") => {
+            let id = &path["/data/order/".len()..];
+            let state = shared.state.lock();
+            match state.orders.get(id) {
+                Some(order) if order.owner == request.owner() => {
+                    Reply::json(serde_json::to_value(order.to_open_order()).unwrap_or_default())
+                },
+                _ => Reply::error(404, format!("order {id} not found")),
+            }
+        },
+        (method, path) => Reply::error(404, format!("{method} {path} is not simulated")),
+    };
+    Ok(reply)
+}
+
+fn book_json(summary: &OrderBookSummary) -> Value {
+    let levels = |levels: &[OrderSummary]| {
+        levels
+            .iter()
+            .map(|l| json!({ "price": l.price.to_string(), "size": l.size.to_string() }))
+            .collect::<Vec<_>>()
+    };
+    json!({
+        "market": summary.market,
+        "asset_id": summary.asset_id,
+        "timestamp": summary.timestamp.to_string(),
+        "bids": levels(&summary.bids),
+        "asks": levels(&summary.asks),
+        "min_order_size": summary.min_order_size.to_string(),
+        "neg_risk": summary.neg_risk,
+        "tick_size": summary.tick_size.to_string(),
+        "last_trade_price": summary.last_trade_price.map(|p| p.to_string()),
+    })
+}
+
+fn top_of_book(summary: &OrderBookSummary) -> std::result::Result<(Decimal, Decimal), Reply> {
+    match (summary.bids.first(), summary.asks.first()) {
+        (Some(bid), Some(ask)) => Ok((bid.price, ask.price)),
+        _ => Err(Reply::error(404, "No orderbook on both sides")),
+    }
+}
+
+fn cancel_reply(shared: &Shared, owner: &str, ids: Vec<String>) -> Reply {
+    let (canceled, not_canceled) =
+        shared.with_state(|state, now_ms, events| state.cancel(owner, ids, now_ms, events));
+    Reply::json(json!({ "canceled": canceled, "not_canceled": not_canceled }))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostOrderBody {
+    order: SignedOrderRequest,
+    owner: String,
+    #[serde(default)]
+    order_type: OrderType,
+    #[serde(default)]
+    post_only: bool,
+}
+
+/// Convert a base-unit amount string (6 decimals) to a decimal quantity
+fn from_token_units(amount: &str) -> Option<Decimal> {
+    Decimal::from_str(amount)
+        .ok()
+        .map(|units| units / Decimal::from(1_000_000))
+}
+
+fn decode_order(body: PostOrderBody) -> std::result::Result<NewOrder, Reply> {
+    let order = body.order;
+    let side: Side = order
+        .side
+        .parse()
+        .map_err(|_| Reply::error(400, format!("invalid side {}", order.side)))?;
+    let (maker, taker) = from_token_units(&order.maker_amount)
+        .zip(from_token_units(&order.taker_amount))
+        .filter(|(maker, taker)| !maker.is_zero() && !taker.is_zero())
+        .ok_or_else(|| Reply::error(400, "invalid order amounts"))?;
+    // BUY orders give collateral for shares; SELL orders give shares for collateral
+    let (price, size) = match side {
+        Side::BUY => (maker / taker, taker),
+        Side::SELL => (taker / maker, maker),
+    };
+    Ok(NewOrder {
+        owner: body.owner,
+        maker_address: order.maker,
+        token_id: order.token_id,
+        side,
+        price: price.normalize(),
+        size,
+        order_type: body.order_type,
+        post_only: body.post_only,
+        expiration: order.expiration.parse().unwrap_or(0),
+    })
+}
+
+fn post_order(shared: &Shared, request: &Request) -> std::result::Result<Reply, Reply> {
+    let mut order = decode_order(request.json()?)?;
+    // Round to the market tick so amount rounding in the signed payload
+    // does not leave the price a hair off-tick
+    if let Some(tick) = shared
+        .state
+        .lock()
+        .books
+        .get(&order.token_id)
+        .map(|b| b.market.tick_size)
+    {
+        order.price = order.price.round_dp(tick.scale());
+    }
+
+    let submitted = shared
+        .with_state(|state, now_ms, events| state.submit(order, now_ms, events))
+        .map_err(|message| Reply::error(400, message))?;
+    Ok(Reply::json(json!({
+        "success": true,
+        "orderID": submitted.order_id,
+        "status": submitted.status,
+        "makingAmount": submitted.making_amount.to_string(),
+        "takingAmount": submitted.taking_amount.to_string(),
+        "transactionsHashes": [],
+        "tradeIds": submitted.trade_ids,
+        "errorMsg": "",
+    })))
+}
+
+// ----------------------------------------------------------------------------
+// WebSocket
+// ----------------------------------------------------------------------------
+
+fn event_asset_id(message: &StreamMessage) -> Option<&str> {
+    match message {
+        StreamMessage::Book(m) => Some(&m.asset_id),
+        StreamMessage::PriceChange(m) => m.price_changes.first().map(|c| c.asset_id.as_str()),
+        StreamMessage::LastTradePrice(m) => Some(&m.asset_id),
+        StreamMessage::TickSizeChange(m) => Some(&m.asset_id),
+        _ => None,
+    }
+}
+
+fn frame(messages: &[StreamMessage]) -> Option<Message> {
+    serde_json::to_string(messages).ok().map(Message::Text)
+}
+
+async fn serve_ws(socket: TcpStream, shared: Arc<Shared>, mut shutdown: watch::Receiver<bool>) {
+    let mut ws = match tokio_tungstenite::accept_async(socket).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            debug!("Simulator WS handshake failed: {}", e);
+            return;
+        },
+    };
+    let mut events = shared.events.subscribe();
+    let mut assets: HashSet<String> = HashSet::new();
+    // `Some(owner)` once subscribed to the user channel; an empty owner sees all users
+    let mut user: Option<String> = None;
+    let mut markets: HashSet<String> = HashSet::new();
+
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => {
+                let _ = ws.close(None).await;
+                return;
+            },
+            incoming = ws.next() => {
+                let text = match incoming {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Ping(payload))) => {
+                        let _ = ws.send(Message::Pong(payload)).await;
+                        continue;
+                    },
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    Some(Ok(_)) => continue,
+                };
+                if text.trim().eq_ignore_ascii_case("ping") {
+                    let _ = ws.send(Message::Text("PONG".to_string())).await;
+                    continue;
+                }
+                let Ok(subscription) = serde_json::from_str::<WssSubscription>(&text) else {
+                    continue;
+                };
+                let unsubscribe = subscription.operation.as_deref() == Some("unsubscribe");
+
+                if subscription.channel_type.eq_ignore_ascii_case("user") {
+                    if unsubscribe {
+                        user = None;
+                    } else {
+                        user = Some(subscription.auth.map(|a| a.api_key).unwrap_or_default());
+                        markets.extend(subscription.markets);
+                    }
+                    continue;
+                }
+
+                if unsubscribe {
+                    for id in &subscription.asset_ids {
+                        assets.remove(id);
+                    }
+                    continue;
+                }
+                let added: Vec<String> = subscription
+                    .asset_ids
+                    .into_iter()
+                    .filter(|id| assets.insert(id.clone()))
+                    .collect();
+                if subscription.initial_dump == Some(false) || added.is_empty() {
+                    continue;
+                }
+                let now_ms = shared.clock.now_millis();
+                let snapshots: Vec<StreamMessage> = {
+                    let state = shared.state.lock();
+                    added.iter().filter_map(|id| state.snapshot(id, now_ms)).collect()
+                };
+                if let Some(frame) = frame(&snapshots) {
+                    if ws.send(frame).await.is_err() {
+                        return;
+                    }
+                }
+            },
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!("Simulator WS client lagged by {} events", skipped);
+                        continue;
+                    },
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                let wanted = match &event.owner {
+                    None => event_asset_id(&event.message).is_some_and(|id| assets.contains(id)),
+                    Some(owner) => user.as_ref().is_some_and(|user| {
+                        (user.is_empty() || user == owner)
+                            && (markets.is_empty() || markets.contains(market_of(&event.message)))
+                    }),
+                };
+                if !wanted {
+                    continue;
+                }
+                if let Some(frame) = frame(std::slice::from_ref(&event.message)) {
+                    if ws.send(frame).await.is_err() {
+                        return;
+                    }
+                }
+            },
+        }
+    }
+}
+
+fn market_of(message: &StreamMessage) -> &str {
+    match message {
+        StreamMessage::Trade(m) => &m.market,
+        StreamMessage::Order(m) => &m.market,
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ClobClient;
+    use crate::stream::WebSocketStream;
+    use crate::types::{ApiCredentials, OrderArgs, PostOrderOptions, WssSubscription};
+    use crate::ClientConfig;
+    use rust_decimal_macros::dec;
+    use std::time::Duration;
+
+    const TOKEN: &str = "12345";
+
+    fn trader(base_url: &str) -> ClobClient {
+        ClobClient::from_config(ClientConfig {
+            base_url: base_url.to_string(),
+            chain: 137,
+            private_key: Some(
+                "0x1234567890123456789012345678901234567890123456789012345678901234".to_string(),
+            ),
+            api_credentials: Some(ApiCredentials {
+                api_key: "trader".to_string(),
+                secret: "dGVzdF9zZWNyZXRfa2V5XzEyMzQ1".to_string(),
+                passphrase: "test_passphrase".to_string(),
+            }),
+            ..ClientConfig::default()
+        })
+        .expect("trader client")
+    }
+
+    async fn next(stream: &mut WebSocketStream) -> StreamMessage {
+        tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await
+            .expect("message before timeout")
+            .expect("stream open")
+            .expect("valid message")
+    }
+
+    async fn simulator() -> ExchangeSimulator {
+        let simulator = ExchangeSimulator::start().await.unwrap();
+        simulator.add_market(TOKEN, SimMarket::new("0xcondition"));
+        simulator
+            .seed_order(TOKEN, Side::BUY, dec!(0.48), dec!(100))
+            .unwrap();
+        simulator
+            .seed_order(TOKEN, Side::SELL, dec!(0.52), dec!(20))
+            .unwrap();
+        simulator
+            .seed_order(TOKEN, Side::SELL, dec!(0.53), dec!(50))
+            .unwrap();
+        simulator
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_client_market_data_against_simulator() {
+        let simulator = simulator().await;
+        let client = ClobClient::new(&simulator.base_url());
+
+        let book = client.get_order_book(TOKEN).await.unwrap();
+        assert_eq!(book.bids[0].price, dec!(0.48));
+        assert_eq!(book.asks[0].price, dec!(0.52));
+        assert_eq!(book.asks[1].size, dec!(50));
+        assert_eq!(client.get_midpoint(TOKEN).await.unwrap().mid, dec!(0.50));
+        assert_eq!(client.get_spread(TOKEN).await.unwrap().spread, dec!(0.04));
+        assert_eq!(client.get_tick_size(TOKEN).await.unwrap(), dec!(0.01));
+        assert!(!client.get_neg_risk(TOKEN).await.unwrap());
+        assert!(client.get_server_time().await.unwrap() > 0);
+        assert!(client.get_order_book("unknown").await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_orders_match_rest_and_cancel() {
+        let simulator = simulator().await;
+        let client = trader(&simulator.base_url());
+
+        // Crosses 20 @ 0.52 and 10 @ 0.53
+        let buy = OrderArgs::new(TOKEN.parse().unwrap(), dec!(0.53), dec!(30), Side::BUY);
+        let response = client
+            .create_and_post_order(&buy, None, None)
+            .await
+            .unwrap();
+        assert!(response.success);
+        assert_eq!(response.status, "matched");
+        assert_eq!(response.trade_ids.len(), 2);
+        assert_eq!(simulator.book(TOKEN).unwrap().asks[0].size, dec!(40));
+        assert_eq!(simulator.trades().len(), 2);
+
+        // Rests below the ask
+        let bid = OrderArgs::new(TOKEN.parse().unwrap(), dec!(0.50), dec!(10), Side::BUY);
+        let resting = client
+            .create_and_post_order(&bid, None, None)
+            .await
+            .unwrap();
+        assert_eq!(resting.status, "live");
+        let open = client.get_orders(None, None).await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].price, dec!(0.50));
+        assert_eq!(simulator.book(TOKEN).unwrap().bids[0].price, dec!(0.50));
+
+        // Post-only orders that would cross are rejected
+        let crossing = OrderArgs::new(TOKEN.parse().unwrap(), dec!(0.53), dec!(10), Side::BUY);
+        let post_only = PostOrderOptions {
+            post_only: true,
+            ..PostOrderOptions::default()
+        };
+        assert!(client
+            .create_and_post_order(&crossing, None, Some(&post_only))
+            .await
+            .is_err());
+
+        let cancelled = client.cancel(&resting.order_id).await.unwrap();
+        assert_eq!(cancelled.canceled, vec![resting.order_id.clone()]);
+        assert!(client.get_orders(None, None).await.unwrap().is_empty());
+        assert_eq!(
+            client.get_order(&resting.order_id).await.unwrap().status,
+            "CANCELED"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_partial_market_order_rests_what_did_not_execute() {
+        let simulator = simulator().await;
+        let client = trader(&simulator.base_url());
+
+        // The asks hold 10.4 + 26.5 USDC up to 0.53
+        let mut args = crate::types::MarketOrderArgs::new(
+            TOKEN.parse().unwrap(),
+            dec!(50),
+            Side::BUY,
+            crate::types::OrderType::FOK,
+        );
+        args.price_limit = Some(dec!(0.53));
+        let outcome = client
+            .create_and_post_market_order_partial(&args, None, crate::types::MarketRemainder::Rest)
+            .await
+            .unwrap();
+        let execution = outcome.execution.unwrap();
+        assert!(execution.success);
+        assert_eq!(
+            outcome.executed,
+            execution.making_amount.parse::<Decimal>().unwrap()
+        );
+        assert!(outcome.executed > Decimal::ZERO);
+        assert!(outcome.follow_up.unwrap().success);
+        assert_eq!(outcome.remainder, Decimal::ZERO);
+        let open = client.get_orders(None, None).await.unwrap();
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].price, dec!(0.53));
+        assert_eq!(
+            open[0].original_size,
+            ((dec!(50) - outcome.executed) / dec!(0.53)).trunc_with_scale(2)
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_duplicate_guard_keeps_orders_whose_post_was_cancelled() {
+        let simulator = simulator().await;
+        let client = trader(&simulator.base_url());
+        client.set_duplicate_guard(std::sync::Arc::new(crate::dedup::DuplicateGuard::default()));
+        let is_duplicate = |result: Result<crate::types::PostOrderResponse>| {
+            matches!(
+                result,
+                Err(PolyfillError::Order {
+                    kind: crate::errors::OrderErrorKind::DuplicateOrder,
+                    ..
+                })
+            )
+        };
+
+        let mut market = crate::types::MarketOrderArgs::new(
+            TOKEN.parse().unwrap(),
+            dec!(5),
+            Side::BUY,
+            crate::types::OrderType::FOK,
+        );
+        market.price_limit = Some(dec!(0.53));
+        assert!(
+            client
+                .create_and_post_market_order(&market, None, None)
+                .await
+                .unwrap()
+                .success
+        );
+        assert!(is_duplicate(
+            client
+                .create_and_post_market_order(&market, None, None)
+                .await
+        ));
+
+        // With the market details cached, the post is under way when the
+        // timeout drops it, so the order may be live and still blocks a
+        // look-alike
+        let bid = OrderArgs::new(TOKEN.parse().unwrap(), dec!(0.45), dec!(10), Side::BUY);
+        let _ = tokio::time::timeout(
+            Duration::ZERO,
+            client.create_and_post_order(&bid, None, None),
+        )
+        .await;
+        assert!(is_duplicate(
+            client.create_and_post_order(&bid, None, None).await
+        ));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_market_channel_publishes_snapshot_and_trades() {
+        let simulator = simulator().await;
+        let mut stream = WebSocketStream::new(&simulator.market_ws_url());
+        stream
+            .subscribe_async(WssSubscription {
+                channel_type: "market".to_string(),
+                operation: Some("subscribe".to_string()),
+                markets: vec![],
+                asset_ids: vec![TOKEN.to_string()],
+                initial_dump: Some(true),
+                custom_feature_enabled: None,
+                auth: None,
+            })
+            .await
+            .unwrap();
+
+        match next(&mut stream).await {
+            StreamMessage::Book(book) => {
+                assert_eq!(book.asset_id, TOKEN);
+                assert_eq!(book.bids[0].price, dec!(0.48));
+            },
+            other => panic!("expected book snapshot, got {other:?}"),
+        }
+
+        simulator
+            .seed_order(TOKEN, Side::SELL, dec!(0.48), dec!(10))
+            .unwrap();
+        let mut saw_trade = false;
+        let mut saw_level_update = false;
+        while !(saw_trade && saw_level_update) {
+            match next(&mut stream).await {
+                StreamMessage::LastTradePrice(trade) => {
+                    assert_eq!(trade.price, dec!(0.48));
+                    saw_trade = true;
+                },
+                StreamMessage::PriceChange(change) => {
+                    let entry = &change.price_changes[0];
+                    assert_eq!(entry.size, Some(dec!(90)));
+                    saw_level_update = true;
+                },
+                _ => {},
+            }
+        }
+    }
+}
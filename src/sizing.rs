@@ -0,0 +1,341 @@
+//! Position sizing
+//!
+//! Calculators that turn a probability estimate, a price and a bankroll into
+//! a recommended order size for a binary outcome token. Every recommendation
+//! passes through [`SizingLimits`] so a strategy cannot size past its risk
+//! budget regardless of how confident the estimate is.
+//!
+//! Sizes are in shares; a share pays 1 if the outcome resolves true. Buying at
+//! `price` risks `price` per share, selling risks `1 - price`.
+
+use crate::errors::{PolyfillError, Result};
+use crate::types::Side;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+/// Caps applied to every sizing recommendation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizingLimits {
+    /// Largest share of bankroll a single order may put at risk
+    pub max_bankroll_fraction: Decimal,
+    /// Hard cap on capital at risk per order
+    pub max_risk: Option<Decimal>,
+    /// Hard cap on shares per order
+    pub max_size: Option<Decimal>,
+    /// Recommendations below this size are rounded down to zero
+    pub min_order_size: Decimal,
+    /// Sizes are rounded down to this many decimal places
+    pub size_decimals: u32,
+}
+
+impl Default for SizingLimits {
+    fn default() -> Self {
+        Self {
+            max_bankroll_fraction: Decimal::new(25, 2),
+            max_risk: None,
+            max_size: None,
+            min_order_size: Decimal::from(5),
+            size_decimals: 2,
+        }
+    }
+}
+
+/// Recommended order size with the reasoning behind it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SizeRecommendation {
+    pub side: Side,
+    pub price: Decimal,
+    /// Shares to trade; zero means "do not trade"
+    pub size: Decimal,
+    /// Capital at risk if the order fills and the position loses
+    pub risk: Decimal,
+    /// Bankroll fraction the calculator asked for before limits
+    pub target_fraction: Decimal,
+    /// Whether a limit reduced the size
+    pub capped: bool,
+}
+
+/// One market in a [`PositionSizer::risk_parity`] allocation
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskParityLeg {
+    pub side: Side,
+    pub price: Decimal,
+    /// Estimated probability the token resolves true
+    pub probability: Decimal,
+}
+
+/// Optimal Kelly fraction of bankroll for a binary token.
+///
+/// Buying at `price` with win probability `probability` stakes
+/// `(probability - price) / (1 - price)`; selling is the mirror image.
+/// Returns zero when the estimate shows no edge.
+pub fn kelly_fraction(probability: Decimal, price: Decimal, side: Side) -> Decimal {
+    let (edge, odds_denominator) = match side {
+        Side::BUY => (probability - price, Decimal::ONE - price),
+        Side::SELL => (price - probability, price),
+    };
+    if edge <= Decimal::ZERO || odds_denominator <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    edge / odds_denominator
+}
+
+/// Capital at risk per share
+fn risk_per_share(price: Decimal, side: Side) -> Decimal {
+    match side {
+        Side::BUY => price,
+        Side::SELL => Decimal::ONE - price,
+    }
+}
+
+fn validate_unit(value: Decimal, name: &str) -> Result<()> {
+    if value <= Decimal::ZERO || value >= Decimal::ONE {
+        return Err(PolyfillError::validation(format!(
+            "{name} must be between 0 and 1 exclusive, got {value}"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_probability(probability: Decimal) -> Result<()> {
+    if probability < Decimal::ZERO || probability > Decimal::ONE {
+        return Err(PolyfillError::validation(format!(
+            "Probability must be between 0 and 1, got {probability}"
+        )));
+    }
+    Ok(())
+}
+
+/// Applies [`SizingLimits`] to the standard sizing methods
+#[derive(Debug, Clone, Default)]
+pub struct PositionSizer {
+    limits: SizingLimits,
+}
+
+impl PositionSizer {
+    pub fn new(limits: SizingLimits) -> Self {
+        Self { limits }
+    }
+
+    pub fn limits(&self) -> &SizingLimits {
+        &self.limits
+    }
+
+    /// Size by the Kelly criterion, scaled by `multiplier` (0.5 for half-Kelly)
+    pub fn kelly(
+        &self,
+        probability: Decimal,
+        price: Decimal,
+        side: Side,
+        bankroll: Decimal,
+        multiplier: Decimal,
+    ) -> Result<SizeRecommendation> {
+        validate_probability(probability)?;
+        validate_unit(price, "Price")?;
+        if multiplier < Decimal::ZERO {
+            return Err(PolyfillError::validation(
+                "Kelly multiplier cannot be negative",
+            ));
+        }
+
+        let fraction = kelly_fraction(probability, price, side) * multiplier;
+        self.size_for_fraction(fraction, price, side, bankroll)
+    }
+
+    /// Risk a fixed `fraction` of bankroll
+    pub fn fixed_fraction(
+        &self,
+        fraction: Decimal,
+        price: Decimal,
+        side: Side,
+        bankroll: Decimal,
+    ) -> Result<SizeRecommendation> {
+        validate_unit(price, "Price")?;
+        if fraction < Decimal::ZERO {
+            return Err(PolyfillError::validation("Fraction cannot be negative"));
+        }
+
+        self.size_for_fraction(fraction, price, side, bankroll)
+    }
+
+    /// Split `capital` so every leg contributes equal payoff volatility.
+    ///
+    /// A share's payoff has standard deviation `sqrt(p * (1 - p))` for
+    /// resolution probability `p`, so legs near 50% receive fewer shares than
+    /// legs near certainty. Each leg is then capped by the sizer's limits.
+    pub fn risk_parity(
+        &self,
+        legs: &[RiskParityLeg],
+        capital: Decimal,
+        bankroll: Decimal,
+    ) -> Result<Vec<SizeRecommendation>> {
+        let mut volatilities = Vec::with_capacity(legs.len());
+        for leg in legs {
+            validate_unit(leg.price, "Price")?;
+            validate_unit(leg.probability, "Probability")?;
+            let variance = leg.probability * (Decimal::ONE - leg.probability);
+            let volatility = variance
+                .to_f64()
+                .map(f64::sqrt)
+                .and_then(Decimal::from_f64)
+                .ok_or_else(|| PolyfillError::validation("Volatility out of range"))?;
+            volatilities.push(volatility);
+        }
+
+        // Shares per leg are k / volatility; pick k so total risk equals capital
+        let risk_per_unit: Decimal = legs
+            .iter()
+            .zip(&volatilities)
+            .map(|(leg, volatility)| risk_per_share(leg.price, leg.side) / volatility)
+            .sum();
+        if risk_per_unit.is_zero() {
+            return Ok(Vec::new());
+        }
+        let scale = capital / risk_per_unit;
+
+        legs.iter()
+            .zip(&volatilities)
+            .map(|(leg, volatility)| {
+                let risk = scale / volatility * risk_per_share(leg.price, leg.side);
+                let fraction = if bankroll.is_zero() {
+                    Decimal::ZERO
+                } else {
+                    risk / bankroll
+                };
+                self.size_for_fraction(fraction, leg.price, leg.side, bankroll)
+            })
+            .collect()
+    }
+
+    fn size_for_fraction(
+        &self,
+        fraction: Decimal,
+        price: Decimal,
+        side: Side,
+        bankroll: Decimal,
+    ) -> Result<SizeRecommendation> {
+        if bankroll < Decimal::ZERO {
+            return Err(PolyfillError::validation("Bankroll cannot be negative"));
+        }
+
+        let per_share = risk_per_share(price, side);
+        let mut risk = bankroll * fraction.min(self.limits.max_bankroll_fraction);
+        if let Some(max_risk) = self.limits.max_risk {
+            risk = risk.min(max_risk);
+        }
+        let mut size = risk / per_share;
+        if let Some(max_size) = self.limits.max_size {
+            size = size.min(max_size);
+        }
+        size = size.trunc_with_scale(self.limits.size_decimals);
+
+        let uncapped = bankroll * fraction / per_share;
+        let capped = size < uncapped.trunc_with_scale(self.limits.size_decimals);
+        if size < self.limits.min_order_size {
+            size = Decimal::ZERO;
+        }
+
+        Ok(SizeRecommendation {
+            side,
+            price,
+            size,
+            risk: size * per_share,
+            target_fraction: fraction,
+            capped,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn sizer() -> PositionSizer {
+        PositionSizer::new(SizingLimits {
+            max_bankroll_fraction: dec!(0.5),
+            min_order_size: dec!(1),
+            ..SizingLimits::default()
+        })
+    }
+
+    #[test]
+    fn test_kelly_fraction() {
+        // 60% estimate on a 50c token: stake 20%
+        assert_eq!(kelly_fraction(dec!(0.6), dec!(0.5), Side::BUY), dec!(0.2));
+        assert_eq!(kelly_fraction(dec!(0.4), dec!(0.5), Side::SELL), dec!(0.2));
+        assert_eq!(
+            kelly_fraction(dec!(0.4), dec!(0.5), Side::BUY),
+            Decimal::ZERO
+        );
+    }
+
+    #[test]
+    fn test_kelly_respects_limits() {
+        let half_kelly = sizer()
+            .kelly(dec!(0.6), dec!(0.5), Side::BUY, dec!(1000), dec!(0.5))
+            .unwrap();
+        assert_eq!(half_kelly.risk, dec!(100));
+        assert_eq!(half_kelly.size, dec!(200));
+        assert!(!half_kelly.capped);
+
+        // Full Kelly on a huge edge is clipped to 50% of bankroll
+        let capped = sizer()
+            .kelly(dec!(0.99), dec!(0.1), Side::BUY, dec!(1000), dec!(1))
+            .unwrap();
+        assert_eq!(capped.risk, dec!(500));
+        assert!(capped.capped);
+
+        let no_edge = sizer()
+            .kelly(dec!(0.4), dec!(0.5), Side::BUY, dec!(1000), dec!(1))
+            .unwrap();
+        assert_eq!(no_edge.size, Decimal::ZERO);
+
+        assert!(sizer()
+            .kelly(dec!(1.2), dec!(0.5), Side::BUY, dec!(1000), dec!(1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_fixed_fraction_rounds_and_applies_minimum() {
+        let sizer = PositionSizer::new(SizingLimits {
+            max_risk: Some(dec!(30)),
+            ..SizingLimits::default()
+        });
+        // Selling at 0.70 risks 0.30 per share
+        let rec = sizer
+            .fixed_fraction(dec!(0.1), dec!(0.7), Side::SELL, dec!(1000))
+            .unwrap();
+        assert_eq!(rec.size, dec!(100));
+        assert!(rec.capped);
+
+        let tiny = sizer
+            .fixed_fraction(dec!(0.001), dec!(0.5), Side::BUY, dec!(1000))
+            .unwrap();
+        assert_eq!(tiny.size, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_risk_parity_equalizes_volatility() {
+        let legs = [
+            RiskParityLeg {
+                side: Side::BUY,
+                price: dec!(0.5),
+                probability: dec!(0.5),
+            },
+            RiskParityLeg {
+                side: Side::BUY,
+                price: dec!(0.9),
+                probability: dec!(0.9),
+            },
+        ];
+        let recs = sizer().risk_parity(&legs, dec!(100), dec!(1000)).unwrap();
+        assert_eq!(recs.len(), 2);
+
+        // Volatilities 0.5 and 0.3: share counts are inversely proportional
+        let ratio = recs[0].size / recs[1].size;
+        assert!((ratio - dec!(0.6)).abs() < dec!(0.01));
+        let total_risk: Decimal = recs.iter().map(|r| r.risk).sum();
+        assert!((total_risk - dec!(100)).abs() < dec!(0.05));
+    }
+}
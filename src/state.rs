@@ -0,0 +1,416 @@
+//! SQLite-backed state persistence
+//!
+//! [`StateStore`] keeps open orders, fills, positions and armed triggers in a
+//! SQLite database so a restarted bot can call [`StateStore::recover`] and
+//! resume with the state it had before instead of starting blind.
+//!
+//! Enabled with the `state` feature.
+
+use crate::errors::{PolyfillError, Result};
+use crate::handlers::EventHandlers;
+pub use crate::recovery::{ArmedTrigger, Position};
+use crate::types::{OpenOrder, TradeMessage};
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use rusqlite::{params, Connection, OptionalExtension};
+use rust_decimal::Decimal;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Current on-disk schema version (stored in `PRAGMA user_version`)
+pub const SCHEMA_VERSION: i32 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS orders (
+    id TEXT PRIMARY KEY,
+    body TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS fills (
+    id TEXT PRIMARY KEY,
+    asset_id TEXT NOT NULL,
+    body TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS positions (
+    token_id TEXT PRIMARY KEY,
+    size TEXT NOT NULL,
+    average_price TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS triggers (
+    id TEXT PRIMARY KEY,
+    body TEXT NOT NULL
+);
+";
+
+/// Everything persisted by a [`StateStore`]
+#[derive(Debug, Clone, Default)]
+pub struct RecoveredState {
+    pub open_orders: Vec<OpenOrder>,
+    pub fills: Vec<TradeMessage>,
+    pub positions: Vec<Position>,
+    pub triggers: Vec<ArmedTrigger>,
+}
+
+/// Durable store for orders, fills, positions and armed triggers
+#[derive(Debug)]
+pub struct StateStore {
+    conn: Mutex<Connection>,
+}
+
+impl StateStore {
+    /// Open (or create) a state database at `path`
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let conn = Connection::open(path.as_ref())?;
+        // WAL keeps writers from blocking a concurrent reader (e.g. a dashboard)
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        Self::with_connection(conn)
+    }
+
+    /// Open a throwaway in-memory store (useful for tests and dry runs)
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            return Err(PolyfillError::config(format!(
+                "State database schema version {version} is newer than supported version {SCHEMA_VERSION}"
+            )));
+        }
+
+        conn.execute_batch(SCHEMA)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Insert or replace an open order
+    pub fn upsert_order(&self, order: &OpenOrder) -> Result<()> {
+        let body = serde_json::to_string(order)?;
+        self.conn.lock().execute(
+            "INSERT INTO orders (id, body, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(id) DO UPDATE SET body = excluded.body, updated_at = excluded.updated_at",
+            params![order.id, body, Utc::now().timestamp_millis()],
+        )?;
+        Ok(())
+    }
+
+    /// Replace the full set of open orders (e.g. after a REST reconciliation)
+    pub fn replace_orders(&self, orders: &[OpenOrder]) -> Result<()> {
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM orders", [])?;
+        let now = Utc::now().timestamp_millis();
+        for order in orders {
+            tx.execute(
+                "INSERT INTO orders (id, body, updated_at) VALUES (?1, ?2, ?3)",
+                params![order.id, serde_json::to_string(order)?, now],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Remove an order that is no longer open. Returns `true` if it was stored.
+    pub fn remove_order(&self, order_id: &str) -> Result<bool> {
+        let removed = self
+            .conn
+            .lock()
+            .execute("DELETE FROM orders WHERE id = ?1", params![order_id])?;
+        Ok(removed > 0)
+    }
+
+    /// All stored open orders
+    pub fn open_orders(&self) -> Result<Vec<OpenOrder>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT body FROM orders ORDER BY updated_at, id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.map(|body| Ok(serde_json::from_str(&body?)?)).collect()
+    }
+
+    /// Record a fill and update the matching position.
+    ///
+    /// Fills are de-duplicated by trade ID, so replaying the user channel after
+    /// a reconnect is safe. Returns `false` if the fill was already recorded.
+    pub fn record_fill(&self, fill: &TradeMessage) -> Result<bool> {
+        let body = serde_json::to_string(fill)?;
+        let mut conn = self.conn.lock();
+        let tx = conn.transaction()?;
+
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO fills (id, asset_id, body, recorded_at) VALUES (?1, ?2, ?3, ?4)",
+            params![fill.id, fill.asset_id, body, Utc::now().timestamp_millis()],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        let mut position = Self::load_position(&tx, &fill.asset_id)?
+            .unwrap_or_else(|| Position::new(&fill.asset_id));
+        position.apply_fill(fill.side, fill.size, fill.price);
+        Self::store_position(&tx, &position)?;
+
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// All recorded fills, oldest first
+    pub fn fills(&self) -> Result<Vec<TradeMessage>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT body FROM fills ORDER BY recorded_at, id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.map(|body| Ok(serde_json::from_str(&body?)?)).collect()
+    }
+
+    /// Overwrite a position (e.g. after reconciling against on-chain balances)
+    pub fn set_position(&self, position: &Position) -> Result<()> {
+        Self::store_position(&self.conn.lock(), position)
+    }
+
+    /// Position for a single token
+    pub fn position(&self, token_id: &str) -> Result<Option<Position>> {
+        Self::load_position(&self.conn.lock(), token_id)
+    }
+
+    /// All stored positions
+    pub fn positions(&self) -> Result<Vec<Position>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare(
+            "SELECT token_id, size, average_price, updated_at FROM positions ORDER BY token_id",
+        )?;
+        let rows = stmt.query_map([], Self::position_from_row)?;
+        rows.map(|position| position?).collect()
+    }
+
+    /// Persist an armed trigger (replacing any trigger with the same ID)
+    pub fn arm_trigger(&self, trigger: &ArmedTrigger) -> Result<()> {
+        self.conn.lock().execute(
+            "INSERT OR REPLACE INTO triggers (id, body) VALUES (?1, ?2)",
+            params![trigger.id, serde_json::to_string(trigger)?],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a trigger once it fired or was cancelled
+    pub fn disarm_trigger(&self, trigger_id: &str) -> Result<bool> {
+        let removed = self
+            .conn
+            .lock()
+            .execute("DELETE FROM triggers WHERE id = ?1", params![trigger_id])?;
+        Ok(removed > 0)
+    }
+
+    /// All armed triggers
+    pub fn triggers(&self) -> Result<Vec<ArmedTrigger>> {
+        let conn = self.conn.lock();
+        let mut stmt = conn.prepare("SELECT body FROM triggers ORDER BY id")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        rows.map(|body| Ok(serde_json::from_str(&body?)?)).collect()
+    }
+
+    /// Load everything persisted so far, for use on startup
+    pub fn recover(&self) -> Result<RecoveredState> {
+        let state = RecoveredState {
+            open_orders: self.open_orders()?,
+            fills: self.fills()?,
+            positions: self.positions()?,
+            triggers: self.triggers()?,
+        };
+
+        info!(
+            "Recovered state: {} open orders, {} fills, {} positions, {} triggers",
+            state.open_orders.len(),
+            state.fills.len(),
+            state.positions.len(),
+            state.triggers.len()
+        );
+        Ok(state)
+    }
+
+    /// Record every fill dispatched through `handlers`
+    pub fn attach(self: &Arc<Self>, handlers: &EventHandlers) {
+        let store = Arc::clone(self);
+        handlers.on_fill(move |fill| {
+            if let Err(e) = store.record_fill(fill) {
+                warn!("Failed to persist fill {}: {}", fill.id, e);
+            }
+        });
+    }
+
+    fn load_position(conn: &Connection, token_id: &str) -> Result<Option<Position>> {
+        conn.query_row(
+            "SELECT token_id, size, average_price, updated_at FROM positions WHERE token_id = ?1",
+            params![token_id],
+            Self::position_from_row,
+        )
+        .optional()?
+        .transpose()
+    }
+
+    fn store_position(conn: &Connection, position: &Position) -> Result<()> {
+        conn.execute(
+            "INSERT INTO positions (token_id, size, average_price, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(token_id) DO UPDATE SET size = excluded.size,
+                 average_price = excluded.average_price, updated_at = excluded.updated_at",
+            params![
+                position.token_id,
+                position.size.to_string(),
+                position.average_price.to_string(),
+                position.updated_at.timestamp_millis()
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn position_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Result<Position>> {
+        let token_id: String = row.get(0)?;
+        let size: String = row.get(1)?;
+        let average_price: String = row.get(2)?;
+        let updated_at: i64 = row.get(3)?;
+
+        Ok((|| {
+            Ok(Position {
+                token_id,
+                size: Decimal::from_str(&size).map_err(|e| {
+                    PolyfillError::parse(format!("Invalid position size: {e}"), None)
+                })?,
+                average_price: Decimal::from_str(&average_price).map_err(|e| {
+                    PolyfillError::parse(format!("Invalid position price: {e}"), None)
+                })?,
+                updated_at: DateTime::from_timestamp_millis(updated_at).unwrap_or_else(Utc::now),
+            })
+        })())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderType, Side};
+    use rust_decimal_macros::dec;
+
+    fn fill(id: &str, side: Side, size: Decimal, price: Decimal) -> TradeMessage {
+        TradeMessage {
+            id: id.to_string(),
+            market: "0xabc".to_string(),
+            asset_id: "123".to_string(),
+            side,
+            size,
+            price,
+            status: None,
+            msg_type: None,
+            last_update: None,
+            matchtime: None,
+            timestamp: None,
+            extra: Default::default(),
+        }
+    }
+
+    fn open_order(id: &str) -> OpenOrder {
+        OpenOrder {
+            associate_trades: vec![],
+            id: id.to_string(),
+            status: "LIVE".to_string(),
+            market: "0xabc".to_string(),
+            original_size: dec!(10),
+            outcome: "Yes".to_string(),
+            maker_address: "0x0".to_string(),
+            owner: "owner".to_string(),
+            price: dec!(0.5),
+            side: Side::BUY,
+            size_matched: dec!(0),
+            asset_id: "123".to_string(),
+            expiration: 0,
+            order_type: OrderType::GTC,
+            created_at: 1,
+        }
+    }
+
+    #[test]
+    fn test_fills_update_positions_once() {
+        let store = StateStore::open_in_memory().unwrap();
+        assert!(store
+            .record_fill(&fill("t1", Side::BUY, dec!(10), dec!(0.4)))
+            .unwrap());
+        assert!(store
+            .record_fill(&fill("t2", Side::BUY, dec!(10), dec!(0.6)))
+            .unwrap());
+        // Replayed fill is ignored
+        assert!(!store
+            .record_fill(&fill("t2", Side::BUY, dec!(10), dec!(0.6)))
+            .unwrap());
+        assert!(store
+            .record_fill(&fill("t3", Side::SELL, dec!(5), dec!(0.7)))
+            .unwrap());
+
+        let position = store.position("123").unwrap().unwrap();
+        assert_eq!(position.size, dec!(15));
+        assert_eq!(position.average_price, dec!(0.5));
+        assert_eq!(store.fills().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_recover_after_reopen() {
+        let path = std::env::temp_dir().join(format!("polyfill-state-{}.db", uuid::Uuid::new_v4()));
+
+        {
+            let store = StateStore::open(&path).unwrap();
+            store.upsert_order(&open_order("o1")).unwrap();
+            store.upsert_order(&open_order("o2")).unwrap();
+            assert!(store.remove_order("o2").unwrap());
+            store
+                .record_fill(&fill("t1", Side::BUY, dec!(4), dec!(0.25)))
+                .unwrap();
+            store
+                .arm_trigger(&ArmedTrigger {
+                    id: "stop-1".to_string(),
+                    kind: "stop_loss".to_string(),
+                    token_id: "123".to_string(),
+                    params: serde_json::json!({"price": "0.2"}),
+                    armed_at: Utc::now(),
+                })
+                .unwrap();
+        }
+
+        let store = StateStore::open(&path).unwrap();
+        let state = store.recover().unwrap();
+        assert_eq!(state.open_orders.len(), 1);
+        assert_eq!(state.open_orders[0].id, "o1");
+        assert_eq!(state.fills.len(), 1);
+        assert_eq!(state.positions.len(), 1);
+        assert_eq!(state.positions[0].size, dec!(4));
+        assert_eq!(state.triggers.len(), 1);
+        assert_eq!(state.triggers[0].params["price"], "0.2");
+
+        assert!(store.disarm_trigger("stop-1").unwrap());
+        assert!(store.triggers().unwrap().is_empty());
+
+        drop(store);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[test]
+    fn test_attach_records_dispatched_fills() {
+        let store = Arc::new(StateStore::open_in_memory().unwrap());
+        let handlers = EventHandlers::new();
+        store.attach(&handlers);
+
+        handlers.dispatch(&crate::types::StreamMessage::Trade(fill(
+            "t1",
+            Side::BUY,
+            dec!(1),
+            dec!(0.5),
+        )));
+        assert_eq!(store.fills().unwrap().len(), 1);
+    }
+}
@@ -3,15 +3,19 @@
 //! This module provides high-performance streaming capabilities for
 //! real-time market data and order updates.
 
+use crate::diagnostics::DiagnosticsCapture;
 use crate::errors::{PolyfillError, Result};
 use crate::types::*;
 use crate::ws_hot_path::{WsBookApplyStats, WsBookUpdateProcessor};
+use crate::ws_transport::{BoxedTransport, TungsteniteConnector, WsConnector};
 use chrono::Utc;
 use futures::{ready, SinkExt, Stream, StreamExt};
 use parking_lot::Mutex;
 use serde_json::Value;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
@@ -32,15 +36,12 @@ pub trait MarketStream: Stream<Item = Result<StreamMessage>> + Send + Sync {
 }
 
 /// WebSocket-based market stream implementation
-#[derive(Debug)]
 #[allow(dead_code)]
 pub struct WebSocketStream {
     /// WebSocket connection
-    connection: Option<
-        tokio_tungstenite::WebSocketStream<
-            tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-        >,
-    >,
+    connection: Option<BoxedTransport>,
+    /// Opens the connection and every reconnect
+    connector: Arc<dyn WsConnector>,
     /// URL for the WebSocket connection
     url: String,
     /// Authentication credentials
@@ -57,6 +58,81 @@ pub struct WebSocketStream {
     stats: StreamStats,
     /// Reconnection configuration
     reconnect_config: ReconnectConfig,
+    /// Per-asset/market subscription state
+    subscription_status: Vec<SubscriptionStatus>,
+    /// Number of subscriptions still waiting for their first message
+    unconfirmed: usize,
+    /// Listeners notified when a subscription is confirmed or fails
+    subscription_listeners: Vec<mpsc::UnboundedSender<SubscriptionStatus>>,
+    /// Whether a dropped connection is restored instead of ending the stream
+    auto_reconnect: bool,
+    /// Reconnect attempt in progress
+    reconnecting: Option<Reconnecting>,
+    disconnect_handlers: Vec<DisconnectHandler>,
+    reconnect_handlers: Vec<ReconnectHandler>,
+    /// Dumps payloads that fail to parse
+    diagnostics: Option<DiagnosticsCapture>,
+}
+
+/// Callback invoked when a stream connection drops or a reconnect attempt fails
+pub type DisconnectHandler = Arc<dyn Fn(&DisconnectEvent) + Send + Sync>;
+
+/// Callback invoked when a dropped stream connection is restored
+pub type ReconnectHandler = Arc<dyn Fn(&ReconnectEvent) + Send + Sync>;
+
+/// A lost connection or failed reconnect attempt
+#[derive(Debug, Clone)]
+pub struct DisconnectEvent {
+    /// Reconnect attempts made so far in this outage (0 for the initial drop)
+    pub attempt: u32,
+    /// Why the connection dropped, or why the last attempt failed
+    pub cause: String,
+    /// Wait before the next attempt; `None` when the stream gives up
+    pub retry_in: Option<std::time::Duration>,
+}
+
+/// A dropped connection restored with its subscriptions resent
+#[derive(Debug, Clone)]
+pub struct ReconnectEvent {
+    /// Attempts it took to reconnect
+    pub attempts: u32,
+    /// Why the connection originally dropped
+    pub cause: String,
+    /// Time from the drop until the subscriptions were resent
+    pub downtime: std::time::Duration,
+}
+
+struct Reconnecting {
+    attempt: u32,
+    cause: String,
+    since: std::time::Instant,
+    // Boxed connect-and-resubscribe future; the mutex keeps the stream `Sync`
+    future: Mutex<Pin<Box<dyn Future<Output = Result<BoxedTransport>> + Send>>>,
+}
+
+impl std::fmt::Debug for Reconnecting {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Reconnecting")
+            .field("attempt", &self.attempt)
+            .field("cause", &self.cause)
+            .field("since", &self.since)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for WebSocketStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSocketStream")
+            .field("url", &self.url)
+            .field("connected", &self.connection.is_some())
+            .field("subscriptions", &self.subscriptions.len())
+            .field("pending", &self.pending.len())
+            .field("stats", &self.stats)
+            .field("reconnect_config", &self.reconnect_config)
+            .field("auto_reconnect", &self.auto_reconnect)
+            .field("reconnecting", &self.reconnecting)
+            .finish_non_exhaustive()
+    }
 }
 
 /// Stream statistics
@@ -69,6 +145,234 @@ pub struct StreamStats {
     pub last_message_time: Option<chrono::DateTime<Utc>>,
     pub connection_uptime: std::time::Duration,
     pub reconnect_count: u32,
+    /// Per-token traffic, tracked by [`WebSocketStream`]
+    pub tokens: TokenTrafficStats,
+}
+
+/// Key for traffic that could not be attributed to a token, e.g. a frame
+/// that failed to parse without a readable `asset_id`
+pub const UNATTRIBUTED_TOKEN: &str = "unknown";
+
+/// Message, byte and parse-failure counts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenTraffic {
+    pub messages: u64,
+    pub bytes: u64,
+    pub parse_failures: u64,
+}
+
+impl TokenTraffic {
+    fn add(&mut self, other: TokenTraffic) {
+        self.messages += other.messages;
+        self.bytes += other.bytes;
+        self.parse_failures += other.parse_failures;
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct TokenTrafficCounter {
+    total: TokenTraffic,
+    /// Traffic per second, oldest first
+    buckets: VecDeque<(i64, TokenTraffic)>,
+}
+
+/// Per-token WebSocket traffic, over the connection lifetime and a rolling
+/// window, to find the subscriptions that dominate load.
+///
+/// A message is attributed to every asset it carries, so a `price_change`
+/// for both outcomes of a market counts once for each; a frame's bytes are
+/// split evenly between them.
+#[derive(Debug, Clone)]
+pub struct TokenTrafficStats {
+    window: std::time::Duration,
+    tokens: HashMap<String, TokenTrafficCounter>,
+}
+
+impl Default for TokenTrafficStats {
+    fn default() -> Self {
+        Self::new(std::time::Duration::from_secs(60))
+    }
+}
+
+impl TokenTrafficStats {
+    /// Track traffic over a trailing `window`, at one-second resolution
+    pub fn new(window: std::time::Duration) -> Self {
+        Self {
+            window,
+            tokens: HashMap::new(),
+        }
+    }
+
+    pub fn window(&self) -> std::time::Duration {
+        self.window
+    }
+
+    /// Traffic for `token_id` since the stream was created
+    pub fn total(&self, token_id: &str) -> Option<TokenTraffic> {
+        self.tokens.get(token_id).map(|counter| counter.total)
+    }
+
+    /// Traffic for `token_id` within the window
+    pub fn recent(&self, token_id: &str) -> Option<TokenTraffic> {
+        self.recent_at(token_id, Utc::now().timestamp())
+    }
+
+    /// Tokens by bytes received within the window, busiest first
+    pub fn busiest(&self, limit: usize) -> Vec<(String, TokenTraffic)> {
+        let now = Utc::now().timestamp();
+        let mut tokens: Vec<_> = self
+            .tokens
+            .keys()
+            .filter_map(|id| Some((id.clone(), self.recent_at(id, now)?)))
+            .collect();
+        tokens.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(&b.0)));
+        tokens.truncate(limit);
+        tokens
+    }
+
+    /// Tokens seen so far
+    pub fn tokens(&self) -> impl Iterator<Item = &str> {
+        self.tokens.keys().map(String::as_str)
+    }
+
+    fn recent_at(&self, token_id: &str, now: i64) -> Option<TokenTraffic> {
+        let cutoff = now - self.window.as_secs() as i64;
+        let counter = self.tokens.get(token_id)?;
+        let mut recent = TokenTraffic::default();
+        for (_, traffic) in counter.buckets.iter().filter(|(sec, _)| *sec > cutoff) {
+            recent.add(*traffic);
+        }
+        Some(recent)
+    }
+
+    fn record_at(&mut self, token_id: &str, traffic: TokenTraffic, now: i64) {
+        let counter = match self.tokens.get_mut(token_id) {
+            Some(counter) => counter,
+            None => self.tokens.entry(token_id.to_string()).or_default(),
+        };
+        counter.total.add(traffic);
+        match counter.buckets.back_mut() {
+            Some((sec, bucket)) if *sec == now => bucket.add(traffic),
+            _ => counter.buckets.push_back((now, traffic)),
+        }
+        let cutoff = now - self.window.as_secs() as i64;
+        while counter
+            .buckets
+            .front()
+            .is_some_and(|(sec, _)| *sec <= cutoff)
+        {
+            counter.buckets.pop_front();
+        }
+    }
+
+    /// Attribute a parsed text frame of `bytes` to the assets in `messages`
+    fn record_frame(&mut self, messages: &[StreamMessage], bytes: usize) {
+        let now = Utc::now().timestamp();
+        let mut assets: Vec<&str> = Vec::new();
+        for message in messages {
+            message_assets(message, &mut assets);
+        }
+        if assets.is_empty() {
+            assets.push(UNATTRIBUTED_TOKEN);
+        }
+        let share = bytes as u64 / assets.len() as u64;
+        let mut remainder = bytes as u64 % assets.len() as u64;
+        for asset in assets {
+            let extra = remainder.min(1);
+            remainder -= extra;
+            let traffic = TokenTraffic {
+                messages: 1,
+                bytes: share + extra,
+                parse_failures: 0,
+            };
+            self.record_at(asset, traffic, now);
+        }
+    }
+
+    /// Count a frame that failed to parse against the asset it names, if any
+    fn record_parse_failure(&mut self, text: &str) {
+        let asset = serde_json::from_str::<Value>(text).ok().and_then(|value| {
+            let object = match &value {
+                Value::Array(items) => items.first()?,
+                other => other,
+            };
+            object.get("asset_id")?.as_str().map(str::to_owned)
+        });
+        let traffic = TokenTraffic {
+            messages: 1,
+            bytes: text.len() as u64,
+            parse_failures: 1,
+        };
+        self.record_at(
+            asset.as_deref().unwrap_or(UNATTRIBUTED_TOKEN),
+            traffic,
+            Utc::now().timestamp(),
+        );
+    }
+}
+
+/// Push the asset IDs `message` carries, each once
+fn message_assets<'a>(message: &'a StreamMessage, assets: &mut Vec<&'a str>) {
+    let start = assets.len();
+    let mut push = |asset: &'a str| {
+        if !assets[start..].contains(&asset) {
+            assets.push(asset);
+        }
+    };
+    match message {
+        StreamMessage::Book(book) => push(&book.asset_id),
+        StreamMessage::PriceChange(change) => {
+            for entry in &change.price_changes {
+                push(&entry.asset_id);
+            }
+        },
+        StreamMessage::TickSizeChange(change) => push(&change.asset_id),
+        StreamMessage::LastTradePrice(trade) => push(&trade.asset_id),
+        StreamMessage::BestBidAsk(best) => push(&best.asset_id),
+        StreamMessage::NewMarket(market) => {
+            for asset in &market.asset_ids {
+                push(asset);
+            }
+        },
+        StreamMessage::Trade(trade) => push(&trade.asset_id),
+        StreamMessage::Order(order) => push(&order.asset_id),
+        StreamMessage::MarketResolved(_) | StreamMessage::Unknown => {},
+    }
+}
+
+/// Lifecycle state of a channel subscription
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionState {
+    /// Subscribe message sent, no data received yet
+    Requested,
+    /// First snapshot (market channel) or event (user channel) received
+    Confirmed,
+    /// Rejected by the server or lost before it was confirmed
+    Failed(String),
+}
+
+/// Tracked status of a single asset (market channel) or market (user channel)
+#[derive(Debug, Clone)]
+pub struct SubscriptionStatus {
+    pub channel: WssChannelType,
+    /// Asset ID for the market channel, market ID for the user channel
+    /// (`"*"` when subscribed to all markets)
+    pub id: String,
+    pub state: SubscriptionState,
+    pub requested_at: chrono::DateTime<Utc>,
+    pub updated_at: chrono::DateTime<Utc>,
+}
+
+impl SubscriptionStatus {
+    /// Check if the subscription has delivered data
+    pub fn is_confirmed(&self) -> bool {
+        self.state == SubscriptionState::Confirmed
+    }
+
+    /// Check if the subscription was rejected or lost
+    pub fn is_failed(&self) -> bool {
+        matches!(self.state, SubscriptionState::Failed(_))
+    }
 }
 
 /// Reconnection configuration
@@ -91,6 +395,140 @@ impl Default for ReconnectConfig {
     }
 }
 
+impl ReconnectConfig {
+    /// Delay before reconnect attempt `attempt` (0-based)
+    pub fn delay_for(&self, attempt: u32) -> std::time::Duration {
+        let factor = self
+            .backoff_multiplier
+            .powi(attempt.min(i32::MAX as u32) as i32);
+        std::time::Duration::try_from_secs_f64(self.base_delay.as_secs_f64() * factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+/// Path of the public market channel (order book, prices, trades)
+pub const WS_MARKET_PATH: &str = "/ws/market";
+
+/// Path of the authenticated user channel (orders and trades)
+pub const WS_USER_PATH: &str = "/ws/user";
+
+/// Builder for Polymarket WebSocket endpoint URLs.
+///
+/// The market and user channels are served from different paths on the same
+/// host, so a connection opened against the wrong one is rejected with a 404.
+/// `WsEndpoint` normalizes a base host (optionally already carrying `/ws`,
+/// `/ws/market` or `/ws/user`) and derives the URL for a given channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WsEndpoint {
+    base: String,
+}
+
+impl Default for WsEndpoint {
+    fn default() -> Self {
+        Self::new(crate::DEFAULT_WS_BASE_URL)
+    }
+}
+
+impl WsEndpoint {
+    /// Create an endpoint builder from a base host URL
+    pub fn new(base: &str) -> Self {
+        let mut base = base.trim_end_matches('/');
+        for suffix in [WS_MARKET_PATH, WS_USER_PATH, "/ws"] {
+            if let Some(stripped) = base.strip_suffix(suffix) {
+                // Don't eat into the scheme for hosts like `wss://ws`
+                if stripped.find("://").is_some_and(|i| stripped.len() > i + 3) {
+                    base = stripped;
+                }
+                break;
+            }
+        }
+
+        Self {
+            base: base.to_string(),
+        }
+    }
+
+    /// Base host URL without any channel path
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// Full URL for the given channel
+    pub fn url(&self, channel: WssChannelType) -> String {
+        let path = match channel {
+            WssChannelType::Market => WS_MARKET_PATH,
+            WssChannelType::User => WS_USER_PATH,
+        };
+        format!("{}{}", self.base, path)
+    }
+
+    /// Full URL for the market channel
+    pub fn market_url(&self) -> String {
+        self.url(WssChannelType::Market)
+    }
+
+    /// Full URL for the user channel
+    pub fn user_url(&self) -> String {
+        self.url(WssChannelType::User)
+    }
+
+    /// Returns `true` if `url` points at a host root or one of the known
+    /// channel paths, i.e. it is safe to re-route to another channel path.
+    fn is_routable(url: &str) -> bool {
+        let Ok(parsed) = ::url::Url::parse(url) else {
+            return false;
+        };
+        matches!(
+            parsed.path().trim_end_matches('/'),
+            "" | "/ws" | WS_MARKET_PATH | WS_USER_PATH
+        )
+    }
+}
+
+impl WebSocketStream {
+    /// Create a stream for a specific channel on the given base host
+    pub fn for_channel(base: &str, channel: WssChannelType) -> Self {
+        Self::new(&WsEndpoint::new(base).url(channel))
+    }
+
+    /// URL this stream connects to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Point the stream at the correct path for `channel` before connecting.
+    ///
+    /// URLs with a custom path (e.g. a proxy) are left untouched. Once a
+    /// connection is open it cannot be moved to another channel.
+    fn route_to_channel(&mut self, channel: WssChannelType) -> Result<()> {
+        if !WsEndpoint::is_routable(&self.url) {
+            return Ok(());
+        }
+
+        let target = WsEndpoint::new(&self.url).url(channel);
+        if target == self.url {
+            return Ok(());
+        }
+
+        if self.connection.is_some() {
+            return Err(PolyfillError::stream(
+                format!(
+                    "Stream is connected to {}; {} channel requires a separate stream at {}",
+                    self.url,
+                    channel.as_str().to_lowercase(),
+                    target
+                ),
+                crate::errors::StreamErrorKind::SubscriptionFailed,
+            ));
+        }
+
+        debug!("Routing WebSocket stream from {} to {}", self.url, target);
+        self.url = target;
+        Ok(())
+    }
+}
+
 impl WebSocketStream {
     /// Create a new WebSocket stream
     pub fn new(url: &str) -> Self {
@@ -98,6 +536,7 @@ impl WebSocketStream {
 
         Self {
             connection: None,
+            connector: Arc::new(TungsteniteConnector::default()),
             url: url.to_string(),
             auth: None,
             subscriptions: Vec::new(),
@@ -111,8 +550,17 @@ impl WebSocketStream {
                 last_message_time: None,
                 connection_uptime: std::time::Duration::ZERO,
                 reconnect_count: 0,
+                tokens: TokenTrafficStats::default(),
             },
             reconnect_config: ReconnectConfig::default(),
+            subscription_status: Vec::new(),
+            unconfirmed: 0,
+            subscription_listeners: Vec::new(),
+            auto_reconnect: false,
+            reconnecting: None,
+            disconnect_handlers: Vec::new(),
+            reconnect_handlers: Vec::new(),
+            diagnostics: None,
         }
     }
 
@@ -130,18 +578,51 @@ impl WebSocketStream {
         self
     }
 
+    /// Open connections through `connector` instead of the default
+    /// [`TungsteniteConnector`]
+    pub fn with_connector(mut self, connector: impl WsConnector + 'static) -> Self {
+        self.connector = Arc::new(connector);
+        self
+    }
+
+    /// Write a dump with recent context for every payload that fails to parse
+    pub fn with_diagnostics(mut self, diagnostics: DiagnosticsCapture) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// Reconnect and resubscribe with `config` when the connection drops.
+    ///
+    /// Without this the stream ends when the server closes the connection.
+    pub fn with_reconnect_config(mut self, config: ReconnectConfig) -> Self {
+        self.reconnect_config = config;
+        self.auto_reconnect = true;
+        self
+    }
+
+    pub fn reconnect_config(&self) -> &ReconnectConfig {
+        &self.reconnect_config
+    }
+
+    /// Register a callback for dropped connections and failed reconnect attempts
+    pub fn on_disconnect<F>(&mut self, handler: F)
+    where
+        F: Fn(&DisconnectEvent) + Send + Sync + 'static,
+    {
+        self.disconnect_handlers.push(Arc::new(handler));
+    }
+
+    /// Register a callback for restored connections
+    pub fn on_reconnect<F>(&mut self, handler: F)
+    where
+        F: Fn(&ReconnectEvent) + Send + Sync + 'static,
+    {
+        self.reconnect_handlers.push(Arc::new(handler));
+    }
+
     /// Connect to the WebSocket
     async fn connect(&mut self) -> Result<()> {
-        let (ws_stream, _) = tokio_tungstenite::connect_async(&self.url)
-            .await
-            .map_err(|e| {
-                PolyfillError::stream(
-                    format!("WebSocket connection failed: {}", e),
-                    crate::errors::StreamErrorKind::ConnectionFailed,
-                )
-            })?;
-
-        self.connection = Some(ws_stream);
+        self.connection = Some(self.connector.connect(&self.url).await?);
         info!("Connected to WebSocket stream at {}", self.url);
         Ok(())
     }
@@ -169,6 +650,25 @@ impl WebSocketStream {
 
     /// Subscribe to market data using official Polymarket WebSocket API
     pub async fn subscribe_async(&mut self, subscription: WssSubscription) -> Result<()> {
+        if let Err(e) = self.send_subscription(&subscription).await {
+            if subscription.operation.as_deref() != Some("unsubscribe") {
+                self.record_subscription(&subscription, SubscriptionState::Failed(e.to_string()));
+            }
+            return Err(e);
+        }
+
+        if subscription.operation.as_deref() == Some("unsubscribe") {
+            self.forget_subscription(&subscription);
+        } else {
+            self.record_subscription(&subscription, SubscriptionState::Requested);
+        }
+        self.subscriptions.push(subscription.clone());
+
+        info!("Subscribed to {} channel", subscription.channel_type);
+        Ok(())
+    }
+
+    async fn send_subscription(&mut self, subscription: &WssSubscription) -> Result<()> {
         // Ensure connection
         if self.connection.is_none() {
             self.connect().await?;
@@ -176,15 +676,232 @@ impl WebSocketStream {
 
         // Send subscription message in the format expected by Polymarket
         // The subscription struct will serialize correctly with proper field names
-        let message = serde_json::to_value(&subscription).map_err(|e| {
+        let message = serde_json::to_value(subscription).map_err(|e| {
             PolyfillError::parse(format!("Failed to serialize subscription: {}", e), None)
         })?;
 
-        self.send_message(message).await?;
-        self.subscriptions.push(subscription.clone());
+        self.send_message(message).await
+    }
 
-        info!("Subscribed to {} channel", subscription.channel_type);
-        Ok(())
+    /// Current per-asset/market subscription status, in request order.
+    ///
+    /// A subscription stays [`SubscriptionState::Requested`] until the first
+    /// `book` snapshot (market channel) or order/trade event (user channel)
+    /// for it is received, which distinguishes "no data yet" from a silent
+    /// rejection. Use [`Self::fail_unconfirmed_older_than`] to give up on
+    /// subscriptions that never confirm.
+    pub fn subscriptions(&self) -> &[SubscriptionStatus] {
+        &self.subscription_status
+    }
+
+    /// Receive subscription confirmations and failures as they happen
+    pub fn subscription_events(&mut self) -> mpsc::UnboundedReceiver<SubscriptionStatus> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscription_listeners.push(tx);
+        rx
+    }
+
+    /// Mark subscriptions that have not confirmed within `timeout` as failed.
+    ///
+    /// Returns the number of subscriptions that were failed.
+    pub fn fail_unconfirmed_older_than(&mut self, timeout: std::time::Duration) -> usize {
+        let Ok(timeout) = chrono::Duration::from_std(timeout) else {
+            return 0;
+        };
+        let cutoff = Utc::now() - timeout;
+        let reason = format!(
+            "No data received within {}ms of subscribing",
+            timeout.num_milliseconds()
+        );
+        self.fail_unconfirmed_where(&reason, |status| status.requested_at <= cutoff)
+    }
+
+    fn subscription_channel(subscription: &WssSubscription) -> (WssChannelType, Vec<String>) {
+        if subscription.channel_type.eq_ignore_ascii_case("user") {
+            let ids = if subscription.markets.is_empty() {
+                vec!["*".to_string()]
+            } else {
+                subscription.markets.clone()
+            };
+            (WssChannelType::User, ids)
+        } else {
+            (WssChannelType::Market, subscription.asset_ids.clone())
+        }
+    }
+
+    fn record_subscription(&mut self, subscription: &WssSubscription, state: SubscriptionState) {
+        let (channel, ids) = Self::subscription_channel(subscription);
+        let now = Utc::now();
+
+        for id in ids {
+            let existing = self
+                .subscription_status
+                .iter()
+                .position(|s| s.channel == channel && s.id == id);
+            let status = match existing {
+                Some(index) => {
+                    let status = &mut self.subscription_status[index];
+                    status.state = state.clone();
+                    status.requested_at = now;
+                    status.updated_at = now;
+                    status.clone()
+                },
+                None => {
+                    let status = SubscriptionStatus {
+                        channel,
+                        id,
+                        state: state.clone(),
+                        requested_at: now,
+                        updated_at: now,
+                    };
+                    self.subscription_status.push(status.clone());
+                    status
+                },
+            };
+
+            if let SubscriptionState::Failed(reason) = &status.state {
+                warn!(
+                    "Subscription to {} {} failed: {}",
+                    channel.as_str(),
+                    status.id,
+                    reason
+                );
+                self.notify_subscription(status);
+            }
+        }
+
+        self.recount_unconfirmed();
+    }
+
+    fn forget_subscription(&mut self, subscription: &WssSubscription) {
+        let (channel, ids) = Self::subscription_channel(subscription);
+        self.subscription_status
+            .retain(|s| s.channel != channel || !ids.contains(&s.id));
+        self.recount_unconfirmed();
+    }
+
+    fn recount_unconfirmed(&mut self) {
+        self.unconfirmed = self
+            .subscription_status
+            .iter()
+            .filter(|s| s.state == SubscriptionState::Requested)
+            .count();
+    }
+
+    fn notify_subscription(&mut self, status: SubscriptionStatus) {
+        self.subscription_listeners
+            .retain(|tx| tx.send(status.clone()).is_ok());
+    }
+
+    /// Confirm pending subscriptions covered by an incoming message
+    fn observe_subscription_message(&mut self, message: &StreamMessage) {
+        if self.unconfirmed == 0 {
+            return;
+        }
+
+        let (channel, id) = match message {
+            StreamMessage::Book(book) => (WssChannelType::Market, book.asset_id.as_str()),
+            StreamMessage::Trade(trade) => (WssChannelType::User, trade.market.as_str()),
+            StreamMessage::Order(order) => (WssChannelType::User, order.market.as_str()),
+            _ => return,
+        };
+
+        let now = Utc::now();
+        let mut confirmed = Vec::new();
+        for status in &mut self.subscription_status {
+            let matches = status.channel == channel
+                && (status.id == id || (channel == WssChannelType::User && status.id == "*"));
+            if matches && status.state == SubscriptionState::Requested {
+                status.state = SubscriptionState::Confirmed;
+                status.updated_at = now;
+                confirmed.push(status.clone());
+            }
+        }
+
+        for status in confirmed {
+            debug!(
+                "Subscription to {} {} confirmed",
+                status.channel.as_str(),
+                status.id
+            );
+            self.notify_subscription(status);
+        }
+        self.recount_unconfirmed();
+    }
+
+    /// Fail every subscription still waiting for confirmation
+    fn fail_unconfirmed(&mut self, reason: &str) -> usize {
+        self.fail_unconfirmed_where(reason, |_| true)
+    }
+
+    fn fail_unconfirmed_where(
+        &mut self,
+        reason: &str,
+        predicate: impl Fn(&SubscriptionStatus) -> bool,
+    ) -> usize {
+        if self.unconfirmed == 0 {
+            return 0;
+        }
+
+        let now = Utc::now();
+        let mut failed = Vec::new();
+        for status in &mut self.subscription_status {
+            if status.state == SubscriptionState::Requested && predicate(status) {
+                status.state = SubscriptionState::Failed(reason.to_string());
+                status.updated_at = now;
+                failed.push(status.clone());
+            }
+        }
+
+        let count = failed.len();
+        for status in failed {
+            warn!(
+                "Subscription to {} {} failed: {}",
+                status.channel.as_str(),
+                status.id,
+                reason
+            );
+            self.notify_subscription(status);
+        }
+        self.recount_unconfirmed();
+        count
+    }
+
+    /// Count a decoded text frame and keep it for parse failure dumps
+    fn record_parsed_text(&mut self, text: &str, messages: &[StreamMessage]) {
+        self.stats.tokens.record_frame(messages, text.len());
+        if let Some(diagnostics) = &mut self.diagnostics {
+            diagnostics.record_message(text);
+        }
+    }
+
+    /// Dump a frame that failed to parse, with the current subscriptions
+    fn capture_parse_failure(&mut self, text: &str, error: &PolyfillError) {
+        let Some(diagnostics) = &mut self.diagnostics else {
+            return;
+        };
+        let subscriptions = self
+            .subscription_status
+            .iter()
+            .map(|status| format!("{}:{}", status.channel.as_str(), status.id))
+            .collect();
+        match diagnostics.capture(text, &error.to_string(), subscriptions) {
+            Ok(path) => warn!("Wrote parse failure dump to {}", path.display()),
+            Err(e) => warn!("Failed to write parse failure dump: {}", e),
+        }
+    }
+
+    /// Handle a text frame that could not be decoded as a stream event.
+    ///
+    /// The server answers bad subscriptions with a plain-text frame (e.g.
+    /// `INVALID OPERATION`) rather than JSON; treat that as a rejection of
+    /// whatever is still pending.
+    fn handle_undecodable_text(&mut self, text: &str) {
+        self.stats.tokens.record_parse_failure(text);
+        let trimmed = text.trim();
+        if !trimmed.starts_with('{') && !trimmed.starts_with('[') {
+            self.fail_unconfirmed(trimmed);
+        }
     }
 
     /// Subscribe to user channel (orders and trades)
@@ -205,6 +922,7 @@ impl WebSocketStream {
             auth: Some(auth),
         };
 
+        self.route_to_channel(WssChannelType::User)?;
         self.subscribe_async(subscription).await
     }
 
@@ -221,6 +939,7 @@ impl WebSocketStream {
             auth: None,
         };
 
+        self.route_to_channel(WssChannelType::Market)?;
         self.subscribe_async(subscription).await
     }
 
@@ -240,6 +959,7 @@ impl WebSocketStream {
             auth: None,
         };
 
+        self.route_to_channel(WssChannelType::Market)?;
         self.subscribe_async(subscription).await
     }
 
@@ -255,6 +975,7 @@ impl WebSocketStream {
             auth: None,
         };
 
+        self.route_to_channel(WssChannelType::Market)?;
         self.subscribe_async(subscription).await
     }
 
@@ -276,6 +997,7 @@ impl WebSocketStream {
             auth: Some(auth),
         };
 
+        self.route_to_channel(WssChannelType::User)?;
         self.subscribe_async(subscription).await
     }
 
@@ -290,8 +1012,17 @@ impl WebSocketStream {
                 debug!("Received WebSocket message: {}", text);
 
                 // Parse the message according to Polymarket's `event_type` format
-                let stream_messages = crate::decode::parse_stream_messages(&text)?;
+                let stream_messages = match crate::decode::parse_stream_messages(&text) {
+                    Ok(messages) => messages,
+                    Err(e) => {
+                        self.capture_parse_failure(&text, &e);
+                        self.handle_undecodable_text(&text);
+                        return Err(e);
+                    },
+                };
+                self.record_parsed_text(&text, &stream_messages);
                 for stream_message in stream_messages {
+                    self.observe_subscription_message(&stream_message);
                     self.enqueue(stream_message);
                 }
 
@@ -301,6 +1032,7 @@ impl WebSocketStream {
             tokio_tungstenite::tungstenite::Message::Close(_) => {
                 info!("WebSocket connection closed by server");
                 self.connection = None;
+                self.fail_unconfirmed("Connection closed before subscription was confirmed");
             },
             tokio_tungstenite::tungstenite::Message::Ping(data) => {
                 // Respond with pong
@@ -326,64 +1058,153 @@ impl WebSocketStream {
         Ok(())
     }
 
-    /// Parse Polymarket WebSocket message(s) in `event_type` format.
-    #[allow(dead_code)]
-    fn parse_polymarket_messages(&self, text: &str) -> Result<Vec<StreamMessage>> {
-        crate::decode::parse_stream_messages(text)
+    /// Handle a lost connection: start reconnecting or end the stream.
+    ///
+    /// Returns `false` when the stream should end.
+    fn connection_lost(&mut self, cause: &str) -> bool {
+        self.connection = None;
+        if !self.auto_reconnect || self.subscriptions.is_empty() {
+            self.notify_disconnect(DisconnectEvent {
+                attempt: 0,
+                cause: cause.to_string(),
+                retry_in: None,
+            });
+            self.fail_unconfirmed("Connection closed before subscription was confirmed");
+            return false;
+        }
+        self.schedule_reconnect(0, cause.to_string(), std::time::Instant::now(), cause)
     }
 
-    /// Reconnect with exponential backoff
-    #[allow(dead_code)]
-    async fn reconnect(&mut self) -> Result<()> {
-        let mut delay = self.reconnect_config.base_delay;
-        let mut retries = 0;
-
-        while retries < self.reconnect_config.max_retries {
-            warn!("Attempting to reconnect (attempt {})", retries + 1);
-
-            match self.connect().await {
-                Ok(()) => {
-                    info!("Successfully reconnected");
-                    self.stats.reconnect_count += 1;
-
-                    // Resubscribe to all previous subscriptions
-                    let subscriptions = self.subscriptions.clone();
-                    for subscription in subscriptions {
-                        self.send_message(serde_json::to_value(subscription)?)
-                            .await?;
-                    }
+    /// Schedule the next attempt after `attempts` have been made.
+    ///
+    /// Returns `false` once the retry budget is exhausted.
+    fn schedule_reconnect(
+        &mut self,
+        attempts: u32,
+        cause: String,
+        since: std::time::Instant,
+        last_error: &str,
+    ) -> bool {
+        let retry_in = (attempts < self.reconnect_config.max_retries)
+            .then(|| self.reconnect_config.delay_for(attempts));
+        self.notify_disconnect(DisconnectEvent {
+            attempt: attempts,
+            cause: last_error.to_string(),
+            retry_in,
+        });
+        let Some(delay) = retry_in else {
+            error!(
+                "Giving up on {} after {} reconnect attempts",
+                self.url, attempts
+            );
+            self.fail_unconfirmed("Connection lost before subscription was confirmed");
+            return false;
+        };
 
-                    return Ok(());
-                },
-                Err(e) => {
-                    error!("Reconnection attempt {} failed: {}", retries + 1, e);
-                    retries += 1;
-
-                    if retries < self.reconnect_config.max_retries {
-                        tokio::time::sleep(delay).await;
-                        delay = std::cmp::min(
-                            delay.mul_f64(self.reconnect_config.backoff_multiplier),
-                            self.reconnect_config.max_delay,
-                        );
-                    }
-                },
+        warn!(
+            "Connection to {} lost ({}); reconnect attempt {} in {:?}",
+            self.url,
+            last_error,
+            attempts + 1,
+            delay
+        );
+        let url = self.url.clone();
+        let connector = Arc::clone(&self.connector);
+        let subscriptions = self.subscriptions.clone();
+        let future = async move {
+            tokio::time::sleep(delay).await;
+            let mut connection = connector.connect(&url).await?;
+            for subscription in &subscriptions {
+                let text = serde_json::to_string(subscription)?;
+                connection
+                    .send(tokio_tungstenite::tungstenite::Message::Text(text))
+                    .await
+                    .map_err(|e| {
+                        PolyfillError::stream(
+                            format!("Failed to resubscribe: {}", e),
+                            crate::errors::StreamErrorKind::SubscriptionFailed,
+                        )
+                    })?;
             }
+            Ok(connection)
+        };
+        self.reconnecting = Some(Reconnecting {
+            attempt: attempts + 1,
+            cause,
+            since,
+            future: Mutex::new(Box::pin(future)),
+        });
+        true
+    }
+
+    /// Drive an in-progress reconnect.
+    ///
+    /// Yields an error when the retry budget is exhausted.
+    fn poll_reconnect(&mut self, cx: &mut Context<'_>) -> Poll<Option<PolyfillError>> {
+        let Some(reconnecting) = self.reconnecting.as_mut() else {
+            return Poll::Ready(None);
+        };
+        let result = ready!(reconnecting.future.get_mut().as_mut().poll(cx));
+        let Some(reconnecting) = self.reconnecting.take() else {
+            return Poll::Ready(None);
+        };
+
+        match result {
+            Ok(connection) => {
+                self.connection = Some(connection);
+                self.stats.reconnect_count += 1;
+                for subscription in self.subscriptions.clone() {
+                    if subscription.operation.as_deref() == Some("unsubscribe") {
+                        self.forget_subscription(&subscription);
+                    } else {
+                        self.record_subscription(&subscription, SubscriptionState::Requested);
+                    }
+                }
+                info!(
+                    "Reconnected to {} after {} attempts",
+                    self.url, reconnecting.attempt
+                );
+                let event = ReconnectEvent {
+                    attempts: reconnecting.attempt,
+                    cause: reconnecting.cause,
+                    downtime: reconnecting.since.elapsed(),
+                };
+                for handler in &self.reconnect_handlers {
+                    handler(&event);
+                }
+                Poll::Ready(None)
+            },
+            Err(e) => {
+                let error = e.to_string();
+                if self.schedule_reconnect(
+                    reconnecting.attempt,
+                    reconnecting.cause.clone(),
+                    reconnecting.since,
+                    &error,
+                ) {
+                    // Register the new attempt's timer with the waker
+                    return self.poll_reconnect(cx);
+                }
+                Poll::Ready(Some(PolyfillError::stream(
+                    format!(
+                        "Failed to reconnect after {} attempts: {}",
+                        reconnecting.attempt, reconnecting.cause
+                    ),
+                    crate::errors::StreamErrorKind::ConnectionFailed,
+                )))
+            },
         }
+    }
 
-        Err(PolyfillError::stream(
-            format!(
-                "Failed to reconnect after {} attempts",
-                self.reconnect_config.max_retries
-            ),
-            crate::errors::StreamErrorKind::ConnectionFailed,
-        ))
+    fn notify_disconnect(&self, event: DisconnectEvent) {
+        for handler in &self.disconnect_handlers {
+            handler(&event);
+        }
     }
 }
 
 fn poll_send_pong(
-    connection: &mut tokio_tungstenite::WebSocketStream<
-        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
-    >,
+    connection: &mut BoxedTransport,
     cx: &mut Context<'_>,
     data: Vec<u8>,
 ) -> Poll<Result<()>> {
@@ -427,6 +1248,8 @@ impl WebSocketStream {
     ///
     /// The caller is expected to "warm up" the [`crate::book::OrderBookManager`] by creating books for all
     /// subscribed asset IDs ahead of time. Missing books are treated as an error.
+    /// [`crate::book::OrderBookManager::begin_warmup`] creates them and holds updates back until
+    /// each book is seeded from REST.
     pub fn into_book_applier<'a>(
         mut self,
         books: &'a crate::book::OrderBookManager,
@@ -551,6 +1374,13 @@ impl Stream for WebSocketStream {
                 return Poll::Ready(Some(Ok(message)));
             }
 
+            if self.reconnecting.is_some() {
+                if let Some(e) = ready!(self.poll_reconnect(cx)) {
+                    return Poll::Ready(Some(Err(e)));
+                }
+                continue;
+            }
+
             let Some(connection) = &mut self.connection else {
                 return Poll::Ready(None);
             };
@@ -561,6 +1391,10 @@ impl Stream for WebSocketStream {
                     tokio_tungstenite::tungstenite::Message::Text(text) => {
                         match crate::decode::parse_stream_messages(&text) {
                             Ok(messages) => {
+                                self.record_parsed_text(&text, &messages);
+                                for msg in &messages {
+                                    self.observe_subscription_message(msg);
+                                }
                                 let mut iter = messages.into_iter();
                                 let Some(first) = iter.next() else {
                                     continue;
@@ -574,6 +1408,8 @@ impl Stream for WebSocketStream {
                                 return Poll::Ready(Some(Ok(first)));
                             },
                             Err(e) => {
+                                self.capture_parse_failure(&text, &e);
+                                self.handle_undecodable_text(&text);
                                 self.stats.errors += 1;
                                 return Poll::Ready(Some(Err(e)));
                             },
@@ -581,7 +1417,9 @@ impl Stream for WebSocketStream {
                     },
                     tokio_tungstenite::tungstenite::Message::Close(_) => {
                         info!("WebSocket connection closed by server");
-                        self.connection = None;
+                        if self.connection_lost("Connection closed by server") {
+                            continue;
+                        }
                         return Poll::Ready(None);
                     },
                     tokio_tungstenite::tungstenite::Message::Ping(data) => {
@@ -605,6 +1443,9 @@ impl Stream for WebSocketStream {
                 },
                 Poll::Ready(None) => {
                     info!("WebSocket stream ended");
+                    if self.connection_lost("Connection ended") {
+                        continue;
+                    }
                     return Poll::Ready(None);
                 },
             }
@@ -632,12 +1473,36 @@ impl MarketStream for WebSocketStream {
     }
 }
 
-/// Mock stream for testing
+/// One scripted step of a [`MockStream`]
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum MockEvent {
+    /// Deliver a message (dropped if the stream is disconnected)
+    Message(StreamMessage),
+    /// Deliver an error without changing the connection state
+    Error(PolyfillError),
+    /// Wait before processing the next step
+    Delay(std::time::Duration),
+    /// Drop the connection, yielding a `ConnectionLost` stream error
+    Disconnect,
+    /// Restore the connection and count a reconnect
+    Reconnect,
+}
+
+/// Mock stream for testing.
+///
+/// Plays back a script of [`MockEvent`]s: messages, errors, delays (driven by
+/// tokio's timer, so `start_paused` tests run instantly and deterministically),
+/// and injected disconnects/reconnects. Messages scripted while disconnected
+/// are dropped, which lets consumers exercise gap detection and resync.
 #[derive(Debug)]
 pub struct MockStream {
-    messages: Vec<Result<StreamMessage>>,
+    events: Vec<MockEvent>,
     index: usize,
     connected: bool,
+    delay: Option<Pin<Box<tokio::time::Sleep>>>,
+    dropped: u64,
+    reconnects: u32,
 }
 
 impl Default for MockStream {
@@ -649,35 +1514,121 @@ impl Default for MockStream {
 impl MockStream {
     pub fn new() -> Self {
         Self {
-            messages: Vec::new(),
+            events: Vec::new(),
             index: 0,
             connected: true,
+            delay: None,
+            dropped: 0,
+            reconnects: 0,
+        }
+    }
+
+    /// Build a stream from a full script
+    pub fn from_script(events: impl IntoIterator<Item = MockEvent>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+            ..Self::new()
         }
     }
 
+    pub fn push(&mut self, event: MockEvent) {
+        self.events.push(event);
+    }
+
     pub fn add_message(&mut self, message: StreamMessage) {
-        self.messages.push(Ok(message));
+        self.push(MockEvent::Message(message));
     }
 
     pub fn add_error(&mut self, error: PolyfillError) {
-        self.messages.push(Err(error));
+        self.push(MockEvent::Error(error));
+    }
+
+    /// Pause for `delay` before the next scripted step
+    pub fn add_delay(&mut self, delay: std::time::Duration) {
+        self.push(MockEvent::Delay(delay));
+    }
+
+    pub fn add_disconnect(&mut self) {
+        self.push(MockEvent::Disconnect);
+    }
+
+    pub fn add_reconnect(&mut self) {
+        self.push(MockEvent::Reconnect);
+    }
+
+    /// Deterministically permute the scripted messages among their own slots,
+    /// leaving delays, errors and connection events in place
+    pub fn shuffle_messages(&mut self, seed: u64) {
+        use rand::seq::SliceRandom;
+
+        let slots: Vec<usize> = self
+            .events
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| matches!(e, MockEvent::Message(_)))
+            .map(|(i, _)| i)
+            .collect();
+        let mut order = slots.clone();
+        order.shuffle(&mut crate::sim::SimRng::from_seed(seed));
+
+        let original = self.events.clone();
+        for (&slot, &from) in slots.iter().zip(&order) {
+            self.events[slot] = original[from].clone();
+        }
     }
 
     pub fn set_connected(&mut self, connected: bool) {
         self.connected = connected;
     }
+
+    /// Whether every scripted step has been played
+    pub fn is_finished(&self) -> bool {
+        self.index >= self.events.len() && self.delay.is_none()
+    }
 }
 
 impl Stream for MockStream {
     type Item = Result<StreamMessage>;
 
-    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        if self.index >= self.messages.len() {
-            Poll::Ready(None)
-        } else {
-            let message = self.messages[self.index].clone();
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(delay) = self.delay.as_mut() {
+                ready!(delay.as_mut().poll(cx));
+                self.delay = None;
+            }
+
+            let Some(event) = self.events.get(self.index).cloned() else {
+                return Poll::Ready(None);
+            };
             self.index += 1;
-            Poll::Ready(Some(message))
+
+            match event {
+                MockEvent::Message(message) => {
+                    if self.connected {
+                        return Poll::Ready(Some(Ok(message)));
+                    }
+                    self.dropped += 1;
+                },
+                MockEvent::Error(error) => return Poll::Ready(Some(Err(error))),
+                MockEvent::Delay(delay) => {
+                    self.delay = Some(Box::pin(tokio::time::sleep(delay)));
+                },
+                MockEvent::Disconnect => {
+                    if self.connected {
+                        self.connected = false;
+                        return Poll::Ready(Some(Err(PolyfillError::stream(
+                            "Mock connection dropped",
+                            crate::errors::StreamErrorKind::ConnectionLost,
+                        ))));
+                    }
+                },
+                MockEvent::Reconnect => {
+                    if !self.connected {
+                        self.connected = true;
+                        self.reconnects += 1;
+                    }
+                },
+            }
         }
     }
 }
@@ -696,14 +1647,18 @@ impl MarketStream for MockStream {
     }
 
     fn get_stats(&self) -> StreamStats {
+        let scripted = |f: fn(&MockEvent) -> bool| self.events.iter().filter(|e| f(e)).count();
         StreamStats {
-            messages_received: self.messages.len() as u64,
+            messages_received: scripted(|e| {
+                matches!(e, MockEvent::Message(_) | MockEvent::Error(_))
+            }) as u64,
             messages_sent: 0,
-            errors: self.messages.iter().filter(|m| m.is_err()).count() as u64,
-            dropped_messages: 0,
+            errors: scripted(|e| matches!(e, MockEvent::Error(_))) as u64,
+            dropped_messages: self.dropped,
             last_message_time: None,
             connection_uptime: std::time::Duration::ZERO,
-            reconnect_count: 0,
+            reconnect_count: self.reconnects,
+            tokens: TokenTrafficStats::default(),
         }
     }
 }
@@ -764,17 +1719,91 @@ mod tests {
             bids: vec![],
             asks: vec![],
             hash: None,
+            extra: Default::default(),
         }));
         stream.add_message(StreamMessage::PriceChange(PriceChange {
             market: "0xabc".to_string(),
             timestamp: 1_234_567_891,
             price_changes: vec![],
+            extra: Default::default(),
         }));
 
         assert!(stream.is_connected());
         assert_eq!(stream.get_stats().messages_received, 2);
     }
 
+    fn book(asset_id: &str, timestamp: u64) -> StreamMessage {
+        StreamMessage::Book(BookUpdate {
+            asset_id: asset_id.to_string(),
+            market: "0xabc".to_string(),
+            timestamp,
+            bids: vec![],
+            asks: vec![],
+            hash: None,
+            extra: Default::default(),
+        })
+    }
+
+    fn timestamp(message: &StreamMessage) -> u64 {
+        match message {
+            StreamMessage::Book(book) => book.timestamp,
+            other => panic!("unexpected message {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mock_stream_disconnect_and_reconnect() {
+        let mut stream = MockStream::from_script([
+            MockEvent::Message(book("1", 1)),
+            MockEvent::Disconnect,
+            MockEvent::Message(book("1", 2)),
+            MockEvent::Reconnect,
+            MockEvent::Message(book("1", 3)),
+        ]);
+
+        assert_eq!(timestamp(&stream.next().await.unwrap().unwrap()), 1);
+        let err = stream.next().await.unwrap().unwrap_err();
+        assert!(err.is_retryable());
+        assert!(!stream.is_connected());
+
+        // The message scripted while disconnected is lost
+        assert_eq!(timestamp(&stream.next().await.unwrap().unwrap()), 3);
+        assert!(stream.is_connected());
+        assert!(stream.next().await.is_none());
+        assert!(stream.is_finished());
+
+        let stats = stream.get_stats();
+        assert_eq!(stats.dropped_messages, 1);
+        assert_eq!(stats.reconnect_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_stream_delays_and_shuffle() {
+        let mut stream = MockStream::new();
+        stream.add_message(book("1", 1));
+        stream.add_delay(std::time::Duration::from_millis(20));
+        stream.add_message(book("1", 2));
+
+        let started = std::time::Instant::now();
+        stream.next().await.unwrap().unwrap();
+        stream.next().await.unwrap().unwrap();
+        assert!(started.elapsed() >= std::time::Duration::from_millis(20));
+
+        let script = || (1..=8).map(|ts| MockEvent::Message(book("1", ts)));
+        let mut first = MockStream::from_script(script());
+        let mut second = MockStream::from_script(script());
+        first.shuffle_messages(7);
+        second.shuffle_messages(7);
+
+        let first: Vec<u64> = first.map(|m| timestamp(&m.unwrap())).collect().await;
+        let second: Vec<u64> = second.map(|m| timestamp(&m.unwrap())).collect().await;
+        assert_eq!(first, second);
+        assert_ne!(first, (1..=8).collect::<Vec<_>>());
+        let mut sorted = first.clone();
+        sorted.sort();
+        assert_eq!(sorted, (1..=8).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_stream_manager() {
         let mut manager = StreamManager::new();
@@ -791,6 +1820,7 @@ mod tests {
             bids: vec![],
             asks: vec![],
             hash: None,
+            extra: Default::default(),
         });
         assert!(manager.broadcast_message(message).is_ok());
 
@@ -808,6 +1838,7 @@ mod tests {
             market: "0xabc".to_string(),
             timestamp: 1_234_567_891,
             price_changes: vec![],
+            extra: Default::default(),
         });
         assert!(manager.broadcast_message(message).is_ok());
 
@@ -866,4 +1897,329 @@ mod tests {
         assert_eq!(snapshot.asks[0].price, Decimal::from_str("0.76").unwrap());
         assert_eq!(snapshot.asks[0].size, Decimal::from_str("6").unwrap());
     }
+
+    #[test]
+    fn test_ws_endpoint_derives_channel_paths() {
+        let endpoint = WsEndpoint::default();
+        assert_eq!(
+            endpoint.market_url(),
+            "wss://ws-subscriptions-clob.polymarket.com/ws/market"
+        );
+        assert_eq!(
+            endpoint.user_url(),
+            "wss://ws-subscriptions-clob.polymarket.com/ws/user"
+        );
+
+        for base in [
+            "wss://example.com",
+            "wss://example.com/",
+            "wss://example.com/ws",
+            "wss://example.com/ws/market",
+            "wss://example.com/ws/user/",
+        ] {
+            assert_eq!(WsEndpoint::new(base).base(), "wss://example.com");
+        }
+        assert_eq!(WsEndpoint::new("wss://ws").base(), "wss://ws");
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_routes_to_channel_path() {
+        // Routing happens before any connection attempt, so an unreachable
+        // host still lets us observe the selected URL.
+        let mut stream = WebSocketStream::new("ws://127.0.0.1:1/ws/user");
+        assert!(stream
+            .subscribe_market_channel(vec!["1".to_string()])
+            .await
+            .is_err());
+        assert_eq!(stream.url(), "ws://127.0.0.1:1/ws/market");
+
+        let mut stream = WebSocketStream::new("ws://127.0.0.1:1").with_auth(ApiCredentials {
+            api_key: "key".to_string(),
+            secret: "secret".to_string(),
+            passphrase: "pass".to_string(),
+        });
+        assert!(stream
+            .subscribe_user_channel(vec!["0xabc".to_string()])
+            .await
+            .is_err());
+        assert_eq!(stream.url(), "ws://127.0.0.1:1/ws/user");
+
+        // Custom paths (proxies etc.) are left alone
+        let mut stream = WebSocketStream::new("ws://127.0.0.1:1/feed");
+        assert!(stream
+            .subscribe_market_channel(vec!["1".to_string()])
+            .await
+            .is_err());
+        assert_eq!(stream.url(), "ws://127.0.0.1:1/feed");
+    }
+
+    #[test]
+    fn test_for_channel() {
+        let stream = WebSocketStream::for_channel("wss://example.com/ws", WssChannelType::User);
+        assert_eq!(stream.url(), "wss://example.com/ws/user");
+    }
+
+    fn market_subscription(asset_ids: &[&str]) -> WssSubscription {
+        WssSubscription {
+            channel_type: "market".to_string(),
+            operation: Some("subscribe".to_string()),
+            markets: Vec::new(),
+            asset_ids: asset_ids.iter().map(|id| id.to_string()).collect(),
+            initial_dump: Some(true),
+            custom_feature_enabled: None,
+            auth: None,
+        }
+    }
+
+    fn book_message(asset_id: &str) -> StreamMessage {
+        StreamMessage::Book(BookUpdate {
+            asset_id: asset_id.to_string(),
+            market: "0xabc".to_string(),
+            timestamp: 1,
+            bids: vec![],
+            asks: vec![],
+            hash: None,
+            extra: Default::default(),
+        })
+    }
+
+    #[test]
+    fn test_subscription_confirmed_by_first_snapshot() {
+        let mut stream = WebSocketStream::new("wss://example.com/ws/market");
+        let mut events = stream.subscription_events();
+        stream.record_subscription(
+            &market_subscription(&["1", "2"]),
+            SubscriptionState::Requested,
+        );
+
+        assert_eq!(stream.subscriptions().len(), 2);
+        assert!(stream
+            .subscriptions()
+            .iter()
+            .all(|s| s.state == SubscriptionState::Requested));
+
+        stream.observe_subscription_message(&book_message("1"));
+        assert!(stream.subscriptions()[0].is_confirmed());
+        assert_eq!(
+            stream.subscriptions()[1].state,
+            SubscriptionState::Requested
+        );
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.id, "1");
+        assert!(event.is_confirmed());
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_subscription_rejection_emits_failure() {
+        let mut stream = WebSocketStream::new("wss://example.com/ws/market");
+        let mut events = stream.subscription_events();
+        stream.record_subscription(
+            &market_subscription(&["1", "2"]),
+            SubscriptionState::Requested,
+        );
+        stream.observe_subscription_message(&book_message("1"));
+        let _ = events.try_recv().unwrap();
+
+        // JSON that fails to decode is not treated as a rejection
+        stream.handle_undecodable_text(r#"{"event_type":"book""#);
+        assert_eq!(
+            stream.subscriptions()[1].state,
+            SubscriptionState::Requested
+        );
+
+        stream.handle_undecodable_text("INVALID OPERATION");
+        assert!(stream.subscriptions()[0].is_confirmed());
+        assert_eq!(
+            stream.subscriptions()[1].state,
+            SubscriptionState::Failed("INVALID OPERATION".to_string())
+        );
+
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.id, "2");
+        assert!(event.is_failed());
+    }
+
+    #[test]
+    fn test_subscription_timeout_and_unsubscribe() {
+        let mut stream = WebSocketStream::new("wss://example.com/ws/market");
+        stream.record_subscription(&market_subscription(&["1"]), SubscriptionState::Requested);
+        assert_eq!(
+            stream.fail_unconfirmed_older_than(std::time::Duration::from_secs(60)),
+            0
+        );
+        assert_eq!(
+            stream.fail_unconfirmed_older_than(std::time::Duration::ZERO),
+            1
+        );
+        assert!(stream.subscriptions()[0].is_failed());
+
+        let mut unsubscribe = market_subscription(&["1"]);
+        unsubscribe.operation = Some("unsubscribe".to_string());
+        stream.forget_subscription(&unsubscribe);
+        assert!(stream.subscriptions().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_connection_failure_marks_failed() {
+        let mut stream = WebSocketStream::new("ws://127.0.0.1:1/ws/market");
+        assert!(stream
+            .subscribe_market_channel(vec!["1".to_string()])
+            .await
+            .is_err());
+        assert_eq!(stream.subscriptions().len(), 1);
+        assert!(stream.subscriptions()[0].is_failed());
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_and_resubscribes() {
+        use tokio_tungstenite::tungstenite::Message;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            // First connection drops right after the subscription
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            ws.next().await.unwrap().unwrap();
+            ws.close(None).await.unwrap();
+
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            let resubscribe = ws.next().await.unwrap().unwrap();
+            ws.send(Message::Text(
+                r#"{"event_type":"book","asset_id":"1","market":"0xabc","timestamp":1,"bids":[],"asks":[]}"#
+                    .to_string(),
+            ))
+            .await
+            .unwrap();
+            resubscribe
+        });
+
+        let mut stream = WebSocketStream::new(&format!("ws://{addr}/ws/market"))
+            .with_reconnect_config(ReconnectConfig {
+                base_delay: std::time::Duration::from_millis(10),
+                ..ReconnectConfig::default()
+            });
+        let disconnects = Arc::new(Mutex::new(Vec::new()));
+        let reconnects = Arc::new(Mutex::new(Vec::new()));
+        let seen = disconnects.clone();
+        stream.on_disconnect(move |event| seen.lock().push(event.clone()));
+        let seen = reconnects.clone();
+        stream.on_reconnect(move |event| seen.lock().push(event.clone()));
+
+        stream
+            .subscribe_market_channel(vec!["1".to_string()])
+            .await
+            .unwrap();
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(matches!(message, StreamMessage::Book(_)));
+
+        let resubscribe = server.await.unwrap();
+        assert!(resubscribe
+            .to_text()
+            .unwrap()
+            .contains(r#""assets_ids":["1"]"#));
+        assert_eq!(stream.get_stats().reconnect_count, 1);
+        assert!(stream.subscriptions()[0].is_confirmed());
+
+        let disconnects = disconnects.lock();
+        assert_eq!(disconnects.len(), 1);
+        assert_eq!(disconnects[0].attempt, 0);
+        assert!(disconnects[0].retry_in.is_some());
+        let reconnects = reconnects.lock();
+        assert_eq!(reconnects.len(), 1);
+        assert_eq!(reconnects[0].attempts, 1);
+        assert_eq!(reconnects[0].cause, disconnects[0].cause);
+    }
+
+    #[test]
+    fn test_reconnect_delay_is_capped() {
+        let config = ReconnectConfig::default();
+        assert_eq!(config.delay_for(0), std::time::Duration::from_secs(1));
+        assert_eq!(config.delay_for(3), std::time::Duration::from_secs(8));
+        assert_eq!(config.delay_for(u32::MAX), config.max_delay);
+    }
+    #[tokio::test]
+    async fn test_in_memory_transport_via_custom_connector() {
+        use crate::ws_transport::{BoxedTransport, WsConnector};
+        use futures::future::BoxFuture;
+        use tokio_tungstenite::tungstenite::Message;
+
+        // Serves each connection over an in-process duplex pipe
+        struct MemoryConnector;
+
+        impl WsConnector for MemoryConnector {
+            fn connect<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<BoxedTransport>> {
+                Box::pin(async move {
+                    let (client, server) = tokio::io::duplex(64 * 1024);
+                    tokio::spawn(async move {
+                        let mut ws = tokio_tungstenite::accept_async(server).await.unwrap();
+                        ws.next().await.unwrap().unwrap();
+                        ws.send(Message::Text(
+                            r#"{"event_type":"book","asset_id":"1","market":"0xabc","timestamp":1,"bids":[],"asks":[]}"#
+                                .to_string(),
+                        ))
+                        .await
+                        .unwrap();
+                    });
+                    let (connection, _) = tokio_tungstenite::client_async(url, client).await?;
+                    Ok(Box::new(connection) as BoxedTransport)
+                })
+            }
+        }
+
+        let mut stream =
+            WebSocketStream::new("ws://memory/ws/market").with_connector(MemoryConnector);
+        stream
+            .subscribe_market_channel(vec!["1".to_string()])
+            .await
+            .unwrap();
+        let message = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert!(matches!(message, StreamMessage::Book(_)));
+        assert!(stream.subscriptions()[0].is_confirmed());
+        let traffic = stream.get_stats().tokens.total("1").unwrap();
+        assert_eq!((traffic.messages, traffic.parse_failures), (1, 0));
+        assert!(traffic.bytes > 0);
+    }
+
+    #[test]
+    fn test_token_traffic_attribution_and_window() {
+        let mut stats = TokenTrafficStats::new(std::time::Duration::from_secs(10));
+        let frame = r#"{"event_type":"price_change","market":"0xabc","timestamp":"1",
+            "price_changes":[{"asset_id":"1","price":"0.5","size":"10","side":"BUY"},
+                             {"asset_id":"2","price":"0.5","size":"10","side":"SELL"}]}"#;
+        let messages = crate::decode::parse_stream_messages(frame).unwrap();
+        stats.record_frame(&messages, 101);
+        assert_eq!(stats.total("1").unwrap().bytes, 51);
+        assert_eq!(stats.total("2").unwrap().bytes, 50);
+        assert_eq!(stats.total("2").unwrap().messages, 1);
+
+        stats.record_parse_failure(r#"{"event_type":"book","asset_id":"2","bids":7}"#);
+        stats.record_parse_failure("not json");
+        assert_eq!(stats.total("2").unwrap().parse_failures, 1);
+        assert_eq!(stats.total(UNATTRIBUTED_TOKEN).unwrap().parse_failures, 1);
+        assert_eq!(stats.busiest(1)[0].0, "2");
+
+        // Old buckets leave the window but stay in the totals
+        let now = Utc::now().timestamp();
+        let traffic = TokenTraffic {
+            messages: 1,
+            bytes: 500,
+            parse_failures: 0,
+        };
+        stats.record_at("3", traffic, now - 30);
+        stats.record_at("3", traffic, now);
+        assert_eq!(stats.recent_at("3", now).unwrap().bytes, 500);
+        assert_eq!(stats.total("3").unwrap().bytes, 1000);
+    }
 }
@@ -0,0 +1,468 @@
+//! Market tradability scoring
+//!
+//! [`TradabilityRules`] folds a token's book, market metadata and recent
+//! update rate into one score between 0 and 1 so a quoting bot can pick its
+//! markets automatically. Each component is scored on its own:
+//!
+//! - spread: 1 when locked, 0 at `max_spread`
+//! - depth: size resting within `depth_band` of the mid on the thinner
+//!   side, 1 at `target_depth`
+//! - activity: book updates per minute from [`UpdateRate`], 1 at
+//!   `target_updates_per_minute`
+//! - resolution: 0 at `min_time_to_resolution`, 1 from
+//!   `full_time_to_resolution`; 0.5 when the end date is unknown
+//! - rewards: 1 when the market runs a liquidity reward program
+//!
+//! and the components are averaged with [`TradabilityWeights`]. One-sided
+//! books, markets not accepting orders, spreads wider than `max_spread` and
+//! markets resolving within `min_time_to_resolution` are rejected whatever
+//! their score.
+
+use crate::book::{OrderBook, OrderBookManager};
+use crate::types::{Market, Side, StreamMessage};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Relative weight of each score component
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradabilityWeights {
+    pub spread: f64,
+    pub depth: f64,
+    pub activity: f64,
+    pub resolution: f64,
+    pub rewards: f64,
+}
+
+impl Default for TradabilityWeights {
+    fn default() -> Self {
+        Self {
+            spread: 0.3,
+            depth: 0.25,
+            activity: 0.2,
+            resolution: 0.15,
+            rewards: 0.1,
+        }
+    }
+}
+
+/// How markets are scored and which ones pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradabilityRules {
+    /// Widest spread worth quoting
+    pub max_spread: Decimal,
+    /// Distance from the mid counted as depth
+    pub depth_band: Decimal,
+    /// Depth (shares) that scores full marks
+    pub target_depth: Decimal,
+    /// Update rate that scores full marks
+    pub target_updates_per_minute: f64,
+    /// Markets resolving sooner than this are rejected
+    pub min_time_to_resolution: Duration,
+    /// Markets resolving at least this far out score full marks
+    pub full_time_to_resolution: Duration,
+    pub weights: TradabilityWeights,
+    /// Lowest score [`TradabilityRules::select`] keeps
+    pub min_score: f64,
+}
+
+impl Default for TradabilityRules {
+    fn default() -> Self {
+        Self {
+            max_spread: Decimal::new(5, 2),
+            depth_band: Decimal::new(5, 2),
+            target_depth: Decimal::from(1000),
+            target_updates_per_minute: 30.0,
+            min_time_to_resolution: Duration::from_secs(3600),
+            full_time_to_resolution: Duration::from_secs(7 * 86_400),
+            weights: TradabilityWeights::default(),
+            min_score: 0.5,
+        }
+    }
+}
+
+/// What a score is computed from
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradabilityInputs {
+    pub token_id: String,
+    /// `None` when the book is one-sided or empty
+    pub spread: Option<Decimal>,
+    /// Size within the depth band on the thinner side
+    pub depth: Decimal,
+    pub updates_per_minute: f64,
+    /// `None` when the end date is unknown
+    pub time_to_resolution: Option<Duration>,
+    pub has_rewards: bool,
+    pub accepting_orders: bool,
+}
+
+impl TradabilityInputs {
+    /// Inputs for a token with no book, activity or metadata yet
+    pub fn new(token_id: impl Into<String>) -> Self {
+        Self {
+            token_id: token_id.into(),
+            spread: None,
+            depth: Decimal::ZERO,
+            updates_per_minute: 0.0,
+            time_to_resolution: None,
+            has_rewards: false,
+            accepting_orders: true,
+        }
+    }
+
+    /// Spread and depth within `depth_band` of the mid
+    pub fn from_book(book: &OrderBook, depth_band: Decimal) -> Self {
+        let mut inputs = Self::new(book.token_id.as_str());
+        inputs.spread = book.spread();
+        if let Some(mid) = book.mid_price() {
+            let low = (mid - depth_band).max(Decimal::ZERO);
+            let high = (mid + depth_band).min(Decimal::ONE);
+            let bids = book.liquidity_in_range(low, mid, Side::SELL);
+            let asks = book.liquidity_in_range(mid, high, Side::BUY);
+            inputs.depth = bids.min(asks);
+        }
+        inputs
+    }
+
+    /// Take the end date, reward program and trading status from `market`
+    pub fn with_market(mut self, market: &Market, now: DateTime<Utc>) -> Self {
+        self.time_to_resolution = market
+            .end_date_iso
+            .as_deref()
+            .and_then(parse_end_date)
+            .map(|end| (end - now).to_std().unwrap_or_default());
+        self.has_rewards = has_rewards(market);
+        self.accepting_orders =
+            market.active && !market.closed && market.enable_order_book && market.accepting_orders;
+        self
+    }
+
+    pub fn with_updates_per_minute(mut self, updates_per_minute: f64) -> Self {
+        self.updates_per_minute = updates_per_minute;
+        self
+    }
+}
+
+/// A token's score and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradabilityScore {
+    pub token_id: String,
+    /// Weighted average of the components, 0 to 1
+    pub score: f64,
+    pub spread: f64,
+    pub depth: f64,
+    pub activity: f64,
+    pub resolution: f64,
+    pub rewards: f64,
+    /// Why the token cannot be quoted regardless of score
+    pub rejected: Option<String>,
+}
+
+impl TradabilityScore {
+    pub fn is_tradable(&self, min_score: f64) -> bool {
+        self.rejected.is_none() && self.score >= min_score
+    }
+}
+
+impl TradabilityRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_spread(mut self, max_spread: Decimal) -> Self {
+        self.max_spread = max_spread;
+        self
+    }
+
+    pub fn with_depth(mut self, depth_band: Decimal, target_depth: Decimal) -> Self {
+        self.depth_band = depth_band;
+        self.target_depth = target_depth;
+        self
+    }
+
+    pub fn with_target_update_rate(mut self, updates_per_minute: f64) -> Self {
+        self.target_updates_per_minute = updates_per_minute;
+        self
+    }
+
+    pub fn with_resolution_window(mut self, min: Duration, full: Duration) -> Self {
+        self.min_time_to_resolution = min;
+        self.full_time_to_resolution = full;
+        self
+    }
+
+    pub fn with_weights(mut self, weights: TradabilityWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    pub fn with_min_score(mut self, min_score: f64) -> Self {
+        self.min_score = min_score;
+        self
+    }
+
+    pub fn score(&self, inputs: &TradabilityInputs) -> TradabilityScore {
+        let spread = inputs
+            .spread
+            .map_or(0.0, |spread| 1.0 - ratio(spread, self.max_spread));
+        let depth = ratio(inputs.depth, self.target_depth);
+        let activity = if self.target_updates_per_minute > 0.0 {
+            (inputs.updates_per_minute / self.target_updates_per_minute).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let resolution = inputs.time_to_resolution.map_or(0.5, |left| {
+            let span = self
+                .full_time_to_resolution
+                .saturating_sub(self.min_time_to_resolution);
+            if span.is_zero() {
+                return if left >= self.full_time_to_resolution {
+                    1.0
+                } else {
+                    0.0
+                };
+            }
+            (left
+                .saturating_sub(self.min_time_to_resolution)
+                .as_secs_f64()
+                / span.as_secs_f64())
+            .clamp(0.0, 1.0)
+        });
+        let rewards = if inputs.has_rewards { 1.0 } else { 0.0 };
+
+        let w = &self.weights;
+        let total = w.spread + w.depth + w.activity + w.resolution + w.rewards;
+        let score = if total > 0.0 {
+            (w.spread * spread
+                + w.depth * depth
+                + w.activity * activity
+                + w.resolution * resolution
+                + w.rewards * rewards)
+                / total
+        } else {
+            0.0
+        };
+
+        let rejected = if !inputs.accepting_orders {
+            Some("market is not accepting orders".to_string())
+        } else if inputs.spread.is_none() {
+            Some("book is one-sided or empty".to_string())
+        } else if inputs.spread.is_some_and(|spread| spread > self.max_spread) {
+            Some(format!("spread wider than {}", self.max_spread))
+        } else if inputs
+            .time_to_resolution
+            .is_some_and(|left| left < self.min_time_to_resolution)
+        {
+            Some("market resolves too soon".to_string())
+        } else {
+            None
+        };
+
+        TradabilityScore {
+            token_id: inputs.token_id.clone(),
+            score,
+            spread,
+            depth,
+            activity,
+            resolution,
+            rewards,
+            rejected,
+        }
+    }
+
+    /// Scores of the tradable tokens at or above `min_score`, best first
+    pub fn select(
+        &self,
+        inputs: impl IntoIterator<Item = TradabilityInputs>,
+    ) -> Vec<TradabilityScore> {
+        let mut scores: Vec<TradabilityScore> = inputs
+            .into_iter()
+            .map(|inputs| self.score(&inputs))
+            .filter(|score| score.is_tradable(self.min_score))
+            .collect();
+        scores.sort_by(|a, b| b.score.total_cmp(&a.score));
+        scores
+    }
+
+    /// [`Self::select`] over every token of `markets` with a book in `books`
+    pub fn select_markets(
+        &self,
+        books: &OrderBookManager,
+        markets: &[Market],
+        rates: &UpdateRate,
+        now: DateTime<Utc>,
+    ) -> Vec<TradabilityScore> {
+        let now_ms = now.timestamp_millis().max(0) as u64;
+        let inputs = markets.iter().flat_map(|market| {
+            market.tokens.iter().filter_map(move |token| {
+                let inputs = books
+                    .with_book(&token.token_id, |book| {
+                        TradabilityInputs::from_book(book, self.depth_band)
+                    })
+                    .ok()?;
+                Some(
+                    inputs
+                        .with_market(market, now)
+                        .with_updates_per_minute(rates.per_minute(&token.token_id, now_ms)),
+                )
+            })
+        });
+        self.select(inputs)
+    }
+}
+
+/// Book updates per token over a sliding window, clocked by exchange
+/// timestamps
+#[derive(Debug, Clone)]
+pub struct UpdateRate {
+    window_ms: u64,
+    updates: HashMap<String, VecDeque<u64>>,
+}
+
+impl Default for UpdateRate {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(300))
+    }
+}
+
+impl UpdateRate {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window_ms: (window.as_millis() as u64).max(1),
+            updates: HashMap::new(),
+        }
+    }
+
+    /// Count book snapshots and price changes
+    pub fn observe(&mut self, message: &StreamMessage) {
+        match message {
+            StreamMessage::Book(update) => self.record(&update.asset_id, update.timestamp),
+            StreamMessage::PriceChange(change) => {
+                for entry in &change.price_changes {
+                    self.record(&entry.asset_id, change.timestamp);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    pub fn record(&mut self, token_id: &str, timestamp_ms: u64) {
+        let updates = self.updates.entry(token_id.to_string()).or_default();
+        updates.push_back(timestamp_ms);
+        let cutoff = timestamp_ms.saturating_sub(self.window_ms);
+        while updates.front().is_some_and(|t| *t <= cutoff) {
+            updates.pop_front();
+        }
+    }
+
+    /// Updates per minute over the window ending at `now_ms`
+    pub fn per_minute(&self, token_id: &str, now_ms: u64) -> f64 {
+        let Some(updates) = self.updates.get(token_id) else {
+            return 0.0;
+        };
+        let cutoff = now_ms.saturating_sub(self.window_ms);
+        let count = updates
+            .iter()
+            .filter(|t| **t > cutoff && **t <= now_ms)
+            .count();
+        count as f64 * 60_000.0 / self.window_ms as f64
+    }
+}
+
+fn ratio(value: Decimal, target: Decimal) -> f64 {
+    if target <= Decimal::ZERO {
+        return 1.0;
+    }
+    (value / target).to_f64().unwrap_or(0.0).clamp(0.0, 1.0)
+}
+
+fn has_rewards(market: &Market) -> bool {
+    let rates = match &market.rewards.rates {
+        Some(serde_json::Value::Array(rates)) => !rates.is_empty(),
+        Some(serde_json::Value::Null) | None => false,
+        Some(_) => true,
+    };
+    rates || market.rewards.max_spread > Decimal::ZERO
+}
+
+pub(crate) fn parse_end_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            let date = NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()?;
+            Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_scores_and_selects_tradable_tokens() {
+        let books = OrderBookManager::new(10);
+        let update: crate::types::BookUpdate = serde_json::from_str(
+            r#"{"asset_id":"1","market":"0xabc","timestamp":"1000",
+                "bids":[{"price":"0.49","size":"400"},{"price":"0.30","size":"900"}],
+                "asks":[{"price":"0.51","size":"600"}]}"#,
+        )
+        .unwrap();
+        books.get_or_create_book("1").unwrap();
+        books.apply_book_update(&update).unwrap();
+        let deep = books
+            .with_book("1", |book| TradabilityInputs::from_book(book, dec!(0.05)))
+            .unwrap();
+        assert_eq!(deep.spread, Some(dec!(0.02)));
+        // The 0.30 bid sits outside the band
+        assert_eq!(deep.depth, dec!(400));
+
+        let mut rates = UpdateRate::new(Duration::from_secs(60));
+        for t in 0..30 {
+            rates.record("1", 1_000 + t * 1_000);
+        }
+        assert_eq!(rates.per_minute("1", 60_000), 30.0);
+        assert_eq!(rates.per_minute("1", 200_000), 0.0);
+
+        let rules = TradabilityRules::new().with_depth(dec!(0.05), dec!(400));
+        let deep = TradabilityInputs {
+            time_to_resolution: Some(Duration::from_secs(30 * 86_400)),
+            has_rewards: true,
+            ..deep.with_updates_per_minute(rates.per_minute("1", 60_000))
+        };
+        let score = rules.score(&deep);
+        assert!((score.spread - 0.6).abs() < 1e-9);
+        assert_eq!(
+            (score.depth, score.activity, score.resolution),
+            (1.0, 1.0, 1.0)
+        );
+        assert!((score.score - 0.88).abs() < 1e-9);
+
+        let thin = TradabilityInputs {
+            spread: Some(dec!(0.04)),
+            depth: dec!(10),
+            ..TradabilityInputs::new("2")
+        };
+        let expiring = TradabilityInputs {
+            time_to_resolution: Some(Duration::from_secs(60)),
+            ..deep.clone()
+        };
+        assert_eq!(
+            rules.score(&expiring).rejected.as_deref(),
+            Some("market resolves too soon")
+        );
+        let wide = TradabilityInputs {
+            spread: Some(dec!(0.10)),
+            ..deep.clone()
+        };
+        assert!(rules.score(&wide).rejected.is_some());
+
+        let selected = rules.select([thin.clone(), expiring, wide, deep]);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].token_id, "1");
+        assert_eq!(rules.with_min_score(0.0).select([thin]).len(), 1);
+    }
+}
@@ -0,0 +1,374 @@
+//! Trade clustering and large-print detection
+//!
+//! [`TradeFlowDetector`] watches the trade tape of every token it sees and
+//! emits [`TradeFlowEvent`]s:
+//!
+//! - a large print is a trade whose size sits `large_print_sigma` standard
+//!   deviations above the mean of the trailing window
+//! - a cluster is a run of same-side trades, each within `cluster_gap` of the
+//!   previous one; it is reported once it reaches `min_cluster_trades` trades
+//!   and again when it ends
+//!
+//! Both are useful for momentum strategies and as a signal to pull quotes
+//! while the market moves violently; see [`TradeFlowDetector::active_cluster`].
+
+use crate::types::{Side, StreamMessage};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+/// Events kept for slow [`TradeFlowDetector::subscribe`] receivers
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// Detection thresholds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeFlowConfig {
+    /// Trailing window for the trade size distribution
+    pub window: Duration,
+    /// Trades required in the window before large prints are reported
+    pub min_samples: usize,
+    /// Standard deviations above the mean size that make a print large
+    pub large_print_sigma: f64,
+    /// Longest pause between two trades of the same cluster
+    pub cluster_gap: Duration,
+    /// Trades required before a cluster is reported
+    pub min_cluster_trades: usize,
+}
+
+impl Default for TradeFlowConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60 * 60),
+            min_samples: 20,
+            large_print_sigma: 3.0,
+            cluster_gap: Duration::from_secs(2),
+            min_cluster_trades: 5,
+        }
+    }
+}
+
+impl TradeFlowConfig {
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(2);
+        self
+    }
+
+    pub fn with_large_print_sigma(mut self, sigma: f64) -> Self {
+        self.large_print_sigma = sigma;
+        self
+    }
+
+    pub fn with_cluster_gap(mut self, gap: Duration) -> Self {
+        self.cluster_gap = gap;
+        self
+    }
+
+    pub fn with_min_cluster_trades(mut self, trades: usize) -> Self {
+        self.min_cluster_trades = trades.max(2);
+        self
+    }
+}
+
+/// Run of same-side trades in quick succession
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeCluster {
+    /// Side of the aggressive orders
+    pub side: Side,
+    pub trades: usize,
+    pub volume: Decimal,
+    pub first_price: Decimal,
+    pub last_price: Decimal,
+    /// Milliseconds since the epoch
+    pub started_at: u64,
+    pub last_trade_at: u64,
+}
+
+impl TradeCluster {
+    /// Price moved from the first trade to the last
+    pub fn price_move(&self) -> Decimal {
+        self.last_price - self.first_price
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.last_trade_at - self.started_at)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TradeFlowEvent {
+    /// A trade far larger than recent ones
+    LargePrint {
+        token_id: String,
+        side: Option<Side>,
+        price: Decimal,
+        size: Decimal,
+        /// Standard deviations above the trailing mean size
+        sigma: f64,
+        timestamp: u64,
+    },
+    /// A cluster reached `min_cluster_trades`
+    ClusterStarted {
+        token_id: String,
+        cluster: TradeCluster,
+    },
+    /// A reported cluster ended: the gap elapsed or the other side traded
+    ClusterEnded {
+        token_id: String,
+        cluster: TradeCluster,
+    },
+}
+
+#[derive(Debug, Default)]
+struct TokenTape {
+    /// `(timestamp, size)` within the window
+    sizes: VecDeque<(u64, f64)>,
+    sum: f64,
+    sum_sq: f64,
+    cluster: Option<TradeCluster>,
+}
+
+impl TokenTape {
+    fn evict(&mut self, cutoff: u64) {
+        while let Some(&(timestamp, size)) = self.sizes.front() {
+            if timestamp >= cutoff {
+                break;
+            }
+            self.sizes.pop_front();
+            self.sum -= size;
+            self.sum_sq -= size * size;
+        }
+    }
+
+    /// Mean and standard deviation of sizes in the window
+    fn distribution(&self) -> (f64, f64) {
+        let n = self.sizes.len() as f64;
+        let mean = self.sum / n;
+        let variance = (self.sum_sq / n - mean * mean).max(0.0);
+        (mean, variance.sqrt())
+    }
+}
+
+/// Detects large prints and trade clusters per token
+#[derive(Debug)]
+pub struct TradeFlowDetector {
+    config: TradeFlowConfig,
+    tapes: HashMap<String, TokenTape>,
+    events: broadcast::Sender<TradeFlowEvent>,
+}
+
+impl Default for TradeFlowDetector {
+    fn default() -> Self {
+        Self::new(TradeFlowConfig::default())
+    }
+}
+
+impl TradeFlowDetector {
+    pub fn new(config: TradeFlowConfig) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            config,
+            tapes: HashMap::new(),
+            events,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<TradeFlowEvent> {
+        self.events.subscribe()
+    }
+
+    /// Feed a market-channel message; only `last_trade_price` is used
+    pub fn observe(&mut self, message: &StreamMessage) -> Vec<TradeFlowEvent> {
+        match message {
+            StreamMessage::LastTradePrice(trade) => match trade.size {
+                Some(size) => self.record_trade(
+                    &trade.asset_id,
+                    trade.side,
+                    trade.price,
+                    size,
+                    trade.timestamp,
+                ),
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Record a trade; `side` is the aggressor's side when known
+    pub fn record_trade(
+        &mut self,
+        token_id: &str,
+        side: Option<Side>,
+        price: Decimal,
+        size: Decimal,
+        timestamp: u64,
+    ) -> Vec<TradeFlowEvent> {
+        let config = self.config;
+        let tape = self.tapes.entry(token_id.to_string()).or_default();
+        let mut found = Vec::new();
+
+        // Judge the print against the trades before it
+        tape.evict(timestamp.saturating_sub(config.window.as_millis() as u64));
+        let size_f64 = size.to_f64().unwrap_or(0.0);
+        if tape.sizes.len() >= config.min_samples {
+            let (mean, std_dev) = tape.distribution();
+            if std_dev > 0.0 {
+                let sigma = (size_f64 - mean) / std_dev;
+                if sigma >= config.large_print_sigma {
+                    found.push(TradeFlowEvent::LargePrint {
+                        token_id: token_id.to_string(),
+                        side,
+                        price,
+                        size,
+                        sigma,
+                        timestamp,
+                    });
+                }
+            }
+        }
+        tape.sizes.push_back((timestamp, size_f64));
+        tape.sum += size_f64;
+        tape.sum_sq += size_f64 * size_f64;
+
+        let gap = config.cluster_gap.as_millis() as u64;
+        let extends = |cluster: &TradeCluster| {
+            Some(cluster.side) == side && timestamp <= cluster.last_trade_at + gap
+        };
+        match tape.cluster.as_mut() {
+            Some(cluster) if extends(cluster) => {
+                cluster.trades += 1;
+                cluster.volume += size;
+                cluster.last_price = price;
+                cluster.last_trade_at = timestamp;
+                if cluster.trades == config.min_cluster_trades {
+                    found.push(TradeFlowEvent::ClusterStarted {
+                        token_id: token_id.to_string(),
+                        cluster: *cluster,
+                    });
+                }
+            },
+            _ => {
+                if let Some(ended) = tape.cluster.take() {
+                    if ended.trades >= config.min_cluster_trades {
+                        found.push(TradeFlowEvent::ClusterEnded {
+                            token_id: token_id.to_string(),
+                            cluster: ended,
+                        });
+                    }
+                }
+                tape.cluster = side.map(|side| TradeCluster {
+                    side,
+                    trades: 1,
+                    volume: size,
+                    first_price: price,
+                    last_price: price,
+                    started_at: timestamp,
+                    last_trade_at: timestamp,
+                });
+            },
+        }
+
+        for event in &found {
+            let _ = self.events.send(event.clone());
+        }
+        found
+    }
+
+    /// Close clusters whose gap has elapsed by `now` (milliseconds since the
+    /// epoch). Clusters otherwise only end when the next trade arrives.
+    pub fn expire(&mut self, now: u64) -> Vec<TradeFlowEvent> {
+        let gap = self.config.cluster_gap.as_millis() as u64;
+        let min_trades = self.config.min_cluster_trades;
+        let mut found = Vec::new();
+        for (token_id, tape) in &mut self.tapes {
+            if tape
+                .cluster
+                .is_some_and(|cluster| now > cluster.last_trade_at + gap)
+            {
+                let ended = tape.cluster.take().expect("cluster checked above");
+                if ended.trades >= min_trades {
+                    found.push(TradeFlowEvent::ClusterEnded {
+                        token_id: token_id.clone(),
+                        cluster: ended,
+                    });
+                }
+            }
+        }
+        for event in &found {
+            let _ = self.events.send(event.clone());
+        }
+        found
+    }
+
+    /// The cluster in progress for `token_id` at `now`, once it has enough
+    /// trades to be reported. Quoting strategies can stand aside while it is
+    /// `Some`.
+    pub fn active_cluster(&self, token_id: &str, now: u64) -> Option<TradeCluster> {
+        let gap = self.config.cluster_gap.as_millis() as u64;
+        self.tapes
+            .get(token_id)?
+            .cluster
+            .filter(|c| c.trades >= self.config.min_cluster_trades && now <= c.last_trade_at + gap)
+    }
+
+    /// Forget a token's tape
+    pub fn reset(&mut self, token_id: &str) {
+        self.tapes.remove(token_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_large_prints_and_clusters() {
+        let mut detector =
+            TradeFlowDetector::new(TradeFlowConfig::default().with_min_cluster_trades(3));
+        let mut events = detector.subscribe();
+
+        // Alternating sides every 10s: a steady tape of 9-11 share trades
+        for i in 0..30u64 {
+            let side = if i % 2 == 0 { Side::BUY } else { Side::SELL };
+            let size = Decimal::from(9 + i % 3);
+            let found = detector.record_trade("1", Some(side), dec!(0.50), size, i * 10_000);
+            assert!(found.is_empty());
+        }
+
+        let found = detector.record_trade("1", Some(Side::BUY), dec!(0.51), dec!(200), 300_000);
+        assert!(matches!(
+            found.as_slice(),
+            [TradeFlowEvent::LargePrint { sigma, .. }] if *sigma > 3.0
+        ));
+        assert_eq!(events.try_recv().unwrap(), found[0]);
+
+        // Two more buys within the gap complete a cluster
+        assert!(detector
+            .record_trade("1", Some(Side::BUY), dec!(0.52), dec!(10), 301_000)
+            .is_empty());
+        let found = detector.record_trade("1", Some(Side::BUY), dec!(0.54), dec!(10), 302_500);
+        let [TradeFlowEvent::ClusterStarted { cluster, .. }] = found.as_slice() else {
+            panic!("expected a cluster, got {found:?}");
+        };
+        assert_eq!(cluster.trades, 3);
+        assert_eq!(cluster.volume, dec!(220));
+        assert_eq!(cluster.price_move(), dec!(0.03));
+        assert!(detector.active_cluster("1", 303_000).is_some());
+
+        // The gap elapses without another buy
+        assert!(detector.active_cluster("1", 305_000).is_none());
+        let found = detector.expire(305_000);
+        assert!(matches!(
+            found.as_slice(),
+            [TradeFlowEvent::ClusterEnded { cluster, .. }] if cluster.trades == 3
+        ));
+        assert!(detector.expire(306_000).is_empty());
+    }
+}
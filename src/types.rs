@@ -3,11 +3,12 @@
 //! This module defines all the stable public types used throughout the client.
 //! These types are optimized for latency-sensitive trading environments.
 
-use alloy_primitives::Address;
+use alloy_primitives::{Address, U256};
 use chrono::{DateTime, Utc};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 // ============================================================================
 // FIXED-POINT OPTIMIZATION FOR HOT PATH PERFORMANCE
@@ -39,6 +40,8 @@ use serde::{Deserialize, Serialize};
 /// - Can represent prices from $0.0001 to $429,496.7295 (way more than needed)
 /// - Fits in CPU register for fast operations
 /// - No sign bit needed since prices are always positive
+///
+/// [`crate::Ticks`] is the typed wrapper used by the public fast API.
 pub type Price = u32;
 
 /// Quantity/size represented as fixed-point integer for performance
@@ -52,6 +55,8 @@ pub type Price = u32;
 /// - Can represent quantities from -922,337,203,685.4775 to +922,337,203,685.4775
 /// - Signed because we need to handle both buys (+) and sells (-)
 /// - Large enough for any realistic trading size
+///
+/// [`crate::Units`] is the typed wrapper used by the public fast API.
 pub type Qty = i64;
 
 /// Scale factor for converting between Decimal and fixed-point
@@ -217,8 +222,11 @@ pub fn is_price_tick_aligned(decimal: Decimal, tick_size_decimal: Decimal) -> bo
     price_ticks % tick_size_ticks == 0
 }
 
-/// Trading side for orders
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Trading side for orders.
+///
+/// Serializes as `"BUY"`/`"SELL"` and deserializes from any casing; use
+/// [`lowercase`] for payloads that expect `"buy"`/`"sell"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum Side {
     BUY = 0,
@@ -241,8 +249,10 @@ impl Side {
     }
 }
 
-/// Order type specifications
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
+/// Order type specifications.
+///
+/// Serializes in upper case and deserializes from any casing, like [`Side`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 #[allow(clippy::upper_case_acronyms)]
 pub enum OrderType {
     #[default]
@@ -263,6 +273,128 @@ impl OrderType {
     }
 }
 
+impl std::str::FromStr for Side {
+    type Err = crate::errors::PolyfillError;
+
+    fn from_str(s: &str) -> crate::errors::Result<Self> {
+        if s.eq_ignore_ascii_case("BUY") {
+            Ok(Side::BUY)
+        } else if s.eq_ignore_ascii_case("SELL") {
+            Ok(Side::SELL)
+        } else {
+            Err(crate::errors::PolyfillError::parse(
+                format!("Invalid side: {s}"),
+                None,
+            ))
+        }
+    }
+}
+
+impl std::str::FromStr for OrderType {
+    type Err = crate::errors::PolyfillError;
+
+    fn from_str(s: &str) -> crate::errors::Result<Self> {
+        [
+            OrderType::GTC,
+            OrderType::FOK,
+            OrderType::GTD,
+            OrderType::FAK,
+        ]
+        .into_iter()
+        .find(|order_type| order_type.as_str().eq_ignore_ascii_case(s))
+        .ok_or_else(|| {
+            crate::errors::PolyfillError::parse(format!("Invalid order type: {s}"), None)
+        })
+    }
+}
+
+macro_rules! impl_wire_enum {
+    ($ty:ty, $expecting:literal) => {
+        impl AsRef<str> for $ty {
+            fn as_ref(&self) -> &str {
+                self.as_str()
+            }
+        }
+
+        impl std::fmt::Display for $ty {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $ty {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> std::result::Result<Self, D::Error> {
+                struct CaseInsensitive;
+
+                impl serde::de::Visitor<'_> for CaseInsensitive {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str($expecting)
+                    }
+
+                    fn visit_str<E: serde::de::Error>(
+                        self,
+                        value: &str,
+                    ) -> std::result::Result<$ty, E> {
+                        value
+                            .parse()
+                            .map_err(|_| E::invalid_value(serde::de::Unexpected::Str(value), &self))
+                    }
+                }
+
+                deserializer.deserialize_str(CaseInsensitive)
+            }
+        }
+    };
+}
+
+impl_wire_enum!(Side, "BUY or SELL in any casing");
+impl_wire_enum!(OrderType, "GTC, FOK, GTD or FAK in any casing");
+
+/// Lowercase serialization for [`Side`] and [`OrderType`] fields:
+/// `#[serde(with = "crate::types::lowercase")]`. Deserialization stays case
+/// insensitive.
+pub mod lowercase {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: AsRef<str>,
+        S: Serializer,
+    {
+        let mut buf = [0u8; 8];
+        let upper = value.as_ref().as_bytes();
+        match buf.get_mut(..upper.len()) {
+            Some(lower) => {
+                lower.copy_from_slice(upper);
+                lower.make_ascii_lowercase();
+                serializer.serialize_str(std::str::from_utf8(lower).unwrap_or_default())
+            },
+            None => serializer.serialize_str(&value.as_ref().to_ascii_lowercase()),
+        }
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
 /// Order status in the system
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OrderStatus {
@@ -296,7 +428,7 @@ pub struct MarketSnapshot {
 ///
 /// This is what we expose to users and serialize to JSON.
 /// It uses Decimal for precision and human readability.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BookLevel {
     #[serde(with = "rust_decimal::serde::str")]
     pub price: Decimal,
@@ -468,13 +600,7 @@ impl FastOrderDelta {
 
         // Hash the token_id for fast lookups
         // This avoids string comparisons in the hot path
-        let token_id_hash = {
-            use std::collections::hash_map::DefaultHasher;
-            use std::hash::{Hash, Hasher};
-            let mut hasher = DefaultHasher::new();
-            delta.token_id.hash(&mut hasher);
-            hasher.finish()
-        };
+        let token_id_hash = crate::intern::token_id_hash(&delta.token_id);
 
         Ok(Self {
             token_id_hash,
@@ -569,10 +695,138 @@ pub struct ApiCredentials {
     pub passphrase: String,
 }
 
+/// Validated ERC-1155 token id.
+///
+/// The decimal format is checked once on construction, so order arguments
+/// can never carry an empty or malformed id. Clones share the string and the
+/// parsed [`U256`] is kept alongside it.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TokenId {
+    id: Arc<str>,
+    value: U256,
+}
+
+impl TokenId {
+    /// Parse a decimal token id
+    pub fn new(token_id: &str) -> crate::errors::Result<Self> {
+        let value = crate::utils::address::token_id_to_u256(token_id)?;
+        Ok(Self {
+            id: Arc::from(token_id),
+            value,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    pub fn to_u256(&self) -> U256 {
+        self.value
+    }
+}
+
+impl PartialEq for TokenId {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for TokenId {}
+
+impl std::hash::Hash for TokenId {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl PartialEq<str> for TokenId {
+    fn eq(&self, other: &str) -> bool {
+        &*self.id == other
+    }
+}
+
+impl PartialEq<&str> for TokenId {
+    fn eq(&self, other: &&str) -> bool {
+        &*self.id == *other
+    }
+}
+
+impl std::ops::Deref for TokenId {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl AsRef<str> for TokenId {
+    fn as_ref(&self) -> &str {
+        &self.id
+    }
+}
+
+impl std::borrow::Borrow<str> for TokenId {
+    fn borrow(&self) -> &str {
+        &self.id
+    }
+}
+
+impl std::fmt::Debug for TokenId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&*self.id, f)
+    }
+}
+
+impl std::fmt::Display for TokenId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.id)
+    }
+}
+
+impl std::str::FromStr for TokenId {
+    type Err = crate::errors::PolyfillError;
+
+    fn from_str(token_id: &str) -> crate::errors::Result<Self> {
+        Self::new(token_id)
+    }
+}
+
+impl TryFrom<&str> for TokenId {
+    type Error = crate::errors::PolyfillError;
+
+    fn try_from(token_id: &str) -> crate::errors::Result<Self> {
+        Self::new(token_id)
+    }
+}
+
+impl TryFrom<String> for TokenId {
+    type Error = crate::errors::PolyfillError;
+
+    fn try_from(token_id: String) -> crate::errors::Result<Self> {
+        Self::new(&token_id)
+    }
+}
+
+impl From<U256> for TokenId {
+    fn from(value: U256) -> Self {
+        Self {
+            id: Arc::from(value.to_string()),
+            value,
+        }
+    }
+}
+
+impl From<TokenId> for String {
+    fn from(token_id: TokenId) -> Self {
+        token_id.id.to_string()
+    }
+}
+
 /// Limit order arguments for V2 order creation.
 #[derive(Debug, Clone, PartialEq)]
 pub struct OrderArgs {
-    pub token_id: String,
+    pub token_id: TokenId,
     pub price: Decimal,
     pub size: Decimal,
     pub side: Side,
@@ -582,9 +836,9 @@ pub struct OrderArgs {
 }
 
 impl OrderArgs {
-    pub fn new(token_id: &str, price: Decimal, size: Decimal, side: Side) -> Self {
+    pub fn new(token_id: TokenId, price: Decimal, size: Decimal, side: Side) -> Self {
         Self {
-            token_id: token_id.to_string(),
+            token_id,
             price,
             size,
             side,
@@ -595,24 +849,10 @@ impl OrderArgs {
     }
 }
 
-impl Default for OrderArgs {
-    fn default() -> Self {
-        Self {
-            token_id: String::new(),
-            price: Decimal::ZERO,
-            size: Decimal::ZERO,
-            side: Side::BUY,
-            expiration: None,
-            builder_code: None,
-            metadata: None,
-        }
-    }
-}
-
 /// Market order arguments for V2 order creation.
 #[derive(Debug, Clone, PartialEq)]
 pub struct MarketOrderArgs {
-    pub token_id: String,
+    pub token_id: TokenId,
     pub amount: Decimal,
     pub side: Side,
     pub order_type: OrderType,
@@ -623,9 +863,9 @@ pub struct MarketOrderArgs {
 }
 
 impl MarketOrderArgs {
-    pub fn new(token_id: &str, amount: Decimal, side: Side, order_type: OrderType) -> Self {
+    pub fn new(token_id: TokenId, amount: Decimal, side: Side, order_type: OrderType) -> Self {
         Self {
-            token_id: token_id.to_string(),
+            token_id,
             amount,
             side,
             order_type,
@@ -637,6 +877,32 @@ impl MarketOrderArgs {
     }
 }
 
+/// What to do with the part of a market order the book cannot fill within
+/// its price limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketRemainder {
+    /// Execute what fits and return the rest
+    Return,
+    /// Execute what fits and rest the rest as a GTC limit order at the price
+    /// limit (or the worst price reached when there is none)
+    Rest,
+}
+
+/// Outcome of a market order that may only partly execute
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialMarketOrder {
+    /// Collateral (buys) or shares (sells) asked for
+    pub requested: Decimal,
+    /// Amount the market order executed, as reported by the venue
+    pub executed: Decimal,
+    /// Amount neither executed nor rested
+    pub remainder: Decimal,
+    /// Response to the market order, if one was sent
+    pub execution: Option<PostOrderResponse>,
+    /// Response to the follow-up limit order, if one was sent
+    pub follow_up: Option<PostOrderResponse>,
+}
+
 /// Options used while constructing an order.
 #[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct CreateOrderOptions {
@@ -888,6 +1154,18 @@ pub struct ClientConfig {
     pub timeout: Option<std::time::Duration>,
     /// Maximum number of connections
     pub max_connections: Option<usize>,
+    /// Resolve hosts through this cache instead of on every new connection
+    #[serde(skip)]
+    pub dns_cache: Option<crate::dns::DnsCache>,
+    /// Ask for gzip or deflate encoded responses and decompress them as they
+    /// stream in. Worth turning off only on links where CPU costs more than
+    /// bandwidth, such as co-located hosts.
+    #[serde(default = "default_compression")]
+    pub compression: bool,
+}
+
+fn default_compression() -> bool {
+    true
 }
 
 impl Default for ClientConfig {
@@ -902,6 +1180,8 @@ impl Default for ClientConfig {
             funder: None,
             timeout: Some(std::time::Duration::from_secs(30)),
             max_connections: Some(100),
+            dns_cache: None,
+            compression: true,
         }
     }
 }
@@ -939,6 +1219,16 @@ pub struct WssSubscription {
     pub auth: Option<WssAuth>,
 }
 
+/// Version of the typed WebSocket message model.
+///
+/// Bumped when a modelled field changes meaning. Fields the model does not
+/// know yet are not dropped: each message keeps them in its `extra` map, and
+/// they are written back out on serialization, so recorded messages round-trip.
+pub const STREAM_SCHEMA_VERSION: u32 = 1;
+
+/// JSON fields of a WebSocket message that the typed model does not cover
+pub type UnknownFields = serde_json::Map<String, serde_json::Value>;
+
 /// WebSocket message types for streaming (official Polymarket `event_type` format).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "event_type")]
@@ -975,6 +1265,42 @@ pub enum StreamMessage {
     Unknown,
 }
 
+impl StreamMessage {
+    /// The message's `event_type`; `None` for [`StreamMessage::Unknown`]
+    pub fn event_type(&self) -> Option<crate::decode::StreamEventType> {
+        use crate::decode::StreamEventType;
+        Some(match self {
+            Self::Book(_) => StreamEventType::Book,
+            Self::PriceChange(_) => StreamEventType::PriceChange,
+            Self::TickSizeChange(_) => StreamEventType::TickSizeChange,
+            Self::LastTradePrice(_) => StreamEventType::LastTradePrice,
+            Self::BestBidAsk(_) => StreamEventType::BestBidAsk,
+            Self::NewMarket(_) => StreamEventType::NewMarket,
+            Self::MarketResolved(_) => StreamEventType::MarketResolved,
+            Self::Trade(_) => StreamEventType::Trade,
+            Self::Order(_) => StreamEventType::Order,
+            Self::Unknown => return None,
+        })
+    }
+
+    /// Top-level fields the typed model did not recognise; `None` for
+    /// [`StreamMessage::Unknown`]
+    pub fn extra(&self) -> Option<&UnknownFields> {
+        match self {
+            Self::Book(m) => Some(&m.extra),
+            Self::PriceChange(m) => Some(&m.extra),
+            Self::TickSizeChange(m) => Some(&m.extra),
+            Self::LastTradePrice(m) => Some(&m.extra),
+            Self::BestBidAsk(m) => Some(&m.extra),
+            Self::NewMarket(m) => Some(&m.extra),
+            Self::MarketResolved(m) => Some(&m.extra),
+            Self::Trade(m) => Some(&m.extra),
+            Self::Order(m) => Some(&m.extra),
+            Self::Unknown => None,
+        }
+    }
+}
+
 /// Orderbook update message (full snapshot or delta).
 ///
 /// WebSocket `book` messages expose a millisecond timestamp and optional book hash, but no
@@ -982,6 +1308,7 @@ pub enum StreamMessage {
 /// ordered by websocket arrival order by the book applier; the hash is a duplicate/state
 /// discriminator, not a logical ordering key.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct BookUpdate {
     pub asset_id: String,
     pub market: String,
@@ -998,10 +1325,34 @@ pub struct BookUpdate {
     /// same-timestamp states. It does not encode ordering.
     #[serde(default)]
     pub hash: Option<String>,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
+}
+
+impl BookUpdate {
+    pub fn new(
+        asset_id: impl Into<String>,
+        market: impl Into<String>,
+        timestamp: u64,
+        bids: Vec<OrderSummary>,
+        asks: Vec<OrderSummary>,
+    ) -> Self {
+        Self {
+            asset_id: asset_id.into(),
+            market: market.into(),
+            timestamp,
+            bids,
+            asks,
+            hash: None,
+            extra: UnknownFields::new(),
+        }
+    }
 }
 
 /// Unified wire format for `price_change` events.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct PriceChange {
     pub market: String,
     #[serde(deserialize_with = "crate::decode::deserializers::number_from_string")]
@@ -1011,9 +1362,13 @@ pub struct PriceChange {
         deserialize_with = "crate::decode::deserializers::vec_from_null"
     )]
     pub price_changes: Vec<PriceChangeEntry>,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct PriceChangeEntry {
     pub asset_id: String,
     pub price: Decimal,
@@ -1035,10 +1390,14 @@ pub struct PriceChangeEntry {
         deserialize_with = "crate::decode::deserializers::optional_decimal_from_string"
     )]
     pub best_ask: Option<Decimal>,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
 }
 
 /// Tick size change event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct TickSizeChange {
     pub asset_id: String,
     pub market: String,
@@ -1046,10 +1405,14 @@ pub struct TickSizeChange {
     pub new_tick_size: Decimal,
     #[serde(deserialize_with = "crate::decode::deserializers::number_from_string")]
     pub timestamp: u64,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
 }
 
 /// Last trade price update.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct LastTradePrice {
     pub asset_id: String,
     pub market: String,
@@ -1068,10 +1431,14 @@ pub struct LastTradePrice {
     pub fee_rate_bps: Option<Decimal>,
     #[serde(deserialize_with = "crate::decode::deserializers::number_from_string")]
     pub timestamp: u64,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
 }
 
 /// Best bid/ask update.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct BestBidAsk {
     pub market: String,
     pub asset_id: String,
@@ -1080,10 +1447,14 @@ pub struct BestBidAsk {
     pub spread: Decimal,
     #[serde(deserialize_with = "crate::decode::deserializers::number_from_string")]
     pub timestamp: u64,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
 }
 
 /// New market created event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct NewMarket {
     pub id: String,
     pub question: String,
@@ -1101,10 +1472,14 @@ pub struct NewMarket {
     pub event_message: Option<EventMessage>,
     #[serde(deserialize_with = "crate::decode::deserializers::number_from_string")]
     pub timestamp: u64,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
 }
 
 /// Market resolved event.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct MarketResolved {
     pub id: String,
     #[serde(default)]
@@ -1127,20 +1502,28 @@ pub struct MarketResolved {
     pub event_message: Option<EventMessage>,
     #[serde(deserialize_with = "crate::decode::deserializers::number_from_string")]
     pub timestamp: u64,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
 }
 
 /// Event message object for market events.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct EventMessage {
     pub id: String,
     pub ticker: String,
     pub slug: String,
     pub title: String,
     pub description: String,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
 }
 
 /// User trade execution message.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct TradeMessage {
     pub id: String,
     pub market: String,
@@ -1168,10 +1551,40 @@ pub struct TradeMessage {
         deserialize_with = "crate::decode::deserializers::optional_number_from_string"
     )]
     pub timestamp: Option<u64>,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
+}
+
+impl TradeMessage {
+    pub fn new(
+        id: impl Into<String>,
+        market: impl Into<String>,
+        asset_id: impl Into<String>,
+        side: Side,
+        size: Decimal,
+        price: Decimal,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            market: market.into(),
+            asset_id: asset_id.into(),
+            side,
+            size,
+            price,
+            status: None,
+            msg_type: None,
+            last_update: None,
+            matchtime: None,
+            timestamp: None,
+            extra: UnknownFields::new(),
+        }
+    }
 }
 
 /// User order update message.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct OrderMessage {
     pub id: String,
     pub market: String,
@@ -1199,6 +1612,15 @@ pub struct OrderMessage {
     pub associate_trades: Option<Vec<String>>,
     #[serde(default)]
     pub status: Option<String>,
+    /// GTD expiration in seconds since the epoch; `0` for none
+    #[serde(
+        default,
+        deserialize_with = "crate::decode::deserializers::optional_number_from_string"
+    )]
+    pub expiration: Option<u64>,
+    /// Fields not covered by the model, kept for forward compatibility
+    #[serde(flatten, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: UnknownFields,
 }
 
 /// Subscription parameters for streaming
@@ -1255,7 +1677,6 @@ pub struct Metrics {
 }
 
 // Type aliases for common patterns
-pub type TokenId = String;
 pub type OrderId = String;
 pub type MarketId = String;
 pub type ClientId = String;
@@ -1366,6 +1787,130 @@ pub struct BalanceAllowance {
     pub allowance: Decimal,
 }
 
+/// Position as reported by the data API `/positions` endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataApiPosition {
+    #[serde(default)]
+    pub proxy_wallet: String,
+    /// Token ID
+    pub asset: String,
+    #[serde(default)]
+    pub condition_id: String,
+    pub size: Decimal,
+    #[serde(default)]
+    pub avg_price: Decimal,
+    #[serde(default)]
+    pub initial_value: Decimal,
+    #[serde(default)]
+    pub current_value: Decimal,
+    #[serde(default)]
+    pub cash_pnl: Decimal,
+    #[serde(default)]
+    pub cur_price: Decimal,
+    #[serde(default)]
+    pub redeemable: bool,
+    #[serde(default)]
+    pub outcome: String,
+    /// Whether the market settles through the neg-risk adapter
+    #[serde(default)]
+    pub negative_risk: bool,
+}
+
+/// What [`crate::ClobClient::account_summary`] gathers
+#[derive(Debug, Clone)]
+pub struct AccountSummaryOptions {
+    /// Fills newer than this are included
+    pub fills_lookback: std::time::Duration,
+    /// Wallet whose data API positions are included; positions are skipped if `None`
+    pub positions_user: Option<String>,
+    /// Data API base URL for positions
+    pub data_api_url: String,
+}
+
+impl Default for AccountSummaryOptions {
+    fn default() -> Self {
+        Self {
+            fills_lookback: std::time::Duration::from_secs(24 * 60 * 60),
+            positions_user: None,
+            data_api_url: crate::backfill::DEFAULT_DATA_API_URL.to_string(),
+        }
+    }
+}
+
+impl AccountSummaryOptions {
+    /// Include data API positions held by `user` (usually the proxy wallet)
+    pub fn with_positions(mut self, user: impl Into<String>) -> Self {
+        self.positions_user = Some(user.into());
+        self
+    }
+}
+
+/// Snapshot of an account's balances, orders, fills and positions
+#[derive(Debug, Clone)]
+pub struct AccountSummary {
+    /// Raw collateral `/balance-allowance` response
+    pub balance_allowance: serde_json::Value,
+    pub open_orders: Vec<OpenOrder>,
+    /// Raw `/data/trades` entries within the lookback window
+    pub recent_fills: Vec<serde_json::Value>,
+    /// `None` unless positions were requested
+    pub positions: Option<Vec<DataApiPosition>>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// How [`crate::ClobClient::safe_shutdown`] waits for its cancels
+#[derive(Debug, Clone)]
+pub struct ShutdownOptions {
+    /// How long to wait for cancelled orders to leave the book
+    pub timeout: std::time::Duration,
+    /// Delay between checks of the open orders
+    pub poll_interval: std::time::Duration,
+}
+
+impl Default for ShutdownOptions {
+    fn default() -> Self {
+        Self {
+            timeout: std::time::Duration::from_secs(10),
+            poll_interval: std::time::Duration::from_millis(500),
+        }
+    }
+}
+
+impl ShutdownOptions {
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: std::time::Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+/// What happened to each open order during a shutdown
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownReport {
+    /// Orders open when the shutdown started
+    pub open_orders: Vec<OpenOrder>,
+    /// Orders the exchange confirmed as cancelled
+    pub cancelled: Vec<String>,
+    /// Orders the exchange refused to cancel, with its reason
+    pub not_cancelled: std::collections::HashMap<String, String>,
+    /// Final state of orders that matched while shutting down
+    pub filled: Vec<OpenOrder>,
+    /// Orders still open when the timeout ran out
+    pub still_open: Vec<OpenOrder>,
+}
+
+impl ShutdownReport {
+    /// Every open order was cancelled and none is left on the book
+    pub fn is_clean(&self) -> bool {
+        self.not_cancelled.is_empty() && self.still_open.is_empty()
+    }
+}
+
 /// Parameters for balance allowance queries (from reference implementation)
 #[derive(Default)]
 pub struct BalanceAllowanceParams {
@@ -1564,6 +2109,48 @@ pub struct OrderBookSummary {
     pub last_trade_price: Option<Decimal>,
 }
 
+impl OrderBookSummary {
+    /// Keep only the best `depth` levels per side, in their original order
+    pub fn truncate_depth(&mut self, depth: usize) {
+        keep_best_levels(&mut self.bids, depth, |a, b| b.cmp(a));
+        keep_best_levels(&mut self.asks, depth, |a, b| a.cmp(b));
+    }
+}
+
+/// Retain the `depth` levels that sort first under `better`
+fn keep_best_levels(
+    levels: &mut Vec<OrderSummary>,
+    depth: usize,
+    better: impl Fn(&Decimal, &Decimal) -> std::cmp::Ordering,
+) {
+    if levels.len() <= depth {
+        return;
+    }
+    if depth == 0 {
+        levels.clear();
+        return;
+    }
+    let mut prices: Vec<Decimal> = levels.iter().map(|level| level.price).collect();
+    let (_, cutoff, _) = prices.select_nth_unstable_by(depth - 1, &better);
+    let cutoff = *cutoff;
+    let mut kept = 0;
+    levels.retain(|level| {
+        let keep = kept < depth && better(&level.price, &cutoff).is_le();
+        kept += keep as usize;
+        keep
+    });
+}
+
+/// Levels per side to fetch with [`crate::ClobClient::get_order_book_with_depth`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BookDepth {
+    /// Every level the exchange holds
+    #[default]
+    Full,
+    /// Only the best `n` levels per side
+    Top(usize),
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderSummary {
     #[serde(with = "rust_decimal::serde::str")]
@@ -1987,4 +2574,68 @@ mod tests {
         assert_eq!(book.last_delta_sequence, 42);
         assert_eq!(book.last_snapshot_timestamp_ms, 0);
     }
+
+    #[test]
+    fn side_and_order_type_parse_any_casing() {
+        for raw in ["\"BUY\"", "\"buy\"", "\"Buy\""] {
+            assert_eq!(serde_json::from_str::<Side>(raw).unwrap(), Side::BUY);
+        }
+        assert_eq!(
+            serde_json::from_str::<OrderType>("\"fak\"").unwrap(),
+            OrderType::FAK
+        );
+        assert!(serde_json::from_str::<Side>("\"hold\"").is_err());
+        assert_eq!(serde_json::to_string(&Side::SELL).unwrap(), "\"SELL\"");
+
+        #[derive(Serialize, Deserialize)]
+        struct Lower {
+            #[serde(with = "lowercase")]
+            side: Side,
+            #[serde(with = "lowercase")]
+            order_type: OrderType,
+        }
+        let parsed: Lower = serde_json::from_str(r#"{"side":"SELL","order_type":"GTD"}"#).unwrap();
+        assert_eq!(parsed.side, Side::SELL);
+        assert_eq!(
+            serde_json::to_string(&parsed).unwrap(),
+            r#"{"side":"sell","order_type":"gtd"}"#
+        );
+    }
+
+    #[test]
+    fn token_id_validates_and_converts() {
+        for bad in ["", "0x12", "12a", " 12", "-1"] {
+            assert!(
+                matches!(
+                    TokenId::new(bad),
+                    Err(crate::errors::PolyfillError::Validation { .. })
+                ),
+                "{bad:?}"
+            );
+            assert!(bad.parse::<TokenId>().is_err());
+        }
+
+        let raw = "71321045679252212594626385532706912750332728571942532289631379312455583992563";
+        let token: TokenId = raw.parse().unwrap();
+        assert_eq!(token.to_string(), raw);
+        assert_eq!(token.to_string().parse::<TokenId>().unwrap(), token);
+        assert_eq!(token.to_u256(), U256::from_str_radix(raw, 10).unwrap());
+        assert_eq!(token, raw);
+
+        let from_value = TokenId::from(U256::from(42u64));
+        assert_eq!(from_value.as_str(), "42");
+        assert_eq!(from_value, TokenId::new("42").unwrap());
+        assert_eq!(from_value.to_u256(), U256::from(42u64));
+
+        let json = serde_json::to_string(&token).unwrap();
+        assert_eq!(json, format!("\"{raw}\""));
+        assert_eq!(serde_json::from_str::<TokenId>(&json).unwrap(), token);
+        let err = serde_json::from_str::<TokenId>("\"\"").unwrap_err();
+        assert!(
+            err.to_string().contains("Token ID cannot be empty"),
+            "{err}"
+        );
+        assert!(serde_json::from_str::<TokenId>("\"abc\"").is_err());
+        assert!(serde_json::from_str::<TokenId>("12").is_err());
+    }
 }
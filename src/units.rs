@@ -0,0 +1,166 @@
+//! Typed price units
+//!
+//! The same price shows up as `0.75` on the wire, `75` cents on a UI, `75%`
+//! in a report and `7500` ticks inside the book. Passing a bare [`Decimal`]
+//! between those layers makes it easy to mix them up. [`Probability`],
+//! [`Cents`] and [`Percent`] carry the unit in the type, convert between
+//! each other explicitly, and the typed helpers in [`crate::utils::math`]
+//! only accept the unit they expect.
+
+use crate::errors::{PolyfillError, Result};
+use crate::types::{decimal_to_price, price_to_decimal, Price};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A price as a probability in `[0, 1]`, the unit orders are placed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Decimal", into = "Decimal")]
+pub struct Probability(Decimal);
+
+impl Probability {
+    pub const ZERO: Self = Self(Decimal::ZERO);
+    pub const ONE: Self = Self(Decimal::ONE);
+
+    /// Fails unless `value` is in `[0, 1]`; catches `75` passed for `0.75`
+    pub fn new(value: Decimal) -> Result<Self> {
+        if value < Decimal::ZERO || value > Decimal::ONE {
+            return Err(PolyfillError::validation(format!(
+                "Probability {value} is outside [0, 1]"
+            )));
+        }
+        Ok(Self(value))
+    }
+
+    pub fn value(self) -> Decimal {
+        self.0
+    }
+
+    /// Probability of the other outcome
+    pub fn complement(self) -> Self {
+        Self(Decimal::ONE - self.0)
+    }
+
+    pub fn to_cents(self) -> Cents {
+        Cents(self.0 * Decimal::ONE_HUNDRED)
+    }
+
+    pub fn to_percent(self) -> Percent {
+        Percent(self.0 * Decimal::ONE_HUNDRED)
+    }
+
+    /// Fixed-point ticks as used by the order book
+    pub fn to_ticks(self) -> Result<Price> {
+        decimal_to_price(self.0).map_err(PolyfillError::validation)
+    }
+
+    pub fn from_ticks(ticks: Price) -> Result<Self> {
+        Self::new(price_to_decimal(ticks))
+    }
+}
+
+impl TryFrom<Decimal> for Probability {
+    type Error = PolyfillError;
+
+    fn try_from(value: Decimal) -> Result<Self> {
+        Self::new(value)
+    }
+}
+
+impl From<Probability> for Decimal {
+    fn from(probability: Probability) -> Self {
+        probability.0
+    }
+}
+
+impl fmt::Display for Probability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A price in cents per share, `0..=100` for a valid probability
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Cents(Decimal);
+
+impl Cents {
+    pub fn new(value: Decimal) -> Self {
+        Self(value)
+    }
+
+    pub fn value(self) -> Decimal {
+        self.0
+    }
+
+    /// Fails unless the amount is a valid probability
+    pub fn to_probability(self) -> Result<Probability> {
+        Probability::new(self.0 / Decimal::ONE_HUNDRED)
+    }
+}
+
+impl fmt::Display for Cents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}¢", self.0)
+    }
+}
+
+/// A percentage, e.g. `2.5` for 2.5%
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Percent(Decimal);
+
+impl Percent {
+    pub fn new(value: Decimal) -> Self {
+        Self(value)
+    }
+
+    /// From a fraction, e.g. `0.025` for 2.5%
+    pub fn from_fraction(fraction: Decimal) -> Self {
+        Self(fraction * Decimal::ONE_HUNDRED)
+    }
+
+    pub fn value(self) -> Decimal {
+        self.0
+    }
+
+    pub fn fraction(self) -> Decimal {
+        self.0 / Decimal::ONE_HUNDRED
+    }
+
+    /// Fails unless the percentage is a valid probability
+    pub fn to_probability(self) -> Result<Probability> {
+        Probability::new(self.fraction())
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_unit_conversions_round_trip() {
+        let p = Probability::new(dec!(0.75)).unwrap();
+        assert_eq!(p.to_cents(), Cents::new(dec!(75)));
+        assert_eq!(p.to_percent().to_string(), "75.00%");
+        assert_eq!(p.to_ticks().unwrap(), 7500);
+        assert_eq!(Probability::from_ticks(7500).unwrap(), p);
+        assert_eq!(Cents::new(dec!(75)).to_probability().unwrap(), p);
+        assert_eq!(p.complement().value(), dec!(0.25));
+        assert_eq!(Percent::from_fraction(dec!(0.025)).value(), dec!(2.5));
+
+        // The classic mix-up
+        assert!(Probability::new(dec!(75)).is_err());
+        assert!(Cents::new(dec!(7500)).to_probability().is_err());
+        assert!(serde_json::from_str::<Probability>("\"75\"").is_err());
+        let json = serde_json::to_string(&p).unwrap();
+        assert_eq!(serde_json::from_str::<Probability>(&json).unwrap(), p);
+    }
+}
@@ -35,7 +35,7 @@ pub mod time {
     pub fn now_millis() -> u64 {
         SystemTime::now()
             .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
+            .unwrap_or_default()
             .as_millis() as u64
     }
 
@@ -127,6 +127,7 @@ pub mod crypto {
 pub mod math {
     use super::*;
     use crate::types::{Price, Qty, SCALE_FACTOR};
+    use crate::units::{Cents, Percent, Probability};
     use rust_decimal::prelude::*;
 
     // ========================================================================
@@ -296,6 +297,39 @@ pub mod math {
         price >= tick_size && price <= (Decimal::ONE - tick_size)
     }
 
+    // ========================================================================
+    // TYPED UNIT FUNCTIONS
+    // ========================================================================
+    //
+    // Same calculations over crate::units types, so a percent or cents value
+    // cannot be passed where a probability is expected.
+
+    /// Round a probability to a tick size given as a probability
+    pub fn round_probability_to_tick(price: Probability, tick_size: Probability) -> Probability {
+        let rounded = round_to_tick(price.value(), tick_size.value());
+        Probability::new(rounded.clamp(Decimal::ZERO, Decimal::ONE)).unwrap_or(price)
+    }
+
+    /// Mid price of a bid and ask
+    pub fn mid_probability(bid: Probability, ask: Probability) -> Option<Probability> {
+        mid_price(bid.value(), ask.value()).and_then(|mid| Probability::new(mid).ok())
+    }
+
+    /// Spread of a bid and ask in cents
+    pub fn spread_cents(bid: Probability, ask: Probability) -> Option<Cents> {
+        (ask > bid).then(|| Cents::new((ask.value() - bid.value()) * Decimal::ONE_HUNDRED))
+    }
+
+    /// Spread of a bid and ask as a percentage of the bid
+    pub fn spread_percent(bid: Probability, ask: Probability) -> Option<Percent> {
+        spread_pct(bid.value(), ask.value()).map(Percent::new)
+    }
+
+    /// Check a probability against `[tick_size, 1 - tick_size]`
+    pub fn is_valid_probability(price: Probability, tick_size: Probability) -> bool {
+        is_valid_price(price.value(), tick_size.value())
+    }
+
     /// Calculate maximum slippage for market order
     pub fn calculate_slippage(
         target_price: Decimal,
@@ -326,6 +360,7 @@ pub mod retry {
     use super::*;
     use std::future::Future;
     use tokio::time::{sleep, Duration};
+    use tokio_util::sync::CancellationToken;
 
     /// Exponential backoff configuration
     #[derive(Debug, Clone)]
@@ -349,8 +384,154 @@ pub mod retry {
         }
     }
 
+    impl RetryConfig {
+        /// Backoff before retry number `retry` (1 for the first retry)
+        pub fn backoff(&self, retry: u32) -> Duration {
+            let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+            let delay = self.initial_delay.as_secs_f64() * self.backoff_factor.powi(exponent);
+            let delay = delay.min(self.max_delay.as_secs_f64());
+            let delay = if self.jitter {
+                delay * (0.95 + rand::random::<f64>() * 0.1) // ±5%
+            } else {
+                delay
+            };
+            Duration::from_secs_f64(delay.max(0.0))
+        }
+    }
+
+    /// How safe it is to repeat a request
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum EndpointClass {
+        /// Reads, including batch reads sent as POST
+        Read,
+        /// Order placement and other non-idempotent writes
+        Write,
+        /// Cancels, which can be repeated safely
+        Cancel,
+    }
+
+    /// Caps retries at a fraction of requests, so a struggling venue is not
+    /// hit with multiplied load.
+    ///
+    /// Every request deposits `ratio` tokens and every retry spends one. The
+    /// balance starts at, and never exceeds, `reserve`, which allows short
+    /// bursts of retries after a quiet period.
+    #[derive(Debug)]
+    pub struct RetryBudget {
+        ratio: f64,
+        reserve: f64,
+        balance: parking_lot::Mutex<f64>,
+    }
+
+    impl Default for RetryBudget {
+        fn default() -> Self {
+            Self::new(0.1, 10)
+        }
+    }
+
+    impl RetryBudget {
+        pub fn new(ratio: f64, reserve: u32) -> Self {
+            Self {
+                ratio: ratio.max(0.0),
+                reserve: reserve as f64,
+                balance: parking_lot::Mutex::new(reserve as f64),
+            }
+        }
+
+        /// Credit one request
+        pub fn deposit(&self) {
+            let mut balance = self.balance.lock();
+            *balance = (*balance + self.ratio).min(self.reserve);
+        }
+
+        /// Spend one retry, if the budget allows it
+        pub fn try_withdraw(&self) -> bool {
+            let mut balance = self.balance.lock();
+            if *balance < 1.0 {
+                return false;
+            }
+            *balance -= 1.0;
+            true
+        }
+
+        pub fn available(&self) -> f64 {
+            *self.balance.lock()
+        }
+    }
+
+    /// Per-class retry settings sharing one [`RetryBudget`]
+    #[derive(Debug, Clone)]
+    pub struct RetryPolicy {
+        pub read: RetryConfig,
+        pub write: RetryConfig,
+        pub cancel: RetryConfig,
+        pub budget: std::sync::Arc<RetryBudget>,
+    }
+
+    impl Default for RetryPolicy {
+        /// Retries reads and cancels; never repeats order placement
+        fn default() -> Self {
+            Self {
+                read: RetryConfig::default(),
+                write: RetryConfig {
+                    max_attempts: 1,
+                    ..RetryConfig::default()
+                },
+                cancel: RetryConfig::default(),
+                budget: std::sync::Arc::new(RetryBudget::default()),
+            }
+        }
+    }
+
+    impl RetryPolicy {
+        pub fn with_read(mut self, config: RetryConfig) -> Self {
+            self.read = config;
+            self
+        }
+
+        pub fn with_write(mut self, config: RetryConfig) -> Self {
+            self.write = config;
+            self
+        }
+
+        pub fn with_cancel(mut self, config: RetryConfig) -> Self {
+            self.cancel = config;
+            self
+        }
+
+        /// Share `budget` with other clients, e.g. all clients of a process
+        pub fn with_budget(mut self, budget: std::sync::Arc<RetryBudget>) -> Self {
+            self.budget = budget;
+            self
+        }
+
+        pub fn config(&self, class: EndpointClass) -> &RetryConfig {
+            match class {
+                EndpointClass::Read => &self.read,
+                EndpointClass::Write => &self.write,
+                EndpointClass::Cancel => &self.cancel,
+            }
+        }
+    }
+
     /// Retry a future with exponential backoff
-    pub async fn with_retry<F, Fut, T>(config: &RetryConfig, mut operation: F) -> Result<T>
+    pub async fn with_retry<F, Fut, T>(config: &RetryConfig, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        with_retry_cancellable(config, &CancellationToken::new(), operation).await
+    }
+
+    /// Retry with backoff until `cancel` fires.
+    ///
+    /// Cancellation aborts an in-flight attempt or a backoff sleep
+    /// immediately and returns [`PolyfillError::Cancelled`].
+    pub async fn with_retry_cancellable<F, Fut, T>(
+        config: &RetryConfig,
+        cancel: &CancellationToken,
+        mut operation: F,
+    ) -> Result<T>
     where
         F: FnMut() -> Fut,
         Fut: Future<Output = Result<T>>,
@@ -359,9 +540,12 @@ pub mod retry {
         let mut last_error = None;
 
         for attempt in 0..config.max_attempts {
-            match operation().await {
+            match until_cancelled(cancel, "retry", operation()).await {
                 Ok(result) => return Ok(result),
                 Err(err) => {
+                    if matches!(err, PolyfillError::Cancelled { .. }) {
+                        return Err(err);
+                    }
                     last_error = Some(err.clone());
 
                     if !err.is_retryable() || attempt == config.max_attempts - 1 {
@@ -377,7 +561,11 @@ pub mod retry {
                         delay
                     };
 
-                    sleep(actual_delay).await;
+                    until_cancelled(cancel, "retry backoff", async {
+                        sleep(actual_delay).await;
+                        Ok(())
+                    })
+                    .await?;
 
                     // Exponential backoff
                     delay = std::cmp::min(
@@ -397,6 +585,18 @@ pub mod retry {
             )
         }))
     }
+
+    /// Run `future` unless `cancel` fires first
+    pub async fn until_cancelled<T>(
+        cancel: &CancellationToken,
+        operation: &str,
+        future: impl Future<Output = Result<T>>,
+    ) -> Result<T> {
+        cancel
+            .run_until_cancelled(future)
+            .await
+            .unwrap_or_else(|| Err(PolyfillError::cancelled(operation)))
+    }
 }
 
 /// Address and token ID utilities
@@ -457,6 +657,7 @@ pub mod url {
 /// Rate limiting utilities
 pub mod rate_limit {
     use super::*;
+    use std::collections::HashMap;
     use std::sync::{Arc, Mutex};
 
     /// Simple token bucket rate limiter
@@ -491,6 +692,13 @@ pub mod rate_limit {
             }
         }
 
+        /// Wait until a token is available and consume it
+        pub async fn acquire(&self) {
+            while !self.try_consume() {
+                tokio::time::sleep(self.refill_rate).await;
+            }
+        }
+
         fn refill(&self) {
             let now = SystemTime::now();
             let mut last_refill = self.last_refill.lock().unwrap();
@@ -504,6 +712,42 @@ pub mod rate_limit {
             }
         }
     }
+
+    /// Rate limiters shared per API key.
+    ///
+    /// Every client and component trading under one key should draw from the
+    /// same bucket, since the exchange enforces limits per key rather than
+    /// per connection.
+    #[derive(Debug)]
+    pub struct RateLimitRegistry {
+        capacity: usize,
+        refill_per_second: usize,
+        buckets: Mutex<HashMap<String, Arc<TokenBucket>>>,
+    }
+
+    impl RateLimitRegistry {
+        /// Buckets created by this registry hold `capacity` tokens refilled at
+        /// `refill_per_second`
+        pub fn new(capacity: usize, refill_per_second: usize) -> Self {
+            Self {
+                capacity,
+                refill_per_second,
+                buckets: Mutex::new(HashMap::new()),
+            }
+        }
+
+        /// The shared bucket for `api_key`, created on first use
+        pub fn limiter(&self, api_key: &str) -> Arc<TokenBucket> {
+            self.buckets
+                .lock()
+                .unwrap()
+                .entry(api_key.to_string())
+                .or_insert_with(|| {
+                    Arc::new(TokenBucket::new(self.capacity, self.refill_per_second))
+                })
+                .clone()
+        }
+    }
 }
 
 #[cfg(test)]
@@ -530,6 +774,24 @@ mod tests {
         assert_eq!(mid, Decimal::from_str("0.51").unwrap());
     }
 
+    #[test]
+    fn test_typed_math() {
+        use crate::units::{Cents, Probability};
+        use math::{mid_probability, round_probability_to_tick, spread_cents, spread_percent};
+
+        let p = |s: &str| Probability::new(Decimal::from_str(s).unwrap()).unwrap();
+        assert_eq!(mid_probability(p("0.40"), p("0.44")), Some(p("0.42")));
+        assert_eq!(
+            spread_cents(p("0.40"), p("0.44")),
+            Some(Cents::new(Decimal::from(4)))
+        );
+        assert_eq!(
+            spread_percent(p("0.40"), p("0.44")).unwrap().value(),
+            Decimal::from(10)
+        );
+        assert_eq!(round_probability_to_tick(p("0.567"), p("0.01")), p("0.57"));
+    }
+
     #[test]
     fn test_token_units_conversion() {
         use math::{decimal_to_token_units, token_units_to_decimal};
@@ -552,4 +814,51 @@ mod tests {
         let invalid = "invalid_address";
         assert!(parse_address(invalid).is_err());
     }
+
+    #[test]
+    fn test_retry_budget_limits_retries() {
+        use retry::RetryBudget;
+
+        let budget = RetryBudget::new(0.5, 2);
+        assert!(budget.try_withdraw());
+        assert!(budget.try_withdraw());
+        assert!(!budget.try_withdraw());
+        budget.deposit();
+        assert!(!budget.try_withdraw());
+        budget.deposit();
+        assert!(budget.try_withdraw());
+        // Never above the reserve
+        for _ in 0..10 {
+            budget.deposit();
+        }
+        assert_eq!(budget.available(), 2.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_cancelled_during_backoff() {
+        use retry::{with_retry_cancellable, RetryConfig};
+        use tokio_util::sync::CancellationToken;
+
+        let config = RetryConfig {
+            max_attempts: 10,
+            initial_delay: Duration::from_secs(60),
+            jitter: false,
+            ..RetryConfig::default()
+        };
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            trigger.cancel();
+        });
+
+        let mut attempts = 0;
+        let result: Result<()> = with_retry_cancellable(&config, &cancel, || {
+            attempts += 1;
+            async { Err(PolyfillError::rate_limit("slow down")) }
+        })
+        .await;
+        assert!(matches!(result, Err(PolyfillError::Cancelled { .. })));
+        assert_eq!(attempts, 1);
+    }
 }
@@ -0,0 +1,240 @@
+//! Market watchlists with hot reload
+//!
+//! A [`Watchlist`] names the tokens a process streams books for, either
+//! directly or grouped under a label:
+//!
+//! ```toml
+//! tokens = ["123", "456"]
+//!
+//! [markets]
+//! election = ["789", "790"]
+//! ```
+//!
+//! JSON files use the same shape. TOML needs the `toml` feature; the format
+//! is picked from the file extension.
+//!
+//! [`WatchlistWatcher`] re-reads the file and reports each change as a
+//! [`WatchlistChange`], which [`WatchlistChange::apply`] turns into
+//! market-channel subscribes and unsubscribes and book creation and removal,
+//! so coverage can change without restarting the process.
+
+use crate::book::OrderBookManager;
+use crate::errors::{PolyfillError, Result};
+use crate::stream::WebSocketStream;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// Tokens to keep books and subscriptions for
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Watchlist {
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    /// Tokens grouped by a label of the operator's choosing
+    #[serde(default)]
+    pub markets: BTreeMap<String, Vec<String>>,
+}
+
+impl Watchlist {
+    pub fn from_json_str(s: &str) -> Result<Self> {
+        serde_json::from_str(s)
+            .map_err(|e| PolyfillError::parse(format!("Invalid watchlist: {e}"), Some(Box::new(e))))
+    }
+
+    /// Parse a TOML watchlist; needs the `toml` feature
+    pub fn from_toml_str(s: &str) -> Result<Self> {
+        let value = crate::client_config::parse_toml(s)?;
+        serde_json::from_value(value)
+            .map_err(|e| PolyfillError::parse(format!("Invalid watchlist: {e}"), Some(Box::new(e))))
+    }
+
+    /// Read a `.toml` or `.json` watchlist
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            PolyfillError::internal(format!("Failed to read watchlist {}", path.display()), e)
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml_str(&contents),
+            _ => Self::from_json_str(&contents),
+        }
+    }
+
+    /// Every token on the list, labelled or not
+    pub fn token_ids(&self) -> BTreeSet<String> {
+        self.tokens
+            .iter()
+            .chain(self.markets.values().flatten())
+            .cloned()
+            .collect()
+    }
+
+    /// Tokens to add and remove to go from `self` to `next`
+    pub fn diff(&self, next: &Watchlist) -> WatchlistChange {
+        let current = self.token_ids();
+        let next = next.token_ids();
+        WatchlistChange {
+            added: next.difference(&current).cloned().collect(),
+            removed: current.difference(&next).cloned().collect(),
+        }
+    }
+}
+
+/// Tokens that joined or left a watchlist
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WatchlistChange {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl WatchlistChange {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Unsubscribe and drop the books of removed tokens, then create books
+    /// for added tokens and subscribe to them.
+    ///
+    /// New books fill from the subscription's initial book snapshot.
+    pub async fn apply(
+        &self,
+        stream: &mut WebSocketStream,
+        books: &OrderBookManager,
+    ) -> Result<()> {
+        if !self.removed.is_empty() {
+            stream
+                .unsubscribe_market_channel(self.removed.clone())
+                .await?;
+            for token_id in &self.removed {
+                books.remove_book(token_id);
+            }
+        }
+        if !self.added.is_empty() {
+            for token_id in &self.added {
+                books.get_or_create_book(token_id)?;
+            }
+            stream.subscribe_market_channel(self.added.clone()).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Watches a watchlist file for changes
+#[derive(Debug)]
+pub struct WatchlistWatcher {
+    path: PathBuf,
+    current: Watchlist,
+}
+
+impl WatchlistWatcher {
+    /// Load the watchlist at `path`; fails if it cannot be read
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let current = Watchlist::load(&path)?;
+        Ok(Self { path, current })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The last watchlist loaded
+    pub fn watchlist(&self) -> &Watchlist {
+        &self.current
+    }
+
+    /// Re-read the file; returns what changed since the last load, if
+    /// anything. An unreadable or invalid file leaves the watchlist as is.
+    pub fn poll(&mut self) -> Result<Option<WatchlistChange>> {
+        let next = Watchlist::load(&self.path)?;
+        let change = self.current.diff(&next);
+        self.current = next;
+        Ok((!change.is_empty()).then_some(change))
+    }
+
+    /// Check the file every `interval` until the receiver is dropped.
+    ///
+    /// Files that fail to load are logged and checked again at the next tick.
+    pub async fn run(
+        mut self,
+        interval: Duration,
+        changes: mpsc::UnboundedSender<WatchlistChange>,
+    ) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        // The first tick completes immediately; the initial list is already loaded
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            if changes.is_closed() {
+                return;
+            }
+            match self.poll() {
+                Ok(Some(change)) => {
+                    info!(
+                        "Watchlist {} changed: {} added, {} removed",
+                        self.path.display(),
+                        change.added.len(),
+                        change.removed.len()
+                    );
+                    if changes.send(change).is_err() {
+                        return;
+                    }
+                },
+                Ok(None) => {},
+                Err(e) => warn!("Watchlist reload failed: {}", e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watcher_reports_changes_and_keeps_last_good_list() {
+        let dir = std::env::temp_dir().join(format!("polyfill-watch-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("watchlist.json");
+        std::fs::write(
+            &path,
+            r#"{"tokens":["1","2"],"markets":{"election":["3"]}}"#,
+        )
+        .unwrap();
+
+        let mut watcher = WatchlistWatcher::new(&path).unwrap();
+        assert_eq!(watcher.watchlist().token_ids().len(), 3);
+        assert_eq!(watcher.poll().unwrap(), None);
+
+        std::fs::write(
+            &path,
+            r#"{"tokens":["2"],"markets":{"election":["3","4"]}}"#,
+        )
+        .unwrap();
+        let change = watcher.poll().unwrap().unwrap();
+        assert_eq!(change.added, vec!["4".to_string()]);
+        assert_eq!(change.removed, vec!["1".to_string()]);
+
+        std::fs::write(&path, r#"{"tokens":"#).unwrap();
+        assert!(watcher.poll().is_err());
+        assert_eq!(watcher.watchlist().tokens, vec!["2".to_string()]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_watchlist_matches_json() {
+        let toml =
+            Watchlist::from_toml_str("tokens = [\"1\"]\n\n[markets]\nelection = [\"2\", \"3\"]\n")
+                .unwrap();
+        let json = Watchlist::from_json_str(r#"{"tokens":["1"],"markets":{"election":["2","3"]}}"#)
+            .unwrap();
+        assert_eq!(toml, json);
+    }
+}
@@ -0,0 +1,377 @@
+//! Webhook forwarding for account events
+//!
+//! [`WebhookForwarder`] POSTs fill, order and market resolution events to a
+//! user-configured HTTP endpoint so external systems (risk dashboards, chat
+//! bridges) can consume account activity without linking Rust.
+//!
+//! Each request carries an `X-Polyfill-Timestamp` header and, when a secret is
+//! configured, an `X-Polyfill-Signature` header of the form `sha256=<hex>`,
+//! computed as HMAC-SHA256 over `"{timestamp}.{body}"`.
+
+use crate::errors::{PolyfillError, Result};
+use crate::handlers::EventHandlers;
+use crate::types::{MarketResolved, OrderMessage, StreamMessage, TradeMessage};
+use crate::utils::retry::{with_retry_cancellable, RetryConfig};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+/// Header carrying the Unix timestamp (milliseconds) the payload was signed at
+pub const TIMESTAMP_HEADER: &str = "X-Polyfill-Timestamp";
+
+/// Header carrying the HMAC-SHA256 signature of the payload
+pub const SIGNATURE_HEADER: &str = "X-Polyfill-Signature";
+
+/// Webhook forwarder configuration
+#[derive(Debug, Clone)]
+pub struct WebhookConfig {
+    /// Endpoint receiving the POSTed events
+    pub url: String,
+    /// Shared secret used to sign payloads (unsigned if `None`)
+    pub secret: Option<String>,
+    /// Per-request timeout
+    pub timeout: Duration,
+    /// Retry policy for failed deliveries
+    pub retry: RetryConfig,
+    /// Events buffered by [`WebhookForwarder::attach`] while the endpoint
+    /// is slow; further events are dropped once it is full
+    pub queue_capacity: usize,
+}
+
+impl WebhookConfig {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            secret: None,
+            timeout: Duration::from_secs(5),
+            retry: RetryConfig::default(),
+            queue_capacity: 1024,
+        }
+    }
+
+    /// Sign payloads with the given shared secret
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        self.secret = Some(secret.into());
+        self
+    }
+}
+
+/// Account event delivered to the webhook
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    Fill(TradeMessage),
+    Order(OrderMessage),
+    Resolution(MarketResolved),
+}
+
+impl WebhookEvent {
+    /// Convert a stream message into a webhook event, if it is forwardable
+    pub fn from_stream_message(message: &StreamMessage) -> Option<Self> {
+        match message {
+            StreamMessage::Trade(trade) => Some(Self::Fill(trade.clone())),
+            StreamMessage::Order(order) => Some(Self::Order(order.clone())),
+            StreamMessage::MarketResolved(resolved) => Some(Self::Resolution(resolved.clone())),
+            _ => None,
+        }
+    }
+}
+
+/// Sign a webhook payload, returning the `sha256=<hex>` header value
+pub fn sign_payload(secret: &str, timestamp: u64, body: &[u8]) -> Result<String> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| PolyfillError::internal("HMAC initialization failed", e))?;
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    Ok(format!(
+        "sha256={}",
+        alloy_primitives::hex::encode(mac.finalize().into_bytes())
+    ))
+}
+
+/// Forwards account events to an HTTP endpoint with retries and signing
+#[derive(Debug, Clone)]
+pub struct WebhookForwarder {
+    http_client: reqwest::Client,
+    config: WebhookConfig,
+    cancel: CancellationToken,
+    dropped: Arc<AtomicU64>,
+}
+
+impl WebhookForwarder {
+    pub fn new(config: WebhookConfig) -> Result<Self> {
+        let http_client = reqwest::ClientBuilder::new()
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e| PolyfillError::config(format!("Failed to build webhook client: {e}")))?;
+
+        Ok(Self {
+            http_client,
+            config,
+            cancel: CancellationToken::new(),
+            dropped: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Abandon in-flight deliveries and their retries when `cancel` fires
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    /// Deliver a single event, retrying transient failures
+    pub async fn send(&self, event: &WebhookEvent) -> Result<()> {
+        let body = serde_json::to_vec(event)?;
+        with_retry_cancellable(&self.config.retry, &self.cancel, || self.post(&body)).await
+    }
+
+    /// Forward a stream message if it is a fill, order or resolution event.
+    ///
+    /// Returns `false` for messages that are not forwarded.
+    pub async fn forward(&self, message: &StreamMessage) -> Result<bool> {
+        match WebhookEvent::from_stream_message(message) {
+            Some(event) => self.send(&event).await.map(|()| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Forward fills, order updates and market resolutions dispatched
+    /// through `handlers`. Must be called within a Tokio runtime.
+    ///
+    /// Events are queued and delivered in order by a single worker task, so
+    /// a slow endpoint never blocks the consumer. When the endpoint falls
+    /// `queue_capacity` events behind, new events are dropped with a warning
+    /// and counted in [`dropped_events`](Self::dropped_events) rather than
+    /// applying backpressure to the stream. Delivery failures are logged.
+    /// The worker stops once `handlers` is dropped or cancellation fires.
+    pub fn attach(self: &Arc<Self>, handlers: &EventHandlers) {
+        let (queue, mut events) = mpsc::channel::<WebhookEvent>(self.config.queue_capacity.max(1));
+        let forwarder = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    event = events.recv() => match event {
+                        Some(event) => event,
+                        None => break,
+                    },
+                    _ = forwarder.cancel.cancelled() => break,
+                };
+                if let Err(e) = forwarder.send(&event).await {
+                    warn!("Webhook delivery to {} failed: {}", forwarder.config.url, e);
+                }
+            }
+        });
+
+        let forwarder = Arc::clone(self);
+        handlers.on_message(move |message| {
+            if let Some(event) = WebhookEvent::from_stream_message(message) {
+                if queue.try_send(event).is_err() {
+                    forwarder.dropped.fetch_add(1, Ordering::Relaxed);
+                    warn!(
+                        "Webhook queue for {} is full, dropping event",
+                        forwarder.config.url
+                    );
+                }
+            }
+        });
+    }
+
+    /// Events dropped by [`attach`](Self::attach) because the queue was full
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    async fn post(&self, body: &[u8]) -> Result<()> {
+        let timestamp = crate::utils::time::now_millis();
+        let mut request = self
+            .http_client
+            .post(&self.config.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string());
+
+        if let Some(secret) = &self.config.secret {
+            request = request.header(SIGNATURE_HEADER, sign_payload(secret, timestamp, body)?);
+        }
+
+        let response = request.body(body.to_vec()).send().await?;
+        let status = response.status();
+        if status.is_success() {
+            debug!("Delivered webhook event to {}", self.config.url);
+            return Ok(());
+        }
+
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(PolyfillError::rate_limit("Webhook endpoint rate limited"));
+        }
+
+        Err(
+            PolyfillError::api_from_response("POST", response, "Webhook endpoint rejected event")
+                .await,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    fn trade_message() -> StreamMessage {
+        serde_json::from_str(
+            r#"{"event_type":"trade","id":"t1","market":"0xabc","asset_id":"1","side":"BUY","size":"10","price":"0.5"}"#,
+        )
+        .unwrap()
+    }
+
+    fn test_config(url: String) -> WebhookConfig {
+        let mut config = WebhookConfig::new(url).with_secret("s3cret");
+        config.retry = RetryConfig {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            backoff_factor: 2.0,
+            jitter: false,
+        };
+        config
+    }
+
+    #[test]
+    fn test_sign_payload_is_deterministic() {
+        let a = sign_payload("secret", 1, b"{}").unwrap();
+        let b = sign_payload("secret", 1, b"{}").unwrap();
+        let c = sign_payload("secret", 2, b"{}").unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert!(a.starts_with("sha256="));
+        assert_eq!(a.len(), "sha256=".len() + 64);
+    }
+
+    #[test]
+    fn test_only_account_events_are_forwardable() {
+        assert!(matches!(
+            WebhookEvent::from_stream_message(&trade_message()),
+            Some(WebhookEvent::Fill(_))
+        ));
+        assert!(WebhookEvent::from_stream_message(&StreamMessage::Unknown).is_none());
+
+        let json =
+            serde_json::to_value(WebhookEvent::from_stream_message(&trade_message())).unwrap();
+        assert_eq!(json["type"], "fill");
+        assert_eq!(json["data"]["id"], "t1");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_forward_signs_and_posts() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/hook")
+            .match_header(
+                SIGNATURE_HEADER,
+                Matcher::Regex("^sha256=[0-9a-f]{64}$".into()),
+            )
+            .match_header(TIMESTAMP_HEADER, Matcher::Any)
+            .match_body(Matcher::PartialJsonString(r#"{"type":"fill"}"#.into()))
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let forwarder =
+            WebhookForwarder::new(test_config(format!("{}/hook", server.url()))).unwrap();
+        assert!(forwarder.forward(&trade_message()).await.unwrap());
+        assert!(!forwarder.forward(&StreamMessage::Unknown).await.unwrap());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_attach_delivers_queued_events() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/hook")
+            .with_status(200)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let forwarder =
+            Arc::new(WebhookForwarder::new(test_config(format!("{}/hook", server.url()))).unwrap());
+        let handlers = EventHandlers::new();
+        forwarder.attach(&handlers);
+        for _ in 0..3 {
+            handlers.dispatch(&trade_message());
+        }
+        handlers.dispatch(&StreamMessage::Unknown);
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while !mock.matched_async().await && tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        mock.assert_async().await;
+        assert_eq!(forwarder.dropped_events(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_attach_drops_events_when_queue_is_full() {
+        // Accepts connections but never answers, so the worker stays busy
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = test_config(format!("http://{}/hook", listener.local_addr().unwrap()));
+        config.queue_capacity = 1;
+
+        let cancel = CancellationToken::new();
+        let forwarder = Arc::new(
+            WebhookForwarder::new(config)
+                .unwrap()
+                .with_cancellation(cancel.clone()),
+        );
+        let handlers = EventHandlers::new();
+        forwarder.attach(&handlers);
+        for _ in 0..5 {
+            handlers.dispatch(&trade_message());
+        }
+
+        // At most one event is in flight and one queued
+        assert!(forwarder.dropped_events() >= 3);
+        cancel.cancel();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_send_retries_server_errors() {
+        let mut server = Server::new_async().await;
+        let failing = server
+            .mock("POST", "/hook")
+            .with_status(503)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let forwarder =
+            WebhookForwarder::new(test_config(format!("{}/hook", server.url()))).unwrap();
+        let event = WebhookEvent::from_stream_message(&trade_message()).unwrap();
+        let err = forwarder.send(&event).await.unwrap_err();
+        assert!(matches!(err, PolyfillError::Api { status: 503, .. }));
+        failing.assert_async().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_client_errors_are_not_retried() {
+        let mut server = Server::new_async().await;
+        let rejected = server
+            .mock("POST", "/hook")
+            .with_status(400)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let forwarder =
+            WebhookForwarder::new(test_config(format!("{}/hook", server.url()))).unwrap();
+        let event = WebhookEvent::from_stream_message(&trade_message()).unwrap();
+        assert!(forwarder.send(&event).await.is_err());
+        rejected.assert_async().await;
+    }
+}
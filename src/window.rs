@@ -0,0 +1,369 @@
+//! Per-token streaming windows
+//!
+//! [`StreamWindows`] keeps one window per token over values pulled from
+//! stream messages (trade prices, trade sizes, midpoints, spreads) and
+//! reduces each window to [`WindowStats`]: count, sum, min, max, first and
+//! last. Windows are either tumbling (fixed buckets, emitted as
+//! [`ClosedWindow`]s when the next bucket starts) or sliding (samples at
+//! most the window length older than the newest one). Time is the exchange timestamp in
+//! milliseconds; a sample older than the newest one seen for its token
+//! counts as arriving at that newest timestamp.
+
+use crate::intern::{TokenKey, TokenMap};
+use crate::types::StreamMessage;
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Reductions over the values in a window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WindowStats {
+    pub count: u64,
+    pub sum: Decimal,
+    pub min: Option<Decimal>,
+    pub max: Option<Decimal>,
+    pub first: Option<Decimal>,
+    pub last: Option<Decimal>,
+}
+
+impl WindowStats {
+    pub fn push(&mut self, value: Decimal) {
+        self.count += 1;
+        self.sum += value;
+        self.min = Some(self.min.map_or(value, |min| min.min(value)));
+        self.max = Some(self.max.map_or(value, |max| max.max(value)));
+        self.first.get_or_insert(value);
+        self.last = Some(value);
+    }
+
+    pub fn mean(&self) -> Option<Decimal> {
+        (self.count > 0).then(|| self.sum / Decimal::from(self.count))
+    }
+
+    /// `max - min`
+    pub fn range(&self) -> Option<Decimal> {
+        Some(self.max? - self.min?)
+    }
+}
+
+/// Shape of a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowSpec {
+    /// Back-to-back buckets aligned to multiples of the length
+    Tumbling(Duration),
+    /// Samples at most this much older than the newest one
+    Sliding(Duration),
+}
+
+impl WindowSpec {
+    fn length_ms(&self) -> u64 {
+        match self {
+            WindowSpec::Tumbling(length) | WindowSpec::Sliding(length) => {
+                (length.as_millis() as u64).max(1)
+            },
+        }
+    }
+}
+
+/// Value a window takes from each stream message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowSource {
+    /// Price of `last_trade_price` prints
+    TradePrice,
+    /// Size of `last_trade_price` prints that carry one
+    TradeSize,
+    /// Midpoint from `best_bid_ask` and `price_change` updates
+    Midpoint,
+    /// Spread from `best_bid_ask` and `price_change` updates
+    Spread,
+}
+
+impl WindowSource {
+    /// `(token, timestamp, value)` samples carried by `message`
+    pub fn samples(&self, message: &StreamMessage) -> Vec<(String, u64, Decimal)> {
+        let quote = |bid: Decimal, ask: Decimal| match self {
+            WindowSource::Midpoint => Some((bid + ask) / Decimal::TWO),
+            WindowSource::Spread => Some(ask - bid),
+            _ => None,
+        };
+        match message {
+            StreamMessage::LastTradePrice(trade) => {
+                let value = match self {
+                    WindowSource::TradePrice => Some(trade.price),
+                    WindowSource::TradeSize => trade.size,
+                    _ => None,
+                };
+                value
+                    .map(|value| vec![(trade.asset_id.clone(), trade.timestamp, value)])
+                    .unwrap_or_default()
+            },
+            StreamMessage::BestBidAsk(update) => quote(update.best_bid, update.best_ask)
+                .map(|value| vec![(update.asset_id.clone(), update.timestamp, value)])
+                .unwrap_or_default(),
+            StreamMessage::PriceChange(change) => change
+                .price_changes
+                .iter()
+                .filter_map(|entry| {
+                    let value = quote(entry.best_bid?, entry.best_ask?)?;
+                    Some((entry.asset_id.clone(), change.timestamp, value))
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// A finished tumbling bucket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClosedWindow {
+    pub token_id: String,
+    /// Bucket start (inclusive)
+    pub start_ms: u64,
+    /// Bucket end (exclusive)
+    pub end_ms: u64,
+    pub stats: WindowStats,
+}
+
+#[derive(Debug, Default)]
+struct Bucket {
+    start_ms: u64,
+    stats: WindowStats,
+}
+
+#[derive(Debug, Default)]
+struct Trail {
+    samples: VecDeque<(u64, Decimal)>,
+    sum: Decimal,
+    /// `(push index, value)` candidates for the minimum, values increasing
+    mins: VecDeque<(u64, Decimal)>,
+    /// Candidates for the maximum, values decreasing
+    maxs: VecDeque<(u64, Decimal)>,
+    pushed: u64,
+    evicted: u64,
+}
+
+impl Trail {
+    fn push(&mut self, at: u64, value: Decimal, length_ms: u64) {
+        let index = self.pushed;
+        self.pushed += 1;
+        self.samples.push_back((at, value));
+        self.sum += value;
+        while self.mins.back().is_some_and(|(_, min)| *min >= value) {
+            self.mins.pop_back();
+        }
+        self.mins.push_back((index, value));
+        while self.maxs.back().is_some_and(|(_, max)| *max <= value) {
+            self.maxs.pop_back();
+        }
+        self.maxs.push_back((index, value));
+
+        while self
+            .samples
+            .front()
+            .is_some_and(|(sample_at, _)| sample_at.saturating_add(length_ms) < at)
+        {
+            let (_, value) = self.samples.pop_front().unwrap_or_default();
+            self.sum -= value;
+            if self.mins.front().is_some_and(|(i, _)| *i == self.evicted) {
+                self.mins.pop_front();
+            }
+            if self.maxs.front().is_some_and(|(i, _)| *i == self.evicted) {
+                self.maxs.pop_front();
+            }
+            self.evicted += 1;
+        }
+    }
+
+    fn stats(&self) -> WindowStats {
+        WindowStats {
+            count: self.samples.len() as u64,
+            sum: self.sum,
+            min: self.mins.front().map(|(_, min)| *min),
+            max: self.maxs.front().map(|(_, max)| *max),
+            first: self.samples.front().map(|(_, value)| *value),
+            last: self.samples.back().map(|(_, value)| *value),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum TokenWindow {
+    Tumbling(Bucket),
+    Sliding(Trail),
+}
+
+#[derive(Debug)]
+struct TokenState {
+    newest_ms: u64,
+    window: TokenWindow,
+}
+
+/// One window per token over a [`WindowSource`]
+#[derive(Debug)]
+pub struct StreamWindows {
+    spec: WindowSpec,
+    source: WindowSource,
+    tokens: TokenMap<TokenState>,
+}
+
+impl StreamWindows {
+    pub fn new(spec: WindowSpec, source: WindowSource) -> Self {
+        Self {
+            spec,
+            source,
+            tokens: TokenMap::default(),
+        }
+    }
+
+    pub fn spec(&self) -> WindowSpec {
+        self.spec
+    }
+
+    /// Add the samples `message` carries; returns the tumbling buckets they
+    /// closed
+    pub fn observe(&mut self, message: &StreamMessage) -> Vec<ClosedWindow> {
+        self.source
+            .samples(message)
+            .into_iter()
+            .filter_map(|(token_id, at, value)| self.push(&token_id, at, value))
+            .collect()
+    }
+
+    /// Add one sample; returns the tumbling bucket it closed, if any
+    pub fn push(
+        &mut self,
+        token_id: &str,
+        timestamp_ms: u64,
+        value: Decimal,
+    ) -> Option<ClosedWindow> {
+        let length_ms = self.spec.length_ms();
+        let state = self
+            .tokens
+            .entry(TokenKey::new(token_id))
+            .or_insert_with(|| TokenState {
+                newest_ms: timestamp_ms,
+                window: match self.spec {
+                    WindowSpec::Tumbling(_) => TokenWindow::Tumbling(Bucket {
+                        start_ms: timestamp_ms - timestamp_ms % length_ms,
+                        stats: WindowStats::default(),
+                    }),
+                    WindowSpec::Sliding(_) => TokenWindow::Sliding(Trail::default()),
+                },
+            });
+        let at = timestamp_ms.max(state.newest_ms);
+        state.newest_ms = at;
+
+        match &mut state.window {
+            TokenWindow::Tumbling(bucket) => {
+                let start_ms = at - at % length_ms;
+                let closed = (start_ms != bucket.start_ms).then(|| {
+                    let closed = ClosedWindow {
+                        token_id: token_id.to_string(),
+                        start_ms: bucket.start_ms,
+                        end_ms: bucket.start_ms + length_ms,
+                        stats: std::mem::take(&mut bucket.stats),
+                    };
+                    bucket.start_ms = start_ms;
+                    closed
+                });
+                bucket.stats.push(value);
+                closed
+            },
+            TokenWindow::Sliding(trail) => {
+                trail.push(at, value, length_ms);
+                None
+            },
+        }
+    }
+
+    /// Stats of the current bucket or trailing window
+    pub fn stats(&self, token_id: &str) -> Option<WindowStats> {
+        let state = self.tokens.get(&TokenKey::lookup(token_id)?)?;
+        Some(match &state.window {
+            TokenWindow::Tumbling(bucket) => bucket.stats,
+            TokenWindow::Sliding(trail) => trail.stats(),
+        })
+    }
+
+    /// Close every open tumbling bucket, e.g. at the end of a replay
+    pub fn flush(&mut self) -> Vec<ClosedWindow> {
+        if matches!(self.spec, WindowSpec::Sliding(_)) {
+            return Vec::new();
+        }
+        let length_ms = self.spec.length_ms();
+        let mut closed: Vec<ClosedWindow> = self
+            .tokens
+            .drain()
+            .filter_map(|(token, state)| match state.window {
+                TokenWindow::Tumbling(bucket) if bucket.stats.count > 0 => Some(ClosedWindow {
+                    token_id: token.as_str().to_string(),
+                    start_ms: bucket.start_ms,
+                    end_ms: bucket.start_ms + length_ms,
+                    stats: bucket.stats,
+                }),
+                _ => None,
+            })
+            .collect();
+        closed.sort_by(|a, b| a.token_id.cmp(&b.token_id));
+        closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn trade(price: &str, size: &str, timestamp: u64) -> StreamMessage {
+        serde_json::from_str(&format!(
+            r#"{{"event_type":"last_trade_price","asset_id":"1","market":"0xabc",
+                "price":"{price}","side":"BUY","size":"{size}","timestamp":"{timestamp}"}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_tumbling_and_sliding_windows() {
+        let mut buckets = StreamWindows::new(
+            WindowSpec::Tumbling(Duration::from_secs(60)),
+            WindowSource::TradePrice,
+        );
+        assert!(buckets.observe(&trade("0.50", "10", 1_000)).is_empty());
+        assert!(buckets.observe(&trade("0.55", "10", 30_000)).is_empty());
+        assert!(buckets.observe(&trade("0.45", "10", 59_999)).is_empty());
+        let closed = buckets.observe(&trade("0.60", "10", 60_000));
+        assert_eq!(closed.len(), 1);
+        assert_eq!((closed[0].start_ms, closed[0].end_ms), (0, 60_000));
+        let stats = closed[0].stats;
+        assert_eq!(stats.count, 3);
+        assert_eq!(
+            (stats.first, stats.last),
+            (Some(dec!(0.50)), Some(dec!(0.45)))
+        );
+        assert_eq!(stats.range(), Some(dec!(0.10)));
+        assert_eq!(stats.mean(), Some(dec!(0.50)));
+        let rest = buckets.flush();
+        assert_eq!(rest[0].start_ms, 60_000);
+        assert_eq!(rest[0].stats.sum, dec!(0.60));
+
+        let mut trailing = StreamWindows::new(
+            WindowSpec::Sliding(Duration::from_secs(10)),
+            WindowSource::TradeSize,
+        );
+        for (size, at) in [("5", 0), ("9", 4_000), ("2", 10_000), ("7", 12_000)] {
+            trailing.observe(&trade("0.5", size, at));
+        }
+        // The first sample is more than ten seconds older than the newest
+        let stats = trailing.stats("1").unwrap();
+        assert_eq!(stats.count, 3);
+        assert_eq!(stats.sum, dec!(18));
+        assert_eq!((stats.min, stats.max), (Some(dec!(2)), Some(dec!(9))));
+        trailing.push("1", 30_000, dec!(4));
+        let stats = trailing.stats("1").unwrap();
+        assert_eq!(
+            (stats.count, stats.min, stats.max),
+            (1, Some(dec!(4)), Some(dec!(4)))
+        );
+        assert!(trailing.stats("2").is_none());
+    }
+}
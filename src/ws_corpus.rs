@@ -0,0 +1,314 @@
+//! WebSocket payload regression corpus
+//!
+//! A [`ReplayCorpus`] is a set of captured WebSocket frames. Replaying it
+//! decodes every frame with [`StreamFrame`] and feeds `book` snapshots
+//! through both the typed applier and the [`WsBookUpdateProcessor`] hot
+//! path, flagging frames that fail to decode or where the two parsers leave
+//! different books. [`ReplayCorpus::builtin`] ships real venue payloads
+//! (market and user channel messages, batched arrays and edge cases); add
+//! your own captures with [`ReplayCorpus::load_dir`] to check a parser
+//! change against the shapes your feed actually sees.
+
+use crate::book::OrderBookManager;
+use crate::decode::{Decoder, StreamEventType, StreamFrame};
+use crate::errors::{PolyfillError, Result};
+use crate::types::StreamMessage;
+use crate::ws_hot_path::WsBookUpdateProcessor;
+use std::path::{Path, PathBuf};
+
+const BUILTIN: [(&str, &str); 9] = [
+    ("book.json", include_str!("../tests/fixtures/ws/book.json")),
+    (
+        "book_array.json",
+        include_str!("../tests/fixtures/ws/book_array.json"),
+    ),
+    (
+        "price_change.json",
+        include_str!("../tests/fixtures/ws/price_change.json"),
+    ),
+    (
+        "tick_size_change.json",
+        include_str!("../tests/fixtures/ws/tick_size_change.json"),
+    ),
+    (
+        "last_trade_price.json",
+        include_str!("../tests/fixtures/ws/last_trade_price.json"),
+    ),
+    (
+        "best_bid_ask.json",
+        include_str!("../tests/fixtures/ws/best_bid_ask.json"),
+    ),
+    (
+        "user_trade.json",
+        include_str!("../tests/fixtures/ws/user_trade.json"),
+    ),
+    (
+        "user_order.json",
+        include_str!("../tests/fixtures/ws/user_order.json"),
+    ),
+    (
+        "edge_cases.jsonl",
+        include_str!("../tests/fixtures/ws/edge_cases.jsonl"),
+    ),
+];
+
+/// One captured frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CorpusEntry {
+    /// File name, with `:line` for frames from `.jsonl` files
+    pub name: String,
+    pub payload: String,
+}
+
+/// What replaying one frame produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayOutcome {
+    pub name: String,
+    /// Event type of each decoded message; `None` for unmodelled ones
+    pub event_types: Vec<Option<StreamEventType>>,
+    /// Top-level fields the typed model did not recognise
+    pub unmodelled_fields: Vec<String>,
+    /// Why the frame failed, if it did
+    pub error: Option<String>,
+}
+
+/// Outcomes of a whole corpus
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorpusReport {
+    pub outcomes: Vec<ReplayOutcome>,
+}
+
+impl CorpusReport {
+    pub fn failures(&self) -> impl Iterator<Item = &ReplayOutcome> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.error.is_some())
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Number of decoded messages of `event_type`
+    pub fn count(&self, event_type: StreamEventType) -> usize {
+        self.outcomes
+            .iter()
+            .flat_map(|outcome| &outcome.event_types)
+            .filter(|t| **t == Some(event_type))
+            .count()
+    }
+}
+
+/// A set of captured WebSocket frames
+#[derive(Debug, Clone, Default)]
+pub struct ReplayCorpus {
+    entries: Vec<CorpusEntry>,
+}
+
+impl ReplayCorpus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Payloads shipped with the crate
+    pub fn builtin() -> Self {
+        let mut corpus = Self::new();
+        for (name, contents) in BUILTIN {
+            corpus.add_file(name, contents);
+        }
+        corpus
+    }
+
+    /// Read every `.json` (one frame per file) and `.jsonl` (one frame per
+    /// line) file in `dir`, in name order
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let entries = std::fs::read_dir(dir).map_err(|e| {
+            PolyfillError::internal(format!("Failed to read corpus {}", dir.display()), e)
+        })?;
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for entry in entries {
+            let path = entry
+                .map_err(|e| PolyfillError::internal("Failed to read corpus directory", e))?
+                .path();
+            if path
+                .extension()
+                .is_some_and(|ext| ext == "json" || ext == "jsonl")
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut corpus = Self::new();
+        for path in paths {
+            let contents = std::fs::read_to_string(&path).map_err(|e| {
+                PolyfillError::internal(format!("Failed to read {}", path.display()), e)
+            })?;
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            corpus.add_file(&name, &contents);
+        }
+        Ok(corpus)
+    }
+
+    pub fn push(&mut self, name: impl Into<String>, payload: impl Into<String>) {
+        self.entries.push(CorpusEntry {
+            name: name.into(),
+            payload: payload.into(),
+        });
+    }
+
+    pub fn extend(&mut self, other: ReplayCorpus) {
+        self.entries.extend(other.entries);
+    }
+
+    pub fn entries(&self) -> &[CorpusEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Replay every frame, each against fresh books
+    pub fn replay(&self) -> CorpusReport {
+        CorpusReport {
+            outcomes: self.entries.iter().map(replay_entry).collect(),
+        }
+    }
+
+    fn add_file(&mut self, name: &str, contents: &str) {
+        if name.ends_with(".jsonl") {
+            for (i, line) in contents.lines().enumerate() {
+                if !line.trim().is_empty() {
+                    self.push(format!("{name}:{}", i + 1), line.trim());
+                }
+            }
+        } else {
+            self.push(name, contents.trim());
+        }
+    }
+}
+
+fn replay_entry(entry: &CorpusEntry) -> ReplayOutcome {
+    let mut outcome = ReplayOutcome {
+        name: entry.name.clone(),
+        event_types: Vec::new(),
+        unmodelled_fields: Vec::new(),
+        error: None,
+    };
+    let messages = match StreamFrame(entry.payload.as_bytes()).decode() {
+        Ok(messages) => messages,
+        Err(e) => {
+            outcome.error = Some(format!("decode failed: {e}"));
+            return outcome;
+        },
+    };
+    for message in &messages {
+        outcome.event_types.push(message.event_type());
+        if let Some(extra) = message.extra() {
+            outcome.unmodelled_fields.extend(extra.keys().cloned());
+        }
+    }
+    outcome.error = compare_book_paths(&entry.payload, &messages).err();
+    outcome
+}
+
+/// Apply the frame's book snapshots through the typed and hot-path parsers
+/// and compare the books they leave
+fn compare_book_paths(
+    payload: &str,
+    messages: &[StreamMessage],
+) -> std::result::Result<(), String> {
+    let updates: Vec<_> = messages
+        .iter()
+        .filter_map(|message| match message {
+            StreamMessage::Book(update) => Some(update),
+            _ => None,
+        })
+        .collect();
+    if updates.is_empty() {
+        return Ok(());
+    }
+
+    let (typed, hot) = (OrderBookManager::new(1000), OrderBookManager::new(1000));
+    for update in &updates {
+        for books in [&typed, &hot] {
+            books
+                .get_or_create_book(&update.asset_id)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    for update in &updates {
+        typed
+            .apply_book_update(update)
+            .map_err(|e| format!("typed apply failed: {e}"))?;
+    }
+    let stats = WsBookUpdateProcessor::new(payload.len())
+        .process_text(payload.to_string(), &hot)
+        .map_err(|e| format!("hot path failed: {e}"))?;
+    if stats.book_messages != updates.len() {
+        return Err(format!(
+            "hot path saw {} book messages, typed decoder {}",
+            stats.book_messages,
+            updates.len()
+        ));
+    }
+    for update in updates {
+        let levels = |books: &OrderBookManager| {
+            books
+                .with_book(&update.asset_id, |book| (book.bids(None), book.asks(None)))
+                .map_err(|e| e.to_string())
+        };
+        if levels(&typed)? != levels(&hot)? {
+            return Err(format!(
+                "typed and hot-path books differ for {}",
+                update.asset_id
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_corpus_replays_cleanly() {
+        let corpus = ReplayCorpus::builtin();
+        let report = corpus.replay();
+        let failures: Vec<_> = report.failures().collect();
+        assert!(failures.is_empty(), "{failures:#?}");
+        assert_eq!(report.count(StreamEventType::Book), 5);
+        assert_eq!(report.count(StreamEventType::Trade), 1);
+        assert_eq!(report.count(StreamEventType::Order), 1);
+        let trade = report
+            .outcomes
+            .iter()
+            .find(|outcome| outcome.name == "user_trade.json")
+            .unwrap();
+        assert!(trade
+            .unmodelled_fields
+            .contains(&"maker_orders".to_string()));
+
+        // User captures load from disk alongside the builtin set
+        let dir = std::env::temp_dir().join(format!("polyfill-corpus-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("mine.jsonl"), "{\"event_type\":\"book\"}\n\n[]\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "ignored").unwrap();
+        let mine = ReplayCorpus::load_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(mine.len(), 2);
+        let report = mine.replay();
+        let failed: Vec<_> = report.failures().map(|o| o.name.as_str()).collect();
+        assert_eq!(failed, ["mine.jsonl:1"]);
+    }
+}
@@ -9,8 +9,9 @@
 //! make the *processing* layer allocation-free so we can enforce it with tests.
 
 use crate::book::{OrderBookManager, ParsedBookLevel};
+use crate::decode::fast_parse::{parse_price_ticks, parse_qty_units};
 use crate::errors::{PolyfillError, Result};
-use crate::types::{Price, Qty, Side, MAX_PRICE_TICKS, MAX_QTY, MIN_PRICE_TICKS, SCALE_FACTOR};
+use crate::types::Side;
 use simd_json::prelude::*;
 
 /// Summary of what happened while processing a WS payload.
@@ -210,8 +211,8 @@ fn collect_levels<'tape, 'input>(
             .and_then(|v| v.into_string())
             .ok_or_else(|| PolyfillError::parse("Missing size", None))?;
 
-        let price_ticks = parse_price_ticks_4dp(price_str)?;
-        let size_units = parse_qty_scaled_4dp(size_str)?;
+        let price_ticks = parse_price_ticks(price_str)?;
+        let size_units = parse_qty_units(size_str)?;
 
         parsed_levels.push(ParsedBookLevel {
             side,
@@ -224,87 +225,6 @@ fn collect_levels<'tape, 'input>(
     Ok(applied)
 }
 
-#[inline]
-fn parse_price_ticks_4dp(value: &str) -> Result<Price> {
-    let scaled = parse_scaled_4_u64(value)?;
-    if scaled < MIN_PRICE_TICKS as u64 {
-        return Err(PolyfillError::validation("Invalid price"));
-    }
-    if scaled > MAX_PRICE_TICKS as u64 {
-        return Err(PolyfillError::validation("Invalid price"));
-    }
-
-    Ok(scaled as Price)
-}
-
-#[inline]
-fn parse_qty_scaled_4dp(value: &str) -> Result<Qty> {
-    let scaled = parse_scaled_4_u64(value)?;
-    if scaled > MAX_QTY as u64 {
-        return Err(PolyfillError::validation("Invalid size"));
-    }
-
-    Ok(scaled as Qty)
-}
-
-#[inline]
-fn parse_scaled_4_u64(value: &str) -> Result<u64> {
-    if value.is_empty() {
-        return Err(PolyfillError::parse("invalid decimal", None));
-    }
-
-    let mut whole = 0u64;
-    let mut frac = 0u64;
-    let mut frac_digits = 0u8;
-    let mut seen_dot = false;
-    let mut seen_digit = false;
-
-    for &byte in value.as_bytes() {
-        match byte {
-            b'0'..=b'9' => {
-                seen_digit = true;
-                let digit = (byte - b'0') as u64;
-                if seen_dot {
-                    if frac_digits >= 4 {
-                        if digit != 0 {
-                            return Err(PolyfillError::parse("too many decimal places", None));
-                        }
-                    } else {
-                        frac = frac
-                            .checked_mul(10)
-                            .and_then(|x| x.checked_add(digit))
-                            .ok_or_else(|| PolyfillError::parse("scaled value overflow", None))?;
-                        frac_digits += 1;
-                    }
-                } else {
-                    whole = whole
-                        .checked_mul(10)
-                        .and_then(|x| x.checked_add(digit))
-                        .ok_or_else(|| PolyfillError::parse("scaled value overflow", None))?;
-                }
-            },
-            b'.' if !seen_dot => {
-                seen_dot = true;
-            },
-            _ => return Err(PolyfillError::parse("invalid decimal", None)),
-        }
-    }
-
-    if !seen_digit {
-        return Err(PolyfillError::parse("invalid decimal", None));
-    }
-
-    while frac_digits < 4 {
-        frac *= 10;
-        frac_digits += 1;
-    }
-
-    whole
-        .checked_mul(SCALE_FACTOR as u64)
-        .and_then(|x| x.checked_add(frac))
-        .ok_or_else(|| PolyfillError::parse("scaled value overflow", None))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -313,22 +233,22 @@ mod tests {
 
     #[test]
     fn fixed_point_parser_matches_expected_price_ticks() {
-        assert_eq!(parse_price_ticks_4dp("0.6543").unwrap(), 6543);
-        assert_eq!(parse_price_ticks_4dp("1.0000").unwrap(), 10_000);
-        assert_eq!(parse_price_ticks_4dp("1.000000").unwrap(), 10_000);
-        assert!(parse_price_ticks_4dp("0.00005").is_err());
-        assert!(parse_price_ticks_4dp("0").is_err());
-        assert!(parse_price_ticks_4dp("-0.1").is_err());
+        assert_eq!(parse_price_ticks("0.6543").unwrap(), 6543);
+        assert_eq!(parse_price_ticks("1.0000").unwrap(), 10_000);
+        assert_eq!(parse_price_ticks("1.000000").unwrap(), 10_000);
+        assert!(parse_price_ticks("0.00005").is_err());
+        assert!(parse_price_ticks("0").is_err());
+        assert!(parse_price_ticks("-0.1").is_err());
     }
 
     #[test]
     fn fixed_point_parser_matches_expected_qty_units() {
-        assert_eq!(parse_qty_scaled_4dp("100.0").unwrap(), 1_000_000);
-        assert_eq!(parse_qty_scaled_4dp("0.0000").unwrap(), 0);
-        assert_eq!(parse_qty_scaled_4dp("1.234500").unwrap(), 12_345);
-        assert!(parse_qty_scaled_4dp("-50.5").is_err());
-        assert!(parse_qty_scaled_4dp("0.00004").is_err());
-        assert!(parse_qty_scaled_4dp("0.00005").is_err());
+        assert_eq!(parse_qty_units("100.0").unwrap(), 1_000_000);
+        assert_eq!(parse_qty_units("0.0000").unwrap(), 0);
+        assert_eq!(parse_qty_units("1.234500").unwrap(), 12_345);
+        assert!(parse_qty_units("-50.5").is_err());
+        assert!(parse_qty_units("0.00004").is_err());
+        assert!(parse_qty_units("0.00005").is_err());
     }
 
     #[test]
@@ -374,6 +294,7 @@ mod tests {
                     size: dec!(20),
                 }],
                 hash: None,
+                extra: Default::default(),
             })
             .unwrap();
 
@@ -414,6 +335,7 @@ mod tests {
                     size: dec!(20),
                 }],
                 hash: None,
+                extra: Default::default(),
             })
             .unwrap();
 
@@ -0,0 +1,360 @@
+//! Pluggable WebSocket transport
+//!
+//! [`crate::WebSocketStream`] opens its connections through a [`WsConnector`]
+//! and talks to the result only as a [`WsTransport`]: a sink and stream of
+//! tungstenite messages. The default [`TungsteniteConnector`] dials TCP and
+//! performs the TLS and WebSocket handshakes, with options for custom
+//! headers, a custom TLS connector, socket tuning and pinning the address to
+//! connect to. Tests and unusual deployments can supply their own connector
+//! instead, e.g. one serving an in-memory transport.
+
+use crate::dns::{connect_happy_eyeballs_with, DnsCache};
+use crate::errors::{PolyfillError, Result, StreamErrorKind};
+use futures::future::BoxFuture;
+use futures::{Sink, Stream};
+use std::net::SocketAddr;
+use tokio::net::{TcpSocket, TcpStream};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::{HeaderName, HeaderValue};
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::Connector;
+
+/// A connected WebSocket: a sink and stream of tungstenite messages
+pub trait WsTransport:
+    Stream<Item = std::result::Result<Message, WsError>>
+    + Sink<Message, Error = WsError>
+    + Send
+    + Sync
+    + Unpin
+{
+}
+
+impl<T> WsTransport for T where
+    T: Stream<Item = std::result::Result<Message, WsError>>
+        + Sink<Message, Error = WsError>
+        + Send
+        + Sync
+        + Unpin
+{
+}
+
+/// Boxed transport as held by a stream
+pub type BoxedTransport = Box<dyn WsTransport>;
+
+/// Opens WebSocket connections
+pub trait WsConnector: Send + Sync {
+    /// Connect to `url` and complete the WebSocket handshake
+    fn connect<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<BoxedTransport>>;
+}
+
+impl<T: WsConnector + ?Sized> WsConnector for std::sync::Arc<T> {
+    fn connect<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<BoxedTransport>> {
+        (**self).connect(url)
+    }
+}
+
+/// TCP options applied to every WebSocket socket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SocketOptions {
+    /// Disable Nagle's algorithm so small frames go out immediately
+    pub nodelay: bool,
+    /// `SO_RCVBUF`, set before connecting so the window scale can use it
+    pub recv_buffer_size: Option<u32>,
+    /// `SO_SNDBUF`
+    pub send_buffer_size: Option<u32>,
+    /// `SO_KEEPALIVE`, so the kernel notices a silently dropped peer
+    pub keepalive: bool,
+}
+
+impl Default for SocketOptions {
+    fn default() -> Self {
+        Self {
+            nodelay: true,
+            recv_buffer_size: None,
+            send_buffer_size: None,
+            keepalive: false,
+        }
+    }
+}
+
+impl SocketOptions {
+    async fn connect(self, addr: SocketAddr) -> std::io::Result<TcpStream> {
+        let socket = if addr.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        socket.set_keepalive(self.keepalive)?;
+        let stream = socket.connect(addr).await?;
+        stream.set_nodelay(self.nodelay)?;
+        Ok(stream)
+    }
+}
+
+/// Default connector: TCP, optional TLS and the tungstenite handshake
+#[derive(Clone, Default)]
+pub struct TungsteniteConnector {
+    headers: Vec<(String, String)>,
+    tls: Option<Connector>,
+    connect_addr: Option<SocketAddr>,
+    dns_cache: Option<DnsCache>,
+    socket: SocketOptions,
+    config: Option<WebSocketConfig>,
+}
+
+impl std::fmt::Debug for TungsteniteConnector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TungsteniteConnector")
+            .field("headers", &self.headers.len())
+            .field("custom_tls", &self.tls.is_some())
+            .field("connect_addr", &self.connect_addr)
+            .field("dns_cache", &self.dns_cache.is_some())
+            .field("socket", &self.socket)
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl TungsteniteConnector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send an extra header with the handshake request
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Use a custom TLS connector (certificates, client auth, roots)
+    pub fn with_tls(mut self, connector: Connector) -> Self {
+        self.tls = Some(connector);
+        self
+    }
+
+    /// Dial `addr` instead of resolving the URL host.
+    ///
+    /// TLS server name and the `Host` header still come from the URL, so this
+    /// can point a connection at a specific edge or a pre-resolved address.
+    pub fn with_connect_addr(mut self, addr: SocketAddr) -> Self {
+        self.connect_addr = Some(addr);
+        self
+    }
+
+    /// Resolve hosts through `cache` and dial its addresses happy-eyeballs
+    /// style, so reconnects skip DNS entirely once the host is cached
+    pub fn with_dns_cache(mut self, cache: DnsCache) -> Self {
+        self.dns_cache = Some(cache);
+        self
+    }
+
+    pub fn with_socket_options(mut self, options: SocketOptions) -> Self {
+        self.socket = options;
+        self
+    }
+
+    /// Toggle `TCP_NODELAY` (on by default)
+    pub fn with_nodelay(mut self, nodelay: bool) -> Self {
+        self.socket.nodelay = nodelay;
+        self
+    }
+
+    /// Kernel receive buffer size; raise it for large book snapshots
+    pub fn with_recv_buffer_size(mut self, bytes: u32) -> Self {
+        self.socket.recv_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Kernel send buffer size
+    pub fn with_send_buffer_size(mut self, bytes: u32) -> Self {
+        self.socket.send_buffer_size = Some(bytes);
+        self
+    }
+
+    /// Toggle `SO_KEEPALIVE` (off by default)
+    pub fn with_keepalive(mut self, keepalive: bool) -> Self {
+        self.socket.keepalive = keepalive;
+        self
+    }
+
+    /// Tungstenite protocol settings (frame and message size limits)
+    pub fn with_config(mut self, config: WebSocketConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// Largest accepted frame; `None` removes the limit
+    pub fn with_max_frame_size(mut self, bytes: Option<usize>) -> Self {
+        self.config
+            .get_or_insert_with(WebSocketConfig::default)
+            .max_frame_size = bytes;
+        self
+    }
+
+    /// Largest accepted message after reassembly; `None` removes the limit
+    pub fn with_max_message_size(mut self, bytes: Option<usize>) -> Self {
+        self.config
+            .get_or_insert_with(WebSocketConfig::default)
+            .max_message_size = bytes;
+        self
+    }
+
+    pub fn socket_options(&self) -> SocketOptions {
+        self.socket
+    }
+
+    async fn open(&self, url: &str) -> Result<BoxedTransport> {
+        let connection_failed =
+            |message: String| PolyfillError::stream(message, StreamErrorKind::ConnectionFailed);
+
+        let mut request = url
+            .into_client_request()
+            .map_err(|e| connection_failed(format!("Invalid WebSocket URL {url}: {e}")))?;
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| PolyfillError::validation(format!("Invalid header {name}: {e}")))?;
+            let value = HeaderValue::from_str(value).map_err(|e| {
+                PolyfillError::validation(format!("Invalid value for header {name}: {e}"))
+            })?;
+            request.headers_mut().insert(name, value);
+        }
+
+        let addrs = match self.connect_addr {
+            Some(addr) => vec![addr],
+            None => {
+                let uri = request.uri();
+                let host = uri
+                    .host()
+                    .ok_or_else(|| connection_failed(format!("No host in {url}")))?
+                    .trim_start_matches('[')
+                    .trim_end_matches(']');
+                let port = uri
+                    .port_u16()
+                    .unwrap_or(if uri.scheme_str() == Some("wss") {
+                        443
+                    } else {
+                        80
+                    });
+                match &self.dns_cache {
+                    Some(cache) => cache
+                        .resolve(host)
+                        .await?
+                        .iter()
+                        .map(|ip| SocketAddr::new(*ip, port))
+                        .collect(),
+                    None => tokio::net::lookup_host((host, port))
+                        .await
+                        .map_err(|e| connection_failed(format!("Failed to resolve {host}: {e}")))?
+                        .collect(),
+                }
+            },
+        };
+        let socket = connect_happy_eyeballs_with(&addrs, |addr| self.socket.connect(addr))
+            .await
+            .map_err(|e| connection_failed(format!("WebSocket connection failed: {e}")))?;
+
+        let (connection, _) = tokio_tungstenite::client_async_tls_with_config(
+            request,
+            socket,
+            self.config,
+            self.tls.clone(),
+        )
+        .await
+        .map_err(|e| connection_failed(format!("WebSocket connection failed: {e}")))?;
+        Ok(Box::new(connection))
+    }
+}
+
+impl WsConnector for TungsteniteConnector {
+    fn connect<'a>(&'a self, url: &'a str) -> BoxFuture<'a, Result<BoxedTransport>> {
+        Box::pin(self.open(url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{SinkExt, StreamExt};
+
+    #[tokio::test]
+    async fn test_socket_options_reach_the_dialed_socket() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connector = TungsteniteConnector::new()
+            .with_nodelay(false)
+            .with_keepalive(true)
+            .with_recv_buffer_size(256 * 1024)
+            .with_send_buffer_size(128 * 1024);
+        let options = connector.socket_options();
+        assert_eq!(
+            options,
+            SocketOptions {
+                nodelay: false,
+                recv_buffer_size: Some(256 * 1024),
+                send_buffer_size: Some(128 * 1024),
+                keepalive: true,
+            }
+        );
+
+        let stream = options.connect(addr).await.unwrap();
+        assert!(!stream.nodelay().unwrap());
+        let socket = TcpSocket::from_std_stream(stream.into_std().unwrap());
+        assert!(socket.keepalive().unwrap());
+        // The kernel may round the buffers up, but never below the request
+        assert!(socket.recv_buffer_size().unwrap() >= 256 * 1024);
+        assert!(socket.send_buffer_size().unwrap() >= 128 * 1024);
+
+        let stream = SocketOptions::default().connect(addr).await.unwrap();
+        assert!(stream.nodelay().unwrap());
+    }
+
+    #[tokio::test]
+    #[allow(clippy::result_large_err)] // tungstenite's handshake callback signature
+    async fn test_connector_sends_headers_to_pinned_address() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let mut seen = None;
+            let mut ws = tokio_tungstenite::accept_hdr_async(
+                socket,
+                |request: &tokio_tungstenite::tungstenite::handshake::server::Request, response| {
+                    seen = Some((
+                        request.headers()["host"].to_str().unwrap().to_string(),
+                        request.headers()["x-client"].to_str().unwrap().to_string(),
+                    ));
+                    Ok(response)
+                },
+            )
+            .await
+            .unwrap();
+            ws.send(Message::Text("hello".to_string())).await.unwrap();
+            seen.unwrap()
+        });
+
+        // The URL host is never resolved; the pinned address is dialed instead
+        let connector = TungsteniteConnector::new()
+            .with_header("x-client", "polyfill")
+            .with_connect_addr(addr)
+            .with_recv_buffer_size(1 << 20)
+            .with_max_frame_size(None);
+        assert!(connector.socket_options().nodelay);
+        let mut transport = connector
+            .connect("ws://exchange.invalid:9/ws/market")
+            .await
+            .unwrap();
+        let message = transport.next().await.unwrap().unwrap();
+        assert_eq!(message, Message::Text("hello".to_string()));
+
+        let (host, client) = server.await.unwrap();
+        assert_eq!(host, "exchange.invalid:9");
+        assert_eq!(client, "polyfill");
+    }
+}
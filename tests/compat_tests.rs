@@ -0,0 +1,108 @@
+// Drop-in compatibility with the polymarket-rs-client `ClobClient` API
+use polyfill_rs::{
+    ApiCredentials, BalanceAllowanceParams, BookParams, ClientConfig, ClobClient, OpenOrderParams,
+    PolyfillError, TradeParams,
+};
+
+const PRIVATE_KEY: &str = "0x1234567890123456789012345678901234567890123456789012345678901234";
+
+fn creds() -> ApiCredentials {
+    ApiCredentials {
+        api_key: "test_key".to_string(),
+        secret: "dGVzdF9zZWNyZXRfa2V5XzEyMzQ1".to_string(),
+        passphrase: "test_passphrase".to_string(),
+    }
+}
+
+/// Calls every shim with polymarket-rs-client's argument and return types.
+/// Never run; it only has to compile.
+#[allow(dead_code, deprecated)]
+async fn polymarket_rs_client_signatures(client: &ClobClient) {
+    let _: Option<String> = client.get_address();
+    let _: Option<String> = client.get_collateral_address();
+    let _: Option<String> = client.get_conditional_address();
+    let _: Option<String> = client.get_exchange_address();
+    let _: bool = client.get_ok().await;
+    let _: polyfill_rs::Result<u64> = client.get_server_time().await;
+
+    let _ = client.create_api_key(None).await;
+    let _ = client.derive_api_key(None).await;
+    let _ = client.create_or_derive_api_key(None).await;
+    let _ = client.get_api_keys().await;
+    let _ = client.delete_api_key().await;
+
+    let token_ids = vec!["1".to_string()];
+    let _ = client.get_midpoint("1").await;
+    let _ = client.get_midpoints(&token_ids).await;
+    let _ = client.get_price("1", polyfill_rs::Side::BUY).await;
+    let _ = client.get_prices(&[] as &[BookParams]).await;
+    let _ = client.get_spread("1").await;
+    let _ = client.get_spreads(&token_ids).await;
+    let _ = client.get_tick_size("1").await;
+    let _ = client.get_neg_risk("1").await;
+    let _ = client.get_order_book("1").await;
+    let _ = client.get_order_books(&token_ids).await;
+    let _ = client.get_last_trade_price("1").await;
+    let _ = client.get_last_trade_prices(&token_ids).await;
+
+    let _ = client.cancel("order").await;
+    let _ = client.cancel_orders(&["order".to_string()]).await;
+    let _ = client.cancel_all().await;
+    let _ = client.cancel_market_orders(None, Some("1")).await;
+    let _ = client.get_orders(None::<&OpenOrderParams>, None).await;
+    let _ = client.get_order("order").await;
+    let _ = client.get_trades(None::<&TradeParams>, None).await;
+    let _ = client.get_notifications().await;
+    let _ = client.drop_notifications(&[]).await;
+    let _ = client
+        .get_balance_allowance(None::<BalanceAllowanceParams>)
+        .await;
+    let _ = client
+        .update_balance_allowance(None::<BalanceAllowanceParams>)
+        .await;
+    let _ = client.is_order_scoring("order").await;
+    let _ = client.are_orders_scoring(&["order"]).await;
+
+    let _ = client.get_sampling_markets(None).await;
+    let _ = client.get_sampling_simplified_markets(None).await;
+    let _ = client.get_markets(None).await;
+    let _ = client.get_simplified_markets(None).await;
+    let _ = client.get_market("0xabc").await;
+    let _ = client.get_market_trades_events("0xabc").await;
+
+    let _: ClobClient = ClobClient::with_l1_headers("http://localhost", PRIVATE_KEY, 137);
+    let _: ClobClient = ClobClient::with_l2_headers("http://localhost", PRIVATE_KEY, 137, creds());
+}
+
+#[test]
+fn test_contract_addresses_and_auth_levels() {
+    let public = ClobClient::new("http://localhost");
+    assert_eq!(public.get_address(), None);
+    assert!(matches!(
+        public.assert_level_1_auth(),
+        Err(PolyfillError::Auth { .. })
+    ));
+    assert!(public.assert_level_2_auth().is_err());
+
+    let l1 = ClobClient::from_config(ClientConfig {
+        base_url: "http://localhost".to_string(),
+        chain: 137,
+        private_key: Some(PRIVATE_KEY.to_string()),
+        ..ClientConfig::default()
+    })
+    .unwrap();
+    let address = l1.get_address().unwrap();
+    assert!(address.starts_with("0x") && address.len() == 42);
+    assert_eq!(
+        l1.get_exchange_address().as_deref(),
+        Some("0xE111180000d2663C0091e4f400237545B87B996B")
+    );
+    assert!(l1.get_collateral_address().is_some());
+    assert!(l1.get_conditional_address().is_some());
+    l1.assert_level_1_auth().unwrap();
+    let err = l1.assert_level_2_auth().unwrap_err();
+    assert!(err.to_string().contains("API credentials not set"));
+
+    l1.set_api_creds(creds()).unwrap();
+    l1.assert_level_2_auth().unwrap();
+}
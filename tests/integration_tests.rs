@@ -4,63 +4,35 @@
 
 use polyfill_rs::{ClientConfig, ClobClient, OrderArgs, Side};
 use rust_decimal_macros::dec;
-use std::env;
 
 const HOST: &str = "https://clob.polymarket.com";
-const CHAIN_ID: u64 = 137;
 
-fn load_env_vars() -> (String, Option<String>, Option<String>, Option<String>) {
+/// Client settings from `POLYMARKET_*` variables (and a local `.env` file)
+fn env_config() -> ClientConfig {
     dotenvy::dotenv().ok();
-
-    let private_key =
-        env::var("POLYMARKET_PRIVATE_KEY").expect("POLYMARKET_PRIVATE_KEY must be set in .env");
-    let api_key = env::var("POLYMARKET_API_KEY").ok();
-    let api_secret = env::var("POLYMARKET_API_SECRET")
-        .or_else(|_| env::var("POLYMARKET_SECRET"))
-        .ok();
-    let api_passphrase = env::var("POLYMARKET_API_PASSPHRASE")
-        .or_else(|_| env::var("POLYMARKET_PASSPHRASE"))
-        .ok();
-
-    (private_key, api_key, api_secret, api_passphrase)
-}
-
-fn env_signature_type() -> Option<u8> {
-    env::var("POLYMARKET_SIGNATURE_TYPE")
-        .ok()
-        .and_then(|value| value.parse::<u8>().ok())
-}
-
-fn env_funder() -> Option<String> {
-    env::var("POLYMARKET_FUNDER")
-        .or_else(|_| env::var("POLYMARKET_FUNDER_ADDRESS"))
-        .ok()
+    let config = ClientConfig::from_env().expect("invalid POLYMARKET_* configuration");
+    assert!(
+        config.private_key.is_some(),
+        "POLYMARKET_PRIVATE_KEY must be set in .env"
+    );
+    config
 }
 
-fn bootstrap_client(private_key: &str) -> ClobClient {
+fn bootstrap_client(config: &ClientConfig) -> ClobClient {
     ClobClient::from_config(ClientConfig {
-        base_url: HOST.to_string(),
-        chain: CHAIN_ID,
-        private_key: Some(private_key.to_string()),
-        signature_type: env_signature_type(),
-        funder: env_funder(),
-        ..ClientConfig::default()
+        api_credentials: None,
+        ..config.clone()
     })
     .expect("failed to build bootstrap client")
 }
 
 fn authenticated_client(
-    private_key: String,
+    config: &ClientConfig,
     api_credentials: polyfill_rs::ApiCredentials,
 ) -> ClobClient {
     ClobClient::from_config(ClientConfig {
-        base_url: HOST.to_string(),
-        chain: CHAIN_ID,
-        private_key: Some(private_key),
         api_credentials: Some(api_credentials),
-        signature_type: env_signature_type(),
-        funder: env_funder(),
-        ..ClientConfig::default()
+        ..config.clone()
     })
     .expect("failed to build authenticated client")
 }
@@ -68,9 +40,9 @@ fn authenticated_client(
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_real_api_create_derive_api_key() {
-    let (private_key, _, _, _) = load_env_vars();
+    let config = env_config();
 
-    let client = bootstrap_client(&private_key);
+    let client = bootstrap_client(&config);
 
     // Test creating/deriving API key
     let result = client.create_or_derive_api_key(None).await;
@@ -91,10 +63,10 @@ async fn test_real_api_create_derive_api_key() {
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_real_api_authenticated_order_flow() {
-    let (private_key, _, _, _) = load_env_vars();
+    let config = env_config();
 
     // Initialize client with L1 headers
-    let bootstrap = bootstrap_client(&private_key);
+    let bootstrap = bootstrap_client(&config);
 
     // Step 1: Create/derive API credentials
     println!("Step 1: Creating/deriving API credentials...");
@@ -102,7 +74,7 @@ async fn test_real_api_authenticated_order_flow() {
         .create_or_derive_api_key(None)
         .await
         .expect("Failed to create/derive API key");
-    let client = authenticated_client(private_key, api_creds);
+    let client = authenticated_client(&config, api_creds);
     println!("PASS: API credentials set");
 
     // Step 2: Get a valid token_id from active markets
@@ -151,7 +123,7 @@ async fn test_real_api_authenticated_order_flow() {
         side, order_price, order_size
     );
     let order_args = OrderArgs {
-        token_id,
+        token_id: token_id.parse().expect("valid token id"),
         price: order_price,
         size: order_size,
         side,
@@ -210,14 +182,14 @@ async fn test_real_api_authenticated_order_flow() {
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_real_api_get_orders() {
-    let (private_key, _, _, _) = load_env_vars();
+    let config = env_config();
 
-    let bootstrap = bootstrap_client(&private_key);
+    let bootstrap = bootstrap_client(&config);
     let api_creds = bootstrap
         .create_or_derive_api_key(None)
         .await
         .expect("Failed to create/derive API key");
-    let client = authenticated_client(private_key, api_creds);
+    let client = authenticated_client(&config, api_creds);
 
     println!("Testing get_orders...");
     let result = client.get_orders(None, None).await;
@@ -240,14 +212,14 @@ async fn test_real_api_get_orders() {
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_real_api_get_trades() {
-    let (private_key, _, _, _) = load_env_vars();
+    let config = env_config();
 
-    let bootstrap = bootstrap_client(&private_key);
+    let bootstrap = bootstrap_client(&config);
     let api_creds = bootstrap
         .create_or_derive_api_key(None)
         .await
         .expect("Failed to create/derive API key");
-    let client = authenticated_client(private_key, api_creds);
+    let client = authenticated_client(&config, api_creds);
 
     println!("Testing get_trades...");
     let result = client.get_trades(None, None).await;
@@ -269,14 +241,14 @@ async fn test_real_api_get_trades() {
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_real_api_get_balance_allowance() {
-    let (private_key, _, _, _) = load_env_vars();
+    let config = env_config();
 
-    let bootstrap = bootstrap_client(&private_key);
+    let bootstrap = bootstrap_client(&config);
     let api_creds = bootstrap
         .create_or_derive_api_key(None)
         .await
         .expect("Failed to create/derive API key");
-    let client = authenticated_client(private_key, api_creds);
+    let client = authenticated_client(&config, api_creds);
 
     println!("Testing get_balance_allowance...");
 
@@ -357,14 +329,14 @@ async fn test_real_api_get_balance_allowance() {
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_real_api_get_api_keys() {
-    let (private_key, _, _, _) = load_env_vars();
+    let config = env_config();
 
-    let bootstrap = bootstrap_client(&private_key);
+    let bootstrap = bootstrap_client(&config);
     let api_creds = bootstrap
         .create_or_derive_api_key(None)
         .await
         .expect("Failed to create/derive API key");
-    let client = authenticated_client(private_key, api_creds);
+    let client = authenticated_client(&config, api_creds);
 
     println!("Testing get_api_keys...");
     let result = client.get_api_keys().await;
@@ -387,14 +359,14 @@ async fn test_real_api_get_api_keys() {
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_real_api_get_notifications() {
-    let (private_key, _, _, _) = load_env_vars();
+    let config = env_config();
 
-    let bootstrap = bootstrap_client(&private_key);
+    let bootstrap = bootstrap_client(&config);
     let api_creds = bootstrap
         .create_or_derive_api_key(None)
         .await
         .expect("Failed to create/derive API key");
-    let client = authenticated_client(private_key, api_creds);
+    let client = authenticated_client(&config, api_creds);
 
     println!("Testing get_notifications...");
     let result = client.get_notifications().await;
@@ -417,9 +389,9 @@ async fn test_real_api_get_notifications() {
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_real_api_market_data_endpoints() {
-    let (private_key, _, _, _) = load_env_vars();
+    let config = env_config();
 
-    let client = bootstrap_client(&private_key);
+    let client = bootstrap_client(&config);
 
     println!("Testing market data endpoints (no auth required)...");
 
@@ -484,9 +456,9 @@ async fn test_real_api_market_data_endpoints() {
 #[tokio::test(flavor = "multi_thread")]
 #[ignore]
 async fn test_real_api_batch_endpoints() {
-    let (private_key, _, _, _) = load_env_vars();
+    let config = env_config();
 
-    let client = bootstrap_client(&private_key);
+    let client = bootstrap_client(&config);
 
     println!("Testing batch endpoints...");
 
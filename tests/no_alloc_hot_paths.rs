@@ -253,6 +253,29 @@ fn no_alloc_mid_and_spread_fast() {
     guard.assert_no_heap_traffic();
 }
 
+#[test]
+fn no_alloc_decimal_parsing() {
+    #[derive(serde::Deserialize)]
+    struct Level {
+        #[serde(deserialize_with = "polyfill_rs::decode::deserializers::decimal_from_string")]
+        price: Decimal,
+        #[serde(deserialize_with = "polyfill_rs::decode::deserializers::decimal_from_string")]
+        size: Decimal,
+    }
+    let json = r#"{"price":"0.6543","size":1250.5}"#;
+
+    let _ = heap_operation_count();
+
+    let guard = NoHeapTrafficGuard::new();
+    let level: Level = serde_json::from_str(json).unwrap();
+    let ticks = polyfill_rs::decode::fast_parse::parse_price_ticks("0.6543").unwrap();
+    guard.assert_no_heap_traffic();
+
+    assert_eq!(level.price, Decimal::from_str("0.6543").unwrap());
+    assert_eq!(level.size, Decimal::from_str("1250.5").unwrap());
+    assert_eq!(ticks, 6543);
+}
+
 #[test]
 fn no_alloc_book_analysis_fast_paths() {
     let token_id = "test_token";
@@ -326,20 +349,19 @@ fn no_alloc_apply_book_update_existing_levels() {
     book.apply_delta_fast(mk_delta(token_hash, Side::SELL, 7600, 1_000_000, 2))
         .unwrap();
 
-    let update = polyfill_rs::types::BookUpdate {
-        asset_id: asset_id.to_string(),
-        market: "0xabc".to_string(),
-        timestamp: 10,
-        bids: vec![polyfill_rs::types::OrderSummary {
+    let update = polyfill_rs::types::BookUpdate::new(
+        asset_id,
+        "0xabc",
+        10,
+        vec![polyfill_rs::types::OrderSummary {
             price: Decimal::from_str("0.75").unwrap(),
             size: Decimal::from_str("200.0").unwrap(),
         }],
-        asks: vec![polyfill_rs::types::OrderSummary {
+        vec![polyfill_rs::types::OrderSummary {
             price: Decimal::from_str("0.76").unwrap(),
             size: Decimal::from_str("50.0").unwrap(),
         }],
-        hash: None,
-    };
+    );
 
     // Warm up allocator-counter TLS access before measuring (defensive).
     let _ = heap_operation_count();
@@ -377,20 +399,19 @@ fn no_alloc_book_manager_apply_book_update_existing_levels() {
         })
         .unwrap();
 
-    let update = polyfill_rs::types::BookUpdate {
-        asset_id: asset_id.to_string(),
-        market: "0xabc".to_string(),
-        timestamp: 10,
-        bids: vec![polyfill_rs::types::OrderSummary {
+    let update = polyfill_rs::types::BookUpdate::new(
+        asset_id,
+        "0xabc",
+        10,
+        vec![polyfill_rs::types::OrderSummary {
             price: Decimal::from_str("0.75").unwrap(),
             size: Decimal::from_str("200.0").unwrap(),
         }],
-        asks: vec![polyfill_rs::types::OrderSummary {
+        vec![polyfill_rs::types::OrderSummary {
             price: Decimal::from_str("0.76").unwrap(),
             size: Decimal::from_str("50.0").unwrap(),
         }],
-        hash: None,
-    };
+    );
 
     // Warm up allocator-counter TLS access before measuring (defensive).
     let _ = heap_operation_count();
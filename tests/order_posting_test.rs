@@ -40,7 +40,7 @@ async fn test_post_order_authentication() {
 
     println!("\nStep 2: Attempting to post order (testing authentication)...");
     let order_args = OrderArgs {
-        token_id: token_id.to_string(),
+        token_id: token_id.parse().expect("valid token id"),
         price: Decimal::from_str("0.01").unwrap(), // Very low price, won't fill
         size: Decimal::from_str("1.0").unwrap(),
         side: Side::BUY,